@@ -0,0 +1,5 @@
+// Rebuild when the frontend is rebuilt, since the embedded frontend is read at
+// compile time.
+fn main() {
+    println!("cargo:rerun-if-changed=../frontend/dist");
+}
@@ -0,0 +1,4 @@
+// Rebuild when a migration is added, since `sqlx::migrate!` reads them at compile time.
+fn main() {
+    println!("cargo:rerun-if-changed=migrations");
+}
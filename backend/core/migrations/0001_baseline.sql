@@ -0,0 +1,238 @@
+-- Schema as of the switch to versioned migrations. Tables use IF NOT EXISTS so
+-- databases created before then adopt this baseline without changes.
+
+CREATE TABLE IF NOT EXISTS users (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    username TEXT NOT NULL UNIQUE,
+    password_hash TEXT NOT NULL,
+    savings REAL NOT NULL DEFAULT 0,
+    retirement_savings REAL NOT NULL DEFAULT 0,
+    savings_goal REAL NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TABLE IF NOT EXISTS fixed_expenses (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    label TEXT NOT NULL,
+    amount REAL NOT NULL,
+    billing_period TEXT NOT NULL DEFAULT 'monthly',
+    payment_month INTEGER,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS budget_categories (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    label TEXT NOT NULL,
+    default_amount REAL NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS months (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    year INTEGER NOT NULL,
+    month INTEGER NOT NULL,
+    is_closed INTEGER NOT NULL DEFAULT 0,
+    closed_at TEXT,
+    frozen_at TEXT,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    UNIQUE(user_id, year, month)
+);
+
+CREATE TABLE IF NOT EXISTS income_entries (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    month_id INTEGER NOT NULL,
+    label TEXT NOT NULL,
+    amount REAL NOT NULL,
+    FOREIGN KEY (month_id) REFERENCES months(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS monthly_budgets (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    month_id INTEGER NOT NULL,
+    category_id INTEGER NOT NULL,
+    allocated_amount REAL NOT NULL,
+    FOREIGN KEY (month_id) REFERENCES months(id) ON DELETE CASCADE,
+    FOREIGN KEY (category_id) REFERENCES budget_categories(id) ON DELETE CASCADE,
+    UNIQUE(month_id, category_id)
+);
+
+CREATE TABLE IF NOT EXISTS items (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    month_id INTEGER NOT NULL,
+    category_id INTEGER NOT NULL,
+    description TEXT NOT NULL,
+    amount REAL NOT NULL,
+    spent_on TEXT NOT NULL,
+    savings_destination TEXT NOT NULL DEFAULT 'none',
+    created_at TEXT,
+    FOREIGN KEY (month_id) REFERENCES months(id) ON DELETE CASCADE,
+    FOREIGN KEY (category_id) REFERENCES budget_categories(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS monthly_snapshots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    month_id INTEGER NOT NULL UNIQUE,
+    pdf_data BLOB NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (month_id) REFERENCES months(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS user_settings (
+    user_id INTEGER PRIMARY KEY,
+    retirement_monthly_contribution REAL,
+    retirement_return_rate REAL,
+    retirement_current_age INTEGER,
+    retirement_target_age INTEGER,
+    locale TEXT,
+    currency TEXT,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS insights (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    fingerprint TEXT NOT NULL,
+    message TEXT NOT NULL,
+    is_read INTEGER NOT NULL DEFAULT 0,
+    is_dismissed INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    UNIQUE(user_id, fingerprint)
+);
+
+CREATE TABLE IF NOT EXISTS insight_scores (
+    user_id INTEGER PRIMARY KEY,
+    health_score INTEGER NOT NULL,
+    computed_at TEXT NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS month_shares (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    month_id INTEGER NOT NULL,
+    expires_at TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (month_id) REFERENCES months(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS activity_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    month_id INTEGER,
+    actor TEXT NOT NULL,
+    entity_type TEXT NOT NULL,
+    entity_id INTEGER NOT NULL,
+    action TEXT NOT NULL,
+    summary TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (month_id) REFERENCES months(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS jwt_keys (
+    kid TEXT PRIMARY KEY,
+    algorithm TEXT NOT NULL,
+    public_key TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    retired_at TEXT
+);
+
+CREATE TABLE IF NOT EXISTS auth_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    ip TEXT,
+    user_agent TEXT,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS jobs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    payload TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'queued',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    error TEXT,
+    created_at TEXT NOT NULL,
+    finished_at TEXT,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS closed_month_fixed_expenses (
+    month_id INTEGER NOT NULL,
+    fixed_expense_id INTEGER NOT NULL,
+    label TEXT NOT NULL,
+    amount REAL NOT NULL,
+    billing_period TEXT NOT NULL DEFAULT 'monthly',
+    payment_month INTEGER,
+    PRIMARY KEY (month_id, fixed_expense_id),
+    FOREIGN KEY (month_id) REFERENCES months(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS closed_month_budgets (
+    month_id INTEGER NOT NULL,
+    budget_id INTEGER NOT NULL,
+    category_id INTEGER NOT NULL,
+    category_label TEXT NOT NULL,
+    allocated_amount REAL NOT NULL,
+    PRIMARY KEY (month_id, budget_id),
+    FOREIGN KEY (month_id) REFERENCES months(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS closed_month_items (
+    month_id INTEGER NOT NULL,
+    item_id INTEGER NOT NULL,
+    category_id INTEGER NOT NULL,
+    category_label TEXT NOT NULL,
+    description TEXT NOT NULL,
+    amount REAL NOT NULL,
+    spent_on TEXT NOT NULL,
+    savings_destination TEXT NOT NULL,
+    PRIMARY KEY (month_id, item_id),
+    FOREIGN KEY (month_id) REFERENCES months(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS wealth_snapshots (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    month_id INTEGER NOT NULL UNIQUE,
+    savings REAL NOT NULL,
+    retirement_savings REAL NOT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (month_id) REFERENCES months(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS public_stats_shares (
+    user_id INTEGER PRIMARY KEY,
+    slug TEXT NOT NULL UNIQUE,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS budget_reviews (
+    month_id INTEGER NOT NULL,
+    budget_id INTEGER NOT NULL,
+    rating INTEGER NOT NULL,
+    note TEXT,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (month_id, budget_id),
+    FOREIGN KEY (month_id) REFERENCES months(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS budget_plans (
+    user_id INTEGER NOT NULL,
+    category_id INTEGER NOT NULL,
+    year INTEGER NOT NULL,
+    month INTEGER NOT NULL,
+    amount REAL NOT NULL,
+    PRIMARY KEY (category_id, year, month),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (category_id) REFERENCES budget_categories(id) ON DELETE CASCADE
+);
@@ -0,0 +1,3 @@
+-- A locked category stops accepting new items for that month.
+ALTER TABLE monthly_budgets ADD COLUMN locked_at DATETIME;
+ALTER TABLE monthly_budgets ADD COLUMN lock_reason TEXT;
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS wishlist (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    description TEXT NOT NULL,
+    estimated_cost REAL NOT NULL,
+    priority INTEGER NOT NULL DEFAULT 3,
+    category_id INTEGER,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (category_id) REFERENCES budget_categories(id) ON DELETE SET NULL
+);
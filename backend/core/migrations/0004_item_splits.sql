@@ -0,0 +1,12 @@
+-- Shares of an item owed by people who don't have an account.
+CREATE TABLE IF NOT EXISTS item_splits (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    item_id INTEGER NOT NULL,
+    person TEXT NOT NULL,
+    amount REAL NOT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (item_id) REFERENCES items(id) ON DELETE CASCADE
+);
+
+-- Income entries recorded as repayment of a split.
+ALTER TABLE income_entries ADD COLUMN split_id INTEGER REFERENCES item_splits(id) ON DELETE SET NULL;
@@ -0,0 +1,2 @@
+-- `pending`, `submitted` or `reimbursed` for items someone else pays back; NULL otherwise.
+ALTER TABLE items ADD COLUMN reimbursement_status TEXT;
@@ -0,0 +1,13 @@
+-- Default rates for mileage and per-diem items.
+ALTER TABLE user_settings ADD COLUMN mileage_rate REAL;
+ALTER TABLE user_settings ADD COLUMN per_diem_rate REAL;
+
+-- How a mileage or per-diem item's amount was worked out.
+CREATE TABLE IF NOT EXISTS item_calculations (
+    item_id INTEGER PRIMARY KEY,
+    kind TEXT NOT NULL,
+    quantity REAL NOT NULL,
+    rate REAL NOT NULL,
+    purpose TEXT,
+    FOREIGN KEY (item_id) REFERENCES items(id) ON DELETE CASCADE
+);
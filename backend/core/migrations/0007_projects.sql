@@ -0,0 +1,11 @@
+-- Budgets for things that span several months, like a renovation or a trip.
+CREATE TABLE IF NOT EXISTS projects (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    budget REAL NOT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+ALTER TABLE items ADD COLUMN project_id INTEGER REFERENCES projects(id) ON DELETE SET NULL;
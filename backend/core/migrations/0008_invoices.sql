@@ -0,0 +1,16 @@
+-- Invoices sent to clients. Paying one records an income entry in the month it was paid.
+CREATE TABLE IF NOT EXISTS invoices (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    client TEXT NOT NULL,
+    amount REAL NOT NULL,
+    issued_on TEXT NOT NULL,
+    due_on TEXT NOT NULL,
+    -- `sent`, `paid` or `cancelled`
+    status TEXT NOT NULL DEFAULT 'sent',
+    paid_on TEXT,
+    income_id INTEGER,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (income_id) REFERENCES income_entries(id) ON DELETE SET NULL
+);
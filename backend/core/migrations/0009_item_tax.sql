@@ -0,0 +1,6 @@
+-- VAT/GST included in an item's amount. The rate is a percentage; the amount is derived from it.
+ALTER TABLE items ADD COLUMN tax_rate REAL;
+ALTER TABLE items ADD COLUMN tax_amount REAL;
+
+ALTER TABLE closed_month_items ADD COLUMN tax_rate REAL;
+ALTER TABLE closed_month_items ADD COLUMN tax_amount REAL;
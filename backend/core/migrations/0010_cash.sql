@@ -0,0 +1,2 @@
+-- Items paid out of the cash wallet, which ATM withdrawals (savings_destination `cash`) fill up.
+ALTER TABLE items ADD COLUMN paid_in_cash INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,3 @@
+-- Monthly amount booked to savings in each new month, and the category it's booked under.
+ALTER TABLE user_settings ADD COLUMN savings_auto_contribution REAL;
+ALTER TABLE user_settings ADD COLUMN savings_auto_category_id INTEGER;
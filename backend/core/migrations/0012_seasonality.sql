@@ -0,0 +1,9 @@
+-- Expected spending per calendar month for categories detected as seasonal.
+CREATE TABLE IF NOT EXISTS category_seasonality (
+    category_id INTEGER NOT NULL,
+    month INTEGER NOT NULL,
+    expected REAL NOT NULL,
+    computed_at TEXT NOT NULL,
+    PRIMARY KEY (category_id, month),
+    FOREIGN KEY (category_id) REFERENCES budget_categories(id) ON DELETE CASCADE
+);
@@ -0,0 +1,27 @@
+-- Report definitions users can run on demand or on a weekly or monthly schedule.
+CREATE TABLE IF NOT EXISTS saved_reports (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    spec TEXT NOT NULL,
+    schedule TEXT,
+    delivery TEXT,
+    target TEXT,
+    next_run_at TEXT,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Files generated by report runs, kept for download. One per job, so retries reuse it.
+CREATE TABLE IF NOT EXISTS report_artifacts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    report_id INTEGER NOT NULL,
+    job_id INTEGER UNIQUE,
+    period_start TEXT NOT NULL,
+    period_end TEXT NOT NULL,
+    filename TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    data BLOB NOT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (report_id) REFERENCES saved_reports(id) ON DELETE CASCADE
+);
@@ -0,0 +1,54 @@
+-- Files such as PDF snapshots now live in a storage backend and rows refer to them by
+-- key. The default `database` backend keeps them in this table.
+CREATE TABLE IF NOT EXISTS stored_objects (
+    key TEXT PRIMARY KEY,
+    data BLOB NOT NULL,
+    created_at TEXT NOT NULL
+);
+
+INSERT INTO stored_objects (key, data, created_at)
+SELECT 'snapshots/' || month_id || '.pdf', pdf_data, strftime('%Y-%m-%dT%H:%M:%SZ', created_at)
+FROM monthly_snapshots;
+
+CREATE TABLE monthly_snapshots_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    month_id INTEGER NOT NULL UNIQUE,
+    storage_key TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (month_id) REFERENCES months(id) ON DELETE CASCADE
+);
+
+INSERT INTO monthly_snapshots_new (id, month_id, storage_key, size, created_at)
+SELECT id, month_id, 'snapshots/' || month_id || '.pdf', LENGTH(pdf_data), created_at
+FROM monthly_snapshots;
+
+DROP TABLE monthly_snapshots;
+ALTER TABLE monthly_snapshots_new RENAME TO monthly_snapshots;
+
+INSERT INTO stored_objects (key, data, created_at)
+SELECT 'reports/' || report_id || '/' || COALESCE(job_id, 'a' || id), data, created_at
+FROM report_artifacts;
+
+CREATE TABLE report_artifacts_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    report_id INTEGER NOT NULL,
+    job_id INTEGER UNIQUE,
+    period_start TEXT NOT NULL,
+    period_end TEXT NOT NULL,
+    filename TEXT NOT NULL,
+    content_type TEXT NOT NULL,
+    storage_key TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (report_id) REFERENCES saved_reports(id) ON DELETE CASCADE
+);
+
+INSERT INTO report_artifacts_new
+    (id, report_id, job_id, period_start, period_end, filename, content_type, storage_key, size, created_at)
+SELECT id, report_id, job_id, period_start, period_end, filename, content_type,
+       'reports/' || report_id || '/' || COALESCE(job_id, 'a' || id), LENGTH(data), created_at
+FROM report_artifacts;
+
+DROP TABLE report_artifacts;
+ALTER TABLE report_artifacts_new RENAME TO report_artifacts;
@@ -0,0 +1,3 @@
+-- SHA-256 of each PDF snapshot, checked when it is downloaded. Snapshots stored before
+-- this get theirs on first read.
+ALTER TABLE monthly_snapshots ADD COLUMN sha256 TEXT;
@@ -0,0 +1,8 @@
+-- Ed25519 signature of each PDF snapshot when PDF_SIGNING_KEY is set, with the public
+-- key that made it and the code printed on the PDF to look it up.
+ALTER TABLE monthly_snapshots ADD COLUMN signature TEXT;
+ALTER TABLE monthly_snapshots ADD COLUMN signed_by TEXT;
+ALTER TABLE monthly_snapshots ADD COLUMN verification_code TEXT;
+
+CREATE UNIQUE INDEX idx_monthly_snapshots_verification_code
+    ON monthly_snapshots(verification_code);
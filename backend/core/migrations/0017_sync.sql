@@ -0,0 +1,205 @@
+-- Change tracking for offline clients syncing with GET /api/sync. Every insert and
+-- update stamps the row with the next value of a global clock; deletes leave a
+-- tombstone carrying that value. Rows from before this start at version 0.
+CREATE TABLE IF NOT EXISTS sync_clock (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    version INTEGER NOT NULL,
+    -- Tombstones up to this version have been pruned
+    pruned_through INTEGER NOT NULL DEFAULT 0
+);
+INSERT INTO sync_clock (id, version) VALUES (1, 0);
+
+CREATE TABLE IF NOT EXISTS sync_deletions (
+    version INTEGER PRIMARY KEY,
+    user_id INTEGER NOT NULL,
+    entity_type TEXT NOT NULL,
+    entity_id INTEGER NOT NULL,
+    deleted_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now'))
+);
+CREATE INDEX IF NOT EXISTS idx_sync_deletions_user ON sync_deletions(user_id, version);
+
+ALTER TABLE months ADD COLUMN version INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE months ADD COLUMN updated_at TEXT;
+CREATE INDEX IF NOT EXISTS idx_months_version ON months(version);
+
+CREATE TRIGGER IF NOT EXISTS months_sync_insert AFTER INSERT ON months
+BEGIN
+    UPDATE sync_clock SET version = version + 1;
+    UPDATE months
+    SET version = (SELECT version FROM sync_clock),
+        updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+    WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS months_sync_update AFTER UPDATE ON months
+WHEN NEW.version = OLD.version
+BEGIN
+    UPDATE sync_clock SET version = version + 1;
+    UPDATE months
+    SET version = (SELECT version FROM sync_clock),
+        updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+    WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS months_sync_delete AFTER DELETE ON months
+BEGIN
+    UPDATE sync_clock SET version = version + 1;
+    INSERT INTO sync_deletions (version, user_id, entity_type, entity_id)
+    SELECT sync_clock.version, OLD.user_id, 'month', OLD.id
+    FROM sync_clock;
+END;
+
+ALTER TABLE budget_categories ADD COLUMN version INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE budget_categories ADD COLUMN updated_at TEXT;
+CREATE INDEX IF NOT EXISTS idx_budget_categories_version ON budget_categories(version);
+
+CREATE TRIGGER IF NOT EXISTS budget_categories_sync_insert AFTER INSERT ON budget_categories
+BEGIN
+    UPDATE sync_clock SET version = version + 1;
+    UPDATE budget_categories
+    SET version = (SELECT version FROM sync_clock),
+        updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+    WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS budget_categories_sync_update AFTER UPDATE ON budget_categories
+WHEN NEW.version = OLD.version
+BEGIN
+    UPDATE sync_clock SET version = version + 1;
+    UPDATE budget_categories
+    SET version = (SELECT version FROM sync_clock),
+        updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+    WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS budget_categories_sync_delete AFTER DELETE ON budget_categories
+BEGIN
+    UPDATE sync_clock SET version = version + 1;
+    INSERT INTO sync_deletions (version, user_id, entity_type, entity_id)
+    SELECT sync_clock.version, OLD.user_id, 'category', OLD.id
+    FROM sync_clock;
+END;
+
+ALTER TABLE fixed_expenses ADD COLUMN version INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE fixed_expenses ADD COLUMN updated_at TEXT;
+CREATE INDEX IF NOT EXISTS idx_fixed_expenses_version ON fixed_expenses(version);
+
+CREATE TRIGGER IF NOT EXISTS fixed_expenses_sync_insert AFTER INSERT ON fixed_expenses
+BEGIN
+    UPDATE sync_clock SET version = version + 1;
+    UPDATE fixed_expenses
+    SET version = (SELECT version FROM sync_clock),
+        updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+    WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS fixed_expenses_sync_update AFTER UPDATE ON fixed_expenses
+WHEN NEW.version = OLD.version
+BEGIN
+    UPDATE sync_clock SET version = version + 1;
+    UPDATE fixed_expenses
+    SET version = (SELECT version FROM sync_clock),
+        updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+    WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS fixed_expenses_sync_delete AFTER DELETE ON fixed_expenses
+BEGIN
+    UPDATE sync_clock SET version = version + 1;
+    INSERT INTO sync_deletions (version, user_id, entity_type, entity_id)
+    SELECT sync_clock.version, OLD.user_id, 'fixed_expense', OLD.id
+    FROM sync_clock;
+END;
+
+ALTER TABLE income_entries ADD COLUMN version INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE income_entries ADD COLUMN updated_at TEXT;
+CREATE INDEX IF NOT EXISTS idx_income_entries_version ON income_entries(version);
+
+CREATE TRIGGER IF NOT EXISTS income_entries_sync_insert AFTER INSERT ON income_entries
+BEGIN
+    UPDATE sync_clock SET version = version + 1;
+    UPDATE income_entries
+    SET version = (SELECT version FROM sync_clock),
+        updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+    WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS income_entries_sync_update AFTER UPDATE ON income_entries
+WHEN NEW.version = OLD.version
+BEGIN
+    UPDATE sync_clock SET version = version + 1;
+    UPDATE income_entries
+    SET version = (SELECT version FROM sync_clock),
+        updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+    WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS income_entries_sync_delete AFTER DELETE ON income_entries
+BEGIN
+    UPDATE sync_clock SET version = version + 1;
+    INSERT INTO sync_deletions (version, user_id, entity_type, entity_id)
+    SELECT sync_clock.version, months.user_id, 'income', OLD.id
+    FROM sync_clock JOIN months ON months.id = OLD.month_id;
+END;
+
+ALTER TABLE monthly_budgets ADD COLUMN version INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE monthly_budgets ADD COLUMN updated_at TEXT;
+CREATE INDEX IF NOT EXISTS idx_monthly_budgets_version ON monthly_budgets(version);
+
+CREATE TRIGGER IF NOT EXISTS monthly_budgets_sync_insert AFTER INSERT ON monthly_budgets
+BEGIN
+    UPDATE sync_clock SET version = version + 1;
+    UPDATE monthly_budgets
+    SET version = (SELECT version FROM sync_clock),
+        updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+    WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS monthly_budgets_sync_update AFTER UPDATE ON monthly_budgets
+WHEN NEW.version = OLD.version
+BEGIN
+    UPDATE sync_clock SET version = version + 1;
+    UPDATE monthly_budgets
+    SET version = (SELECT version FROM sync_clock),
+        updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+    WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS monthly_budgets_sync_delete AFTER DELETE ON monthly_budgets
+BEGIN
+    UPDATE sync_clock SET version = version + 1;
+    INSERT INTO sync_deletions (version, user_id, entity_type, entity_id)
+    SELECT sync_clock.version, months.user_id, 'budget', OLD.id
+    FROM sync_clock JOIN months ON months.id = OLD.month_id;
+END;
+
+ALTER TABLE items ADD COLUMN version INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE items ADD COLUMN updated_at TEXT;
+CREATE INDEX IF NOT EXISTS idx_items_version ON items(version);
+
+CREATE TRIGGER IF NOT EXISTS items_sync_insert AFTER INSERT ON items
+BEGIN
+    UPDATE sync_clock SET version = version + 1;
+    UPDATE items
+    SET version = (SELECT version FROM sync_clock),
+        updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+    WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS items_sync_update AFTER UPDATE ON items
+WHEN NEW.version = OLD.version
+BEGIN
+    UPDATE sync_clock SET version = version + 1;
+    UPDATE items
+    SET version = (SELECT version FROM sync_clock),
+        updated_at = strftime('%Y-%m-%dT%H:%M:%fZ', 'now')
+    WHERE id = NEW.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS items_sync_delete AFTER DELETE ON items
+BEGIN
+    UPDATE sync_clock SET version = version + 1;
+    INSERT INTO sync_deletions (version, user_id, entity_type, entity_id)
+    SELECT sync_clock.version, months.user_id, 'item', OLD.id
+    FROM sync_clock JOIN months ON months.id = OLD.month_id;
+END;
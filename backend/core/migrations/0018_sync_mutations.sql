@@ -0,0 +1,10 @@
+-- Client-generated IDs of the mutations applied through POST /api/sync/batch, so a
+-- batch sent again after a lost response is not applied twice.
+CREATE TABLE IF NOT EXISTS sync_mutations (
+    user_id INTEGER NOT NULL,
+    client_id TEXT NOT NULL,
+    entity_id INTEGER NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%fZ', 'now')),
+    PRIMARY KEY (user_id, client_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
@@ -0,0 +1,10 @@
+-- Optional UUIDs chosen by offline clients when they create items, income and
+-- categories, so a resubmitted create returns the existing row.
+ALTER TABLE items ADD COLUMN uuid TEXT;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_items_uuid ON items(uuid);
+
+ALTER TABLE income_entries ADD COLUMN uuid TEXT;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_income_entries_uuid ON income_entries(uuid);
+
+ALTER TABLE budget_categories ADD COLUMN uuid TEXT;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_budget_categories_uuid ON budget_categories(uuid);
@@ -0,0 +1,5 @@
+-- Categories can be budgeted as a share of the month's income instead of a fixed
+-- amount. Each month's allocation remembers the share it follows until it is set
+-- by hand.
+ALTER TABLE budget_categories ADD COLUMN default_percent REAL;
+ALTER TABLE monthly_budgets ADD COLUMN allocated_percent REAL;
@@ -0,0 +1,2 @@
+-- Opt-out for recomputing percentage allocations when a month's income changes.
+ALTER TABLE user_settings ADD COLUMN rebudget_on_income_change INTEGER;
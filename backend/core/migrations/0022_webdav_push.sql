@@ -0,0 +1,8 @@
+-- WebDAV folder, such as a Nextcloud folder, that closed months' PDFs are uploaded to.
+ALTER TABLE user_settings ADD COLUMN webdav_url TEXT;
+
+-- Upload state of each snapshot: pending, synced or failed. NULL when the user had no
+-- WebDAV folder set when the month was closed.
+ALTER TABLE monthly_snapshots ADD COLUMN sync_status TEXT;
+ALTER TABLE monthly_snapshots ADD COLUMN synced_at TEXT;
+ALTER TABLE monthly_snapshots ADD COLUMN sync_error TEXT;
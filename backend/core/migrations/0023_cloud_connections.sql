@@ -0,0 +1,19 @@
+-- Cloud drives a user has authorized payme to upload reports and PDF snapshots to.
+CREATE TABLE IF NOT EXISTS cloud_connections (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    -- dropbox or google_drive
+    provider TEXT NOT NULL,
+    refresh_token TEXT NOT NULL,
+    -- Dropbox path, or name of the Google Drive folder created on connecting
+    folder TEXT NOT NULL,
+    -- Google Drive id of that folder
+    folder_id TEXT,
+    -- Whether closed months' PDFs are uploaded, not only reports delivered here
+    mirror_snapshots INTEGER NOT NULL DEFAULT 1,
+    last_upload_at TEXT,
+    last_error TEXT,
+    created_at TEXT NOT NULL,
+    UNIQUE (user_id, provider),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
@@ -0,0 +1,19 @@
+-- Steps a user ticks off before closing a month, such as paying the credit card.
+CREATE TABLE IF NOT EXISTS close_checklist (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    label TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- The checklist as it was worded when a month was closed. acknowledged is 0 for steps
+-- skipped by forcing the close.
+CREATE TABLE IF NOT EXISTS closed_month_checklist (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    month_id INTEGER NOT NULL,
+    label TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    acknowledged INTEGER NOT NULL,
+    FOREIGN KEY (month_id) REFERENCES months(id) ON DELETE CASCADE
+);
@@ -0,0 +1,12 @@
+-- Part of a category's allocation set aside for a planned purchase. item_id is set once
+-- the purchase is posted, which consumes the earmark; deleting the item reopens it.
+CREATE TABLE IF NOT EXISTS budget_earmarks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    budget_id INTEGER NOT NULL,
+    label TEXT NOT NULL,
+    amount REAL NOT NULL,
+    item_id INTEGER,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (budget_id) REFERENCES monthly_budgets(id) ON DELETE CASCADE,
+    FOREIGN KEY (item_id) REFERENCES items(id) ON DELETE SET NULL
+);
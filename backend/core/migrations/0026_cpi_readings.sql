@@ -0,0 +1,10 @@
+-- Consumer price index readings a user supplies so long-range stats can be restated in
+-- real terms. Only the ratio between readings matters, so any base period works.
+CREATE TABLE IF NOT EXISTS cpi_readings (
+    user_id INTEGER NOT NULL,
+    year INTEGER NOT NULL,
+    month INTEGER NOT NULL,
+    value REAL NOT NULL,
+    PRIMARY KEY (user_id, year, month),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
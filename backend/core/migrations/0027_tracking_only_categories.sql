@@ -0,0 +1,3 @@
+-- Categories whose spending is itemized but left out of totals, remaining money and
+-- allocations, such as work expenses that are paid back later.
+ALTER TABLE budget_categories ADD COLUMN tracking_only INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,5 @@
+-- Business profile: items can name the client they were spent for and be marked
+-- billable to that client.
+ALTER TABLE user_settings ADD COLUMN business_profile INTEGER;
+ALTER TABLE items ADD COLUMN client TEXT;
+ALTER TABLE items ADD COLUMN billable INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,32 @@
+-- Every amount a fixed expense has had, from the day it took effect. Triggers record
+-- new expenses and changes to their amount or billing period, so every way of writing
+-- fixed expenses keeps the history. A second change on the same day replaces the first.
+CREATE TABLE IF NOT EXISTS fixed_expense_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    fixed_expense_id INTEGER NOT NULL,
+    amount REAL NOT NULL,
+    billing_period TEXT NOT NULL,
+    effective_from TEXT NOT NULL,
+    UNIQUE (fixed_expense_id, effective_from),
+    FOREIGN KEY (fixed_expense_id) REFERENCES fixed_expenses(id) ON DELETE CASCADE
+);
+
+-- Existing expenses are taken to have cost what they do now since the user's first month.
+INSERT INTO fixed_expense_history (fixed_expense_id, amount, billing_period, effective_from)
+SELECT fe.id, fe.amount, fe.billing_period,
+       COALESCE((SELECT MIN(printf('%04d-%02d-01', m.year, m.month))
+                 FROM months m WHERE m.user_id = fe.user_id), date('now'))
+FROM fixed_expenses fe;
+
+CREATE TRIGGER IF NOT EXISTS fixed_expense_history_insert AFTER INSERT ON fixed_expenses
+BEGIN
+    INSERT OR REPLACE INTO fixed_expense_history (fixed_expense_id, amount, billing_period, effective_from)
+    VALUES (NEW.id, NEW.amount, NEW.billing_period, date('now'));
+END;
+
+CREATE TRIGGER IF NOT EXISTS fixed_expense_history_update AFTER UPDATE OF amount, billing_period ON fixed_expenses
+WHEN NEW.amount != OLD.amount OR NEW.billing_period != OLD.billing_period
+BEGIN
+    INSERT OR REPLACE INTO fixed_expense_history (fixed_expense_id, amount, billing_period, effective_from)
+    VALUES (NEW.id, NEW.amount, NEW.billing_period, date('now'));
+END;
@@ -0,0 +1,13 @@
+-- What changed over a month, worked out when it is closed. Wealth changes are against
+-- the previous closed month's snapshot and stay null when there is none.
+CREATE TABLE IF NOT EXISTS month_digests (
+    month_id INTEGER PRIMARY KEY,
+    savings_change REAL,
+    net_worth_change REAL,
+    top_overspent_category TEXT,
+    top_overspent_by REAL,
+    biggest_purchase TEXT,
+    biggest_purchase_amount REAL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (month_id) REFERENCES months(id) ON DELETE CASCADE
+);
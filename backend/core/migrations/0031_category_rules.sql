@@ -0,0 +1,16 @@
+-- Categories created by the server rather than the user, such as the "Uncategorized"
+-- category items fall back to when no rule matches them. One per user at most.
+ALTER TABLE budget_categories ADD COLUMN system INTEGER NOT NULL DEFAULT 0;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_budget_categories_system
+    ON budget_categories(user_id) WHERE system = 1;
+
+-- Rules that pick a category for items created without one, tried in position order.
+CREATE TABLE IF NOT EXISTS category_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    category_id INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    pattern TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    FOREIGN KEY (category_id) REFERENCES budget_categories(id) ON DELETE CASCADE
+);
+CREATE INDEX IF NOT EXISTS idx_category_rules_category ON category_rules(category_id);
@@ -0,0 +1,2 @@
+-- Lets months be closed while items are still in the "Uncategorized" category.
+ALTER TABLE user_settings ADD COLUMN close_with_uncategorized INTEGER;
@@ -0,0 +1,7 @@
+-- Days of the month money moves on, rolled around weekends and the bank holidays of
+-- the user's country: the payday for income and a payment day for each fixed expense.
+ALTER TABLE user_settings ADD COLUMN holiday_country TEXT;
+ALTER TABLE user_settings ADD COLUMN payday INTEGER;
+ALTER TABLE user_settings ADD COLUMN payday_roll TEXT;
+ALTER TABLE fixed_expenses ADD COLUMN payment_day INTEGER;
+ALTER TABLE fixed_expenses ADD COLUMN payment_roll TEXT;
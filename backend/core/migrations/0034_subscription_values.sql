@@ -0,0 +1,12 @@
+-- What a subscription is worth to the user, in their words. Subscriptions without one
+-- are reported as candidates to cancel. `key` is `fixed_expense:<id>` or
+-- `item:<lowercased description>`, as listed by the subscriptions view.
+CREATE TABLE IF NOT EXISTS subscription_values (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id INTEGER NOT NULL,
+    key TEXT NOT NULL,
+    value TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    UNIQUE (user_id, key)
+);
@@ -0,0 +1,5 @@
+-- Users budgeting by the week or fortnight instead of the calendar month. Budgets stay
+-- stored per month; `period_starts_on` is the first day of one period, so the others
+-- can be counted from it.
+ALTER TABLE users ADD COLUMN period_type TEXT NOT NULL DEFAULT 'monthly';
+ALTER TABLE users ADD COLUMN period_starts_on TEXT;
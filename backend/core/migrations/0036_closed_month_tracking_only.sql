@@ -0,0 +1,10 @@
+-- Closed months keep whether each category was tracking-only when they were closed, so
+-- flipping the flag later doesn't change their totals. Months closed before this
+-- migration take the flag as it is now.
+ALTER TABLE closed_month_budgets ADD COLUMN tracking_only INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE closed_month_items ADD COLUMN tracking_only INTEGER NOT NULL DEFAULT 0;
+
+UPDATE closed_month_budgets SET tracking_only = 1
+WHERE category_id IN (SELECT id FROM budget_categories WHERE tracking_only = 1);
+UPDATE closed_month_items SET tracking_only = 1
+WHERE category_id IN (SELECT id FROM budget_categories WHERE tracking_only = 1);
@@ -0,0 +1,80 @@
+//! Restating amounts from different months in the prices of one month, using the
+//! consumer price index readings the user supplied.
+
+use sqlx::SqliteExecutor;
+
+use crate::models::CpiReading;
+
+/// A user's CPI readings, oldest first.
+pub struct PriceIndex {
+    readings: Vec<CpiReading>,
+}
+
+impl PriceIndex {
+    /// `None` when there are no readings to adjust with.
+    pub fn new(mut readings: Vec<CpiReading>) -> Option<Self> {
+        if readings.is_empty() {
+            return None;
+        }
+        readings.sort_by_key(|r| (r.year, r.month));
+        Some(Self { readings })
+    }
+
+    /// The latest reading. Adjusted amounts are in its prices.
+    pub fn base(&self) -> &CpiReading {
+        self.readings.last().expect("a price index has readings")
+    }
+
+    /// Multiplier that restates an amount from `year`/`month` in base prices. Months
+    /// without a reading use the latest one before them, or the first one when the
+    /// series starts later.
+    pub fn factor(&self, year: i32, month: i32) -> f64 {
+        let reading = self
+            .readings
+            .iter()
+            .rev()
+            .find(|r| (r.year, r.month) <= (year, month))
+            .unwrap_or(&self.readings[0]);
+        self.base().value / reading.value
+    }
+}
+
+pub async fn load_readings<'e>(
+    executor: impl SqliteExecutor<'e>,
+    user_id: i64,
+) -> Result<Vec<CpiReading>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT year, month, value FROM cpi_readings WHERE user_id = ? ORDER BY year, month",
+    )
+    .bind(user_id)
+    .fetch_all(executor)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(year: i32, month: i32, value: f64) -> CpiReading {
+        CpiReading { year, month, value }
+    }
+
+    #[test]
+    fn test_factor() {
+        assert!(PriceIndex::new(vec![]).is_none());
+
+        let index = PriceIndex::new(vec![
+            reading(2024, 1, 120.0),
+            reading(2022, 1, 100.0),
+            reading(2023, 1, 110.0),
+        ])
+        .unwrap();
+        assert_eq!(index.base().year, 2024);
+        assert_eq!(index.factor(2024, 6), 1.0);
+        assert_eq!(index.factor(2022, 1), 1.2);
+        // Between readings the earlier one applies, before the series the first one
+        assert_eq!(index.factor(2022, 11), 1.2);
+        assert_eq!(index.factor(2020, 3), 1.2);
+        assert!((index.factor(2023, 5) - 120.0 / 110.0).abs() < 1e-9);
+    }
+}
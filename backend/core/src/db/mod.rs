@@ -0,0 +1,294 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::time::Duration;
+
+use sqlx::{
+    migrate::Migrator,
+    sqlite::{SqliteConnectOptions, SqlitePoolOptions},
+    SqliteConnection, SqlitePool,
+};
+
+/// Versioned schema migrations from `migrations/`, embedded in the binary.
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+/// Age after which a migration lock is assumed to belong to a crashed process.
+const STALE_LOCK_MINUTES: i64 = 10;
+
+pub async fn create_pool(database_url: &str) -> Result<SqlitePool, sqlx::Error> {
+    let options = SqliteConnectOptions::from_str(database_url)?.foreign_keys(true);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(5)
+        .connect_with(options)
+        .await?;
+    Ok(pool)
+}
+
+/// Applies pending migrations. A lock row keeps two containers started against the
+/// same database from migrating it at the same time.
+pub async fn run_migrations(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    acquire_migration_lock(pool).await?;
+    let result = migrate(pool).await;
+    release_migration_lock(pool).await?;
+    result
+}
+
+async fn migrate(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    // Later migrations read columns that databases from before versioning may lack, so
+    // those get them first
+    let existing: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'users')",
+    )
+    .fetch_one(pool)
+    .await?;
+    if existing {
+        upgrade_legacy_schema(pool).await?;
+    }
+    MIGRATOR.run(pool).await?;
+
+    // Months closed before their data was copied keep what they report today
+    let unfrozen: Vec<i64> =
+        sqlx::query_scalar("SELECT id FROM months WHERE is_closed = 1 AND frozen_at IS NULL")
+            .fetch_all(pool)
+            .await?;
+    for month_id in unfrozen {
+        let mut tx = pool.begin().await?;
+        freeze_month(&mut tx, month_id).await?;
+        tx.commit().await?;
+    }
+
+    Ok(())
+}
+
+/// SQLite has no advisory locks, so the lock is a single row that only one process
+/// can insert. Locks older than [`STALE_LOCK_MINUTES`] are taken over.
+async fn acquire_migration_lock(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS migration_lock (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            holder INTEGER NOT NULL,
+            acquired_at TEXT NOT NULL DEFAULT (datetime('now'))
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    loop {
+        sqlx::query("DELETE FROM migration_lock WHERE acquired_at < datetime('now', ?)")
+            .bind(format!("-{STALE_LOCK_MINUTES} minutes"))
+            .execute(pool)
+            .await?;
+
+        let acquired =
+            sqlx::query("INSERT OR IGNORE INTO migration_lock (id, holder) VALUES (1, ?)")
+                .bind(std::process::id())
+                .execute(pool)
+                .await?
+                .rows_affected()
+                == 1;
+        if acquired {
+            return Ok(());
+        }
+
+        tracing::info!("Waiting for another process to finish migrating the database");
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+}
+
+async fn release_migration_lock(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM migration_lock WHERE id = 1")
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Versions, descriptions and install times of the migrations applied so far.
+pub async fn applied_migrations(
+    pool: &SqlitePool,
+) -> Result<Vec<(i64, String, String)>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT version, description, installed_on FROM _sqlx_migrations WHERE success = 1 ORDER BY version",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Columns added before migrations were versioned. Runs before the migrations on
+/// databases that already have tables; where a column exists, or its table is only
+/// created by the baseline migration, the statement fails and is ignored.
+async fn upgrade_legacy_schema(pool: &SqlitePool) -> Result<(), sqlx::Error> {
+    sqlx::query("ALTER TABLE users ADD COLUMN savings REAL NOT NULL DEFAULT 0")
+        .execute(pool)
+        .await
+        .ok();
+
+    sqlx::query("ALTER TABLE users ADD COLUMN retirement_savings REAL NOT NULL DEFAULT 0")
+        .execute(pool)
+        .await
+        .ok();
+
+    sqlx::query("ALTER TABLE users ADD COLUMN savings_goal REAL NOT NULL DEFAULT 0")
+        .execute(pool)
+        .await
+        .ok();
+
+    sqlx::query("UPDATE users SET retirement_savings = roth_ira WHERE retirement_savings = 0 AND roth_ira IS NOT NULL AND roth_ira > 0")
+        .execute(pool)
+        .await
+        .ok();
+
+    // Read by the fixed expense history migration
+    let _ = sqlx::query(
+        "ALTER TABLE fixed_expenses ADD COLUMN billing_period TEXT NOT NULL DEFAULT 'monthly'",
+    )
+    .execute(pool)
+    .await;
+
+    let _ = sqlx::query("ALTER TABLE fixed_expenses ADD COLUMN payment_month INTEGER")
+        .execute(pool)
+        .await;
+
+    let _ = sqlx::query("ALTER TABLE months ADD COLUMN frozen_at TEXT")
+        .execute(pool)
+        .await;
+
+    let _ = sqlx::query(
+        "ALTER TABLE items ADD COLUMN savings_destination TEXT NOT NULL DEFAULT 'none'",
+    )
+    .execute(pool)
+    .await;
+
+    let _ = sqlx::query("ALTER TABLE items ADD COLUMN created_at TEXT")
+        .execute(pool)
+        .await;
+
+    sqlx::query("UPDATE items SET savings_destination = 'none' WHERE savings_destination = '' OR savings_destination IS NULL")
+        .execute(pool)
+        .await?;
+
+    sqlx::query("ALTER TABLE user_settings ADD COLUMN locale TEXT")
+        .execute(pool)
+        .await
+        .ok();
+
+    sqlx::query("ALTER TABLE user_settings ADD COLUMN currency TEXT")
+        .execute(pool)
+        .await
+        .ok();
+
+    let _ = sqlx::query(
+        "ALTER TABLE closed_month_fixed_expenses ADD COLUMN billing_period TEXT NOT NULL DEFAULT 'monthly'",
+    )
+    .execute(pool)
+    .await;
+
+    let _ = sqlx::query("ALTER TABLE closed_month_fixed_expenses ADD COLUMN payment_month INTEGER")
+        .execute(pool)
+        .await;
+
+    Ok(())
+}
+
+/// Copies the fixed expenses, budgets and items a closed month reports into the
+/// `closed_month_*` tables, so later edits or deletions of categories and fixed
+/// expenses don't change it.
+pub async fn freeze_month(conn: &mut SqliteConnection, month_id: i64) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO closed_month_fixed_expenses
+            (month_id, fixed_expense_id, label, amount, billing_period, payment_month)
+        SELECT m.id, fe.id, fe.label, fe.amount, fe.billing_period, fe.payment_month
+        FROM months m
+        JOIN fixed_expenses fe ON fe.user_id = m.user_id
+        WHERE m.id = ?
+        "#,
+    )
+    .bind(month_id)
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO closed_month_budgets
+            (month_id, budget_id, category_id, category_label, allocated_amount, tracking_only)
+        SELECT mb.month_id, mb.id, mb.category_id, bc.label, mb.allocated_amount, bc.tracking_only
+        FROM monthly_budgets mb
+        JOIN budget_categories bc ON mb.category_id = bc.id
+        WHERE mb.month_id = ?
+        "#,
+    )
+    .bind(month_id)
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO closed_month_items
+            (month_id, item_id, category_id, category_label, description, amount, spent_on, savings_destination, tax_rate, tax_amount, tracking_only)
+        SELECT i.month_id, i.id, i.category_id, bc.label, i.description, i.amount, i.spent_on, i.savings_destination, i.tax_rate, i.tax_amount, bc.tracking_only
+        FROM items i
+        JOIN budget_categories bc ON i.category_id = bc.id
+        WHERE i.month_id = ?
+        "#,
+    )
+    .bind(month_id)
+    .execute(&mut *conn)
+    .await?;
+
+    sqlx::query("UPDATE months SET frozen_at = datetime('now') WHERE id = ?")
+        .bind(month_id)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(())
+}
+
+/// Counts rows per table whose foreign key points at a missing parent. Such rows can
+/// only come from databases written with foreign keys switched off, such as manual
+/// edits in the sqlite3 shell.
+pub async fn find_orphans(pool: &SqlitePool) -> Result<BTreeMap<String, i64>, sqlx::Error> {
+    let violations: Vec<(String, Option<i64>, String, i64)> =
+        sqlx::query_as("PRAGMA foreign_key_check")
+            .fetch_all(pool)
+            .await?;
+
+    let mut orphans = BTreeMap::new();
+    for (table, _, _, _) in violations {
+        *orphans.entry(table).or_insert(0) += 1;
+    }
+    Ok(orphans)
+}
+
+/// Deletes rows whose foreign key points at a missing parent and returns how many were
+/// removed per table. Deleting a parent can orphan its own children, so this repeats
+/// until a pass finds nothing left to delete.
+pub async fn repair_orphans(pool: &SqlitePool) -> Result<BTreeMap<String, i64>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let mut removed = BTreeMap::new();
+
+    loop {
+        let violations: Vec<(String, Option<i64>, String, i64)> =
+            sqlx::query_as("PRAGMA foreign_key_check")
+                .fetch_all(&mut *tx)
+                .await?;
+        let mut deleted_this_pass = 0;
+        for (table, rowid, _, _) in violations {
+            let Some(rowid) = rowid else { continue };
+            // Table names come from SQLite itself, not from user input
+            let deleted = sqlx::query(&format!("DELETE FROM \"{table}\" WHERE rowid = ?"))
+                .bind(rowid)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+            *removed.entry(table).or_insert(0) += deleted as i64;
+            deleted_this_pass += deleted;
+        }
+        if deleted_this_pass == 0 {
+            break;
+        }
+    }
+
+    tx.commit().await?;
+    Ok(removed)
+}
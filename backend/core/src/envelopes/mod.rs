@@ -0,0 +1,117 @@
+use crate::models::{CoverSuggestion, Envelope};
+
+/// Suggests transfers that cover overspent envelopes, largest deficit first.
+///
+/// Unassigned money is used before anything is taken from other envelopes, which give
+/// from their surplus starting with the largest. Amounts are rounded to cents.
+pub fn cover_suggestions(envelopes: &[Envelope], unassigned: f64) -> Vec<CoverSuggestion> {
+    let cents = |amount: f64| (amount * 100.0).round() as i64;
+
+    let mut deficits: Vec<(i64, i64)> = envelopes
+        .iter()
+        .filter(|e| cents(e.available) < 0)
+        .map(|e| (e.budget_id, -cents(e.available)))
+        .collect();
+    deficits.sort_by_key(|&(id, amount)| (-amount, id));
+
+    let mut sources: Vec<(Option<i64>, i64)> = Vec::new();
+    if cents(unassigned) > 0 {
+        sources.push((None, cents(unassigned)));
+    }
+    let mut surpluses: Vec<(Option<i64>, i64)> = envelopes
+        .iter()
+        .filter(|e| cents(e.available) > 0)
+        .map(|e| (Some(e.budget_id), cents(e.available)))
+        .collect();
+    surpluses.sort_by_key(|&(id, amount)| (-amount, id));
+    sources.extend(surpluses);
+
+    let mut suggestions = Vec::new();
+    let mut sources = sources.into_iter().peekable();
+    for (to_budget_id, mut needed) in deficits {
+        while needed > 0 {
+            let Some((from_budget_id, left)) = sources.peek_mut() else {
+                return suggestions;
+            };
+            let amount = needed.min(*left);
+            suggestions.push(CoverSuggestion {
+                from_budget_id: *from_budget_id,
+                to_budget_id,
+                amount: amount as f64 / 100.0,
+            });
+            needed -= amount;
+            *left -= amount;
+            if *left == 0 {
+                sources.next();
+            }
+        }
+    }
+
+    suggestions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn envelope(budget_id: i64, available: f64) -> Envelope {
+        Envelope {
+            budget_id,
+            category_id: budget_id,
+            category_label: format!("Category {budget_id}"),
+            allocated: 100.0,
+            spent: 100.0 - available,
+            available,
+        }
+    }
+
+    fn suggestion(from: Option<i64>, to: i64, amount: f64) -> CoverSuggestion {
+        CoverSuggestion {
+            from_budget_id: from,
+            to_budget_id: to,
+            amount,
+        }
+    }
+
+    #[test]
+    fn test_no_deficit_no_suggestions() {
+        let envelopes = [envelope(1, 20.0), envelope(2, 0.0)];
+        assert!(cover_suggestions(&envelopes, 50.0).is_empty());
+    }
+
+    #[test]
+    fn test_unassigned_money_is_used_first() {
+        let envelopes = [envelope(1, -30.0), envelope(2, 80.0)];
+        assert_eq!(
+            cover_suggestions(&envelopes, 20.0),
+            vec![suggestion(None, 1, 20.0), suggestion(Some(2), 1, 10.0)]
+        );
+    }
+
+    #[test]
+    fn test_largest_surplus_covers_largest_deficit() {
+        let envelopes = [
+            envelope(1, -15.5),
+            envelope(2, 10.0),
+            envelope(3, -40.0),
+            envelope(4, 45.0),
+        ];
+        assert_eq!(
+            cover_suggestions(&envelopes, 0.0),
+            vec![
+                suggestion(Some(4), 3, 40.0),
+                suggestion(Some(4), 1, 5.0),
+                suggestion(Some(2), 1, 10.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stops_when_money_runs_out() {
+        let envelopes = [envelope(1, -50.0), envelope(2, 10.0)];
+        assert_eq!(
+            cover_suggestions(&envelopes, -5.0),
+            vec![suggestion(Some(2), 1, 10.0)]
+        );
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+/// Average spending on a category in a typical month.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CategorySpend {
+    pub category_id: i64,
+    pub label: String,
+    pub monthly: f64,
+}
+
+/// What a typical month looks like, averaged from the user's recent months.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Baseline {
+    pub income: f64,
+    /// Fixed expenses spread to a monthly figure.
+    pub fixed: f64,
+    pub categories: Vec<CategorySpend>,
+}
+
+/// Hypothetical changes applied on top of a baseline.
+#[derive(Debug, Clone, Default)]
+pub struct Scenario {
+    /// Added to the monthly income, negative for a pay cut.
+    pub income_change: f64,
+    /// Percentage change per category id, e.g. -20.0 to cut it by a fifth.
+    pub category_changes: HashMap<i64, f64>,
+    /// New recurring payments added to the fixed costs, e.g. a loan.
+    pub extra_fixed: f64,
+}
+
+impl Baseline {
+    pub fn spending(&self) -> f64 {
+        self.categories.iter().map(|c| c.monthly).sum()
+    }
+
+    /// What's left at the end of a typical month, negative for a deficit.
+    pub fn remaining(&self) -> f64 {
+        self.income - self.fixed - self.spending()
+    }
+
+    /// The typical month once the scenario's changes are made. Spending never goes
+    /// below zero.
+    pub fn apply(&self, scenario: &Scenario) -> Baseline {
+        Baseline {
+            income: self.income + scenario.income_change,
+            fixed: self.fixed + scenario.extra_fixed,
+            categories: self
+                .categories
+                .iter()
+                .map(|c| {
+                    let percent = scenario
+                        .category_changes
+                        .get(&c.category_id)
+                        .copied()
+                        .unwrap_or(0.0);
+                    CategorySpend {
+                        monthly: (c.monthly * (1.0 + percent / 100.0)).max(0.0),
+                        ..c.clone()
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    /// Projects the current month's remaining money: spending so far, plus each
+    /// category's typical daily rate for the days left. Days are 1-based and
+    /// `days_elapsed` includes today.
+    pub fn month_end(
+        &self,
+        income: f64,
+        spent_so_far: &HashMap<i64, f64>,
+        days_elapsed: i64,
+        days_in_month: i64,
+    ) -> f64 {
+        let days_left = (days_in_month - days_elapsed).max(0) as f64;
+        let mut spending: f64 = spent_so_far.values().sum();
+        for category in &self.categories {
+            spending += category.monthly / days_in_month.max(1) as f64 * days_left;
+        }
+        income - self.fixed - spending
+    }
+}
+
+/// Money accumulated at the end of each of the next `months` months.
+pub fn cumulative(monthly: f64, months: usize) -> Vec<f64> {
+    (1..=months).map(|m| monthly * m as f64).collect()
+}
+
+/// Balance at the end of each of the next `years` years when `monthly` is added
+/// every month and the balance grows by `annual_rate` percent a year, compounded
+/// monthly.
+pub fn compound(start: f64, monthly: f64, annual_rate: f64, years: u32) -> Vec<f64> {
+    let monthly_rate = annual_rate / 100.0 / 12.0;
+    let mut balance = start;
+    (0..years)
+        .map(|_| {
+            for _ in 0..12 {
+                balance = balance * (1.0 + monthly_rate) + monthly;
+            }
+            balance
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn baseline() -> Baseline {
+        Baseline {
+            income: 4000.0,
+            fixed: 1500.0,
+            categories: vec![
+                CategorySpend {
+                    category_id: 1,
+                    label: "Food".to_string(),
+                    monthly: 600.0,
+                },
+                CategorySpend {
+                    category_id: 2,
+                    label: "Fun".to_string(),
+                    monthly: 300.0,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_remaining() {
+        assert_eq!(baseline().remaining(), 1600.0);
+    }
+
+    #[test]
+    fn test_apply_scenario() {
+        let scenario = Scenario {
+            income_change: 200.0,
+            category_changes: HashMap::from([(2, -20.0), (1, -150.0)]),
+            extra_fixed: 250.0,
+        };
+        let simulated = baseline().apply(&scenario);
+
+        assert_eq!(simulated.income, 4200.0);
+        assert_eq!(simulated.fixed, 1750.0);
+        assert_eq!(simulated.categories[0].monthly, 0.0);
+        assert_eq!(simulated.categories[1].monthly, 240.0);
+        assert_eq!(simulated.remaining(), 2210.0);
+    }
+
+    #[test]
+    fn test_month_end() {
+        let spent = HashMap::from([(1, 250.0), (3, 40.0)]);
+        // 10 of 30 days left add a third of each category's typical month
+        let remaining = baseline().month_end(4000.0, &spent, 20, 30);
+        assert_eq!(remaining, 4000.0 - 1500.0 - 290.0 - 300.0);
+    }
+
+    #[test]
+    fn test_cumulative() {
+        assert_eq!(cumulative(100.0, 3), vec![100.0, 200.0, 300.0]);
+        assert_eq!(cumulative(-50.0, 2), vec![-50.0, -100.0]);
+    }
+
+    #[test]
+    fn test_compound() {
+        assert_eq!(compound(1000.0, 100.0, 0.0, 2), vec![2200.0, 3400.0]);
+
+        let grown = compound(1200.0, 0.0, 12.0, 1);
+        assert!((grown[0] - 1200.0 * 1.01_f64.powi(12)).abs() < 1e-9);
+    }
+}
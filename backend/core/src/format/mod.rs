@@ -0,0 +1,127 @@
+use crate::models::UserSettings;
+
+pub const DEFAULT_LOCALE: &str = "en-US";
+pub const DEFAULT_CURRENCY: &str = "USD";
+
+/// How monetary amounts are rendered in reports.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoneyFormat {
+    pub symbol: String,
+    pub symbol_after: bool,
+    pub decimals: usize,
+    pub thousands_separator: &'static str,
+    pub decimal_separator: &'static str,
+}
+
+impl Default for MoneyFormat {
+    fn default() -> Self {
+        Self::new(DEFAULT_LOCALE, DEFAULT_CURRENCY)
+    }
+}
+
+impl MoneyFormat {
+    /// Builds a format from a BCP 47 locale tag (e.g. `de-DE`) and an ISO 4217 currency code.
+    pub fn new(locale: &str, currency: &str) -> Self {
+        let language = locale
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        let (thousands_separator, decimal_separator, symbol_after) = match language.as_str() {
+            "de" | "es" | "it" | "nl" | "pt" | "da" | "tr" | "id" => (".", ",", true),
+            "fr" | "sv" | "nb" | "no" | "fi" | "pl" | "cs" | "ru" | "uk" => ("\u{a0}", ",", true),
+            _ => (",", ".", false),
+        };
+
+        let currency = currency.to_ascii_uppercase();
+        let symbol = match currency.as_str() {
+            "USD" | "CAD" | "AUD" | "NZD" | "MXN" => "$",
+            "EUR" => "€",
+            "GBP" => "£",
+            "JPY" | "CNY" => "¥",
+            "INR" => "₹",
+            "KRW" => "₩",
+            "SEK" | "NOK" | "DKK" => "kr",
+            other => other,
+        }
+        .to_string();
+
+        let decimals = match currency.as_str() {
+            "JPY" | "KRW" | "CLP" | "ISK" | "VND" | "HUF" => 0,
+            _ => 2,
+        };
+
+        Self {
+            symbol,
+            symbol_after,
+            decimals,
+            thousands_separator,
+            decimal_separator,
+        }
+    }
+
+    pub fn from_settings(settings: &UserSettings) -> Self {
+        Self::new(
+            settings.locale.as_deref().unwrap_or(DEFAULT_LOCALE),
+            settings.currency.as_deref().unwrap_or(DEFAULT_CURRENCY),
+        )
+    }
+
+    /// Formats an amount, e.g. `$1,234.50`, `1.234,50 €` or `¥1,235`.
+    pub fn format(&self, amount: f64) -> String {
+        let sign = if amount < 0.0 { "-" } else { "" };
+        let fixed = format!("{:.*}", self.decimals, amount.abs());
+        let (whole, fraction) = match fixed.split_once('.') {
+            Some((whole, fraction)) => (whole, Some(fraction)),
+            None => (fixed.as_str(), None),
+        };
+
+        let mut grouped = String::new();
+        for (i, digit) in whole.chars().enumerate() {
+            if i > 0 && (whole.len() - i) % 3 == 0 {
+                grouped.push_str(self.thousands_separator);
+            }
+            grouped.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            grouped.push_str(self.decimal_separator);
+            grouped.push_str(fraction);
+        }
+
+        if self.symbol_after {
+            format!("{sign}{grouped}\u{a0}{}", self.symbol)
+        } else {
+            format!("{sign}{}{grouped}", self.symbol)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_format() {
+        assert_eq!(MoneyFormat::default().format(1234.5), "$1,234.50");
+        assert_eq!(MoneyFormat::default().format(-12.0), "-$12.00");
+    }
+
+    #[test]
+    fn test_german_euro_format() {
+        let format = MoneyFormat::new("de-DE", "EUR");
+        assert_eq!(format.format(1234567.891), "1.234.567,89\u{a0}€");
+    }
+
+    #[test]
+    fn test_zero_decimal_currency() {
+        let format = MoneyFormat::new("ja-JP", "JPY");
+        assert_eq!(format.format(1234.6), "¥1,235");
+    }
+
+    #[test]
+    fn test_unknown_currency_uses_code() {
+        let format = MoneyFormat::new("en-US", "brl");
+        assert_eq!(format.format(10.0), "BRL10.00");
+    }
+}
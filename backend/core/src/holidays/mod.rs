@@ -0,0 +1,258 @@
+//! Bank holiday calendars, to work out when a payment scheduled on a given day of the
+//! month actually goes through. Only nationwide holidays are known; regional ones are
+//! left out.
+
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+/// Countries with a holiday calendar, as ISO 3166 codes.
+pub const COUNTRIES: [&str; 4] = ["DE", "FR", "GB", "US"];
+
+/// Which way a payment moves when it falls on a weekend or a bank holiday.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Roll {
+    /// Stays on the scheduled day.
+    None,
+    /// Moves to the next business day.
+    Forward,
+    /// Moves to the previous business day, as salaries usually are.
+    Backward,
+}
+
+impl Roll {
+    pub fn parse(roll: &str) -> Option<Self> {
+        match roll {
+            "none" => Some(Roll::None),
+            "forward" => Some(Roll::Forward),
+            "backward" => Some(Roll::Backward),
+            _ => None,
+        }
+    }
+}
+
+/// The country's bank holidays in `year`, in date order, or `None` for a country
+/// without a calendar. Holidays falling on a weekend are followed by the day off
+/// given in their place, where the country has one.
+pub fn bank_holidays(country: &str, year: i32) -> Option<Vec<NaiveDate>> {
+    let date = |month: u32, day: u32| NaiveDate::from_ymd_opt(year, month, day);
+    let easter = easter_sunday(year)?;
+    let mut days = match country {
+        "DE" => vec![
+            date(1, 1)?,
+            easter - Duration::days(2),
+            easter + Duration::days(1),
+            date(5, 1)?,
+            easter + Duration::days(39),
+            easter + Duration::days(50),
+            date(10, 3)?,
+            date(12, 25)?,
+            date(12, 26)?,
+        ],
+        "FR" => vec![
+            date(1, 1)?,
+            easter + Duration::days(1),
+            date(5, 1)?,
+            date(5, 8)?,
+            easter + Duration::days(39),
+            easter + Duration::days(50),
+            date(7, 14)?,
+            date(8, 15)?,
+            date(11, 1)?,
+            date(11, 11)?,
+            date(12, 25)?,
+        ],
+        // England and Wales
+        "GB" => {
+            let mut days = vec![
+                date(1, 1)?,
+                easter - Duration::days(2),
+                easter + Duration::days(1),
+                nth_weekday(year, 5, Weekday::Mon, 1)?,
+                last_weekday(year, 5, Weekday::Mon)?,
+                last_weekday(year, 8, Weekday::Mon)?,
+                date(12, 25)?,
+                date(12, 26)?,
+            ];
+            // A holiday on a weekend gives the next weekday that isn't one already
+            let weekend: Vec<NaiveDate> = days.iter().copied().filter(|d| is_weekend(*d)).collect();
+            for day in weekend {
+                let mut substitute = day + Duration::days(1);
+                while is_weekend(substitute) || days.contains(&substitute) {
+                    substitute += Duration::days(1);
+                }
+                days.push(substitute);
+            }
+            days
+        }
+        // Federal holidays, observed on the Friday before or the Monday after when
+        // they fall on a weekend
+        "US" => {
+            let observed = |day: NaiveDate| match day.weekday() {
+                Weekday::Sat => day - Duration::days(1),
+                Weekday::Sun => day + Duration::days(1),
+                _ => day,
+            };
+            vec![
+                observed(date(1, 1)?),
+                nth_weekday(year, 1, Weekday::Mon, 3)?,
+                nth_weekday(year, 2, Weekday::Mon, 3)?,
+                last_weekday(year, 5, Weekday::Mon)?,
+                observed(date(6, 19)?),
+                observed(date(7, 4)?),
+                nth_weekday(year, 9, Weekday::Mon, 1)?,
+                nth_weekday(year, 10, Weekday::Mon, 2)?,
+                observed(date(11, 11)?),
+                nth_weekday(year, 11, Weekday::Thu, 4)?,
+                observed(date(12, 25)?),
+            ]
+        }
+        _ => return None,
+    };
+    days.sort();
+    days.dedup();
+    Some(days)
+}
+
+/// Whether money moves on that day: not a weekend, nor one of the country's bank
+/// holidays. Without a country only weekends are skipped.
+pub fn is_business_day(date: NaiveDate, country: Option<&str>) -> bool {
+    !is_weekend(date)
+        && !country
+            .and_then(|country| bank_holidays(country, date.year()))
+            .is_some_and(|days| days.contains(&date))
+}
+
+/// The day a payment scheduled on `date` goes through.
+pub fn roll(date: NaiveDate, roll: Roll, country: Option<&str>) -> NaiveDate {
+    let step = match roll {
+        Roll::None => return date,
+        Roll::Forward => Duration::days(1),
+        Roll::Backward => Duration::days(-1),
+    };
+    let mut day = date;
+    while !is_business_day(day, country) {
+        day += step;
+    }
+    day
+}
+
+/// The day a payment scheduled on `day` of a month goes through. Days past the end of
+/// the month fall on its last day.
+pub fn scheduled(
+    year: i32,
+    month: u32,
+    day: u32,
+    how: Roll,
+    country: Option<&str>,
+) -> Option<NaiveDate> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+    let last = first.checked_add_months(chrono::Months::new(1))? - Duration::days(1);
+    let date = NaiveDate::from_ymd_opt(year, month, day.clamp(1, last.day()))?;
+    Some(roll(date, how, country))
+}
+
+fn is_weekend(date: NaiveDate) -> bool {
+    matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+}
+
+/// Anonymous Gregorian algorithm.
+fn easter_sunday(year: i32) -> Option<NaiveDate> {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: u8) -> Option<NaiveDate> {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, n)
+}
+
+fn last_weekday(year: i32, month: u32, weekday: Weekday) -> Option<NaiveDate> {
+    nth_weekday(year, month, weekday, 5).or_else(|| nth_weekday(year, month, weekday, 4))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_easter() {
+        assert_eq!(easter_sunday(2024), Some(date(2024, 3, 31)));
+        assert_eq!(easter_sunday(2025), Some(date(2025, 4, 20)));
+        assert_eq!(easter_sunday(2038), Some(date(2038, 4, 25)));
+    }
+
+    #[test]
+    fn test_bank_holidays() {
+        let gb = bank_holidays("GB", 2021).unwrap();
+        // Christmas on a Saturday moves to Monday 27th, Boxing Day to Tuesday 28th
+        assert!(gb.contains(&date(2021, 12, 27)));
+        assert!(gb.contains(&date(2021, 12, 28)));
+        assert!(gb.contains(&date(2021, 5, 31)));
+        assert_eq!(gb.len(), 10);
+
+        let us = bank_holidays("US", 2021).unwrap();
+        assert!(us.contains(&date(2021, 7, 5)));
+        assert!(us.contains(&date(2021, 11, 25)));
+
+        let fr = bank_holidays("FR", 2024).unwrap();
+        assert!(fr.contains(&date(2024, 5, 9)));
+        assert!(fr.contains(&date(2024, 5, 20)));
+        assert_eq!(fr.len(), 11);
+
+        assert!(bank_holidays("XX", 2024).is_none());
+    }
+
+    #[test]
+    fn test_roll_around_weekends_and_holidays() {
+        // Saturday 25 May 2024, with Whit Monday on the 20th and Good Friday in March
+        assert_eq!(
+            roll(date(2024, 5, 25), Roll::Backward, None),
+            date(2024, 5, 24)
+        );
+        assert_eq!(
+            roll(date(2024, 5, 25), Roll::Forward, None),
+            date(2024, 5, 27)
+        );
+        assert_eq!(
+            roll(date(2024, 5, 20), Roll::Forward, Some("DE")),
+            date(2024, 5, 21)
+        );
+        // Easter Monday 1 April after Good Friday 29 March
+        assert_eq!(
+            roll(date(2024, 4, 1), Roll::Backward, Some("DE")),
+            date(2024, 3, 28)
+        );
+        assert_eq!(
+            roll(date(2024, 5, 25), Roll::None, Some("DE")),
+            date(2024, 5, 25)
+        );
+    }
+
+    #[test]
+    fn test_scheduled_clamps_to_month_end() {
+        // 31 June is 30 June 2024, a Sunday
+        assert_eq!(
+            scheduled(2024, 6, 31, Roll::Backward, None),
+            Some(date(2024, 6, 28))
+        );
+        assert_eq!(
+            scheduled(2024, 6, 31, Roll::Forward, None),
+            Some(date(2024, 7, 1))
+        );
+    }
+}
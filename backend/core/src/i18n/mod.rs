@@ -0,0 +1,335 @@
+/// Languages with a message catalog. English is the fallback for anything else.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Locale {
+    #[default]
+    En,
+    Fr,
+    De,
+}
+
+/// Keys for every translatable message the API produces.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Text {
+    ErrorValidation,
+    ErrorBadRequest,
+    ErrorMalformedBody,
+    ErrorInvalidBody,
+    ErrorConflict,
+    ErrorDuplicate,
+    ErrorQuotaExceeded,
+    ErrorForbidden,
+    ErrorNotFound,
+    ErrorUnauthorized,
+    ErrorLocked,
+    ErrorReadOnly,
+    ErrorInternal,
+    InsightCategorySpike,
+    InsightFixedCostRatio,
+    InsightNoSpendStreak,
+    InsightInvoiceOverdue,
+    InsightIncomeRebudget,
+    ReportTitle,
+    ReportIncome,
+    ReportFixedExpenses,
+    ReportBudgetVsActual,
+    ReportSpendingItems,
+    ReportSummary,
+    ReportTotalIncome,
+    ReportTotalFixed,
+    ReportTotalSpent,
+    ReportRemaining,
+    ReportDeficit,
+    ReportNoSpendDays,
+    ReportOverBy,
+    ReportLeft,
+    ReportReview,
+    ReportMileage,
+    ReportPerDiem,
+    ReportItemTax,
+    ReportTotalTax,
+    ReportTotalTracked,
+    ReportTrackingOnly,
+    ReportVerify,
+    ReportCloseChecklist,
+    ReportChecklistDone,
+    ReportChecklistSkipped,
+    ReportDigest,
+    ReportSavingsChange,
+    ReportNetWorthChange,
+    ReportTopOverspent,
+    ReportBiggestPurchase,
+}
+
+impl Locale {
+    /// Matches the primary subtag of a BCP 47 tag, e.g. `fr-CA` -> French.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let language = tag.trim().split(['-', '_']).next()?.to_ascii_lowercase();
+        match language.as_str() {
+            "en" => Some(Locale::En),
+            "fr" => Some(Locale::Fr),
+            "de" => Some(Locale::De),
+            _ => None,
+        }
+    }
+
+    pub fn tag(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Fr => "fr",
+            Locale::De => "de",
+        }
+    }
+
+    /// Picks the supported language with the highest `q` weight from an `Accept-Language` header.
+    pub fn from_accept_language(header: &str) -> Option<Self> {
+        let mut best: Option<(Self, f32)> = None;
+        for part in header.split(',') {
+            let mut pieces = part.split(';');
+            let Some(locale) = pieces.next().and_then(Self::from_tag) else {
+                continue;
+            };
+            let weight = pieces
+                .find_map(|p| p.trim().strip_prefix("q="))
+                .and_then(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            if best.is_none_or(|(_, w)| weight > w) {
+                best = Some((locale, weight));
+            }
+        }
+        best.map(|(locale, _)| locale)
+    }
+
+    /// A saved user preference wins over the request header.
+    pub fn resolve(preferred: Option<&str>, accept_language: Option<&str>) -> Self {
+        preferred
+            .and_then(Self::from_tag)
+            .or_else(|| accept_language.and_then(Self::from_accept_language))
+            .unwrap_or_default()
+    }
+
+    pub fn text(self, text: Text) -> &'static str {
+        match self {
+            Locale::En => en(text),
+            Locale::Fr => fr(text),
+            Locale::De => de(text),
+        }
+    }
+
+    /// Looks up a message and fills its `{name}` placeholders.
+    pub fn render(self, text: Text, args: &[(&str, &str)]) -> String {
+        args.iter()
+            .fold(self.text(text).to_string(), |message, (name, value)| {
+                message.replace(&format!("{{{name}}}"), value)
+            })
+    }
+}
+
+fn en(text: Text) -> &'static str {
+    match text {
+        Text::ErrorValidation => "Some fields are invalid",
+        Text::ErrorBadRequest => "The request could not be processed",
+        Text::ErrorMalformedBody => "The request body is not valid JSON",
+        Text::ErrorInvalidBody => "The request body does not have the expected fields",
+        Text::ErrorConflict => "This conflicts with existing data",
+        Text::ErrorDuplicate => "This looks like an item entered moments ago",
+        Text::ErrorQuotaExceeded => "This account has reached a limit set on this server",
+        Text::ErrorForbidden => "This is not allowed right now",
+        Text::ErrorNotFound => "Not found",
+        Text::ErrorUnauthorized => "You need to sign in",
+        Text::ErrorLocked => "Too many failed attempts, please try again later",
+        Text::ErrorReadOnly => {
+            "The server is in read-only maintenance mode, please try again later"
+        }
+        Text::ErrorInternal => "Something went wrong on our side",
+        Text::InsightCategorySpike => {
+            "Spending on {label} is up {percent}% vs your {months}-month average"
+        }
+        Text::InsightFixedCostRatio => "Fixed costs take up {percent}% of this month's income",
+        Text::InsightNoSpendStreak => "You went {days} days in a row without spending this month",
+        Text::InsightInvoiceOverdue => {
+            "The invoice to {client} for {amount} is {days} days overdue"
+        }
+        Text::InsightIncomeRebudget => {
+            "Income for {month}/{year} is now {income}, so percentage budgets were updated: {changes}. Left to budget: {amount}"
+        }
+        Text::ReportTitle => "Financial Summary - {month}/{year}",
+        Text::ReportIncome => "INCOME",
+        Text::ReportFixedExpenses => "FIXED EXPENSES",
+        Text::ReportBudgetVsActual => "BUDGET VS ACTUAL",
+        Text::ReportSpendingItems => "SPENDING ITEMS",
+        Text::ReportSummary => "SUMMARY",
+        Text::ReportTotalIncome => "Total Income: {amount}",
+        Text::ReportTotalFixed => "Total Fixed: {amount}",
+        Text::ReportTotalSpent => "Total Spent: {amount}",
+        Text::ReportRemaining => "Remaining: {amount}",
+        Text::ReportDeficit => "Deficit: {amount}",
+        Text::ReportNoSpendDays => "No-spend days: {days}",
+        Text::ReportOverBy => "OVER by {amount}",
+        Text::ReportLeft => "{amount} remaining",
+        Text::ReportReview => "Review: {rating}/5",
+        Text::ReportMileage => "Mileage: {quantity} x {rate}",
+        Text::ReportPerDiem => "Per diem: {quantity} days x {rate}",
+        Text::ReportItemTax => "Incl. {rate}% tax: {amount}",
+        Text::ReportTotalTax => "Tax included: {amount}",
+        Text::ReportTotalTracked => "Tracked, not counted: {amount}",
+        Text::ReportTrackingOnly => "tracking only",
+        Text::ReportVerify => "Signed report, verify it at {url}",
+        Text::ReportCloseChecklist => "CLOSE CHECKLIST",
+        Text::ReportChecklistDone => "Done: {label}",
+        Text::ReportChecklistSkipped => "Skipped: {label}",
+        Text::ReportDigest => "MONTH IN REVIEW",
+        Text::ReportSavingsChange => "Savings: {amount} since last month",
+        Text::ReportNetWorthChange => "Net worth: {amount} since last month",
+        Text::ReportTopOverspent => "Most overspent: {category}, over by {amount}",
+        Text::ReportBiggestPurchase => "Biggest purchase: {description}, {amount}",
+    }
+}
+
+fn fr(text: Text) -> &'static str {
+    match text {
+        Text::ErrorValidation => "Certains champs sont invalides",
+        Text::ErrorBadRequest => "La requête n'a pas pu être traitée",
+        Text::ErrorMalformedBody => "Le corps de la requête n'est pas du JSON valide",
+        Text::ErrorInvalidBody => "Le corps de la requête n'a pas les champs attendus",
+        Text::ErrorConflict => "Cela entre en conflit avec des données existantes",
+        Text::ErrorDuplicate => "Cela ressemble à une dépense saisie il y a quelques instants",
+        Text::ErrorQuotaExceeded => "Ce compte a atteint une limite fixée sur ce serveur",
+        Text::ErrorForbidden => "Ce n'est pas autorisé pour le moment",
+        Text::ErrorNotFound => "Introuvable",
+        Text::ErrorUnauthorized => "Vous devez vous connecter",
+        Text::ErrorLocked => "Trop de tentatives échouées, veuillez réessayer plus tard",
+        Text::ErrorReadOnly => "Le serveur est en maintenance en lecture seule, veuillez réessayer plus tard",
+        Text::ErrorInternal => "Une erreur est survenue de notre côté",
+        Text::InsightCategorySpike => {
+            "Les dépenses en {label} ont augmenté de {percent} % par rapport à votre moyenne sur {months} mois"
+        }
+        Text::InsightFixedCostRatio => {
+            "Les charges fixes représentent {percent} % des revenus de ce mois"
+        }
+        Text::InsightNoSpendStreak => "Vous avez passé {days} jours d'affilée sans dépenser ce mois-ci",
+        Text::InsightInvoiceOverdue => "La facture à {client} de {amount} est en retard de {days} jours",
+        Text::InsightIncomeRebudget => {
+            "Les revenus de {month}/{year} sont maintenant de {income}, les budgets en pourcentage ont été mis à jour : {changes}. Reste à budgéter : {amount}"
+        }
+        Text::ReportTitle => "Bilan financier - {month}/{year}",
+        Text::ReportIncome => "REVENUS",
+        Text::ReportFixedExpenses => "CHARGES FIXES",
+        Text::ReportBudgetVsActual => "BUDGET ET RÉEL",
+        Text::ReportSpendingItems => "DÉPENSES",
+        Text::ReportSummary => "RÉSUMÉ",
+        Text::ReportTotalIncome => "Total des revenus : {amount}",
+        Text::ReportTotalFixed => "Total des charges fixes : {amount}",
+        Text::ReportTotalSpent => "Total dépensé : {amount}",
+        Text::ReportRemaining => "Reste : {amount}",
+        Text::ReportDeficit => "Déficit : {amount}",
+        Text::ReportNoSpendDays => "Jours sans dépense : {days}",
+        Text::ReportOverBy => "DÉPASSÉ de {amount}",
+        Text::ReportLeft => "{amount} restants",
+        Text::ReportReview => "Bilan : {rating}/5",
+        Text::ReportMileage => "Kilométrage : {quantity} x {rate}",
+        Text::ReportPerDiem => "Indemnité journalière : {quantity} jours x {rate}",
+        Text::ReportItemTax => "Dont TVA {rate} % : {amount}",
+        Text::ReportTotalTax => "TVA incluse : {amount}",
+        Text::ReportTotalTracked => "Suivi, non compté : {amount}",
+        Text::ReportTrackingOnly => "suivi uniquement",
+        Text::ReportVerify => "Rapport signé, vérifiable sur {url}",
+        Text::ReportCloseChecklist => "LISTE DE CLÔTURE",
+        Text::ReportChecklistDone => "Fait : {label}",
+        Text::ReportChecklistSkipped => "Ignoré : {label}",
+        Text::ReportDigest => "LE MOIS EN BREF",
+        Text::ReportSavingsChange => "Épargne : {amount} depuis le mois dernier",
+        Text::ReportNetWorthChange => "Patrimoine net : {amount} depuis le mois dernier",
+        Text::ReportTopOverspent => "Plus gros dépassement : {category}, de {amount}",
+        Text::ReportBiggestPurchase => "Plus gros achat : {description}, {amount}",
+    }
+}
+
+fn de(text: Text) -> &'static str {
+    match text {
+        Text::ErrorValidation => "Einige Felder sind ungültig",
+        Text::ErrorBadRequest => "Die Anfrage konnte nicht verarbeitet werden",
+        Text::ErrorMalformedBody => "Der Inhalt der Anfrage ist kein gültiges JSON",
+        Text::ErrorInvalidBody => "Der Inhalt der Anfrage hat nicht die erwarteten Felder",
+        Text::ErrorConflict => "Das steht im Konflikt mit vorhandenen Daten",
+        Text::ErrorDuplicate => "Das sieht aus wie ein gerade erst erfasster Eintrag",
+        Text::ErrorQuotaExceeded => "Dieses Konto hat ein auf diesem Server festgelegtes Limit erreicht",
+        Text::ErrorForbidden => "Das ist derzeit nicht erlaubt",
+        Text::ErrorNotFound => "Nicht gefunden",
+        Text::ErrorUnauthorized => "Bitte melde dich an",
+        Text::ErrorLocked => "Zu viele Fehlversuche, bitte versuche es später erneut",
+        Text::ErrorReadOnly => {
+            "Der Server ist im schreibgeschützten Wartungsmodus, bitte versuche es später erneut"
+        }
+        Text::ErrorInternal => "Bei uns ist etwas schiefgelaufen",
+        Text::InsightCategorySpike => {
+            "Die Ausgaben für {label} liegen {percent} % über deinem {months}-Monats-Durchschnitt"
+        }
+        Text::InsightFixedCostRatio => {
+            "Fixkosten machen {percent} % der Einnahmen dieses Monats aus"
+        }
+        Text::InsightNoSpendStreak => "Du hast diesen Monat {days} Tage in Folge nichts ausgegeben",
+        Text::InsightInvoiceOverdue => {
+            "Die Rechnung an {client} über {amount} ist seit {days} Tagen überfällig"
+        }
+        Text::InsightIncomeRebudget => {
+            "Das Einkommen für {month}/{year} beträgt jetzt {income}, prozentuale Budgets wurden angepasst: {changes}. Noch zu verplanen: {amount}"
+        }
+        Text::ReportTitle => "Finanzübersicht - {month}/{year}",
+        Text::ReportIncome => "EINNAHMEN",
+        Text::ReportFixedExpenses => "FIXKOSTEN",
+        Text::ReportBudgetVsActual => "BUDGET UND IST",
+        Text::ReportSpendingItems => "AUSGABEN",
+        Text::ReportSummary => "ZUSAMMENFASSUNG",
+        Text::ReportTotalIncome => "Einnahmen gesamt: {amount}",
+        Text::ReportTotalFixed => "Fixkosten gesamt: {amount}",
+        Text::ReportTotalSpent => "Ausgaben gesamt: {amount}",
+        Text::ReportRemaining => "Verbleibend: {amount}",
+        Text::ReportDeficit => "Defizit: {amount}",
+        Text::ReportNoSpendDays => "Tage ohne Ausgaben: {days}",
+        Text::ReportOverBy => "ÜBERSCHRITTEN um {amount}",
+        Text::ReportLeft => "{amount} übrig",
+        Text::ReportReview => "Rückblick: {rating}/5",
+        Text::ReportMileage => "Kilometergeld: {quantity} x {rate}",
+        Text::ReportPerDiem => "Tagegeld: {quantity} Tage x {rate}",
+        Text::ReportItemTax => "Inkl. {rate} % MwSt.: {amount}",
+        Text::ReportTotalTax => "Enthaltene MwSt.: {amount}",
+        Text::ReportTotalTracked => "Erfasst, nicht angerechnet: {amount}",
+        Text::ReportTrackingOnly => "nur erfasst",
+        Text::ReportVerify => "Signierter Bericht, prüfbar unter {url}",
+        Text::ReportCloseChecklist => "ABSCHLUSS-CHECKLISTE",
+        Text::ReportChecklistDone => "Erledigt: {label}",
+        Text::ReportChecklistSkipped => "Übersprungen: {label}",
+        Text::ReportDigest => "MONATSRÜCKBLICK",
+        Text::ReportSavingsChange => "Ersparnisse: {amount} seit letztem Monat",
+        Text::ReportNetWorthChange => "Nettovermögen: {amount} seit letztem Monat",
+        Text::ReportTopOverspent => "Größte Überschreitung: {category}, um {amount}",
+        Text::ReportBiggestPurchase => "Größter Einkauf: {description}, {amount}",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accept_language_prefers_highest_weight() {
+        assert_eq!(
+            Locale::from_accept_language("en;q=0.5, de-DE;q=0.9, ja"),
+            Some(Locale::De)
+        );
+        assert_eq!(Locale::from_accept_language("ja, zh"), None);
+    }
+
+    #[test]
+    fn test_saved_locale_wins_over_header() {
+        assert_eq!(Locale::resolve(Some("fr-CA"), Some("de")), Locale::Fr);
+        assert_eq!(Locale::resolve(Some("pt-BR"), Some("de")), Locale::De);
+        assert_eq!(Locale::resolve(None, None), Locale::En);
+    }
+
+    #[test]
+    fn test_render_fills_placeholders() {
+        let text = Locale::De.render(Text::ReportTotalSpent, &[("amount", "12,00 €")]);
+        assert_eq!(text, "Ausgaben gesamt: 12,00 €");
+    }
+}
@@ -0,0 +1,24 @@
+//! The budget engine behind payme: the schema and its migrations, month summaries,
+//! forecasts and the PDF report. Nothing here depends on the HTTP server, so a CLI or a
+//! desktop app can open the database and use it directly.
+//!
+//! ```ignore
+//! let pool = payme_core::db::create_pool("sqlite:payme.db").await?;
+//! payme_core::db::run_migrations(&pool).await?;
+//! let summary = payme_core::summary::month_summary(&pool, user_id, month_id, false).await?;
+//! let pdf = payme_core::pdf::generate_pdf(&summary, &MoneyFormat::default(), Locale::En)?;
+//! ```
+
+pub mod cpi;
+pub mod db;
+pub mod envelopes;
+pub mod forecast;
+pub mod format;
+pub mod holidays;
+pub mod i18n;
+pub mod models;
+pub mod pdf;
+pub mod periods;
+pub mod seasonality;
+pub mod streaks;
+pub mod summary;
@@ -0,0 +1,1120 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::holidays::{self, Roll};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct FixedExpense {
+    pub id: i64,
+    pub user_id: i64,
+    pub label: String,
+    /// Amount of each payment.
+    pub amount: f64,
+    /// `monthly`, `quarterly` or `yearly`
+    pub billing_period: String,
+    /// Month (1-12) of a payment for quarterly and yearly expenses. Quarterly ones repeat
+    /// every three months from it.
+    pub payment_month: Option<i32>,
+    /// Day of the month (1-31) the payment is scheduled on.
+    #[sqlx(default)]
+    #[serde(default)]
+    pub payment_day: Option<i32>,
+    /// `forward` (the default) or `backward` to the nearest business day when the
+    /// payment day is a weekend or bank holiday, or `none`.
+    #[sqlx(default)]
+    #[serde(default)]
+    pub payment_roll: Option<String>,
+}
+
+impl FixedExpense {
+    /// Months covered by one payment, or `None` for an unknown billing period.
+    pub fn period_months(billing_period: &str) -> Option<i32> {
+        match billing_period {
+            "monthly" => Some(1),
+            "quarterly" => Some(3),
+            "yearly" => Some(12),
+            _ => None,
+        }
+    }
+
+    /// The payment spread evenly over the months it covers.
+    pub fn monthly_amount(&self) -> f64 {
+        self.amount / Self::period_months(&self.billing_period).unwrap_or(1) as f64
+    }
+
+    /// Whether a payment falls in the given calendar month.
+    pub fn is_due_in(&self, month: i32) -> bool {
+        let period = Self::period_months(&self.billing_period).unwrap_or(1);
+        match self.payment_month {
+            Some(payment_month) if period > 1 => (month - payment_month).rem_euclid(period) == 0,
+            _ => true,
+        }
+    }
+
+    /// The day the payment due in the month goes through, given the bank holidays of
+    /// `country`. `None` without a payment day.
+    pub fn due_on(&self, year: i32, month: i32, country: Option<&str>) -> Option<NaiveDate> {
+        let roll = self
+            .payment_roll
+            .as_deref()
+            .and_then(Roll::parse)
+            .unwrap_or(Roll::Forward);
+        holidays::scheduled(
+            year,
+            u32::try_from(month).ok()?,
+            u32::try_from(self.payment_day?).ok()?,
+            roll,
+            country,
+        )
+    }
+}
+
+/// An amount a fixed expense had from `effective_from` until its next change.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct FixedExpensePrice {
+    pub amount: f64,
+    pub billing_period: String,
+    pub effective_from: NaiveDate,
+    /// Change in the monthly share from the previous price, in percent. Null for the
+    /// first price.
+    #[sqlx(skip)]
+    pub change_percent: Option<f64>,
+}
+
+/// How the cost of the user's fixed expenses moved over a year.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FixedCostInflation {
+    pub year: i32,
+    pub from: NaiveDate,
+    /// End of the year, or today for the current year.
+    pub to: NaiveDate,
+    /// Monthly cost on `from` of the expenses that existed then and still exist.
+    pub start_monthly_total: f64,
+    /// Monthly cost of those same expenses on `to`.
+    pub end_monthly_total: f64,
+    /// Rise from the start to the end total, in percent. Null when there was nothing
+    /// to compare.
+    pub change_percent: Option<f64>,
+    /// Rise in the user's CPI series over the same months, when they have one.
+    pub cpi_change_percent: Option<f64>,
+    /// Largest rise first. Expenses added during the year come last and don't count
+    /// towards the totals.
+    pub expenses: Vec<FixedExpenseInflation>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FixedExpenseInflation {
+    pub fixed_expense_id: i64,
+    pub label: String,
+    /// Null when the expense was added during the year.
+    pub start_monthly_amount: Option<f64>,
+    pub end_monthly_amount: f64,
+    pub change_percent: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct BudgetCategory {
+    pub id: i64,
+    pub user_id: i64,
+    pub label: String,
+    pub default_amount: f64,
+    /// UUID the client created it with, to recognise it before it has an ID.
+    #[sqlx(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    /// Share of each month's income to allocate, in percent. Takes the place of
+    /// `default_amount` when set.
+    #[sqlx(default)]
+    #[serde(default)]
+    pub default_percent: Option<f64>,
+    /// Spending is itemized but left out of allocations, `total_spent` and `remaining`,
+    /// e.g. work expenses paid back later.
+    #[sqlx(default)]
+    #[serde(default)]
+    pub tracking_only: bool,
+    /// Created by the server, like the "Uncategorized" category.
+    #[sqlx(default)]
+    #[serde(default)]
+    pub system: bool,
+}
+
+/// Picks this category for items created without one whose description matches.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct CategoryRule {
+    pub id: i64,
+    pub category_id: i64,
+    /// `contains` matches the pattern anywhere in the description, ignoring case, which
+    /// also suits an emoji. `regex` matches a regular expression, ignoring case.
+    pub kind: String,
+    pub pattern: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct UserSettings {
+    pub retirement_monthly_contribution: Option<f64>,
+    pub retirement_return_rate: Option<f64>,
+    pub retirement_current_age: Option<i32>,
+    pub retirement_target_age: Option<i32>,
+    pub locale: Option<String>,
+    pub currency: Option<String>,
+    /// Amount per unit of distance used for mileage items.
+    pub mileage_rate: Option<f64>,
+    /// Daily allowance used for per-diem items.
+    pub per_diem_rate: Option<f64>,
+    /// Amount booked to savings in each new month.
+    pub savings_auto_contribution: Option<f64>,
+    /// Category the savings auto-contribution is booked under.
+    pub savings_auto_category_id: Option<i64>,
+    /// Whether percentage allocations follow income changes in open months. Unset means on.
+    pub rebudget_on_income_change: Option<bool>,
+    /// WebDAV folder, such as a Nextcloud folder, that closed months' PDFs are uploaded to,
+    /// with its password shown as `********`.
+    pub webdav_url: Option<String>,
+    /// Business profile: items can be assigned to a client and marked billable. Unset
+    /// means off.
+    pub business_profile: Option<bool>,
+    /// Months can be closed while items are still uncategorized. Unset means they can't.
+    pub close_with_uncategorized: Option<bool>,
+    /// ISO 3166 code of the country whose bank holidays payments move around.
+    pub holiday_country: Option<String>,
+    /// Day of the month (1-31) income is paid on.
+    pub payday: Option<i32>,
+    /// `backward` (the default), `forward` or `none`: where the payday moves when it
+    /// is a weekend or bank holiday.
+    pub payday_roll: Option<String>,
+}
+
+impl UserSettings {
+    /// The day income lands in the month, when a payday is set.
+    pub fn payday_on(&self, year: i32, month: i32) -> Option<NaiveDate> {
+        let roll = self
+            .payday_roll
+            .as_deref()
+            .and_then(Roll::parse)
+            .unwrap_or(Roll::Backward);
+        holidays::scheduled(
+            year,
+            u32::try_from(month).ok()?,
+            u32::try_from(self.payday?).ok()?,
+            roll,
+            self.holiday_country.as_deref(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct Month {
+    pub id: i64,
+    pub user_id: i64,
+    pub year: i32,
+    pub month: i32,
+    pub is_closed: bool,
+    pub closed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct IncomeEntry {
+    pub id: i64,
+    pub month_id: i64,
+    pub label: String,
+    pub amount: f64,
+    /// UUID the client created it with, to recognise it before it has an ID.
+    #[sqlx(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct MonthlyBudget {
+    pub id: i64,
+    pub month_id: i64,
+    pub category_id: i64,
+    pub allocated_amount: f64,
+    /// Set while the category refuses new items for this month.
+    pub locked_at: Option<DateTime<Utc>>,
+    pub lock_reason: Option<String>,
+    /// Share of the month's income the allocation follows, in percent. Cleared when
+    /// the allocation is set by hand.
+    #[sqlx(default)]
+    #[serde(default)]
+    pub allocated_percent: Option<f64>,
+    /// Average monthly spending in the category over the six months before this one,
+    /// when asked for with `include_average`. Null when there are no earlier months.
+    #[sqlx(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub average_spent: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct Item {
+    pub id: i64,
+    pub month_id: i64,
+    pub category_id: i64,
+    pub description: String,
+    pub amount: f64,
+    pub spent_on: NaiveDate,
+    pub savings_destination: String,
+    /// `pending`, `submitted` or `reimbursed` when someone else pays the item back.
+    pub reimbursement_status: Option<String>,
+    /// VAT/GST rate included in the amount, in percent.
+    pub tax_rate: Option<f64>,
+    /// Tax part of the amount, derived from the rate.
+    pub tax_amount: Option<f64>,
+    /// Paid out of the cash wallet.
+    pub paid_in_cash: bool,
+    /// UUID the client created it with, to recognise it before it has an ID.
+    #[sqlx(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    /// Client the item was spent for, in the business profile.
+    #[sqlx(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
+    /// To be invoiced to `client`.
+    #[sqlx(default)]
+    #[serde(default)]
+    pub billable: bool,
+}
+
+/// Tax included in a tax-inclusive `amount` at `rate` percent, rounded to the cent.
+pub fn included_tax(amount: f64, rate: f64) -> f64 {
+    (amount * rate / (100.0 + rate) * 100.0).round() / 100.0
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MonthlyBudgetWithCategory {
+    pub id: i64,
+    pub month_id: i64,
+    pub category_id: i64,
+    pub category_label: String,
+    pub allocated_amount: f64,
+    pub spent_amount: f64,
+    /// What is left to spend: allocated minus spent. Negative when overspent.
+    pub available: f64,
+    /// Set aside by open earmarks for planned purchases.
+    pub committed: f64,
+    /// What is left once the earmarks are honoured: available minus committed.
+    pub free: f64,
+    pub earmarks: Vec<Earmark>,
+    /// The category is tracking only: its spending is listed but not counted against
+    /// the allocation or the month's totals.
+    pub tracking_only: bool,
+    pub review: Option<BudgetReview>,
+}
+
+/// Part of a category's allocation set aside for a planned purchase.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow, ToSchema)]
+pub struct Earmark {
+    pub id: i64,
+    pub budget_id: i64,
+    pub label: String,
+    pub amount: f64,
+    /// Item the purchase was posted as. The earmark no longer counts as committed then.
+    pub item_id: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Envelope {
+    pub budget_id: i64,
+    pub category_id: i64,
+    pub category_label: String,
+    pub allocated: f64,
+    pub spent: f64,
+    pub available: f64,
+}
+
+/// A move of money into an overspent envelope.
+#[derive(Debug, Clone, PartialEq, Serialize, ToSchema)]
+pub struct CoverSuggestion {
+    /// Envelope to take the money from, or none to use money not yet assigned to any envelope.
+    pub from_budget_id: Option<i64>,
+    pub to_budget_id: i64,
+    pub amount: f64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct EnvelopesResponse {
+    pub month_id: i64,
+    /// Income left after fixed expenses and every envelope's allocation.
+    pub unassigned: f64,
+    pub envelopes: Vec<Envelope>,
+    /// Transfers that bring every overspent envelope back to zero, where possible.
+    pub suggestions: Vec<CoverSuggestion>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct YearPlan {
+    pub year: i32,
+    pub categories: Vec<CategoryPlan>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CategoryPlan {
+    pub category_id: i64,
+    pub category_label: String,
+    /// Allocation for each month, January first.
+    pub amounts: Vec<f64>,
+    /// Expected spending for each month when the category is seasonal.
+    pub seasonal: Option<Vec<f64>>,
+}
+
+/// End-of-month reflection on how a category went.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct BudgetReview {
+    pub budget_id: i64,
+    /// Satisfaction from 1 (poor) to 5 (great).
+    pub rating: i64,
+    pub note: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MonthSummary {
+    pub month: Month,
+    pub income_entries: Vec<IncomeEntry>,
+    pub fixed_expenses: Vec<FixedExpense>,
+    pub budgets: Vec<MonthlyBudgetWithCategory>,
+    pub items: Vec<ItemWithCategory>,
+    pub total_income: f64,
+    /// Fixed expenses spread to a monthly figure, so yearly and quarterly bills count a
+    /// share every month.
+    pub total_fixed: f64,
+    /// Fixed expense payments actually due this month.
+    pub fixed_due: f64,
+    pub total_budgeted: f64,
+    /// Income not yet given a job: income - fixed - budgeted. Zero means every unit of
+    /// income is planned; negative means the plan allocates more than comes in.
+    pub to_be_budgeted: f64,
+    pub total_spent: f64,
+    /// Spent in tracking-only categories, left out of `total_spent` and `remaining`.
+    pub tracked_spent: f64,
+    pub remaining: f64,
+    /// Days so far this month without any spending (fixed expenses and savings excluded).
+    pub no_spend_days: i64,
+    pub metrics: MonthMetrics,
+    /// SHA-256 of the month's stored PDF snapshot, once a closed month has one.
+    pub pdf_sha256: Option<String>,
+    /// Upload state of the PDF snapshot to the user's WebDAV folder: `pending`, `synced`
+    /// or `failed`. Null when it isn't uploaded anywhere.
+    pub pdf_sync_status: Option<String>,
+    /// The close checklist as it was when the month was closed. Empty for open months.
+    pub close_checklist: Vec<ChecklistAcknowledgement>,
+    /// What changed over the month, recorded when it was closed.
+    pub digest: Option<MonthDigest>,
+}
+
+/// Month-over-month changes recorded when a month is closed.
+#[derive(Debug, Clone, PartialEq, Serialize, sqlx::FromRow, ToSchema)]
+pub struct MonthDigest {
+    /// Change in savings since the previous closed month. Null for the first one.
+    pub savings_change: Option<f64>,
+    /// Change in savings plus retirement savings since the previous closed month.
+    pub net_worth_change: Option<f64>,
+    /// Category spent furthest past its allocation. Null when none was overspent.
+    pub top_overspent_category: Option<String>,
+    pub top_overspent_by: Option<f64>,
+    /// Description of the largest item counted as spending.
+    pub biggest_purchase: Option<String>,
+    pub biggest_purchase_amount: Option<f64>,
+}
+
+/// A step the user ticks off before closing a month.
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct ChecklistEntry {
+    pub id: i64,
+    pub label: String,
+}
+
+/// A close checklist step recorded when its month was closed.
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct ChecklistAcknowledgement {
+    pub label: String,
+    /// False when the month was closed with `force` without ticking it off.
+    pub acknowledged: bool,
+}
+
+/// Ratios derived from the month totals. Ratios are fractions (0.25 = 25%) and are
+/// null when the month has no income.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct MonthMetrics {
+    /// (income - fixed - spent) / income
+    pub savings_rate: Option<f64>,
+    /// fixed / income
+    pub fixed_cost_ratio: Option<f64>,
+    /// Spending per elapsed day of the month, excluding fixed expenses and savings.
+    pub discretionary_per_day: f64,
+}
+
+/// How the user's budget is divided into periods.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BudgetPeriodSettings {
+    /// `monthly` (default), `weekly` or `biweekly`.
+    pub period_type: String,
+    /// First day of one weekly or biweekly period, which all others are counted from.
+    pub starts_on: Option<NaiveDate>,
+}
+
+/// A budgeting period, with what was planned and spent in it.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PeriodSummary {
+    pub period_type: String,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+    /// The months the period overlaps, which hold its budgets and entries.
+    pub month_ids: Vec<i64>,
+    /// Each month's income weighted by its share of days in the period.
+    pub total_income: f64,
+    pub total_fixed: f64,
+    pub total_budgeted: f64,
+    pub total_spent: f64,
+    pub remaining: f64,
+    pub categories: Vec<PeriodCategory>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PeriodCategory {
+    pub category_id: i64,
+    pub category_label: String,
+    /// The monthly allocations' share for the days of the period.
+    pub allocated_amount: f64,
+    /// Spending dated inside the period.
+    pub spent_amount: f64,
+    pub available: f64,
+    pub tracking_only: bool,
+}
+
+/// How spending in each category compares with an even spread of its budget over
+/// the month.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MonthPace {
+    pub month_id: i64,
+    pub days_in_month: i64,
+    /// Days of the month up to and including today; all of them for past months.
+    pub days_elapsed: i64,
+    pub categories: Vec<CategoryPace>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CategoryPace {
+    pub category_id: i64,
+    pub category_label: String,
+    pub allocated_amount: f64,
+    pub spent_amount: f64,
+    /// What would have been spent by now at an even pace.
+    pub expected_amount: f64,
+    /// Spent over expected: above 1 is ahead of budget. Null before the month starts
+    /// or without an allocation.
+    pub pace: Option<f64>,
+    /// Average spending per elapsed day.
+    pub daily_rate: f64,
+    /// How many days the remaining budget lasts at the daily rate. Null without
+    /// spending, 0 once the budget is used up.
+    pub days_left: Option<f64>,
+    /// Spending per day, for the days with any.
+    pub daily: Vec<DailySpend>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DailySpend {
+    pub date: NaiveDate,
+    pub amount: f64,
+}
+
+/// A description the user has entered before, offered while they type a new item.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct DescriptionSuggestion {
+    /// Spelling used most recently.
+    pub description: String,
+    /// Category the description is filed under most often.
+    pub category_id: i64,
+    pub category_label: String,
+    pub median_amount: f64,
+    /// How many items used this description.
+    pub uses: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct ItemWithCategory {
+    pub id: i64,
+    pub month_id: i64,
+    pub category_id: i64,
+    pub category_label: String,
+    pub description: String,
+    pub amount: f64,
+    pub spent_on: NaiveDate,
+    pub savings_destination: String,
+    /// `pending`, `submitted` or `reimbursed` when someone else pays the item back.
+    pub reimbursement_status: Option<String>,
+    /// VAT/GST rate included in the amount, in percent.
+    pub tax_rate: Option<f64>,
+    /// Tax part of the amount, derived from the rate.
+    pub tax_amount: Option<f64>,
+    /// Paid out of the cash wallet.
+    pub paid_in_cash: bool,
+    /// UUID the client created it with, to recognise it before it has an ID.
+    #[sqlx(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uuid: Option<String>,
+    /// Client the item was spent for, in the business profile.
+    #[sqlx(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
+    /// To be invoiced to `client`.
+    #[sqlx(default)]
+    #[serde(default)]
+    pub billable: bool,
+    /// Set for mileage and per-diem items.
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calculation: Option<ItemCalculation>,
+}
+
+impl ItemWithCategory {
+    /// Whether the item counts as spending rather than a transfer to savings. With
+    /// `exclude_reimbursed`, items already paid back don't count either.
+    pub fn counts_as_spending(&self, exclude_reimbursed: bool) -> bool {
+        self.savings_destination == "none"
+            && !(exclude_reimbursed && self.reimbursement_status.as_deref() == Some("reimbursed"))
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CategoryStats {
+    pub category_id: i64,
+    pub category_label: String,
+    pub current_month_spent: f64,
+    pub previous_month_spent: f64,
+    pub change_amount: f64,
+    pub change_percent: Option<f64>,
+    /// What a seasonal category is expected to cost in the current month.
+    pub seasonal_expected: Option<f64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MonthlyStats {
+    pub year: i32,
+    pub month: i32,
+    pub total_income: f64,
+    pub total_spent: f64,
+    pub total_fixed: f64,
+    pub net: f64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StatsResponse {
+    pub category_comparisons: Vec<CategoryStats>,
+    pub monthly_trends: Vec<MonthlyStats>,
+    pub average_monthly_spending: f64,
+    pub average_monthly_income: f64,
+    /// With `real_terms`, the CPI reading whose prices all amounts are restated in.
+    pub price_base: Option<CpiReading>,
+}
+
+/// Consumer price index for a month. Only ratios between readings are used.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct CpiReading {
+    pub year: i32,
+    pub month: i32,
+    pub value: f64,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct DescriptionStats {
+    pub description: String,
+    pub total_spent: f64,
+    pub purchase_count: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TopSpendingResponse {
+    pub since: NaiveDate,
+    pub category_id: Option<i64>,
+    pub top_by_spend: Vec<DescriptionStats>,
+    pub largest_transactions: Vec<ItemWithCategory>,
+    pub most_frequent: Vec<DescriptionStats>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MonthNoSpend {
+    pub year: i32,
+    pub month: i32,
+    pub no_spend_days: i64,
+    pub longest_streak: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct StreaksResponse {
+    /// No-spend days in a row up to and including today.
+    pub current_streak: i64,
+    pub best_streak: i64,
+    pub months: Vec<MonthNoSpend>,
+}
+
+/// A category whose spending follows the seasons, e.g. heating in winter.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SeasonalCategory {
+    pub category_id: i64,
+    pub category_label: String,
+    /// Flat average over all months, for comparison.
+    pub average: f64,
+    /// Expected spending for each month, January first.
+    pub expected: Vec<f64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SeasonalityResponse {
+    /// When seasonality was last detected, if ever.
+    pub computed_at: Option<DateTime<Utc>>,
+    pub categories: Vec<SeasonalCategory>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct Subscription {
+    /// `fixed_expense`, or `item` for a charge detected in spending.
+    pub source: String,
+    /// Identifies the subscription when tagging its value: `fixed_expense:<id>` or
+    /// `item:<lowercased description>`.
+    pub key: String,
+    pub fixed_expense_id: Option<i64>,
+    pub label: String,
+    pub category_label: Option<String>,
+    pub monthly_amount: f64,
+    pub annual_cost: f64,
+    /// Amount charged the period before, when known.
+    pub previous_amount: Option<f64>,
+    /// The amount differs from the previous period's.
+    pub price_changed: bool,
+    pub last_charged: Option<NaiveDate>,
+    pub next_renewal: Option<NaiveDate>,
+    /// What the user gets out of it, when they've said. Null makes it a cancel candidate.
+    pub value: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SubscriptionsResponse {
+    pub subscriptions: Vec<Subscription>,
+    pub monthly_total: f64,
+    pub annual_total: f64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CancelCandidates {
+    /// Subscriptions without a tagged value, most expensive first.
+    pub candidates: Vec<Subscription>,
+    /// Yearly cost of all candidates together.
+    pub annual_savings: f64,
+}
+
+/// A record flagged by the data-quality report.
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct QualityFinding {
+    pub id: i64,
+    pub month_id: Option<i64>,
+    pub label: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DataQualityReport {
+    /// Items recorded with an amount of zero.
+    pub zero_amount_items: Vec<QualityFinding>,
+    /// Categories without any spending in the last six months.
+    pub idle_categories: Vec<QualityFinding>,
+    /// Monthly budgets whose category no longer exists.
+    pub orphaned_budgets: Vec<QualityFinding>,
+    pub months_without_income: Vec<QualityFinding>,
+    /// Fixed expenses repeating the label, amount and billing period of an earlier one.
+    pub duplicate_fixed_expenses: Vec<QualityFinding>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct AuthEvent {
+    pub id: i64,
+    /// `login_succeeded`, `login_failed` or `login_blocked`
+    pub kind: String,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Savings balances recorded when a month was closed.
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct WealthSnapshot {
+    pub month_id: i64,
+    pub year: i32,
+    pub month: i32,
+    pub savings: f64,
+    pub retirement_savings: f64,
+    pub net_worth: f64,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// What a saved report contains.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ReportSpec {
+    /// `week` or `month`: the last complete one before the run.
+    pub period: String,
+    /// Only include these categories. Empty includes all of them.
+    #[serde(default)]
+    pub category_ids: Vec<i64>,
+    /// `csv`, `json`, or `backup`: a zip of the full account export and the PDF of
+    /// each month in the period.
+    pub format: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SavedReport {
+    pub id: i64,
+    pub name: String,
+    pub spec: ReportSpec,
+    /// `weekly` or `monthly`, or `None` to run on demand only.
+    pub schedule: Option<String>,
+    /// `email`, `webhook`, `webdav`, `s3`, `dropbox` or `google_drive`, or `None` to only
+    /// keep the file for download.
+    pub delivery: Option<String>,
+    /// Email address, webhook URL, WebDAV folder or S3 bucket the report is delivered to,
+    /// with any password or secret key shown as `********`.
+    pub target: Option<String>,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A cloud drive the user has connected.
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct CloudConnection {
+    /// `dropbox` or `google_drive`.
+    pub provider: String,
+    /// Dropbox path, or name of the Google Drive folder, that files are uploaded to.
+    pub folder: String,
+    /// Whether closed months' PDFs are uploaded too, not only reports delivered here.
+    pub mirror_snapshots: bool,
+    pub last_upload_at: Option<DateTime<Utc>>,
+    /// Why the last upload failed. Cleared by the next successful one.
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A file generated by a report run.
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct ReportArtifact {
+    pub id: i64,
+    pub report_id: i64,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub filename: String,
+    pub content_type: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct Job {
+    pub id: i64,
+    /// What the job does, e.g. `month_pdf`
+    pub kind: String,
+    /// `queued`, `running`, `done` or `failed`
+    pub status: String,
+    pub attempts: i64,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct Insight {
+    pub id: i64,
+    pub kind: String,
+    pub message: String,
+    pub is_read: bool,
+    pub is_dismissed: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct InsightsResponse {
+    pub health_score: i64,
+    pub insights: Vec<Insight>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct ActivityEntry {
+    pub id: i64,
+    pub month_id: Option<i64>,
+    pub actor: String,
+    pub entity_type: String,
+    pub entity_id: i64,
+    pub action: String,
+    pub summary: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ActivityPage {
+    pub entries: Vec<ActivityEntry>,
+    pub page: i64,
+    pub per_page: i64,
+    pub total: i64,
+}
+
+/// Something the user plans to buy, turned into an item once purchased.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct WishlistEntry {
+    pub id: i64,
+    pub description: String,
+    pub estimated_cost: f64,
+    /// From 1 (buy first) to 5 (can wait).
+    pub priority: i64,
+    /// Category the purchase is planned against.
+    pub category_id: Option<i64>,
+    pub category_label: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Share of an item that someone without an account owes back.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct ItemSplit {
+    pub id: i64,
+    pub item_id: i64,
+    pub person: String,
+    pub amount: f64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A split that hasn't been fully repaid yet.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct IouEntry {
+    pub split_id: i64,
+    pub item_id: i64,
+    pub month_id: i64,
+    pub description: String,
+    pub spent_on: NaiveDate,
+    pub amount: f64,
+    /// Sum of the income entries recorded as repayments.
+    pub repaid: f64,
+    pub outstanding: f64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PersonIou {
+    pub person: String,
+    pub outstanding: f64,
+    pub entries: Vec<IouEntry>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct IouReport {
+    /// People who still owe something, largest amount first.
+    pub people: Vec<PersonIou>,
+    pub total_outstanding: f64,
+}
+
+/// Reimbursable items that haven't been paid back yet.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ReimbursementsReport {
+    /// Oldest first.
+    pub items: Vec<ItemWithCategory>,
+    pub total_pending: f64,
+    pub total_submitted: f64,
+    pub total_outstanding: f64,
+}
+
+/// Parameters a mileage or per-diem item's amount was calculated from.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct ItemCalculation {
+    /// `mileage` or `per_diem`.
+    pub kind: String,
+    /// Distance driven, or number of days.
+    pub quantity: f64,
+    /// Amount per unit of distance, or per day.
+    pub rate: f64,
+    pub purpose: Option<String>,
+}
+
+impl ItemCalculation {
+    /// Amount to record, rounded to the cent.
+    pub fn amount(&self) -> f64 {
+        (self.quantity * self.rate * 100.0).round() / 100.0
+    }
+
+    /// Description generated for the item, e.g. `Client visit - mileage 120 x 0.67`.
+    pub fn description(&self) -> String {
+        let calculation = match self.kind.as_str() {
+            "per_diem" => format!("per diem {} days x {:.2}", self.quantity, self.rate),
+            _ => format!("mileage {} x {:.2}", self.quantity, self.rate),
+        };
+        match &self.purpose {
+            Some(purpose) => format!("{purpose} - {calculation}"),
+            None => {
+                let mut chars = calculation.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_uppercase().chain(chars).collect())
+                    .unwrap_or_default()
+            }
+        }
+    }
+}
+
+/// A budget for something that spans months, which items from any month can count against.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct Project {
+    pub id: i64,
+    pub name: String,
+    pub budget: f64,
+    /// Sum of the linked items.
+    pub spent: f64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct ProjectMonth {
+    pub month_id: i64,
+    pub year: i32,
+    pub month: i32,
+    pub spent: f64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ProjectSummary {
+    pub project: Project,
+    /// Budget left, negative once the project is over budget.
+    pub remaining: f64,
+    /// Spending per month, oldest first.
+    pub months: Vec<ProjectMonth>,
+    pub items: Vec<ItemWithCategory>,
+}
+
+/// An invoice sent to a client, expected as income until it's paid.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow, ToSchema)]
+pub struct Invoice {
+    pub id: i64,
+    pub client: String,
+    pub amount: f64,
+    pub issued_on: NaiveDate,
+    pub due_on: NaiveDate,
+    /// `sent`, `paid` or `cancelled`.
+    pub status: String,
+    pub paid_on: Option<NaiveDate>,
+    /// Income entry recorded when the invoice was paid.
+    pub income_id: Option<i64>,
+    /// Still unpaid after the due date.
+    pub overdue: bool,
+}
+
+/// Tax included in items over a year or a month, for the user's tax records.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TaxSummary {
+    pub year: i32,
+    pub month: Option<i32>,
+    /// Amounts paid, tax included.
+    pub gross: f64,
+    pub tax: f64,
+    pub net: f64,
+    /// Totals per tax rate, lowest rate first.
+    pub rates: Vec<TaxRateTotal>,
+    /// Totals per month, in calendar order.
+    pub months: Vec<TaxMonthTotal>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TaxRateTotal {
+    pub rate: f64,
+    pub gross: f64,
+    pub tax: f64,
+    pub net: f64,
+    pub items: i64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TaxMonthTotal {
+    pub month: i32,
+    pub gross: f64,
+    pub tax: f64,
+    pub net: f64,
+}
+
+/// Cash withdrawn into the wallet against the cash spending recorded as items.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CashReport {
+    pub withdrawn: f64,
+    pub itemized: f64,
+    /// Withdrawn but not itemized: still in the wallet, or spent without a record.
+    pub untracked: f64,
+    /// Most recent month first.
+    pub months: Vec<CashMonth>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct CashMonth {
+    pub month_id: i64,
+    pub year: i32,
+    pub month: i32,
+    pub withdrawn: f64,
+    pub itemized: f64,
+    pub untracked: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expense(amount: f64, billing_period: &str, payment_month: Option<i32>) -> FixedExpense {
+        FixedExpense {
+            id: 1,
+            user_id: 1,
+            label: "Insurance".to_string(),
+            amount,
+            billing_period: billing_period.to_string(),
+            payment_month,
+            payment_day: None,
+            payment_roll: None,
+        }
+    }
+
+    #[test]
+    fn test_monthly_amount() {
+        assert_eq!(expense(90.0, "monthly", None).monthly_amount(), 90.0);
+        assert_eq!(expense(90.0, "quarterly", Some(2)).monthly_amount(), 30.0);
+        assert_eq!(expense(1200.0, "yearly", Some(6)).monthly_amount(), 100.0);
+    }
+
+    #[test]
+    fn test_is_due_in() {
+        let quarterly = expense(90.0, "quarterly", Some(11));
+        let due: Vec<i32> = (1..=12).filter(|m| quarterly.is_due_in(*m)).collect();
+        assert_eq!(due, vec![2, 5, 8, 11]);
+
+        let yearly = expense(1200.0, "yearly", Some(6));
+        assert!(yearly.is_due_in(6));
+        assert!(!yearly.is_due_in(7));
+        assert!(expense(90.0, "monthly", None).is_due_in(7));
+    }
+
+    #[test]
+    fn test_included_tax() {
+        assert_eq!(included_tax(120.0, 20.0), 20.0);
+        assert_eq!(included_tax(10.0, 7.7), 0.71);
+        assert_eq!(included_tax(50.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn test_item_calculation() {
+        let mileage = ItemCalculation {
+            kind: "mileage".to_string(),
+            quantity: 123.4,
+            rate: 0.67,
+            purpose: Some("Client visit".to_string()),
+        };
+        assert_eq!(mileage.amount(), 82.68);
+        assert_eq!(mileage.description(), "Client visit - mileage 123.4 x 0.67");
+
+        let per_diem = ItemCalculation {
+            kind: "per_diem".to_string(),
+            quantity: 3.0,
+            rate: 45.0,
+            purpose: None,
+        };
+        assert_eq!(per_diem.amount(), 135.0);
+        assert_eq!(per_diem.description(), "Per diem 3 days x 45.00");
+    }
+}
@@ -0,0 +1,572 @@
+use printpdf::*;
+use qrcode::QrCode;
+use std::io::BufWriter;
+
+use crate::format::MoneyFormat;
+use crate::i18n::{Locale, Text};
+use crate::models::MonthSummary;
+
+pub fn generate_pdf(
+    summary: &MonthSummary,
+    money: &MoneyFormat,
+    locale: Locale,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    render(summary, money, locale, None)
+}
+
+/// Like `generate_pdf`, with a line pointing at `verify_url` where the report's
+/// signature can be checked. Absolute URLs are also drawn as a QR code.
+pub fn generate_verifiable_pdf(
+    summary: &MonthSummary,
+    money: &MoneyFormat,
+    locale: Locale,
+    verify_url: &str,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    render(summary, money, locale, Some(verify_url))
+}
+
+fn render(
+    summary: &MonthSummary,
+    money: &MoneyFormat,
+    locale: Locale,
+    verify_url: Option<&str>,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let amount = |text: Text, value: f64| locale.render(text, &[("amount", &money.format(value))]);
+    let title = locale.render(
+        Text::ReportTitle,
+        &[
+            ("month", &summary.month.month.to_string()),
+            ("year", &summary.month.year.to_string()),
+        ],
+    );
+    let (doc, page1, layer1) = PdfDocument::new(&title, Mm(210.0), Mm(297.0), "Layer 1");
+
+    let layer = doc.get_page(page1).get_layer(layer1);
+    let font = doc.add_builtin_font(BuiltinFont::Helvetica)?;
+    let font_bold = doc.add_builtin_font(BuiltinFont::HelveticaBold)?;
+
+    let mut y = 270.0;
+    let left_margin = 20.0;
+    let line_height = 6.0;
+
+    if let Some(url) = verify_url {
+        let text = locale.render(Text::ReportVerify, &[("url", url)]);
+        layer.use_text(&text, 7.0, Mm(left_margin), Mm(290.0), &font);
+        if url.contains("://") {
+            draw_qr(&layer, url, 170.0, 262.0, 25.0)?;
+        }
+    }
+
+    layer.use_text(&title, 16.0, Mm(left_margin), Mm(y), &font_bold);
+    y -= line_height * 2.0;
+
+    if let Some(digest) = &summary.digest {
+        let change = |text: Text, value: f64| {
+            let sign = if value > 0.0 { "+" } else { "" };
+            locale.render(
+                text,
+                &[("amount", &format!("{sign}{}", money.format(value)))],
+            )
+        };
+        let mut lines = Vec::new();
+        if let Some(value) = digest.savings_change {
+            lines.push(change(Text::ReportSavingsChange, value));
+        }
+        if let Some(value) = digest.net_worth_change {
+            lines.push(change(Text::ReportNetWorthChange, value));
+        }
+        if let (Some(category), Some(value)) =
+            (&digest.top_overspent_category, digest.top_overspent_by)
+        {
+            lines.push(locale.render(
+                Text::ReportTopOverspent,
+                &[("category", category), ("amount", &money.format(value))],
+            ));
+        }
+        if let (Some(description), Some(value)) =
+            (&digest.biggest_purchase, digest.biggest_purchase_amount)
+        {
+            lines.push(locale.render(
+                Text::ReportBiggestPurchase,
+                &[
+                    ("description", description),
+                    ("amount", &money.format(value)),
+                ],
+            ));
+        }
+
+        if !lines.is_empty() {
+            layer.use_text(
+                locale.text(Text::ReportDigest),
+                12.0,
+                Mm(left_margin),
+                Mm(y),
+                &font_bold,
+            );
+            y -= line_height;
+            for line in &lines {
+                layer.use_text(format!("  {line}"), 10.0, Mm(left_margin), Mm(y), &font);
+                y -= line_height;
+            }
+            y -= line_height;
+        }
+    }
+
+    layer.use_text(
+        locale.text(Text::ReportIncome),
+        12.0,
+        Mm(left_margin),
+        Mm(y),
+        &font_bold,
+    );
+    y -= line_height;
+
+    for entry in &summary.income_entries {
+        let text = format!("  {} - {}", entry.label, money.format(entry.amount));
+        layer.use_text(&text, 10.0, Mm(left_margin), Mm(y), &font);
+        y -= line_height;
+    }
+
+    let total_income_text = amount(Text::ReportTotalIncome, summary.total_income);
+    layer.use_text(&total_income_text, 10.0, Mm(left_margin), Mm(y), &font_bold);
+    y -= line_height * 2.0;
+
+    layer.use_text(
+        locale.text(Text::ReportFixedExpenses),
+        12.0,
+        Mm(left_margin),
+        Mm(y),
+        &font_bold,
+    );
+    y -= line_height;
+
+    for expense in &summary.fixed_expenses {
+        let text = format!(
+            "  {} - {}",
+            expense.label,
+            money.format(expense.monthly_amount())
+        );
+        layer.use_text(&text, 10.0, Mm(left_margin), Mm(y), &font);
+        y -= line_height;
+    }
+
+    let total_fixed_text = amount(Text::ReportTotalFixed, summary.total_fixed);
+    layer.use_text(&total_fixed_text, 10.0, Mm(left_margin), Mm(y), &font_bold);
+    y -= line_height * 2.0;
+
+    layer.use_text(
+        locale.text(Text::ReportBudgetVsActual),
+        12.0,
+        Mm(left_margin),
+        Mm(y),
+        &font_bold,
+    );
+    y -= line_height;
+
+    for budget in &summary.budgets {
+        let status = if budget.tracking_only {
+            locale.text(Text::ReportTrackingOnly).to_string()
+        } else if budget.spent_amount > budget.allocated_amount {
+            amount(
+                Text::ReportOverBy,
+                budget.spent_amount - budget.allocated_amount,
+            )
+        } else {
+            amount(
+                Text::ReportLeft,
+                budget.allocated_amount - budget.spent_amount,
+            )
+        };
+
+        let text = format!(
+            "  {}: {} / {} ({})",
+            budget.category_label,
+            money.format(budget.spent_amount),
+            money.format(budget.allocated_amount),
+            status
+        );
+        layer.use_text(&text, 10.0, Mm(left_margin), Mm(y), &font);
+        y -= line_height;
+
+        if let Some(review) = &budget.review {
+            let mut text = format!(
+                "    {}",
+                locale.render(
+                    Text::ReportReview,
+                    &[("rating", &review.rating.to_string())]
+                )
+            );
+            if let Some(note) = review.note.as_deref().filter(|n| !n.is_empty()) {
+                text.push_str(&format!(" - {note}"));
+            }
+            layer.use_text(&text, 9.0, Mm(left_margin), Mm(y), &font);
+            y -= line_height;
+        }
+    }
+
+    y -= line_height;
+
+    layer.use_text(
+        locale.text(Text::ReportSpendingItems),
+        12.0,
+        Mm(left_margin),
+        Mm(y),
+        &font_bold,
+    );
+    y -= line_height;
+
+    for item in &summary.items {
+        if y < 20.0 {
+            break;
+        }
+        let text = format!(
+            "  {} - {} - {} ({})",
+            item.spent_on,
+            item.description,
+            money.format(item.amount),
+            item.category_label
+        );
+        layer.use_text(&text, 9.0, Mm(left_margin), Mm(y), &font);
+        y -= line_height;
+        if let Some(calculation) = &item.calculation {
+            let text = if calculation.kind == "per_diem" {
+                Text::ReportPerDiem
+            } else {
+                Text::ReportMileage
+            };
+            let detail = locale.render(
+                text,
+                &[
+                    ("quantity", &calculation.quantity.to_string()),
+                    ("rate", &money.format(calculation.rate)),
+                ],
+            );
+            layer.use_text(
+                format!("      {detail}"),
+                8.0,
+                Mm(left_margin),
+                Mm(y),
+                &font,
+            );
+            y -= line_height;
+        }
+        if let (Some(rate), Some(tax)) = (item.tax_rate, item.tax_amount) {
+            let detail = locale.render(
+                Text::ReportItemTax,
+                &[("rate", &rate.to_string()), ("amount", &money.format(tax))],
+            );
+            layer.use_text(
+                format!("      {detail}"),
+                8.0,
+                Mm(left_margin),
+                Mm(y),
+                &font,
+            );
+            y -= line_height;
+        }
+    }
+
+    y -= line_height;
+
+    layer.use_text(
+        locale.text(Text::ReportSummary),
+        12.0,
+        Mm(left_margin),
+        Mm(y),
+        &font_bold,
+    );
+    y -= line_height;
+
+    let total_spent_text = amount(Text::ReportTotalSpent, summary.total_spent);
+    layer.use_text(&total_spent_text, 10.0, Mm(left_margin), Mm(y), &font);
+    y -= line_height;
+
+    if summary.tracked_spent > 0.0 {
+        let tracked_text = amount(Text::ReportTotalTracked, summary.tracked_spent);
+        layer.use_text(&tracked_text, 10.0, Mm(left_margin), Mm(y), &font);
+        y -= line_height;
+    }
+
+    let taxed: Vec<f64> = summary.items.iter().filter_map(|i| i.tax_amount).collect();
+    if !taxed.is_empty() {
+        let total_tax_text = amount(Text::ReportTotalTax, taxed.iter().sum());
+        layer.use_text(&total_tax_text, 10.0, Mm(left_margin), Mm(y), &font);
+        y -= line_height;
+    }
+
+    let no_spend_text = locale.render(
+        Text::ReportNoSpendDays,
+        &[("days", &summary.no_spend_days.to_string())],
+    );
+    layer.use_text(&no_spend_text, 10.0, Mm(left_margin), Mm(y), &font);
+    y -= line_height;
+
+    let remaining_text = if summary.remaining >= 0.0 {
+        amount(Text::ReportRemaining, summary.remaining)
+    } else {
+        amount(Text::ReportDeficit, summary.remaining)
+    };
+
+    layer.use_text(&remaining_text, 10.0, Mm(left_margin), Mm(y), &font_bold);
+
+    if !summary.close_checklist.is_empty() {
+        y -= line_height * 2.0;
+        layer.use_text(
+            locale.text(Text::ReportCloseChecklist),
+            12.0,
+            Mm(left_margin),
+            Mm(y),
+            &font_bold,
+        );
+        for entry in &summary.close_checklist {
+            y -= line_height;
+            let text = if entry.acknowledged {
+                Text::ReportChecklistDone
+            } else {
+                Text::ReportChecklistSkipped
+            };
+            let text = locale.render(text, &[("label", &entry.label)]);
+            layer.use_text(format!("  {text}"), 10.0, Mm(left_margin), Mm(y), &font);
+        }
+    }
+
+    let mut buffer = BufWriter::new(Vec::new());
+    doc.save(&mut buffer)?;
+    Ok(buffer.into_inner()?)
+}
+
+/// Draws `data` as a QR code `size` mm wide, with its lower left corner at `x`, `y`.
+fn draw_qr(
+    layer: &PdfLayerReference,
+    data: &str,
+    x: f32,
+    y: f32,
+    size: f32,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let code = QrCode::new(data)?;
+    let width = code.width();
+    let module = size / width as f32;
+
+    layer.set_fill_color(Color::Greyscale(Greyscale::new(0.0, None)));
+    for (i, color) in code.to_colors().into_iter().enumerate() {
+        if color != qrcode::Color::Dark {
+            continue;
+        }
+        let left = x + (i % width) as f32 * module;
+        let top = y + size - (i / width) as f32 * module;
+        layer.add_rect(Rect::new(
+            Mm(left),
+            Mm(top - module),
+            Mm(left + module),
+            Mm(top),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        BudgetReview, ChecklistAcknowledgement, FixedExpense, IncomeEntry, ItemWithCategory, Month,
+        MonthDigest, MonthMetrics, MonthlyBudgetWithCategory,
+    };
+    use chrono::NaiveDate;
+
+    fn create_test_summary() -> MonthSummary {
+        MonthSummary {
+            month: Month {
+                id: 1,
+                user_id: 1,
+                year: 2024,
+                month: 6,
+                is_closed: false,
+                closed_at: None,
+            },
+            income_entries: vec![IncomeEntry {
+                id: 1,
+                month_id: 1,
+                label: "Salary".to_string(),
+                amount: 5000.0,
+                uuid: None,
+            }],
+            fixed_expenses: vec![FixedExpense {
+                id: 1,
+                user_id: 1,
+                label: "Rent".to_string(),
+                amount: 1500.0,
+                billing_period: "monthly".to_string(),
+                payment_month: None,
+                payment_day: None,
+                payment_roll: None,
+            }],
+            budgets: vec![MonthlyBudgetWithCategory {
+                id: 1,
+                month_id: 1,
+                category_id: 1,
+                category_label: "Food".to_string(),
+                allocated_amount: 500.0,
+                spent_amount: 300.0,
+                available: 200.0,
+                committed: 0.0,
+                free: 200.0,
+                earmarks: vec![],
+                tracking_only: false,
+                review: Some(BudgetReview {
+                    budget_id: 1,
+                    rating: 4,
+                    note: Some("Cooked at home more".to_string()),
+                    updated_at: chrono::Utc::now(),
+                }),
+            }],
+            items: vec![ItemWithCategory {
+                id: 1,
+                month_id: 1,
+                category_id: 1,
+                category_label: "Food".to_string(),
+                description: "Groceries".to_string(),
+                amount: 150.0,
+                spent_on: NaiveDate::from_ymd_opt(2024, 6, 15).unwrap(),
+                savings_destination: "none".to_string(),
+                reimbursement_status: None,
+                tax_rate: None,
+                tax_amount: None,
+                paid_in_cash: false,
+                uuid: None,
+                client: None,
+                billable: false,
+                calculation: None,
+            }],
+            total_income: 5000.0,
+            total_fixed: 1500.0,
+            fixed_due: 1500.0,
+            total_budgeted: 500.0,
+            to_be_budgeted: 3000.0,
+            total_spent: 300.0,
+            tracked_spent: 0.0,
+            remaining: 3200.0,
+            no_spend_days: 29,
+            metrics: MonthMetrics {
+                savings_rate: Some(0.64),
+                fixed_cost_ratio: Some(0.3),
+                discretionary_per_day: 10.0,
+            },
+            pdf_sha256: None,
+            pdf_sync_status: None,
+            close_checklist: vec![
+                ChecklistAcknowledgement {
+                    label: "Paid the credit card".to_string(),
+                    acknowledged: true,
+                },
+                ChecklistAcknowledgement {
+                    label: "Updated savings balance".to_string(),
+                    acknowledged: false,
+                },
+            ],
+            digest: Some(MonthDigest {
+                savings_change: Some(250.0),
+                net_worth_change: Some(-120.5),
+                top_overspent_category: Some("Food".to_string()),
+                top_overspent_by: Some(42.0),
+                biggest_purchase: Some("Groceries".to_string()),
+                biggest_purchase_amount: Some(150.0),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_generate_pdf_basic() {
+        let summary = create_test_summary();
+        let result = generate_pdf(&summary, &MoneyFormat::default(), Locale::En);
+
+        assert!(result.is_ok());
+        let pdf_data = result.unwrap();
+
+        // PDF should have content
+        assert!(!pdf_data.is_empty());
+
+        // PDF should start with %PDF header
+        assert!(pdf_data.starts_with(b"%PDF"));
+    }
+
+    #[test]
+    fn test_generate_verifiable_pdf() {
+        let summary = create_test_summary();
+        let plain = generate_pdf(&summary, &MoneyFormat::default(), Locale::En).unwrap();
+        let verifiable = generate_verifiable_pdf(
+            &summary,
+            &MoneyFormat::default(),
+            Locale::En,
+            "https://payme.example.com/api/v1/pdf-signatures/abc",
+        )
+        .unwrap();
+
+        assert!(verifiable.starts_with(b"%PDF"));
+        assert!(verifiable.len() > plain.len());
+    }
+
+    #[test]
+    fn test_generate_pdf_empty_summary() {
+        let summary = MonthSummary {
+            month: Month {
+                id: 1,
+                user_id: 1,
+                year: 2024,
+                month: 6,
+                is_closed: false,
+                closed_at: None,
+            },
+            income_entries: vec![],
+            fixed_expenses: vec![],
+            budgets: vec![],
+            items: vec![],
+            total_income: 0.0,
+            total_fixed: 0.0,
+            fixed_due: 0.0,
+            total_budgeted: 0.0,
+            to_be_budgeted: 0.0,
+            total_spent: 0.0,
+            tracked_spent: 0.0,
+            remaining: 0.0,
+            no_spend_days: 0,
+            metrics: MonthMetrics {
+                savings_rate: None,
+                fixed_cost_ratio: None,
+                discretionary_per_day: 0.0,
+            },
+            pdf_sha256: None,
+            pdf_sync_status: None,
+            close_checklist: Vec::new(),
+            digest: None,
+        };
+
+        let result = generate_pdf(&summary, &MoneyFormat::default(), Locale::En);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_generate_pdf_with_deficit() {
+        let mut summary = create_test_summary();
+        summary.remaining = -500.0;
+
+        let result = generate_pdf(&summary, &MoneyFormat::default(), Locale::En);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_generate_pdf_over_budget() {
+        let mut summary = create_test_summary();
+        summary.budgets[0].spent_amount = 600.0; // Over the 500 allocated
+
+        let result = generate_pdf(&summary, &MoneyFormat::default(), Locale::En);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_generate_pdf_localized_currency() {
+        let summary = create_test_summary();
+        let result = generate_pdf(&summary, &MoneyFormat::new("de-DE", "EUR"), Locale::De);
+        assert!(result.is_ok());
+    }
+}
@@ -0,0 +1,188 @@
+//! Budget periods other than the calendar month. Budgets, income and fixed expenses are
+//! still kept per month, so a week gets the share of each month it overlaps, while its
+//! spending is the items actually dated inside it.
+
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Duration, Months, NaiveDate};
+
+use crate::models::{MonthSummary, PeriodCategory, PeriodSummary};
+
+/// How a user's budget is divided.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeriodType {
+    Monthly,
+    Weekly,
+    Biweekly,
+}
+
+impl PeriodType {
+    pub fn parse(period_type: &str) -> Option<Self> {
+        match period_type {
+            "monthly" => Some(PeriodType::Monthly),
+            "weekly" => Some(PeriodType::Weekly),
+            "biweekly" => Some(PeriodType::Biweekly),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PeriodType::Monthly => "monthly",
+            PeriodType::Weekly => "weekly",
+            PeriodType::Biweekly => "biweekly",
+        }
+    }
+}
+
+/// First and last day of the period `date` falls in. Weekly and biweekly periods are
+/// counted from `starts_on`, the first day of any one of them.
+pub fn period_containing(
+    period_type: PeriodType,
+    starts_on: NaiveDate,
+    date: NaiveDate,
+) -> (NaiveDate, NaiveDate) {
+    let length = match period_type {
+        PeriodType::Monthly => {
+            let first = date.with_day(1).unwrap_or(date);
+            let last = (first + Months::new(1)).pred_opt().unwrap_or(first);
+            return (first, last);
+        }
+        PeriodType::Weekly => 7,
+        PeriodType::Biweekly => 14,
+    };
+    let offset = (date - starts_on).num_days().rem_euclid(length);
+    let start = date - Duration::days(offset);
+    (start, start + Duration::days(length - 1))
+}
+
+/// The `(year, month)` of every calendar month between `start` and `end`, in order.
+pub fn months_spanned(start: NaiveDate, end: NaiveDate) -> Vec<(i32, i32)> {
+    let mut months = Vec::new();
+    let mut first = start.with_day(1).unwrap_or(start);
+    while first <= end {
+        months.push((first.year(), first.month() as i32));
+        first = first + Months::new(1);
+    }
+    months
+}
+
+/// Fraction of the month's days that fall between `start` and `end`.
+pub fn month_share(year: i32, month: i32, start: NaiveDate, end: NaiveDate) -> f64 {
+    let Some(first) = NaiveDate::from_ymd_opt(year, month as u32, 1) else {
+        return 0.0;
+    };
+    let last = (first + Months::new(1)).pred_opt().unwrap_or(first);
+    let overlap = (end.min(last) - start.max(first)).num_days() + 1;
+    let days = (last - first).num_days() + 1;
+    overlap.max(0) as f64 / days as f64
+}
+
+/// Sums the summaries of the months a period spans into the period's own: each month's
+/// income, fixed expenses and allocations weighted by its share of days in the period,
+/// and the items dated inside it as spending.
+pub fn period_summary(
+    period_type: PeriodType,
+    start: NaiveDate,
+    end: NaiveDate,
+    months: &[MonthSummary],
+) -> PeriodSummary {
+    let mut total_income = 0.0;
+    let mut total_fixed = 0.0;
+    let mut categories: BTreeMap<i64, PeriodCategory> = BTreeMap::new();
+
+    for summary in months {
+        let share = month_share(summary.month.year, summary.month.month, start, end);
+        total_income += summary.total_income * share;
+        total_fixed += summary.total_fixed * share;
+        for budget in &summary.budgets {
+            let category = categories
+                .entry(budget.category_id)
+                .or_insert_with(|| PeriodCategory {
+                    category_id: budget.category_id,
+                    category_label: budget.category_label.clone(),
+                    allocated_amount: 0.0,
+                    spent_amount: 0.0,
+                    available: 0.0,
+                    tracking_only: budget.tracking_only,
+                });
+            category.allocated_amount += budget.allocated_amount * share;
+        }
+        for item in &summary.items {
+            if item.spent_on < start || item.spent_on > end || !item.counts_as_spending(false) {
+                continue;
+            }
+            if let Some(category) = categories.get_mut(&item.category_id) {
+                category.spent_amount += item.amount;
+            }
+        }
+    }
+
+    let categories: Vec<PeriodCategory> = categories
+        .into_values()
+        .map(|mut category| {
+            category.available = if category.tracking_only {
+                category.allocated_amount
+            } else {
+                category.allocated_amount - category.spent_amount
+            };
+            category
+        })
+        .collect();
+    let counted = categories.iter().filter(|c| !c.tracking_only);
+    let total_budgeted = counted.clone().map(|c| c.allocated_amount).sum();
+    let total_spent = counted.map(|c| c.spent_amount).sum();
+
+    PeriodSummary {
+        period_type: period_type.as_str().to_string(),
+        start,
+        end,
+        month_ids: months.iter().map(|m| m.month.id).collect(),
+        total_income,
+        total_fixed,
+        total_budgeted,
+        total_spent,
+        remaining: total_income - total_fixed - total_spent,
+        categories,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_period_containing() {
+        let monday = date(2024, 1, 1);
+        assert_eq!(
+            period_containing(PeriodType::Weekly, monday, date(2024, 6, 13)),
+            (date(2024, 6, 10), date(2024, 6, 16))
+        );
+        assert_eq!(
+            period_containing(PeriodType::Biweekly, monday, date(2024, 6, 13)),
+            (date(2024, 6, 3), date(2024, 6, 16))
+        );
+        // Dates before the anchor fall in earlier periods of the same rhythm
+        assert_eq!(
+            period_containing(PeriodType::Biweekly, monday, date(2023, 12, 20)),
+            (date(2023, 12, 18), date(2023, 12, 31))
+        );
+        assert_eq!(
+            period_containing(PeriodType::Monthly, monday, date(2024, 2, 13)),
+            (date(2024, 2, 1), date(2024, 2, 29))
+        );
+    }
+
+    #[test]
+    fn test_month_share() {
+        let (start, end) = (date(2024, 4, 29), date(2024, 5, 5));
+        assert_eq!(months_spanned(start, end), vec![(2024, 4), (2024, 5)]);
+        assert_eq!(month_share(2024, 4, start, end), 2.0 / 30.0);
+        assert_eq!(month_share(2024, 5, start, end), 5.0 / 31.0);
+        assert_eq!(month_share(2024, 6, start, end), 0.0);
+    }
+}
@@ -0,0 +1,92 @@
+/// Tracked months of history needed before a category can be called seasonal.
+pub const MIN_HISTORY_MONTHS: usize = 12;
+/// A calendar month this far from the category's average (as a share of it) makes
+/// the category seasonal.
+const SEASONAL_DEVIATION: f64 = 0.5;
+
+/// Expected spending for each calendar month, January first, when a category's
+/// spending follows the seasons. `history` holds one entry per tracked month: the
+/// calendar month (1-12) and what was spent on the category, zero included.
+///
+/// Returns `None` with less than [`MIN_HISTORY_MONTHS`] of history or when every
+/// calendar month stays close to the average. Calendar months missing from the
+/// history are expected to match the average.
+pub fn seasonal_expectations(history: &[(u32, f64)]) -> Option<Vec<f64>> {
+    if history.len() < MIN_HISTORY_MONTHS {
+        return None;
+    }
+    let average = history.iter().map(|(_, amount)| amount).sum::<f64>() / history.len() as f64;
+    if average <= 0.0 {
+        return None;
+    }
+
+    let mut totals = [0.0; 12];
+    let mut counts = [0usize; 12];
+    for &(month, amount) in history {
+        if let Some(index) = (month as usize).checked_sub(1).filter(|i| *i < 12) {
+            totals[index] += amount;
+            counts[index] += 1;
+        }
+    }
+    let expected: Vec<f64> = totals
+        .iter()
+        .zip(counts)
+        .map(|(total, count)| {
+            if count == 0 {
+                average
+            } else {
+                total / count as f64
+            }
+        })
+        .collect();
+
+    expected
+        .iter()
+        .any(|e| (e - average).abs() >= average * SEASONAL_DEVIATION)
+        .then_some(expected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn year_of(amounts: [f64; 12]) -> Vec<(u32, f64)> {
+        (1..=12).zip(amounts).collect()
+    }
+
+    #[test]
+    fn test_heating_is_seasonal() {
+        let history = year_of([
+            200.0, 180.0, 120.0, 60.0, 20.0, 0.0, 0.0, 0.0, 20.0, 80.0, 150.0, 210.0,
+        ]);
+        let expected = seasonal_expectations(&history).unwrap();
+        assert_eq!(expected[0], 200.0);
+        assert_eq!(expected[6], 0.0);
+    }
+
+    #[test]
+    fn test_steady_spending_is_not_seasonal() {
+        let history = year_of([
+            400.0, 420.0, 390.0, 410.0, 400.0, 380.0, 430.0, 400.0, 410.0, 390.0, 400.0, 420.0,
+        ]);
+        assert_eq!(seasonal_expectations(&history), None);
+    }
+
+    #[test]
+    fn test_short_history_is_not_seasonal() {
+        let history: Vec<(u32, f64)> = (1..=11)
+            .map(|m| (m, if m == 1 { 500.0 } else { 0.0 }))
+            .collect();
+        assert_eq!(seasonal_expectations(&history), None);
+    }
+
+    #[test]
+    fn test_repeated_months_are_averaged() {
+        let mut history = year_of([
+            100.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 300.0,
+        ]);
+        history.push((12, 500.0));
+        let expected = seasonal_expectations(&history).unwrap();
+        assert_eq!(expected[11], 400.0);
+    }
+}
@@ -0,0 +1,94 @@
+use std::collections::HashSet;
+
+use chrono::{Datelike, Duration, NaiveDate};
+
+/// No-spend figures for one calendar month.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MonthStreaks {
+    pub no_spend_days: i64,
+    pub longest_streak: i64,
+}
+
+/// Counts days without spending in a month, stopping at `today` for the current month.
+pub fn month_streaks(
+    year: i32,
+    month: i32,
+    spend_days: &HashSet<NaiveDate>,
+    today: NaiveDate,
+) -> MonthStreaks {
+    let Some(first) = NaiveDate::from_ymd_opt(year, month as u32, 1) else {
+        return MonthStreaks::default();
+    };
+    let mut streaks = MonthStreaks::default();
+    let mut current = 0;
+    let mut day = first;
+
+    while day.month() == first.month() && day <= today {
+        if spend_days.contains(&day) {
+            current = 0;
+        } else {
+            current += 1;
+            streaks.no_spend_days += 1;
+            streaks.longest_streak = streaks.longest_streak.max(current);
+        }
+        day += Duration::days(1);
+    }
+
+    streaks
+}
+
+/// Returns the best run of no-spend days between `start` and `today`, and the run
+/// that is still going today.
+pub fn overall_streaks(
+    start: NaiveDate,
+    spend_days: &HashSet<NaiveDate>,
+    today: NaiveDate,
+) -> (i64, i64) {
+    let mut best = 0;
+    let mut current = 0;
+    let mut day = start;
+
+    while day <= today {
+        if spend_days.contains(&day) {
+            current = 0;
+        } else {
+            current += 1;
+            best = best.max(current);
+        }
+        day += Duration::days(1);
+    }
+
+    (best, current)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 6, day).unwrap()
+    }
+
+    #[test]
+    fn test_month_streaks_past_month() {
+        let spend_days = HashSet::from([date(3), date(10)]);
+        let streaks = month_streaks(2024, 6, &spend_days, date(1) + Duration::days(60));
+        assert_eq!(streaks.no_spend_days, 28);
+        assert_eq!(streaks.longest_streak, 20);
+    }
+
+    #[test]
+    fn test_month_streaks_stop_at_today() {
+        let spend_days = HashSet::from([date(2)]);
+        let streaks = month_streaks(2024, 6, &spend_days, date(5));
+        assert_eq!(streaks.no_spend_days, 4);
+        assert_eq!(streaks.longest_streak, 3);
+    }
+
+    #[test]
+    fn test_overall_streaks() {
+        let spend_days = HashSet::from([date(5), date(8)]);
+        assert_eq!(overall_streaks(date(1), &spend_days, date(10)), (4, 2));
+        assert_eq!(overall_streaks(date(1), &spend_days, date(8)), (4, 0));
+    }
+}
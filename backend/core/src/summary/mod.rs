@@ -0,0 +1,432 @@
+//! Month summaries: a month's entries and budgets with the totals derived from them.
+//! Closed months are summarized from the copies taken when they were closed.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Duration, Months, NaiveDate, Utc};
+use sqlx::SqlitePool;
+
+use crate::models::{
+    BudgetReview, ChecklistAcknowledgement, Earmark, FixedExpense, IncomeEntry, ItemCalculation,
+    ItemWithCategory, Month, MonthDigest, MonthMetrics, MonthSummary, MonthlyBudgetWithCategory,
+};
+use crate::streaks;
+
+/// Everything shown for a month: its entries, budgets with what was spent against
+/// them, and the totals. Reimbursed items are left out of the spent amounts when
+/// `exclude_reimbursed` is set.
+pub async fn month_summary(
+    pool: &SqlitePool,
+    user_id: i64,
+    month_id: i64,
+    exclude_reimbursed: bool,
+) -> Result<MonthSummary, sqlx::Error> {
+    let month: Month = sqlx::query_as(
+        "SELECT id, user_id, year, month, is_closed, closed_at FROM months WHERE id = ?",
+    )
+    .bind(month_id)
+    .fetch_one(pool)
+    .await?;
+
+    let income_entries: Vec<IncomeEntry> =
+        sqlx::query_as("SELECT id, month_id, label, amount FROM income_entries WHERE month_id = ?")
+            .bind(month_id)
+            .fetch_all(pool)
+            .await?;
+
+    let frozen: bool = sqlx::query_scalar("SELECT frozen_at IS NOT NULL FROM months WHERE id = ?")
+        .bind(month_id)
+        .fetch_one(pool)
+        .await?;
+
+    // Closed months report the copies taken at close time
+    let (fixed_expenses, budgets, mut items, tracking_only) = if frozen {
+        frozen_month_data(pool, user_id, month_id).await?
+    } else {
+        live_month_data(pool, user_id, month_id).await?
+    };
+
+    let mut calculations: HashMap<i64, ItemCalculation> = sqlx::query_as::<_, CalculationRow>(
+        r#"
+        SELECT c.item_id, c.kind, c.quantity, c.rate, c.purpose
+        FROM item_calculations c
+        JOIN items i ON c.item_id = i.id
+        WHERE i.month_id = ?
+        "#,
+    )
+    .bind(month_id)
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|row| (row.item_id, row.calculation))
+    .collect();
+    for item in &mut items {
+        item.calculation = calculations.remove(&item.id);
+    }
+
+    let reviews: Vec<BudgetReview> = sqlx::query_as(
+        "SELECT budget_id, rating, note, updated_at FROM budget_reviews WHERE month_id = ?",
+    )
+    .bind(month_id)
+    .fetch_all(pool)
+    .await?;
+
+    let earmarks: Vec<Earmark> = sqlx::query_as(
+        r#"
+        SELECT e.id, e.budget_id, e.label, e.amount, e.item_id, e.created_at
+        FROM budget_earmarks e
+        JOIN monthly_budgets mb ON e.budget_id = mb.id
+        WHERE mb.month_id = ?
+        ORDER BY e.created_at, e.id
+        "#,
+    )
+    .bind(month_id)
+    .fetch_all(pool)
+    .await?;
+
+    let budgets: Vec<MonthlyBudgetWithCategory> = budgets
+        .into_iter()
+        .map(|mut b| {
+            b.spent_amount = items
+                .iter()
+                .filter(|i| {
+                    i.category_id == b.category_id && i.counts_as_spending(exclude_reimbursed)
+                })
+                .map(|i| i.amount)
+                .sum();
+            b.tracking_only = tracking_only.contains(&b.category_id);
+            // Tracking-only spending is itemized without eating into the allocation
+            b.available = if b.tracking_only {
+                b.allocated_amount
+            } else {
+                b.allocated_amount - b.spent_amount
+            };
+            b.earmarks = earmarks
+                .iter()
+                .filter(|e| e.budget_id == b.id)
+                .cloned()
+                .collect();
+            b.committed = b
+                .earmarks
+                .iter()
+                .filter(|e| e.item_id.is_none())
+                .map(|e| e.amount)
+                .sum();
+            b.free = b.available - b.committed;
+            b.review = reviews.iter().find(|r| r.budget_id == b.id).cloned();
+            b
+        })
+        .collect();
+
+    let total_income: f64 = income_entries.iter().map(|i| i.amount).sum();
+    let total_fixed: f64 = fixed_expenses.iter().map(|e| e.monthly_amount()).sum();
+    let fixed_due: f64 = fixed_expenses
+        .iter()
+        .filter(|e| e.is_due_in(month.month))
+        .map(|e| e.amount)
+        .sum();
+    let total_budgeted: f64 = budgets.iter().map(|b| b.allocated_amount).sum();
+    let to_be_budgeted = total_income - total_fixed - total_budgeted;
+    // Only count items as "spent" if they're not being transferred to savings
+    let (tracked, counted): (Vec<&ItemWithCategory>, Vec<&ItemWithCategory>) = items
+        .iter()
+        .filter(|i| i.counts_as_spending(exclude_reimbursed))
+        .partition(|i| tracking_only.contains(&i.category_id));
+    let total_spent: f64 = counted.iter().map(|i| i.amount).sum();
+    let tracked_spent: f64 = tracked.iter().map(|i| i.amount).sum();
+    let remaining = total_income - total_fixed - total_spent;
+    let spend_days: HashSet<NaiveDate> = items
+        .iter()
+        .filter(|i| i.savings_destination == "none" && !tracking_only.contains(&i.category_id))
+        .map(|i| i.spent_on)
+        .collect();
+    let today = Utc::now().date_naive();
+    let no_spend_days =
+        streaks::month_streaks(month.year, month.month, &spend_days, today).no_spend_days;
+    let metrics = month_metrics(
+        total_income,
+        total_fixed,
+        total_spent,
+        days_elapsed(month.year, month.month, today),
+    );
+
+    let (pdf_sha256, pdf_sync_status): (Option<String>, Option<String>) =
+        sqlx::query_as("SELECT sha256, sync_status FROM monthly_snapshots WHERE month_id = ?")
+            .bind(month_id)
+            .fetch_optional(pool)
+            .await?
+            .unwrap_or_default();
+    let close_checklist: Vec<ChecklistAcknowledgement> = sqlx::query_as(
+        "SELECT label, acknowledged FROM closed_month_checklist WHERE month_id = ? ORDER BY position",
+    )
+    .bind(month_id)
+    .fetch_all(pool)
+    .await?;
+    let digest: Option<MonthDigest> = sqlx::query_as(
+        r#"
+        SELECT savings_change, net_worth_change, top_overspent_category, top_overspent_by,
+               biggest_purchase, biggest_purchase_amount
+        FROM month_digests WHERE month_id = ?
+        "#,
+    )
+    .bind(month_id)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(MonthSummary {
+        month,
+        income_entries,
+        fixed_expenses,
+        budgets,
+        items,
+        total_income,
+        total_fixed,
+        fixed_due,
+        total_budgeted,
+        to_be_budgeted,
+        total_spent,
+        tracked_spent,
+        remaining,
+        no_spend_days,
+        metrics,
+        pdf_sha256,
+        pdf_sync_status,
+        close_checklist,
+        digest,
+    })
+}
+
+/// Works out the digest recorded when a month is closed. `wealth` is the user's
+/// `(savings, retirement_savings)` now and `previous` what they were when the previous
+/// month was closed.
+pub fn month_digest(
+    summary: &MonthSummary,
+    wealth: (f64, f64),
+    previous: Option<(f64, f64)>,
+) -> MonthDigest {
+    let overspent = summary
+        .budgets
+        .iter()
+        .filter(|b| !b.tracking_only && b.spent_amount > b.allocated_amount)
+        .max_by(|a, b| {
+            (a.spent_amount - a.allocated_amount).total_cmp(&(b.spent_amount - b.allocated_amount))
+        });
+    let biggest = summary
+        .items
+        .iter()
+        .filter(|i| i.counts_as_spending(false))
+        .max_by(|a, b| a.amount.total_cmp(&b.amount));
+    let round = |amount: f64| (amount * 100.0).round() / 100.0;
+
+    MonthDigest {
+        savings_change: previous.map(|(savings, _)| round(wealth.0 - savings)),
+        net_worth_change: previous
+            .map(|(savings, retirement)| round(wealth.0 + wealth.1 - savings - retirement)),
+        top_overspent_category: overspent.map(|b| b.category_label.clone()),
+        top_overspent_by: overspent.map(|b| round(b.spent_amount - b.allocated_amount)),
+        biggest_purchase: biggest.map(|i| i.description.clone()),
+        biggest_purchase_amount: biggest.map(|i| i.amount),
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct CalculationRow {
+    item_id: i64,
+    #[sqlx(flatten)]
+    calculation: ItemCalculation,
+}
+
+/// Fixed expenses, budgets and items, with the ids of the tracking-only categories.
+type MonthData = (
+    Vec<FixedExpense>,
+    Vec<MonthlyBudgetWithCategory>,
+    Vec<ItemWithCategory>,
+    HashSet<i64>,
+);
+
+async fn live_month_data(
+    pool: &SqlitePool,
+    user_id: i64,
+    month_id: i64,
+) -> Result<MonthData, sqlx::Error> {
+    let fixed_expenses: Vec<FixedExpense> =
+        sqlx::query_as(
+            "SELECT id, user_id, label, amount, billing_period, payment_month, payment_day, payment_roll FROM fixed_expenses WHERE user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+
+    let budgets: Vec<MonthlyBudgetWithCategory> =
+        sqlx::query_as::<_, (i64, i64, i64, String, f64)>(
+            r#"
+        SELECT mb.id, mb.month_id, mb.category_id, bc.label, mb.allocated_amount
+        FROM monthly_budgets mb
+        JOIN budget_categories bc ON mb.category_id = bc.id
+        WHERE mb.month_id = ?
+        "#,
+        )
+        .bind(month_id)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(
+            |(id, month_id, category_id, category_label, allocated_amount)| {
+                MonthlyBudgetWithCategory {
+                    id,
+                    month_id,
+                    category_id,
+                    category_label,
+                    allocated_amount,
+                    spent_amount: 0.0,
+                    available: allocated_amount,
+                    committed: 0.0,
+                    free: allocated_amount,
+                    earmarks: Vec::new(),
+                    tracking_only: false,
+                    review: None,
+                }
+            },
+        )
+        .collect();
+
+    let items: Vec<ItemWithCategory> = sqlx::query_as(
+        r#"
+        SELECT i.id, i.month_id, i.category_id, bc.label as category_label, i.description, i.amount, i.spent_on, i.savings_destination, i.reimbursement_status, i.tax_rate, i.tax_amount, i.paid_in_cash, i.uuid, i.client, i.billable
+        FROM items i
+        JOIN budget_categories bc ON i.category_id = bc.id
+        WHERE i.month_id = ?
+        ORDER BY i.spent_on DESC
+        "#,
+    )
+    .bind(month_id)
+    .fetch_all(pool)
+    .await?;
+
+    let tracking_only: HashSet<i64> = sqlx::query_scalar(
+        "SELECT id FROM budget_categories WHERE user_id = ? AND tracking_only = 1",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .collect();
+
+    Ok((fixed_expenses, budgets, items, tracking_only))
+}
+
+async fn frozen_month_data(
+    pool: &SqlitePool,
+    user_id: i64,
+    month_id: i64,
+) -> Result<MonthData, sqlx::Error> {
+    let fixed_expenses: Vec<FixedExpense> = sqlx::query_as(
+        r#"
+        SELECT fixed_expense_id AS id, ? AS user_id, label, amount, billing_period, payment_month
+        FROM closed_month_fixed_expenses
+        WHERE month_id = ?
+        "#,
+    )
+    .bind(user_id)
+    .bind(month_id)
+    .fetch_all(pool)
+    .await?;
+
+    let budgets: Vec<MonthlyBudgetWithCategory> =
+        sqlx::query_as::<_, (i64, i64, i64, String, f64, bool)>(
+            r#"
+        SELECT budget_id, month_id, category_id, category_label, allocated_amount, tracking_only
+        FROM closed_month_budgets
+        WHERE month_id = ?
+        "#,
+        )
+        .bind(month_id)
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(
+            |(id, month_id, category_id, category_label, allocated_amount, tracking_only)| {
+                MonthlyBudgetWithCategory {
+                    id,
+                    month_id,
+                    category_id,
+                    category_label,
+                    allocated_amount,
+                    spent_amount: 0.0,
+                    available: allocated_amount,
+                    committed: 0.0,
+                    free: allocated_amount,
+                    earmarks: Vec::new(),
+                    tracking_only,
+                    review: None,
+                }
+            },
+        )
+        .collect();
+
+    let items: Vec<ItemWithCategory> = sqlx::query_as(
+        r#"
+        SELECT c.item_id AS id, c.month_id, c.category_id, c.category_label, c.description, c.amount, c.spent_on, c.savings_destination,
+               i.reimbursement_status, c.tax_rate, c.tax_amount,
+               COALESCE(i.paid_in_cash, 0) AS paid_in_cash, i.uuid,
+               i.client, COALESCE(i.billable, 0) AS billable
+        FROM closed_month_items c
+        LEFT JOIN items i ON i.id = c.item_id
+        WHERE c.month_id = ?
+        ORDER BY c.spent_on DESC
+        "#,
+    )
+    .bind(month_id)
+    .fetch_all(pool)
+    .await?;
+
+    // The flag as it was at close time, whether or not the category still exists
+    let tracking_only: HashSet<i64> = sqlx::query_scalar(
+        r#"
+        SELECT category_id FROM closed_month_budgets WHERE month_id = ? AND tracking_only = 1
+        UNION
+        SELECT category_id FROM closed_month_items WHERE month_id = ? AND tracking_only = 1
+        "#,
+    )
+    .bind(month_id)
+    .bind(month_id)
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .collect();
+
+    Ok((fixed_expenses, budgets, items, tracking_only))
+}
+
+fn month_metrics(total_income: f64, total_fixed: f64, total_spent: f64, days: i64) -> MonthMetrics {
+    let ratio = |amount: f64| (total_income > 0.0).then(|| amount / total_income);
+    MonthMetrics {
+        savings_rate: ratio(total_income - total_fixed - total_spent),
+        fixed_cost_ratio: ratio(total_fixed),
+        discretionary_per_day: total_spent / days.max(1) as f64,
+    }
+}
+
+/// Days of the month up to and including `today`; the full length for past months.
+pub fn days_elapsed(year: i32, month: i32, today: NaiveDate) -> i64 {
+    let Some(first) = NaiveDate::from_ymd_opt(year, month as u32, 1) else {
+        return 0;
+    };
+    let last = first
+        .checked_add_months(Months::new(1))
+        .map_or(first, |next| next - Duration::days(1));
+    (last.min(today) - first).num_days() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_days_elapsed() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        assert_eq!(days_elapsed(2024, 3, today), 10);
+        assert_eq!(days_elapsed(2024, 2, today), 29);
+        assert_eq!(days_elapsed(2024, 13, today), 0);
+    }
+}
@@ -0,0 +1,32 @@
+use sqlx::SqliteExecutor;
+
+use crate::middleware::auth::Claims;
+
+/// Records a change made by the authenticated user in the activity log.
+pub async fn record<'e>(
+    executor: impl SqliteExecutor<'e>,
+    claims: &Claims,
+    month_id: Option<i64>,
+    entity_type: &str,
+    entity_id: i64,
+    action: &str,
+    summary: String,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO activity_log (user_id, month_id, actor, entity_type, entity_id, action, summary)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(claims.sub)
+    .bind(month_id)
+    .bind(&claims.username)
+    .bind(entity_type)
+    .bind(entity_id)
+    .bind(action)
+    .bind(summary)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
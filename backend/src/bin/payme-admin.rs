@@ -0,0 +1,110 @@
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::process::ExitCode;
+
+use payme::cli;
+use payme::config::{self, Config};
+use payme::db;
+use payme::error::PaymeError;
+use payme::storage;
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    if matches!(args.as_slice(), [] | ["help" | "-h" | "--help"]) {
+        println!("{}", cli::USAGE);
+        return ExitCode::SUCCESS;
+    }
+    if args == ["openapi"] {
+        println!("{}", cli::openapi_spec());
+        return ExitCode::SUCCESS;
+    }
+
+    let config = Config::from_env();
+    let pool = match db::create_pool(&config.database_url).await {
+        Ok(pool) => pool,
+        Err(e) => {
+            eprintln!("Failed to open {}: {e}", config.database_url);
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Err(e) = db::run_migrations(&pool).await {
+        eprintln!("Failed to run migrations: {e}");
+        return ExitCode::FAILURE;
+    }
+
+    match run(&pool, &args).await {
+        Ok(Some(output)) => {
+            println!("{output}");
+            ExitCode::SUCCESS
+        }
+        Ok(None) => {
+            eprintln!("{}", cli::USAGE);
+            ExitCode::from(2)
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Runs a command and returns what to print, or `None` for unrecognized arguments.
+async fn run(pool: &sqlx::SqlitePool, args: &[&str]) -> Result<Option<String>, PaymeError> {
+    let output = match args {
+        ["create-user", username] => {
+            let id = cli::create_user(pool, username, &read_password()?).await?;
+            format!("Created user {username} with id {id}")
+        }
+        ["reset-password", username] => {
+            cli::reset_password(pool, username, &read_password()?).await?;
+            format!("Password of {username} changed")
+        }
+        ["export-user", username] => {
+            let export = cli::export_user(pool, username).await?;
+            serde_json::to_string_pretty(&export)
+                .map_err(|e| PaymeError::Internal(e.to_string()))?
+        }
+        ["backup", path] => {
+            cli::backup(pool, Path::new(path)).await?;
+            format!("Database copied to {path}")
+        }
+        ["upload-backup"] => {
+            if config::storage_backend() == "database" {
+                return Err(PaymeError::BadRequest(
+                    "Set STORAGE_BACKEND to local or s3 to upload backups".to_string(),
+                ));
+            }
+            let key = cli::upload_backup(pool, storage::backend(pool).as_ref()).await?;
+            format!("Database backed up to {key}")
+        }
+        ["move-files"] => {
+            let moved = storage::move_from_database(pool).await?;
+            format!("Moved {moved} files to {}", config::storage_backend())
+        }
+        ["reopen-month", username, year, month] => {
+            let (Ok(year), Ok(month)) = (year.parse(), month.parse()) else {
+                return Ok(None);
+            };
+            cli::reopen_month(pool, storage::backend(pool).as_ref(), username, year, month).await?;
+            format!("Reopened {year}-{month:02} for {username}")
+        }
+        ["rotate-jwt-keys"] => cli::rotate_jwt_keys(pool).await?,
+        _ => return Ok(None),
+    };
+    Ok(Some(output))
+}
+
+/// Reads the password from the first line of stdin, so it stays out of the shell
+/// history and process list.
+fn read_password() -> Result<String, PaymeError> {
+    eprint!("Password: ");
+    io::stderr().flush().ok();
+    let mut line = String::new();
+    io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(|e| PaymeError::Internal(e.to_string()))?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
@@ -0,0 +1,163 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::error::PaymeError;
+
+/// One change applied to many fixed expenses or categories at once, such as the yearly
+/// rent and utilities increase.
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct BulkEdit {
+    /// Rows whose amount `increase_percent` applies to.
+    #[serde(default)]
+    #[validate(length(max = 500))]
+    pub ids: Vec<i64>,
+    /// Raises each amount by this percentage, rounded to the cent. Negative lowers them.
+    #[validate(range(min = -100.0, max = 1000.0))]
+    pub increase_percent: Option<f64>,
+    /// New labels.
+    #[serde(default)]
+    #[validate(nested)]
+    pub labels: Vec<Relabel>,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct Relabel {
+    pub id: i64,
+    #[validate(length(min = 1, max = 100))]
+    pub label: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct BulkEditResult {
+    /// Nothing was saved; `changes` shows what would have been.
+    pub dry_run: bool,
+    /// In ID order.
+    pub changes: Vec<BulkChange>,
+}
+
+#[derive(Debug, PartialEq, Serialize, ToSchema)]
+pub struct BulkChange {
+    pub id: i64,
+    pub old_label: String,
+    pub label: String,
+    pub old_amount: f64,
+    pub amount: f64,
+}
+
+impl BulkEdit {
+    /// Works out the new label and amount of every row the edit touches, given the
+    /// user's rows as `(id, label, amount)`. `kind` names the rows in errors.
+    pub fn plan(
+        &self,
+        rows: &[(i64, String, f64)],
+        kind: &str,
+    ) -> Result<Vec<BulkChange>, PaymeError> {
+        match (self.increase_percent, self.ids.is_empty()) {
+            (Some(_), true) => {
+                return Err(PaymeError::BadRequest(
+                    "increase_percent needs ids to apply to".to_string(),
+                ))
+            }
+            (None, false) => {
+                return Err(PaymeError::BadRequest(
+                    "ids need an increase_percent".to_string(),
+                ))
+            }
+            (None, true) if self.labels.is_empty() => {
+                return Err(PaymeError::BadRequest("Nothing to change".to_string()))
+            }
+            _ => {}
+        }
+
+        let known: HashMap<i64, (&String, f64)> = rows
+            .iter()
+            .map(|(id, label, amount)| (*id, (label, *amount)))
+            .collect();
+        if let Some(unknown) = self
+            .ids
+            .iter()
+            .chain(self.labels.iter().map(|r| &r.id))
+            .find(|id| !known.contains_key(id))
+        {
+            return Err(PaymeError::BadRequest(format!("Unknown {kind} {unknown}")));
+        }
+
+        let mut changes: Vec<BulkChange> = rows
+            .iter()
+            .filter(|(id, _, _)| self.ids.contains(id) || self.labels.iter().any(|r| r.id == *id))
+            .map(|(id, label, amount)| BulkChange {
+                id: *id,
+                old_label: label.clone(),
+                label: label.clone(),
+                old_amount: *amount,
+                amount: *amount,
+            })
+            .collect();
+        for change in &mut changes {
+            if let (Some(percent), true) = (self.increase_percent, self.ids.contains(&change.id)) {
+                change.amount =
+                    (change.old_amount * (1.0 + percent / 100.0) * 100.0).round() / 100.0;
+            }
+            if let Some(relabel) = self.labels.iter().rfind(|r| r.id == change.id) {
+                change.label = relabel.label.clone();
+            }
+        }
+        changes.sort_by_key(|c| c.id);
+        Ok(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows() -> Vec<(i64, String, f64)> {
+        vec![
+            (1, "Rent".to_string(), 1200.0),
+            (2, "Power".to_string(), 83.35),
+            (3, "Internet".to_string(), 40.0),
+        ]
+    }
+
+    #[test]
+    fn test_plan_raises_and_relabels() {
+        let edit = BulkEdit {
+            ids: vec![2, 1],
+            increase_percent: Some(3.5),
+            labels: vec![Relabel {
+                id: 3,
+                label: "Fibre".to_string(),
+            }],
+        };
+
+        let changes = edit.plan(&rows(), "fixed expense").unwrap();
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[0].amount, 1242.0);
+        assert_eq!(changes[1].amount, 86.27);
+        assert_eq!(changes[2].amount, 40.0);
+        assert_eq!(changes[2].old_label, "Internet");
+        assert_eq!(changes[2].label, "Fibre");
+    }
+
+    #[test]
+    fn test_plan_rejects_unknown_ids_and_empty_edits() {
+        let edit = BulkEdit {
+            ids: vec![1, 9],
+            increase_percent: Some(5.0),
+            labels: vec![],
+        };
+        assert!(edit.plan(&rows(), "category").is_err());
+
+        let edit = BulkEdit {
+            ids: vec![],
+            increase_percent: None,
+            labels: vec![],
+        };
+        assert!(edit.plan(&rows(), "category").is_err());
+    }
+}
@@ -0,0 +1,168 @@
+//! Picks a category for items created without one, from the rules set on the user's
+//! categories. Items no rule matches go to the user's "Uncategorized" category, which
+//! is created the first time it is needed.
+
+use regex::{Regex, RegexBuilder};
+use sqlx::SqliteConnection;
+
+use crate::error::PaymeError;
+use crate::models::CategoryRule;
+
+/// Label of the category items fall back to.
+pub const UNCATEGORIZED: &str = "Uncategorized";
+
+/// Checks that a rule can be matched, returning why not otherwise.
+pub fn check_rule(kind: &str, pattern: &str) -> Result<(), String> {
+    match kind {
+        "contains" => Ok(()),
+        "regex" => RegexBuilder::new(pattern)
+            .case_insensitive(true)
+            .build()
+            .map(|_| ())
+            .map_err(|e| format!("Invalid regex {pattern:?}: {e}")),
+        _ => Err(format!(
+            "Unknown rule kind {kind:?}, expected contains or regex"
+        )),
+    }
+}
+
+/// The user's rules, compiled once so matching many of them against a description
+/// doesn't rebuild each regex. Rules that don't compile are left out.
+pub struct Rules(Vec<(i64, Matcher)>);
+
+enum Matcher {
+    /// Lowercased.
+    Contains(String),
+    Regex(Regex),
+}
+
+impl Rules {
+    pub fn compile(rules: &[CategoryRule]) -> Self {
+        let compiled = rules
+            .iter()
+            .filter_map(|rule| {
+                let matcher = match rule.kind.as_str() {
+                    "contains" => Matcher::Contains(rule.pattern.to_lowercase()),
+                    "regex" => Matcher::Regex(
+                        RegexBuilder::new(&rule.pattern)
+                            .case_insensitive(true)
+                            .build()
+                            .ok()?,
+                    ),
+                    _ => return None,
+                };
+                Some((rule.category_id, matcher))
+            })
+            .collect();
+        Rules(compiled)
+    }
+
+    /// The category of the first rule matching the description, ignoring case.
+    pub fn category_for(&self, description: &str) -> Option<i64> {
+        let lowercase = description.to_lowercase();
+        self.0
+            .iter()
+            .find(|(_, matcher)| match matcher {
+                Matcher::Contains(pattern) => lowercase.contains(pattern.as_str()),
+                Matcher::Regex(re) => re.is_match(description),
+            })
+            .map(|(category_id, _)| *category_id)
+    }
+}
+
+/// The category of the first rule matching the description, trying categories in the
+/// order they were created and each one's rules in order, otherwise the user's
+/// "Uncategorized" category.
+pub(crate) async fn assign(
+    conn: &mut SqliteConnection,
+    user_id: i64,
+    description: &str,
+) -> Result<i64, PaymeError> {
+    let rules: Vec<CategoryRule> = sqlx::query_as(
+        r#"
+        SELECT r.id, r.category_id, r.kind, r.pattern
+        FROM category_rules r
+        JOIN budget_categories bc ON r.category_id = bc.id
+        WHERE bc.user_id = ?
+        ORDER BY bc.id, r.position
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    match Rules::compile(&rules).category_for(description) {
+        Some(category_id) => Ok(category_id),
+        None => uncategorized(conn, user_id).await,
+    }
+}
+
+/// The user's "Uncategorized" category, created if they don't have it yet. It gets no
+/// allocation in new months.
+pub(crate) async fn uncategorized(
+    conn: &mut SqliteConnection,
+    user_id: i64,
+) -> Result<i64, PaymeError> {
+    let existing: Option<i64> =
+        sqlx::query_scalar("SELECT id FROM budget_categories WHERE user_id = ? AND system = 1")
+            .bind(user_id)
+            .fetch_optional(&mut *conn)
+            .await?;
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+
+    // A concurrent request may have created it since; the unique index on system
+    // categories then rejects this insert and the other one is used
+    let inserted = sqlx::query_scalar(
+        "INSERT INTO budget_categories (user_id, label, default_amount, system) VALUES (?, ?, 0, 1) ON CONFLICT DO NOTHING RETURNING id",
+    )
+    .bind(user_id)
+    .bind(UNCATEGORIZED)
+    .fetch_optional(&mut *conn)
+    .await?;
+    match inserted {
+        Some(id) => Ok(id),
+        None => Ok(sqlx::query_scalar(
+            "SELECT id FROM budget_categories WHERE user_id = ? AND system = 1",
+        )
+        .bind(user_id)
+        .fetch_one(&mut *conn)
+        .await?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(kind: &str, pattern: &str) -> CategoryRule {
+        CategoryRule {
+            id: 1,
+            category_id: 1,
+            kind: kind.to_string(),
+            pattern: pattern.to_string(),
+        }
+    }
+
+    fn matches(rule: CategoryRule, description: &str) -> bool {
+        Rules::compile(&[rule]).category_for(description).is_some()
+    }
+
+    #[test]
+    fn test_matches_ignores_case() {
+        assert!(matches(rule("contains", "tesco"), "TESCO Express"));
+        assert!(matches(rule("contains", "🍕"), "Friday 🍕 night"));
+        assert!(!matches(rule("contains", "aldi"), "Lidl"));
+        assert!(matches(rule("regex", r"^uber\s*(eats)?$"), "Uber Eats"));
+        assert!(!matches(rule("regex", r"^uber$"), "Uber Eats"));
+        assert!(!matches(rule("regex", "("), "("));
+    }
+
+    #[test]
+    fn test_check_rule() {
+        assert!(check_rule("contains", "(").is_ok());
+        assert!(check_rule("regex", "(").is_err());
+        assert!(check_rule("glob", "*").is_err());
+    }
+}
@@ -0,0 +1,234 @@
+use std::env;
+use std::path::Path;
+
+use chrono::Utc;
+use futures_util::stream::{StreamExt, TryStreamExt};
+use sqlx::SqlitePool;
+use tokio_util::io::ReaderStream;
+use utoipa::OpenApi;
+use validator::Validate;
+
+use crate::error::PaymeError;
+use crate::handlers::auth::{self, AuthRequest};
+use crate::handlers::export::{self, UserExport};
+use crate::jwt;
+use crate::openapi::ApiDoc;
+use crate::storage::Storage;
+
+pub const USAGE: &str = "\
+Usage: payme-admin <command>
+
+Commands:
+  create-user <username>                 Create an account; reads the password from stdin
+  reset-password <username>              Set a new password; reads it from stdin
+  export-user <username>                 Print the user's data as JSON
+  backup <path>                          Write a consistent copy of the database to <path>
+  upload-backup                          Store a copy of the database under backups/ in STORAGE_BACKEND
+  move-files                             Move stored files from the database to STORAGE_BACKEND
+  reopen-month <username> <year> <month> Reopen a closed month
+  rotate-jwt-keys                        Start signing tokens with a new key
+  openapi                                Print the OpenAPI spec, e.g. to generate a client
+
+DATABASE_URL selects the database, as for the server.";
+
+/// The API description served at `/api-docs/openapi.json`, pretty-printed.
+pub fn openapi_spec() -> String {
+    ApiDoc::openapi()
+        .to_pretty_json()
+        .expect("the OpenAPI document serializes")
+}
+
+/// Creates an account with the same checks and starter data as registration.
+pub async fn create_user(
+    pool: &SqlitePool,
+    username: &str,
+    password: &str,
+) -> Result<i64, PaymeError> {
+    AuthRequest {
+        username: username.to_string(),
+        password: password.to_string(),
+    }
+    .validate()?;
+
+    let taken: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE username = ?)")
+        .bind(username)
+        .fetch_one(pool)
+        .await?;
+    if taken {
+        return Err(PaymeError::Conflict(format!(
+            "Username {username} already exists"
+        )));
+    }
+
+    auth::create_account(pool, username, password).await
+}
+
+pub async fn reset_password(
+    pool: &SqlitePool,
+    username: &str,
+    password: &str,
+) -> Result<(), PaymeError> {
+    AuthRequest {
+        username: username.to_string(),
+        password: password.to_string(),
+    }
+    .validate()?;
+
+    let user_id = find_user(pool, username).await?;
+    sqlx::query("UPDATE users SET password_hash = ? WHERE id = ?")
+        .bind(auth::hash_password(password)?)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn export_user(pool: &SqlitePool, username: &str) -> Result<UserExport, PaymeError> {
+    let user_id = find_user(pool, username).await?;
+    export::build_export(pool, user_id).await
+}
+
+/// Copies the database with `VACUUM INTO`, which gives a consistent snapshot even
+/// while the server keeps writing.
+pub async fn backup(pool: &SqlitePool, path: &Path) -> Result<(), PaymeError> {
+    if path.exists() {
+        return Err(PaymeError::Conflict(format!(
+            "{} already exists",
+            path.display()
+        )));
+    }
+
+    sqlx::query("VACUUM INTO ?")
+        .bind(path.to_string_lossy())
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Backs the database up to `storage`, streaming the copy from a temporary file.
+/// Returns the key it was stored under.
+pub async fn upload_backup(pool: &SqlitePool, storage: &dyn Storage) -> Result<String, PaymeError> {
+    let key = format!("backups/payme-{}.db", Utc::now().format("%Y%m%d-%H%M%S"));
+    let path = env::temp_dir().join(format!("payme-backup-{}.db", uuid::Uuid::new_v4()));
+    backup(pool, &path).await?;
+
+    let stored = async {
+        let file = tokio::fs::File::open(&path)
+            .await
+            .map_err(|e| PaymeError::Internal(e.to_string()))?;
+        let data = ReaderStream::new(file)
+            .map_err(|e| PaymeError::Internal(e.to_string()))
+            .boxed();
+        storage.put(&key, data).await
+    }
+    .await;
+    let _ = tokio::fs::remove_file(&path).await;
+    stored?;
+
+    Ok(key)
+}
+
+/// Reopens a closed month for editing. Its frozen copies, wealth snapshot and PDF are
+/// dropped so closing it again records the corrected figures. The PDF file is removed
+/// from `storage` once the rows are gone.
+pub async fn reopen_month(
+    pool: &SqlitePool,
+    storage: &dyn Storage,
+    username: &str,
+    year: i32,
+    month: i32,
+) -> Result<(), PaymeError> {
+    let user_id = find_user(pool, username).await?;
+    let (month_id, is_closed): (i64, bool) = sqlx::query_as(
+        "SELECT id, is_closed FROM months WHERE user_id = ? AND year = ? AND month = ?",
+    )
+    .bind(user_id)
+    .bind(year)
+    .bind(month)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| PaymeError::BadRequest(format!("{username} has no month {year}-{month:02}")))?;
+    if !is_closed {
+        return Err(PaymeError::BadRequest(format!(
+            "{year}-{month:02} is not closed"
+        )));
+    }
+
+    let mut tx = pool.begin().await?;
+    let snapshot_key: Option<String> =
+        sqlx::query_scalar("SELECT storage_key FROM monthly_snapshots WHERE month_id = ?")
+            .bind(month_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+    for table in [
+        "closed_month_fixed_expenses",
+        "closed_month_budgets",
+        "closed_month_items",
+        "closed_month_checklist",
+        "wealth_snapshots",
+        "month_digests",
+        "monthly_snapshots",
+    ] {
+        sqlx::query(&format!("DELETE FROM {table} WHERE month_id = ?"))
+            .bind(month_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    sqlx::query("UPDATE months SET is_closed = 0, closed_at = NULL, frozen_at = NULL WHERE id = ?")
+        .bind(month_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    if let Some(key) = snapshot_key {
+        storage.delete(&key).await?;
+    }
+
+    Ok(())
+}
+
+/// Rotates the signing key and returns what to put in the server's environment.
+///
+/// HMAC secrets are generated here and the current one moves to
+/// `JWT_PREVIOUS_SECRETS`, so sessions stay valid until they expire. Key pairs have to
+/// be created by the operator; once `JWT_PRIVATE_KEY_FILE` and `JWT_PUBLIC_KEY_FILE`
+/// point at the new pair, this publishes it and retires the old public key.
+pub async fn rotate_jwt_keys(pool: &SqlitePool) -> Result<String, PaymeError> {
+    let algorithm = env::var("JWT_ALGORITHM").unwrap_or_else(|_| "HS256".to_string());
+    if !algorithm.eq_ignore_ascii_case("HS256") {
+        let keys = jwt::SigningKeys::from_env().map_err(PaymeError::BadRequest)?;
+        keys.publish(pool).await?;
+        return Ok(format!(
+            "Published key {}. Restart every instance to sign with it.",
+            keys.kid()
+        ));
+    }
+
+    let current = env::var("JWT_SECRET").unwrap_or_else(|_| jwt::DEFAULT_SECRET.to_string());
+    let previous: Vec<String> = std::iter::once(current)
+        .chain(
+            env::var("JWT_PREVIOUS_SECRETS")
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(String::from),
+        )
+        .collect();
+
+    Ok(format!(
+        "JWT_SECRET={}\nJWT_PREVIOUS_SECRETS={}",
+        jwt::generate_secret(),
+        previous.join(",")
+    ))
+}
+
+async fn find_user(pool: &SqlitePool, username: &str) -> Result<i64, PaymeError> {
+    sqlx::query_scalar("SELECT id FROM users WHERE username = ?")
+        .bind(username)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| PaymeError::BadRequest(format!("No user named {username}")))
+}
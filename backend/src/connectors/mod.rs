@@ -0,0 +1,580 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use reqwest::header::CONTENT_TYPE;
+use serde::Deserialize;
+use serde_json::json;
+use sqlx::SqlitePool;
+use url::Url;
+
+use crate::config::{self, OAuthClient};
+use crate::crypto;
+use crate::delivery::Document;
+use crate::error::PaymeError;
+
+/// How long a provider has to answer a token exchange or an upload.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A cloud drive that exports and PDF snapshots can be mirrored to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Provider {
+    Dropbox,
+    GoogleDrive,
+}
+
+/// Addresses of a provider's OAuth and file APIs.
+struct Endpoints {
+    authorize: String,
+    token: String,
+    upload: String,
+    /// Where Google Drive folders are created. Unused for Dropbox.
+    files: String,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+}
+
+/// What is stored once the user has authorized payme.
+pub struct Connection {
+    pub refresh_token: String,
+    /// Google Drive id of the folder created for the uploads. `None` for Dropbox, which
+    /// uploads by path.
+    pub folder_id: Option<String>,
+}
+
+impl Provider {
+    /// `dropbox` or `google_drive`.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "dropbox" => Some(Self::Dropbox),
+            "google_drive" => Some(Self::GoogleDrive),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Dropbox => "dropbox",
+            Self::GoogleDrive => "google_drive",
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Dropbox => "Dropbox",
+            Self::GoogleDrive => "Google Drive",
+        }
+    }
+
+    fn endpoints(self) -> Endpoints {
+        match self {
+            Self::Dropbox => Endpoints {
+                authorize: "https://www.dropbox.com/oauth2/authorize".to_string(),
+                token: "https://api.dropboxapi.com/oauth2/token".to_string(),
+                upload: "https://content.dropboxapi.com/2/files/upload".to_string(),
+                files: String::new(),
+            },
+            Self::GoogleDrive => Endpoints {
+                authorize: "https://accounts.google.com/o/oauth2/v2/auth".to_string(),
+                token: "https://oauth2.googleapis.com/token".to_string(),
+                upload: "https://www.googleapis.com/upload/drive/v3/files".to_string(),
+                files: "https://www.googleapis.com/drive/v3/files".to_string(),
+            },
+        }
+    }
+
+    /// The app credentials, or an error when the server has none for this provider.
+    pub fn client(self) -> Result<OAuthClient, PaymeError> {
+        let client = match self {
+            Self::Dropbox => config::dropbox_client(),
+            Self::GoogleDrive => config::google_client(),
+        };
+        client.ok_or_else(|| self.not_configured())
+    }
+
+    /// Where the provider sends the user back to after they authorize payme.
+    pub fn redirect_uri(self) -> Result<String, PaymeError> {
+        let public_url = config::public_url().ok_or_else(|| self.not_configured())?;
+        Ok(format!(
+            "{public_url}/api/v1/connectors/{}/callback",
+            self.as_str()
+        ))
+    }
+
+    fn not_configured(self) -> PaymeError {
+        PaymeError::BadRequest(format!("{} is not configured on this server", self.name()))
+    }
+
+    fn failed(self, e: reqwest::Error) -> PaymeError {
+        PaymeError::Internal(format!("{} request failed: {e}", self.name()))
+    }
+
+    /// Page the user is sent to for granting payme offline access. Google Drive access
+    /// is limited to files payme creates.
+    pub fn authorize_url(self, client_id: &str, redirect_uri: &str, state: &str) -> String {
+        let mut params = vec![
+            ("client_id", client_id),
+            ("response_type", "code"),
+            ("redirect_uri", redirect_uri),
+            ("state", state),
+        ];
+        match self {
+            Self::Dropbox => params.push(("token_access_type", "offline")),
+            Self::GoogleDrive => params.extend([
+                ("access_type", "offline"),
+                ("prompt", "consent"),
+                ("scope", "https://www.googleapis.com/auth/drive.file"),
+            ]),
+        }
+        Url::parse_with_params(&self.endpoints().authorize, params)
+            .expect("authorize endpoints are valid URLs")
+            .to_string()
+    }
+
+    /// Trades the code the user came back with for a refresh token and, for Google
+    /// Drive, creates the folder uploads go to.
+    pub async fn connect(
+        self,
+        code: &str,
+        redirect_uri: &str,
+        folder: &str,
+    ) -> Result<Connection, PaymeError> {
+        connect(
+            self,
+            &self.endpoints(),
+            &self.client()?,
+            code,
+            redirect_uri,
+            folder,
+        )
+        .await
+    }
+
+    /// Uploads the document into the connected folder, replacing a Dropbox file of the
+    /// same name.
+    pub async fn upload(
+        self,
+        refresh_token: &str,
+        folder: &str,
+        folder_id: Option<&str>,
+        document: Document,
+    ) -> Result<(), PaymeError> {
+        upload(
+            self,
+            &self.endpoints(),
+            &self.client()?,
+            refresh_token,
+            folder,
+            folder_id,
+            document,
+        )
+        .await
+    }
+}
+
+/// Uploads the document through the user's connection to the provider and records the
+/// outcome on the connection.
+pub async fn upload_for(
+    pool: &SqlitePool,
+    user_id: i64,
+    provider: Provider,
+    document: Document,
+) -> Result<(), PaymeError> {
+    let (refresh_token, folder, folder_id): (String, String, Option<String>) = sqlx::query_as(
+        "SELECT refresh_token, folder, folder_id FROM cloud_connections WHERE user_id = ? AND provider = ?",
+    )
+    .bind(user_id)
+    .bind(provider.as_str())
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| {
+        PaymeError::BadRequest(format!("{} is not connected", provider.name()))
+    })?;
+
+    let refresh_token = crypto::open_text(&refresh_token)?;
+    let result = provider
+        .upload(&refresh_token, &folder, folder_id.as_deref(), document)
+        .await;
+    let query = match &result {
+        Ok(()) => sqlx::query(
+            "UPDATE cloud_connections SET last_upload_at = ?, last_error = NULL WHERE user_id = ? AND provider = ?",
+        )
+        .bind(Utc::now()),
+        Err(e) => sqlx::query(
+            "UPDATE cloud_connections SET last_error = ? WHERE user_id = ? AND provider = ?",
+        )
+        .bind(e.to_string()),
+    };
+    query
+        .bind(user_id)
+        .bind(provider.as_str())
+        .execute(pool)
+        .await?;
+
+    result
+}
+
+async fn connect(
+    provider: Provider,
+    endpoints: &Endpoints,
+    client: &OAuthClient,
+    code: &str,
+    redirect_uri: &str,
+    folder: &str,
+) -> Result<Connection, PaymeError> {
+    let tokens = request_token(
+        provider,
+        endpoints,
+        client,
+        &[
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", redirect_uri),
+        ],
+    )
+    .await?;
+    let refresh_token = tokens.refresh_token.ok_or_else(|| {
+        PaymeError::BadRequest(format!("{} did not grant offline access", provider.name()))
+    })?;
+
+    let folder_id = match provider {
+        Provider::Dropbox => None,
+        Provider::GoogleDrive => {
+            #[derive(Deserialize)]
+            struct File {
+                id: String,
+            }
+            let file: File = send(
+                provider,
+                reqwest::Client::new()
+                    .post(&endpoints.files)
+                    .bearer_auth(&tokens.access_token)
+                    .json(&json!({
+                        "name": folder,
+                        "mimeType": "application/vnd.google-apps.folder",
+                    })),
+            )
+            .await?
+            .json()
+            .await
+            .map_err(|e| provider.failed(e))?;
+            Some(file.id)
+        }
+    };
+
+    Ok(Connection {
+        refresh_token,
+        folder_id,
+    })
+}
+
+async fn upload(
+    provider: Provider,
+    endpoints: &Endpoints,
+    client: &OAuthClient,
+    refresh_token: &str,
+    folder: &str,
+    folder_id: Option<&str>,
+    document: Document,
+) -> Result<(), PaymeError> {
+    let access_token = request_token(
+        provider,
+        endpoints,
+        client,
+        &[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+        ],
+    )
+    .await?
+    .access_token;
+
+    let request = reqwest::Client::new()
+        .post(&endpoints.upload)
+        .bearer_auth(access_token);
+    let request = match provider {
+        Provider::Dropbox => {
+            let path = match folder.trim_matches('/') {
+                "" => format!("/{}", document.filename),
+                folder => format!("/{folder}/{}", document.filename),
+            };
+            let arg = json!({ "path": path, "mode": "overwrite", "mute": true });
+            request
+                .header("Dropbox-API-Arg", ascii_json(&arg.to_string()))
+                .header(CONTENT_TYPE, "application/octet-stream")
+                .body(document.data)
+        }
+        Provider::GoogleDrive => {
+            let boundary = uuid::Uuid::new_v4().simple().to_string();
+            let mut metadata = json!({ "name": document.filename });
+            if let Some(folder_id) = folder_id {
+                metadata["parents"] = json!([folder_id]);
+            }
+            request
+                .query(&[("uploadType", "multipart")])
+                .header(
+                    CONTENT_TYPE,
+                    format!("multipart/related; boundary={boundary}"),
+                )
+                .body(multipart_related(&boundary, &metadata, &document))
+        }
+    };
+    send(provider, request).await?;
+
+    Ok(())
+}
+
+async fn request_token(
+    provider: Provider,
+    endpoints: &Endpoints,
+    client: &OAuthClient,
+    params: &[(&str, &str)],
+) -> Result<TokenResponse, PaymeError> {
+    let mut form = vec![
+        ("client_id", client.client_id.as_str()),
+        ("client_secret", client.client_secret.as_str()),
+    ];
+    form.extend_from_slice(params);
+
+    send(
+        provider,
+        reqwest::Client::new().post(&endpoints.token).form(&form),
+    )
+    .await?
+    .json()
+    .await
+    .map_err(|e| provider.failed(e))
+}
+
+async fn send(
+    provider: Provider,
+    request: reqwest::RequestBuilder,
+) -> Result<reqwest::Response, PaymeError> {
+    request
+        .timeout(REQUEST_TIMEOUT)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| provider.failed(e))
+}
+
+/// A Google Drive upload body: the file's metadata followed by its contents.
+fn multipart_related(boundary: &str, metadata: &serde_json::Value, document: &Document) -> Vec<u8> {
+    let mut body = format!(
+        "--{boundary}\r\nContent-Type: application/json; charset=UTF-8\r\n\r\n{metadata}\r\n--{boundary}\r\nContent-Type: {}\r\n\r\n",
+        document.content_type
+    )
+    .into_bytes();
+    body.extend_from_slice(&document.data);
+    body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+    body
+}
+
+/// Escapes non-ASCII characters so the JSON fits in an HTTP header, as Dropbox requires.
+fn ascii_json(json: &str) -> String {
+    let mut escaped = String::with_capacity(json.len());
+    for c in json.chars() {
+        if c.is_ascii() {
+            escaped.push(c);
+        } else {
+            let mut units = [0; 2];
+            for unit in c.encode_utf16(&mut units) {
+                escaped.push_str(&format!("\\u{unit:04x}"));
+            }
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use axum::extract::{Form, Request};
+    use axum::routing::post;
+    use axum::Router;
+
+    use super::*;
+
+    /// Serves a provider stand-in that hands out tokens and records every other
+    /// request as `(path, headers, body)`.
+    async fn provider_server() -> (
+        Endpoints,
+        Arc<Mutex<Vec<(String, axum::http::HeaderMap, Vec<u8>)>>>,
+    ) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        let app = Router::new()
+            .route(
+                "/token",
+                post(|Form(form): Form<Vec<(String, String)>>| async move {
+                    let grant = form.iter().find(|(key, _)| key == "grant_type").unwrap();
+                    axum::Json(match grant.1.as_str() {
+                        "authorization_code" => {
+                            json!({ "access_token": "access-1", "refresh_token": "refresh-1" })
+                        }
+                        _ => json!({ "access_token": "access-2" }),
+                    })
+                }),
+            )
+            .fallback(move |request: Request| {
+                let recorded = recorded.clone();
+                async move {
+                    let path = request.uri().path().to_string();
+                    let headers = request.headers().clone();
+                    let body = axum::body::to_bytes(request.into_body(), usize::MAX)
+                        .await
+                        .unwrap();
+                    recorded
+                        .lock()
+                        .unwrap()
+                        .push((path, headers, body.to_vec()));
+                    axum::Json(json!({ "id": "folder-1" }))
+                }
+            });
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let endpoints = Endpoints {
+            authorize: format!("{base}/authorize"),
+            token: format!("{base}/token"),
+            upload: format!("{base}/upload"),
+            files: format!("{base}/files"),
+        };
+        (endpoints, requests)
+    }
+
+    fn client() -> OAuthClient {
+        OAuthClient {
+            client_id: "app".to_string(),
+            client_secret: "secret".to_string(),
+        }
+    }
+
+    fn document() -> Document {
+        Document {
+            filename: "month-2024-06.pdf".to_string(),
+            content_type: "application/pdf".to_string(),
+            data: b"%PDF".to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_authorize_url() {
+        let url = Provider::GoogleDrive.authorize_url("app", "https://payme.test/cb", "s1");
+        let url = Url::parse(&url).unwrap();
+        let params: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        assert_eq!(url.host_str(), Some("accounts.google.com"));
+        assert!(params.contains(&("client_id".to_string(), "app".to_string())));
+        assert!(params.contains(&("state".to_string(), "s1".to_string())));
+        assert!(params.contains(&(
+            "redirect_uri".to_string(),
+            "https://payme.test/cb".to_string()
+        )));
+        assert!(params.contains(&("access_type".to_string(), "offline".to_string())));
+
+        let url = Provider::Dropbox.authorize_url("app", "https://payme.test/cb", "s1");
+        assert!(url.contains("token_access_type=offline"));
+    }
+
+    #[test]
+    fn test_ascii_json() {
+        assert_eq!(ascii_json(r#"{"path":"/a"}"#), r#"{"path":"/a"}"#);
+        assert_eq!(ascii_json("\"Café 🎉\""), r#""Caf\u00e9 \ud83c\udf89""#);
+    }
+
+    #[tokio::test]
+    async fn test_dropbox_connect_and_upload() {
+        let (endpoints, requests) = provider_server().await;
+        let provider = Provider::Dropbox;
+
+        let connection = connect(provider, &endpoints, &client(), "code", "cb", "/payme")
+            .await
+            .unwrap();
+        assert_eq!(connection.refresh_token, "refresh-1");
+        assert_eq!(connection.folder_id, None);
+
+        upload(
+            provider,
+            &endpoints,
+            &client(),
+            "refresh-1",
+            "payme/",
+            None,
+            document(),
+        )
+        .await
+        .unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        let (path, headers, body) = &requests[0];
+        assert_eq!(path, "/upload");
+        assert_eq!(headers["authorization"], "Bearer access-2");
+        let arg: serde_json::Value =
+            serde_json::from_str(headers["dropbox-api-arg"].to_str().unwrap()).unwrap();
+        assert_eq!(arg["path"], "/payme/month-2024-06.pdf");
+        assert_eq!(arg["mode"], "overwrite");
+        assert_eq!(body, b"%PDF");
+    }
+
+    #[tokio::test]
+    async fn test_google_drive_connect_and_upload() {
+        let (endpoints, requests) = provider_server().await;
+        let provider = Provider::GoogleDrive;
+
+        let connection = connect(provider, &endpoints, &client(), "code", "cb", "payme")
+            .await
+            .unwrap();
+        assert_eq!(connection.folder_id.as_deref(), Some("folder-1"));
+
+        upload(
+            provider,
+            &endpoints,
+            &client(),
+            "refresh-1",
+            "payme",
+            Some("folder-1"),
+            document(),
+        )
+        .await
+        .unwrap();
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests.len(), 2);
+        let (path, _, body) = &requests[0];
+        assert_eq!(path, "/files");
+        let folder: serde_json::Value = serde_json::from_slice(body).unwrap();
+        assert_eq!(folder["name"], "payme");
+
+        let (path, headers, body) = &requests[1];
+        assert_eq!(path, "/upload");
+        assert_eq!(headers["authorization"], "Bearer access-2");
+        let body = String::from_utf8_lossy(body);
+        assert!(body.contains(r#""parents":["folder-1"]"#));
+        assert!(body.contains("Content-Type: application/pdf\r\n\r\n%PDF\r\n"));
+    }
+
+    #[tokio::test]
+    async fn test_upload_error_names_provider() {
+        let (mut endpoints, _) = provider_server().await;
+        endpoints.token = endpoints.token.replace("/token", "/missing-token");
+
+        let err = upload(
+            Provider::Dropbox,
+            &endpoints,
+            &client(),
+            "refresh-1",
+            "/payme",
+            None,
+            document(),
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("Dropbox request failed"));
+    }
+}
@@ -0,0 +1,275 @@
+use std::sync::OnceLock;
+
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Nonce,
+};
+use futures_util::stream::{self, StreamExt};
+use sqlx::SqlitePool;
+
+use crate::error::PaymeError;
+use crate::storage;
+
+/// Prefix of every encrypted blob, followed by the 12 byte nonce and the ciphertext.
+const MAGIC: &[u8] = b"PAYMEENC1";
+/// Prefix of an encrypted string kept in a text column, followed by the blob in hex.
+const TEXT_PREFIX: &str = "enc:";
+/// Text columns holding secrets sealed with [`seal_text`], as `(table, column)`.
+const SECRET_COLUMNS: &[(&str, &str)] = &[
+    ("saved_reports", "target"),
+    ("user_settings", "webdav_url"),
+    ("cloud_connections", "refresh_token"),
+];
+const NONCE_LEN: usize = 12;
+
+/// AES-256-GCM encryption for blobs stored in the database, such as PDF snapshots.
+pub struct BlobCipher {
+    cipher: Aes256Gcm,
+}
+
+static CIPHER: OnceLock<Option<BlobCipher>> = OnceLock::new();
+
+/// Cipher for the key in `DATA_ENCRYPTION_KEY` (64 hex characters), or `None` when
+/// encryption at rest is not configured.
+pub fn blob_cipher() -> Option<&'static BlobCipher> {
+    CIPHER
+        .get_or_init(|| {
+            let key = std::env::var("DATA_ENCRYPTION_KEY").ok()?;
+            Some(
+                BlobCipher::from_hex(&key)
+                    .unwrap_or_else(|e| panic!("Invalid DATA_ENCRYPTION_KEY: {e}")),
+            )
+        })
+        .as_ref()
+}
+
+impl BlobCipher {
+    pub fn new(key: &[u8; 32]) -> Self {
+        Self {
+            cipher: Aes256Gcm::new(key.into()),
+        }
+    }
+
+    pub fn from_hex(key: &str) -> Result<Self, String> {
+        Ok(Self::new(&key_from_hex(key)?))
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, PaymeError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|e| PaymeError::Internal(e.to_string()))?;
+
+        let mut blob = Vec::with_capacity(MAGIC.len() + NONCE_LEN + ciphertext.len());
+        blob.extend_from_slice(MAGIC);
+        blob.extend_from_slice(&nonce);
+        blob.extend_from_slice(&ciphertext);
+        Ok(blob)
+    }
+
+    pub fn decrypt(&self, blob: &[u8]) -> Result<Vec<u8>, PaymeError> {
+        let body = blob
+            .strip_prefix(MAGIC)
+            .filter(|body| body.len() > NONCE_LEN)
+            .ok_or_else(|| PaymeError::Internal("Blob is not encrypted".to_string()))?;
+        let (nonce, ciphertext) = body.split_at(NONCE_LEN);
+
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| PaymeError::Internal("Failed to decrypt blob".to_string()))
+    }
+
+    /// Encrypts a secret for a text column.
+    pub fn encrypt_text(&self, text: &str) -> Result<String, PaymeError> {
+        let blob = self.encrypt(text.as_bytes())?;
+        Ok(format!("{TEXT_PREFIX}{}", storage::to_hex(&blob)))
+    }
+
+    pub fn decrypt_text(&self, text: &str) -> Result<String, PaymeError> {
+        let blob = text
+            .strip_prefix(TEXT_PREFIX)
+            .and_then(from_hex)
+            .ok_or_else(|| PaymeError::Internal("Text is not encrypted".to_string()))?;
+        String::from_utf8(self.decrypt(&blob)?)
+            .map_err(|_| PaymeError::Internal("Failed to decrypt text".to_string()))
+    }
+}
+
+/// Parses a 32 byte key written as 64 hex characters.
+pub fn key_from_hex(key: &str) -> Result<[u8; 32], String> {
+    let key = key.trim();
+    if key.len() != 64 || !key.is_ascii() {
+        return Err("expected 64 hex characters".to_string());
+    }
+    let mut bytes = [0u8; 32];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&key[i * 2..i * 2 + 2], 16)
+            .map_err(|_| "expected 64 hex characters".to_string())?;
+    }
+    Ok(bytes)
+}
+
+pub fn is_encrypted(blob: &[u8]) -> bool {
+    blob.starts_with(MAGIC)
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Encrypts data before it is stored, or returns it unchanged when no key is configured.
+pub fn seal(data: Vec<u8>) -> Result<Vec<u8>, PaymeError> {
+    match blob_cipher() {
+        Some(cipher) => cipher.encrypt(&data),
+        None => Ok(data),
+    }
+}
+
+/// Reverses `seal`. Blobs stored before encryption was enabled are returned as is.
+pub fn open(blob: Vec<u8>) -> Result<Vec<u8>, PaymeError> {
+    if !is_encrypted(&blob) {
+        return Ok(blob);
+    }
+    blob_cipher()
+        .ok_or_else(|| {
+            PaymeError::Internal("Encrypted blob found but DATA_ENCRYPTION_KEY is not set".into())
+        })?
+        .decrypt(&blob)
+}
+
+/// Like [`seal`], for secrets kept in text columns, such as credentials in a delivery
+/// target.
+pub fn seal_text(text: &str) -> Result<String, PaymeError> {
+    match blob_cipher() {
+        Some(cipher) => cipher.encrypt_text(text),
+        None => Ok(text.to_string()),
+    }
+}
+
+/// Reverses [`seal_text`]. Text stored before encryption was enabled is returned as is.
+pub fn open_text(text: &str) -> Result<String, PaymeError> {
+    if !text.starts_with(TEXT_PREFIX) {
+        return Ok(text.to_string());
+    }
+    blob_cipher()
+        .ok_or_else(|| {
+            PaymeError::Internal("Encrypted text found but DATA_ENCRYPTION_KEY is not set".into())
+        })?
+        .decrypt_text(text)
+}
+
+/// Encrypts secrets kept in text columns that were stored in plaintext. Returns how
+/// many were updated.
+pub async fn encrypt_existing_secrets(pool: &SqlitePool) -> Result<usize, PaymeError> {
+    let Some(cipher) = blob_cipher() else {
+        return Ok(0);
+    };
+
+    let mut updated = 0;
+    for (table, column) in SECRET_COLUMNS {
+        let secrets: Vec<(i64, String)> = sqlx::query_as(&format!(
+            "SELECT rowid, {column} FROM {table} WHERE {column} IS NOT NULL AND {column} NOT LIKE '{TEXT_PREFIX}%'"
+        ))
+        .fetch_all(pool)
+        .await?;
+        for (rowid, text) in secrets {
+            sqlx::query(&format!("UPDATE {table} SET {column} = ? WHERE rowid = ?"))
+                .bind(cipher.encrypt_text(&text)?)
+                .bind(rowid)
+                .execute(pool)
+                .await?;
+            updated += 1;
+        }
+    }
+
+    Ok(updated)
+}
+
+/// Encrypts PDF snapshots and report files that were stored in plaintext. Returns how
+/// many were updated.
+pub async fn encrypt_existing_files(pool: &SqlitePool) -> Result<usize, PaymeError> {
+    let Some(cipher) = blob_cipher() else {
+        return Ok(0);
+    };
+
+    let files: Vec<(String, String, i64)> = sqlx::query_as(
+        r#"
+        SELECT storage_key, 'monthly_snapshots', id FROM monthly_snapshots
+        UNION ALL
+        SELECT storage_key, 'report_artifacts', id FROM report_artifacts
+        "#,
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let storage = storage::backend(pool);
+    let mut updated = 0;
+    for (key, table, id) in files {
+        let data = storage::read(pool, storage.as_ref(), &key).await?;
+        if is_encrypted(&data) {
+            continue;
+        }
+        let sealed = bytes::Bytes::from(cipher.encrypt(&data)?);
+        let size = storage
+            .put(&key, stream::once(async { Ok(sealed) }).boxed())
+            .await?;
+        sqlx::query(&format!("UPDATE {table} SET size = ? WHERE id = ?"))
+            .bind(size as i64)
+            .bind(id)
+            .execute(pool)
+            .await?;
+        updated += 1;
+    }
+
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let cipher = BlobCipher::new(&[7u8; 32]);
+        let blob = cipher.encrypt(b"%PDF-1.3 report").unwrap();
+        assert!(is_encrypted(&blob));
+        assert!(!blob.windows(4).any(|w| w == b"%PDF"));
+        assert_eq!(cipher.decrypt(&blob).unwrap(), b"%PDF-1.3 report");
+    }
+
+    #[test]
+    fn test_wrong_key_fails() {
+        let blob = BlobCipher::new(&[7u8; 32]).encrypt(b"secret").unwrap();
+        assert!(BlobCipher::new(&[8u8; 32]).decrypt(&blob).is_err());
+    }
+
+    #[test]
+    fn test_text_round_trip() {
+        let cipher = BlobCipher::new(&[7u8; 32]);
+        let text = cipher
+            .encrypt_text("s3://KEY:secret@backups/payme")
+            .unwrap();
+        assert!(text.starts_with(TEXT_PREFIX));
+        assert!(!text.contains("secret"));
+        assert_eq!(
+            cipher.decrypt_text(&text).unwrap(),
+            "s3://KEY:secret@backups/payme"
+        );
+        assert!(BlobCipher::new(&[8u8; 32]).decrypt_text(&text).is_err());
+        assert!(cipher.decrypt_text("enc:zz").is_err());
+    }
+
+    #[test]
+    fn test_from_hex() {
+        assert!(BlobCipher::from_hex(&"ab".repeat(32)).is_ok());
+        assert!(BlobCipher::from_hex("abcd").is_err());
+        assert!(BlobCipher::from_hex(&"zz".repeat(32)).is_err());
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Sqlite, SqliteConnection, Transaction};
+use utoipa::{IntoParams, ToSchema};
+
+/// `?dry_run=true` on a destructive endpoint runs it inside a transaction that is rolled
+/// back, and answers with a [`DryRunReport`] instead of the usual response.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DryRunQuery {
+    /// Report what would change without changing anything.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct DryRunReport {
+    pub dry_run: bool,
+    /// Rows per table, cascades included.
+    pub changes: Vec<TableChanges>,
+    /// Months that would gain, lose or change rows, as `YYYY-MM`.
+    pub affected_months: Vec<String>,
+}
+
+#[derive(Serialize, ToSchema, Default)]
+pub struct TableChanges {
+    pub table: String,
+    pub inserted: i64,
+    pub updated: i64,
+    pub deleted: i64,
+}
+
+/// Tables that belong to the migration or sync machinery rather than to users.
+const UNTRACKED: &[&str] = &[
+    "_sqlx_migrations",
+    "migration_lock",
+    "sync_clock",
+    "sync_deletions",
+];
+
+/// Records every insert, update and delete made on `conn` from here on, including
+/// those done by foreign key cascades. The temporary triggers and table this creates
+/// disappear when the transaction is rolled back.
+pub async fn track_changes(conn: &mut SqliteConnection) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "CREATE TEMP TABLE dry_run_changes (tbl TEXT NOT NULL, op TEXT NOT NULL, month_id INTEGER, year INTEGER, month INTEGER)",
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    let tables: Vec<String> = sqlx::query_scalar(
+        "SELECT name FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for table in tables.iter().filter(|t| !UNTRACKED.contains(&t.as_str())) {
+        let has_month_id: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM pragma_table_info(?) WHERE name = 'month_id')",
+        )
+        .bind(table)
+        .fetch_one(&mut *conn)
+        .await?;
+        let has_version: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM pragma_table_info(?) WHERE name = 'version')",
+        )
+        .bind(table)
+        .fetch_one(&mut *conn)
+        .await?;
+
+        for (event, op, row) in [
+            ("INSERT", "inserted", "NEW"),
+            ("UPDATE", "updated", "NEW"),
+            ("DELETE", "deleted", "OLD"),
+        ] {
+            // Months carry their own year and month, since they may be gone by the
+            // time the report is read
+            let month = if table == "months" {
+                format!("{row}.id, {row}.year, {row}.month")
+            } else if has_month_id {
+                format!("{row}.month_id, NULL, NULL")
+            } else {
+                "NULL, NULL, NULL".to_string()
+            };
+            // Updates made by the sync triggers only stamp the row's version
+            let when = if event == "UPDATE" && has_version {
+                "WHEN NEW.version = OLD.version"
+            } else {
+                ""
+            };
+            // Table names come from SQLite itself, not from user input
+            sqlx::query(&format!(
+                r#"
+                CREATE TEMP TRIGGER "dry_run_{table}_{op}" AFTER {event} ON main."{table}" {when}
+                BEGIN
+                    INSERT INTO dry_run_changes (tbl, op, month_id, year, month)
+                    VALUES ('{table}', '{op}', {month});
+                END
+                "#
+            ))
+            .execute(&mut *conn)
+            .await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Commits the transaction, or for a dry run collects what it changed and rolls it
+/// back. [`track_changes`] must have been called on it for dry runs.
+pub async fn finish(
+    mut tx: Transaction<'_, Sqlite>,
+    dry_run: bool,
+) -> Result<Option<DryRunReport>, sqlx::Error> {
+    if !dry_run {
+        tx.commit().await?;
+        return Ok(None);
+    }
+
+    let counts: Vec<(String, String, i64)> = sqlx::query_as(
+        "SELECT tbl, op, COUNT(*) FROM dry_run_changes GROUP BY tbl, op ORDER BY tbl",
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    let mut changes: BTreeMap<String, TableChanges> = BTreeMap::new();
+    for (table, op, count) in counts {
+        let entry = changes
+            .entry(table.clone())
+            .or_insert_with(|| TableChanges {
+                table,
+                ..Default::default()
+            });
+        match op.as_str() {
+            "inserted" => entry.inserted = count,
+            "updated" => entry.updated = count,
+            _ => entry.deleted = count,
+        }
+    }
+
+    let months: Vec<(i32, i32)> = sqlx::query_as(
+        r#"
+        SELECT DISTINCT COALESCE(c.year, mc.year, m.year), COALESCE(c.month, mc.month, m.month)
+        FROM dry_run_changes c
+        LEFT JOIN (SELECT DISTINCT month_id, year, month FROM dry_run_changes WHERE tbl = 'months') mc
+            ON mc.month_id = c.month_id
+        LEFT JOIN months m ON m.id = c.month_id
+        WHERE c.month_id IS NOT NULL
+        "#,
+    )
+    .fetch_all(&mut *tx)
+    .await?;
+    let affected_months: BTreeSet<String> = months
+        .into_iter()
+        .map(|(year, month)| format!("{year}-{month:02}"))
+        .collect();
+
+    tx.rollback().await?;
+
+    Ok(Some(DryRunReport {
+        dry_run: true,
+        changes: changes.into_values().collect(),
+        affected_months: affected_months.into_iter().collect(),
+    }))
+}
@@ -5,6 +5,8 @@ use axum::{
 use thiserror::Error;
 use validator::ValidationErrors;
 
+use crate::i18n::Text;
+
 #[derive(Error, Debug)]
 pub enum PaymeError {
     #[error("Database error: {0}")]
@@ -19,25 +21,109 @@ pub enum PaymeError {
     #[error("Unauthorized")]
     Unauthorized,
 
+    #[error("Too many failed attempts")]
+    Locked,
+
+    #[error("Read-only maintenance mode")]
+    ReadOnly,
+
     #[error("Bad request: {0}")]
     BadRequest(String),
 
+    #[error("Malformed body: {0}")]
+    MalformedBody(String),
+
+    #[error("Invalid body: {reason}")]
+    InvalidBody {
+        /// Path of the offending field, e.g. `items[0].amount`. Absent when the body
+        /// as a whole has the wrong type.
+        field: Option<String>,
+        reason: String,
+    },
+
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+
+    #[error("Conflict: {0}")]
+    Conflict(String),
+
+    /// The item matches the recent item with this ID.
+    #[error("Duplicate of item {0}")]
+    Duplicate(i64),
+
+    #[error("Quota exceeded: {0}")]
+    QuotaExceeded(String),
+
     #[error("Internal error: {0}")]
     Internal(String),
 }
 
+/// Attached to error responses so the locale middleware can render a translated body.
+#[derive(Debug, Clone)]
+pub struct ErrorMessage {
+    pub text: Text,
+    pub fields: Vec<String>,
+    /// Explanation shown to the user as-is, for errors whose cause they chose.
+    pub reason: Option<String>,
+    /// The recent item a likely duplicate matches.
+    pub duplicate_of: Option<i64>,
+}
+
 impl IntoResponse for PaymeError {
     fn into_response(self) -> Response {
-        let status = match &self {
-            PaymeError::Database(_) => StatusCode::INTERNAL_SERVER_ERROR,
-            PaymeError::Validation(_) => StatusCode::BAD_REQUEST,
-            PaymeError::NotFound => StatusCode::NOT_FOUND,
-            PaymeError::Unauthorized => StatusCode::UNAUTHORIZED,
-            PaymeError::BadRequest(_) => StatusCode::BAD_REQUEST,
-            PaymeError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        let (status, text) = match &self {
+            PaymeError::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, Text::ErrorInternal),
+            PaymeError::Validation(_) => (StatusCode::BAD_REQUEST, Text::ErrorValidation),
+            PaymeError::NotFound => (StatusCode::NOT_FOUND, Text::ErrorNotFound),
+            PaymeError::Unauthorized => (StatusCode::UNAUTHORIZED, Text::ErrorUnauthorized),
+            PaymeError::Locked => (StatusCode::TOO_MANY_REQUESTS, Text::ErrorLocked),
+            PaymeError::ReadOnly => (StatusCode::SERVICE_UNAVAILABLE, Text::ErrorReadOnly),
+            PaymeError::BadRequest(_) => (StatusCode::BAD_REQUEST, Text::ErrorBadRequest),
+            PaymeError::MalformedBody(_) => (StatusCode::BAD_REQUEST, Text::ErrorMalformedBody),
+            PaymeError::InvalidBody { .. } => {
+                (StatusCode::UNPROCESSABLE_ENTITY, Text::ErrorInvalidBody)
+            }
+            PaymeError::Forbidden(_) => (StatusCode::FORBIDDEN, Text::ErrorForbidden),
+            PaymeError::Conflict(_) => (StatusCode::CONFLICT, Text::ErrorConflict),
+            PaymeError::Duplicate(_) => (StatusCode::CONFLICT, Text::ErrorDuplicate),
+            PaymeError::QuotaExceeded(_) => (StatusCode::CONFLICT, Text::ErrorQuotaExceeded),
+            PaymeError::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, Text::ErrorInternal),
+        };
+        let mut fields: Vec<String> = match &self {
+            PaymeError::Validation(errors) => errors
+                .field_errors()
+                .keys()
+                .map(|field| field.to_string())
+                .collect(),
+            PaymeError::InvalidBody {
+                field: Some(field), ..
+            } => vec![field.clone()],
+            _ => Vec::new(),
+        };
+        fields.sort();
+        let reason = match &self {
+            PaymeError::BadRequest(reason)
+            | PaymeError::Conflict(reason)
+            | PaymeError::Forbidden(reason)
+            | PaymeError::QuotaExceeded(reason)
+            | PaymeError::MalformedBody(reason)
+            | PaymeError::InvalidBody { reason, .. } => Some(reason.clone()),
+            _ => None,
+        };
+        let duplicate_of = match &self {
+            PaymeError::Duplicate(id) => Some(*id),
+            _ => None,
         };
         tracing::error!("{self}");
-        status.into_response()
+
+        let mut response = status.into_response();
+        response.extensions_mut().insert(ErrorMessage {
+            text,
+            fields,
+            reason,
+            duplicate_of,
+        });
+        response
     }
 }
 
@@ -67,6 +153,52 @@ mod tests {
         assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
 
+    #[test]
+    fn test_invalid_body_status() {
+        let error = PaymeError::InvalidBody {
+            field: Some("amount".to_string()),
+            reason: "invalid type".to_string(),
+        };
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[test]
+    fn test_conflict_status() {
+        let error = PaymeError::Conflict("test".to_string());
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[test]
+    fn test_duplicate_status() {
+        let response = PaymeError::Duplicate(7).into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let message = response.extensions().get::<ErrorMessage>().unwrap();
+        assert_eq!(message.duplicate_of, Some(7));
+    }
+
+    #[test]
+    fn test_quota_exceeded_status() {
+        let error = PaymeError::QuotaExceeded("test".to_string());
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[test]
+    fn test_forbidden_status() {
+        let error = PaymeError::Forbidden("test".to_string());
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn test_read_only_status() {
+        let error = PaymeError::ReadOnly;
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
     #[test]
     fn test_internal_status() {
         let error = PaymeError::Internal("test".to_string());
@@ -0,0 +1,172 @@
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, OptionalFromRequest, Request},
+    http::{header::CONTENT_TYPE, HeaderMap},
+    response::{IntoResponse, Response},
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::error::PaymeError;
+
+/// Drop-in for `axum::Json` whose rejections say which field is wrong and why,
+/// instead of axum's plain-text 400 and 422 bodies.
+pub struct Json<T>(pub T);
+
+impl<T, S> FromRequest<S> for Json<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = PaymeError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if !is_json(request.headers()) {
+            return Err(PaymeError::MalformedBody(
+                "Expected a body with Content-Type: application/json".to_string(),
+            ));
+        }
+        let body = Bytes::from_request(request, state)
+            .await
+            .map_err(|rejection| PaymeError::MalformedBody(rejection.body_text()))?;
+        parse(&body).map(Json)
+    }
+}
+
+/// `Option<Json<T>>` is `None` when the request body is empty, whatever its Content-Type.
+impl<T, S> OptionalFromRequest<S> for Json<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = PaymeError;
+
+    async fn from_request(request: Request, state: &S) -> Result<Option<Self>, Self::Rejection> {
+        let json = is_json(request.headers());
+        let body = Bytes::from_request(request, state)
+            .await
+            .map_err(|rejection| PaymeError::MalformedBody(rejection.body_text()))?;
+        if body.is_empty() {
+            return Ok(None);
+        }
+        if !json {
+            return Err(PaymeError::MalformedBody(
+                "Expected a body with Content-Type: application/json".to_string(),
+            ));
+        }
+        parse(&body).map(|value| Some(Json(value)))
+    }
+}
+
+impl<T: Serialize> IntoResponse for Json<T> {
+    fn into_response(self) -> Response {
+        axum::Json(self.0).into_response()
+    }
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    let Some(content_type) = headers.get(CONTENT_TYPE).and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    essence == "application/json"
+        || (essence.starts_with("application/") && essence.ends_with("+json"))
+}
+
+/// Malformed JSON is a 400; JSON of the wrong shape is a 422 naming the field.
+fn parse<T: DeserializeOwned>(body: &[u8]) -> Result<T, PaymeError> {
+    let mut deserializer = serde_json::Deserializer::from_slice(body);
+    let value = serde_path_to_error::deserialize(&mut deserializer).map_err(|error| {
+        let path = error.path().to_string();
+        let error = error.into_inner();
+        if !error.is_data() {
+            return PaymeError::MalformedBody(error.to_string());
+        }
+        let reason = message(&error);
+        // Missing fields are reported against their parent, so name them here
+        let missing = reason
+            .strip_prefix("missing field `")
+            .and_then(|rest| rest.strip_suffix('`'));
+        let field = match (path.as_str(), missing) {
+            (".", Some(missing)) => Some(missing.to_string()),
+            (".", None) => None,
+            (parent, Some(missing)) => Some(format!("{parent}.{missing}")),
+            (path, None) => Some(path.to_string()),
+        };
+        PaymeError::InvalidBody { field, reason }
+    })?;
+    deserializer
+        .end()
+        .map_err(|error| PaymeError::MalformedBody(error.to_string()))?;
+    Ok(value)
+}
+
+/// The error without serde_json's trailing "at line 1 column 42".
+fn message(error: &serde_json::Error) -> String {
+    let message = error.to_string();
+    match message.rsplit_once(" at line ") {
+        Some((message, _)) => message.to_string(),
+        None => message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    #[allow(dead_code)]
+    struct Payload {
+        amount: f64,
+        #[serde(default)]
+        lines: Vec<Line>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    #[allow(dead_code)]
+    struct Line {
+        label: String,
+    }
+
+    fn invalid(body: &str) -> (Option<String>, String) {
+        match parse::<Payload>(body.as_bytes()) {
+            Err(PaymeError::InvalidBody { field, reason }) => (field, reason),
+            other => panic!("expected an invalid body, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_wrong_type_names_the_field() {
+        let (field, reason) = invalid(r#"{"amount": "12"}"#);
+        assert_eq!(field.as_deref(), Some("amount"));
+        assert_eq!(reason, r#"invalid type: string "12", expected f64"#);
+    }
+
+    #[test]
+    fn test_unknown_field() {
+        let (field, reason) = invalid(r#"{"ammount": 12}"#);
+        assert_eq!(field.as_deref(), Some("ammount"));
+        assert!(reason.starts_with("unknown field `ammount`"));
+    }
+
+    #[test]
+    fn test_missing_nested_field() {
+        let (field, _) = invalid(r#"{"amount": 1, "lines": [{}]}"#);
+        assert_eq!(field.as_deref(), Some("lines[0].label"));
+    }
+
+    #[test]
+    fn test_syntax_error_is_malformed() {
+        assert!(matches!(
+            parse::<Payload>(br#"{"amount": 1"#),
+            Err(PaymeError::MalformedBody(_))
+        ));
+    }
+}
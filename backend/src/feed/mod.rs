@@ -0,0 +1,350 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Duration, NaiveDate, Utc};
+use sqlx::{SqliteExecutor, SqlitePool};
+
+use crate::format::{MoneyFormat, DEFAULT_CURRENCY, DEFAULT_LOCALE};
+use crate::handlers::sync;
+use crate::i18n::{Locale, Text};
+use crate::jobs;
+use crate::storage;
+use crate::streaks;
+
+/// Spending this far above the trailing average (in percent) is reported.
+const SPIKE_THRESHOLD_PERCENT: f64 = 40.0;
+/// Fixed costs above this share of income are reported.
+const FIXED_RATIO_THRESHOLD: f64 = 0.5;
+/// Shortest no-spend streak (in days) worth celebrating.
+const MIN_NO_SPEND_STREAK: i64 = 3;
+/// Insight kinds recorded when something happens rather than derived by a rule, so
+/// recomputing the rules leaves them in place.
+const EVENT_KINDS: &[&str] = &["income_rebudget"];
+
+struct GeneratedInsight {
+    kind: &'static str,
+    fingerprint: String,
+    message: String,
+}
+
+/// Recomputes the rule-based insights for a user and stores them.
+///
+/// Insights are keyed by a fingerprint so that read and dismissed state survives
+/// recomputation. Insights whose rule no longer fires are removed unless dismissed.
+/// Messages use the user's saved locale and currency. Also stores the health score (0-100) of the
+/// most recent month, which is returned.
+pub async fn refresh_insights(pool: &SqlitePool, user_id: i64) -> Result<i64, sqlx::Error> {
+    let months: Vec<(i64, i32, i32)> = sqlx::query_as(
+        "SELECT id, year, month FROM months WHERE user_id = ? ORDER BY year DESC, month DESC LIMIT 4",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    let Some(&(latest_id, year, month)) = months.first() else {
+        sqlx::query("DELETE FROM insights WHERE user_id = ? AND is_dismissed = 0")
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+        save_health_score(pool, user_id, 100).await?;
+        return Ok(100);
+    };
+
+    let (saved_locale, currency): (Option<String>, Option<String>) =
+        sqlx::query_as("SELECT locale, currency FROM user_settings WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?
+            .unwrap_or_default();
+    let locale = Locale::resolve(saved_locale.as_deref(), None);
+    let money = MoneyFormat::new(
+        saved_locale.as_deref().unwrap_or(DEFAULT_LOCALE),
+        currency.as_deref().unwrap_or(DEFAULT_CURRENCY),
+    );
+
+    let mut generated = Vec::new();
+
+    let spent_by_category: Vec<(i64, i64, String, f64)> = sqlx::query_as(
+        r#"
+        SELECT i.month_id, i.category_id, bc.label, SUM(i.amount)
+        FROM items i
+        JOIN budget_categories bc ON i.category_id = bc.id
+        JOIN months m ON i.month_id = m.id
+        WHERE m.user_id = ? AND i.savings_destination = 'none' AND bc.tracking_only = 0
+        GROUP BY i.month_id, i.category_id
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    let previous_ids: Vec<i64> = months.iter().skip(1).map(|m| m.0).collect();
+    if !previous_ids.is_empty() {
+        let mut previous_totals: HashMap<i64, f64> = HashMap::new();
+        for (month_id, category_id, _, spent) in &spent_by_category {
+            if previous_ids.contains(month_id) {
+                *previous_totals.entry(*category_id).or_default() += spent;
+            }
+        }
+
+        for (month_id, category_id, label, spent) in &spent_by_category {
+            if *month_id != latest_id {
+                continue;
+            }
+            let average = previous_totals.get(category_id).copied().unwrap_or(0.0)
+                / previous_ids.len() as f64;
+            if average <= 0.0 {
+                continue;
+            }
+            let change_percent = (spent - average) / average * 100.0;
+            if change_percent >= SPIKE_THRESHOLD_PERCENT {
+                generated.push(GeneratedInsight {
+                    kind: "category_spike",
+                    fingerprint: format!("category_spike:{latest_id}:{category_id}"),
+                    message: locale.render(
+                        Text::InsightCategorySpike,
+                        &[
+                            ("label", label),
+                            ("percent", &format!("{change_percent:.0}")),
+                            ("months", &previous_ids.len().to_string()),
+                        ],
+                    ),
+                });
+            }
+        }
+    }
+
+    let total_income: f64 = sqlx::query_scalar(
+        "SELECT COALESCE(SUM(amount), 0.0) FROM income_entries WHERE month_id = ?",
+    )
+    .bind(latest_id)
+    .fetch_one(pool)
+    .await?;
+
+    let total_fixed: f64 = sqlx::query_scalar(
+        "SELECT COALESCE(SUM(amount), 0.0) FROM fixed_expenses WHERE user_id = ?",
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+
+    let fixed_too_high = total_income > 0.0 && total_fixed / total_income > FIXED_RATIO_THRESHOLD;
+    if fixed_too_high {
+        generated.push(GeneratedInsight {
+            kind: "fixed_cost_ratio",
+            fingerprint: format!("fixed_cost_ratio:{latest_id}"),
+            message: locale.render(
+                Text::InsightFixedCostRatio,
+                &[(
+                    "percent",
+                    &format!("{:.0}", total_fixed / total_income * 100.0),
+                )],
+            ),
+        });
+    }
+
+    let spend_days: Vec<(NaiveDate,)> = sqlx::query_as(
+        r#"
+        SELECT DISTINCT spent_on FROM items
+        WHERE month_id = ? AND savings_destination = 'none'
+          AND category_id NOT IN (SELECT id FROM budget_categories WHERE tracking_only = 1)
+        "#,
+    )
+    .bind(latest_id)
+    .fetch_all(pool)
+    .await?;
+    let spend_days: HashSet<NaiveDate> = spend_days.into_iter().map(|(d,)| d).collect();
+
+    let streak =
+        streaks::month_streaks(year, month, &spend_days, Utc::now().date_naive()).longest_streak;
+    if streak >= MIN_NO_SPEND_STREAK {
+        generated.push(GeneratedInsight {
+            kind: "no_spend_streak",
+            fingerprint: format!("no_spend_streak:{latest_id}"),
+            message: locale.render(Text::InsightNoSpendStreak, &[("days", &streak.to_string())]),
+        });
+    }
+
+    let today = Utc::now().date_naive();
+    let overdue_invoices: Vec<(i64, String, f64, NaiveDate)> = sqlx::query_as(
+        "SELECT id, client, amount, due_on FROM invoices WHERE user_id = ? AND status = 'sent' AND due_on < ?",
+    )
+    .bind(user_id)
+    .bind(today)
+    .fetch_all(pool)
+    .await?;
+    for (invoice_id, client, amount, due_on) in overdue_invoices {
+        generated.push(GeneratedInsight {
+            kind: "invoice_overdue",
+            fingerprint: format!("invoice_overdue:{invoice_id}"),
+            message: locale.render(
+                Text::InsightInvoiceOverdue,
+                &[
+                    ("client", &client),
+                    ("amount", &money.format(amount)),
+                    ("days", &(today - due_on).num_days().to_string()),
+                ],
+            ),
+        });
+    }
+
+    let overspent_categories: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM monthly_budgets mb
+        WHERE mb.month_id = ? AND mb.category_id NOT IN (SELECT id FROM budget_categories WHERE tracking_only = 1)
+          AND mb.allocated_amount < (
+            SELECT COALESCE(SUM(i.amount), 0.0) FROM items i
+            WHERE i.month_id = mb.month_id AND i.category_id = mb.category_id
+              AND i.savings_destination = 'none'
+        )
+        "#,
+    )
+    .bind(latest_id)
+    .fetch_one(pool)
+    .await?;
+
+    let total_spent: f64 = spent_by_category
+        .iter()
+        .filter(|(month_id, ..)| *month_id == latest_id)
+        .map(|(.., spent)| spent)
+        .sum();
+
+    let mut tx = pool.begin().await?;
+    let fingerprints: Vec<&str> = generated.iter().map(|g| g.fingerprint.as_str()).collect();
+    let existing: Vec<(i64, String, String)> = sqlx::query_as(
+        "SELECT id, kind, fingerprint FROM insights WHERE user_id = ? AND is_dismissed = 0",
+    )
+    .bind(user_id)
+    .fetch_all(&mut *tx)
+    .await?;
+    for (id, kind, fingerprint) in existing {
+        if !fingerprints.contains(&fingerprint.as_str()) && !EVENT_KINDS.contains(&kind.as_str()) {
+            sqlx::query("DELETE FROM insights WHERE id = ?")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+        }
+    }
+
+    for insight in &generated {
+        sqlx::query(
+            r#"
+            INSERT INTO insights (user_id, kind, fingerprint, message) VALUES (?, ?, ?, ?)
+            ON CONFLICT(user_id, fingerprint) DO UPDATE SET message = excluded.message
+            "#,
+        )
+        .bind(user_id)
+        .bind(insight.kind)
+        .bind(&insight.fingerprint)
+        .bind(&insight.message)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    let mut score = 100;
+    if fixed_too_high {
+        score -= 30;
+    }
+    score -= (overspent_categories * 10).min(40);
+    if total_income - total_fixed - total_spent < 0.0 {
+        score -= 30;
+    }
+
+    let score = score.max(0);
+    save_health_score(pool, user_id, score).await?;
+    Ok(score)
+}
+
+/// Stores an insight about something that just happened. One with the same fingerprint is
+/// replaced and shown again as a new, unread insight.
+pub async fn record_event<'e>(
+    executor: impl SqliteExecutor<'e>,
+    user_id: i64,
+    kind: &'static str,
+    fingerprint: &str,
+    message: &str,
+) -> Result<(), sqlx::Error> {
+    debug_assert!(EVENT_KINDS.contains(&kind));
+    sqlx::query(
+        r#"
+        INSERT INTO insights (user_id, kind, fingerprint, message) VALUES (?, ?, ?, ?)
+        ON CONFLICT(user_id, fingerprint) DO UPDATE SET
+            message = excluded.message,
+            is_read = 0,
+            is_dismissed = 0,
+            created_at = datetime('now')
+        "#,
+    )
+    .bind(user_id)
+    .bind(kind)
+    .bind(fingerprint)
+    .bind(message)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+async fn save_health_score(pool: &SqlitePool, user_id: i64, score: i64) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO insight_scores (user_id, health_score, computed_at) VALUES (?, ?, datetime('now'))
+        ON CONFLICT(user_id) DO UPDATE SET
+            health_score = excluded.health_score,
+            computed_at = excluded.computed_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(score)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Recomputes insights for every user once a night (UTC), queues seasonality
+/// detection for them and removes stored files nothing refers to any more.
+pub async fn run_nightly_refresh(pool: SqlitePool) {
+    loop {
+        let now = Utc::now();
+        let next_midnight = (now.date_naive() + Duration::days(1))
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc();
+        let wait = (next_midnight - now).to_std().unwrap_or_default();
+        tokio::time::sleep(wait).await;
+
+        let users: Vec<(i64,)> = match sqlx::query_as("SELECT id FROM users")
+            .fetch_all(&pool)
+            .await
+        {
+            Ok(users) => users,
+            Err(e) => {
+                tracing::error!("Failed to list users for insights refresh: {e}");
+                continue;
+            }
+        };
+
+        for (user_id,) in users {
+            if let Err(e) = refresh_insights(&pool, user_id).await {
+                tracing::error!("Failed to refresh insights for user {user_id}: {e}");
+            }
+            if let Err(e) = jobs::enqueue(&pool, user_id, jobs::SEASONALITY, &()).await {
+                tracing::error!("Failed to queue seasonality detection for user {user_id}: {e}");
+            }
+        }
+        jobs::wake();
+
+        match storage::remove_orphans(&pool, storage::backend(&pool).as_ref(), Utc::now()).await {
+            Ok(0) => {}
+            Ok(count) => tracing::info!("Removed {count} orphaned stored files"),
+            Err(e) => tracing::error!("Failed to remove orphaned stored files: {e}"),
+        }
+        if let Err(e) = sync::prune_deletions(&pool, Utc::now() - sync::HISTORY_KEPT).await {
+            tracing::error!("Failed to prune sync deletions: {e}");
+        }
+        if let Err(e) = sync::prune_mutations(&pool, Utc::now() - sync::HISTORY_KEPT).await {
+            tracing::error!("Failed to prune applied sync mutations: {e}");
+        }
+    }
+}
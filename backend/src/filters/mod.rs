@@ -0,0 +1,214 @@
+use chrono::NaiveDate;
+use serde::Deserialize;
+use sqlx::{QueryBuilder, Sqlite};
+use utoipa::IntoParams;
+use validator::{ValidationError, ValidationErrors};
+
+use crate::error::PaymeError;
+
+/// Query parameters shared by the collection listings. Each listing says which
+/// columns they apply to; asking for a filter a listing has no column for is a
+/// validation error rather than being ignored.
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListFilter {
+    /// Only entries in this category.
+    pub category_id: Option<i64>,
+    /// Only entries on or after this date (`YYYY-MM-DD`).
+    pub from: Option<NaiveDate>,
+    /// Only entries on or before this date (`YYYY-MM-DD`).
+    pub to: Option<NaiveDate>,
+    /// Only entries of at least this amount.
+    pub min_amount: Option<f64>,
+    /// Only entries of at most this amount.
+    pub max_amount: Option<f64>,
+    /// Only entries whose description or label contains this text, ignoring case.
+    pub q: Option<String>,
+}
+
+/// SQL expressions a listing filters on. They come from the code, never from the
+/// request; every value from the request is bound.
+pub struct Columns {
+    pub category: Option<&'static str>,
+    /// Must evaluate to a `YYYY-MM-DD` string.
+    pub date: Option<&'static str>,
+    pub amount: Option<&'static str>,
+    pub text: &'static [&'static str],
+}
+
+impl ListFilter {
+    /// Appends ` AND ...` conditions for the filters that are set to a query that
+    /// already has a `WHERE` clause.
+    pub fn apply(
+        &self,
+        query: &mut QueryBuilder<'_, Sqlite>,
+        columns: &Columns,
+    ) -> Result<(), PaymeError> {
+        self.check(columns)?;
+
+        if let (Some(column), Some(category_id)) = (columns.category, self.category_id) {
+            query
+                .push(format!(" AND {column} = "))
+                .push_bind(category_id);
+        }
+        if let Some(column) = columns.date {
+            if let Some(from) = self.from {
+                query
+                    .push(format!(" AND {column} >= "))
+                    .push_bind(from.to_string());
+            }
+            if let Some(to) = self.to {
+                query
+                    .push(format!(" AND {column} <= "))
+                    .push_bind(to.to_string());
+            }
+        }
+        if let Some(column) = columns.amount {
+            if let Some(min) = self.min_amount {
+                query.push(format!(" AND {column} >= ")).push_bind(min);
+            }
+            if let Some(max) = self.max_amount {
+                query.push(format!(" AND {column} <= ")).push_bind(max);
+            }
+        }
+        if let Some(text) = self.text() {
+            let pattern = format!("%{}%", escape_like(&text));
+            query.push(" AND (");
+            for (i, column) in columns.text.iter().enumerate() {
+                if i > 0 {
+                    query.push(" OR ");
+                }
+                query
+                    .push(format!("{column} LIKE "))
+                    .push_bind(pattern.clone())
+                    .push(" ESCAPE '\\'");
+            }
+            query.push(")");
+        }
+        Ok(())
+    }
+
+    fn text(&self) -> Option<String> {
+        self.q
+            .as_deref()
+            .map(str::trim)
+            .filter(|q| !q.is_empty())
+            .map(str::to_string)
+    }
+
+    fn check(&self, columns: &Columns) -> Result<(), PaymeError> {
+        let mut errors = ValidationErrors::new();
+        let unsupported = [
+            (
+                "category_id",
+                self.category_id.is_some() && columns.category.is_none(),
+            ),
+            ("from", self.from.is_some() && columns.date.is_none()),
+            ("to", self.to.is_some() && columns.date.is_none()),
+            (
+                "min_amount",
+                self.min_amount.is_some() && columns.amount.is_none(),
+            ),
+            (
+                "max_amount",
+                self.max_amount.is_some() && columns.amount.is_none(),
+            ),
+            ("q", self.text().is_some() && columns.text.is_empty()),
+        ];
+        for (field, unsupported) in unsupported {
+            if unsupported {
+                errors.add(field, ValidationError::new("unsupported"));
+            }
+        }
+        if matches!((self.from, self.to), (Some(from), Some(to)) if from > to) {
+            errors.add("to", ValidationError::new("range"));
+        }
+        if matches!((self.min_amount, self.max_amount), (Some(min), Some(max)) if min > max) {
+            errors.add("max_amount", ValidationError::new("range"));
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.into())
+        }
+    }
+}
+
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLUMNS: Columns = Columns {
+        category: Some("category_id"),
+        date: Some("spent_on"),
+        amount: Some("amount"),
+        text: &["description", "label"],
+    };
+
+    fn sql(filter: &ListFilter, columns: &Columns) -> Result<String, PaymeError> {
+        let mut query = QueryBuilder::new("SELECT * FROM items WHERE month_id = 1");
+        filter.apply(&mut query, columns)?;
+        Ok(query.sql().to_string())
+    }
+
+    #[test]
+    fn test_no_filter_adds_nothing() {
+        let sql = sql(&ListFilter::default(), &COLUMNS).unwrap();
+        assert_eq!(sql, "SELECT * FROM items WHERE month_id = 1");
+    }
+
+    #[test]
+    fn test_filters_are_bound() {
+        let filter = ListFilter {
+            category_id: Some(3),
+            from: NaiveDate::from_ymd_opt(2024, 6, 1),
+            max_amount: Some(50.0),
+            q: Some(" coffee ".to_string()),
+            ..Default::default()
+        };
+        let sql = sql(&filter, &COLUMNS).unwrap();
+        assert_eq!(
+            sql,
+            "SELECT * FROM items WHERE month_id = 1 AND category_id = ? AND spent_on >= ? \
+             AND amount <= ? AND (description LIKE ? ESCAPE '\\' OR label LIKE ? ESCAPE '\\')"
+        );
+    }
+
+    #[test]
+    fn test_unsupported_and_inverted_filters_are_rejected() {
+        let columns = Columns {
+            category: None,
+            date: None,
+            amount: Some("amount"),
+            text: &[],
+        };
+        let filter = ListFilter {
+            category_id: Some(3),
+            min_amount: Some(10.0),
+            max_amount: Some(5.0),
+            ..Default::default()
+        };
+        let Err(PaymeError::Validation(errors)) = sql(&filter, &columns) else {
+            panic!("expected a validation error");
+        };
+        let mut fields: Vec<_> = errors
+            .field_errors()
+            .keys()
+            .map(|f| f.to_string())
+            .collect();
+        fields.sort();
+        assert_eq!(fields, ["category_id", "max_amount"]);
+    }
+
+    #[test]
+    fn test_escape_like() {
+        assert_eq!(escape_like(r"50%_off\"), r"50\%\_off\\");
+    }
+}
@@ -0,0 +1,72 @@
+use axum::{
+    body::Body,
+    http::{
+        header::{CACHE_CONTROL, CONTENT_TYPE},
+        StatusCode, Uri,
+    },
+    response::{IntoResponse, Response},
+};
+use rust_embed::RustEmbed;
+
+/// The built frontend, embedded at compile time when `frontend/dist` exists. Builds
+/// without it serve the API only, e.g. when the frontend runs on the Vite dev server.
+#[derive(RustEmbed)]
+#[folder = "../frontend/dist"]
+#[allow_missing = true]
+struct Assets;
+
+/// Vite fingerprints everything under `assets/`, so those never change in place.
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+/// `index.html` and unhashed files such as the favicon are revalidated on each load.
+const REVALIDATE: &str = "no-cache";
+
+/// Whether this binary carries a frontend build.
+pub fn is_embedded() -> bool {
+    Assets::get("index.html").is_some()
+}
+
+/// Serves embedded files, falling back to `index.html` so client-side routes work on
+/// reload. Paths under `/api` never fall back, so unknown endpoints stay 404s.
+pub async fn serve(uri: Uri) -> Response {
+    let path = uri.path().trim_start_matches('/');
+    if path == "api" || path.starts_with("api/") {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    let (path, file) = match Assets::get(path) {
+        Some(file) if !path.is_empty() => (path, file),
+        _ => match Assets::get("index.html") {
+            Some(file) => ("index.html", file),
+            None => return StatusCode::NOT_FOUND.into_response(),
+        },
+    };
+
+    (
+        [
+            (CONTENT_TYPE, file.metadata.mimetype().to_string()),
+            (CACHE_CONTROL, cache_control(path).to_string()),
+        ],
+        Body::from(file.data),
+    )
+        .into_response()
+}
+
+fn cache_control(path: &str) -> &'static str {
+    if path.starts_with("assets/") {
+        IMMUTABLE
+    } else {
+        REVALIDATE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_only_hashed_assets_are_cached() {
+        assert_eq!(cache_control("assets/index-3f2a1b.js"), IMMUTABLE);
+        assert_eq!(cache_control("index.html"), REVALIDATE);
+        assert_eq!(cache_control("favicon.svg"), REVALIDATE);
+    }
+}
@@ -0,0 +1,327 @@
+use std::collections::BTreeMap;
+
+use axum::{extract::State, http::HeaderMap, Extension};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use utoipa::ToSchema;
+
+use crate::db;
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::handlers::usage::{usage_for, UsageReport};
+use crate::logging;
+use crate::middleware::maintenance::MaintenanceMode;
+
+#[derive(Serialize, ToSchema)]
+pub struct MaintenanceStatus {
+    pub read_only: bool,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateMaintenance {
+    pub read_only: bool,
+}
+
+/// Used both to report and to change the level.
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct LogLevel {
+    /// `off`, `error`, `warn`, `info`, `debug` or `trace`.
+    pub level: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct MigrationInfo {
+    pub version: i64,
+    pub description: String,
+    /// When the migration ran. Absent for pending migrations.
+    pub installed_on: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct MigrationStatus {
+    pub applied: Vec<MigrationInfo>,
+    /// Migrations built into this binary that have not run yet.
+    pub pending: Vec<MigrationInfo>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct IntegrityReport {
+    /// Rows per table whose foreign key points at a missing parent.
+    pub orphans: BTreeMap<String, i64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct IntegrityRepair {
+    /// Rows deleted per table.
+    pub removed: BTreeMap<String, i64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct UserUsage {
+    pub user_id: i64,
+    pub username: String,
+    #[serde(flatten)]
+    pub usage: UsageReport,
+}
+
+fn require_admin(mode: &MaintenanceMode, headers: &HeaderMap) -> Result<(), PaymeError> {
+    let token = headers.get("X-Admin-Token").and_then(|v| v.to_str().ok());
+    if mode.is_admin(token) {
+        Ok(())
+    } else {
+        Err(PaymeError::Unauthorized)
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/maintenance",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, body = MaintenanceStatus),
+        (status = 401, description = "Missing or wrong admin token")
+    ),
+    tag = "Admin",
+    summary = "Get maintenance mode",
+    description = "Reports whether the API is currently read-only."
+)]
+pub async fn get_maintenance(
+    Extension(mode): Extension<MaintenanceMode>,
+    headers: HeaderMap,
+) -> Result<Json<MaintenanceStatus>, PaymeError> {
+    require_admin(&mode, &headers)?;
+
+    Ok(Json(MaintenanceStatus {
+        read_only: mode.is_read_only(),
+    }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/maintenance",
+    security(("admin_token" = [])),
+    request_body = UpdateMaintenance,
+    responses(
+        (status = 200, body = MaintenanceStatus),
+        (status = 401, description = "Missing or wrong admin token")
+    ),
+    tag = "Admin",
+    summary = "Toggle maintenance mode",
+    description = "Switches read-only mode on or off. While read-only, every request that could write data is rejected with 503, so backups and migrations don't race with writes."
+)]
+pub async fn update_maintenance(
+    Extension(mode): Extension<MaintenanceMode>,
+    headers: HeaderMap,
+    payload: Result<Json<UpdateMaintenance>, PaymeError>,
+) -> Result<Json<MaintenanceStatus>, PaymeError> {
+    // Checked before the body so callers without the token learn nothing about it
+    require_admin(&mode, &headers)?;
+    let Json(payload) = payload?;
+
+    mode.set_read_only(payload.read_only);
+    tracing::warn!(
+        "Maintenance mode {}",
+        if payload.read_only {
+            "enabled: API is read-only"
+        } else {
+            "disabled"
+        }
+    );
+
+    Ok(Json(MaintenanceStatus {
+        read_only: mode.is_read_only(),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/integrity",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, body = IntegrityReport),
+        (status = 401, description = "Missing or wrong admin token")
+    ),
+    tag = "Admin",
+    summary = "Check referential integrity",
+    description = "Counts rows whose foreign key points at a deleted parent, e.g. items of a deleted category. These can only appear in databases edited with foreign keys switched off."
+)]
+pub async fn get_integrity(
+    State(pool): State<SqlitePool>,
+    Extension(mode): Extension<MaintenanceMode>,
+    headers: HeaderMap,
+) -> Result<Json<IntegrityReport>, PaymeError> {
+    require_admin(&mode, &headers)?;
+
+    Ok(Json(IntegrityReport {
+        orphans: db::find_orphans(&pool).await?,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/admin/integrity/repair",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, body = IntegrityRepair),
+        (status = 401, description = "Missing or wrong admin token")
+    ),
+    tag = "Admin",
+    summary = "Repair referential integrity",
+    description = "Deletes every row whose foreign key points at a missing parent, in one transaction, and reports how many rows were removed per table."
+)]
+pub async fn repair_integrity(
+    State(pool): State<SqlitePool>,
+    Extension(mode): Extension<MaintenanceMode>,
+    headers: HeaderMap,
+) -> Result<Json<IntegrityRepair>, PaymeError> {
+    require_admin(&mode, &headers)?;
+
+    let removed = db::repair_orphans(&pool).await?;
+    if !removed.is_empty() {
+        tracing::warn!("Removed rows with missing parents: {removed:?}");
+    }
+
+    Ok(Json(IntegrityRepair { removed }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/migrations",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, body = MigrationStatus),
+        (status = 401, description = "Missing or wrong admin token")
+    ),
+    tag = "Admin",
+    summary = "Get migration status",
+    description = "Lists the schema migrations applied to the database and those built into the running server that have not been applied. Pending migrations run automatically at startup."
+)]
+pub async fn get_migrations(
+    State(pool): State<SqlitePool>,
+    Extension(mode): Extension<MaintenanceMode>,
+    headers: HeaderMap,
+) -> Result<Json<MigrationStatus>, PaymeError> {
+    require_admin(&mode, &headers)?;
+
+    let applied: Vec<MigrationInfo> = db::applied_migrations(&pool)
+        .await?
+        .into_iter()
+        .map(|(version, description, installed_on)| MigrationInfo {
+            version,
+            description,
+            installed_on: Some(installed_on),
+        })
+        .collect();
+    let pending = db::MIGRATOR
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .filter(|m| applied.iter().all(|a| a.version != m.version))
+        .map(|m| MigrationInfo {
+            version: m.version,
+            description: m.description.to_string(),
+            installed_on: None,
+        })
+        .collect();
+
+    Ok(Json(MigrationStatus { applied, pending }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/log-level",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, body = LogLevel),
+        (status = 401, description = "Missing or wrong admin token")
+    ),
+    tag = "Admin",
+    summary = "Get log level",
+    description = "Returns the level the server currently logs at."
+)]
+pub async fn get_log_level(
+    Extension(mode): Extension<MaintenanceMode>,
+    headers: HeaderMap,
+) -> Result<Json<LogLevel>, PaymeError> {
+    require_admin(&mode, &headers)?;
+
+    Ok(Json(LogLevel {
+        level: logging::level().to_string().to_lowercase(),
+    }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/admin/log-level",
+    security(("admin_token" = [])),
+    request_body = LogLevel,
+    responses(
+        (status = 200, body = LogLevel),
+        (status = 400, description = "Unknown level"),
+        (status = 401, description = "Missing or wrong admin token")
+    ),
+    tag = "Admin",
+    summary = "Set log level",
+    description = "Changes the log level until the next restart, e.g. to `debug` while investigating a problem. `LOG_LEVEL` sets the level at startup."
+)]
+pub async fn update_log_level(
+    Extension(mode): Extension<MaintenanceMode>,
+    headers: HeaderMap,
+    payload: Result<Json<LogLevel>, PaymeError>,
+) -> Result<Json<LogLevel>, PaymeError> {
+    require_admin(&mode, &headers)?;
+    let Json(payload) = payload?;
+
+    let level = payload
+        .level
+        .trim()
+        .parse()
+        .map_err(|_| PaymeError::BadRequest(format!("Unknown log level {}", payload.level)))?;
+    logging::set_level(level).map_err(PaymeError::Internal)?;
+    tracing::info!("Log level set to {level}");
+
+    Ok(Json(LogLevel {
+        level: level.to_string().to_lowercase(),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/admin/usage",
+    security(("admin_token" = [])),
+    responses(
+        (status = 200, body = [UserUsage]),
+        (status = 401, description = "Missing or wrong admin token")
+    ),
+    tag = "Admin",
+    summary = "Get data usage per user",
+    description = "Reports the data usage of every account, largest storage first."
+)]
+pub async fn get_usage(
+    State(pool): State<SqlitePool>,
+    Extension(mode): Extension<MaintenanceMode>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<UserUsage>>, PaymeError> {
+    require_admin(&mode, &headers)?;
+
+    let users: Vec<(i64, String)> = sqlx::query_as("SELECT id, username FROM users")
+        .fetch_all(&pool)
+        .await?;
+
+    let mut usage = Vec::with_capacity(users.len());
+    for (user_id, username) in users {
+        usage.push(UserUsage {
+            user_id,
+            username,
+            usage: usage_for(&pool, user_id).await?,
+        });
+    }
+    usage.sort_by(|a, b| {
+        b.usage
+            .total_bytes
+            .cmp(&a.usage.total_bytes)
+            .then(a.user_id.cmp(&b.user_id))
+    });
+
+    Ok(Json(usage))
+}
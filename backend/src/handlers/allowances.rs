@@ -0,0 +1,356 @@
+use axum::extract::{Path, State};
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use utoipa::ToSchema;
+use validator::{Validate, ValidationErrors};
+
+use crate::activity;
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::handlers::items::verify_category_unlocked;
+use crate::handlers::settings::{load_settings, money_format};
+use crate::middleware::auth::Claims;
+use crate::models::{Item, ItemCalculation};
+use crate::quotas;
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateMileage {
+    pub category_id: i64,
+    #[validate(range(exclusive_min = 0.0, max = 100000.0))]
+    pub distance: f64,
+    /// Amount per unit of distance, the `mileage_rate` setting by default.
+    #[validate(range(min = 0.0), custom(function = "crate::money::amount"))]
+    pub rate: Option<f64>,
+    pub spent_on: NaiveDate,
+    /// Prefixed to the generated description, e.g. `Client visit`.
+    #[validate(length(min = 1, max = 150))]
+    pub purpose: Option<String>,
+    #[serde(default)]
+    pub reimbursable: bool,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreatePerDiem {
+    pub category_id: i64,
+    /// Number of days, half days allowed.
+    #[validate(range(exclusive_min = 0.0, max = 366.0))]
+    pub days: f64,
+    /// Daily allowance, the `per_diem_rate` setting by default.
+    #[validate(range(min = 0.0), custom(function = "crate::money::amount"))]
+    pub rate: Option<f64>,
+    pub spent_on: NaiveDate,
+    #[validate(length(min = 1, max = 150))]
+    pub purpose: Option<String>,
+    #[serde(default)]
+    pub reimbursable: bool,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct Recalculate {
+    /// New rate, the current setting by default.
+    #[validate(range(min = 0.0), custom(function = "crate::money::amount"))]
+    pub rate: Option<f64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CalculatedItem {
+    #[serde(flatten)]
+    pub item: Item,
+    pub calculation: ItemCalculation,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/months/{id}/items/mileage",
+    params(("id" = i64, Path, description = "Month ID")),
+    request_body = CreateMileage,
+    responses(
+        (status = 200, body = CalculatedItem),
+        (status = 400, description = "No rate given or configured, invalid category, or the month is closed"),
+        (status = 403, description = "Category is locked for this month"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Items",
+    summary = "Record mileage",
+    description = "Records distance times the mileage rate as an item with a generated description. The distance and rate are kept with the item for the PDF and for recalculating after a rate change."
+)]
+pub async fn create_mileage(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(month_id): Path<i64>,
+    Json(payload): Json<CreateMileage>,
+) -> Result<Json<CalculatedItem>, PaymeError> {
+    payload.validate()?;
+    let rate = match payload.rate {
+        Some(rate) => rate,
+        None => configured_rate(&pool, claims.sub, "mileage").await?,
+    };
+    let calculation = ItemCalculation {
+        kind: "mileage".to_string(),
+        quantity: payload.distance,
+        rate,
+        purpose: trimmed(payload.purpose),
+    };
+
+    record(
+        &pool,
+        &claims,
+        month_id,
+        payload.category_id,
+        payload.spent_on,
+        payload.reimbursable,
+        calculation,
+    )
+    .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/months/{id}/items/per-diem",
+    params(("id" = i64, Path, description = "Month ID")),
+    request_body = CreatePerDiem,
+    responses(
+        (status = 200, body = CalculatedItem),
+        (status = 400, description = "No rate given or configured, invalid category, or the month is closed"),
+        (status = 403, description = "Category is locked for this month"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Items",
+    summary = "Record a per diem",
+    description = "Records days times the daily allowance as an item with a generated description. The days and rate are kept with the item for the PDF and for recalculating after a rate change."
+)]
+pub async fn create_per_diem(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(month_id): Path<i64>,
+    Json(payload): Json<CreatePerDiem>,
+) -> Result<Json<CalculatedItem>, PaymeError> {
+    payload.validate()?;
+    let rate = match payload.rate {
+        Some(rate) => rate,
+        None => configured_rate(&pool, claims.sub, "per_diem").await?,
+    };
+    let calculation = ItemCalculation {
+        kind: "per_diem".to_string(),
+        quantity: payload.days,
+        rate,
+        purpose: trimmed(payload.purpose),
+    };
+
+    record(
+        &pool,
+        &claims,
+        month_id,
+        payload.category_id,
+        payload.spent_on,
+        payload.reimbursable,
+        calculation,
+    )
+    .await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/months/{month_id}/items/{id}/recalculate",
+    params(
+        ("month_id" = i64, Path, description = "Month ID"),
+        ("id" = i64, Path, description = "Item (Transaction) ID")
+    ),
+    request_body = Recalculate,
+    responses(
+        (status = 200, body = CalculatedItem),
+        (status = 400, description = "Not a mileage or per-diem item, no rate configured, or the month is closed"),
+        (status = 404, description = "Item not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Items",
+    summary = "Recalculate with a new rate",
+    description = "Applies a new rate, or the one currently in settings, to a mileage or per-diem item and updates its amount and description."
+)]
+pub async fn recalculate(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path((month_id, item_id)): Path<(i64, i64)>,
+    Json(payload): Json<Recalculate>,
+) -> Result<Json<CalculatedItem>, PaymeError> {
+    payload.validate()?;
+    verify_month_open(&pool, claims.sub, month_id).await?;
+
+    let mut calculation: ItemCalculation = sqlx::query_as(
+        r#"
+        SELECT c.kind, c.quantity, c.rate, c.purpose
+        FROM item_calculations c
+        JOIN items i ON c.item_id = i.id
+        WHERE c.item_id = ? AND i.month_id = ?
+        "#,
+    )
+    .bind(item_id)
+    .bind(month_id)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or_else(|| PaymeError::BadRequest("Item is not a mileage or per-diem item".to_string()))?;
+    calculation.rate = match payload.rate {
+        Some(rate) => rate,
+        None => configured_rate(&pool, claims.sub, &calculation.kind).await?,
+    };
+
+    let amount = checked_amount(&calculation)?;
+
+    let mut tx = pool.begin().await?;
+    let item: Item = sqlx::query_as(
+        r#"
+        UPDATE items SET amount = ?, description = ?, tax_amount = ROUND(? * tax_rate / (100 + tax_rate), 2)
+        WHERE id = ?
+        RETURNING id, month_id, category_id, description, amount, spent_on, savings_destination, reimbursement_status, tax_rate, tax_amount, paid_in_cash, uuid
+        "#,
+    )
+    .bind(amount)
+    .bind(calculation.description())
+    .bind(amount)
+    .bind(item_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    sqlx::query("UPDATE item_calculations SET rate = ? WHERE item_id = ?")
+        .bind(calculation.rate)
+        .bind(item_id)
+        .execute(&mut *tx)
+        .await?;
+    let money = money_format(&mut *tx, claims.sub).await?;
+    activity::record(
+        &mut *tx,
+        &claims,
+        Some(month_id),
+        "item",
+        item_id,
+        "update",
+        format!(
+            "{} recalculated {} {}",
+            claims.username,
+            item.description,
+            money.format(item.amount)
+        ),
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(Json(CalculatedItem { item, calculation }))
+}
+
+/// The computed amount, held to the same limits as one typed in.
+fn checked_amount(calculation: &ItemCalculation) -> Result<f64, PaymeError> {
+    let amount = calculation.amount();
+    crate::money::amount(amount).map_err(|error| {
+        let mut errors = ValidationErrors::new();
+        errors.add("amount", error);
+        errors
+    })?;
+    Ok(amount)
+}
+
+/// Inserts the item and its calculation, after the same checks as a regular item.
+async fn record(
+    pool: &SqlitePool,
+    claims: &Claims,
+    month_id: i64,
+    category_id: i64,
+    spent_on: NaiveDate,
+    reimbursable: bool,
+    calculation: ItemCalculation,
+) -> Result<Json<CalculatedItem>, PaymeError> {
+    verify_month_open(pool, claims.sub, month_id).await?;
+    let _category: (i64,) =
+        sqlx::query_as("SELECT id FROM budget_categories WHERE id = ? AND user_id = ?")
+            .bind(category_id)
+            .bind(claims.sub)
+            .fetch_optional(pool)
+            .await?
+            .ok_or(PaymeError::BadRequest("Invalid category".to_string()))?;
+    verify_category_unlocked(pool, month_id, category_id).await?;
+    quotas::check_items(pool, month_id, 1).await?;
+    let amount = checked_amount(&calculation)?;
+
+    let mut tx = pool.begin().await?;
+    let item: Item = sqlx::query_as(
+        r#"
+        INSERT INTO items (month_id, category_id, description, amount, spent_on, savings_destination, reimbursement_status, created_at)
+        VALUES (?, ?, ?, ?, ?, 'none', ?, ?)
+        RETURNING id, month_id, category_id, description, amount, spent_on, savings_destination, reimbursement_status, tax_rate, tax_amount, paid_in_cash, uuid
+        "#,
+    )
+    .bind(month_id)
+    .bind(category_id)
+    .bind(calculation.description())
+    .bind(amount)
+    .bind(spent_on)
+    .bind(reimbursable.then_some("pending"))
+    .bind(Utc::now())
+    .fetch_one(&mut *tx)
+    .await?;
+    sqlx::query(
+        "INSERT INTO item_calculations (item_id, kind, quantity, rate, purpose) VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(item.id)
+    .bind(&calculation.kind)
+    .bind(calculation.quantity)
+    .bind(calculation.rate)
+    .bind(&calculation.purpose)
+    .execute(&mut *tx)
+    .await?;
+    let money = money_format(&mut *tx, claims.sub).await?;
+    activity::record(
+        &mut *tx,
+        claims,
+        Some(month_id),
+        "item",
+        item.id,
+        "create",
+        format!(
+            "{} added {} {}",
+            claims.username,
+            item.description,
+            money.format(item.amount)
+        ),
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(Json(CalculatedItem { item, calculation }))
+}
+
+/// The user's saved rate for `mileage` or `per_diem` items.
+async fn configured_rate(pool: &SqlitePool, user_id: i64, kind: &str) -> Result<f64, PaymeError> {
+    let settings = load_settings(pool, user_id).await?;
+    let (rate, setting) = match kind {
+        "per_diem" => (settings.per_diem_rate, "per_diem_rate"),
+        _ => (settings.mileage_rate, "mileage_rate"),
+    };
+    rate.ok_or_else(|| PaymeError::BadRequest(format!("Pass a rate or set {setting} in settings")))
+}
+
+async fn verify_month_open(
+    pool: &SqlitePool,
+    user_id: i64,
+    month_id: i64,
+) -> Result<(), PaymeError> {
+    let month: (bool,) =
+        sqlx::query_as("SELECT is_closed FROM months WHERE id = ? AND user_id = ?")
+            .bind(month_id)
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or(PaymeError::NotFound)?;
+
+    if month.0 {
+        return Err(PaymeError::BadRequest("Month is closed".to_string()));
+    }
+    Ok(())
+}
+
+fn trimmed(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
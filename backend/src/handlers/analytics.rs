@@ -0,0 +1,340 @@
+use axum::extract::{Query, State};
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use utoipa::{IntoParams, ToSchema};
+
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::jobs;
+use crate::middleware::auth::Claims;
+use crate::models::{
+    DescriptionStats, ItemWithCategory, MonthNoSpend, SeasonalCategory, SeasonalityResponse,
+    StreaksResponse, TopSpendingResponse,
+};
+use crate::streaks;
+
+fn default_period() -> String {
+    "90d".to_string()
+}
+
+fn default_limit() -> i64 {
+    10
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TopSpendingQuery {
+    /// Look-back window such as `30d`, `12w`, `6m` or `1y`. Defaults to `90d`.
+    #[serde(default = "default_period")]
+    pub period: String,
+    /// Only consider items in this category.
+    pub category_id: Option<i64>,
+    /// Entries per list, 1-50. Defaults to 10.
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/analytics/top",
+    params(TopSpendingQuery),
+    responses(
+        (status = 200, body = TopSpendingResponse),
+        (status = 400, description = "Invalid period or limit"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Insights",
+    summary = "Top spending",
+    description = "Ranks descriptions by total spend and by number of purchases, and lists the largest single transactions over a recent period. Savings transfers are excluded."
+)]
+pub async fn get_top_spending(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Query(query): Query<TopSpendingQuery>,
+) -> Result<Json<TopSpendingResponse>, PaymeError> {
+    let days = parse_period_days(&query.period)
+        .ok_or_else(|| PaymeError::BadRequest(format!("Invalid period: {}", query.period)))?;
+    if !(1..=50).contains(&query.limit) {
+        return Err(PaymeError::BadRequest(
+            "Limit must be between 1 and 50".to_string(),
+        ));
+    }
+    let since: NaiveDate = Utc::now().date_naive() - Duration::days(days);
+
+    let grouped = |order_by: &str| {
+        format!(
+            r#"
+            SELECT MIN(i.description) as description, SUM(i.amount) as total_spent,
+                   COUNT(*) as purchase_count
+            FROM items i
+            JOIN months m ON i.month_id = m.id
+            WHERE m.user_id = ? AND i.spent_on >= ? AND i.savings_destination = 'none'
+              AND i.category_id NOT IN (SELECT id FROM budget_categories WHERE tracking_only = 1)
+              AND (? IS NULL OR i.category_id = ?)
+            GROUP BY LOWER(TRIM(i.description))
+            ORDER BY {order_by}
+            LIMIT ?
+            "#
+        )
+    };
+
+    let top_by_spend: Vec<DescriptionStats> =
+        sqlx::query_as(&grouped("total_spent DESC, purchase_count DESC"))
+            .bind(claims.sub)
+            .bind(since)
+            .bind(query.category_id)
+            .bind(query.category_id)
+            .bind(query.limit)
+            .fetch_all(&pool)
+            .await?;
+
+    let most_frequent: Vec<DescriptionStats> =
+        sqlx::query_as(&grouped("purchase_count DESC, total_spent DESC"))
+            .bind(claims.sub)
+            .bind(since)
+            .bind(query.category_id)
+            .bind(query.category_id)
+            .bind(query.limit)
+            .fetch_all(&pool)
+            .await?;
+
+    let largest_transactions: Vec<ItemWithCategory> = sqlx::query_as(
+        r#"
+        SELECT i.id, i.month_id, i.category_id, bc.label as category_label, i.description, i.amount, i.spent_on, i.savings_destination, i.reimbursement_status, i.tax_rate, i.tax_amount, i.paid_in_cash, i.uuid, i.client, i.billable
+        FROM items i
+        JOIN months m ON i.month_id = m.id
+        JOIN budget_categories bc ON i.category_id = bc.id
+        WHERE m.user_id = ? AND i.spent_on >= ? AND i.savings_destination = 'none'
+          AND bc.tracking_only = 0
+          AND (? IS NULL OR i.category_id = ?)
+        ORDER BY i.amount DESC, i.spent_on DESC
+        LIMIT ?
+        "#,
+    )
+    .bind(claims.sub)
+    .bind(since)
+    .bind(query.category_id)
+    .bind(query.category_id)
+    .bind(query.limit)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(TopSpendingResponse {
+        since,
+        category_id: query.category_id,
+        top_by_spend,
+        largest_transactions,
+        most_frequent,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/analytics/streaks",
+    responses(
+        (status = 200, body = StreaksResponse),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Insights",
+    summary = "No-spend streaks",
+    description = "Counts days without spending per month, plus the current and best runs of such days since the first tracked month. Fixed expenses and savings transfers do not count as spending."
+)]
+pub async fn get_streaks(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+) -> Result<Json<StreaksResponse>, PaymeError> {
+    let months: Vec<(i32, i32)> = sqlx::query_as(
+        "SELECT year, month FROM months WHERE user_id = ? ORDER BY year DESC, month DESC",
+    )
+    .bind(claims.sub)
+    .fetch_all(&pool)
+    .await?;
+
+    let spend_days: Vec<(NaiveDate,)> = sqlx::query_as(
+        r#"
+        SELECT DISTINCT i.spent_on FROM items i
+        JOIN months m ON i.month_id = m.id
+        WHERE m.user_id = ? AND i.savings_destination = 'none'
+          AND i.category_id NOT IN (SELECT id FROM budget_categories WHERE tracking_only = 1)
+        "#,
+    )
+    .bind(claims.sub)
+    .fetch_all(&pool)
+    .await?;
+    let spend_days: HashSet<NaiveDate> = spend_days.into_iter().map(|(d,)| d).collect();
+
+    let today = Utc::now().date_naive();
+    let first_tracked_day = months
+        .iter()
+        .filter_map(|(year, month)| NaiveDate::from_ymd_opt(*year, *month as u32, 1))
+        .min();
+    let (best_streak, current_streak) = match first_tracked_day {
+        Some(start) => streaks::overall_streaks(start, &spend_days, today),
+        None => (0, 0),
+    };
+
+    let months = months
+        .into_iter()
+        .map(|(year, month)| {
+            let month_streaks = streaks::month_streaks(year, month, &spend_days, today);
+            MonthNoSpend {
+                year,
+                month,
+                no_spend_days: month_streaks.no_spend_days,
+                longest_streak: month_streaks.longest_streak,
+            }
+        })
+        .collect();
+
+    Ok(Json(StreaksResponse {
+        current_streak,
+        best_streak,
+        months,
+    }))
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SeasonalityRefresh {
+    /// Poll it at `/api/v1/jobs/{id}`.
+    pub job_id: i64,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/analytics/seasonality",
+    responses(
+        (status = 200, body = SeasonalityResponse),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Insights",
+    summary = "Seasonal categories",
+    description = "Lists categories whose spending follows the seasons, with the spending expected in each calendar month instead of a flat average. Detection needs at least 12 months of history and runs nightly or on demand."
+)]
+pub async fn get_seasonality(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+) -> Result<Json<SeasonalityResponse>, PaymeError> {
+    let computed_at: Option<DateTime<Utc>> = sqlx::query_scalar(
+        r#"
+        SELECT MAX(cs.computed_at) FROM category_seasonality cs
+        JOIN budget_categories bc ON cs.category_id = bc.id
+        WHERE bc.user_id = ?
+        "#,
+    )
+    .bind(claims.sub)
+    .fetch_one(&pool)
+    .await?;
+
+    let labels: Vec<(i64, String)> =
+        sqlx::query_as("SELECT id, label FROM budget_categories WHERE user_id = ? ORDER BY label")
+            .bind(claims.sub)
+            .fetch_all(&pool)
+            .await?;
+    let mut seasonality = load_seasonality(&pool, claims.sub).await?;
+
+    let categories = labels
+        .into_iter()
+        .filter_map(|(category_id, category_label)| {
+            let expected = seasonality.remove(&category_id)?;
+            Some(SeasonalCategory {
+                category_id,
+                category_label,
+                average: expected.iter().sum::<f64>() / 12.0,
+                expected,
+            })
+        })
+        .collect();
+
+    Ok(Json(SeasonalityResponse {
+        computed_at,
+        categories,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/analytics/seasonality/refresh",
+    responses(
+        (status = 200, body = SeasonalityRefresh),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Insights",
+    summary = "Detect seasonal categories now",
+    description = "Queues seasonality detection instead of waiting for the nightly run."
+)]
+pub async fn refresh_seasonality(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+) -> Result<Json<SeasonalityRefresh>, PaymeError> {
+    let job_id = jobs::enqueue(&pool, claims.sub, jobs::SEASONALITY, &()).await?;
+    jobs::wake();
+
+    Ok(Json(SeasonalityRefresh { job_id }))
+}
+
+/// Expected spending per calendar month, January first, for each of the user's
+/// seasonal categories.
+pub(crate) async fn load_seasonality(
+    pool: &SqlitePool,
+    user_id: i64,
+) -> Result<HashMap<i64, Vec<f64>>, PaymeError> {
+    let rows: Vec<(i64, i32, f64)> = sqlx::query_as(
+        r#"
+        SELECT cs.category_id, cs.month, cs.expected FROM category_seasonality cs
+        JOIN budget_categories bc ON cs.category_id = bc.id
+        WHERE bc.user_id = ?
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut seasonality: HashMap<i64, Vec<f64>> = HashMap::new();
+    for (category_id, month, expected) in rows {
+        if let Some(slot) = seasonality
+            .entry(category_id)
+            .or_insert_with(|| vec![0.0; 12])
+            .get_mut((month - 1) as usize)
+        {
+            *slot = expected;
+        }
+    }
+
+    Ok(seasonality)
+}
+
+/// Converts `30d`, `12w`, `6m` or `1y` into a number of days.
+fn parse_period_days(period: &str) -> Option<i64> {
+    let period = period.trim();
+    let unit = period.chars().last()?;
+    let count: i64 = period[..period.len() - unit.len_utf8()].parse().ok()?;
+    let days = match unit {
+        'd' => count,
+        'w' => count.checked_mul(7)?,
+        'm' => count.checked_mul(30)?,
+        'y' => count.checked_mul(365)?,
+        _ => return None,
+    };
+    (1..=3650).contains(&days).then_some(days)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_period_days() {
+        assert_eq!(parse_period_days("90d"), Some(90));
+        assert_eq!(parse_period_days("2w"), Some(14));
+        assert_eq!(parse_period_days("6m"), Some(180));
+        assert_eq!(parse_period_days("1y"), Some(365));
+        assert_eq!(parse_period_days("0d"), None);
+        assert_eq!(parse_period_days("90"), None);
+        assert_eq!(parse_period_days("d"), None);
+        assert_eq!(parse_period_days(&format!("{}y", i64::MAX)), None);
+        assert_eq!(parse_period_days(&format!("{}w", i64::MAX / 2)), None);
+    }
+}
@@ -2,18 +2,28 @@ use argon2::{
     password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
     Argon2,
 };
-use axum::{extract::State, response::IntoResponse, Json};
+use std::net::{IpAddr, SocketAddr};
+
+use axum::{
+    extract::{rejection::ExtensionRejection, ConnectInfo, Query, State},
+    http::HeaderMap,
+    response::{IntoResponse, Response},
+};
 use axum_extra::extract::cookie::{Cookie, CookieJar, SameSite};
 use chrono::{Duration, Utc};
-use jsonwebtoken::{encode, EncodingKey, Header};
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
 use url::Url;
 use utoipa::ToSchema;
 use validator::Validate;
 
+use crate::config;
+use crate::dry_run::{self, DryRunQuery};
 use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::jwt;
 use crate::middleware::auth::Claims;
+use crate::models::AuthEvent;
 
 #[derive(Deserialize, ToSchema, Validate)]
 pub struct AuthRequest {
@@ -31,62 +41,107 @@ pub struct AuthResponse {
 
 #[utoipa::path(
     post,
-    path = "/api/auth/register",
+    path = "/api/v1/auth/register",
     request_body = AuthRequest,
     responses(
         (status = 200, description = "User registered successfully", body = AuthResponse),
+        (status = 400, description = "Username must be 3 to 32 characters and the password 6 to 128"),
         (status = 409, description = "Username already exists"),
         (status = 500, description = "Internal server error")
     ),
+    security(()),
     tag = "Auth",
     summary = "Register a new account",
-    description = "Creates a new user record. Returns the newly created user's ID and username."
+    description = "Creates a new user record with a starter set of budget categories and fixed expenses. Returns the newly created user's ID and username."
 )]
 pub async fn register(
     State(pool): State<SqlitePool>,
     Json(payload): Json<AuthRequest>,
 ) -> Result<impl IntoResponse, PaymeError> {
     payload.validate()?;
-    let salt = SaltString::generate(&mut OsRng);
-    let argon2 = Argon2::default();
-    let password_hash = argon2
-        .hash_password(payload.password.as_bytes(), &salt)
-        .map_err(|e| PaymeError::Internal(e.to_string()))?
-        .to_string();
+    let id = create_account(&pool, &payload.username, &payload.password).await?;
 
-    let result = sqlx::query_scalar::<_, i64>(
+    Ok(Json(AuthResponse {
+        id,
+        username: payload.username,
+    }))
+}
+
+/// Creates a user with the configured starter categories and fixed expenses and
+/// returns its ID.
+pub async fn create_account(
+    pool: &SqlitePool,
+    username: &str,
+    password: &str,
+) -> Result<i64, PaymeError> {
+    let password_hash = hash_password(password)?;
+
+    let mut tx = pool.begin().await?;
+    let user_id = sqlx::query_scalar::<_, i64>(
         "INSERT INTO users (username, password_hash) VALUES (?, ?) RETURNING id",
     )
-    .bind(&payload.username)
+    .bind(username)
     .bind(&password_hash)
-    .fetch_one(&pool)
+    .fetch_one(&mut *tx)
     .await?;
 
-    Ok(Json(AuthResponse {
-        id: result,
-        username: payload.username,
-    }))
+    for (label, amount) in config::seed_categories() {
+        sqlx::query(
+            "INSERT INTO budget_categories (user_id, label, default_amount) VALUES (?, ?, ?)",
+        )
+        .bind(user_id)
+        .bind(&label)
+        .bind(amount)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    for (label, amount) in config::seed_fixed_expenses() {
+        sqlx::query("INSERT INTO fixed_expenses (user_id, label, amount) VALUES (?, ?, ?)")
+            .bind(user_id)
+            .bind(&label)
+            .bind(amount)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    Ok(user_id)
+}
+
+pub fn hash_password(password: &str) -> Result<String, PaymeError> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| PaymeError::Internal(e.to_string()))
 }
 
 #[utoipa::path(
     post,
-    path = "/api/auth/login",
+    path = "/api/v1/auth/login",
     request_body = AuthRequest,
     responses(
         (status = 200, description = "Login successful", body = AuthResponse),
+        (status = 400, description = "Username or password of an impossible length"),
         (status = 401, description = "Invalid credentials"),
+        (status = 429, description = "Too many failed attempts; account temporarily locked"),
         (status = 500, description = "Internal server error")
     ),
+    security(()),
     tag = "Auth",
     summary = "Authenticate user",
-    description = "Verifies credentials and issues a JWT token."
+    description = "Verifies credentials and issues a JWT token. Repeated failures lock the account for a cooldown period."
 )]
 pub async fn login(
     State(pool): State<SqlitePool>,
     jar: CookieJar,
+    peer: Result<ConnectInfo<SocketAddr>, ExtensionRejection>,
+    headers: HeaderMap,
     Json(payload): Json<AuthRequest>,
 ) -> Result<impl IntoResponse, PaymeError> {
     payload.validate()?;
+    let ip = client_ip(peer.ok().map(|ConnectInfo(addr)| addr.ip()), &headers);
     let user: (i64, String, String) =
         sqlx::query_as("SELECT id, username, password_hash FROM users WHERE username = ?")
             .bind(&payload.username)
@@ -94,33 +149,38 @@ pub async fn login(
             .await?
             .ok_or(PaymeError::Unauthorized)?;
 
+    if recent_failed_logins(&pool, user.0).await? >= config::login_max_failures() {
+        record_auth_event(&pool, user.0, "login_blocked", ip, &headers).await?;
+        return Err(PaymeError::Locked);
+    }
+
     let parsed_hash =
         PasswordHash::new(&user.2).map_err(|e| PaymeError::Internal(e.to_string()))?;
-    Argon2::default()
+    if Argon2::default()
         .verify_password(payload.password.as_bytes(), &parsed_hash)
-        .map_err(|_| PaymeError::Unauthorized)?;
-
-    let secret = std::env::var("JWT_SECRET")
-        .unwrap_or_else(|_| "payme-secret-key-change-in-production".to_string());
-
+        .is_err()
+    {
+        record_auth_event(&pool, user.0, "login_failed", ip, &headers).await?;
+        if recent_failed_logins(&pool, user.0).await? == config::login_max_failures() {
+            tracing::warn!("Account {} locked after repeated failed logins", user.1);
+        }
+        return Err(PaymeError::Unauthorized);
+    }
+    record_auth_event(&pool, user.0, "login_succeeded", ip, &headers).await?;
+
+    let keys = jwt::keys();
     let claims = Claims {
         sub: user.0,
         username: user.1.clone(),
-        exp: (Utc::now() + Duration::days(30)).timestamp() as usize,
+        exp: (Utc::now() + keys.lifetime()).timestamp() as usize,
     };
-
-    let token = encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(secret.as_bytes()),
-    )
-    .map_err(|e| PaymeError::Internal(e.to_string()))?;
+    let token = keys.sign(&claims)?;
 
     let cookie = Cookie::build(("token", token))
         .path("/")
         .http_only(true)
         .same_site(SameSite::Lax)
-        .max_age(time::Duration::days(30))
+        .max_age(time::Duration::seconds(keys.lifetime().num_seconds()))
         .build();
 
     Ok((
@@ -134,7 +194,7 @@ pub async fn login(
 
 #[utoipa::path(
     post,
-    path = "/api/auth/logout",
+    path = "/api/v1/auth/logout",
     responses(
         (status = 200, description = "Logout successful."),
         (status = 500, description = "Internal server error")
@@ -155,7 +215,7 @@ pub async fn logout(jar: CookieJar) -> impl IntoResponse {
 
 #[utoipa::path(
     get,
-    path = "/api/auth/me",
+    path = "/api/v1/auth/me",
     responses(
         (status = 200, description = "Current user retrieved", body = AuthResponse),
         (status = 404, description = "User not found"),
@@ -181,6 +241,108 @@ pub async fn me(
     }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/v1/auth/security-events",
+    responses(
+        (status = 200, description = "Recent sign-in activity", body = [AuthEvent]),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Auth",
+    summary = "List security events",
+    description = "Returns the 50 most recent sign-in attempts on the account, including failed and blocked ones, with the client IP and user agent."
+)]
+pub async fn list_security_events(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+) -> Result<Json<Vec<AuthEvent>>, PaymeError> {
+    let events: Vec<AuthEvent> = sqlx::query_as(
+        r#"
+        SELECT id, kind, ip, user_agent, created_at FROM auth_events
+        WHERE user_id = ? ORDER BY created_at DESC, id DESC LIMIT 50
+        "#,
+    )
+    .bind(claims.sub)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(events))
+}
+
+/// Failed logins since the last successful one, within the lockout window.
+async fn recent_failed_logins(pool: &SqlitePool, user_id: i64) -> Result<i64, PaymeError> {
+    let since = Utc::now() - Duration::minutes(config::login_lockout_minutes());
+    let count: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM auth_events
+        WHERE user_id = ? AND kind = 'login_failed' AND created_at >= ?
+          AND created_at > COALESCE(
+              (SELECT MAX(created_at) FROM auth_events
+               WHERE user_id = ? AND kind = 'login_succeeded'), '')
+        "#,
+    )
+    .bind(user_id)
+    .bind(since)
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+
+    Ok(count)
+}
+
+/// Address the request came from. `X-Forwarded-For` and `X-Real-IP` are only believed
+/// when the connection comes from one of `TRUSTED_PROXIES`, since anyone else can set
+/// them; the client is then the last forwarded address that isn't a trusted proxy.
+fn client_ip(peer: Option<IpAddr>, headers: &HeaderMap) -> Option<IpAddr> {
+    let trusted = config::trusted_proxies();
+    let peer = peer?;
+    if !trusted.contains(&peer) {
+        return Some(peer);
+    }
+
+    let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+    let forwarded = header("x-forwarded-for")
+        .map(|chain| {
+            chain
+                .split(',')
+                .map_while(|hop| hop.trim().parse::<IpAddr>().ok())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    forwarded
+        .into_iter()
+        .rev()
+        .find(|ip| !trusted.contains(ip))
+        .or_else(|| header("x-real-ip").and_then(|ip| ip.trim().parse().ok()))
+        .or(Some(peer))
+}
+
+async fn record_auth_event(
+    pool: &SqlitePool,
+    user_id: i64,
+    kind: &str,
+    ip: Option<IpAddr>,
+    headers: &HeaderMap,
+) -> Result<(), PaymeError> {
+    let user_agent = headers
+        .get("user-agent")
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string());
+
+    sqlx::query(
+        "INSERT INTO auth_events (user_id, kind, ip, user_agent, created_at) VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(user_id)
+    .bind(kind)
+    .bind(ip.map(|ip| ip.to_string()))
+    .bind(user_agent)
+    .bind(Utc::now())
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
 pub async fn export_db(
     axum::Extension(claims): axum::Extension<Claims>,
 ) -> Result<impl IntoResponse, PaymeError> {
@@ -216,7 +378,7 @@ pub struct ChangeUsernameRequest {
 
 #[utoipa::path(
     put,
-    path = "/api/auth/change-username",
+    path = "/api/v1/auth/change-username",
     request_body = ChangeUsernameRequest,
     responses(
         (status = 200, description = "Username changed successfully", body = AuthResponse),
@@ -256,7 +418,7 @@ pub struct ChangePasswordRequest {
 
 #[utoipa::path(
     put,
-    path = "/api/auth/change-password",
+    path = "/api/v1/auth/change-password",
     request_body = ChangePasswordRequest,
     responses(
         (status = 200, description = "Password changed successfully"),
@@ -286,12 +448,7 @@ pub async fn change_password(
         .verify_password(payload.current_password.as_bytes(), &parsed_hash)
         .map_err(|_| PaymeError::Unauthorized)?;
 
-    let salt = SaltString::generate(&mut OsRng);
-    let argon2 = Argon2::default();
-    let new_password_hash = argon2
-        .hash_password(payload.new_password.as_bytes(), &salt)
-        .map_err(|e| PaymeError::Internal(e.to_string()))?
-        .to_string();
+    let new_password_hash = hash_password(&payload.new_password)?;
 
     sqlx::query("UPDATE users SET password_hash = ? WHERE id = ?")
         .bind(&new_password_hash)
@@ -312,10 +469,11 @@ pub struct ClearDataRequest {
 
 #[utoipa::path(
     delete,
-    path = "/api/auth/clear-data",
+    path = "/api/v1/auth/clear-data",
+    params(DryRunQuery),
     request_body = ClearDataRequest,
     responses(
-        (status = 200, description = "All data cleared successfully"),
+        (status = 200, description = "All data cleared successfully, or with `dry_run` a DryRunReport of everything that would be deleted"),
         (status = 401, description = "Invalid password"),
         (status = 500, description = "Internal server error")
     ),
@@ -327,8 +485,9 @@ pub async fn clear_all_data(
     State(pool): State<SqlitePool>,
     jar: CookieJar,
     axum::Extension(claims): axum::Extension<Claims>,
+    Query(query): Query<DryRunQuery>,
     Json(payload): Json<ClearDataRequest>,
-) -> Result<impl IntoResponse, PaymeError> {
+) -> Result<Response, PaymeError> {
     payload.validate()?;
 
     let user: (String,) = sqlx::query_as("SELECT password_hash FROM users WHERE id = ?")
@@ -343,10 +502,21 @@ pub async fn clear_all_data(
         .verify_password(payload.password.as_bytes(), &parsed_hash)
         .map_err(|_| PaymeError::Unauthorized)?;
 
+    let mut tx = pool.begin().await?;
+    if query.dry_run {
+        dry_run::track_changes(&mut tx).await?;
+    }
     sqlx::query("DELETE FROM users WHERE id = ?")
         .bind(claims.sub)
-        .execute(&pool)
+        .execute(&mut *tx)
         .await?;
+    sqlx::query("DELETE FROM sync_deletions WHERE user_id = ?")
+        .bind(claims.sub)
+        .execute(&mut *tx)
+        .await?;
+    if let Some(report) = dry_run::finish(tx, query.dry_run).await? {
+        return Ok(Json(report).into_response());
+    }
 
     let cookie = Cookie::build(("token", ""))
         .path("/")
@@ -357,5 +527,6 @@ pub async fn clear_all_data(
     Ok((
         jar.add(cookie),
         Json(serde_json::json!({"message": "All data cleared"})),
-    ))
+    )
+        .into_response())
 }
@@ -1,42 +1,135 @@
+use std::collections::HashMap;
+
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
-    Json,
+    response::{IntoResponse, Response},
 };
-use serde::Deserialize;
-use sqlx::SqlitePool;
-use utoipa::ToSchema;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqliteConnection, SqliteExecutor, SqlitePool};
+use utoipa::{IntoParams, ToSchema};
 use validator::Validate;
 
+use crate::activity;
+use crate::bulk::{BulkEdit, BulkEditResult};
+use crate::dry_run::{self, DryRunQuery, DryRunReport};
+use crate::envelopes;
 use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::feed;
+use crate::format::{MoneyFormat, DEFAULT_CURRENCY, DEFAULT_LOCALE};
+use crate::handlers::months::get_month_summary;
+use crate::handlers::settings::money_format;
+use crate::handlers::sync;
+use crate::i18n::{Locale, Text};
 use crate::middleware::auth::Claims;
-use crate::models::{BudgetCategory, MonthlyBudget};
+use crate::models::{
+    BudgetCategory, BudgetReview, Earmark, Envelope, EnvelopesResponse, FixedExpense, MonthlyBudget,
+};
 
 #[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct CreateCategory {
     #[validate(length(min = 1, max = 100))]
     pub label: String,
-    #[validate(range(min = 0.0))]
+    #[validate(range(min = 0.0), custom(function = "crate::money::amount"))]
     pub default_amount: f64,
+    /// Allocate this percentage of each month's income instead of `default_amount`.
+    #[validate(range(exclusive_min = 0.0, max = 100.0))]
+    pub default_percent: Option<f64>,
+    /// Itemize spending without counting it against allocations or the month's totals.
+    #[serde(default)]
+    pub tracking_only: bool,
+    /// UUID chosen by the client. Creating a category with a UUID already used returns
+    /// that category instead of adding another.
+    pub uuid: Option<String>,
 }
 
 #[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateCategory {
     #[validate(length(min = 1, max = 100))]
     pub label: Option<String>,
-    #[validate(range(min = 0.0))]
+    #[validate(range(min = 0.0), custom(function = "crate::money::amount"))]
     pub default_amount: Option<f64>,
+    /// Percentage of each month's income to allocate; `null` goes back to `default_amount`.
+    #[serde(default, deserialize_with = "crate::handlers::items::explicit_null")]
+    #[schema(value_type = Option<f64>)]
+    #[validate(range(exclusive_min = 0.0, max = 100.0))]
+    pub default_percent: Option<Option<f64>>,
+    /// Itemize spending without counting it against allocations or the month's totals.
+    /// Applies to past months too.
+    pub tracking_only: Option<bool>,
 }
 
 #[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateMonthlyBudget {
-    #[validate(range(min = 0.0))]
+    #[validate(range(min = 0.0), custom(function = "crate::money::amount"))]
     pub allocated_amount: f64,
 }
 
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MonthlyBudgetsQuery {
+    /// Attach each category's average spending over the previous six months.
+    #[serde(default)]
+    pub include_average: bool,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct LockBudget {
+    /// Why the category is closed for the month, shown when an item is refused.
+    #[validate(length(max = 200))]
+    pub reason: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct ReviewBudget {
+    /// Satisfaction from 1 (poor) to 5 (great).
+    #[validate(range(min = 1, max = 5))]
+    pub rating: i64,
+    #[validate(length(max = 500))]
+    pub note: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct YearReview {
+    pub year: i32,
+    /// Reviewed categories, by label.
+    pub categories: Vec<CategoryYearReview>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CategoryYearReview {
+    pub category_label: String,
+    pub average_rating: f64,
+    /// Reviewed months, January first.
+    pub months: Vec<MonthReview>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct MonthReview {
+    pub month: i32,
+    /// Satisfaction from 1 (poor) to 5 (great).
+    pub rating: i64,
+    pub note: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateEarmark {
+    /// What the money is for. Posting an item with the same description consumes it.
+    #[validate(length(min = 1, max = 200))]
+    pub label: String,
+    #[validate(range(exclusive_min = 0.0), custom(function = "crate::money::amount"))]
+    pub amount: f64,
+}
+
 #[utoipa::path(
     get,
-    path = "/api/categories",
+    path = "/api/v1/categories",
     responses(
         (status = 200, body = [BudgetCategory]),
         (status = 500, description = "Internal server error")
@@ -50,7 +143,7 @@ pub async fn list_categories(
     axum::Extension(claims): axum::Extension<Claims>,
 ) -> Result<Json<Vec<BudgetCategory>>, PaymeError> {
     let categories: Vec<BudgetCategory> = sqlx::query_as(
-        "SELECT id, user_id, label, default_amount FROM budget_categories WHERE user_id = ?",
+        "SELECT id, user_id, label, default_amount, uuid, default_percent, tracking_only, system FROM budget_categories WHERE user_id = ?",
     )
     .bind(claims.sub)
     .fetch_all(&pool)
@@ -61,10 +154,11 @@ pub async fn list_categories(
 
 #[utoipa::path(
     post,
-    path = "/api/categories",
+    path = "/api/v1/categories",
     request_body = CreateCategory,
     responses(
-        (status = 201, description = "Category created and added to open months", body = BudgetCategory),
+        (status = 200, description = "Category created and added to open months", body = BudgetCategory),
+        (status = 400, description = "Invalid category"),
         (status = 500, description = "Internal server error")
     ),
     tag = "Configuration",
@@ -77,12 +171,34 @@ pub async fn create_category(
     Json(payload): Json<CreateCategory>,
 ) -> Result<Json<BudgetCategory>, PaymeError> {
     payload.validate()?;
+    let uuid = sync::client_uuid(payload.uuid.as_deref())?;
+    if let Some(uuid) = &uuid {
+        let existing: Option<BudgetCategory> = sqlx::query_as(
+            "SELECT id, user_id, label, default_amount, uuid, default_percent, tracking_only, system FROM budget_categories WHERE uuid = ?",
+        )
+        .bind(uuid)
+        .fetch_optional(&pool)
+        .await?;
+        match existing {
+            Some(category) if category.user_id == claims.sub => return Ok(Json(category)),
+            Some(_) => {
+                return Err(PaymeError::Conflict(format!(
+                    "UUID {uuid} is already in use"
+                )))
+            }
+            None => {}
+        }
+    }
+
     let id: i64 = sqlx::query_scalar(
-        "INSERT INTO budget_categories (user_id, label, default_amount) VALUES (?, ?, ?) RETURNING id",
+        "INSERT INTO budget_categories (user_id, label, default_amount, uuid, default_percent, tracking_only) VALUES (?, ?, ?, ?, ?, ?) RETURNING id",
     )
     .bind(claims.sub)
     .bind(&payload.label)
     .bind(payload.default_amount)
+    .bind(&uuid)
+    .bind(payload.default_percent)
+    .bind(payload.tracking_only)
     .fetch_one(&pool)
     .await?;
 
@@ -94,14 +210,18 @@ pub async fn create_category(
 
     for (month_id,) in open_months {
         sqlx::query(
-            "INSERT OR IGNORE INTO monthly_budgets (month_id, category_id, allocated_amount) VALUES (?, ?, ?)",
+            "INSERT OR IGNORE INTO monthly_budgets (month_id, category_id, allocated_amount, allocated_percent) VALUES (?, ?, ?, ?)",
         )
         .bind(month_id)
         .bind(id)
         .bind(payload.default_amount)
+        .bind(payload.default_percent)
         .execute(&pool)
         .await
         .ok();
+        if payload.default_percent.is_some() {
+            apply_percent_allocations(&pool, month_id).await?;
+        }
     }
 
     Ok(Json(BudgetCategory {
@@ -109,16 +229,21 @@ pub async fn create_category(
         user_id: claims.sub,
         label: payload.label,
         default_amount: payload.default_amount,
+        uuid,
+        default_percent: payload.default_percent,
+        tracking_only: payload.tracking_only,
+        system: false,
     }))
 }
 
 #[utoipa::path(
     put,
-    path = "/api/categories/{id}",
+    path = "/api/v1/categories/{id}",
     params(("id" = i64, Path, description = "Category ID")),
     request_body = UpdateCategory,
     responses(
         (status = 200, body = BudgetCategory),
+        (status = 400, description = "The Uncategorized category"),
         (status = 500, description = "Internal server error")
     ),
     tag = "Configuration",
@@ -133,21 +258,32 @@ pub async fn update_category(
 ) -> Result<Json<BudgetCategory>, PaymeError> {
     payload.validate()?;
     let existing: BudgetCategory = sqlx::query_as(
-        "SELECT id, user_id, label, default_amount FROM budget_categories WHERE id = ? AND user_id = ?",
+        "SELECT id, user_id, label, default_amount, uuid, default_percent, tracking_only, system FROM budget_categories WHERE id = ? AND user_id = ?",
     )
     .bind(category_id)
     .bind(claims.sub)
     .fetch_optional(&pool)
     .await?
     .ok_or(PaymeError::NotFound)?;
+    if existing.system {
+        return Err(PaymeError::BadRequest(
+            "The Uncategorized category can't be changed".to_string(),
+        ));
+    }
 
     let label = payload.label.unwrap_or(existing.label);
     let default_amount = payload.default_amount.unwrap_or(existing.default_amount);
+    let default_percent = payload.default_percent.unwrap_or(existing.default_percent);
+    let tracking_only = payload.tracking_only.unwrap_or(existing.tracking_only);
 
-    sqlx::query("UPDATE budget_categories SET label = ?, default_amount = ? WHERE id = ?")
-        .bind(&label)
-        .bind(default_amount)
-        .bind(category_id)
+    sqlx::query(
+        "UPDATE budget_categories SET label = ?, default_amount = ?, default_percent = ?, tracking_only = ? WHERE id = ?",
+    )
+    .bind(&label)
+    .bind(default_amount)
+    .bind(default_percent)
+    .bind(tracking_only)
+    .bind(category_id)
         .execute(&pool)
         .await?;
 
@@ -156,14 +292,70 @@ pub async fn update_category(
         user_id: claims.sub,
         label,
         default_amount,
+        uuid: existing.uuid,
+        default_percent,
+        tracking_only,
+        system: existing.system,
+    }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/categories/bulk",
+    params(DryRunQuery),
+    request_body = BulkEdit,
+    responses(
+        (status = 200, body = BulkEditResult),
+        (status = 400, description = "An unknown category, or ids without an increase"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Configuration",
+    summary = "Bulk edit categories",
+    description = "Raises the default amounts of several categories by a percentage and relabels them in one transaction. Months already created keep their allocations. With `dry_run`, answers with the changes without saving them."
+)]
+pub async fn bulk_update_categories(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Query(query): Query<DryRunQuery>,
+    Json(payload): Json<BulkEdit>,
+) -> Result<Json<BulkEditResult>, PaymeError> {
+    payload.validate()?;
+    let rows: Vec<(i64, String, f64)> = sqlx::query_as(
+        "SELECT id, label, default_amount FROM budget_categories WHERE user_id = ? AND system = 0",
+    )
+    .bind(claims.sub)
+    .fetch_all(&pool)
+    .await?;
+    let changes = payload.plan(&rows, "category")?;
+
+    if !query.dry_run {
+        let mut tx = pool.begin().await?;
+        for change in &changes {
+            sqlx::query("UPDATE budget_categories SET label = ?, default_amount = ? WHERE id = ?")
+                .bind(&change.label)
+                .bind(change.amount)
+                .bind(change.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+    }
+
+    Ok(Json(BulkEditResult {
+        dry_run: query.dry_run,
+        changes,
     }))
 }
 
 #[utoipa::path(
     delete,
-    path = "/api/categories/{id}",
-    params(("id" = i64, Path, description = "Category ID")),
-    responses((status = 204, description = "Deleted")),
+    path = "/api/v1/categories/{id}",
+    params(("id" = i64, Path, description = "Category ID"), DryRunQuery),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 200, body = DryRunReport, description = "With `dry_run`, what deleting the category and its budgets and items would remove"),
+        (status = 400, description = "The Uncategorized category")
+    ),
     tag = "Configuration",
     summary = "Delete global category",
 )]
@@ -171,32 +363,55 @@ pub async fn delete_category(
     State(pool): State<SqlitePool>,
     axum::Extension(claims): axum::Extension<Claims>,
     Path(category_id): Path<i64>,
-) -> Result<StatusCode, PaymeError> {
+    Query(query): Query<DryRunQuery>,
+) -> Result<Response, PaymeError> {
+    // Deleting the fallback category would delete the items in it
+    let system: Option<bool> =
+        sqlx::query_scalar("SELECT system FROM budget_categories WHERE id = ? AND user_id = ?")
+            .bind(category_id)
+            .bind(claims.sub)
+            .fetch_optional(&pool)
+            .await?;
+    if system == Some(true) {
+        return Err(PaymeError::BadRequest(
+            "The Uncategorized category can't be deleted".to_string(),
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+    if query.dry_run {
+        dry_run::track_changes(&mut tx).await?;
+    }
+
     sqlx::query("DELETE FROM budget_categories WHERE id = ? AND user_id = ?")
         .bind(category_id)
         .bind(claims.sub)
-        .execute(&pool)
+        .execute(&mut *tx)
         .await?;
 
-    Ok(StatusCode::NO_CONTENT)
+    if let Some(report) = dry_run::finish(tx, query.dry_run).await? {
+        return Ok(Json(report).into_response());
+    }
+    Ok(StatusCode::NO_CONTENT.into_response())
 }
 
 #[utoipa::path(
     get,
-    path = "/api/months/{id}/budgets",
-    params(("id" = i64, Path, description = "Month ID")),
+    path = "/api/v1/months/{id}/budgets",
+    params(("id" = i64, Path, description = "Month ID"), MonthlyBudgetsQuery),
     responses(
         (status = 200, body = [MonthlyBudget]),
         (status = 500, description = "Internal server error")
     ),
     tag = "Budgets",
     summary = "List monthly allocations",
-    description = "Retrieves the specific budget allocations for a specific month."
+    description = "Retrieves the specific budget allocations for a specific month. With `include_average=true`, each allocation comes with the category's average monthly spending over the six months before, counting only the months the user has."
 )]
 pub async fn list_monthly_budgets(
     State(pool): State<SqlitePool>,
     axum::Extension(claims): axum::Extension<Claims>,
     Path(month_id): Path<i64>,
+    Query(query): Query<MonthlyBudgetsQuery>,
 ) -> Result<Json<Vec<MonthlyBudget>>, PaymeError> {
     let _month: (i64,) = sqlx::query_as("SELECT id FROM months WHERE id = ? AND user_id = ?")
         .bind(month_id)
@@ -205,19 +420,40 @@ pub async fn list_monthly_budgets(
         .await?
         .ok_or(PaymeError::NotFound)?;
 
-    let budgets: Vec<MonthlyBudget> = sqlx::query_as(
-        "SELECT id, month_id, category_id, allocated_amount FROM monthly_budgets WHERE month_id = ?",
-    )
-    .bind(month_id)
-    .fetch_all(&pool)
-    .await?;
+    let sql = if query.include_average {
+        r#"
+        WITH target AS (
+            SELECT user_id, year * 12 + month - 1 AS idx FROM months WHERE id = ?1
+        ),
+        history AS (
+            SELECT m.id
+            FROM months m, target t
+            WHERE m.user_id = t.user_id
+              AND m.year * 12 + m.month - 1 BETWEEN t.idx - 6 AND t.idx - 1
+        ),
+        spent AS (
+            SELECT i.category_id, SUM(i.amount) AS total
+            FROM items i
+            WHERE i.month_id IN (SELECT id FROM history) AND i.savings_destination = 'none'
+            GROUP BY i.category_id
+        )
+        SELECT mb.id, mb.month_id, mb.category_id, mb.allocated_amount, mb.locked_at, mb.lock_reason, mb.allocated_percent,
+               COALESCE(s.total, 0.0) / NULLIF((SELECT COUNT(*) FROM history), 0) AS average_spent
+        FROM monthly_budgets mb
+        LEFT JOIN spent s ON s.category_id = mb.category_id
+        WHERE mb.month_id = ?1
+        "#
+    } else {
+        "SELECT id, month_id, category_id, allocated_amount, locked_at, lock_reason, allocated_percent FROM monthly_budgets WHERE month_id = ?1"
+    };
+    let budgets: Vec<MonthlyBudget> = sqlx::query_as(sql).bind(month_id).fetch_all(&pool).await?;
 
     Ok(Json(budgets))
 }
 
 #[utoipa::path(
     put,
-    path = "/api/months/{month_id}/budgets/{id}",
+    path = "/api/v1/months/{month_id}/budgets/{id}",
     params(
         ("month_id" = i64, Path, description = "Month ID"),
         ("id" = i64, Path, description = "Budget ID")
@@ -251,7 +487,7 @@ pub async fn update_monthly_budget(
     }
 
     let existing: MonthlyBudget = sqlx::query_as(
-        "SELECT id, month_id, category_id, allocated_amount FROM monthly_budgets WHERE id = ? AND month_id = ?",
+        "SELECT id, month_id, category_id, allocated_amount, locked_at, lock_reason FROM monthly_budgets WHERE id = ? AND month_id = ?",
     )
     .bind(budget_id)
     .bind(month_id)
@@ -259,16 +495,654 @@ pub async fn update_monthly_budget(
     .await?
     .ok_or(PaymeError::NotFound)?;
 
-    sqlx::query("UPDATE monthly_budgets SET allocated_amount = ? WHERE id = ?")
-        .bind(payload.allocated_amount)
-        .bind(budget_id)
-        .execute(&pool)
-        .await?;
+    sqlx::query(
+        "UPDATE monthly_budgets SET allocated_amount = ?, allocated_percent = NULL WHERE id = ?",
+    )
+    .bind(payload.allocated_amount)
+    .bind(budget_id)
+    .execute(&pool)
+    .await?;
+
+    let category_label: String =
+        sqlx::query_scalar("SELECT label FROM budget_categories WHERE id = ?")
+            .bind(existing.category_id)
+            .fetch_one(&pool)
+            .await?;
+
+    let money = money_format(&pool, claims.sub).await?;
+    activity::record(
+        &pool,
+        &claims,
+        Some(month_id),
+        "budget",
+        budget_id,
+        "update",
+        format!(
+            "{} set {} budget to {}",
+            claims.username,
+            category_label,
+            money.format(payload.allocated_amount)
+        ),
+    )
+    .await?;
 
     Ok(Json(MonthlyBudget {
         id: budget_id,
         month_id,
         category_id: existing.category_id,
         allocated_amount: payload.allocated_amount,
+        locked_at: existing.locked_at,
+        lock_reason: existing.lock_reason,
+        allocated_percent: None,
+        average_spent: None,
+    }))
+}
+
+/// Sets the allocations that follow a share of income to that share of the month's
+/// income as it stands. Closed months keep their allocations.
+pub(crate) async fn apply_percent_allocations<'e>(
+    executor: impl SqliteExecutor<'e>,
+    month_id: i64,
+) -> Result<(), PaymeError> {
+    sqlx::query(
+        r#"
+        WITH income AS (
+            SELECT COALESCE(SUM(amount), 0.0) AS total FROM income_entries WHERE month_id = ?1
+        )
+        UPDATE monthly_budgets
+        SET allocated_amount = ROUND(allocated_percent * (SELECT total FROM income) / 100, 2)
+        WHERE month_id = ?1
+          AND allocated_percent IS NOT NULL
+          AND allocated_amount <> ROUND(allocated_percent * (SELECT total FROM income) / 100, 2)
+          AND EXISTS (SELECT 1 FROM months WHERE id = ?1 AND is_closed = 0)
+        "#,
+    )
+    .bind(month_id)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Brings a month's budget in line with its income after an income entry changed.
+///
+/// Unless the user turned it off, allocations that follow a share of income are
+/// recomputed, and when any of them moved an alert lists the changes together with
+/// what is left to budget.
+pub(crate) async fn income_changed(
+    conn: &mut SqliteConnection,
+    user_id: i64,
+    month_id: i64,
+) -> Result<(), PaymeError> {
+    let (enabled, locale, currency): (Option<bool>, Option<String>, Option<String>) =
+        sqlx::query_as(
+            "SELECT rebudget_on_income_change, locale, currency FROM user_settings WHERE user_id = ?",
+        )
+        .bind(user_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .unwrap_or_default();
+    if enabled == Some(false) {
+        return Ok(());
+    }
+
+    let before: Vec<(i64, String, f64)> = sqlx::query_as(
+        r#"
+        SELECT mb.id, bc.label, mb.allocated_amount
+        FROM monthly_budgets mb
+        JOIN budget_categories bc ON mb.category_id = bc.id
+        WHERE mb.month_id = ? AND mb.allocated_percent IS NOT NULL
+        ORDER BY bc.label
+        "#,
+    )
+    .bind(month_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    if before.is_empty() {
+        return Ok(());
+    }
+
+    apply_percent_allocations(&mut *conn, month_id).await?;
+
+    let after: HashMap<i64, f64> =
+        sqlx::query_as("SELECT id, allocated_amount FROM monthly_budgets WHERE month_id = ?")
+            .bind(month_id)
+            .fetch_all(&mut *conn)
+            .await?
+            .into_iter()
+            .collect();
+
+    let money = MoneyFormat::new(
+        locale.as_deref().unwrap_or(DEFAULT_LOCALE),
+        currency.as_deref().unwrap_or(DEFAULT_CURRENCY),
+    );
+    let changes: Vec<String> = before
+        .iter()
+        .filter_map(|(id, label, old)| {
+            let new = after.get(id)?;
+            ((old - new).abs() >= 0.005)
+                .then(|| format!("{label} {} → {}", money.format(*old), money.format(*new)))
+        })
+        .collect();
+    if changes.is_empty() {
+        return Ok(());
+    }
+
+    let (year, month): (i32, i32) = sqlx::query_as("SELECT year, month FROM months WHERE id = ?")
+        .bind(month_id)
+        .fetch_one(&mut *conn)
+        .await?;
+    let income: f64 = sqlx::query_scalar(
+        "SELECT COALESCE(SUM(amount), 0.0) FROM income_entries WHERE month_id = ?",
+    )
+    .bind(month_id)
+    .fetch_one(&mut *conn)
+    .await?;
+    let fixed_expenses: Vec<FixedExpense> = sqlx::query_as(
+        "SELECT id, user_id, label, amount, billing_period, payment_month, payment_day, payment_roll FROM fixed_expenses WHERE user_id = ?",
+    )
+    .bind(user_id)
+    .fetch_all(&mut *conn)
+    .await?;
+    let total_fixed: f64 = fixed_expenses.iter().map(|e| e.monthly_amount()).sum();
+    let total_budgeted: f64 = after.values().sum();
+
+    let message = Locale::resolve(locale.as_deref(), None).render(
+        Text::InsightIncomeRebudget,
+        &[
+            ("month", &month.to_string()),
+            ("year", &year.to_string()),
+            ("income", &money.format(income)),
+            ("changes", &changes.join(", ")),
+            (
+                "amount",
+                &money.format(income - total_fixed - total_budgeted),
+            ),
+        ],
+    );
+    feed::record_event(
+        &mut *conn,
+        user_id,
+        "income_rebudget",
+        &format!("income_rebudget:{month_id}"),
+        &message,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/months/{month_id}/budgets/{id}/lock",
+    params(
+        ("month_id" = i64, Path, description = "Month ID"),
+        ("id" = i64, Path, description = "Budget ID")
+    ),
+    request_body = LockBudget,
+    responses(
+        (status = 200, body = MonthlyBudget),
+        (status = 400, description = "Month is closed"),
+        (status = 404, description = "Budget not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Budgets",
+    summary = "Lock a category",
+    description = "Stops a category from accepting new items for the rest of the month, e.g. no more eating out. Adding or moving an item into it is refused with 403 and the reason. Existing items and the rest of the month stay editable."
+)]
+pub async fn lock_monthly_budget(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path((month_id, budget_id)): Path<(i64, i64)>,
+    Json(payload): Json<LockBudget>,
+) -> Result<Json<MonthlyBudget>, PaymeError> {
+    payload.validate()?;
+    let reason = payload
+        .reason
+        .map(|r| r.trim().to_string())
+        .filter(|r| !r.is_empty());
+    set_lock(&pool, &claims, month_id, budget_id, Some(reason)).await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/months/{month_id}/budgets/{id}/lock",
+    params(
+        ("month_id" = i64, Path, description = "Month ID"),
+        ("id" = i64, Path, description = "Budget ID")
+    ),
+    responses(
+        (status = 200, body = MonthlyBudget),
+        (status = 400, description = "Month is closed"),
+        (status = 404, description = "Budget not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Budgets",
+    summary = "Unlock a category",
+    description = "Lets a locked category accept new items again."
+)]
+pub async fn unlock_monthly_budget(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path((month_id, budget_id)): Path<(i64, i64)>,
+) -> Result<Json<MonthlyBudget>, PaymeError> {
+    set_lock(&pool, &claims, month_id, budget_id, None).await
+}
+
+/// Locks the budget with an optional reason when `lock` is `Some`, unlocks it otherwise.
+async fn set_lock(
+    pool: &SqlitePool,
+    claims: &Claims,
+    month_id: i64,
+    budget_id: i64,
+    lock: Option<Option<String>>,
+) -> Result<Json<MonthlyBudget>, PaymeError> {
+    let month: (bool,) =
+        sqlx::query_as("SELECT is_closed FROM months WHERE id = ? AND user_id = ?")
+            .bind(month_id)
+            .bind(claims.sub)
+            .fetch_optional(pool)
+            .await?
+            .ok_or(PaymeError::NotFound)?;
+
+    if month.0 {
+        return Err(PaymeError::BadRequest("Month is closed".to_string()));
+    }
+
+    let locked_at = lock.as_ref().map(|_| Utc::now());
+    let reason = lock.flatten();
+    let budget: MonthlyBudget = sqlx::query_as(
+        r#"
+        UPDATE monthly_budgets SET locked_at = ?, lock_reason = ?
+        WHERE id = ? AND month_id = ?
+        RETURNING id, month_id, category_id, allocated_amount, locked_at, lock_reason
+        "#,
+    )
+    .bind(locked_at)
+    .bind(&reason)
+    .bind(budget_id)
+    .bind(month_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(PaymeError::NotFound)?;
+
+    let category_label: String =
+        sqlx::query_scalar("SELECT label FROM budget_categories WHERE id = ?")
+            .bind(budget.category_id)
+            .fetch_one(pool)
+            .await?;
+
+    let (action, summary) = match (locked_at, &reason) {
+        (None, _) => (
+            "unlock",
+            format!("{} unlocked {}", claims.username, category_label),
+        ),
+        (Some(_), Some(reason)) => (
+            "lock",
+            format!("{} locked {}: {}", claims.username, category_label, reason),
+        ),
+        (Some(_), None) => (
+            "lock",
+            format!("{} locked {}", claims.username, category_label),
+        ),
+    };
+    activity::record(
+        pool,
+        claims,
+        Some(month_id),
+        "budget",
+        budget_id,
+        action,
+        summary,
+    )
+    .await?;
+
+    Ok(Json(budget))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/months/{month_id}/budgets/{id}/review",
+    params(
+        ("month_id" = i64, Path, description = "Month ID"),
+        ("id" = i64, Path, description = "Budget ID")
+    ),
+    request_body = ReviewBudget,
+    responses(
+        (status = 200, body = BudgetReview),
+        (status = 400, description = "Invalid rating or note, or the month is closed"),
+        (status = 404, description = "Budget not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Budgets",
+    summary = "Review a category",
+    description = "Saves a satisfaction rating and short reflection for a category's month, replacing any earlier review. Reviews are taken before the month is closed, as its PDF snapshot is final. They appear in the month summary, the PDF and the yearly review."
+)]
+pub async fn review_monthly_budget(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path((month_id, budget_id)): Path<(i64, i64)>,
+    Json(payload): Json<ReviewBudget>,
+) -> Result<Json<BudgetReview>, PaymeError> {
+    payload.validate()?;
+
+    let (is_closed, category_label): (bool, Option<String>) = sqlx::query_as(
+        r#"
+        SELECT m.is_closed,
+               (SELECT bc.label FROM monthly_budgets mb
+                JOIN budget_categories bc ON mb.category_id = bc.id
+                WHERE mb.id = ? AND mb.month_id = m.id)
+        FROM months m WHERE m.id = ? AND m.user_id = ?
+        "#,
+    )
+    .bind(budget_id)
+    .bind(month_id)
+    .bind(claims.sub)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(PaymeError::NotFound)?;
+    if is_closed {
+        return Err(PaymeError::BadRequest("Month is closed".to_string()));
+    }
+    let category_label = category_label.ok_or(PaymeError::NotFound)?;
+
+    let note = payload
+        .note
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty());
+    let review: BudgetReview = sqlx::query_as(
+        r#"
+        INSERT INTO budget_reviews (month_id, budget_id, rating, note, updated_at)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT(month_id, budget_id) DO UPDATE SET
+            rating = excluded.rating,
+            note = excluded.note,
+            updated_at = excluded.updated_at
+        RETURNING budget_id, rating, note, updated_at
+        "#,
+    )
+    .bind(month_id)
+    .bind(budget_id)
+    .bind(payload.rating)
+    .bind(note)
+    .bind(Utc::now())
+    .fetch_one(&pool)
+    .await?;
+
+    activity::record(
+        &pool,
+        &claims,
+        Some(month_id),
+        "budget",
+        budget_id,
+        "review",
+        format!(
+            "{} rated {} {}/5",
+            claims.username, category_label, review.rating
+        ),
+    )
+    .await?;
+
+    Ok(Json(review))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/reviews/{year}",
+    params(("year" = i32, Path, description = "Year")),
+    responses(
+        (status = 200, body = YearReview),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Budgets",
+    summary = "Get the year's reviews",
+    description = "Collects the category reviews of a year's months, grouped by category with their average rating, so satisfaction can be compared month to month. Categories deleted since keep the label they had."
+)]
+pub async fn get_year_review(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(year): Path<i32>,
+) -> Result<Json<YearReview>, PaymeError> {
+    // Closed months keep the label the category had when they were closed
+    let rows: Vec<(String, i32, i64, Option<String>)> = sqlx::query_as(
+        r#"
+        SELECT COALESCE(cmb.category_label, bc.label) AS category_label, m.month, r.rating, r.note
+        FROM budget_reviews r
+        JOIN months m ON r.month_id = m.id
+        LEFT JOIN closed_month_budgets cmb
+            ON cmb.month_id = r.month_id AND cmb.budget_id = r.budget_id
+        LEFT JOIN monthly_budgets mb ON mb.id = r.budget_id AND mb.month_id = r.month_id
+        LEFT JOIN budget_categories bc ON bc.id = mb.category_id
+        WHERE m.user_id = ? AND m.year = ? AND COALESCE(cmb.category_label, bc.label) IS NOT NULL
+        ORDER BY category_label, m.month
+        "#,
+    )
+    .bind(claims.sub)
+    .bind(year)
+    .fetch_all(&pool)
+    .await?;
+
+    let mut categories: Vec<CategoryYearReview> = Vec::new();
+    for (category_label, month, rating, note) in rows {
+        let review = MonthReview {
+            month,
+            rating,
+            note,
+        };
+        match categories.last_mut() {
+            Some(last) if last.category_label == category_label => last.months.push(review),
+            _ => categories.push(CategoryYearReview {
+                category_label,
+                average_rating: 0.0,
+                months: vec![review],
+            }),
+        }
+    }
+    for category in &mut categories {
+        let total: i64 = category.months.iter().map(|m| m.rating).sum();
+        category.average_rating = total as f64 / category.months.len() as f64;
+    }
+
+    Ok(Json(YearReview { year, categories }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/months/{month_id}/budgets/{id}/earmarks",
+    params(
+        ("month_id" = i64, Path, description = "Month ID"),
+        ("id" = i64, Path, description = "Budget ID")
+    ),
+    request_body = CreateEarmark,
+    responses(
+        (status = 200, body = Earmark),
+        (status = 400, description = "Month is closed, or the amount is more than the category has free"),
+        (status = 404, description = "Budget not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Budgets",
+    summary = "Earmark part of a category",
+    description = "Sets part of a category's allocation aside for a planned purchase, e.g. 200 of Fun for concert tickets. The month summary shows it as committed until an item is posted for it, either by passing `earmark_id` or by describing the item with the earmark's label."
+)]
+pub async fn create_earmark(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path((month_id, budget_id)): Path<(i64, i64)>,
+    Json(payload): Json<CreateEarmark>,
+) -> Result<Json<Earmark>, PaymeError> {
+    payload.validate()?;
+    let label = payload.label.trim().to_string();
+    if label.is_empty() {
+        return Err(PaymeError::BadRequest("Label is required".to_string()));
+    }
+
+    let month: (bool,) =
+        sqlx::query_as("SELECT is_closed FROM months WHERE id = ? AND user_id = ?")
+            .bind(month_id)
+            .bind(claims.sub)
+            .fetch_optional(&pool)
+            .await?
+            .ok_or(PaymeError::NotFound)?;
+    if month.0 {
+        return Err(PaymeError::BadRequest("Month is closed".to_string()));
+    }
+
+    let summary = get_month_summary(&pool, claims.sub, month_id).await?.0;
+    let budget = summary
+        .budgets
+        .iter()
+        .find(|b| b.id == budget_id)
+        .ok_or(PaymeError::NotFound)?;
+    let money = money_format(&pool, claims.sub).await?;
+    if payload.amount > budget.free + 0.005 {
+        return Err(PaymeError::BadRequest(format!(
+            "Only {} of {} is free to earmark",
+            money.format(budget.free.max(0.0)),
+            budget.category_label
+        )));
+    }
+
+    let earmark: Earmark = sqlx::query_as(
+        r#"
+        INSERT INTO budget_earmarks (budget_id, label, amount, created_at)
+        VALUES (?, ?, ?, ?)
+        RETURNING id, budget_id, label, amount, item_id, created_at
+        "#,
+    )
+    .bind(budget_id)
+    .bind(&label)
+    .bind(payload.amount)
+    .bind(Utc::now())
+    .fetch_one(&pool)
+    .await?;
+
+    activity::record(
+        &pool,
+        &claims,
+        Some(month_id),
+        "budget",
+        budget_id,
+        "earmark",
+        format!(
+            "{} earmarked {} of {} for {}",
+            claims.username,
+            money.format(earmark.amount),
+            budget.category_label,
+            earmark.label
+        ),
+    )
+    .await?;
+
+    Ok(Json(earmark))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/months/{month_id}/budgets/{id}/earmarks/{earmark_id}",
+    params(
+        ("month_id" = i64, Path, description = "Month ID"),
+        ("id" = i64, Path, description = "Budget ID"),
+        ("earmark_id" = i64, Path, description = "Earmark ID")
+    ),
+    responses(
+        (status = 204, description = "Earmark removed"),
+        (status = 400, description = "Month is closed"),
+        (status = 404, description = "Earmark not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Budgets",
+    summary = "Remove an earmark",
+    description = "Releases the money set aside back into the category's free remainder. An item already posted for it is kept."
+)]
+pub async fn delete_earmark(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path((month_id, budget_id, earmark_id)): Path<(i64, i64, i64)>,
+) -> Result<StatusCode, PaymeError> {
+    let earmark: (bool, String) = sqlx::query_as(
+        r#"
+        SELECT m.is_closed, e.label FROM budget_earmarks e
+        JOIN monthly_budgets mb ON e.budget_id = mb.id
+        JOIN months m ON mb.month_id = m.id
+        WHERE e.id = ? AND e.budget_id = ? AND m.id = ? AND m.user_id = ?
+        "#,
+    )
+    .bind(earmark_id)
+    .bind(budget_id)
+    .bind(month_id)
+    .bind(claims.sub)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(PaymeError::NotFound)?;
+    if earmark.0 {
+        return Err(PaymeError::BadRequest("Month is closed".to_string()));
+    }
+
+    sqlx::query("DELETE FROM budget_earmarks WHERE id = ?")
+        .bind(earmark_id)
+        .execute(&pool)
+        .await?;
+
+    activity::record(
+        &pool,
+        &claims,
+        Some(month_id),
+        "budget",
+        budget_id,
+        "unearmark",
+        format!("{} removed the earmark for {}", claims.username, earmark.1),
+    )
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/months/{id}/envelopes",
+    params(("id" = i64, Path, description = "Month ID")),
+    responses(
+        (status = 200, body = EnvelopesResponse),
+        (status = 404, description = "Month not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Budgets",
+    summary = "Get envelope balances",
+    description = "Lists each category's available balance (allocated minus spent) and suggests moves that cover overspent categories, first from unassigned income, then from categories with money left."
+)]
+pub async fn get_envelopes(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(month_id): Path<i64>,
+) -> Result<Json<EnvelopesResponse>, PaymeError> {
+    let _month: (i64,) = sqlx::query_as("SELECT id FROM months WHERE id = ? AND user_id = ?")
+        .bind(month_id)
+        .bind(claims.sub)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or(PaymeError::NotFound)?;
+
+    let summary = get_month_summary(&pool, claims.sub, month_id).await?.0;
+
+    let envelopes: Vec<Envelope> = summary
+        .budgets
+        .into_iter()
+        .filter(|b| !b.tracking_only)
+        .map(|b| Envelope {
+            budget_id: b.id,
+            category_id: b.category_id,
+            category_label: b.category_label,
+            allocated: b.allocated_amount,
+            spent: b.spent_amount,
+            available: b.available,
+        })
+        .collect();
+    let unassigned = summary.total_income - summary.total_fixed - summary.total_budgeted;
+    let suggestions = envelopes::cover_suggestions(&envelopes, unassigned);
+
+    Ok(Json(EnvelopesResponse {
+        month_id,
+        unassigned,
+        envelopes,
+        suggestions,
     }))
 }
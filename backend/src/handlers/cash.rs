@@ -0,0 +1,52 @@
+use axum::extract::State;
+use sqlx::SqlitePool;
+
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::middleware::auth::Claims;
+use crate::models::{CashMonth, CashReport};
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/cash",
+    responses(
+        (status = 200, body = CashReport),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Items",
+    summary = "Cash wallet",
+    description = "Compares cash withdrawn (items with `savings_destination` `cash`) with the items paid in cash, overall and per month. What's left is untracked: still in the wallet, or spent without being recorded."
+)]
+pub async fn get_cash_report(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+) -> Result<Json<CashReport>, PaymeError> {
+    let months: Vec<CashMonth> = sqlx::query_as(
+        r#"
+        SELECT month_id, year, month, withdrawn, itemized, withdrawn - itemized AS untracked
+        FROM (
+            SELECT m.id AS month_id, m.year, m.month,
+                   COALESCE(SUM(CASE WHEN i.savings_destination = 'cash' THEN i.amount END), 0.0) AS withdrawn,
+                   COALESCE(SUM(CASE WHEN i.paid_in_cash = 1 THEN i.amount END), 0.0) AS itemized
+            FROM items i
+            JOIN months m ON i.month_id = m.id
+            WHERE m.user_id = ? AND (i.savings_destination = 'cash' OR i.paid_in_cash = 1)
+            GROUP BY m.id
+        )
+        ORDER BY year DESC, month DESC
+        "#,
+    )
+    .bind(claims.sub)
+    .fetch_all(&pool)
+    .await?;
+
+    let withdrawn: f64 = months.iter().map(|m| m.withdrawn).sum();
+    let itemized: f64 = months.iter().map(|m| m.itemized).sum();
+
+    Ok(Json(CashReport {
+        withdrawn,
+        itemized,
+        untracked: withdrawn - itemized,
+        months,
+    }))
+}
@@ -0,0 +1,101 @@
+use axum::extract::{Path, State};
+use chrono::NaiveDate;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use utoipa::ToSchema;
+
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::handlers::settings::load_settings;
+use crate::holidays::{self, Roll};
+use crate::middleware::auth::Claims;
+use crate::summary;
+
+/// Money coming in or going out on a given day.
+#[derive(Serialize, ToSchema)]
+pub struct CashflowEntry {
+    /// Business day the money moves on.
+    pub date: NaiveDate,
+    /// Day it is scheduled on, before moving around weekends and bank holidays.
+    pub scheduled_on: NaiveDate,
+    /// `income` or `fixed_expense`.
+    pub kind: String,
+    pub label: String,
+    /// Positive for income, negative for payments.
+    pub amount: f64,
+    /// Sum of the amounts up to and including this entry.
+    pub balance: f64,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/months/{id}/cashflow",
+    params(("id" = i64, Path, description = "Month ID")),
+    responses(
+        (status = 200, body = [CashflowEntry]),
+        (status = 404, description = "Month not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Months",
+    summary = "Get the cashflow calendar",
+    description = "Lists the days money moves in the month: income on the payday and fixed expenses due this month on their payment day, each moved around weekends and the bank holidays of the `holiday_country` setting. Income needs the `payday` setting and fixed expenses a `payment_day`; the others are left out. A payment rolled forward past the month's end is listed on the day it goes through."
+)]
+pub async fn get_cashflow(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(month_id): Path<i64>,
+) -> Result<Json<Vec<CashflowEntry>>, PaymeError> {
+    let _month: (i64,) = sqlx::query_as("SELECT id FROM months WHERE id = ? AND user_id = ?")
+        .bind(month_id)
+        .bind(claims.sub)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or(PaymeError::NotFound)?;
+    let summary = summary::month_summary(&pool, claims.sub, month_id, false).await?;
+    let settings = load_settings(&pool, claims.sub).await?;
+    let (year, month) = (summary.month.year, summary.month.month);
+    let unrolled = |day: i32| holidays::scheduled(year, month as u32, day as u32, Roll::None, None);
+
+    let mut entries = Vec::new();
+    if let (Some(payday), Some(date)) = (settings.payday, settings.payday_on(year, month)) {
+        for income in &summary.income_entries {
+            entries.push(CashflowEntry {
+                date,
+                scheduled_on: unrolled(payday).unwrap_or(date),
+                kind: "income".to_string(),
+                label: income.label.clone(),
+                amount: income.amount,
+                balance: 0.0,
+            });
+        }
+    }
+    let country = settings.holiday_country.as_deref();
+    for expense in summary
+        .fixed_expenses
+        .iter()
+        .filter(|expense| expense.is_due_in(month))
+    {
+        let (Some(day), Some(date)) = (expense.payment_day, expense.due_on(year, month, country))
+        else {
+            continue;
+        };
+        entries.push(CashflowEntry {
+            date,
+            scheduled_on: unrolled(day).unwrap_or(date),
+            kind: "fixed_expense".to_string(),
+            label: expense.label.clone(),
+            amount: -expense.amount,
+            balance: 0.0,
+        });
+    }
+
+    // Income first on a day money also goes out
+    entries.sort_by_key(|entry| (entry.date, entry.kind != "income"));
+    let mut balance = 0.0;
+    for entry in &mut entries {
+        balance += entry.amount;
+        entry.balance = (balance * 100.0).round() / 100.0;
+    }
+
+    Ok(Json(entries))
+}
@@ -0,0 +1,100 @@
+use axum::extract::State;
+use serde::Deserialize;
+use sqlx::{SqliteExecutor, SqlitePool};
+use utoipa::ToSchema;
+use validator::{Validate, ValidationError};
+
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::middleware::auth::Claims;
+use crate::models::ChecklistEntry;
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct SetChecklist {
+    /// Steps in the order they are shown, e.g. "Paid the credit card". An empty list
+    /// removes the checklist.
+    #[validate(length(max = 20), custom(function = "labels"))]
+    pub entries: Vec<String>,
+}
+
+fn labels(entries: &[String]) -> Result<(), ValidationError> {
+    if entries
+        .iter()
+        .all(|label| (1..=100).contains(&label.trim().chars().count()))
+    {
+        Ok(())
+    } else {
+        Err(ValidationError::new("length"))
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/close-checklist",
+    responses(
+        (status = 200, body = [ChecklistEntry]),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Months",
+    summary = "Get the close checklist",
+    description = "Lists the steps to acknowledge before closing a month."
+)]
+pub async fn get_checklist(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+) -> Result<Json<Vec<ChecklistEntry>>, PaymeError> {
+    load_checklist(&pool, claims.sub).await.map(Json)
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/close-checklist",
+    request_body = SetChecklist,
+    responses(
+        (status = 200, body = [ChecklistEntry]),
+        (status = 400, description = "Too many entries, or an empty or overlong label"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Months",
+    summary = "Replace the close checklist",
+    description = "Replaces the steps to acknowledge before closing a month. Entries get new ids; months already closed keep the checklist they were closed with."
+)]
+pub async fn set_checklist(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Json(payload): Json<SetChecklist>,
+) -> Result<Json<Vec<ChecklistEntry>>, PaymeError> {
+    payload.validate()?;
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM close_checklist WHERE user_id = ?")
+        .bind(claims.sub)
+        .execute(&mut *tx)
+        .await?;
+    for (position, label) in payload.entries.iter().enumerate() {
+        sqlx::query("INSERT INTO close_checklist (user_id, label, position) VALUES (?, ?, ?)")
+            .bind(claims.sub)
+            .bind(label.trim())
+            .bind(position as i64)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    load_checklist(&pool, claims.sub).await.map(Json)
+}
+
+pub(crate) async fn load_checklist<'e>(
+    executor: impl SqliteExecutor<'e>,
+    user_id: i64,
+) -> Result<Vec<ChecklistEntry>, PaymeError> {
+    let entries = sqlx::query_as(
+        "SELECT id, label FROM close_checklist WHERE user_id = ? ORDER BY position, id",
+    )
+    .bind(user_id)
+    .fetch_all(executor)
+    .await?;
+
+    Ok(entries)
+}
@@ -0,0 +1,94 @@
+use axum::extract::{Query, State};
+use serde::Serialize;
+use sqlx::{QueryBuilder, SqlitePool};
+use utoipa::ToSchema;
+
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::filters::{Columns, ListFilter};
+use crate::middleware::auth::Claims;
+
+/// Billable spending for one client, to invoice them from.
+#[derive(Serialize, ToSchema)]
+pub struct ClientReport {
+    pub client: String,
+    pub total: f64,
+    /// Oldest month first; months without billable items are left out.
+    pub months: Vec<ClientMonth>,
+}
+
+#[derive(Serialize, ToSchema, sqlx::FromRow)]
+pub struct ClientMonth {
+    pub year: i32,
+    pub month: i32,
+    pub total: f64,
+    /// How many billable items make up the total.
+    pub items: i64,
+}
+
+#[derive(sqlx::FromRow)]
+struct ClientMonthRow {
+    client: String,
+    #[sqlx(flatten)]
+    month: ClientMonth,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/clients/report",
+    params(ListFilter),
+    responses(
+        (status = 200, body = [ClientReport]),
+        (status = 400, description = "Invalid filter"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Clients",
+    summary = "Billable spending by client",
+    description = "Totals billable items per client and month, for invoicing. Filters by category, spending date, amount and text in the client name. Savings transfers are left out, and items stay in the report after the business profile is turned off."
+)]
+pub async fn get_client_report(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Query(filter): Query<ListFilter>,
+) -> Result<Json<Vec<ClientReport>>, PaymeError> {
+    let mut query = QueryBuilder::new(
+        r#"
+        SELECT i.client, m.year, m.month, SUM(i.amount) AS total, COUNT(*) AS items
+        FROM items i
+        JOIN months m ON i.month_id = m.id
+        WHERE i.billable = 1 AND i.client IS NOT NULL AND i.savings_destination = 'none'
+          AND m.user_id = "#,
+    );
+    query.push_bind(claims.sub);
+    filter.apply(
+        &mut query,
+        &Columns {
+            category: Some("i.category_id"),
+            date: Some("i.spent_on"),
+            amount: Some("i.amount"),
+            text: &["i.client"],
+        },
+    )?;
+    query.push(" GROUP BY i.client, m.year, m.month ORDER BY i.client, m.year, m.month");
+    let rows: Vec<ClientMonthRow> = query.build_query_as().fetch_all(&pool).await?;
+
+    let mut reports: Vec<ClientReport> = Vec::new();
+    for row in rows {
+        match reports.last_mut() {
+            Some(report) if report.client == row.client => {
+                report.total += row.month.total;
+                report.months.push(row.month);
+            }
+            _ => reports.push(ClientReport {
+                client: row.client,
+                total: row.month.total,
+                months: vec![row.month],
+            }),
+        }
+    }
+    for report in &mut reports {
+        report.total = (report.total * 100.0).round() / 100.0;
+    }
+
+    Ok(Json(reports))
+}
@@ -0,0 +1,246 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Redirect,
+};
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+use crate::connectors::Provider;
+use crate::crypto;
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::jwt;
+use crate::middleware::auth::Claims;
+use crate::middleware::maintenance::MaintenanceMode;
+use crate::models::CloudConnection;
+
+/// How long the user has to grant access once they start connecting.
+const AUTHORIZE_MINUTES: i64 = 15;
+
+fn default_folder() -> String {
+    "payme".to_string()
+}
+
+fn default_mirror_snapshots() -> bool {
+    true
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct ConnectCloud {
+    /// Dropbox folder, or name of the Google Drive folder to create, that files are
+    /// uploaded to. Defaults to `payme`.
+    #[serde(default = "default_folder")]
+    #[validate(length(min = 1, max = 200))]
+    pub folder: String,
+    /// Also upload each closed month's PDF. Defaults to true.
+    #[serde(default = "default_mirror_snapshots")]
+    pub mirror_snapshots: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CloudAuthorization {
+    /// Page to send the user to. The provider brings them back to the callback.
+    pub url: String,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CloudCallback {
+    pub code: Option<String>,
+    pub state: Option<String>,
+    /// Set by the provider when the user declined.
+    pub error: Option<String>,
+}
+
+/// Carried through the provider in the OAuth `state`, so the callback knows whose
+/// connection it completes and can't be replayed for someone else.
+#[derive(Serialize, Deserialize)]
+struct ConnectState {
+    uid: i64,
+    provider: String,
+    folder: String,
+    mirror: bool,
+    exp: usize,
+}
+
+fn provider(name: &str) -> Result<Provider, PaymeError> {
+    Provider::parse(name).ok_or(PaymeError::NotFound)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/connectors",
+    responses(
+        (status = 200, body = [CloudConnection]),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Reports",
+    summary = "List cloud drive connections",
+    description = "Lists the Dropbox and Google Drive accounts reports and PDF snapshots are uploaded to."
+)]
+pub async fn list_connections(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+) -> Result<Json<Vec<CloudConnection>>, PaymeError> {
+    let connections: Vec<CloudConnection> = sqlx::query_as(
+        r#"
+        SELECT provider, folder, mirror_snapshots, last_upload_at, last_error, created_at
+        FROM cloud_connections WHERE user_id = ? ORDER BY provider
+        "#,
+    )
+    .bind(claims.sub)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(connections))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/connectors/{provider}/authorize",
+    params(("provider" = String, Path, description = "`dropbox` or `google_drive`")),
+    request_body = ConnectCloud,
+    responses(
+        (status = 200, body = CloudAuthorization),
+        (status = 400, description = "The provider is not configured on this server"),
+        (status = 404, description = "Unknown provider"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Reports",
+    summary = "Start connecting a cloud drive",
+    description = "Returns the provider's page where the user grants payme access. Connecting again replaces the existing connection."
+)]
+pub async fn authorize_connection(
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(name): Path<String>,
+    Json(payload): Json<ConnectCloud>,
+) -> Result<Json<CloudAuthorization>, PaymeError> {
+    payload.validate()?;
+    let provider = provider(&name)?;
+    let client = provider.client()?;
+    let redirect_uri = provider.redirect_uri()?;
+
+    let state = jwt::keys().sign(&ConnectState {
+        uid: claims.sub,
+        provider: provider.as_str().to_string(),
+        folder: payload.folder.trim().to_string(),
+        mirror: payload.mirror_snapshots,
+        exp: (Utc::now() + Duration::minutes(AUTHORIZE_MINUTES)).timestamp() as usize,
+    })?;
+
+    Ok(Json(CloudAuthorization {
+        url: provider.authorize_url(&client.client_id, &redirect_uri, &state),
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/connectors/{provider}/callback",
+    params(
+        ("provider" = String, Path, description = "`dropbox` or `google_drive`"),
+        CloudCallback
+    ),
+    responses(
+        (status = 303, description = "Connected, or declined by the user; redirects to the app"),
+        (status = 400, description = "Missing code, or an expired or invalid state"),
+        (status = 404, description = "Unknown provider"),
+        (status = 500, description = "The provider rejected the code"),
+        (status = 503, description = "The API is read-only")
+    ),
+    security(()),
+    tag = "Reports",
+    summary = "Finish connecting a cloud drive",
+    description = "Where the provider sends the user back to. Stores the connection and redirects to the app with `connected` or `connector_error` in the query."
+)]
+pub async fn connection_callback(
+    State(pool): State<SqlitePool>,
+    axum::Extension(mode): axum::Extension<MaintenanceMode>,
+    Path(name): Path<String>,
+    Query(query): Query<CloudCallback>,
+) -> Result<Redirect, PaymeError> {
+    // A GET, but it stores the connection
+    if mode.is_read_only() {
+        return Err(PaymeError::ReadOnly);
+    }
+    let provider = provider(&name)?;
+    if query.error.is_some() {
+        return Ok(Redirect::to(&format!(
+            "/?connector_error={}",
+            provider.as_str()
+        )));
+    }
+    let (Some(code), Some(state)) = (query.code, query.state) else {
+        return Err(PaymeError::BadRequest("Missing code or state".to_string()));
+    };
+    let state: ConnectState = jwt::keys()
+        .verify(&pool, &state)
+        .await
+        .ok()
+        .filter(|state: &ConnectState| state.provider == provider.as_str())
+        .ok_or_else(|| PaymeError::BadRequest("The link has expired, connect again".to_string()))?;
+
+    let connection = provider
+        .connect(&code, &provider.redirect_uri()?, &state.folder)
+        .await?;
+    sqlx::query(
+        r#"
+        INSERT INTO cloud_connections
+            (user_id, provider, refresh_token, folder, folder_id, mirror_snapshots, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(user_id, provider) DO UPDATE SET
+            refresh_token = excluded.refresh_token,
+            folder = excluded.folder,
+            folder_id = excluded.folder_id,
+            mirror_snapshots = excluded.mirror_snapshots,
+            last_error = NULL,
+            created_at = excluded.created_at
+        "#,
+    )
+    .bind(state.uid)
+    .bind(provider.as_str())
+    .bind(crypto::seal_text(&connection.refresh_token)?)
+    .bind(&state.folder)
+    .bind(&connection.folder_id)
+    .bind(state.mirror)
+    .bind(Utc::now())
+    .execute(&pool)
+    .await?;
+
+    Ok(Redirect::to(&format!("/?connected={}", provider.as_str())))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/connectors/{provider}",
+    params(("provider" = String, Path, description = "`dropbox` or `google_drive`")),
+    responses(
+        (status = 204, description = "Disconnected"),
+        (status = 404, description = "Not connected"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Reports",
+    summary = "Disconnect a cloud drive",
+    description = "Forgets the connection. Files already uploaded stay in the drive; reports delivered there fail until it is connected again."
+)]
+pub async fn delete_connection(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, PaymeError> {
+    let provider = provider(&name)?;
+    let result = sqlx::query("DELETE FROM cloud_connections WHERE user_id = ? AND provider = ?")
+        .bind(claims.sub)
+        .bind(provider.as_str())
+        .execute(&pool)
+        .await?;
+    if result.rows_affected() == 0 {
+        return Err(PaymeError::NotFound);
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
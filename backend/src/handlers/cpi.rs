@@ -0,0 +1,95 @@
+use std::collections::HashSet;
+
+use axum::extract::State;
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use utoipa::ToSchema;
+use validator::{Validate, ValidationError};
+
+use crate::cpi;
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::middleware::auth::Claims;
+use crate::models::CpiReading;
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct SetCpi {
+    /// Monthly readings from any published series, e.g. the national CPI. An empty list
+    /// removes the series.
+    #[validate(length(max = 1200), custom(function = "readings"))]
+    pub readings: Vec<CpiReading>,
+}
+
+fn readings(readings: &[CpiReading]) -> Result<(), ValidationError> {
+    let mut seen = HashSet::new();
+    let valid = readings.iter().all(|r| {
+        (1..=12).contains(&r.month)
+            && (1900..=2200).contains(&r.year)
+            && r.value.is_finite()
+            && r.value > 0.0
+            && seen.insert((r.year, r.month))
+    });
+    if valid {
+        Ok(())
+    } else {
+        Err(ValidationError::new("cpi_reading"))
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/cpi",
+    responses(
+        (status = 200, body = [CpiReading]),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Insights",
+    summary = "Get the CPI series",
+    description = "Lists the consumer price index readings used to show stats in real terms, oldest first."
+)]
+pub async fn get_cpi(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+) -> Result<Json<Vec<CpiReading>>, PaymeError> {
+    Ok(Json(cpi::load_readings(&pool, claims.sub).await?))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/cpi",
+    request_body = SetCpi,
+    responses(
+        (status = 200, body = [CpiReading]),
+        (status = 400, description = "Invalid month, a non-positive value, or a month given twice"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Insights",
+    summary = "Replace the CPI series",
+    description = "Replaces the consumer price index readings. Any base period works, only the ratios between readings are used. Months without a reading use the latest one before them."
+)]
+pub async fn set_cpi(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Json(payload): Json<SetCpi>,
+) -> Result<Json<Vec<CpiReading>>, PaymeError> {
+    payload.validate()?;
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM cpi_readings WHERE user_id = ?")
+        .bind(claims.sub)
+        .execute(&mut *tx)
+        .await?;
+    for reading in &payload.readings {
+        sqlx::query("INSERT INTO cpi_readings (user_id, year, month, value) VALUES (?, ?, ?, ?)")
+            .bind(claims.sub)
+            .bind(reading.year)
+            .bind(reading.month)
+            .bind(reading.value)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    Ok(Json(cpi::load_readings(&pool, claims.sub).await?))
+}
@@ -0,0 +1,229 @@
+use axum::extract::State;
+use chrono::{Datelike, Months, NaiveDate, Utc};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use utoipa::ToSchema;
+
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::feed::refresh_insights;
+use crate::handlers::months::{current_month_to_read, get_month_summary};
+use crate::handlers::settings::load_settings;
+use crate::middleware::auth::Claims;
+use crate::middleware::maintenance::MaintenanceMode;
+use crate::models::{FixedExpense, Insight, Month, MonthMetrics, MonthSummary, UserSettings};
+
+/// Insights shown as alerts on the home screen.
+const ALERTS: i64 = 3;
+
+#[derive(Serialize, ToSchema)]
+pub struct Dashboard {
+    pub month: DashboardMonth,
+    pub savings: f64,
+    pub savings_goal: f64,
+    pub retirement_savings: f64,
+    /// The newest insights that haven't been dismissed, unread ones first.
+    pub alerts: Vec<Insight>,
+    /// Fixed expenses due this month and next month.
+    pub upcoming_fixed_expenses: Vec<UpcomingFixedExpense>,
+    /// The next day income lands, moved around weekends and bank holidays. Null
+    /// without a payday setting.
+    pub next_payday: Option<NaiveDate>,
+    /// The previous month, when the user has one.
+    pub last_month: Option<MonthComparison>,
+}
+
+/// The current month's totals, without its entries.
+#[derive(Serialize, ToSchema)]
+pub struct DashboardMonth {
+    pub month: Month,
+    pub total_income: f64,
+    pub total_fixed: f64,
+    pub fixed_due: f64,
+    pub total_budgeted: f64,
+    pub to_be_budgeted: f64,
+    pub total_spent: f64,
+    pub remaining: f64,
+    pub no_spend_days: i64,
+    pub metrics: MonthMetrics,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct UpcomingFixedExpense {
+    #[serde(flatten)]
+    pub expense: FixedExpense,
+    pub due_year: i32,
+    pub due_month: i32,
+    /// Business day the payment goes through, when the expense has a payment day.
+    pub due_on: Option<NaiveDate>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct MonthComparison {
+    pub year: i32,
+    pub month: i32,
+    pub total_income: f64,
+    pub total_spent: f64,
+    pub remaining: f64,
+    /// This month's spending minus last month's, negative when spending went down.
+    pub spent_change: f64,
+    /// `spent_change` as a fraction of last month's spending, null when nothing was spent.
+    pub spent_change_ratio: Option<f64>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/dashboard",
+    responses(
+        (status = 200, body = Dashboard),
+        (status = 500, description = "Internal server error"),
+        (status = 503, description = "The current month doesn't exist yet and the API is read-only")
+    ),
+    tag = "Months",
+    summary = "Get the home screen",
+    description = "Returns what the home screen shows in one call: the current month's totals (creating the month if needed), savings and retirement balances, the top alerts, fixed expenses due this month and next with the day they go through, the next payday, and a comparison with last month."
+)]
+pub async fn get_dashboard(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    axum::Extension(mode): axum::Extension<MaintenanceMode>,
+) -> Result<Json<Dashboard>, PaymeError> {
+    let month = current_month_to_read(&pool, claims.sub, &mode).await?;
+    let Json(summary) = get_month_summary(&pool, claims.sub, month.id).await?;
+
+    let (savings, savings_goal, retirement_savings): (f64, f64, f64) =
+        sqlx::query_as("SELECT savings, savings_goal, retirement_savings FROM users WHERE id = ?")
+            .bind(claims.sub)
+            .fetch_one(&pool)
+            .await?;
+
+    let alerts = load_alerts(&pool, claims.sub, mode.is_read_only()).await?;
+    let settings = load_settings(&pool, claims.sub).await?;
+    let upcoming_fixed_expenses = upcoming(&summary, settings.holiday_country.as_deref());
+    let next_payday = next_payday(&settings, Utc::now().date_naive());
+    let last_month = compare_with_last_month(&pool, claims.sub, &summary).await?;
+
+    Ok(Json(Dashboard {
+        month: DashboardMonth {
+            month: summary.month,
+            total_income: summary.total_income,
+            total_fixed: summary.total_fixed,
+            fixed_due: summary.fixed_due,
+            total_budgeted: summary.total_budgeted,
+            to_be_budgeted: summary.to_be_budgeted,
+            total_spent: summary.total_spent,
+            remaining: summary.remaining,
+            no_spend_days: summary.no_spend_days,
+            metrics: summary.metrics,
+        },
+        savings,
+        savings_goal,
+        retirement_savings,
+        alerts,
+        upcoming_fixed_expenses,
+        next_payday,
+        last_month,
+    }))
+}
+
+async fn load_alerts(
+    pool: &SqlitePool,
+    user_id: i64,
+    read_only: bool,
+) -> Result<Vec<Insight>, PaymeError> {
+    // Insights are computed on first access, as the insights feed does, unless nothing
+    // may be written
+    let scored: bool =
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM insight_scores WHERE user_id = ?)")
+            .bind(user_id)
+            .fetch_one(pool)
+            .await?;
+    if !scored && !read_only {
+        refresh_insights(pool, user_id).await?;
+    }
+
+    let alerts = sqlx::query_as(
+        r#"
+        SELECT id, kind, message, is_read, is_dismissed, created_at
+        FROM insights
+        WHERE user_id = ? AND is_dismissed = 0
+        ORDER BY is_read, created_at DESC, id DESC
+        LIMIT ?
+        "#,
+    )
+    .bind(user_id)
+    .bind(ALERTS)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(alerts)
+}
+
+fn upcoming(summary: &MonthSummary, country: Option<&str>) -> Vec<UpcomingFixedExpense> {
+    let this = (summary.month.year, summary.month.month);
+    let next = if this.1 == 12 {
+        (this.0 + 1, 1)
+    } else {
+        (this.0, this.1 + 1)
+    };
+
+    [this, next]
+        .into_iter()
+        .flat_map(|(year, month)| {
+            summary
+                .fixed_expenses
+                .iter()
+                .filter(move |expense| expense.is_due_in(month))
+                .map(move |expense| UpcomingFixedExpense {
+                    expense: expense.clone(),
+                    due_year: year,
+                    due_month: month,
+                    due_on: expense.due_on(year, month, country),
+                })
+        })
+        .collect()
+}
+
+/// The first payday on or after `today`, looking at this month and the next.
+fn next_payday(settings: &UserSettings, today: NaiveDate) -> Option<NaiveDate> {
+    let next = today.checked_add_months(Months::new(1))?;
+    [today, next]
+        .into_iter()
+        .filter_map(|day| settings.payday_on(day.year(), day.month() as i32))
+        .find(|payday| *payday >= today)
+}
+
+async fn compare_with_last_month(
+    pool: &SqlitePool,
+    user_id: i64,
+    current: &MonthSummary,
+) -> Result<Option<MonthComparison>, PaymeError> {
+    let Some(previous) = NaiveDate::from_ymd_opt(current.month.year, current.month.month as u32, 1)
+        .and_then(|first| first.checked_sub_months(Months::new(1)))
+    else {
+        return Ok(None);
+    };
+    let month_id: Option<i64> =
+        sqlx::query_scalar("SELECT id FROM months WHERE user_id = ? AND year = ? AND month = ?")
+            .bind(user_id)
+            .bind(previous.year())
+            .bind(previous.month() as i32)
+            .fetch_optional(pool)
+            .await?;
+    let Some(month_id) = month_id else {
+        return Ok(None);
+    };
+
+    let Json(last) = get_month_summary(pool, user_id, month_id).await?;
+    let spent_change = current.total_spent - last.total_spent;
+
+    Ok(Some(MonthComparison {
+        year: last.month.year,
+        month: last.month.month,
+        total_income: last.total_income,
+        total_spent: last.total_spent,
+        remaining: last.remaining,
+        spent_change,
+        spent_change_ratio: (last.total_spent > 0.0).then(|| spent_change / last.total_spent),
+    }))
+}
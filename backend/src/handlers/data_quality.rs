@@ -0,0 +1,185 @@
+use axum::extract::{Path, State};
+use chrono::{Months, Utc};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use utoipa::ToSchema;
+
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::middleware::auth::Claims;
+use crate::models::{DataQualityReport, QualityFinding};
+
+/// Months without spending after which a category is reported as idle.
+const IDLE_MONTHS: u32 = 6;
+
+#[derive(Serialize, ToSchema)]
+pub struct DataQualityFix {
+    /// Records removed by the fix.
+    pub fixed: u64,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/maintenance/data-quality",
+    responses(
+        (status = 200, body = DataQualityReport),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Data Management",
+    summary = "Data-quality report",
+    description = "Flags suspicious data: zero-amount items, categories idle for six months, budgets pointing at deleted categories, months without income and duplicated fixed expenses."
+)]
+pub async fn get_data_quality(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+) -> Result<Json<DataQualityReport>, PaymeError> {
+    let zero_amount_items: Vec<QualityFinding> = sqlx::query_as(
+        r#"
+        SELECT i.id, i.month_id, i.description AS label
+        FROM items i
+        JOIN months m ON i.month_id = m.id
+        WHERE m.user_id = ? AND i.amount = 0
+        ORDER BY i.spent_on, i.id
+        "#,
+    )
+    .bind(claims.sub)
+    .fetch_all(&pool)
+    .await?;
+
+    // Only accounts with six months of history can have an idle category
+    let idle_since = Utc::now().date_naive() - Months::new(IDLE_MONTHS);
+    let idle_categories: Vec<QualityFinding> = sqlx::query_as(
+        r#"
+        SELECT bc.id, NULL AS month_id, bc.label
+        FROM budget_categories bc
+        WHERE bc.user_id = ?
+          AND EXISTS (
+            SELECT 1 FROM months m
+            WHERE m.user_id = bc.user_id AND printf('%04d-%02d-01', m.year, m.month) <= ?
+          )
+          AND NOT EXISTS (
+            SELECT 1 FROM items i WHERE i.category_id = bc.id AND i.spent_on > ?
+          )
+        ORDER BY bc.label
+        "#,
+    )
+    .bind(claims.sub)
+    .bind(idle_since)
+    .bind(idle_since)
+    .fetch_all(&pool)
+    .await?;
+
+    let orphaned_budgets: Vec<QualityFinding> = sqlx::query_as(
+        r#"
+        SELECT mb.id, mb.month_id, 'Category ' || mb.category_id AS label
+        FROM monthly_budgets mb
+        JOIN months m ON mb.month_id = m.id
+        LEFT JOIN budget_categories bc ON mb.category_id = bc.id
+        WHERE m.user_id = ? AND bc.id IS NULL
+        ORDER BY mb.month_id, mb.id
+        "#,
+    )
+    .bind(claims.sub)
+    .fetch_all(&pool)
+    .await?;
+
+    let months_without_income: Vec<QualityFinding> = sqlx::query_as(
+        r#"
+        SELECT m.id, m.id AS month_id, printf('%04d-%02d', m.year, m.month) AS label
+        FROM months m
+        WHERE m.user_id = ?
+          AND NOT EXISTS (SELECT 1 FROM income_entries ie WHERE ie.month_id = m.id)
+        ORDER BY m.year, m.month
+        "#,
+    )
+    .bind(claims.sub)
+    .fetch_all(&pool)
+    .await?;
+
+    let duplicate_fixed_expenses: Vec<QualityFinding> = sqlx::query_as(DUPLICATE_FIXED_EXPENSES)
+        .bind(claims.sub)
+        .fetch_all(&pool)
+        .await?;
+
+    Ok(Json(DataQualityReport {
+        zero_amount_items,
+        idle_categories,
+        orphaned_budgets,
+        months_without_income,
+        duplicate_fixed_expenses,
+    }))
+}
+
+/// Every fixed expense but the first with the same label (ignoring case and spacing),
+/// amount and billing period.
+const DUPLICATE_FIXED_EXPENSES: &str = r#"
+    SELECT fe.id, NULL AS month_id, fe.label
+    FROM fixed_expenses fe
+    WHERE fe.user_id = ?
+      AND EXISTS (
+        SELECT 1 FROM fixed_expenses earlier
+        WHERE earlier.user_id = fe.user_id
+          AND LOWER(TRIM(earlier.label)) = LOWER(TRIM(fe.label))
+          AND ABS(earlier.amount - fe.amount) < 0.005
+          AND earlier.billing_period = fe.billing_period
+          AND earlier.id < fe.id
+      )
+    ORDER BY fe.id
+"#;
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/maintenance/data-quality/{check}/fix",
+    params(("check" = String, Path, description = "`zero_amount_items` or `orphaned_budgets`")),
+    responses(
+        (status = 200, body = DataQualityFix),
+        (status = 400, description = "Check has no automatic fix"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Data Management",
+    summary = "Fix a data-quality finding",
+    description = "Deletes the records flagged by a check: zero-amount items in open months or budgets of deleted categories. Idle categories, months without income and duplicated fixed expenses need a decision and are left to the user."
+)]
+pub async fn fix_data_quality(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(check): Path<String>,
+) -> Result<Json<DataQualityFix>, PaymeError> {
+    let result = match check.as_str() {
+        // Closed months keep what they recorded
+        "zero_amount_items" => {
+            sqlx::query(
+                r#"
+                DELETE FROM items
+                WHERE amount = 0 AND month_id IN (
+                    SELECT id FROM months WHERE user_id = ? AND is_closed = 0
+                )
+                "#,
+            )
+            .bind(claims.sub)
+            .execute(&pool)
+            .await?
+        }
+        "orphaned_budgets" => {
+            sqlx::query(
+                r#"
+                DELETE FROM monthly_budgets
+                WHERE month_id IN (SELECT id FROM months WHERE user_id = ?)
+                  AND category_id NOT IN (SELECT id FROM budget_categories)
+                "#,
+            )
+            .bind(claims.sub)
+            .execute(&pool)
+            .await?
+        }
+        other => {
+            return Err(PaymeError::BadRequest(format!(
+                "No automatic fix for {other}"
+            )))
+        }
+    };
+
+    Ok(Json(DataQualityFix {
+        fixed: result.rows_affected(),
+    }))
+}
@@ -1,11 +1,28 @@
-use axum::{extract::State, http::StatusCode, Json};
+use std::io::{Cursor, Write};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{
+        header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+        StatusCode,
+    },
+    response::{IntoResponse, Response},
+};
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
-use utoipa::ToSchema;
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
 
+use crate::dry_run::{self, DryRunQuery};
 use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::handlers::fixed_expenses::{billing_schedule, default_billing_period};
+use crate::handlers::months::get_month_summary;
 use crate::middleware::auth::Claims;
-use crate::models::{BudgetCategory, FixedExpense, IncomeEntry, Item, Month};
+use crate::models::{BudgetCategory, FixedExpense, IncomeEntry, Item, Month, MonthSummary};
+use crate::quotas;
+use crate::redaction::{AmountRedaction, DateRedaction, Redaction, TextRedaction};
 
 #[derive(Serialize, Deserialize, ToSchema)]
 pub struct UserExport {
@@ -21,12 +38,18 @@ pub struct UserExport {
 pub struct FixedExpenseExport {
     pub label: String,
     pub amount: f64,
+    #[serde(default = "default_billing_period")]
+    pub billing_period: String,
+    #[serde(default)]
+    pub payment_month: Option<i32>,
 }
 
 #[derive(Serialize, Deserialize, ToSchema)]
 pub struct CategoryExport {
     pub label: String,
     pub default_amount: f64,
+    #[serde(default)]
+    pub tracking_only: bool,
 }
 
 #[derive(Serialize, Deserialize, ToSchema)]
@@ -59,9 +82,175 @@ pub struct ItemExport {
     pub spent_on: String,
 }
 
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MonthExportQuery {
+    /// `json` (default), `csv` (items only) or `zip` (items.csv, income.csv, budgets.csv
+    /// and summary.json).
+    pub format: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/months/{id}/export",
+    params(
+        ("id" = i64, Path, description = "Month ID"),
+        MonthExportQuery
+    ),
+    responses(
+        (status = 200, description = "The month summary as a file download"),
+        (status = 400, description = "Unknown format"),
+        (status = 404, description = "Month not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Data Management",
+    summary = "Export a month",
+    description = "Downloads the full month summary in a machine-readable format for scripts and spreadsheets."
+)]
+pub async fn export_month(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(month_id): Path<i64>,
+    Query(query): Query<MonthExportQuery>,
+) -> Result<impl IntoResponse, PaymeError> {
+    let format = query.format.as_deref().unwrap_or("json");
+    if !matches!(format, "json" | "csv" | "zip") {
+        return Err(PaymeError::BadRequest(format!("Unknown format {format}")));
+    }
+
+    let owned: Option<i64> =
+        sqlx::query_scalar("SELECT id FROM months WHERE id = ? AND user_id = ?")
+            .bind(month_id)
+            .bind(claims.sub)
+            .fetch_optional(&pool)
+            .await?;
+    owned.ok_or(PaymeError::NotFound)?;
+
+    let summary = get_month_summary(&pool, claims.sub, month_id).await?.0;
+    let name = format!("month-{}-{:02}", summary.month.year, summary.month.month);
+
+    let (content_type, data) = match format {
+        "csv" => ("text/csv", items_csv(&summary)?),
+        "zip" => ("application/zip", month_zip(&summary)?),
+        _ => ("application/json", summary_json(&summary)?),
+    };
+
+    Ok((
+        [
+            (CONTENT_TYPE, content_type.to_string()),
+            (
+                CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{name}.{format}\""),
+            ),
+        ],
+        data,
+    ))
+}
+
+fn summary_json(summary: &MonthSummary) -> Result<Vec<u8>, PaymeError> {
+    serde_json::to_vec_pretty(summary).map_err(|e| PaymeError::Internal(e.to_string()))
+}
+
+pub(crate) fn write_csv(header: &[&str], rows: Vec<Vec<String>>) -> Result<Vec<u8>, PaymeError> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(header)
+        .map_err(|e| PaymeError::Internal(e.to_string()))?;
+    for row in rows {
+        writer
+            .write_record(&row)
+            .map_err(|e| PaymeError::Internal(e.to_string()))?;
+    }
+    writer
+        .into_inner()
+        .map_err(|e| PaymeError::Internal(e.to_string()))
+}
+
+fn items_csv(summary: &MonthSummary) -> Result<Vec<u8>, PaymeError> {
+    write_csv(
+        &[
+            "id",
+            "spent_on",
+            "category",
+            "description",
+            "amount",
+            "savings_destination",
+        ],
+        summary
+            .items
+            .iter()
+            .map(|i| {
+                vec![
+                    i.id.to_string(),
+                    i.spent_on.to_string(),
+                    i.category_label.clone(),
+                    i.description.clone(),
+                    i.amount.to_string(),
+                    i.savings_destination.clone(),
+                ]
+            })
+            .collect(),
+    )
+}
+
+fn income_csv(summary: &MonthSummary) -> Result<Vec<u8>, PaymeError> {
+    write_csv(
+        &["id", "label", "amount"],
+        summary
+            .income_entries
+            .iter()
+            .map(|i| vec![i.id.to_string(), i.label.clone(), i.amount.to_string()])
+            .collect(),
+    )
+}
+
+fn budgets_csv(summary: &MonthSummary) -> Result<Vec<u8>, PaymeError> {
+    write_csv(
+        &["category_id", "category", "allocated", "spent", "remaining"],
+        summary
+            .budgets
+            .iter()
+            .map(|b| {
+                vec![
+                    b.category_id.to_string(),
+                    b.category_label.clone(),
+                    b.allocated_amount.to_string(),
+                    b.spent_amount.to_string(),
+                    (b.allocated_amount - b.spent_amount).to_string(),
+                ]
+            })
+            .collect(),
+    )
+}
+
+fn month_zip(summary: &MonthSummary) -> Result<Vec<u8>, PaymeError> {
+    write_zip(vec![
+        ("items.csv".to_string(), items_csv(summary)?),
+        ("income.csv".to_string(), income_csv(summary)?),
+        ("budgets.csv".to_string(), budgets_csv(summary)?),
+        ("summary.json".to_string(), summary_json(summary)?),
+    ])
+}
+
+/// Packs the named files into a zip archive.
+pub(crate) fn write_zip(files: Vec<(String, Vec<u8>)>) -> Result<Vec<u8>, PaymeError> {
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    for (name, data) in files {
+        zip.start_file(name, options)
+            .map_err(|e| PaymeError::Internal(e.to_string()))?;
+        zip.write_all(&data)
+            .map_err(|e| PaymeError::Internal(e.to_string()))?;
+    }
+    let cursor = zip
+        .finish()
+        .map_err(|e| PaymeError::Internal(e.to_string()))?;
+    Ok(cursor.into_inner())
+}
+
 #[utoipa::path(
     get,
-    path = "/api/export/json",
+    path = "/api/v1/export/json",
     responses(
         (status = 200, description = "A complete JSON export of all user data", body = UserExport),
         (status = 401, description = "Unauthorized"),
@@ -75,37 +264,109 @@ pub async fn export_json(
     State(pool): State<SqlitePool>,
     axum::Extension(claims): axum::Extension<Claims>,
 ) -> Result<Json<UserExport>, PaymeError> {
+    build_export(&pool, claims.sub).await.map(Json)
+}
+
+fn default_bucket_size() -> f64 {
+    10.0
+}
+
+#[derive(Deserialize, IntoParams, Validate)]
+#[into_params(parameter_in = Query)]
+pub struct RedactedExportQuery {
+    /// Item descriptions: `hash` (default), `keep` or `drop`.
+    #[serde(default)]
+    pub descriptions: TextRedaction,
+    /// Category, income and fixed expense labels: `keep` (default), `hash` or `drop`.
+    #[serde(default = "keep_text")]
+    #[param(default = "keep")]
+    pub labels: TextRedaction,
+    /// Amounts: `bucket` (default), `scale` or `keep`.
+    #[serde(default)]
+    pub amounts: AmountRedaction,
+    /// Amounts are rounded to a multiple of this with `amounts=bucket`.
+    #[serde(default = "default_bucket_size")]
+    #[validate(range(exclusive_min = 0.0))]
+    pub bucket_size: f64,
+    /// Factor amounts are multiplied by with `amounts=scale`. Picked at random and left
+    /// out of the export when absent.
+    #[validate(range(exclusive_min = 0.0, max = 100.0))]
+    pub scale_factor: Option<f64>,
+    /// Item dates: `keep` (default) or `month` to move them to the first of their month.
+    #[serde(default)]
+    pub dates: DateRedaction,
+}
+
+fn keep_text() -> TextRedaction {
+    TextRedaction::Keep
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/export/redacted",
+    params(RedactedExportQuery),
+    responses(
+        (status = 200, description = "The user's data in the JSON export format, anonymized", body = UserExport),
+        (status = 400, description = "Unknown redaction or a bucket size or scale factor out of range"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Data Management",
+    summary = "Export anonymized data",
+    description = "Exports the same data as `GET /api/v1/export/json` with private details blurred, to share with a financial coach or attach to a bug report. By default item descriptions are replaced by tokens that stay the same for the same text, and amounts are rounded to the nearest 10. Each field's treatment can be chosen; the result can be imported into another account to reproduce a problem."
+)]
+pub async fn export_redacted(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Query(query): Query<RedactedExportQuery>,
+) -> Result<Json<UserExport>, PaymeError> {
+    query.validate()?;
+    let mut export = build_export(&pool, claims.sub).await?;
+    Redaction::new(
+        query.descriptions,
+        query.labels,
+        query.amounts,
+        query.bucket_size,
+        query.scale_factor,
+        query.dates,
+    )
+    .apply(&mut export);
+    Ok(Json(export))
+}
+
+/// Collects everything a user owns into the portable export format.
+pub async fn build_export(pool: &SqlitePool, user_id: i64) -> Result<UserExport, PaymeError> {
     let savings: f64 = sqlx::query_scalar("SELECT savings FROM users WHERE id = ?")
-        .bind(claims.sub)
-        .fetch_one(&pool)
+        .bind(user_id)
+        .fetch_one(pool)
         .await
         .unwrap_or(0.0);
 
     let retirement_savings: f64 =
         sqlx::query_scalar("SELECT retirement_savings FROM users WHERE id = ?")
-            .bind(claims.sub)
-            .fetch_one(&pool)
+            .bind(user_id)
+            .fetch_one(pool)
             .await
             .unwrap_or(0.0);
 
-    let fixed_expenses: Vec<FixedExpense> =
-        sqlx::query_as("SELECT id, user_id, label, amount FROM fixed_expenses WHERE user_id = ?")
-            .bind(claims.sub)
-            .fetch_all(&pool)
-            .await?;
+    let fixed_expenses: Vec<FixedExpense> = sqlx::query_as(
+        "SELECT id, user_id, label, amount, billing_period, payment_month, payment_day, payment_roll FROM fixed_expenses WHERE user_id = ?",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
 
     let categories: Vec<BudgetCategory> = sqlx::query_as(
-        "SELECT id, user_id, label, default_amount FROM budget_categories WHERE user_id = ?",
+        "SELECT id, user_id, label, default_amount, tracking_only FROM budget_categories WHERE user_id = ?",
     )
-    .bind(claims.sub)
-    .fetch_all(&pool)
+    .bind(user_id)
+    .fetch_all(pool)
     .await?;
 
     let months: Vec<Month> = sqlx::query_as(
         "SELECT id, user_id, year, month, is_closed, closed_at FROM months WHERE user_id = ? ORDER BY year, month",
     )
-    .bind(claims.sub)
-    .fetch_all(&pool)
+    .bind(user_id)
+    .fetch_all(pool)
     .await?;
 
     let mut month_exports = Vec::new();
@@ -115,7 +376,7 @@ pub async fn export_json(
             "SELECT id, month_id, label, amount FROM income_entries WHERE month_id = ?",
         )
         .bind(m.id)
-        .fetch_all(&pool)
+        .fetch_all(pool)
         .await?;
 
         let budgets: Vec<(String, f64)> = sqlx::query_as(
@@ -127,14 +388,14 @@ pub async fn export_json(
             "#,
         )
         .bind(m.id)
-        .fetch_all(&pool)
+        .fetch_all(pool)
         .await?;
 
         let items: Vec<Item> = sqlx::query_as(
-            "SELECT id, month_id, category_id, description, amount, spent_on, savings_destination FROM items WHERE month_id = ?",
+            "SELECT id, month_id, category_id, description, amount, spent_on, savings_destination, reimbursement_status, tax_rate, tax_amount, paid_in_cash, uuid FROM items WHERE month_id = ?",
         )
         .bind(m.id)
-        .fetch_all(&pool)
+        .fetch_all(pool)
         .await?;
 
         let mut item_exports = Vec::new();
@@ -172,7 +433,7 @@ pub async fn export_json(
         });
     }
 
-    Ok(Json(UserExport {
+    Ok(UserExport {
         version: 1,
         savings: Some(savings),
         retirement_savings: Some(retirement_savings),
@@ -181,6 +442,8 @@ pub async fn export_json(
             .map(|e| FixedExpenseExport {
                 label: e.label,
                 amount: e.amount,
+                billing_period: e.billing_period,
+                payment_month: e.payment_month,
             })
             .collect(),
         categories: categories
@@ -188,18 +451,21 @@ pub async fn export_json(
             .map(|c| CategoryExport {
                 label: c.label,
                 default_amount: c.default_amount,
+                tracking_only: c.tracking_only,
             })
             .collect(),
         months: month_exports,
-    }))
+    })
 }
 
 #[utoipa::path(
     post,
-    path = "/api/import/json",
+    path = "/api/v1/import/json",
+    params(DryRunQuery),
     request_body = UserExport,
     responses(
-        (status = 200, description = "Data imported successfully. Note: This overwrites existing user data."),
+        (status = 200, description = "Data imported successfully. Note: This overwrites existing user data. With `dry_run`, a DryRunReport of the rows that would be replaced."),
+        (status = 409, description = "The data has more months, or a month more items, than the server allows"),
         (status = 500, description = "Internal server error during database restoration")
     ),
     tag = "Data Management",
@@ -209,9 +475,15 @@ pub async fn export_json(
 pub async fn import_json(
     State(pool): State<SqlitePool>,
     axum::Extension(claims): axum::Extension<Claims>,
+    Query(query): Query<DryRunQuery>,
     Json(data): Json<UserExport>,
-) -> Result<StatusCode, PaymeError> {
+) -> Result<Response, PaymeError> {
+    quotas::check_replacement(data.months.len(), data.months.iter().map(|m| m.items.len()))?;
+
     let mut tx = pool.begin().await?;
+    if query.dry_run {
+        dry_run::track_changes(&mut tx).await?;
+    }
 
     let months: Vec<(i64,)> = sqlx::query_as("SELECT id FROM months WHERE user_id = ?")
         .bind(claims.sub)
@@ -267,22 +539,28 @@ pub async fn import_json(
     }
 
     for expense in &data.fixed_expenses {
-        sqlx::query("INSERT INTO fixed_expenses (user_id, label, amount) VALUES (?, ?, ?)")
-            .bind(claims.sub)
-            .bind(&expense.label)
-            .bind(expense.amount)
-            .execute(&mut *tx)
-            .await?;
+        let payment_month = billing_schedule(&expense.billing_period, expense.payment_month)?;
+        sqlx::query(
+            "INSERT INTO fixed_expenses (user_id, label, amount, billing_period, payment_month) VALUES (?, ?, ?, ?, ?)",
+        )
+        .bind(claims.sub)
+        .bind(&expense.label)
+        .bind(expense.amount)
+        .bind(&expense.billing_period)
+        .bind(payment_month)
+        .execute(&mut *tx)
+        .await?;
     }
 
     let mut category_map: std::collections::HashMap<String, i64> = std::collections::HashMap::new();
     for cat in &data.categories {
         let id: i64 = sqlx::query_scalar(
-            "INSERT INTO budget_categories (user_id, label, default_amount) VALUES (?, ?, ?) RETURNING id",
+            "INSERT INTO budget_categories (user_id, label, default_amount, tracking_only) VALUES (?, ?, ?, ?) RETURNING id",
         )
         .bind(claims.sub)
         .bind(&cat.label)
         .bind(cat.default_amount)
+        .bind(cat.tracking_only)
         .fetch_one(&mut *tx)
         .await?;
         category_map.insert(cat.label.clone(), id);
@@ -337,6 +615,8 @@ pub async fn import_json(
         }
     }
 
-    tx.commit().await?;
-    Ok(StatusCode::OK)
+    if let Some(report) = dry_run::finish(tx, query.dry_run).await? {
+        return Ok(Json(report).into_response());
+    }
+    Ok(StatusCode::OK.into_response())
 }
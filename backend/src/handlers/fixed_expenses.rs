@@ -1,68 +1,149 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
-    Json,
 };
+use chrono::{Datelike, NaiveDate, Utc};
 use serde::Deserialize;
-use sqlx::SqlitePool;
-use utoipa::ToSchema;
-use validator::Validate;
+use sqlx::{QueryBuilder, SqlitePool};
+use utoipa::{IntoParams, ToSchema};
+use validator::{Validate, ValidationError};
 
+use crate::bulk::{BulkEdit, BulkEditResult};
+use crate::cpi::{self, PriceIndex};
+use crate::dry_run::DryRunQuery;
 use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::filters::{Columns, ListFilter};
+use crate::holidays;
 use crate::middleware::auth::Claims;
-use crate::models::FixedExpense;
+use crate::models::{FixedCostInflation, FixedExpense, FixedExpenseInflation, FixedExpensePrice};
+
+pub(crate) fn default_billing_period() -> String {
+    "monthly".to_string()
+}
 
 #[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct CreateFixedExpense {
     #[validate(length(min = 1, max = 100))]
     pub label: String,
-    #[validate(range(min = 0.0))]
+    /// Amount of each payment.
+    #[validate(range(min = 0.0), custom(function = "crate::money::amount"))]
     pub amount: f64,
+    /// `monthly` (default), `quarterly` or `yearly`.
+    #[serde(default = "default_billing_period")]
+    pub billing_period: String,
+    /// Month (1-12) of a payment. Required for quarterly and yearly expenses.
+    #[validate(range(min = 1, max = 12))]
+    pub payment_month: Option<i32>,
+    /// Day of the month (1-31) the payment is scheduled on. Later than the month's last
+    /// day means its last day.
+    #[validate(range(min = 1, max = 31))]
+    pub payment_day: Option<i32>,
+    /// `forward` (default), `backward` or `none`: where the payment moves when its day
+    /// is a weekend or bank holiday.
+    #[validate(custom(function = "roll"))]
+    pub payment_roll: Option<String>,
 }
 
 #[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateFixedExpense {
     #[validate(length(min = 1, max = 100))]
     pub label: Option<String>,
-    #[validate(range(min = 0.0))]
+    #[validate(range(min = 0.0), custom(function = "crate::money::amount"))]
     pub amount: Option<f64>,
+    pub billing_period: Option<String>,
+    #[validate(range(min = 1, max = 12))]
+    pub payment_month: Option<i32>,
+    /// New payment day, or null to remove it.
+    #[serde(default, deserialize_with = "crate::handlers::items::explicit_null")]
+    #[schema(value_type = Option<i32>)]
+    #[validate(range(min = 1, max = 31))]
+    pub payment_day: Option<Option<i32>>,
+    #[validate(custom(function = "roll"))]
+    pub payment_roll: Option<String>,
+}
+
+pub(crate) fn roll(roll: &str) -> Result<(), ValidationError> {
+    holidays::Roll::parse(roll)
+        .map(|_| ())
+        .ok_or_else(|| ValidationError::new("roll"))
+}
+
+#[derive(Deserialize, IntoParams, Validate)]
+#[into_params(parameter_in = Query)]
+pub struct InflationQuery {
+    /// Calendar year to report on. Defaults to the current one.
+    #[validate(range(min = 1900, max = 2200))]
+    pub year: Option<i32>,
+}
+
+/// Checks a billing period and returns the payment month to store, which only
+/// quarterly and yearly expenses keep.
+pub(crate) fn billing_schedule(
+    billing_period: &str,
+    payment_month: Option<i32>,
+) -> Result<Option<i32>, PaymeError> {
+    match FixedExpense::period_months(billing_period) {
+        None => Err(PaymeError::BadRequest(format!(
+            "Unknown billing period: {billing_period}"
+        ))),
+        Some(1) => Ok(None),
+        Some(_) => payment_month.map(Some).ok_or_else(|| {
+            PaymeError::BadRequest(
+                "payment_month is required for quarterly and yearly expenses".to_string(),
+            )
+        }),
+    }
 }
 
 #[utoipa::path(
     get,
-    path = "/api/fixed-expenses",
+    path = "/api/v1/fixed-expenses",
+    params(ListFilter),
     responses(
         (status = 200, body = [FixedExpense]),
         (status = 500, description = "Internal server error")
     ),
     tag = "Configuration",
     summary = "List fixed expenses",
-    description = "Retrieves all fixed expenses associated with the authenticated user."
+    description = "Retrieves the fixed expenses associated with the authenticated user. Filters by amount and text in the label."
 )]
 pub async fn list_fixed_expenses(
     State(pool): State<SqlitePool>,
     axum::Extension(claims): axum::Extension<Claims>,
+    Query(filter): Query<ListFilter>,
 ) -> Result<Json<Vec<FixedExpense>>, PaymeError> {
-    let expenses: Vec<FixedExpense> =
-        sqlx::query_as("SELECT id, user_id, label, amount FROM fixed_expenses WHERE user_id = ?")
-            .bind(claims.sub)
-            .fetch_all(&pool)
-            .await?;
+    let mut query = QueryBuilder::new(
+        "SELECT id, user_id, label, amount, billing_period, payment_month, payment_day, payment_roll FROM fixed_expenses WHERE user_id = ",
+    );
+    query.push_bind(claims.sub);
+    filter.apply(
+        &mut query,
+        &Columns {
+            category: None,
+            date: None,
+            amount: Some("amount"),
+            text: &["label"],
+        },
+    )?;
+    let expenses: Vec<FixedExpense> = query.build_query_as().fetch_all(&pool).await?;
 
     Ok(Json(expenses))
 }
 
 #[utoipa::path(
     post,
-    path = "/api/fixed-expenses",
+    path = "/api/v1/fixed-expenses",
     request_body = CreateFixedExpense,
     responses(
-        (status = 201, body = FixedExpense),
+        (status = 200, body = FixedExpense),
         (status = 500, description = "Internal server error")
     ),
     tag = "Configuration",
     summary = "Create fixed expense",
-    description = "Adds a new recurring expense (e.g., Rent, Internet) to the user's profile."
+    description = "Adds a new recurring expense (e.g., Rent, Internet) to the user's profile. Quarterly and yearly expenses count a share of each payment every month and need the month a payment falls in."
 )]
 pub async fn create_fixed_expense(
     State(pool): State<SqlitePool>,
@@ -70,12 +151,17 @@ pub async fn create_fixed_expense(
     Json(payload): Json<CreateFixedExpense>,
 ) -> Result<Json<FixedExpense>, PaymeError> {
     payload.validate()?;
+    let payment_month = billing_schedule(&payload.billing_period, payload.payment_month)?;
     let id: i64 = sqlx::query_scalar(
-        "INSERT INTO fixed_expenses (user_id, label, amount) VALUES (?, ?, ?) RETURNING id",
+        "INSERT INTO fixed_expenses (user_id, label, amount, billing_period, payment_month, payment_day, payment_roll) VALUES (?, ?, ?, ?, ?, ?, ?) RETURNING id",
     )
     .bind(claims.sub)
     .bind(&payload.label)
     .bind(payload.amount)
+    .bind(&payload.billing_period)
+    .bind(payment_month)
+    .bind(payload.payment_day)
+    .bind(&payload.payment_roll)
     .fetch_one(&pool)
     .await?;
 
@@ -84,12 +170,16 @@ pub async fn create_fixed_expense(
         user_id: claims.sub,
         label: payload.label,
         amount: payload.amount,
+        billing_period: payload.billing_period,
+        payment_month,
+        payment_day: payload.payment_day,
+        payment_roll: payload.payment_roll,
     }))
 }
 
 #[utoipa::path(
     put,
-    path = "/api/fixed-expenses/{id}",
+    path = "/api/v1/fixed-expenses/{id}",
     params(("id" = i64, Path, description = "Expense ID")),
     request_body = UpdateFixedExpense,
     responses(
@@ -99,7 +189,7 @@ pub async fn create_fixed_expense(
     ),
     tag = "Configuration",
     summary = "Update fixed expense",
-    description = "Updates the label or amount of an existing fixed expense by ID."
+    description = "Updates the label, amount, billing schedule or payment day of an existing fixed expense by ID."
 )]
 pub async fn update_fixed_expense(
     State(pool): State<SqlitePool>,
@@ -109,7 +199,7 @@ pub async fn update_fixed_expense(
 ) -> Result<Json<FixedExpense>, PaymeError> {
     payload.validate()?;
     let existing: FixedExpense = sqlx::query_as(
-        "SELECT id, user_id, label, amount FROM fixed_expenses WHERE id = ? AND user_id = ?",
+        "SELECT id, user_id, label, amount, billing_period, payment_month, payment_day, payment_roll FROM fixed_expenses WHERE id = ? AND user_id = ?",
     )
     .bind(expense_id)
     .bind(claims.sub)
@@ -119,25 +209,90 @@ pub async fn update_fixed_expense(
 
     let label = payload.label.unwrap_or(existing.label);
     let amount = payload.amount.unwrap_or(existing.amount);
+    let billing_period = payload.billing_period.unwrap_or(existing.billing_period);
+    let payment_month = billing_schedule(
+        &billing_period,
+        payload.payment_month.or(existing.payment_month),
+    )?;
 
-    sqlx::query("UPDATE fixed_expenses SET label = ?, amount = ? WHERE id = ?")
-        .bind(&label)
-        .bind(amount)
-        .bind(expense_id)
-        .execute(&pool)
-        .await?;
+    let payment_day = payload.payment_day.unwrap_or(existing.payment_day);
+    let payment_roll = payload.payment_roll.or(existing.payment_roll);
+
+    sqlx::query(
+        "UPDATE fixed_expenses SET label = ?, amount = ?, billing_period = ?, payment_month = ?, payment_day = ?, payment_roll = ? WHERE id = ?",
+    )
+    .bind(&label)
+    .bind(amount)
+    .bind(&billing_period)
+    .bind(payment_month)
+    .bind(payment_day)
+    .bind(&payment_roll)
+    .bind(expense_id)
+    .execute(&pool)
+    .await?;
 
     Ok(Json(FixedExpense {
         id: expense_id,
         user_id: claims.sub,
         label,
         amount,
+        billing_period,
+        payment_month,
+        payment_day,
+        payment_roll,
+    }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/fixed-expenses/bulk",
+    params(DryRunQuery),
+    request_body = BulkEdit,
+    responses(
+        (status = 200, body = BulkEditResult),
+        (status = 400, description = "An unknown expense, or ids without an increase"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Configuration",
+    summary = "Bulk edit fixed expenses",
+    description = "Raises the amounts of several fixed expenses by a percentage and relabels them in one transaction, e.g. the yearly rent and utilities increase. With `dry_run`, answers with the changes without saving them."
+)]
+pub async fn bulk_update_fixed_expenses(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Query(query): Query<DryRunQuery>,
+    Json(payload): Json<BulkEdit>,
+) -> Result<Json<BulkEditResult>, PaymeError> {
+    payload.validate()?;
+    let rows: Vec<(i64, String, f64)> =
+        sqlx::query_as("SELECT id, label, amount FROM fixed_expenses WHERE user_id = ?")
+            .bind(claims.sub)
+            .fetch_all(&pool)
+            .await?;
+    let changes = payload.plan(&rows, "fixed expense")?;
+
+    if !query.dry_run {
+        let mut tx = pool.begin().await?;
+        for change in &changes {
+            sqlx::query("UPDATE fixed_expenses SET label = ?, amount = ? WHERE id = ?")
+                .bind(&change.label)
+                .bind(change.amount)
+                .bind(change.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await?;
+    }
+
+    Ok(Json(BulkEditResult {
+        dry_run: query.dry_run,
+        changes,
     }))
 }
 
 #[utoipa::path(
     delete,
-    path = "/api/fixed-expenses/{id}",
+    path = "/api/v1/fixed-expenses/{id}",
     params(("id" = i64, Path, description = "Expense ID")),
     responses((status = 204, description = "Deleted")),
     tag = "Configuration",
@@ -157,3 +312,170 @@ pub async fn delete_fixed_expense(
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/fixed-expenses/{id}/history",
+    params(("id" = i64, Path, description = "Expense ID")),
+    responses(
+        (status = 200, body = [FixedExpensePrice]),
+        (status = 404, description = "Not Found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Configuration",
+    summary = "Fixed expense price history",
+    description = "Lists every amount the fixed expense has had, oldest first, with the day each took effect and how much the monthly share changed. Changes are recorded whenever the amount or billing period is updated."
+)]
+pub async fn get_fixed_expense_history(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(expense_id): Path<i64>,
+) -> Result<Json<Vec<FixedExpensePrice>>, PaymeError> {
+    let _expense: i64 =
+        sqlx::query_scalar("SELECT id FROM fixed_expenses WHERE id = ? AND user_id = ?")
+            .bind(expense_id)
+            .bind(claims.sub)
+            .fetch_optional(&pool)
+            .await?
+            .ok_or(PaymeError::NotFound)?;
+
+    let mut prices: Vec<FixedExpensePrice> = sqlx::query_as(
+        "SELECT amount, billing_period, effective_from FROM fixed_expense_history WHERE fixed_expense_id = ? ORDER BY effective_from",
+    )
+    .bind(expense_id)
+    .fetch_all(&pool)
+    .await?;
+
+    let mut previous: Option<f64> = None;
+    for price in &mut prices {
+        let monthly = monthly_share(price.amount, &price.billing_period);
+        price.change_percent = previous.and_then(|previous| change_percent(previous, monthly));
+        previous = Some(monthly);
+    }
+
+    Ok(Json(prices))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/fixed-expenses/inflation",
+    params(InflationQuery),
+    responses(
+        (status = 200, body = FixedCostInflation),
+        (status = 400, description = "A year that hasn't started"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Configuration",
+    summary = "Fixed cost inflation",
+    description = "Compares what the user's fixed expenses cost per month at the start of the year with what the same expenses cost at its end, or today for the current year, e.g. \"your fixed costs rose 7.3%\". Quarterly and yearly expenses count with their monthly share. The user's CPI series, if they have one, gives the general inflation to compare against."
+)]
+pub async fn get_fixed_cost_inflation(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Query(query): Query<InflationQuery>,
+) -> Result<Json<FixedCostInflation>, PaymeError> {
+    query.validate()?;
+    let today = Utc::now().date_naive();
+    let year = query.year.unwrap_or(today.year());
+    let from = NaiveDate::from_ymd_opt(year, 1, 1)
+        .ok_or_else(|| PaymeError::BadRequest("Invalid year".to_string()))?;
+    if from > today {
+        return Err(PaymeError::BadRequest(format!("{year} hasn't started")));
+    }
+    let to = NaiveDate::from_ymd_opt(year, 12, 31)
+        .unwrap_or(today)
+        .min(today);
+
+    let expenses: Vec<(i64, String)> =
+        sqlx::query_as("SELECT id, label FROM fixed_expenses WHERE user_id = ? ORDER BY id")
+            .bind(claims.sub)
+            .fetch_all(&pool)
+            .await?;
+    let history: Vec<(i64, f64, String, NaiveDate)> = sqlx::query_as(
+        r#"
+        SELECT h.fixed_expense_id, h.amount, h.billing_period, h.effective_from
+        FROM fixed_expense_history h
+        JOIN fixed_expenses fe ON h.fixed_expense_id = fe.id
+        WHERE fe.user_id = ?
+        ORDER BY h.effective_from
+        "#,
+    )
+    .bind(claims.sub)
+    .fetch_all(&pool)
+    .await?;
+
+    // Monthly share of the price in effect on `date`
+    let price_on = |expense_id: i64, date: NaiveDate| {
+        history
+            .iter()
+            .rfind(|(id, _, _, effective_from)| *id == expense_id && *effective_from <= date)
+            .map(|(_, amount, billing_period, _)| monthly_share(*amount, billing_period))
+    };
+
+    let mut changes: Vec<FixedExpenseInflation> = expenses
+        .into_iter()
+        .filter_map(|(id, label)| {
+            let end = price_on(id, to)?;
+            let start = price_on(id, from);
+            Some(FixedExpenseInflation {
+                fixed_expense_id: id,
+                label,
+                start_monthly_amount: start.map(round_cents),
+                end_monthly_amount: round_cents(end),
+                change_percent: start.and_then(|start| change_percent(start, end)),
+            })
+        })
+        .collect();
+    changes.sort_by(|a, b| {
+        b.start_monthly_amount
+            .is_some()
+            .cmp(&a.start_monthly_amount.is_some())
+            .then(
+                b.change_percent
+                    .unwrap_or(0.0)
+                    .total_cmp(&a.change_percent.unwrap_or(0.0)),
+            )
+    });
+
+    let compared = changes
+        .iter()
+        .filter_map(|c| Some((c.start_monthly_amount?, c.end_monthly_amount)));
+    let start_monthly_total = round_cents(compared.clone().map(|(start, _)| start).sum());
+    let end_monthly_total = round_cents(compared.map(|(_, end)| end).sum());
+
+    let readings = cpi::load_readings(&pool, claims.sub).await?;
+    let covers_start = readings.iter().any(|r| (r.year, r.month) <= (year, 1));
+    let cpi_change_percent = PriceIndex::new(readings)
+        .filter(|_| covers_start)
+        .and_then(|index| {
+            change_percent(
+                1.0 / index.factor(year, 1),
+                1.0 / index.factor(year, to.month() as i32),
+            )
+        });
+
+    Ok(Json(FixedCostInflation {
+        year,
+        from,
+        to,
+        start_monthly_total,
+        end_monthly_total,
+        change_percent: change_percent(start_monthly_total, end_monthly_total),
+        cpi_change_percent,
+        expenses: changes,
+    }))
+}
+
+fn monthly_share(amount: f64, billing_period: &str) -> f64 {
+    amount / FixedExpense::period_months(billing_period).unwrap_or(1) as f64
+}
+
+/// Change from `old` to `new` in percent, to one decimal. `None` when there was
+/// nothing before.
+fn change_percent(old: f64, new: f64) -> Option<f64> {
+    (old > 0.0).then(|| ((new - old) / old * 1000.0).round() / 10.0)
+}
+
+fn round_cents(amount: f64) -> f64 {
+    (amount * 100.0).round() / 100.0
+}
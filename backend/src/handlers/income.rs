@@ -1,62 +1,82 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
-    Json,
 };
 use serde::Deserialize;
-use sqlx::SqlitePool;
+use sqlx::{QueryBuilder, SqlitePool};
 use utoipa::ToSchema;
 use validator::Validate;
 
+use crate::activity;
 use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::filters::{Columns, ListFilter};
+use crate::handlers::budget::income_changed;
+use crate::handlers::settings::money_format;
+use crate::handlers::sync;
 use crate::middleware::auth::Claims;
 use crate::models::IncomeEntry;
 
 #[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct CreateIncome {
     #[validate(length(min = 1, max = 100))]
     pub label: String,
-    #[validate(range(min = 0.0))]
+    #[validate(range(min = 0.0), custom(function = "crate::money::amount"))]
     pub amount: f64,
+    /// UUID chosen by the client. Creating an entry with a UUID already used returns
+    /// that entry instead of adding another.
+    pub uuid: Option<String>,
 }
 
 #[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateIncome {
     #[validate(length(min = 1, max = 100))]
     pub label: Option<String>,
-    #[validate(range(min = 0.0))]
+    #[validate(range(min = 0.0), custom(function = "crate::money::amount"))]
     pub amount: Option<f64>,
 }
 
 #[utoipa::path(
-    get, path = "/api/months/{id}/income",
-    params(("id" = i64, Path)),
+    get, path = "/api/v1/months/{id}/income",
+    params(("id" = i64, Path), ListFilter),
     responses(
         (status = 200, body = [IncomeEntry]),
         (status = 500, description = "Internal server error")
     ),
     tag = "Income",
     summary = "List monthly income",
-    description = "Retrieves all sources of income (paychecks, gifts, etc.) recorded for a specific month."
+    description = "Retrieves all sources of income (paychecks, gifts, etc.) recorded for a specific month. Filters by amount and text in the label."
 )]
 pub async fn list_income(
     State(pool): State<SqlitePool>,
     axum::Extension(claims): axum::Extension<Claims>,
     Path(month_id): Path<i64>,
+    Query(filter): Query<ListFilter>,
 ) -> Result<Json<Vec<IncomeEntry>>, PaymeError> {
     verify_month_access(&pool, claims.sub, month_id).await?;
 
-    let entries: Vec<IncomeEntry> =
-        sqlx::query_as("SELECT id, month_id, label, amount FROM income_entries WHERE month_id = ?")
-            .bind(month_id)
-            .fetch_all(&pool)
-            .await?;
+    let mut query = QueryBuilder::new(
+        "SELECT id, month_id, label, amount, uuid FROM income_entries WHERE month_id = ",
+    );
+    query.push_bind(month_id);
+    filter.apply(
+        &mut query,
+        &Columns {
+            category: None,
+            date: None,
+            amount: Some("amount"),
+            text: &["label"],
+        },
+    )?;
+    let entries: Vec<IncomeEntry> = query.build_query_as().fetch_all(&pool).await?;
 
     Ok(Json(entries))
 }
 
 #[utoipa::path(
-    post, path = "/api/months/{id}/income",
+    post, path = "/api/v1/months/{id}/income",
     params(("id" = i64, Path)),
     request_body = CreateIncome,
     responses(
@@ -74,28 +94,56 @@ pub async fn create_income(
     Json(payload): Json<CreateIncome>,
 ) -> Result<Json<IncomeEntry>, PaymeError> {
     payload.validate()?;
+    let uuid = sync::client_uuid(payload.uuid.as_deref())?;
+    if let Some(uuid) = &uuid {
+        if let Some(entry) = find_by_uuid(&pool, claims.sub, uuid).await? {
+            return Ok(Json(entry));
+        }
+    }
     verify_month_not_closed(&pool, claims.sub, month_id).await?;
 
+    let mut tx = pool.begin().await?;
     let id: i64 = sqlx::query_scalar(
-        "INSERT INTO income_entries (month_id, label, amount) VALUES (?, ?, ?) RETURNING id",
+        "INSERT INTO income_entries (month_id, label, amount, uuid) VALUES (?, ?, ?, ?) RETURNING id",
     )
     .bind(month_id)
     .bind(&payload.label)
     .bind(payload.amount)
-    .fetch_one(&pool)
+    .bind(&uuid)
+    .fetch_one(&mut *tx)
+    .await?;
+    income_changed(&mut tx, claims.sub, month_id).await?;
+
+    let money = money_format(&mut *tx, claims.sub).await?;
+    activity::record(
+        &mut *tx,
+        &claims,
+        Some(month_id),
+        "income",
+        id,
+        "create",
+        format!(
+            "{} added income {} {}",
+            claims.username,
+            payload.label,
+            money.format(payload.amount)
+        ),
+    )
     .await?;
+    tx.commit().await?;
 
     Ok(Json(IncomeEntry {
         id,
         month_id,
         label: payload.label,
         amount: payload.amount,
+        uuid,
     }))
 }
 
 #[utoipa::path(
     put,
-    path = "/api/months/{month_id}/income/{id}",
+    path = "/api/v1/months/{month_id}/income/{id}",
     params(
         ("month_id" = i64, Path, description = "Month ID"),
         ("id" = i64, Path, description = "Income Entry ID")
@@ -119,7 +167,7 @@ pub async fn update_income(
     verify_month_not_closed(&pool, claims.sub, month_id).await?;
 
     let existing: IncomeEntry = sqlx::query_as(
-        "SELECT id, month_id, label, amount FROM income_entries WHERE id = ? AND month_id = ?",
+        "SELECT id, month_id, label, amount, uuid FROM income_entries WHERE id = ? AND month_id = ?",
     )
     .bind(income_id)
     .bind(month_id)
@@ -130,24 +178,45 @@ pub async fn update_income(
     let label = payload.label.unwrap_or(existing.label);
     let amount = payload.amount.unwrap_or(existing.amount);
 
+    let mut tx = pool.begin().await?;
     sqlx::query("UPDATE income_entries SET label = ?, amount = ? WHERE id = ?")
         .bind(&label)
         .bind(amount)
         .bind(income_id)
-        .execute(&pool)
+        .execute(&mut *tx)
         .await?;
+    income_changed(&mut tx, claims.sub, month_id).await?;
+
+    let money = money_format(&mut *tx, claims.sub).await?;
+    activity::record(
+        &mut *tx,
+        &claims,
+        Some(month_id),
+        "income",
+        income_id,
+        "update",
+        format!(
+            "{} updated income {} {}",
+            claims.username,
+            label,
+            money.format(amount)
+        ),
+    )
+    .await?;
+    tx.commit().await?;
 
     Ok(Json(IncomeEntry {
         id: income_id,
         month_id,
         label,
         amount,
+        uuid: existing.uuid,
     }))
 }
 
 #[utoipa::path(
     delete,
-    path = "/api/months/{month_id}/income/{id}",
+    path = "/api/v1/months/{month_id}/income/{id}",
     params(
         ("month_id" = i64, Path, description = "Month ID"),
         ("id" = i64, Path, description = "Income Entry ID")
@@ -167,15 +236,77 @@ pub async fn delete_income(
 ) -> Result<StatusCode, PaymeError> {
     verify_month_not_closed(&pool, claims.sub, month_id).await?;
 
+    let existing: Option<IncomeEntry> = sqlx::query_as(
+        "SELECT id, month_id, label, amount FROM income_entries WHERE id = ? AND month_id = ?",
+    )
+    .bind(income_id)
+    .bind(month_id)
+    .fetch_optional(&pool)
+    .await?;
+
+    let mut tx = pool.begin().await?;
     sqlx::query("DELETE FROM income_entries WHERE id = ? AND month_id = ?")
         .bind(income_id)
         .bind(month_id)
-        .execute(&pool)
+        .execute(&mut *tx)
         .await?;
+    income_changed(&mut tx, claims.sub, month_id).await?;
+
+    if let Some(entry) = existing {
+        let money = money_format(&mut *tx, claims.sub).await?;
+        activity::record(
+            &mut *tx,
+            &claims,
+            Some(month_id),
+            "income",
+            income_id,
+            "delete",
+            format!(
+                "{} removed income {} {}",
+                claims.username,
+                entry.label,
+                money.format(entry.amount)
+            ),
+        )
+        .await?;
+    }
+    tx.commit().await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// The user's income entry created with `uuid`. Fails if another user's entry has it.
+async fn find_by_uuid(
+    pool: &SqlitePool,
+    user_id: i64,
+    uuid: &str,
+) -> Result<Option<IncomeEntry>, PaymeError> {
+    let entry: Option<(i64, i64, String, f64, i64)> = sqlx::query_as(
+        r#"
+        SELECT e.id, e.month_id, e.label, e.amount, m.user_id
+        FROM income_entries e JOIN months m ON e.month_id = m.id
+        WHERE e.uuid = ?
+        "#,
+    )
+    .bind(uuid)
+    .fetch_optional(pool)
+    .await?;
+
+    match entry {
+        None => Ok(None),
+        Some((.., owner)) if owner != user_id => Err(PaymeError::Conflict(format!(
+            "UUID {uuid} is already in use"
+        ))),
+        Some((id, month_id, label, amount, _)) => Ok(Some(IncomeEntry {
+            id,
+            month_id,
+            label,
+            amount,
+            uuid: Some(uuid.to_string()),
+        })),
+    }
+}
+
 async fn verify_month_access(
     pool: &SqlitePool,
     user_id: i64,
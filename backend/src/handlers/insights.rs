@@ -0,0 +1,132 @@
+use axum::extract::{Path, Query, State};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use utoipa::IntoParams;
+
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::feed::refresh_insights;
+use crate::middleware::auth::Claims;
+use crate::middleware::maintenance::MaintenanceMode;
+use crate::models::{Insight, InsightsResponse};
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct InsightsQuery {
+    /// Recompute insights now instead of waiting for the nightly refresh.
+    #[serde(default)]
+    pub refresh: bool,
+    /// Include insights that have been dismissed.
+    #[serde(default)]
+    pub include_dismissed: bool,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/insights",
+    params(InsightsQuery),
+    responses(
+        (status = 200, body = InsightsResponse),
+        (status = 500, description = "Internal server error"),
+        (status = 503, description = "Insights were never computed and the API is read-only")
+    ),
+    tag = "Insights",
+    summary = "Get insights feed",
+    description = "Returns the stored rule-based insights (category spending spikes, high fixed-cost ratio, no-spend streaks, overdue invoices) and a budget health score. Insights are recomputed nightly, on first access, or on demand with `refresh=true`."
+)]
+pub async fn list_insights(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    axum::Extension(mode): axum::Extension<MaintenanceMode>,
+    Query(query): Query<InsightsQuery>,
+) -> Result<Json<InsightsResponse>, PaymeError> {
+    let stored_score: Option<i64> =
+        sqlx::query_scalar("SELECT health_score FROM insight_scores WHERE user_id = ?")
+            .bind(claims.sub)
+            .fetch_optional(&pool)
+            .await?;
+
+    // While read-only, the stored insights are served as they are
+    let health_score = match stored_score {
+        Some(score) if !query.refresh || mode.is_read_only() => score,
+        None if mode.is_read_only() => return Err(PaymeError::ReadOnly),
+        _ => refresh_insights(&pool, claims.sub).await?,
+    };
+
+    let insights: Vec<Insight> = sqlx::query_as(
+        r#"
+        SELECT id, kind, message, is_read, is_dismissed, created_at
+        FROM insights
+        WHERE user_id = ? AND (is_dismissed = 0 OR ?)
+        ORDER BY created_at DESC, id DESC
+        "#,
+    )
+    .bind(claims.sub)
+    .bind(query.include_dismissed)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(InsightsResponse {
+        health_score,
+        insights,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/insights/{id}/read",
+    params(("id" = i64, Path, description = "Insight ID")),
+    responses(
+        (status = 200, body = Insight),
+        (status = 404, description = "Insight not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Insights",
+    summary = "Mark insight as read"
+)]
+pub async fn mark_insight_read(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(insight_id): Path<i64>,
+) -> Result<Json<Insight>, PaymeError> {
+    update_flag(&pool, claims.sub, insight_id, "is_read").await
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/insights/{id}/dismiss",
+    params(("id" = i64, Path, description = "Insight ID")),
+    responses(
+        (status = 200, body = Insight),
+        (status = 404, description = "Insight not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Insights",
+    summary = "Dismiss insight",
+    description = "Hides the insight from the feed. Dismissed insights are not brought back by later refreshes."
+)]
+pub async fn dismiss_insight(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(insight_id): Path<i64>,
+) -> Result<Json<Insight>, PaymeError> {
+    update_flag(&pool, claims.sub, insight_id, "is_dismissed").await
+}
+
+async fn update_flag(
+    pool: &SqlitePool,
+    user_id: i64,
+    insight_id: i64,
+    column: &'static str,
+) -> Result<Json<Insight>, PaymeError> {
+    let insight: Insight = sqlx::query_as(&format!(
+        "UPDATE insights SET {column} = 1 WHERE id = ? AND user_id = ? RETURNING id, kind, message, is_read, is_dismissed, created_at"
+    ))
+    .bind(insight_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(PaymeError::NotFound)?;
+
+    Ok(Json(insight))
+}
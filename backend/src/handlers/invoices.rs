@@ -0,0 +1,357 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+};
+use chrono::{Datelike, NaiveDate, Utc};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::activity;
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::handlers::budget::income_changed;
+use crate::handlers::months::month_for;
+use crate::handlers::settings::money_format;
+use crate::middleware::auth::Claims;
+use crate::models::Invoice;
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateInvoice {
+    #[validate(length(min = 1, max = 100))]
+    pub client: String,
+    #[validate(range(exclusive_min = 0.0), custom(function = "crate::money::amount"))]
+    pub amount: f64,
+    /// Defaults to today.
+    pub issued_on: Option<NaiveDate>,
+    pub due_on: NaiveDate,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateInvoice {
+    #[validate(length(min = 1, max = 100))]
+    pub client: Option<String>,
+    #[validate(range(exclusive_min = 0.0), custom(function = "crate::money::amount"))]
+    pub amount: Option<f64>,
+    pub issued_on: Option<NaiveDate>,
+    pub due_on: Option<NaiveDate>,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateInvoiceStatus {
+    /// `sent`, `paid` or `cancelled`.
+    pub status: String,
+    /// When the payment came in, today by default. Only used for `paid`.
+    pub paid_on: Option<NaiveDate>,
+}
+
+const SELECT_INVOICE: &str = r#"
+    SELECT id, client, amount, issued_on, due_on, status, paid_on, income_id,
+           status = 'sent' AND due_on < date('now') AS overdue
+    FROM invoices
+"#;
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/invoices",
+    responses(
+        (status = 200, body = [Invoice]),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Invoices",
+    summary = "List invoices",
+    description = "Lists invoices by due date, most recent first."
+)]
+pub async fn list_invoices(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+) -> Result<Json<Vec<Invoice>>, PaymeError> {
+    let invoices: Vec<Invoice> = sqlx::query_as(&format!(
+        "{SELECT_INVOICE} WHERE user_id = ? ORDER BY due_on DESC, id DESC"
+    ))
+    .bind(claims.sub)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(invoices))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/invoices",
+    request_body = CreateInvoice,
+    responses(
+        (status = 200, body = Invoice),
+        (status = 400, description = "Invalid invoice"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Invoices",
+    summary = "Record an invoice",
+    description = "Records an invoice sent to a client. It counts as expected income until it's marked as paid."
+)]
+pub async fn create_invoice(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Json(payload): Json<CreateInvoice>,
+) -> Result<Json<Invoice>, PaymeError> {
+    payload.validate()?;
+    let issued_on = payload.issued_on.unwrap_or_else(|| Utc::now().date_naive());
+    if payload.due_on < issued_on {
+        return Err(PaymeError::BadRequest(
+            "Due date can't be before the issue date".to_string(),
+        ));
+    }
+
+    let id: i64 = sqlx::query_scalar(
+        "INSERT INTO invoices (user_id, client, amount, issued_on, due_on, created_at) VALUES (?, ?, ?, ?, ?, ?) RETURNING id",
+    )
+    .bind(claims.sub)
+    .bind(payload.client.trim())
+    .bind(payload.amount)
+    .bind(issued_on)
+    .bind(payload.due_on)
+    .bind(Utc::now())
+    .fetch_one(&pool)
+    .await?;
+
+    Ok(Json(fetch_invoice(&pool, claims.sub, id).await?))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/invoices/{id}",
+    params(("id" = i64, Path, description = "Invoice ID")),
+    request_body = UpdateInvoice,
+    responses(
+        (status = 200, body = Invoice),
+        (status = 400, description = "Invalid dates, or the invoice is already paid"),
+        (status = 404, description = "Invoice not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Invoices",
+    summary = "Update an invoice",
+    description = "Updates an unpaid invoice's client, amount or dates."
+)]
+pub async fn update_invoice(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(invoice_id): Path<i64>,
+    Json(payload): Json<UpdateInvoice>,
+) -> Result<Json<Invoice>, PaymeError> {
+    payload.validate()?;
+    let existing = fetch_invoice(&pool, claims.sub, invoice_id).await?;
+    if existing.status == "paid" {
+        return Err(PaymeError::BadRequest(
+            "Mark the invoice as unpaid before changing it".to_string(),
+        ));
+    }
+
+    let issued_on = payload.issued_on.unwrap_or(existing.issued_on);
+    let due_on = payload.due_on.unwrap_or(existing.due_on);
+    if due_on < issued_on {
+        return Err(PaymeError::BadRequest(
+            "Due date can't be before the issue date".to_string(),
+        ));
+    }
+
+    sqlx::query(
+        "UPDATE invoices SET client = ?, amount = ?, issued_on = ?, due_on = ? WHERE id = ?",
+    )
+    .bind(
+        payload
+            .client
+            .map(|client| client.trim().to_string())
+            .unwrap_or(existing.client),
+    )
+    .bind(payload.amount.unwrap_or(existing.amount))
+    .bind(issued_on)
+    .bind(due_on)
+    .bind(invoice_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(Json(fetch_invoice(&pool, claims.sub, invoice_id).await?))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/invoices/{id}/status",
+    params(("id" = i64, Path, description = "Invoice ID")),
+    request_body = UpdateInvoiceStatus,
+    responses(
+        (status = 200, body = Invoice),
+        (status = 400, description = "Unknown status, or the month of the payment is closed"),
+        (status = 404, description = "Invoice not found"),
+        (status = 409, description = "The invoice's status changed in the meantime"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Invoices",
+    summary = "Mark an invoice paid or cancelled",
+    description = "Marking an invoice as `paid` adds its amount as an income entry in the month it was paid, creating the month if needed. Moving it back to `sent` or `cancelled` removes that income entry again."
+)]
+pub async fn update_invoice_status(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(invoice_id): Path<i64>,
+    Json(payload): Json<UpdateInvoiceStatus>,
+) -> Result<Json<Invoice>, PaymeError> {
+    if !matches!(payload.status.as_str(), "sent" | "paid" | "cancelled") {
+        return Err(PaymeError::BadRequest(format!(
+            "Unknown invoice status: {}",
+            payload.status
+        )));
+    }
+    let invoice = fetch_invoice(&pool, claims.sub, invoice_id).await?;
+    if invoice.status == payload.status {
+        return Ok(Json(invoice));
+    }
+
+    // The month is looked up before the transaction, which it would otherwise wait on
+    let paid_month = if payload.status == "paid" {
+        let paid_on = payload.paid_on.unwrap_or_else(|| Utc::now().date_naive());
+        let month = month_for(&pool, claims.sub, paid_on.year(), paid_on.month() as i32).await?;
+        if month.is_closed {
+            return Err(PaymeError::BadRequest("Month is closed".to_string()));
+        }
+        Some((paid_on, month.id))
+    } else {
+        None
+    };
+
+    let money = money_format(&pool, claims.sub).await?;
+
+    let mut tx = pool.begin().await?;
+    // Claiming the transition first keeps two requests from both booking the payment
+    let claimed =
+        sqlx::query("UPDATE invoices SET status = ? WHERE id = ? AND user_id = ? AND status = ?")
+            .bind(&payload.status)
+            .bind(invoice_id)
+            .bind(claims.sub)
+            .bind(&invoice.status)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+    if claimed != 1 {
+        return Err(PaymeError::Conflict(
+            "The invoice's status changed in the meantime".to_string(),
+        ));
+    }
+    let previous_income: Option<i64> =
+        sqlx::query_scalar("SELECT income_id FROM invoices WHERE id = ?")
+            .bind(invoice_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+    if let Some(income_id) = previous_income {
+        let (month_id, is_closed): (i64, bool) = sqlx::query_as(
+            "SELECT m.id, m.is_closed FROM income_entries ie JOIN months m ON ie.month_id = m.id WHERE ie.id = ?",
+        )
+        .bind(income_id)
+        .fetch_one(&mut *tx)
+        .await?;
+        if is_closed {
+            return Err(PaymeError::BadRequest(
+                "The month this invoice was paid in is closed".to_string(),
+            ));
+        }
+        sqlx::query("DELETE FROM income_entries WHERE id = ?")
+            .bind(income_id)
+            .execute(&mut *tx)
+            .await?;
+        income_changed(&mut tx, claims.sub, month_id).await?;
+        activity::record(
+            &mut *tx,
+            &claims,
+            Some(month_id),
+            "income",
+            income_id,
+            "delete",
+            format!(
+                "{} marked the invoice to {} as {}",
+                claims.username, invoice.client, payload.status
+            ),
+        )
+        .await?;
+    }
+
+    let (paid_on, income_id) = if let Some((paid_on, month_id)) = paid_month {
+        let label = format!("Invoice: {}", invoice.client);
+        let income_id: i64 = sqlx::query_scalar(
+            "INSERT INTO income_entries (month_id, label, amount) VALUES (?, ?, ?) RETURNING id",
+        )
+        .bind(month_id)
+        .bind(&label)
+        .bind(invoice.amount)
+        .fetch_one(&mut *tx)
+        .await?;
+        income_changed(&mut tx, claims.sub, month_id).await?;
+        activity::record(
+            &mut *tx,
+            &claims,
+            Some(month_id),
+            "income",
+            income_id,
+            "create",
+            format!(
+                "{} recorded {} {}",
+                claims.username,
+                label,
+                money.format(invoice.amount)
+            ),
+        )
+        .await?;
+        (Some(paid_on), Some(income_id))
+    } else {
+        (None, None)
+    };
+
+    sqlx::query("UPDATE invoices SET paid_on = ?, income_id = ? WHERE id = ?")
+        .bind(paid_on)
+        .bind(income_id)
+        .bind(invoice_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(Json(fetch_invoice(&pool, claims.sub, invoice_id).await?))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/invoices/{id}",
+    params(("id" = i64, Path, description = "Invoice ID")),
+    responses((status = 204, description = "Deleted")),
+    tag = "Invoices",
+    summary = "Delete an invoice",
+    description = "Deletes an invoice. Income already recorded for it stays."
+)]
+pub async fn delete_invoice(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(invoice_id): Path<i64>,
+) -> Result<StatusCode, PaymeError> {
+    sqlx::query("DELETE FROM invoices WHERE id = ? AND user_id = ?")
+        .bind(invoice_id)
+        .bind(claims.sub)
+        .execute(&pool)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn fetch_invoice(
+    pool: &SqlitePool,
+    user_id: i64,
+    invoice_id: i64,
+) -> Result<Invoice, PaymeError> {
+    sqlx::query_as(&format!("{SELECT_INVOICE} WHERE id = ? AND user_id = ?"))
+        .bind(invoice_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(PaymeError::NotFound)
+}
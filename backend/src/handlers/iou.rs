@@ -0,0 +1,352 @@
+use std::collections::BTreeMap;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::activity;
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::handlers::budget::income_changed;
+use crate::handlers::months::current_month;
+use crate::handlers::settings::money_format;
+use crate::middleware::auth::Claims;
+use crate::models::{IncomeEntry, IouEntry, IouReport, ItemSplit, PersonIou};
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateSplit {
+    /// Name of the person who owes their share, they don't need an account.
+    #[validate(length(min = 1, max = 100))]
+    pub person: String,
+    #[validate(range(exclusive_min = 0.0), custom(function = "crate::money::amount"))]
+    pub amount: f64,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct RecordRepayment {
+    /// Amount paid back, the whole outstanding share by default.
+    #[validate(range(exclusive_min = 0.0), custom(function = "crate::money::amount"))]
+    pub amount: Option<f64>,
+}
+
+#[derive(sqlx::FromRow)]
+struct SplitRow {
+    person: String,
+    #[sqlx(flatten)]
+    entry: IouEntry,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/months/{month_id}/items/{id}/splits",
+    params(
+        ("month_id" = i64, Path, description = "Month ID"),
+        ("id" = i64, Path, description = "Item ID")
+    ),
+    responses(
+        (status = 200, body = [ItemSplit]),
+        (status = 404, description = "Item not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "IOU",
+    summary = "List an item's splits",
+    description = "Lists who shares the cost of an item and how much each of them owes."
+)]
+pub async fn list_splits(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path((month_id, item_id)): Path<(i64, i64)>,
+) -> Result<Json<Vec<ItemSplit>>, PaymeError> {
+    fetch_item(&pool, claims.sub, month_id, item_id).await?;
+
+    let splits: Vec<ItemSplit> = sqlx::query_as(
+        "SELECT id, item_id, person, amount, created_at FROM item_splits WHERE item_id = ? ORDER BY id",
+    )
+    .bind(item_id)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(splits))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/months/{month_id}/items/{id}/splits",
+    params(
+        ("month_id" = i64, Path, description = "Month ID"),
+        ("id" = i64, Path, description = "Item ID")
+    ),
+    request_body = CreateSplit,
+    responses(
+        (status = 200, body = ItemSplit),
+        (status = 400, description = "Splits would add up to more than the item"),
+        (status = 404, description = "Item not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "IOU",
+    summary = "Split an item with someone",
+    description = "Records that a named person owes part of an item. The item keeps its full amount in the budget; repayments come back as income."
+)]
+pub async fn create_split(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path((month_id, item_id)): Path<(i64, i64)>,
+    Json(payload): Json<CreateSplit>,
+) -> Result<Json<ItemSplit>, PaymeError> {
+    payload.validate()?;
+    let person = payload.person.trim().to_string();
+    if person.is_empty() {
+        return Err(PaymeError::BadRequest("Person is required".to_string()));
+    }
+
+    let item_amount = fetch_item(&pool, claims.sub, month_id, item_id).await?;
+    let already_split: f64 =
+        sqlx::query_scalar("SELECT COALESCE(SUM(amount), 0.0) FROM item_splits WHERE item_id = ?")
+            .bind(item_id)
+            .fetch_one(&pool)
+            .await?;
+    if cents(already_split + payload.amount) > cents(item_amount) {
+        let money = money_format(&pool, claims.sub).await?;
+        return Err(PaymeError::BadRequest(format!(
+            "Splits can't exceed the item amount, {} is left to split",
+            money.format(item_amount - already_split)
+        )));
+    }
+
+    let split: ItemSplit = sqlx::query_as(
+        "INSERT INTO item_splits (item_id, person, amount, created_at) VALUES (?, ?, ?, ?) RETURNING id, item_id, person, amount, created_at",
+    )
+    .bind(item_id)
+    .bind(&person)
+    .bind(payload.amount)
+    .bind(Utc::now())
+    .fetch_one(&pool)
+    .await?;
+
+    Ok(Json(split))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/months/{month_id}/items/{item_id}/splits/{id}",
+    params(
+        ("month_id" = i64, Path, description = "Month ID"),
+        ("item_id" = i64, Path, description = "Item ID"),
+        ("id" = i64, Path, description = "Split ID")
+    ),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 404, description = "Item not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "IOU",
+    summary = "Remove a split",
+    description = "Forgets a share. Repayments already recorded stay as plain income."
+)]
+pub async fn delete_split(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path((month_id, item_id, split_id)): Path<(i64, i64, i64)>,
+) -> Result<StatusCode, PaymeError> {
+    fetch_item(&pool, claims.sub, month_id, item_id).await?;
+
+    sqlx::query("DELETE FROM item_splits WHERE id = ? AND item_id = ?")
+        .bind(split_id)
+        .bind(item_id)
+        .execute(&pool)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/iou",
+    responses(
+        (status = 200, body = IouReport),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "IOU",
+    summary = "Outstanding IOUs",
+    description = "Lists what each person still owes across all split items, net of recorded repayments."
+)]
+pub async fn get_iou(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+) -> Result<Json<IouReport>, PaymeError> {
+    let rows: Vec<SplitRow> = sqlx::query_as(
+        r#"
+        SELECT person, split_id, item_id, month_id, description, spent_on, amount, repaid,
+               amount - repaid AS outstanding
+        FROM (
+            SELECT s.person, s.id AS split_id, s.item_id, i.month_id, i.description, i.spent_on,
+                   s.amount,
+                   COALESCE((SELECT SUM(ie.amount) FROM income_entries ie WHERE ie.split_id = s.id), 0.0) AS repaid
+            FROM item_splits s
+            JOIN items i ON s.item_id = i.id
+            JOIN months m ON i.month_id = m.id
+            WHERE m.user_id = ?
+        )
+        ORDER BY spent_on, split_id
+        "#,
+    )
+    .bind(claims.sub)
+    .fetch_all(&pool)
+    .await?;
+
+    // People are matched ignoring case, shown the way they were first entered
+    let mut by_person: BTreeMap<String, PersonIou> = BTreeMap::new();
+    for SplitRow { person, entry } in rows {
+        if cents(entry.outstanding) <= 0 {
+            continue;
+        }
+        let owed = by_person
+            .entry(person.to_lowercase())
+            .or_insert_with(|| PersonIou {
+                person,
+                outstanding: 0.0,
+                entries: Vec::new(),
+            });
+        owed.outstanding += entry.outstanding;
+        owed.entries.push(entry);
+    }
+
+    let mut people: Vec<PersonIou> = by_person.into_values().collect();
+    people.sort_by(|a, b| b.outstanding.total_cmp(&a.outstanding));
+    let total_outstanding = people.iter().map(|p| p.outstanding).sum();
+
+    Ok(Json(IouReport {
+        people,
+        total_outstanding,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/iou/{id}/repayments",
+    params(("id" = i64, Path, description = "Split ID")),
+    request_body = RecordRepayment,
+    responses(
+        (status = 200, description = "Income entry recorded for the repayment", body = IncomeEntry),
+        (status = 400, description = "More than is owed, or the current month is closed"),
+        (status = 404, description = "Split not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "IOU",
+    summary = "Record a repayment",
+    description = "Adds an income entry to the current month, creating it if needed, linked to the split it pays back."
+)]
+pub async fn record_repayment(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(split_id): Path<i64>,
+    Json(payload): Json<RecordRepayment>,
+) -> Result<Json<IncomeEntry>, PaymeError> {
+    payload.validate()?;
+    let (person, description, amount, repaid): (String, String, f64, f64) = sqlx::query_as(
+        r#"
+        SELECT s.person, i.description, s.amount,
+               COALESCE((SELECT SUM(ie.amount) FROM income_entries ie WHERE ie.split_id = s.id), 0.0)
+        FROM item_splits s
+        JOIN items i ON s.item_id = i.id
+        JOIN months m ON i.month_id = m.id
+        WHERE s.id = ? AND m.user_id = ?
+        "#,
+    )
+    .bind(split_id)
+    .bind(claims.sub)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(PaymeError::NotFound)?;
+
+    let outstanding = amount - repaid;
+    let repayment = payload.amount.unwrap_or(outstanding);
+    if cents(outstanding) <= 0 {
+        return Err(PaymeError::BadRequest(format!(
+            "{person} has already repaid {description}"
+        )));
+    }
+    let money = money_format(&pool, claims.sub).await?;
+    if cents(repayment) > cents(outstanding) {
+        return Err(PaymeError::BadRequest(format!(
+            "{person} only owes {} for {description}",
+            money.format(outstanding)
+        )));
+    }
+
+    let month = current_month(&pool, claims.sub).await?;
+    if month.is_closed {
+        return Err(PaymeError::BadRequest("Month is closed".to_string()));
+    }
+
+    let label = format!("{person} repaid {description}");
+    let mut tx = pool.begin().await?;
+    let id: i64 = sqlx::query_scalar(
+        "INSERT INTO income_entries (month_id, label, amount, split_id) VALUES (?, ?, ?, ?) RETURNING id",
+    )
+    .bind(month.id)
+    .bind(&label)
+    .bind(repayment)
+    .bind(split_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    income_changed(&mut tx, claims.sub, month.id).await?;
+
+    activity::record(
+        &mut *tx,
+        &claims,
+        Some(month.id),
+        "income",
+        id,
+        "create",
+        format!(
+            "{} recorded {} {}",
+            claims.username,
+            label,
+            money.format(repayment)
+        ),
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(Json(IncomeEntry {
+        id,
+        month_id: month.id,
+        label,
+        amount: repayment,
+        uuid: None,
+    }))
+}
+
+/// Amount of an item the user owns in that month.
+async fn fetch_item(
+    pool: &SqlitePool,
+    user_id: i64,
+    month_id: i64,
+    item_id: i64,
+) -> Result<f64, PaymeError> {
+    sqlx::query_scalar(
+        r#"
+        SELECT i.amount FROM items i
+        JOIN months m ON i.month_id = m.id
+        WHERE i.id = ? AND i.month_id = ? AND m.user_id = ?
+        "#,
+    )
+    .bind(item_id)
+    .bind(month_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(PaymeError::NotFound)
+}
+
+pub(crate) fn cents(amount: f64) -> i64 {
+    (amount * 100.0).round() as i64
+}
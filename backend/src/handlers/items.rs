@@ -1,70 +1,218 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::StatusCode,
-    Json,
 };
-use chrono::NaiveDate;
-use serde::Deserialize;
-use sqlx::SqlitePool;
-use utoipa::ToSchema;
+use chrono::{Duration, NaiveDate, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use sqlx::{QueryBuilder, SqliteConnection, SqliteExecutor, SqlitePool};
+use utoipa::{IntoParams, ToSchema};
 use validator::Validate;
 
+use crate::activity;
+use crate::categorize;
+use crate::config;
 use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::filters::{Columns, ListFilter};
+use crate::handlers::iou::cents;
+use crate::handlers::settings::money_format;
+use crate::handlers::sync;
 use crate::middleware::auth::Claims;
-use crate::models::{Item, ItemWithCategory};
+use crate::models::{included_tax, DescriptionSuggestion, Item, ItemWithCategory};
+use crate::quotas;
+use crate::suggestions;
 
 fn default_savings_destination() -> String {
     "none".to_string()
 }
 
 #[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct CreateItem {
-    pub category_id: i64,
+    /// Null or left out to pick the category from the rules set on the user's
+    /// categories, falling back to "Uncategorized".
+    #[serde(default)]
+    pub category_id: Option<i64>,
     #[validate(length(min = 1, max = 200))]
     pub description: String,
-    #[validate(range(min = 0.0))]
+    #[validate(range(min = 0.0), custom(function = "crate::money::amount"))]
     pub amount: f64,
     pub spent_on: NaiveDate,
+    /// `none` for spending. `savings`, `retirement_savings` or `cash` (an ATM withdrawal
+    /// into the cash wallet) move money instead and don't count as spending.
     #[serde(default = "default_savings_destination")]
     pub savings_destination: String,
+    /// Paid out of the cash wallet.
+    #[serde(default)]
+    pub paid_in_cash: bool,
+    /// Someone else pays this back, e.g. a work expense. Starts as `pending`.
+    #[serde(default)]
+    pub reimbursable: bool,
+    /// VAT/GST rate included in the amount, in percent. The tax amount is derived from it.
+    #[validate(range(min = 0.0, max = 100.0))]
+    pub tax_rate: Option<f64>,
+    /// Open earmark in the item's category that this purchase consumes. Without it, an
+    /// earmark whose label matches the description is consumed, if there is one.
+    pub earmark_id: Option<i64>,
+    /// UUID chosen by the client. Creating an item with a UUID already used returns
+    /// that item instead of adding another.
+    pub uuid: Option<String>,
+    /// Client the item was spent for. Needs the business profile.
+    #[validate(length(min = 1, max = 100))]
+    pub client: Option<String>,
+    /// To be invoiced to `client`. Needs the business profile and a client.
+    #[serde(default)]
+    pub billable: bool,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CreateItemQuery {
+    /// Save the item even if it looks like a duplicate of a recent entry.
+    #[serde(default)]
+    pub force: bool,
+}
+
+fn default_suggestion_limit() -> usize {
+    10
+}
+
+#[derive(Deserialize, IntoParams, Validate)]
+#[into_params(parameter_in = Query)]
+pub struct SuggestionsQuery {
+    /// Start of the description typed so far, ignoring case. Empty lists the most used.
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(default = "default_suggestion_limit")]
+    #[validate(range(min = 1, max = 50))]
+    pub limit: usize,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CreateItemResponse {
+    #[serde(flatten)]
+    pub item: Item,
+    /// Set when the item was saved with `force=true` despite matching this recent item.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duplicate_of: Option<i64>,
+}
+
+/// Body of the conflict returned for a likely duplicate.
+#[derive(Serialize, ToSchema)]
+pub struct DuplicateItem {
+    pub error: String,
+    /// The recent item this one matches.
+    pub duplicate_of: i64,
 }
 
 #[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateItem {
     pub category_id: Option<i64>,
     #[validate(length(min = 1, max = 200))]
     pub description: Option<String>,
-    #[validate(range(min = 0.0))]
+    #[validate(range(min = 0.0), custom(function = "crate::money::amount"))]
     pub amount: Option<f64>,
     pub spent_on: Option<NaiveDate>,
     pub savings_destination: Option<String>,
+    pub paid_in_cash: Option<bool>,
+    /// New tax rate in percent, or null to remove the tax breakdown.
+    #[serde(default, deserialize_with = "explicit_null")]
+    #[schema(value_type = Option<f64>)]
+    pub tax_rate: Option<Option<f64>>,
+    /// New client, or null to clear it. Needs the business profile.
+    #[validate(length(min = 1, max = 100))]
+    #[serde(default, deserialize_with = "explicit_null")]
+    #[schema(value_type = Option<String>)]
+    pub client: Option<Option<String>>,
+    /// Needs the business profile and a client.
+    pub billable: Option<bool>,
+}
+
+/// Tells a missing field (`None`) apart from an explicit `null` (`Some(None)`).
+pub(crate) fn explicit_null<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateReimbursement {
+    /// `pending`, `submitted` or `reimbursed`, or `none` when the item isn't reimbursable.
+    pub status: String,
 }
 
 #[utoipa::path(
-    get, path = "/api/months/{id}/items",
-    params(("id" = i64, Path)),
+    get, path = "/api/v1/months/{id}/items",
+    params(("id" = i64, Path), ListFilter),
     responses(
         (status = 200, body = [ItemWithCategory]),
         (status = 500, description = "Internal server error")
     ),
     tag = "Items",
     summary = "List transactions",
-    description = "Retrieves all itemized spending for the month, including category labels."
+    description = "Retrieves the itemized spending for the month, including category labels. Filters by category, spending date, amount and text in the description or category label."
 )]
 pub async fn list_items(
     State(pool): State<SqlitePool>,
     axum::Extension(claims): axum::Extension<Claims>,
     Path(month_id): Path<i64>,
+    Query(filter): Query<ListFilter>,
+) -> Result<Json<Vec<ItemWithCategory>>, PaymeError> {
+    verify_month_access(&pool, claims.sub, month_id).await?;
+
+    let mut query = QueryBuilder::new(
+        r#"
+        SELECT i.id, i.month_id, i.category_id, bc.label as category_label, i.description, i.amount, i.spent_on, i.savings_destination, i.reimbursement_status, i.tax_rate, i.tax_amount, i.paid_in_cash, i.uuid, i.client, i.billable
+        FROM items i
+        JOIN budget_categories bc ON i.category_id = bc.id
+        WHERE i.month_id = "#,
+    );
+    query.push_bind(month_id);
+    filter.apply(
+        &mut query,
+        &Columns {
+            category: Some("i.category_id"),
+            date: Some("i.spent_on"),
+            amount: Some("i.amount"),
+            text: &["i.description", "bc.label"],
+        },
+    )?;
+    query.push(" ORDER BY i.spent_on DESC");
+    let items: Vec<ItemWithCategory> = query.build_query_as().fetch_all(&pool).await?;
+
+    Ok(Json(items))
+}
+
+#[utoipa::path(
+    get, path = "/api/v1/months/{id}/items/uncategorized",
+    params(("id" = i64, Path)),
+    responses(
+        (status = 200, body = [ItemWithCategory]),
+        (status = 404, description = "Month not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Items",
+    summary = "List uncategorized transactions",
+    description = "Lists the month's items still in the \"Uncategorized\" category, oldest first, to file them before the month is closed."
+)]
+pub async fn list_uncategorized_items(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(month_id): Path<i64>,
 ) -> Result<Json<Vec<ItemWithCategory>>, PaymeError> {
     verify_month_access(&pool, claims.sub, month_id).await?;
 
     let items: Vec<ItemWithCategory> = sqlx::query_as(
         r#"
-        SELECT i.id, i.month_id, i.category_id, bc.label as category_label, i.description, i.amount, i.spent_on, i.savings_destination
+        SELECT i.id, i.month_id, i.category_id, bc.label as category_label, i.description, i.amount, i.spent_on, i.savings_destination, i.reimbursement_status, i.tax_rate, i.tax_amount, i.paid_in_cash, i.uuid, i.client, i.billable
         FROM items i
         JOIN budget_categories bc ON i.category_id = bc.id
-        WHERE i.month_id = ?
-        ORDER BY i.spent_on DESC
+        WHERE i.month_id = ? AND bc.system = 1
+        ORDER BY i.spent_on, i.id
         "#,
     )
     .bind(month_id)
@@ -75,52 +223,147 @@ pub async fn list_items(
 }
 
 #[utoipa::path(
-    post, path = "/api/months/{id}/items",
-    params(("id" = i64, Path)),
+    get, path = "/api/v1/items/suggestions",
+    params(SuggestionsQuery),
+    responses(
+        (status = 200, body = [DescriptionSuggestion]),
+        (status = 400, description = "Limit out of range"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Items",
+    summary = "Suggest descriptions",
+    description = "Autocompletes a description from the user's earlier spending, most used first, with the category it is usually filed under and its median amount. Results may lag new items by a few minutes."
+)]
+pub async fn suggest_descriptions(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Query(query): Query<SuggestionsQuery>,
+) -> Result<Json<Vec<DescriptionSuggestion>>, PaymeError> {
+    query.validate()?;
+    let suggestions = suggestions::matching(&pool, claims.sub, &query.prefix, query.limit).await?;
+
+    Ok(Json(suggestions))
+}
+
+#[utoipa::path(
+    post, path = "/api/v1/months/{id}/items",
+    params(("id" = i64, Path), CreateItemQuery),
     request_body = CreateItem,
     responses(
-        (status = 200, body = Item),
+        (status = 200, body = CreateItemResponse),
+        (status = 403, description = "Category is locked for this month, or a client was given without the business profile"),
+        (status = 409, description = "Matches an item entered moments ago, named in `duplicate_of`; retry with force=true to keep it. Also returned when the month holds as many items as the server allows", body = DuplicateItem),
         (status = 500, description = "Internal server error")
     ),
     tag = "Items",
     summary = "Record transaction",
-    description = "Logs a new expense against a specific budget category."
+    description = "Logs a new expense against a specific budget category. Without a category, the first rule set on the user's categories matching the description picks one, otherwise it goes to \"Uncategorized\". Items with the same amount, date and description as one entered within the duplicate window are rejected unless forced."
 )]
 pub async fn create_item(
     State(pool): State<SqlitePool>,
     axum::Extension(claims): axum::Extension<Claims>,
     Path(month_id): Path<i64>,
+    Query(query): Query<CreateItemQuery>,
     Json(payload): Json<CreateItem>,
-) -> Result<Json<Item>, PaymeError> {
+) -> Result<Json<CreateItemResponse>, PaymeError> {
+    let mut tx = pool.begin().await?;
+    let response = insert_item(&mut tx, &claims, month_id, payload, query.force).await?;
+    tx.commit().await?;
+    suggestions::invalidate(claims.sub);
+
+    Ok(Json(response))
+}
+
+/// Validates and stores a new item, moving its amount into savings when it is a transfer.
+pub(crate) async fn insert_item(
+    conn: &mut SqliteConnection,
+    claims: &Claims,
+    month_id: i64,
+    payload: CreateItem,
+    force: bool,
+) -> Result<CreateItemResponse, PaymeError> {
     payload.validate()?;
-    verify_month_not_closed(&pool, claims.sub, month_id).await?;
+    let uuid = sync::client_uuid(payload.uuid.as_deref())?;
+    if let Some(uuid) = &uuid {
+        if let Some(item) = find_by_uuid(&mut *conn, claims.sub, uuid).await? {
+            return Ok(CreateItemResponse {
+                item,
+                duplicate_of: None,
+            });
+        }
+    }
+    verify_cash_spending(payload.paid_in_cash, &payload.savings_destination)?;
+    if payload.client.is_some() || payload.billable {
+        verify_business_client(
+            &mut *conn,
+            claims.sub,
+            payload.client.as_deref(),
+            payload.billable,
+        )
+        .await?;
+    }
+    verify_month_not_closed(&mut *conn, claims.sub, month_id).await?;
 
-    let _category: (i64,) =
-        sqlx::query_as("SELECT id FROM budget_categories WHERE id = ? AND user_id = ?")
-            .bind(payload.category_id)
-            .bind(claims.sub)
-            .fetch_optional(&pool)
-            .await?
-            .ok_or(PaymeError::BadRequest("Invalid category".to_string()))?;
+    let duplicate_of = find_recent_duplicate(&mut *conn, claims.sub, &payload).await?;
+    if let Some(duplicate_id) = duplicate_of {
+        if !force {
+            return Err(PaymeError::Duplicate(duplicate_id));
+        }
+    }
 
+    let category_id = match payload.category_id {
+        Some(category_id) => {
+            sqlx::query_scalar("SELECT id FROM budget_categories WHERE id = ? AND user_id = ?")
+                .bind(category_id)
+                .bind(claims.sub)
+                .fetch_optional(&mut *conn)
+                .await?
+                .ok_or(PaymeError::BadRequest("Invalid category".to_string()))?
+        }
+        None => categorize::assign(&mut *conn, claims.sub, &payload.description).await?,
+    };
+    verify_category_unlocked(&mut *conn, month_id, category_id).await?;
+    quotas::check_items(&mut *conn, month_id, 1).await?;
+    let earmark_id = find_earmark(&mut *conn, month_id, category_id, &payload).await?;
+
+    let reimbursement_status = payload.reimbursable.then(|| "pending".to_string());
+    let tax_amount = payload
+        .tax_rate
+        .map(|rate| included_tax(payload.amount, rate));
     let id: i64 = sqlx::query_scalar(
-        "INSERT INTO items (month_id, category_id, description, amount, spent_on, savings_destination) VALUES (?, ?, ?, ?, ?, ?) RETURNING id",
+        "INSERT INTO items (month_id, category_id, description, amount, spent_on, savings_destination, reimbursement_status, tax_rate, tax_amount, paid_in_cash, uuid, client, billable, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?) RETURNING id",
     )
     .bind(month_id)
-    .bind(payload.category_id)
+    .bind(category_id)
     .bind(&payload.description)
     .bind(payload.amount)
     .bind(payload.spent_on)
     .bind(&payload.savings_destination)
-    .fetch_one(&pool)
+    .bind(&reimbursement_status)
+    .bind(payload.tax_rate)
+    .bind(tax_amount)
+    .bind(payload.paid_in_cash)
+    .bind(&uuid)
+    .bind(&payload.client)
+    .bind(payload.billable)
+    .bind(Utc::now())
+    .fetch_one(&mut *conn)
     .await?;
 
+    if let Some(earmark_id) = earmark_id {
+        sqlx::query("UPDATE budget_earmarks SET item_id = ? WHERE id = ?")
+            .bind(id)
+            .bind(earmark_id)
+            .execute(&mut *conn)
+            .await?;
+    }
+
     match payload.savings_destination.as_str() {
         "savings" => {
             sqlx::query("UPDATE users SET savings = savings + ? WHERE id = ?")
                 .bind(payload.amount)
                 .bind(claims.sub)
-                .execute(&pool)
+                .execute(&mut *conn)
                 .await?;
         }
         "retirement_savings" => {
@@ -129,26 +372,53 @@ pub async fn create_item(
             )
             .bind(payload.amount)
             .bind(claims.sub)
-            .execute(&pool)
+            .execute(&mut *conn)
             .await?;
         }
         _ => {}
     }
 
-    Ok(Json(Item {
+    let money = money_format(&mut *conn, claims.sub).await?;
+    activity::record(
+        &mut *conn,
+        claims,
+        Some(month_id),
+        "item",
         id,
-        month_id,
-        category_id: payload.category_id,
-        description: payload.description,
-        amount: payload.amount,
-        spent_on: payload.spent_on,
-        savings_destination: payload.savings_destination,
-    }))
+        "create",
+        format!(
+            "{} added {} {}",
+            claims.username,
+            payload.description,
+            money.format(payload.amount)
+        ),
+    )
+    .await?;
+
+    Ok(CreateItemResponse {
+        item: Item {
+            id,
+            month_id,
+            category_id,
+            description: payload.description,
+            amount: payload.amount,
+            spent_on: payload.spent_on,
+            savings_destination: payload.savings_destination,
+            reimbursement_status,
+            tax_rate: payload.tax_rate,
+            tax_amount,
+            paid_in_cash: payload.paid_in_cash,
+            uuid,
+            client: payload.client,
+            billable: payload.billable,
+        },
+        duplicate_of,
+    })
 }
 
 #[utoipa::path(
     put,
-    path = "/api/months/{month_id}/items/{id}",
+    path = "/api/v1/months/{month_id}/items/{id}",
     params(
         ("month_id" = i64, Path, description = "Month ID"),
         ("id" = i64, Path, description = "Item (Transaction) ID")
@@ -156,6 +426,8 @@ pub async fn create_item(
     request_body = UpdateItem,
     responses(
         (status = 200, description = "Item updated successfully", body = Item),
+        (status = 400, description = "The new amount is less than what is split with other people"),
+        (status = 403, description = "Moving into a category that is locked for this month, or a client was given without the business profile"),
         (status = 404, description = "Item not found"),
         (status = 500, description = "Internal server error")
     ),
@@ -169,15 +441,32 @@ pub async fn update_item(
     Path((month_id, item_id)): Path<(i64, i64)>,
     Json(payload): Json<UpdateItem>,
 ) -> Result<Json<Item>, PaymeError> {
+    let mut tx = pool.begin().await?;
+    let item = apply_item_update(&mut tx, &claims, month_id, item_id, payload).await?;
+    tx.commit().await?;
+    suggestions::invalidate(claims.sub);
+
+    Ok(Json(item))
+}
+
+/// Applies the given changes to an item, moving money between the savings balances
+/// when its destination or amount changes.
+pub(crate) async fn apply_item_update(
+    conn: &mut SqliteConnection,
+    claims: &Claims,
+    month_id: i64,
+    item_id: i64,
+    payload: UpdateItem,
+) -> Result<Item, PaymeError> {
     payload.validate()?;
-    verify_month_not_closed(&pool, claims.sub, month_id).await?;
+    verify_month_not_closed(&mut *conn, claims.sub, month_id).await?;
 
     let existing: Item = sqlx::query_as(
-        "SELECT id, month_id, category_id, description, amount, spent_on, savings_destination FROM items WHERE id = ? AND month_id = ?",
+        "SELECT id, month_id, category_id, description, amount, spent_on, savings_destination, reimbursement_status, tax_rate, tax_amount, paid_in_cash, uuid, client, billable FROM items WHERE id = ? AND month_id = ?",
     )
     .bind(item_id)
     .bind(month_id)
-    .fetch_optional(&pool)
+    .fetch_optional(&mut *conn)
     .await?
     .ok_or(PaymeError::NotFound)?;
 
@@ -188,30 +477,75 @@ pub async fn update_item(
     let savings_destination = payload
         .savings_destination
         .unwrap_or(existing.savings_destination.clone());
+    let paid_in_cash = payload.paid_in_cash.unwrap_or(existing.paid_in_cash);
+    verify_cash_spending(paid_in_cash, &savings_destination)?;
+    let tax_rate = payload.tax_rate.unwrap_or(existing.tax_rate);
+    if tax_rate.is_some_and(|rate| !(0.0..=100.0).contains(&rate)) {
+        return Err(PaymeError::BadRequest(
+            "Tax rate must be between 0 and 100".to_string(),
+        ));
+    }
+    let tax_amount = tax_rate.map(|rate| included_tax(amount, rate));
+    if amount != existing.amount {
+        let split: f64 = sqlx::query_scalar(
+            "SELECT COALESCE(SUM(amount), 0.0) FROM item_splits WHERE item_id = ?",
+        )
+        .bind(item_id)
+        .fetch_one(&mut *conn)
+        .await?;
+        if cents(amount) < cents(split) {
+            let money = money_format(&mut *conn, claims.sub).await?;
+            return Err(PaymeError::BadRequest(format!(
+                "{} of this item is split with other people, remove splits first",
+                money.format(split)
+            )));
+        }
+    }
+    let client = payload.client.clone().unwrap_or(existing.client.clone());
+    let billable = payload.billable.unwrap_or(existing.billable);
+    if payload.client.is_some() || payload.billable.is_some() {
+        verify_business_client(&mut *conn, claims.sub, client.as_deref(), billable).await?;
+    }
 
     if payload.category_id.is_some() {
         let _category: (i64,) =
             sqlx::query_as("SELECT id FROM budget_categories WHERE id = ? AND user_id = ?")
                 .bind(category_id)
                 .bind(claims.sub)
-                .fetch_optional(&pool)
+                .fetch_optional(&mut *conn)
                 .await?
                 .ok_or(PaymeError::BadRequest("Invalid category".to_string()))?;
+        if category_id != existing.category_id {
+            verify_category_unlocked(&mut *conn, month_id, category_id).await?;
+        }
     }
 
     // Update the item first to ensure data consistency
     sqlx::query(
-        "UPDATE items SET category_id = ?, description = ?, amount = ?, spent_on = ?, savings_destination = ? WHERE id = ?",
+        "UPDATE items SET category_id = ?, description = ?, amount = ?, spent_on = ?, savings_destination = ?, tax_rate = ?, tax_amount = ?, paid_in_cash = ?, client = ?, billable = ? WHERE id = ?",
     )
     .bind(category_id)
     .bind(&description)
     .bind(amount)
     .bind(spent_on)
     .bind(&savings_destination)
+    .bind(tax_rate)
+    .bind(tax_amount)
+    .bind(paid_in_cash)
+    .bind(&client)
+    .bind(billable)
     .bind(item_id)
-    .execute(&pool)
+    .execute(&mut *conn)
     .await?;
 
+    // An amount typed in by hand no longer follows the mileage or per-diem rate
+    if amount != existing.amount {
+        sqlx::query("DELETE FROM item_calculations WHERE item_id = ?")
+            .bind(item_id)
+            .execute(&mut *conn)
+            .await?;
+    }
+
     let old_dest = existing.savings_destination.as_str();
     let new_dest = savings_destination.as_str();
 
@@ -222,7 +556,7 @@ pub async fn update_item(
                 sqlx::query("UPDATE users SET savings = savings - ? WHERE id = ?")
                     .bind(existing.amount)
                     .bind(claims.sub)
-                    .execute(&pool)
+                    .execute(&mut *conn)
                     .await?;
             }
             "retirement_savings" => {
@@ -231,7 +565,7 @@ pub async fn update_item(
                 )
                 .bind(existing.amount)
                 .bind(claims.sub)
-                .execute(&pool)
+                .execute(&mut *conn)
                 .await?;
             }
             _ => {}
@@ -242,7 +576,7 @@ pub async fn update_item(
                 sqlx::query("UPDATE users SET savings = savings + ? WHERE id = ?")
                     .bind(amount)
                     .bind(claims.sub)
-                    .execute(&pool)
+                    .execute(&mut *conn)
                     .await?;
             }
             "retirement_savings" => {
@@ -251,14 +585,31 @@ pub async fn update_item(
                 )
                 .bind(amount)
                 .bind(claims.sub)
-                .execute(&pool)
+                .execute(&mut *conn)
                 .await?;
             }
             _ => {}
         }
     }
 
-    Ok(Json(Item {
+    let money = money_format(&mut *conn, claims.sub).await?;
+    activity::record(
+        &mut *conn,
+        claims,
+        Some(month_id),
+        "item",
+        item_id,
+        "update",
+        format!(
+            "{} updated {} {}",
+            claims.username,
+            description,
+            money.format(amount)
+        ),
+    )
+    .await?;
+
+    Ok(Item {
         id: item_id,
         month_id,
         category_id,
@@ -266,12 +617,86 @@ pub async fn update_item(
         amount,
         spent_on,
         savings_destination,
-    }))
+        reimbursement_status: existing.reimbursement_status,
+        tax_rate,
+        tax_amount,
+        paid_in_cash,
+        uuid: existing.uuid,
+        client,
+        billable,
+    })
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/months/{month_id}/items/{id}/reimbursement",
+    params(
+        ("month_id" = i64, Path, description = "Month ID"),
+        ("id" = i64, Path, description = "Item (Transaction) ID")
+    ),
+    request_body = UpdateReimbursement,
+    responses(
+        (status = 200, body = Item),
+        (status = 400, description = "Unknown status"),
+        (status = 404, description = "Item not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Items",
+    summary = "Track a reimbursement",
+    description = "Marks an item as reimbursable or moves it through `pending`, `submitted` and `reimbursed`. Allowed on closed months, since money often comes back after the month ends."
+)]
+pub async fn update_reimbursement(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path((month_id, item_id)): Path<(i64, i64)>,
+    Json(payload): Json<UpdateReimbursement>,
+) -> Result<Json<Item>, PaymeError> {
+    let status = match payload.status.as_str() {
+        "none" => None,
+        "pending" | "submitted" | "reimbursed" => Some(payload.status),
+        other => {
+            return Err(PaymeError::BadRequest(format!(
+                "Unknown reimbursement status: {other}"
+            )))
+        }
+    };
+    verify_month_access(&pool, claims.sub, month_id).await?;
+
+    let item: Item = sqlx::query_as(
+        r#"
+        UPDATE items SET reimbursement_status = ? WHERE id = ? AND month_id = ?
+        RETURNING id, month_id, category_id, description, amount, spent_on, savings_destination, reimbursement_status, tax_rate, tax_amount, paid_in_cash, uuid, client, billable
+        "#,
+    )
+    .bind(&status)
+    .bind(item_id)
+    .bind(month_id)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(PaymeError::NotFound)?;
+
+    activity::record(
+        &pool,
+        &claims,
+        Some(month_id),
+        "item",
+        item_id,
+        "reimbursement",
+        format!(
+            "{} marked {} as {}",
+            claims.username,
+            item.description,
+            status.as_deref().unwrap_or("not reimbursable")
+        ),
+    )
+    .await?;
+
+    Ok(Json(item))
 }
 
 #[utoipa::path(
     delete,
-    path = "/api/months/{month_id}/items/{id}",
+    path = "/api/v1/months/{month_id}/items/{id}",
     params(
         ("month_id" = i64, Path, description = "Month ID"),
         ("id" = i64, Path, description = "Item (Transaction) ID")
@@ -289,14 +714,29 @@ pub async fn delete_item(
     axum::Extension(claims): axum::Extension<Claims>,
     Path((month_id, item_id)): Path<(i64, i64)>,
 ) -> Result<StatusCode, PaymeError> {
-    verify_month_not_closed(&pool, claims.sub, month_id).await?;
+    let mut tx = pool.begin().await?;
+    remove_item(&mut tx, &claims, month_id, item_id).await?;
+    tx.commit().await?;
+    suggestions::invalidate(claims.sub);
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Deletes an item, taking its amount back out of savings when it was a transfer.
+pub(crate) async fn remove_item(
+    conn: &mut SqliteConnection,
+    claims: &Claims,
+    month_id: i64,
+    item_id: i64,
+) -> Result<(), PaymeError> {
+    verify_month_not_closed(&mut *conn, claims.sub, month_id).await?;
 
     let item: Item = sqlx::query_as(
-        "SELECT id, month_id, category_id, description, amount, spent_on, savings_destination FROM items WHERE id = ? AND month_id = ?",
+        "SELECT id, month_id, category_id, description, amount, spent_on, savings_destination, reimbursement_status, tax_rate, tax_amount, paid_in_cash, uuid, client, billable FROM items WHERE id = ? AND month_id = ?",
     )
     .bind(item_id)
     .bind(month_id)
-    .fetch_optional(&pool)
+    .fetch_optional(&mut *conn)
     .await?
     .ok_or(PaymeError::NotFound)?;
 
@@ -305,7 +745,7 @@ pub async fn delete_item(
             sqlx::query("UPDATE users SET savings = savings - ? WHERE id = ?")
                 .bind(item.amount)
                 .bind(claims.sub)
-                .execute(&pool)
+                .execute(&mut *conn)
                 .await?;
         }
         "retirement_savings" => {
@@ -314,7 +754,7 @@ pub async fn delete_item(
             )
             .bind(item.amount)
             .bind(claims.sub)
-            .execute(&pool)
+            .execute(&mut *conn)
             .await?;
         }
         _ => {}
@@ -323,10 +763,57 @@ pub async fn delete_item(
     sqlx::query("DELETE FROM items WHERE id = ? AND month_id = ?")
         .bind(item_id)
         .bind(month_id)
-        .execute(&pool)
+        .execute(&mut *conn)
         .await?;
 
-    Ok(StatusCode::NO_CONTENT)
+    let money = money_format(&mut *conn, claims.sub).await?;
+    activity::record(
+        &mut *conn,
+        claims,
+        Some(month_id),
+        "item",
+        item_id,
+        "delete",
+        format!(
+            "{} removed {} {}",
+            claims.username,
+            item.description,
+            money.format(item.amount)
+        ),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// The user's item created with `uuid`. Fails if another user's item has it.
+async fn find_by_uuid(
+    conn: &mut SqliteConnection,
+    user_id: i64,
+    uuid: &str,
+) -> Result<Option<Item>, PaymeError> {
+    let owner: Option<i64> = sqlx::query_scalar(
+        "SELECT m.user_id FROM items i JOIN months m ON i.month_id = m.id WHERE i.uuid = ?",
+    )
+    .bind(uuid)
+    .fetch_optional(&mut *conn)
+    .await?;
+    match owner {
+        None => return Ok(None),
+        Some(owner) if owner != user_id => {
+            return Err(PaymeError::Conflict(format!(
+                "UUID {uuid} is already in use"
+            )))
+        }
+        Some(_) => {}
+    }
+
+    Ok(sqlx::query_as(
+        "SELECT id, month_id, category_id, description, amount, spent_on, savings_destination, reimbursement_status, tax_rate, tax_amount, paid_in_cash, uuid, client, billable FROM items WHERE uuid = ?",
+    )
+    .bind(uuid)
+    .fetch_optional(&mut *conn)
+    .await?)
 }
 
 async fn verify_month_access(
@@ -344,8 +831,8 @@ async fn verify_month_access(
     exists.map(|_| ()).ok_or(PaymeError::NotFound)
 }
 
-async fn verify_month_not_closed(
-    pool: &SqlitePool,
+async fn verify_month_not_closed<'e>(
+    executor: impl SqliteExecutor<'e>,
     user_id: i64,
     month_id: i64,
 ) -> Result<(), PaymeError> {
@@ -353,7 +840,7 @@ async fn verify_month_not_closed(
         sqlx::query_as("SELECT is_closed FROM months WHERE id = ? AND user_id = ?")
             .bind(month_id)
             .bind(user_id)
-            .fetch_optional(pool)
+            .fetch_optional(executor)
             .await?;
 
     match month {
@@ -362,3 +849,160 @@ async fn verify_month_not_closed(
         None => Err(PaymeError::NotFound),
     }
 }
+
+/// The open earmark a new item consumes: the one it names, otherwise the oldest one in
+/// its category labelled like its description.
+async fn find_earmark(
+    conn: &mut SqliteConnection,
+    month_id: i64,
+    category_id: i64,
+    payload: &CreateItem,
+) -> Result<Option<i64>, PaymeError> {
+    if let Some(earmark_id) = payload.earmark_id {
+        return sqlx::query_scalar(
+            r#"
+            SELECT e.id FROM budget_earmarks e
+            JOIN monthly_budgets mb ON e.budget_id = mb.id
+            WHERE e.id = ? AND mb.month_id = ? AND mb.category_id = ? AND e.item_id IS NULL
+            "#,
+        )
+        .bind(earmark_id)
+        .bind(month_id)
+        .bind(category_id)
+        .fetch_optional(&mut *conn)
+        .await?
+        .map(Some)
+        .ok_or(PaymeError::BadRequest(
+            "Earmark is not open in this category".to_string(),
+        ));
+    }
+
+    let earmark_id = sqlx::query_scalar(
+        r#"
+        SELECT e.id FROM budget_earmarks e
+        JOIN monthly_budgets mb ON e.budget_id = mb.id
+        WHERE mb.month_id = ? AND mb.category_id = ? AND e.item_id IS NULL
+          AND LOWER(e.label) = LOWER(TRIM(?))
+        ORDER BY e.id
+        LIMIT 1
+        "#,
+    )
+    .bind(month_id)
+    .bind(category_id)
+    .bind(&payload.description)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    Ok(earmark_id)
+}
+
+/// Refuses new items for a category the user locked for this month.
+pub(crate) async fn verify_category_unlocked<'e>(
+    executor: impl SqliteExecutor<'e>,
+    month_id: i64,
+    category_id: i64,
+) -> Result<(), PaymeError> {
+    let lock: Option<(String, Option<String>)> = sqlx::query_as(
+        r#"
+        SELECT bc.label, mb.lock_reason FROM monthly_budgets mb
+        JOIN budget_categories bc ON mb.category_id = bc.id
+        WHERE mb.month_id = ? AND mb.category_id = ? AND mb.locked_at IS NOT NULL
+        "#,
+    )
+    .bind(month_id)
+    .bind(category_id)
+    .fetch_optional(executor)
+    .await?;
+
+    match lock {
+        Some((label, Some(reason))) => Err(PaymeError::Forbidden(format!(
+            "{label} is locked for this month: {reason}"
+        ))),
+        Some((label, None)) => Err(PaymeError::Forbidden(format!(
+            "{label} is locked for this month"
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Looks for an item of the same user with the same amount, date and description
+/// (ignoring case, punctuation and spacing) entered within the duplicate window.
+async fn find_recent_duplicate<'e>(
+    executor: impl SqliteExecutor<'e>,
+    user_id: i64,
+    item: &CreateItem,
+) -> Result<Option<i64>, PaymeError> {
+    let window = config::duplicate_window_minutes();
+    if window <= 0 {
+        return Ok(None);
+    }
+
+    let candidates: Vec<(i64, String)> = sqlx::query_as(
+        r#"
+        SELECT i.id, i.description FROM items i
+        JOIN months m ON i.month_id = m.id
+        WHERE m.user_id = ? AND i.spent_on = ? AND ABS(i.amount - ?) < 0.005
+          AND i.created_at >= ?
+        ORDER BY i.id DESC
+        "#,
+    )
+    .bind(user_id)
+    .bind(item.spent_on)
+    .bind(item.amount)
+    .bind(Utc::now() - Duration::minutes(window))
+    .fetch_all(executor)
+    .await?;
+
+    let description = normalize_description(&item.description);
+    Ok(candidates
+        .into_iter()
+        .find(|(_, other)| normalize_description(other) == description)
+        .map(|(id, _)| id))
+}
+
+fn normalize_description(description: &str) -> String {
+    description
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Clients and billing belong to the business profile, and billable items need a client
+/// to be invoiced to.
+async fn verify_business_client(
+    conn: &mut SqliteConnection,
+    user_id: i64,
+    client: Option<&str>,
+    billable: bool,
+) -> Result<(), PaymeError> {
+    let business_profile: Option<bool> =
+        sqlx::query_scalar("SELECT business_profile FROM user_settings WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_optional(&mut *conn)
+            .await?
+            .flatten();
+    if business_profile != Some(true) {
+        return Err(PaymeError::Forbidden(
+            "Clients and billable items need the business profile; turn it on in settings"
+                .to_string(),
+        ));
+    }
+    if billable && client.is_none() {
+        return Err(PaymeError::BadRequest(
+            "Billable items need a client".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Cash spending draws the wallet down, so it can't also be a transfer such as a withdrawal.
+fn verify_cash_spending(paid_in_cash: bool, savings_destination: &str) -> Result<(), PaymeError> {
+    if paid_in_cash && savings_destination != "none" {
+        return Err(PaymeError::BadRequest(
+            "Only spending can be paid in cash".to_string(),
+        ));
+    }
+    Ok(())
+}
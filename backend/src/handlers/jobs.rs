@@ -0,0 +1,42 @@
+use axum::extract::{Path, State};
+use sqlx::SqlitePool;
+
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::middleware::auth::Claims;
+use crate::models::Job;
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/jobs/{id}",
+    params(
+        ("id" = i64, Path, description = "Job ID")
+    ),
+    responses(
+        (status = 200, body = Job),
+        (status = 404, description = "Job not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Jobs",
+    summary = "Get job status",
+    description = "Reports the progress of a background job, such as the PDF generation queued when a month is closed."
+)]
+pub async fn get_job(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(job_id): Path<i64>,
+) -> Result<Json<Job>, PaymeError> {
+    let job: Job = sqlx::query_as(
+        r#"
+        SELECT id, kind, status, attempts, error, created_at, finished_at
+        FROM jobs WHERE id = ? AND user_id = ?
+        "#,
+    )
+    .bind(job_id)
+    .bind(claims.sub)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(PaymeError::NotFound)?;
+
+    Ok(Json(job))
+}
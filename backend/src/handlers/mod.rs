@@ -1,10 +1,44 @@
+pub mod admin;
+pub mod allowances;
+pub mod analytics;
 pub mod auth;
 pub mod budget;
+pub mod cash;
+pub mod cashflow;
+pub mod checklist;
+pub mod clients;
+pub mod connectors;
+pub mod cpi;
+pub mod dashboard;
+pub mod data_quality;
 pub mod export;
 pub mod fixed_expenses;
 pub mod health;
 pub mod income;
+pub mod insights;
+pub mod invoices;
+pub mod iou;
 pub mod items;
+pub mod jobs;
 pub mod months;
+pub mod onboarding;
+pub mod pace;
+pub mod periods;
+pub mod plans;
+pub mod projects;
+pub mod reimbursements;
+pub mod reports;
+pub mod retirement;
+pub mod rules;
 pub mod savings;
+pub mod settings;
+pub mod share;
+pub mod signatures;
+pub mod simulations;
 pub mod stats;
+pub mod subscriptions;
+pub mod sync;
+pub mod tax;
+pub mod tools;
+pub mod usage;
+pub mod wishlist;
@@ -1,48 +1,139 @@
 use axum::{
-    extract::{Path, State},
-    Json,
+    extract::{Path, Query, State},
+    http::HeaderMap,
 };
-use chrono::{Datelike, Utc};
-use sqlx::SqlitePool;
+use chrono::{Datelike, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{QueryBuilder, SqlitePool};
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
 
+use crate::activity;
+use crate::db;
 use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::filters::{Columns, ListFilter};
+use crate::handlers::budget::apply_percent_allocations;
+use crate::handlers::checklist::load_checklist;
+use crate::handlers::savings::book_auto_contribution;
+use crate::handlers::settings::load_settings;
+use crate::i18n;
+use crate::jobs::{self, MonthPdfJob};
 use crate::middleware::auth::Claims;
-use crate::models::{
-    FixedExpense, IncomeEntry, ItemWithCategory, Month, MonthSummary, MonthlyBudgetWithCategory,
-};
-use crate::pdf;
+use crate::middleware::maintenance::MaintenanceMode;
+use crate::middleware::security::PDF_CONTENT_SECURITY_POLICY;
+use crate::models::{ActivityEntry, ActivityPage, Month, MonthDigest, MonthSummary};
+use crate::quotas;
+use crate::storage;
+use crate::summary;
+
+#[derive(Serialize, ToSchema)]
+pub struct CloseMonthResponse {
+    #[serde(flatten)]
+    pub month: Month,
+    /// Job generating the PDF snapshot, see `GET /api/v1/jobs/{id}`.
+    pub pdf_job_id: i64,
+    pub digest: MonthDigest,
+}
+
+#[derive(Deserialize, ToSchema, Default)]
+#[serde(deny_unknown_fields)]
+pub struct CloseMonth {
+    /// Ids of the close checklist entries the user has ticked off.
+    #[serde(default)]
+    pub acknowledged: Vec<i64>,
+    /// Close even if some checklist entries aren't ticked off. They are recorded as skipped.
+    #[serde(default)]
+    pub force: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PdfVerification {
+    pub month_id: i64,
+    /// Hash recorded when the snapshot was stored.
+    pub sha256: String,
+    /// Hash of the snapshot as stored now.
+    pub computed_sha256: String,
+    /// Whether the two match, i.e. the snapshot is unchanged since it was stored.
+    pub valid: bool,
+    pub created_at: NaiveDateTime,
+}
+
+/// A stored PDF snapshot with the hash recorded for it and the hash of its content.
+struct StoredSnapshot {
+    pdf: Vec<u8>,
+    sha256: String,
+    computed_sha256: String,
+    created_at: NaiveDateTime,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SummaryQuery {
+    /// Leave items that have been reimbursed out of the spent amounts.
+    #[serde(default)]
+    pub exclude_reimbursed: bool,
+}
+
+#[derive(Deserialize, IntoParams, Validate)]
+#[into_params(parameter_in = Query)]
+pub struct ActivityQuery {
+    /// Page number, starting at 1.
+    #[validate(range(min = 1))]
+    pub page: Option<i64>,
+    /// Entries per page (default 50, max 200).
+    #[validate(range(min = 1, max = 200))]
+    pub per_page: Option<i64>,
+    /// Only show changes made by this username.
+    pub actor: Option<String>,
+    /// Only show changes to this entity type (`item`, `income`, `budget`, `month`).
+    pub entity_type: Option<String>,
+}
 
 #[utoipa::path(
     get,
-    path = "/api/months",
+    path = "/api/v1/months",
+    params(ListFilter),
     responses(
         (status = 200, description = "List all months for the user", body = [Month]),
         (status = 500, description = "Internal server error")
     ),
     tag = "Months",
     summary = "List all budget months",
-    description = "Retrieves a history of all months created by the user, ordered by date."
+    description = "Retrieves a history of the months created by the user, ordered by date. Filters by date, matching each month by its first day."
 )]
 pub async fn list_months(
     State(pool): State<SqlitePool>,
     axum::Extension(claims): axum::Extension<Claims>,
+    Query(filter): Query<ListFilter>,
 ) -> Result<Json<Vec<Month>>, PaymeError> {
-    let months: Vec<Month> = sqlx::query_as(
-        "SELECT id, user_id, year, month, is_closed, closed_at FROM months WHERE user_id = ? ORDER BY year DESC, month DESC",
-    )
-    .bind(claims.sub)
-    .fetch_all(&pool)
-    .await?;
+    let mut query = QueryBuilder::new(
+        "SELECT id, user_id, year, month, is_closed, closed_at FROM months WHERE user_id = ",
+    );
+    query.push_bind(claims.sub);
+    filter.apply(
+        &mut query,
+        &Columns {
+            category: None,
+            date: Some("printf('%04d-%02d-01', year, month)"),
+            amount: None,
+            text: &[],
+        },
+    )?;
+    query.push(" ORDER BY year DESC, month DESC");
+    let months: Vec<Month> = query.build_query_as().fetch_all(&pool).await?;
 
     Ok(Json(months))
 }
 
 #[utoipa::path(
     get,
-    path = "/api/months/current",
+    path = "/api/v1/months/current",
+    params(SummaryQuery),
     responses(
         (status = 200, description = "Get current month or create it if it doesn't exist", body = MonthSummary),
-        (status = 500, description = "Internal server error")
+        (status = 500, description = "Internal server error"),
+        (status = 503, description = "The month doesn't exist yet and the API is read-only")
     ),
     tag = "Months",
     summary = "Get current month summary",
@@ -51,54 +142,114 @@ pub async fn list_months(
 pub async fn get_or_create_current_month(
     State(pool): State<SqlitePool>,
     axum::Extension(claims): axum::Extension<Claims>,
+    axum::Extension(mode): axum::Extension<MaintenanceMode>,
+    Query(query): Query<SummaryQuery>,
 ) -> Result<Json<MonthSummary>, PaymeError> {
+    let month = current_month_to_read(&pool, claims.sub, &mode).await?;
+    get_month_summary_with(&pool, claims.sub, month.id, query.exclude_reimbursed).await
+}
+
+/// Returns the user's month for today's date, creating it if needed.
+pub(crate) async fn current_month(pool: &SqlitePool, user_id: i64) -> Result<Month, PaymeError> {
     let now = Utc::now();
-    let year = now.year();
-    let month = now.month() as i32;
+    month_for(pool, user_id, now.year(), now.month() as i32).await
+}
 
+/// Like [`current_month`], for reads. While the API is read-only the month isn't
+/// created, so a missing one fails with [`PaymeError::ReadOnly`].
+pub(crate) async fn current_month_to_read(
+    pool: &SqlitePool,
+    user_id: i64,
+    mode: &MaintenanceMode,
+) -> Result<Month, PaymeError> {
+    if !mode.is_read_only() {
+        return current_month(pool, user_id).await;
+    }
+    let now = Utc::now();
+    sqlx::query_as(
+        "SELECT id, user_id, year, month, is_closed, closed_at FROM months WHERE user_id = ? AND year = ? AND month = ?",
+    )
+    .bind(user_id)
+    .bind(now.year())
+    .bind(now.month() as i32)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(PaymeError::ReadOnly)
+}
+
+/// Returns the user's month, creating it with the category defaults (or the year plan)
+/// as budgets when it doesn't exist yet.
+pub(crate) async fn month_for(
+    pool: &SqlitePool,
+    user_id: i64,
+    year: i32,
+    month: i32,
+) -> Result<Month, PaymeError> {
     let existing: Option<Month> = sqlx::query_as(
         "SELECT id, user_id, year, month, is_closed, closed_at FROM months WHERE user_id = ? AND year = ? AND month = ?",
     )
-    .bind(claims.sub)
+    .bind(user_id)
     .bind(year)
     .bind(month)
-    .fetch_optional(&pool)
+    .fetch_optional(pool)
     .await?;
 
     let month_record = match existing {
         Some(m) => m,
         None => {
+            quotas::check_months(pool, user_id, 1).await?;
             let id: i64 = sqlx::query_scalar(
                 "INSERT INTO months (user_id, year, month) VALUES (?, ?, ?) RETURNING id",
             )
-            .bind(claims.sub)
+            .bind(user_id)
             .bind(year)
             .bind(month)
-            .fetch_one(&pool)
+            .fetch_one(pool)
             .await?;
 
-            let categories: Vec<(i64, f64)> = sqlx::query_as(
-                "SELECT id, default_amount FROM budget_categories WHERE user_id = ?",
+            // A year plan takes precedence over the category default, and a share of
+            // income over a fixed default
+            let categories: Vec<(i64, f64, Option<f64>)> = sqlx::query_as(
+                r#"
+                SELECT bc.id, COALESCE(bp.amount, bc.default_amount),
+                       CASE WHEN bp.amount IS NULL THEN bc.default_percent END
+                FROM budget_categories bc
+                LEFT JOIN budget_plans bp
+                    ON bp.category_id = bc.id AND bp.year = ? AND bp.month = ?
+                WHERE bc.user_id = ? AND bc.system = 0
+                "#,
             )
-            .bind(claims.sub)
-            .fetch_all(&pool)
+            .bind(year)
+            .bind(month)
+            .bind(user_id)
+            .fetch_all(pool)
             .await?;
 
-            for (cat_id, default_amount) in categories {
+            for (cat_id, default_amount, default_percent) in categories {
                 sqlx::query(
-                    "INSERT INTO monthly_budgets (month_id, category_id, allocated_amount) VALUES (?, ?, ?)",
+                    "INSERT INTO monthly_budgets (month_id, category_id, allocated_amount, allocated_percent) VALUES (?, ?, ?, ?)",
                 )
                 .bind(id)
                 .bind(cat_id)
                 .bind(default_amount)
-                .execute(&pool)
+                .bind(default_percent)
+                .execute(pool)
                 .await
                 .ok();
             }
+            apply_percent_allocations(pool, id).await?;
+
+            // Only months opened from now on get the auto-contribution, not past ones
+            let today = Utc::now().date_naive();
+            if (year, month) >= (today.year(), today.month() as i32) {
+                if let Some(first) = NaiveDate::from_ymd_opt(year, month as u32, 1) {
+                    book_auto_contribution(pool, user_id, id, first).await?;
+                }
+            }
 
             Month {
                 id,
-                user_id: claims.sub,
+                user_id,
                 year,
                 month,
                 is_closed: false,
@@ -107,14 +258,15 @@ pub async fn get_or_create_current_month(
         }
     };
 
-    get_month_summary(&pool, claims.sub, month_record.id).await
+    Ok(month_record)
 }
 
 #[utoipa::path(
     get,
-    path = "/api/months/{id}",
+    path = "/api/v1/months/{id}",
     params(
-        ("id" = i64, Path, description = "Month ID")
+        ("id" = i64, Path, description = "Month ID"),
+        SummaryQuery
     ),
     responses(
         (status = 200, description = "Get full summary for a specific month", body = MonthSummary),
@@ -128,6 +280,7 @@ pub async fn get_month(
     State(pool): State<SqlitePool>,
     axum::Extension(claims): axum::Extension<Claims>,
     Path(month_id): Path<i64>,
+    Query(query): Query<SummaryQuery>,
 ) -> Result<Json<MonthSummary>, PaymeError> {
     let month: Month = sqlx::query_as(
         "SELECT id, user_id, year, month, is_closed, closed_at FROM months WHERE id = ? AND user_id = ?",
@@ -138,131 +291,55 @@ pub async fn get_month(
     .await?
     .ok_or(PaymeError::NotFound)?;
 
-    get_month_summary(&pool, claims.sub, month.id).await
+    get_month_summary_with(&pool, claims.sub, month.id, query.exclude_reimbursed).await
 }
 
-async fn get_month_summary(
+pub(crate) async fn get_month_summary(
     pool: &SqlitePool,
     user_id: i64,
     month_id: i64,
 ) -> Result<Json<MonthSummary>, PaymeError> {
-    let month: Month = sqlx::query_as(
-        "SELECT id, user_id, year, month, is_closed, closed_at FROM months WHERE id = ?",
-    )
-    .bind(month_id)
-    .fetch_one(pool)
-    .await?;
-
-    let income_entries: Vec<IncomeEntry> =
-        sqlx::query_as("SELECT id, month_id, label, amount FROM income_entries WHERE month_id = ?")
-            .bind(month_id)
-            .fetch_all(pool)
-            .await?;
-
-    let fixed_expenses: Vec<FixedExpense> =
-        sqlx::query_as("SELECT id, user_id, label, amount FROM fixed_expenses WHERE user_id = ?")
-            .bind(user_id)
-            .fetch_all(pool)
-            .await?;
-
-    let budgets: Vec<MonthlyBudgetWithCategory> =
-        sqlx::query_as::<_, (i64, i64, i64, String, f64)>(
-            r#"
-        SELECT mb.id, mb.month_id, mb.category_id, bc.label, mb.allocated_amount
-        FROM monthly_budgets mb
-        JOIN budget_categories bc ON mb.category_id = bc.id
-        WHERE mb.month_id = ?
-        "#,
-        )
-        .bind(month_id)
-        .fetch_all(pool)
-        .await?
-        .into_iter()
-        .map(
-            |(id, month_id, category_id, category_label, allocated_amount)| {
-                MonthlyBudgetWithCategory {
-                    id,
-                    month_id,
-                    category_id,
-                    category_label,
-                    allocated_amount,
-                    spent_amount: 0.0,
-                }
-            },
-        )
-        .collect();
-
-    let items: Vec<ItemWithCategory> = sqlx::query_as(
-        r#"
-        SELECT i.id, i.month_id, i.category_id, bc.label as category_label, i.description, i.amount, i.spent_on, i.savings_destination
-        FROM items i
-        JOIN budget_categories bc ON i.category_id = bc.id
-        WHERE i.month_id = ?
-        ORDER BY i.spent_on DESC
-        "#,
-    )
-    .bind(month_id)
-    .fetch_all(pool)
-    .await?;
-
-    let budgets: Vec<MonthlyBudgetWithCategory> = budgets
-        .into_iter()
-        .map(|mut b| {
-            b.spent_amount = items
-                .iter()
-                .filter(|i| i.category_id == b.category_id && i.savings_destination == "none")
-                .map(|i| i.amount)
-                .sum();
-            b
-        })
-        .collect();
+    get_month_summary_with(pool, user_id, month_id, false).await
+}
 
-    let total_income: f64 = income_entries.iter().map(|i| i.amount).sum();
-    let total_fixed: f64 = fixed_expenses.iter().map(|e| e.amount).sum();
-    let total_budgeted: f64 = budgets.iter().map(|b| b.allocated_amount).sum();
-    // Only count items as "spent" if they're not being transferred to savings
-    let total_spent: f64 = items
-        .iter()
-        .filter(|i| i.savings_destination == "none")
-        .map(|i| i.amount)
-        .sum();
-    let remaining = total_income - total_fixed - total_spent;
-
-    Ok(Json(MonthSummary {
-        month,
-        income_entries,
-        fixed_expenses,
-        budgets,
-        items,
-        total_income,
-        total_fixed,
-        total_budgeted,
-        total_spent,
-        remaining,
-    }))
+/// Like [`get_month_summary`], leaving reimbursed items out of the spent amounts
+/// when `exclude_reimbursed` is set.
+pub(crate) async fn get_month_summary_with(
+    pool: &SqlitePool,
+    user_id: i64,
+    month_id: i64,
+    exclude_reimbursed: bool,
+) -> Result<Json<MonthSummary>, PaymeError> {
+    Ok(Json(
+        summary::month_summary(pool, user_id, month_id, exclude_reimbursed).await?,
+    ))
 }
 
 #[utoipa::path(
     post,
-    path = "/api/months/{id}/close",
+    path = "/api/v1/months/{id}/close",
     params(
         ("id" = i64, Path, description = "Month ID")
     ),
+    request_body(content = Option<CloseMonth>, description = "Needed when the user has a close checklist"),
     responses(
-        (status = 200, description = "Month closed and PDF snapshot generated", body = Month),
-        (status = 400, description = "Month is already closed"),
+        (status = 200, description = "Month closed; the PDF snapshot is generated in the background", body = CloseMonthResponse),
+        (status = 400, description = "Month is already closed, checklist entries aren't ticked off, or items are still uncategorized"),
         (status = 404, description = "Month not found"),
         (status = 500, description = "Internal server error")
     ),
     tag = "Months",
     summary = "Close month and generate report",
-    description = "Finalizes the month, prevents further edits, and generates a PDF snapshot for long-term storage."
+    description = "Finalizes the month, prevents further edits, and queues a job that generates a PDF snapshot for long-term storage. Poll `/api/v1/jobs/{pdf_job_id}` to know when the PDF is ready. Every entry of the user's close checklist has to be acknowledged unless `force` is set; the checklist is recorded with the month. Months with uncategorized items can't be closed unless the `close_with_uncategorized` setting is on."
 )]
 pub async fn close_month(
     State(pool): State<SqlitePool>,
     axum::Extension(claims): axum::Extension<Claims>,
     Path(month_id): Path<i64>,
-) -> Result<Json<Month>, PaymeError> {
+    headers: HeaderMap,
+    payload: Option<Json<CloseMonth>>,
+) -> Result<Json<CloseMonthResponse>, PaymeError> {
+    let payload = payload.map(|Json(payload)| payload).unwrap_or_default();
     let month: Month = sqlx::query_as(
         "SELECT id, user_id, year, month, is_closed, closed_at FROM months WHERE id = ? AND user_id = ?",
     )
@@ -278,41 +355,216 @@ pub async fn close_month(
         ));
     }
 
-    let summary = get_month_summary(&pool, claims.sub, month_id).await?.0;
-    let pdf_data = pdf::generate_pdf(&summary).map_err(|e| PaymeError::Internal(e.to_string()))?;
+    let checklist = load_checklist(&pool, claims.sub).await?;
+    let skipped: Vec<&str> = checklist
+        .iter()
+        .filter(|entry| !payload.acknowledged.contains(&entry.id))
+        .map(|entry| entry.label.as_str())
+        .collect();
+    if !skipped.is_empty() && !payload.force {
+        return Err(PaymeError::BadRequest(format!(
+            "Tick off the close checklist first: {}",
+            skipped.join(", ")
+        )));
+    }
 
-    sqlx::query("INSERT INTO monthly_snapshots (month_id, pdf_data) VALUES (?, ?)")
+    let settings = load_settings(&pool, claims.sub).await?;
+    if settings.close_with_uncategorized != Some(true) {
+        let uncategorized: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM items i
+            JOIN budget_categories bc ON i.category_id = bc.id
+            WHERE i.month_id = ? AND bc.system = 1
+            "#,
+        )
         .bind(month_id)
-        .bind(&pdf_data)
-        .execute(&pool)
+        .fetch_one(&pool)
         .await?;
+        if uncategorized > 0 {
+            return Err(PaymeError::BadRequest(format!(
+                "Categorize the month's {uncategorized} uncategorized items first"
+            )));
+        }
+    }
+    let locale = i18n::locale_for_request(settings.locale.as_deref(), &headers);
+    let digest = build_digest(&pool, claims.sub, &month).await?;
 
-    let now = Utc::now();
-    sqlx::query("UPDATE months SET is_closed = 1, closed_at = ? WHERE id = ?")
-        .bind(now)
+    let mut tx = pool.begin().await?;
+
+    // Guarded on is_closed so a concurrent close cannot queue a second snapshot
+    let closed = sqlx::query(
+        "UPDATE months SET is_closed = 1, closed_at = ? WHERE id = ? AND is_closed = 0",
+    )
+    .bind(Utc::now())
+    .bind(month_id)
+    .execute(&mut *tx)
+    .await?;
+    if closed.rows_affected() == 0 {
+        return Err(PaymeError::BadRequest(
+            "Month is already closed".to_string(),
+        ));
+    }
+
+    db::freeze_month(&mut tx, month_id).await?;
+
+    for (position, entry) in checklist.iter().enumerate() {
+        sqlx::query(
+            "INSERT INTO closed_month_checklist (month_id, label, position, acknowledged) VALUES (?, ?, ?, ?)",
+        )
         .bind(month_id)
-        .execute(&pool)
+        .bind(&entry.label)
+        .bind(position as i64)
+        .bind(payload.acknowledged.contains(&entry.id))
+        .execute(&mut *tx)
         .await?;
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO wealth_snapshots (user_id, month_id, savings, retirement_savings, created_at)
+        SELECT id, ?, savings, retirement_savings, ? FROM users WHERE id = ?
+        "#,
+    )
+    .bind(month_id)
+    .bind(Utc::now())
+    .bind(claims.sub)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO month_digests (month_id, savings_change, net_worth_change,
+            top_overspent_category, top_overspent_by, biggest_purchase, biggest_purchase_amount,
+            created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(month_id)
+    .bind(digest.savings_change)
+    .bind(digest.net_worth_change)
+    .bind(&digest.top_overspent_category)
+    .bind(digest.top_overspent_by)
+    .bind(&digest.biggest_purchase)
+    .bind(digest.biggest_purchase_amount)
+    .bind(Utc::now())
+    .execute(&mut *tx)
+    .await?;
+
+    let job_id = jobs::enqueue(
+        &mut *tx,
+        claims.sub,
+        jobs::MONTH_PDF,
+        &MonthPdfJob {
+            month_id,
+            locale: locale.tag().to_string(),
+        },
+    )
+    .await?;
+
+    activity::record(
+        &mut *tx,
+        &claims,
+        Some(month_id),
+        "month",
+        month_id,
+        "close",
+        format!("{} closed the month", claims.username),
+    )
+    .await?;
 
     let updated: Month = sqlx::query_as(
         "SELECT id, user_id, year, month, is_closed, closed_at FROM months WHERE id = ?",
     )
     .bind(month_id)
-    .fetch_one(&pool)
+    .fetch_one(&mut *tx)
     .await?;
 
-    Ok(Json(updated))
+    tx.commit().await?;
+    jobs::wake();
+
+    Ok(Json(CloseMonthResponse {
+        month: updated,
+        pdf_job_id: job_id,
+        digest,
+    }))
+}
+
+/// Works out the digest of a month about to be closed, comparing the user's savings
+/// balances with the snapshot taken when the previous month was closed.
+async fn build_digest(
+    pool: &SqlitePool,
+    user_id: i64,
+    month: &Month,
+) -> Result<MonthDigest, PaymeError> {
+    let summary = summary::month_summary(pool, user_id, month.id, false).await?;
+    let wealth: (f64, f64) =
+        sqlx::query_as("SELECT savings, retirement_savings FROM users WHERE id = ?")
+            .bind(user_id)
+            .fetch_one(pool)
+            .await?;
+    let previous: Option<(f64, f64)> = sqlx::query_as(
+        r#"
+        SELECT ws.savings, ws.retirement_savings
+        FROM wealth_snapshots ws
+        JOIN months m ON ws.month_id = m.id
+        WHERE ws.user_id = ? AND m.year * 12 + m.month < ?
+        ORDER BY m.year DESC, m.month DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(user_id)
+    .bind(month.year * 12 + month.month)
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(summary::month_digest(&summary, wealth, previous))
 }
 
 #[utoipa::path(
     get,
-    path = "/api/months/{id}/pdf",
+    path = "/api/v1/months/{id}/digest",
+    params(("id" = i64, Path, description = "Month ID")),
+    responses(
+        (status = 200, body = MonthDigest),
+        (status = 404, description = "Month not found or not closed yet"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Months",
+    summary = "Get month close digest",
+    description = "What changed over a closed month: savings and net worth against the previous closed month, the most overspent category and the biggest purchase. Recorded when the month was closed."
+)]
+pub async fn get_month_digest(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(month_id): Path<i64>,
+) -> Result<Json<MonthDigest>, PaymeError> {
+    let digest: MonthDigest = sqlx::query_as(
+        r#"
+        SELECT d.savings_change, d.net_worth_change, d.top_overspent_category,
+               d.top_overspent_by, d.biggest_purchase, d.biggest_purchase_amount
+        FROM month_digests d
+        JOIN months m ON d.month_id = m.id
+        WHERE d.month_id = ? AND m.user_id = ?
+        "#,
+    )
+    .bind(month_id)
+    .bind(claims.sub)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(PaymeError::NotFound)?;
+
+    Ok(Json(digest))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/months/{id}/pdf",
     params(
         ("id" = i64, Path, description = "Month ID")
     ),
     responses(
         (status = 200, description = "Download the PDF snapshot", content_type = "application/pdf"),
-        (status = 404, description = "PDF snapshot not found for this month")
+        (status = 404, description = "PDF snapshot not found for this month, or not generated yet")
     ),
     tag = "Months",
     summary = "Download month PDF",
@@ -332,18 +584,187 @@ pub async fn get_month_pdf(
     .await?
     .ok_or(PaymeError::NotFound)?;
 
-    let snapshot: (Vec<u8>,) =
-        sqlx::query_as("SELECT pdf_data FROM monthly_snapshots WHERE month_id = ?")
-            .bind(month_id)
-            .fetch_optional(&pool)
-            .await?
-            .ok_or(PaymeError::NotFound)?;
+    let pdf = read_snapshot(&pool, month_id)
+        .await?
+        .ok_or(PaymeError::NotFound)?;
 
     Ok((
         [
             ("Content-Type", "application/pdf"),
             ("Content-Disposition", "attachment; filename=\"month.pdf\""),
+            ("Content-Security-Policy", PDF_CONTENT_SECURITY_POLICY),
         ],
-        snapshot.0,
+        pdf,
     ))
 }
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/months/{id}/pdf/verify",
+    params(("id" = i64, Path, description = "Month ID")),
+    responses(
+        (status = 200, body = PdfVerification),
+        (status = 404, description = "Month not found or has no PDF snapshot"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Months",
+    summary = "Verify month PDF",
+    description = "Recomputes the SHA-256 of the stored PDF snapshot and compares it with the one recorded when the month was closed. Compare `sha256` with the hash of a downloaded copy to show it is the archived report."
+)]
+pub async fn verify_month_pdf(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(month_id): Path<i64>,
+) -> Result<Json<PdfVerification>, PaymeError> {
+    let _month: (i64,) = sqlx::query_as("SELECT id FROM months WHERE id = ? AND user_id = ?")
+        .bind(month_id)
+        .bind(claims.sub)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or(PaymeError::NotFound)?;
+
+    let snapshot = load_snapshot(&pool, month_id)
+        .await?
+        .ok_or(PaymeError::NotFound)?;
+
+    Ok(Json(PdfVerification {
+        month_id,
+        valid: snapshot.sha256 == snapshot.computed_sha256,
+        sha256: snapshot.sha256,
+        computed_sha256: snapshot.computed_sha256,
+        created_at: snapshot.created_at,
+    }))
+}
+
+/// The stored PDF snapshot of a month, checked against the hash recorded for it.
+pub(crate) async fn read_snapshot(
+    pool: &SqlitePool,
+    month_id: i64,
+) -> Result<Option<Vec<u8>>, PaymeError> {
+    let Some(snapshot) = load_snapshot(pool, month_id).await? else {
+        return Ok(None);
+    };
+    if snapshot.sha256 != snapshot.computed_sha256 {
+        tracing::error!(
+            "PDF snapshot of month {month_id} does not match its hash {}",
+            snapshot.sha256
+        );
+        return Err(PaymeError::Internal(format!(
+            "PDF snapshot of month {month_id} failed its integrity check"
+        )));
+    }
+
+    Ok(Some(snapshot.pdf))
+}
+
+/// Reads a month's PDF snapshot and hashes it. Snapshots from before hashes were kept
+/// get theirs recorded on first read.
+async fn load_snapshot(
+    pool: &SqlitePool,
+    month_id: i64,
+) -> Result<Option<StoredSnapshot>, PaymeError> {
+    let snapshot: Option<(String, Option<String>, NaiveDateTime)> = sqlx::query_as(
+        "SELECT storage_key, sha256, created_at FROM monthly_snapshots WHERE month_id = ?",
+    )
+    .bind(month_id)
+    .fetch_optional(pool)
+    .await?;
+    let Some((key, sha256, created_at)) = snapshot else {
+        return Ok(None);
+    };
+
+    let pdf = storage::read_sealed(pool, storage::backend(pool).as_ref(), &key).await?;
+    let computed_sha256 = storage::content_hash(&pdf);
+    let sha256 = match sha256 {
+        Some(sha256) => sha256,
+        None => {
+            sqlx::query("UPDATE monthly_snapshots SET sha256 = ? WHERE month_id = ?")
+                .bind(&computed_sha256)
+                .bind(month_id)
+                .execute(pool)
+                .await?;
+            computed_sha256.clone()
+        }
+    };
+
+    Ok(Some(StoredSnapshot {
+        pdf,
+        sha256,
+        computed_sha256,
+        created_at,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/months/{id}/activity",
+    params(
+        ("id" = i64, Path, description = "Month ID"),
+        ActivityQuery
+    ),
+    responses(
+        (status = 200, body = ActivityPage),
+        (status = 404, description = "Month not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Months",
+    summary = "Get month activity feed",
+    description = "Returns a chronological, paginated feed of changes made to the month, newest first, with human-readable summaries."
+)]
+pub async fn list_month_activity(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(month_id): Path<i64>,
+    Query(query): Query<ActivityQuery>,
+) -> Result<Json<ActivityPage>, PaymeError> {
+    query.validate()?;
+    let _month: (i64,) = sqlx::query_as("SELECT id FROM months WHERE id = ? AND user_id = ?")
+        .bind(month_id)
+        .bind(claims.sub)
+        .fetch_optional(&pool)
+        .await?
+        .ok_or(PaymeError::NotFound)?;
+
+    let page = query.page.unwrap_or(1);
+    let per_page = query.per_page.unwrap_or(50);
+
+    let total: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COUNT(*) FROM activity_log
+        WHERE month_id = ? AND (? IS NULL OR actor = ?) AND (? IS NULL OR entity_type = ?)
+        "#,
+    )
+    .bind(month_id)
+    .bind(&query.actor)
+    .bind(&query.actor)
+    .bind(&query.entity_type)
+    .bind(&query.entity_type)
+    .fetch_one(&pool)
+    .await?;
+
+    let entries: Vec<ActivityEntry> = sqlx::query_as(
+        r#"
+        SELECT id, month_id, actor, entity_type, entity_id, action, summary, created_at
+        FROM activity_log
+        WHERE month_id = ? AND (? IS NULL OR actor = ?) AND (? IS NULL OR entity_type = ?)
+        ORDER BY created_at DESC, id DESC
+        LIMIT ? OFFSET ?
+        "#,
+    )
+    .bind(month_id)
+    .bind(&query.actor)
+    .bind(&query.actor)
+    .bind(&query.entity_type)
+    .bind(&query.entity_type)
+    .bind(per_page)
+    .bind((page - 1) * per_page)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(ActivityPage {
+        entries,
+        page,
+        per_page,
+        total,
+    }))
+}
@@ -0,0 +1,160 @@
+use axum::extract::State;
+use chrono::{Datelike, Utc};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::handlers::budget::CreateCategory;
+use crate::handlers::fixed_expenses::{billing_schedule, CreateFixedExpense};
+use crate::handlers::income::CreateIncome;
+use crate::handlers::months::get_month_summary;
+use crate::middleware::auth::Claims;
+use crate::models::MonthSummary;
+
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct OnboardingRequest {
+    /// Replaces the starter categories when present.
+    #[validate(nested)]
+    pub categories: Option<Vec<CreateCategory>>,
+    /// Replaces the starter fixed expenses when present.
+    #[validate(nested)]
+    pub fixed_expenses: Option<Vec<CreateFixedExpense>>,
+    /// Income entries for the first month.
+    #[validate(nested)]
+    #[serde(default)]
+    pub income: Vec<CreateIncome>,
+    #[validate(range(min = 0.0), custom(function = "crate::money::amount"))]
+    pub savings: Option<f64>,
+    #[validate(range(min = 0.0), custom(function = "crate::money::amount"))]
+    pub savings_goal: Option<f64>,
+    #[validate(range(min = 0.0), custom(function = "crate::money::amount"))]
+    pub retirement_savings: Option<f64>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/onboarding",
+    request_body = OnboardingRequest,
+    responses(
+        (status = 200, description = "Account set up; returns the first month", body = MonthSummary),
+        (status = 400, description = "Invalid input or account already has months"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Configuration",
+    summary = "Set up a new account",
+    description = "Saves categories, fixed expenses, income and starting savings, then creates the current month, all in one transaction. Only allowed before the first month exists."
+)]
+pub async fn complete_onboarding(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Json(payload): Json<OnboardingRequest>,
+) -> Result<Json<MonthSummary>, PaymeError> {
+    payload.validate()?;
+
+    let mut tx = pool.begin().await?;
+
+    let month_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM months WHERE user_id = ?")
+        .bind(claims.sub)
+        .fetch_one(&mut *tx)
+        .await?;
+    if month_count > 0 {
+        return Err(PaymeError::BadRequest(
+            "Account is already set up".to_string(),
+        ));
+    }
+
+    if let Some(categories) = &payload.categories {
+        sqlx::query("DELETE FROM budget_categories WHERE user_id = ?")
+            .bind(claims.sub)
+            .execute(&mut *tx)
+            .await?;
+        for category in categories {
+            sqlx::query(
+                "INSERT INTO budget_categories (user_id, label, default_amount) VALUES (?, ?, ?)",
+            )
+            .bind(claims.sub)
+            .bind(&category.label)
+            .bind(category.default_amount)
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+
+    if let Some(fixed_expenses) = &payload.fixed_expenses {
+        sqlx::query("DELETE FROM fixed_expenses WHERE user_id = ?")
+            .bind(claims.sub)
+            .execute(&mut *tx)
+            .await?;
+        for expense in fixed_expenses {
+            let payment_month = billing_schedule(&expense.billing_period, expense.payment_month)?;
+            sqlx::query(
+                "INSERT INTO fixed_expenses (user_id, label, amount, billing_period, payment_month) VALUES (?, ?, ?, ?, ?)",
+            )
+            .bind(claims.sub)
+            .bind(&expense.label)
+            .bind(expense.amount)
+            .bind(&expense.billing_period)
+            .bind(payment_month)
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE users SET
+            savings = COALESCE(?, savings),
+            savings_goal = COALESCE(?, savings_goal),
+            retirement_savings = COALESCE(?, retirement_savings)
+        WHERE id = ?
+        "#,
+    )
+    .bind(payload.savings)
+    .bind(payload.savings_goal)
+    .bind(payload.retirement_savings)
+    .bind(claims.sub)
+    .execute(&mut *tx)
+    .await?;
+
+    let now = Utc::now();
+    let month_id: i64 = sqlx::query_scalar(
+        "INSERT INTO months (user_id, year, month) VALUES (?, ?, ?) RETURNING id",
+    )
+    .bind(claims.sub)
+    .bind(now.year())
+    .bind(now.month() as i32)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO monthly_budgets (month_id, category_id, allocated_amount)
+        SELECT ?, bc.id, COALESCE(bp.amount, bc.default_amount)
+        FROM budget_categories bc
+        LEFT JOIN budget_plans bp ON bp.category_id = bc.id AND bp.year = ? AND bp.month = ?
+        WHERE bc.user_id = ?
+        "#,
+    )
+    .bind(month_id)
+    .bind(now.year())
+    .bind(now.month() as i32)
+    .bind(claims.sub)
+    .execute(&mut *tx)
+    .await?;
+
+    for entry in &payload.income {
+        sqlx::query("INSERT INTO income_entries (month_id, label, amount) VALUES (?, ?, ?)")
+            .bind(month_id)
+            .bind(&entry.label)
+            .bind(entry.amount)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+
+    get_month_summary(&pool, claims.sub, month_id).await
+}
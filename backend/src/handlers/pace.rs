@@ -0,0 +1,179 @@
+use axum::extract::{Path, State};
+use chrono::{Datelike, NaiveDate, Utc};
+use sqlx::SqlitePool;
+
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::middleware::auth::Claims;
+use crate::models::{CategoryPace, DailySpend, MonthPace};
+use crate::summary::days_elapsed;
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/months/{id}/pace",
+    params(("id" = i64, Path, description = "Month ID")),
+    responses(
+        (status = 200, body = MonthPace),
+        (status = 404, description = "Month not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Budgets",
+    summary = "Get budget pace",
+    description = "Compares each category's spending so far with what an even spread of its budget would allow by today, and estimates how many days the rest of the budget lasts at the current daily rate. Savings transfers don't count as spending."
+)]
+pub async fn get_month_pace(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(month_id): Path<i64>,
+) -> Result<Json<MonthPace>, PaymeError> {
+    let month: Option<(i32, i32, bool)> = sqlx::query_as(
+        "SELECT year, month, frozen_at IS NOT NULL FROM months WHERE id = ? AND user_id = ?",
+    )
+    .bind(month_id)
+    .bind(claims.sub)
+    .fetch_optional(&pool)
+    .await?;
+    let (year, month, frozen) = month.ok_or(PaymeError::NotFound)?;
+
+    // Closed months are measured against the copies taken at close time
+    let (budgets_sql, spending_sql) = if frozen {
+        (
+            r#"
+            SELECT category_id, category_label, allocated_amount FROM closed_month_budgets
+            WHERE month_id = ? AND tracking_only = 0
+            ORDER BY category_label
+            "#,
+            r#"
+            SELECT category_id, spent_on, SUM(amount)
+            FROM closed_month_items
+            WHERE month_id = ? AND savings_destination = 'none' AND tracking_only = 0
+            GROUP BY category_id, spent_on
+            ORDER BY spent_on
+            "#,
+        )
+    } else {
+        (
+            r#"
+            SELECT mb.category_id, bc.label, mb.allocated_amount
+            FROM monthly_budgets mb
+            JOIN budget_categories bc ON mb.category_id = bc.id
+            WHERE mb.month_id = ? AND bc.tracking_only = 0
+            ORDER BY bc.label
+            "#,
+            r#"
+            SELECT category_id, spent_on, SUM(amount)
+            FROM items
+            WHERE month_id = ? AND savings_destination = 'none'
+              AND category_id NOT IN (SELECT id FROM budget_categories WHERE tracking_only = 1)
+            GROUP BY category_id, spent_on
+            ORDER BY spent_on
+            "#,
+        )
+    };
+    let budgets: Vec<(i64, String, f64)> = sqlx::query_as(budgets_sql)
+        .bind(month_id)
+        .fetch_all(&pool)
+        .await?;
+    let spending: Vec<(i64, NaiveDate, f64)> = sqlx::query_as(spending_sql)
+        .bind(month_id)
+        .fetch_all(&pool)
+        .await?;
+
+    let days_in_month = days_in_month(year, month);
+    let elapsed = days_elapsed(year, month, Utc::now().date_naive()).clamp(0, days_in_month);
+
+    let categories = budgets
+        .into_iter()
+        .map(|(category_id, category_label, allocated_amount)| {
+            let daily: Vec<DailySpend> = spending
+                .iter()
+                .filter(|(id, _, _)| *id == category_id)
+                .map(|(_, date, amount)| DailySpend {
+                    date: *date,
+                    amount: *amount,
+                })
+                .collect();
+            category_pace(
+                category_id,
+                category_label,
+                allocated_amount,
+                daily,
+                elapsed,
+                days_in_month,
+            )
+        })
+        .collect();
+
+    Ok(Json(MonthPace {
+        month_id,
+        days_in_month,
+        days_elapsed: elapsed,
+        categories,
+    }))
+}
+
+fn category_pace(
+    category_id: i64,
+    category_label: String,
+    allocated_amount: f64,
+    daily: Vec<DailySpend>,
+    elapsed: i64,
+    days_in_month: i64,
+) -> CategoryPace {
+    let spent_amount: f64 = daily.iter().map(|day| day.amount).sum();
+    let expected_amount = allocated_amount * elapsed as f64 / days_in_month.max(1) as f64;
+    let daily_rate = spent_amount / elapsed.max(1) as f64;
+    let available = allocated_amount - spent_amount;
+    let days_left = (daily_rate > 0.0).then(|| (available / daily_rate).max(0.0));
+
+    CategoryPace {
+        category_id,
+        category_label,
+        allocated_amount,
+        spent_amount,
+        expected_amount,
+        pace: (expected_amount > 0.0).then(|| spent_amount / expected_amount),
+        daily_rate,
+        days_left,
+        daily,
+    }
+}
+
+pub(crate) fn days_in_month(year: i32, month: i32) -> i64 {
+    NaiveDate::from_ymd_opt(year, month as u32, 1)
+        .and_then(|first| first.checked_add_months(chrono::Months::new(1)))
+        .and_then(|next| next.pred_opt())
+        .map_or(30, |last| last.day() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_days_in_month() {
+        assert_eq!(days_in_month(2024, 2), 29);
+        assert_eq!(days_in_month(2023, 2), 28);
+        assert_eq!(days_in_month(2024, 12), 31);
+    }
+
+    #[test]
+    fn test_category_pace() {
+        let daily = vec![DailySpend {
+            date: NaiveDate::from_ymd_opt(2024, 6, 2).unwrap(),
+            amount: 200.0,
+        }];
+        let pace = category_pace(1, "Food".to_string(), 600.0, daily, 10, 30);
+        assert_eq!(pace.expected_amount, 200.0);
+        assert_eq!(pace.pace, Some(1.0));
+        assert_eq!(pace.daily_rate, 20.0);
+        assert_eq!(pace.days_left, Some(20.0));
+    }
+
+    #[test]
+    fn test_category_pace_without_spending() {
+        let pace = category_pace(1, "Food".to_string(), 600.0, Vec::new(), 0, 30);
+        assert_eq!(pace.pace, None);
+        assert_eq!(pace.days_left, None);
+    }
+}
@@ -0,0 +1,221 @@
+use axum::extract::{Path, State};
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use utoipa::ToSchema;
+use validator::{Validate, ValidationError};
+
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::handlers::months::{get_month_summary, month_for};
+use crate::middleware::auth::Claims;
+use crate::middleware::maintenance::MaintenanceMode;
+use crate::models::{BudgetPeriodSettings, Month, PeriodSummary};
+use crate::periods::{self, PeriodType};
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdatePeriodSettings {
+    /// `monthly`, `weekly` or `biweekly`.
+    #[validate(custom(function = "period_type"))]
+    pub period_type: String,
+    /// First day of one weekly or biweekly period. Defaults to this week's Monday.
+    pub starts_on: Option<NaiveDate>,
+}
+
+fn period_type(period_type: &str) -> Result<(), ValidationError> {
+    PeriodType::parse(period_type)
+        .map(|_| ())
+        .ok_or_else(|| ValidationError::new("period_type"))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/periods/settings",
+    responses(
+        (status = 200, body = BudgetPeriodSettings),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Months",
+    summary = "Get the budget period",
+    description = "Returns whether the user budgets by the month, the week or the fortnight."
+)]
+pub async fn get_period_settings(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+) -> Result<Json<BudgetPeriodSettings>, PaymeError> {
+    Ok(Json(load_period_settings(&pool, claims.sub).await?))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/periods/settings",
+    request_body = UpdatePeriodSettings,
+    responses(
+        (status = 200, body = BudgetPeriodSettings),
+        (status = 400, description = "Unknown period type"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Months",
+    summary = "Set the budget period",
+    description = "Switches between monthly, weekly and biweekly budgets. Months keep holding the budgets and entries, so switching back and forth loses nothing."
+)]
+pub async fn update_period_settings(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Json(payload): Json<UpdatePeriodSettings>,
+) -> Result<Json<BudgetPeriodSettings>, PaymeError> {
+    payload.validate()?;
+    let starts_on = match payload.period_type.as_str() {
+        "monthly" => None,
+        _ => Some(payload.starts_on.unwrap_or_else(|| {
+            let today = Utc::now().date_naive();
+            today - Duration::days(today.weekday().num_days_from_monday() as i64)
+        })),
+    };
+
+    sqlx::query("UPDATE users SET period_type = ?, period_starts_on = ? WHERE id = ?")
+        .bind(&payload.period_type)
+        .bind(starts_on)
+        .bind(claims.sub)
+        .execute(&pool)
+        .await?;
+
+    Ok(Json(BudgetPeriodSettings {
+        period_type: payload.period_type,
+        starts_on,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/periods/current",
+    responses(
+        (status = 200, body = PeriodSummary),
+        (status = 500, description = "Internal server error"),
+        (status = 503, description = "A month of the period doesn't exist yet and the API is read-only")
+    ),
+    tag = "Months",
+    summary = "Get the current period",
+    description = "Summarizes the budget period today falls in, creating the months it overlaps like the current month is. Income, fixed expenses and allocations are each month's share for the days of the period; spending is the items dated inside it."
+)]
+pub async fn get_current_period(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    axum::Extension(mode): axum::Extension<MaintenanceMode>,
+) -> Result<Json<PeriodSummary>, PaymeError> {
+    let settings = load_period_settings(&pool, claims.sub).await?;
+    let (period_type, start, end) = period_of(&settings, Utc::now().date_naive());
+
+    let mut months = Vec::new();
+    for (year, month) in periods::months_spanned(start, end) {
+        let month = if mode.is_read_only() {
+            find_month(&pool, claims.sub, year, month)
+                .await?
+                .ok_or(PaymeError::ReadOnly)?
+        } else {
+            month_for(&pool, claims.sub, year, month).await?
+        };
+        months.push(month);
+    }
+
+    summarize(&pool, claims.sub, period_type, start, end, months).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/periods/{date}",
+    params(("date" = NaiveDate, Path, description = "Any day of the period, e.g. 2024-06-13")),
+    responses(
+        (status = 200, body = PeriodSummary),
+        (status = 400, description = "Not a date"),
+        (status = 404, description = "None of the months the period overlaps exist"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Months",
+    summary = "Get a period",
+    description = "Summarizes the budget period the date falls in, from the months it overlaps that exist."
+)]
+pub async fn get_period(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(date): Path<NaiveDate>,
+) -> Result<Json<PeriodSummary>, PaymeError> {
+    let settings = load_period_settings(&pool, claims.sub).await?;
+    let (period_type, start, end) = period_of(&settings, date);
+
+    let mut months = Vec::new();
+    for (year, month) in periods::months_spanned(start, end) {
+        if let Some(month) = find_month(&pool, claims.sub, year, month).await? {
+            months.push(month);
+        }
+    }
+    if months.is_empty() {
+        return Err(PaymeError::NotFound);
+    }
+
+    summarize(&pool, claims.sub, period_type, start, end, months).await
+}
+
+async fn load_period_settings(
+    pool: &SqlitePool,
+    user_id: i64,
+) -> Result<BudgetPeriodSettings, PaymeError> {
+    let (period_type, starts_on): (String, Option<NaiveDate>) =
+        sqlx::query_as("SELECT period_type, period_starts_on FROM users WHERE id = ?")
+            .bind(user_id)
+            .fetch_one(pool)
+            .await?;
+    Ok(BudgetPeriodSettings {
+        period_type,
+        starts_on,
+    })
+}
+
+/// The user's period type with the first and last day of the period `date` is in.
+fn period_of(
+    settings: &BudgetPeriodSettings,
+    date: NaiveDate,
+) -> (PeriodType, NaiveDate, NaiveDate) {
+    let period_type = PeriodType::parse(&settings.period_type).unwrap_or(PeriodType::Monthly);
+    let (start, end) =
+        periods::period_containing(period_type, settings.starts_on.unwrap_or(date), date);
+    (period_type, start, end)
+}
+
+async fn find_month(
+    pool: &SqlitePool,
+    user_id: i64,
+    year: i32,
+    month: i32,
+) -> Result<Option<Month>, PaymeError> {
+    Ok(sqlx::query_as(
+        "SELECT id, user_id, year, month, is_closed, closed_at FROM months WHERE user_id = ? AND year = ? AND month = ?",
+    )
+    .bind(user_id)
+    .bind(year)
+    .bind(month)
+    .fetch_optional(pool)
+    .await?)
+}
+
+async fn summarize(
+    pool: &SqlitePool,
+    user_id: i64,
+    period_type: PeriodType,
+    start: NaiveDate,
+    end: NaiveDate,
+    months: Vec<Month>,
+) -> Result<Json<PeriodSummary>, PaymeError> {
+    let mut summaries = Vec::with_capacity(months.len());
+    for month in months {
+        let Json(summary) = get_month_summary(pool, user_id, month.id).await?;
+        summaries.push(summary);
+    }
+    Ok(Json(periods::period_summary(
+        period_type,
+        start,
+        end,
+        &summaries,
+    )))
+}
@@ -0,0 +1,153 @@
+use axum::extract::{Path, State};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use utoipa::ToSchema;
+use validator::{Validate, ValidationError};
+
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::handlers::analytics::load_seasonality;
+use crate::middleware::auth::Claims;
+use crate::models::{CategoryPlan, YearPlan};
+
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct SetYearPlan {
+    #[validate(nested)]
+    pub categories: Vec<PlannedCategory>,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct PlannedCategory {
+    pub category_id: i64,
+    /// Allocation for each month, January first.
+    #[validate(length(equal = 12), custom(function = "non_negative"))]
+    pub amounts: Vec<f64>,
+}
+
+fn non_negative(amounts: &[f64]) -> Result<(), ValidationError> {
+    if amounts.iter().all(|a| *a >= 0.0) {
+        Ok(())
+    } else {
+        Err(ValidationError::new("range"))
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/plans/{year}",
+    params(("year" = i32, Path, description = "Year")),
+    responses(
+        (status = 200, body = YearPlan),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Budgets",
+    summary = "Get a year plan",
+    description = "Returns the monthly allocations planned for each category in the year, with the spending expected each month for seasonal categories. Categories without a plan are left out."
+)]
+pub async fn get_plan(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(year): Path<i32>,
+) -> Result<Json<YearPlan>, PaymeError> {
+    load_plan(&pool, claims.sub, year).await.map(Json)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/plans/{year}",
+    params(("year" = i32, Path, description = "Year")),
+    request_body = SetYearPlan,
+    responses(
+        (status = 200, body = YearPlan),
+        (status = 400, description = "Invalid amounts"),
+        (status = 404, description = "Category not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Budgets",
+    summary = "Plan a year",
+    description = "Replaces the year's plan with twelve monthly allocations per category, e.g. more for utilities in winter or gifts in December. Months created later in that year start from the planned amounts instead of each category's default. Existing months are not changed."
+)]
+pub async fn set_plan(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(year): Path<i32>,
+    Json(payload): Json<SetYearPlan>,
+) -> Result<Json<YearPlan>, PaymeError> {
+    payload.validate()?;
+
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("DELETE FROM budget_plans WHERE user_id = ? AND year = ?")
+        .bind(claims.sub)
+        .bind(year)
+        .execute(&mut *tx)
+        .await?;
+
+    for planned in &payload.categories {
+        let owned: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM budget_categories WHERE id = ? AND user_id = ?)",
+        )
+        .bind(planned.category_id)
+        .bind(claims.sub)
+        .fetch_one(&mut *tx)
+        .await?;
+        if !owned {
+            return Err(PaymeError::NotFound);
+        }
+
+        for (month, amount) in (1..).zip(&planned.amounts) {
+            sqlx::query(
+                r#"
+                INSERT INTO budget_plans (user_id, category_id, year, month, amount)
+                VALUES (?, ?, ?, ?, ?)
+                ON CONFLICT(category_id, year, month) DO UPDATE SET amount = excluded.amount
+                "#,
+            )
+            .bind(claims.sub)
+            .bind(planned.category_id)
+            .bind(year)
+            .bind(month)
+            .bind(amount)
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+
+    tx.commit().await?;
+
+    load_plan(&pool, claims.sub, year).await.map(Json)
+}
+
+async fn load_plan(pool: &SqlitePool, user_id: i64, year: i32) -> Result<YearPlan, PaymeError> {
+    let rows: Vec<(i64, String, i32, f64)> = sqlx::query_as(
+        r#"
+        SELECT bp.category_id, bc.label, bp.month, bp.amount
+        FROM budget_plans bp
+        JOIN budget_categories bc ON bp.category_id = bc.id
+        WHERE bp.user_id = ? AND bp.year = ?
+        ORDER BY bc.label, bp.category_id, bp.month
+        "#,
+    )
+    .bind(user_id)
+    .bind(year)
+    .fetch_all(pool)
+    .await?;
+
+    let mut seasonality = load_seasonality(pool, user_id).await?;
+    let mut categories: Vec<CategoryPlan> = Vec::new();
+    for (category_id, category_label, month, amount) in rows {
+        if categories.last().map(|c| c.category_id) != Some(category_id) {
+            categories.push(CategoryPlan {
+                category_id,
+                category_label,
+                amounts: vec![0.0; 12],
+                seasonal: seasonality.remove(&category_id),
+            });
+        }
+        if let Some(plan) = categories.last_mut() {
+            plan.amounts[(month - 1) as usize] = amount;
+        }
+    }
+
+    Ok(YearPlan { year, categories })
+}
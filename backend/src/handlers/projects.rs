@@ -0,0 +1,302 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::activity;
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::middleware::auth::Claims;
+use crate::models::{ItemWithCategory, Project, ProjectMonth, ProjectSummary};
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateProject {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    #[validate(range(min = 0.0), custom(function = "crate::money::amount"))]
+    pub budget: f64,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateProject {
+    #[validate(length(min = 1, max = 100))]
+    pub name: Option<String>,
+    #[validate(range(min = 0.0), custom(function = "crate::money::amount"))]
+    pub budget: Option<f64>,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct LinkItemProject {
+    /// Project to count the item against, or null to unlink it.
+    pub project_id: Option<i64>,
+}
+
+const SELECT_PROJECT: &str = r#"
+    SELECT p.id, p.name, p.budget,
+           COALESCE((SELECT SUM(i.amount) FROM items i WHERE i.project_id = p.id), 0.0) AS spent,
+           p.created_at
+    FROM projects p
+"#;
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/projects",
+    responses(
+        (status = 200, body = [Project]),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Projects",
+    summary = "List projects",
+    description = "Lists projects, newest first, with what has been spent on each so far."
+)]
+pub async fn list_projects(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+) -> Result<Json<Vec<Project>>, PaymeError> {
+    let projects: Vec<Project> = sqlx::query_as(&format!(
+        "{SELECT_PROJECT} WHERE p.user_id = ? ORDER BY p.created_at DESC, p.id DESC"
+    ))
+    .bind(claims.sub)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(projects))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/projects",
+    request_body = CreateProject,
+    responses(
+        (status = 200, body = Project),
+        (status = 400, description = "Invalid project"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Projects",
+    summary = "Create a project",
+    description = "Creates a project with its own budget, e.g. a renovation or a trip, that items from any month can be linked to."
+)]
+pub async fn create_project(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Json(payload): Json<CreateProject>,
+) -> Result<Json<Project>, PaymeError> {
+    payload.validate()?;
+
+    let id: i64 = sqlx::query_scalar(
+        "INSERT INTO projects (user_id, name, budget, created_at) VALUES (?, ?, ?, ?) RETURNING id",
+    )
+    .bind(claims.sub)
+    .bind(payload.name.trim())
+    .bind(payload.budget)
+    .bind(Utc::now())
+    .fetch_one(&pool)
+    .await?;
+
+    Ok(Json(fetch_project(&pool, claims.sub, id).await?))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/projects/{id}",
+    params(("id" = i64, Path, description = "Project ID")),
+    request_body = UpdateProject,
+    responses(
+        (status = 200, body = Project),
+        (status = 404, description = "Project not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Projects",
+    summary = "Update a project",
+    description = "Renames a project or changes its budget."
+)]
+pub async fn update_project(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(project_id): Path<i64>,
+    Json(payload): Json<UpdateProject>,
+) -> Result<Json<Project>, PaymeError> {
+    payload.validate()?;
+    let existing = fetch_project(&pool, claims.sub, project_id).await?;
+
+    sqlx::query("UPDATE projects SET name = ?, budget = ? WHERE id = ?")
+        .bind(
+            payload
+                .name
+                .map(|name| name.trim().to_string())
+                .unwrap_or(existing.name),
+        )
+        .bind(payload.budget.unwrap_or(existing.budget))
+        .bind(project_id)
+        .execute(&pool)
+        .await?;
+
+    Ok(Json(fetch_project(&pool, claims.sub, project_id).await?))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/projects/{id}",
+    params(("id" = i64, Path, description = "Project ID")),
+    responses((status = 204, description = "Deleted")),
+    tag = "Projects",
+    summary = "Delete a project",
+    description = "Deletes a project. Its items stay in their months, unlinked."
+)]
+pub async fn delete_project(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(project_id): Path<i64>,
+) -> Result<StatusCode, PaymeError> {
+    sqlx::query("DELETE FROM projects WHERE id = ? AND user_id = ?")
+        .bind(project_id)
+        .bind(claims.sub)
+        .execute(&pool)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/projects/{id}/summary",
+    params(("id" = i64, Path, description = "Project ID")),
+    responses(
+        (status = 200, body = ProjectSummary),
+        (status = 404, description = "Project not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Projects",
+    summary = "Project spending",
+    description = "Adds up the items linked to a project across all months and compares them with the project budget."
+)]
+pub async fn get_project_summary(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(project_id): Path<i64>,
+) -> Result<Json<ProjectSummary>, PaymeError> {
+    let project = fetch_project(&pool, claims.sub, project_id).await?;
+
+    let months: Vec<ProjectMonth> = sqlx::query_as(
+        r#"
+        SELECT m.id AS month_id, m.year, m.month, SUM(i.amount) AS spent
+        FROM items i
+        JOIN months m ON i.month_id = m.id
+        WHERE i.project_id = ?
+        GROUP BY m.id
+        ORDER BY m.year, m.month
+        "#,
+    )
+    .bind(project_id)
+    .fetch_all(&pool)
+    .await?;
+
+    let items: Vec<ItemWithCategory> = sqlx::query_as(
+        r#"
+        SELECT i.id, i.month_id, i.category_id, bc.label as category_label, i.description, i.amount, i.spent_on, i.savings_destination, i.reimbursement_status, i.tax_rate, i.tax_amount, i.paid_in_cash, i.uuid, i.client, i.billable
+        FROM items i
+        JOIN budget_categories bc ON i.category_id = bc.id
+        WHERE i.project_id = ?
+        ORDER BY i.spent_on, i.id
+        "#,
+    )
+    .bind(project_id)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(ProjectSummary {
+        remaining: project.budget - project.spent,
+        project,
+        months,
+        items,
+    }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/months/{month_id}/items/{id}/project",
+    params(
+        ("month_id" = i64, Path, description = "Month ID"),
+        ("id" = i64, Path, description = "Item (Transaction) ID")
+    ),
+    request_body = LinkItemProject,
+    responses(
+        (status = 204, description = "Linked"),
+        (status = 404, description = "Item or project not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Projects",
+    summary = "Link an item to a project",
+    description = "Counts an item against a project, or unlinks it. Allowed on closed months, since it doesn't change the month's totals."
+)]
+pub async fn link_item(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path((month_id, item_id)): Path<(i64, i64)>,
+    Json(payload): Json<LinkItemProject>,
+) -> Result<StatusCode, PaymeError> {
+    let project = match payload.project_id {
+        Some(project_id) => Some(fetch_project(&pool, claims.sub, project_id).await?),
+        None => None,
+    };
+
+    let description: String = sqlx::query_scalar(
+        r#"
+        UPDATE items SET project_id = ?
+        WHERE id = ? AND month_id = ? AND month_id IN (SELECT id FROM months WHERE user_id = ?)
+        RETURNING description
+        "#,
+    )
+    .bind(payload.project_id)
+    .bind(item_id)
+    .bind(month_id)
+    .bind(claims.sub)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(PaymeError::NotFound)?;
+
+    let summary = match &project {
+        Some(project) => format!(
+            "{} linked {} to {}",
+            claims.username, description, project.name
+        ),
+        None => format!(
+            "{} unlinked {} from its project",
+            claims.username, description
+        ),
+    };
+    activity::record(
+        &pool,
+        &claims,
+        Some(month_id),
+        "item",
+        item_id,
+        "project",
+        summary,
+    )
+    .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn fetch_project(
+    pool: &SqlitePool,
+    user_id: i64,
+    project_id: i64,
+) -> Result<Project, PaymeError> {
+    sqlx::query_as(&format!(
+        "{SELECT_PROJECT} WHERE p.id = ? AND p.user_id = ?"
+    ))
+    .bind(project_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(PaymeError::NotFound)
+}
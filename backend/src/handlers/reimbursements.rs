@@ -0,0 +1,54 @@
+use axum::extract::State;
+use sqlx::SqlitePool;
+
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::middleware::auth::Claims;
+use crate::models::{ItemWithCategory, ReimbursementsReport};
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/reimbursements",
+    responses(
+        (status = 200, body = ReimbursementsReport),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Items",
+    summary = "Outstanding reimbursements",
+    description = "Lists reimbursable items across all months that are still `pending` or `submitted`, with totals per status."
+)]
+pub async fn list_reimbursements(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+) -> Result<Json<ReimbursementsReport>, PaymeError> {
+    let items: Vec<ItemWithCategory> = sqlx::query_as(
+        r#"
+        SELECT i.id, i.month_id, i.category_id, bc.label as category_label, i.description, i.amount, i.spent_on, i.savings_destination, i.reimbursement_status, i.tax_rate, i.tax_amount, i.paid_in_cash, i.uuid, i.client, i.billable
+        FROM items i
+        JOIN budget_categories bc ON i.category_id = bc.id
+        JOIN months m ON i.month_id = m.id
+        WHERE m.user_id = ? AND i.reimbursement_status IN ('pending', 'submitted')
+        ORDER BY i.spent_on, i.id
+        "#,
+    )
+    .bind(claims.sub)
+    .fetch_all(&pool)
+    .await?;
+
+    let total = |status: &str| {
+        items
+            .iter()
+            .filter(|i| i.reimbursement_status.as_deref() == Some(status))
+            .map(|i| i.amount)
+            .sum::<f64>()
+    };
+    let total_pending = total("pending");
+    let total_submitted = total("submitted");
+
+    Ok(Json(ReimbursementsReport {
+        items,
+        total_pending,
+        total_submitted,
+        total_outstanding: total_pending + total_submitted,
+    }))
+}
@@ -0,0 +1,405 @@
+use axum::{
+    extract::{Path, State},
+    http::{
+        header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+        StatusCode,
+    },
+    response::IntoResponse,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use utoipa::ToSchema;
+use validator::{Validate, ValidateEmail};
+
+use crate::crypto;
+use crate::delivery::{self, S3Target};
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::jobs;
+use crate::middleware::auth::Claims;
+use crate::models::{ReportArtifact, ReportSpec, SavedReport};
+use crate::reports::{load_report, next_run, ReportJob};
+use crate::storage;
+
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct SaveReport {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    pub spec: ReportSpec,
+    /// `weekly` or `monthly`. Leave out to run the report on demand only.
+    pub schedule: Option<String>,
+    /// `email`, `webhook`, `webdav`, `s3`, or a connected `dropbox` or `google_drive`.
+    /// Leave out to only keep the files for download.
+    pub delivery: Option<String>,
+    /// Email address, webhook URL, WebDAV folder URL or
+    /// `s3://ACCESS_KEY_ID:SECRET_ACCESS_KEY@bucket/prefix`, required with those
+    /// deliveries. Cloud drives upload to the folder chosen when connecting. Passwords
+    /// and secret keys are shown back as `********`; sending the target back unchanged
+    /// keeps them.
+    pub target: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ReportRun {
+    /// Poll it at `/api/v1/jobs/{id}`.
+    pub job_id: i64,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/reports/saved",
+    responses(
+        (status = 200, body = [SavedReport]),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Reports",
+    summary = "List saved reports",
+    description = "Lists saved report definitions by name."
+)]
+pub async fn list_reports(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+) -> Result<Json<Vec<SavedReport>>, PaymeError> {
+    let ids: Vec<(i64,)> =
+        sqlx::query_as("SELECT id FROM saved_reports WHERE user_id = ? ORDER BY name, id")
+            .bind(claims.sub)
+            .fetch_all(&pool)
+            .await?;
+
+    let mut reports = Vec::with_capacity(ids.len());
+    for (id,) in ids {
+        reports.push(redacted(load_report(&pool, claims.sub, id).await?));
+    }
+
+    Ok(Json(reports))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/reports/saved",
+    request_body = SaveReport,
+    responses(
+        (status = 200, body = SavedReport),
+        (status = 400, description = "Invalid report definition"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Reports",
+    summary = "Save a report",
+    description = "Saves a report definition: the period it covers, the categories it includes and its format. A scheduled report runs every Monday or on the first of the month, and is delivered by email, to a webhook, to a WebDAV folder (such as Nextcloud) or to an S3 bucket when one is set. The `backup` format is a zip of the full account export and the PDF of each month in the period, so a copy of the data lives outside this server."
+)]
+pub async fn create_report(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Json(payload): Json<SaveReport>,
+) -> Result<Json<SavedReport>, PaymeError> {
+    check_report(&pool, claims.sub, &payload).await?;
+
+    let id: i64 = sqlx::query_scalar(
+        r#"
+        INSERT INTO saved_reports (user_id, name, spec, schedule, delivery, target, next_run_at, created_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        RETURNING id
+        "#,
+    )
+    .bind(claims.sub)
+    .bind(payload.name.trim())
+    .bind(spec_json(&payload.spec)?)
+    .bind(&payload.schedule)
+    .bind(&payload.delivery)
+    .bind(payload.target.as_deref().map(crypto::seal_text).transpose()?)
+    .bind(first_run(&payload))
+    .bind(Utc::now())
+    .fetch_one(&pool)
+    .await?;
+
+    Ok(Json(redacted(load_report(&pool, claims.sub, id).await?)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/reports/saved/{id}",
+    params(("id" = i64, Path, description = "Report ID")),
+    request_body = SaveReport,
+    responses(
+        (status = 200, body = SavedReport),
+        (status = 400, description = "Invalid report definition"),
+        (status = 404, description = "Report not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Reports",
+    summary = "Update a saved report",
+    description = "Replaces a report definition. Changing the schedule restarts it from today."
+)]
+pub async fn update_report(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(report_id): Path<i64>,
+    Json(mut payload): Json<SaveReport>,
+) -> Result<Json<SavedReport>, PaymeError> {
+    let existing = load_report(&pool, claims.sub, report_id).await?;
+    if let (Some(target), Some(stored)) = (&payload.target, &existing.target) {
+        if *target == delivery::redact(stored) {
+            payload.target = Some(stored.clone());
+        }
+    }
+    check_report(&pool, claims.sub, &payload).await?;
+
+    let next_run_at = if existing.schedule == payload.schedule {
+        existing.next_run_at
+    } else {
+        first_run(&payload)
+    };
+
+    sqlx::query(
+        r#"
+        UPDATE saved_reports
+        SET name = ?, spec = ?, schedule = ?, delivery = ?, target = ?, next_run_at = ?
+        WHERE id = ?
+        "#,
+    )
+    .bind(payload.name.trim())
+    .bind(spec_json(&payload.spec)?)
+    .bind(&payload.schedule)
+    .bind(&payload.delivery)
+    .bind(
+        payload
+            .target
+            .as_deref()
+            .map(crypto::seal_text)
+            .transpose()?,
+    )
+    .bind(next_run_at)
+    .bind(report_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(Json(redacted(
+        load_report(&pool, claims.sub, report_id).await?,
+    )))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/reports/saved/{id}",
+    params(("id" = i64, Path, description = "Report ID")),
+    responses((status = 204, description = "Deleted")),
+    tag = "Reports",
+    summary = "Delete a saved report",
+    description = "Deletes a report definition along with its stored files."
+)]
+pub async fn delete_report(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(report_id): Path<i64>,
+) -> Result<StatusCode, PaymeError> {
+    sqlx::query("DELETE FROM saved_reports WHERE id = ? AND user_id = ?")
+        .bind(report_id)
+        .bind(claims.sub)
+        .execute(&pool)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/reports/saved/{id}/run",
+    params(("id" = i64, Path, description = "Report ID")),
+    responses(
+        (status = 200, body = ReportRun),
+        (status = 404, description = "Report not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Reports",
+    summary = "Run a saved report now",
+    description = "Queues a run of the report for its last complete period. The file is stored for download and delivered like a scheduled run."
+)]
+pub async fn run_report(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(report_id): Path<i64>,
+) -> Result<Json<ReportRun>, PaymeError> {
+    load_report(&pool, claims.sub, report_id).await?;
+
+    let job_id = jobs::enqueue(&pool, claims.sub, jobs::REPORT, &ReportJob { report_id }).await?;
+    jobs::wake();
+
+    Ok(Json(ReportRun { job_id }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/reports/saved/{id}/artifacts",
+    params(("id" = i64, Path, description = "Report ID")),
+    responses(
+        (status = 200, body = [ReportArtifact]),
+        (status = 404, description = "Report not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Reports",
+    summary = "List generated files",
+    description = "Lists the files generated by the report's runs, newest first."
+)]
+pub async fn list_artifacts(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(report_id): Path<i64>,
+) -> Result<Json<Vec<ReportArtifact>>, PaymeError> {
+    load_report(&pool, claims.sub, report_id).await?;
+
+    let artifacts: Vec<ReportArtifact> = sqlx::query_as(
+        r#"
+        SELECT id, report_id, period_start, period_end, filename, content_type, created_at
+        FROM report_artifacts WHERE report_id = ?
+        ORDER BY created_at DESC, id DESC
+        "#,
+    )
+    .bind(report_id)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(artifacts))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/reports/artifacts/{id}",
+    params(("id" = i64, Path, description = "Artifact ID")),
+    responses(
+        (status = 200, description = "The generated file"),
+        (status = 404, description = "File not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Reports",
+    summary = "Download a generated file",
+    description = "Downloads a file generated by a report run."
+)]
+pub async fn download_artifact(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(artifact_id): Path<i64>,
+) -> Result<impl IntoResponse, PaymeError> {
+    let (filename, content_type, key): (String, String, String) = sqlx::query_as(
+        r#"
+        SELECT a.filename, a.content_type, a.storage_key
+        FROM report_artifacts a
+        JOIN saved_reports r ON a.report_id = r.id
+        WHERE a.id = ? AND r.user_id = ?
+        "#,
+    )
+    .bind(artifact_id)
+    .bind(claims.sub)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(PaymeError::NotFound)?;
+
+    Ok((
+        [
+            (CONTENT_TYPE, content_type),
+            (
+                CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{filename}\""),
+            ),
+        ],
+        storage::sealed_body(&pool, storage::backend(&pool).as_ref(), &key).await?,
+    ))
+}
+
+async fn check_report(
+    pool: &SqlitePool,
+    user_id: i64,
+    payload: &SaveReport,
+) -> Result<(), PaymeError> {
+    payload.validate()?;
+    let bad_request = |message: &str| Err(PaymeError::BadRequest(message.to_string()));
+
+    if !matches!(payload.spec.period.as_str(), "week" | "month") {
+        return bad_request("Period must be week or month");
+    }
+    if !matches!(payload.spec.format.as_str(), "csv" | "json" | "backup") {
+        return bad_request("Format must be csv, json or backup");
+    }
+    if !matches!(
+        payload.schedule.as_deref(),
+        None | Some("weekly" | "monthly")
+    ) {
+        return bad_request("Schedule must be weekly or monthly");
+    }
+    match (payload.delivery.as_deref(), payload.target.as_deref()) {
+        (None, _) => {}
+        (Some("email"), Some(target)) if target.validate_email() => {}
+        (Some("webhook" | "webdav"), Some(target))
+            if url::Url::parse(target)
+                .is_ok_and(|url| matches!(url.scheme(), "http" | "https")) =>
+        {
+            let url = url::Url::parse(target).map_err(|e| PaymeError::BadRequest(e.to_string()))?;
+            delivery::check_public(&url).await?;
+        }
+        (Some("s3"), Some(target)) => {
+            if let Some(endpoint) = S3Target::parse(target)?.endpoint {
+                let endpoint = url::Url::parse(&endpoint)
+                    .map_err(|e| PaymeError::BadRequest(format!("Invalid S3 endpoint: {e}")))?;
+                delivery::check_public(&endpoint).await?;
+            }
+        }
+        (Some(name @ ("dropbox" | "google_drive")), _) => {
+            let connected: bool = sqlx::query_scalar(
+                "SELECT EXISTS(SELECT 1 FROM cloud_connections WHERE user_id = ? AND provider = ?)",
+            )
+            .bind(user_id)
+            .bind(name)
+            .fetch_one(pool)
+            .await?;
+            if !connected {
+                return bad_request("Connect the cloud drive before delivering to it");
+            }
+        }
+        (Some("email" | "webhook" | "webdav" | "s3"), _) => {
+            return bad_request(
+                "Delivery needs a valid email address, http(s) URL or s3:// URL as target",
+            )
+        }
+        _ => {
+            return bad_request(
+                "Delivery must be email, webhook, webdav, s3, dropbox or google_drive",
+            )
+        }
+    }
+
+    for category_id in &payload.spec.category_ids {
+        let owned: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM budget_categories WHERE id = ? AND user_id = ?)",
+        )
+        .bind(category_id)
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?;
+        if !owned {
+            return Err(PaymeError::BadRequest(format!(
+                "Unknown category {category_id}"
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// The report as shown to the user, without the password or secret key of its target.
+fn redacted(report: SavedReport) -> SavedReport {
+    SavedReport {
+        target: report.target.as_deref().map(delivery::redact),
+        ..report
+    }
+}
+
+fn first_run(payload: &SaveReport) -> Option<chrono::DateTime<Utc>> {
+    payload
+        .schedule
+        .as_deref()
+        .map(|schedule| next_run(schedule, Utc::now().date_naive()))
+}
+
+fn spec_json(spec: &ReportSpec) -> Result<String, PaymeError> {
+    serde_json::to_string(spec).map_err(|e| PaymeError::Internal(e.to_string()))
+}
@@ -0,0 +1,171 @@
+use axum::extract::{Query, State};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::handlers::settings::{load_settings, save_settings};
+use crate::middleware::auth::Claims;
+use crate::middleware::maintenance::MaintenanceMode;
+
+const DEFAULT_RETURN_RATE: f64 = 5.0;
+
+#[derive(Deserialize, IntoParams, Validate)]
+#[into_params(parameter_in = Query)]
+pub struct ProjectionQuery {
+    /// Monthly contribution. Defaults to the saved assumption, then to your historical rate.
+    #[validate(range(min = 0.0), custom(function = "crate::money::amount"))]
+    pub monthly_contribution: Option<f64>,
+    /// Expected annual return in percent (e.g. 7.0).
+    #[validate(range(min = -100.0, max = 100.0))]
+    pub return_rate: Option<f64>,
+    #[validate(range(min = 0, max = 120))]
+    pub current_age: Option<i32>,
+    #[validate(range(min = 0, max = 120))]
+    pub target_age: Option<i32>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ProjectionPoint {
+    pub year: i32,
+    pub age: i32,
+    pub total_contributions: f64,
+    pub balance: f64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct RetirementProjection {
+    pub starting_balance: f64,
+    pub monthly_contribution: f64,
+    pub historical_monthly_contribution: f64,
+    pub return_rate: f64,
+    pub current_age: i32,
+    pub target_age: i32,
+    pub projected_balance: f64,
+    pub points: Vec<ProjectionPoint>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/retirement/projection",
+    params(ProjectionQuery),
+    responses(
+        (status = 200, body = RetirementProjection),
+        (status = 400, description = "Missing or inconsistent ages"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Wealth",
+    summary = "Project retirement savings",
+    description = "Builds a year-by-year projection of the retirement balance up to the target age. Assumptions passed in the query are saved to your settings and reused when omitted, except while the API is read-only."
+)]
+pub async fn get_projection(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    axum::Extension(mode): axum::Extension<MaintenanceMode>,
+    Query(query): Query<ProjectionQuery>,
+) -> Result<Json<RetirementProjection>, PaymeError> {
+    query.validate()?;
+    let mut settings = load_settings(&pool, claims.sub).await?;
+
+    settings.retirement_monthly_contribution = query
+        .monthly_contribution
+        .or(settings.retirement_monthly_contribution);
+    settings.retirement_return_rate = query.return_rate.or(settings.retirement_return_rate);
+    settings.retirement_current_age = query.current_age.or(settings.retirement_current_age);
+    settings.retirement_target_age = query.target_age.or(settings.retirement_target_age);
+
+    let current_age = settings
+        .retirement_current_age
+        .ok_or(PaymeError::BadRequest(
+            "current_age is required".to_string(),
+        ))?;
+    let target_age = settings
+        .retirement_target_age
+        .ok_or(PaymeError::BadRequest("target_age is required".to_string()))?;
+
+    if target_age <= current_age {
+        return Err(PaymeError::BadRequest(
+            "target_age must be greater than current_age".to_string(),
+        ));
+    }
+
+    // The assumptions still apply to this projection when they can't be saved
+    if !mode.is_read_only() {
+        save_settings(&pool, claims.sub, &settings).await?;
+    }
+
+    let starting_balance: f64 =
+        sqlx::query_scalar("SELECT retirement_savings FROM users WHERE id = ?")
+            .bind(claims.sub)
+            .fetch_one(&pool)
+            .await?;
+
+    // Average retirement contribution per tracked month, based on items routed to retirement savings
+    let historical_monthly_contribution: f64 = sqlx::query_scalar(
+        r#"
+        SELECT COALESCE(SUM(i.amount) / COUNT(DISTINCT m.id), 0.0)
+        FROM months m
+        LEFT JOIN items i ON i.month_id = m.id AND i.savings_destination = 'retirement_savings'
+        WHERE m.user_id = ?
+        "#,
+    )
+    .bind(claims.sub)
+    .fetch_one(&pool)
+    .await?;
+
+    let monthly_contribution = settings
+        .retirement_monthly_contribution
+        .unwrap_or(historical_monthly_contribution);
+    let return_rate = settings
+        .retirement_return_rate
+        .unwrap_or(DEFAULT_RETURN_RATE);
+
+    let points = project(
+        starting_balance,
+        monthly_contribution,
+        return_rate,
+        current_age,
+        target_age,
+    );
+    let projected_balance = points.last().map(|p| p.balance).unwrap_or(starting_balance);
+
+    Ok(Json(RetirementProjection {
+        starting_balance,
+        monthly_contribution,
+        historical_monthly_contribution,
+        return_rate,
+        current_age,
+        target_age,
+        projected_balance,
+        points,
+    }))
+}
+
+fn project(
+    starting_balance: f64,
+    monthly_contribution: f64,
+    return_rate: f64,
+    current_age: i32,
+    target_age: i32,
+) -> Vec<ProjectionPoint> {
+    let monthly_rate = return_rate / 100.0 / 12.0;
+    let mut balance = starting_balance;
+    let mut total_contributions = 0.0;
+
+    (1..=target_age - current_age)
+        .map(|year| {
+            for _ in 0..12 {
+                balance = balance * (1.0 + monthly_rate) + monthly_contribution;
+                total_contributions += monthly_contribution;
+            }
+            ProjectionPoint {
+                year,
+                age: current_age + year,
+                total_contributions,
+                balance,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,127 @@
+use axum::extract::{Path, State};
+use serde::{Deserialize, Serialize};
+use sqlx::{SqliteExecutor, SqlitePool};
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::categorize;
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::middleware::auth::Claims;
+use crate::models::CategoryRule;
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct SetCategoryRules {
+    /// Tried in this order. An empty list removes the category's rules.
+    #[validate(length(max = 20), nested)]
+    pub rules: Vec<NewCategoryRule>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct NewCategoryRule {
+    /// `contains` or `regex`.
+    pub kind: String,
+    #[validate(length(min = 1, max = 200))]
+    pub pattern: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/categories/{id}/rules",
+    params(("id" = i64, Path, description = "Category ID")),
+    responses(
+        (status = 200, body = [CategoryRule]),
+        (status = 404, description = "Category not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Configuration",
+    summary = "Get category rules",
+    description = "Lists the rules that file items created without a category into this one."
+)]
+pub async fn get_category_rules(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(category_id): Path<i64>,
+) -> Result<Json<Vec<CategoryRule>>, PaymeError> {
+    verify_category(&pool, claims.sub, category_id).await?;
+    load_rules(&pool, category_id).await.map(Json)
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/categories/{id}/rules",
+    params(("id" = i64, Path, description = "Category ID")),
+    request_body = SetCategoryRules,
+    responses(
+        (status = 200, body = [CategoryRule]),
+        (status = 400, description = "Too many rules, an unknown kind or a regex that doesn't compile"),
+        (status = 404, description = "Category not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Configuration",
+    summary = "Replace category rules",
+    description = "Replaces the rules that file items created without a category into this one. `contains` looks for the pattern in the description, e.g. a shop name or an emoji, and `regex` matches a regular expression; both ignore case. Items already created keep their category."
+)]
+pub async fn set_category_rules(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(category_id): Path<i64>,
+    Json(payload): Json<SetCategoryRules>,
+) -> Result<Json<Vec<CategoryRule>>, PaymeError> {
+    payload.validate()?;
+    verify_category(&pool, claims.sub, category_id).await?;
+    for rule in &payload.rules {
+        categorize::check_rule(&rule.kind, &rule.pattern).map_err(PaymeError::BadRequest)?;
+    }
+
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM category_rules WHERE category_id = ?")
+        .bind(category_id)
+        .execute(&mut *tx)
+        .await?;
+    for (position, rule) in payload.rules.iter().enumerate() {
+        sqlx::query(
+            "INSERT INTO category_rules (category_id, kind, pattern, position) VALUES (?, ?, ?, ?)",
+        )
+        .bind(category_id)
+        .bind(&rule.kind)
+        .bind(&rule.pattern)
+        .bind(position as i64)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    load_rules(&pool, category_id).await.map(Json)
+}
+
+async fn verify_category<'e>(
+    executor: impl SqliteExecutor<'e>,
+    user_id: i64,
+    category_id: i64,
+) -> Result<(), PaymeError> {
+    sqlx::query_scalar::<_, i64>("SELECT id FROM budget_categories WHERE id = ? AND user_id = ?")
+        .bind(category_id)
+        .bind(user_id)
+        .fetch_optional(executor)
+        .await?
+        .ok_or(PaymeError::NotFound)?;
+
+    Ok(())
+}
+
+async fn load_rules<'e>(
+    executor: impl SqliteExecutor<'e>,
+    category_id: i64,
+) -> Result<Vec<CategoryRule>, PaymeError> {
+    let rules = sqlx::query_as(
+        "SELECT id, category_id, kind, pattern FROM category_rules WHERE category_id = ? ORDER BY position, id",
+    )
+    .bind(category_id)
+    .fetch_all(executor)
+    .await?;
+
+    Ok(rules)
+}
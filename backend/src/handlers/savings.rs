@@ -1,11 +1,15 @@
-use axum::{extract::State, Json};
+use axum::extract::State;
+use chrono::{NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
 use utoipa::ToSchema;
 use validator::Validate;
 
 use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::handlers::settings::load_settings;
 use crate::middleware::auth::Claims;
+use crate::models::WealthSnapshot;
 
 #[derive(Serialize, ToSchema)]
 pub struct SavingsResponse {
@@ -14,14 +18,16 @@ pub struct SavingsResponse {
 }
 
 #[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateSavings {
-    #[validate(range(min = 0.0))]
+    #[validate(range(min = 0.0), custom(function = "crate::money::amount"))]
     pub savings: f64,
 }
 
 #[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateSavingsGoal {
-    #[validate(range(min = 0.0))]
+    #[validate(range(min = 0.0), custom(function = "crate::money::amount"))]
     pub savings_goal: f64,
 }
 
@@ -31,14 +37,15 @@ pub struct RetirementSavingsResponse {
 }
 
 #[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
 pub struct UpdateRetirementSavings {
-    #[validate(range(min = 0.0))]
+    #[validate(range(min = 0.0), custom(function = "crate::money::amount"))]
     pub retirement_savings: f64,
 }
 
 #[utoipa::path(
     get,
-    path = "/api/savings",
+    path = "/api/v1/savings",
     responses(
         (status = 200, body = SavingsResponse),
         (status = 500, description = "Internal server error")
@@ -65,7 +72,7 @@ pub async fn get_savings(
 
 #[utoipa::path(
     put,
-    path = "/api/savings",
+    path = "/api/v1/savings",
     request_body = UpdateSavings,
     responses(
         (status = 200, body = SavingsResponse),
@@ -100,7 +107,7 @@ pub async fn update_savings(
 
 #[utoipa::path(
     put,
-    path = "/api/savings/goal",
+    path = "/api/v1/savings/goal",
     request_body = UpdateSavingsGoal,
     responses(
         (status = 200, body = SavingsResponse),
@@ -135,7 +142,7 @@ pub async fn update_savings_goal(
 
 #[utoipa::path(
     get,
-    path = "/api/retirement-savings",
+    path = "/api/v1/retirement-savings",
     responses(
         (status = 200, body = RetirementSavingsResponse),
         (status = 500, description = "Internal server error")
@@ -160,7 +167,7 @@ pub async fn get_retirement_savings(
 
 #[utoipa::path(
     put,
-    path = "/api/retirement-savings",
+    path = "/api/v1/retirement-savings",
     request_body = UpdateRetirementSavings,
     responses(
         (status = 200, body = RetirementSavingsResponse),
@@ -186,3 +193,83 @@ pub async fn update_retirement_savings(
         retirement_savings: payload.retirement_savings,
     }))
 }
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/wealth/history",
+    responses(
+        (status = 200, body = Vec<WealthSnapshot>),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Wealth",
+    summary = "Get net worth history",
+    description = "Lists the savings and retirement balances recorded each time a month was closed, oldest first."
+)]
+pub async fn get_wealth_history(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+) -> Result<Json<Vec<WealthSnapshot>>, PaymeError> {
+    let history: Vec<WealthSnapshot> = sqlx::query_as(
+        r#"
+        SELECT ws.month_id, m.year, m.month, ws.savings, ws.retirement_savings,
+               ws.savings + ws.retirement_savings AS net_worth, ws.created_at AS recorded_at
+        FROM wealth_snapshots ws
+        JOIN months m ON ws.month_id = m.id
+        WHERE ws.user_id = ?
+        ORDER BY m.year, m.month
+        "#,
+    )
+    .bind(claims.sub)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(history))
+}
+
+/// Books the savings auto-contribution, if one is set, into a newly opened month
+/// as an item routed to savings.
+pub(crate) async fn book_auto_contribution(
+    pool: &SqlitePool,
+    user_id: i64,
+    month_id: i64,
+    spent_on: NaiveDate,
+) -> Result<(), PaymeError> {
+    let settings = load_settings(pool, user_id).await?;
+    let (Some(amount), Some(category_id)) = (
+        settings.savings_auto_contribution,
+        settings.savings_auto_category_id,
+    ) else {
+        return Ok(());
+    };
+
+    // The item and the balance it adds to are written together
+    let mut tx = pool.begin().await?;
+    // The category may have been deleted since the rule was set
+    let booked = sqlx::query(
+        r#"
+        INSERT INTO items (month_id, category_id, description, amount, spent_on, savings_destination, created_at)
+        SELECT ?, id, 'Savings contribution', ?, ?, 'savings', ?
+        FROM budget_categories WHERE id = ? AND user_id = ?
+        "#,
+    )
+    .bind(month_id)
+    .bind(amount)
+    .bind(spent_on)
+    .bind(Utc::now())
+    .bind(category_id)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    if booked > 0 {
+        sqlx::query("UPDATE users SET savings = savings + ? WHERE id = ?")
+            .bind(amount)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
@@ -0,0 +1,369 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use utoipa::ToSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::format::MoneyFormat;
+use crate::handlers::months::{get_month_summary, read_snapshot};
+use crate::handlers::settings::load_settings;
+use crate::i18n;
+use crate::jwt;
+use crate::middleware::auth::Claims;
+use crate::middleware::security::PDF_CONTENT_SECURITY_POLICY;
+use crate::models::MonthSummary;
+use crate::pdf;
+
+fn default_expires_in_days() -> i64 {
+    7
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateShare {
+    #[serde(default = "default_expires_in_days")]
+    #[validate(range(min = 1, max = 90))]
+    pub expires_in_days: i64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ShareResponse {
+    pub token: String,
+    pub url: String,
+    pub pdf_url: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PublicStatsLink {
+    pub slug: String,
+    pub url: String,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CategoryShare {
+    pub label: String,
+    /// Share of the month's spending, in whole percent.
+    pub percent: f64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PublicStats {
+    pub year: i32,
+    pub month: i32,
+    /// Income left after fixed costs and spending, in whole percent. Absent without income.
+    pub savings_rate_percent: Option<f64>,
+    /// Categories with spending, largest share first.
+    pub categories: Vec<CategoryShare>,
+}
+
+/// Claims carried by a share token. The share row must still exist for the token to be honoured.
+#[derive(Serialize, Deserialize)]
+struct ShareClaims {
+    sid: i64,
+    mid: i64,
+    exp: usize,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/months/{id}/share",
+    params(("id" = i64, Path, description = "Month ID")),
+    request_body = CreateShare,
+    responses(
+        (status = 200, description = "Share link created", body = ShareResponse),
+        (status = 404, description = "Month not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Months",
+    summary = "Create read-only share link",
+    description = "Issues a signed, expiring link that exposes a read-only summary and PDF of the month without authentication."
+)]
+pub async fn create_share(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(month_id): Path<i64>,
+    Json(payload): Json<CreateShare>,
+) -> Result<Json<ShareResponse>, PaymeError> {
+    payload.validate()?;
+    verify_month_access(&pool, claims.sub, month_id).await?;
+
+    let expires_at = Utc::now() + Duration::days(payload.expires_in_days);
+    let share_id: i64 = sqlx::query_scalar(
+        "INSERT INTO month_shares (month_id, expires_at) VALUES (?, ?) RETURNING id",
+    )
+    .bind(month_id)
+    .bind(expires_at)
+    .fetch_one(&pool)
+    .await?;
+
+    let token = jwt::keys().sign(&ShareClaims {
+        sid: share_id,
+        mid: month_id,
+        exp: expires_at.timestamp() as usize,
+    })?;
+
+    Ok(Json(ShareResponse {
+        url: format!("/api/v1/shared/{token}"),
+        pdf_url: format!("/api/v1/shared/{token}/pdf"),
+        token,
+        expires_at,
+    }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/months/{id}/share",
+    params(("id" = i64, Path, description = "Month ID")),
+    responses(
+        (status = 204, description = "All share links for the month revoked"),
+        (status = 404, description = "Month not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Months",
+    summary = "Revoke share links",
+    description = "Revokes every share link issued for the month."
+)]
+pub async fn revoke_shares(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(month_id): Path<i64>,
+) -> Result<StatusCode, PaymeError> {
+    verify_month_access(&pool, claims.sub, month_id).await?;
+
+    sqlx::query("DELETE FROM month_shares WHERE month_id = ?")
+        .bind(month_id)
+        .execute(&pool)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/shared/{token}",
+    params(("token" = String, Path, description = "Share token")),
+    responses(
+        (status = 200, body = MonthSummary),
+        (status = 404, description = "Link is invalid, expired, or revoked"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(()),
+    tag = "Months",
+    summary = "View shared month",
+    description = "Returns the read-only summary behind a share link. No authentication required."
+)]
+pub async fn get_shared_month(
+    State(pool): State<SqlitePool>,
+    Path(token): Path<String>,
+) -> Result<Json<MonthSummary>, PaymeError> {
+    let (user_id, month_id) = resolve_share(&pool, &token).await?;
+    get_month_summary(&pool, user_id, month_id).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/shared/{token}/pdf",
+    params(("token" = String, Path, description = "Share token")),
+    responses(
+        (status = 200, description = "PDF report for the shared month", content_type = "application/pdf"),
+        (status = 404, description = "Link is invalid, expired, or revoked"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(()),
+    tag = "Months",
+    summary = "Download shared month PDF",
+    description = "Returns the stored snapshot for closed months, or a freshly generated report for open ones."
+)]
+pub async fn get_shared_month_pdf(
+    State(pool): State<SqlitePool>,
+    Path(token): Path<String>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, PaymeError> {
+    let (user_id, month_id) = resolve_share(&pool, &token).await?;
+
+    let pdf_data = match read_snapshot(&pool, month_id).await? {
+        Some(pdf) => pdf,
+        None => {
+            let summary = get_month_summary(&pool, user_id, month_id).await?.0;
+            let settings = load_settings(&pool, user_id).await?;
+            let locale = i18n::locale_for_request(settings.locale.as_deref(), &headers);
+            pdf::generate_pdf(&summary, &MoneyFormat::from_settings(&settings), locale)
+                .map_err(|e| PaymeError::Internal(e.to_string()))?
+        }
+    };
+
+    Ok((
+        [
+            ("Content-Type", "application/pdf"),
+            ("Content-Disposition", "attachment; filename=\"month.pdf\""),
+            ("Content-Security-Policy", PDF_CONTENT_SECURITY_POLICY),
+        ],
+        pdf_data,
+    ))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/public-stats",
+    responses(
+        (status = 200, description = "Public stats enabled", body = PublicStatsLink),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Months",
+    summary = "Enable public stats",
+    description = "Opts in to a public page showing the latest month's spending split and savings rate as percentages. Returns the existing link if already enabled."
+)]
+pub async fn enable_public_stats(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+) -> Result<Json<PublicStatsLink>, PaymeError> {
+    sqlx::query(
+        r#"
+        INSERT INTO public_stats_shares (user_id, slug, created_at) VALUES (?, ?, ?)
+        ON CONFLICT(user_id) DO NOTHING
+        "#,
+    )
+    .bind(claims.sub)
+    .bind(Uuid::new_v4().simple().to_string())
+    .bind(Utc::now())
+    .execute(&pool)
+    .await?;
+
+    let slug: String = sqlx::query_scalar("SELECT slug FROM public_stats_shares WHERE user_id = ?")
+        .bind(claims.sub)
+        .fetch_one(&pool)
+        .await?;
+
+    Ok(Json(PublicStatsLink {
+        url: format!("/api/v1/public/stats/{slug}"),
+        slug,
+    }))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/public-stats",
+    responses(
+        (status = 204, description = "Public stats disabled"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Months",
+    summary = "Disable public stats",
+    description = "Removes the public stats page. Re-enabling issues a new link."
+)]
+pub async fn disable_public_stats(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+) -> Result<StatusCode, PaymeError> {
+    sqlx::query("DELETE FROM public_stats_shares WHERE user_id = ?")
+        .bind(claims.sub)
+        .execute(&pool)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/public/stats/{slug}",
+    params(("slug" = String, Path, description = "Public stats slug")),
+    responses(
+        (status = 200, body = PublicStats),
+        (status = 404, description = "Public stats are not enabled for this link"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(()),
+    tag = "Months",
+    summary = "View public stats",
+    description = "Returns the share of spending per category and the savings rate of the owner's latest month, rounded to whole percent. No amounts are included. No authentication required."
+)]
+pub async fn get_public_stats(
+    State(pool): State<SqlitePool>,
+    Path(slug): Path<String>,
+) -> Result<Json<PublicStats>, PaymeError> {
+    let (user_id, month_id): (i64, i64) = sqlx::query_as(
+        r#"
+        SELECT p.user_id, m.id FROM public_stats_shares p
+        JOIN months m ON m.user_id = p.user_id
+        WHERE p.slug = ?
+        ORDER BY m.year DESC, m.month DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(&slug)
+    .fetch_optional(&pool)
+    .await?
+    .ok_or(PaymeError::NotFound)?;
+
+    let summary = get_month_summary(&pool, user_id, month_id).await?.0;
+    Ok(Json(public_stats(&summary)))
+}
+
+/// Reduces a summary to rounded percentages so no absolute amount can be derived.
+fn public_stats(summary: &MonthSummary) -> PublicStats {
+    let percent = |ratio: f64| (ratio * 100.0).round();
+    let mut categories: Vec<CategoryShare> = summary
+        .budgets
+        .iter()
+        .filter(|b| summary.total_spent > 0.0 && b.spent_amount > 0.0)
+        .map(|b| CategoryShare {
+            label: b.category_label.clone(),
+            percent: percent(b.spent_amount / summary.total_spent),
+        })
+        .collect();
+    categories.sort_by(|a, b| b.percent.total_cmp(&a.percent));
+
+    PublicStats {
+        year: summary.month.year,
+        month: summary.month.month,
+        savings_rate_percent: summary.metrics.savings_rate.map(percent),
+        categories,
+    }
+}
+
+/// Validates a share token and returns the owning user and month.
+async fn resolve_share(pool: &SqlitePool, token: &str) -> Result<(i64, i64), PaymeError> {
+    let claims: ShareClaims = jwt::keys().verify(pool, token).await.map_err(|e| match e {
+        PaymeError::Unauthorized => PaymeError::NotFound,
+        other => other,
+    })?;
+
+    let share: (i64,) = sqlx::query_as(
+        r#"
+        SELECT m.user_id FROM month_shares s
+        JOIN months m ON s.month_id = m.id
+        WHERE s.id = ? AND s.month_id = ?
+        "#,
+    )
+    .bind(claims.sid)
+    .bind(claims.mid)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(PaymeError::NotFound)?;
+
+    Ok((share.0, claims.mid))
+}
+
+async fn verify_month_access(
+    pool: &SqlitePool,
+    user_id: i64,
+    month_id: i64,
+) -> Result<(), PaymeError> {
+    let exists: Option<(i64,)> =
+        sqlx::query_as("SELECT id FROM months WHERE id = ? AND user_id = ?")
+            .bind(month_id)
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?;
+
+    exists.map(|_| ()).ok_or(PaymeError::NotFound)
+}
@@ -0,0 +1,143 @@
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+};
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use sqlx::SqlitePool;
+use utoipa::ToSchema;
+
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::signing;
+use crate::storage;
+
+#[derive(Serialize, ToSchema)]
+pub struct PdfSignature {
+    pub year: i32,
+    pub month: i32,
+    /// SHA-256 of the signed PDF, in hex.
+    pub sha256: String,
+    /// Ed25519 signature over the PDF's bytes, in hex.
+    pub signature: String,
+    /// Ed25519 public key that made the signature, in hex.
+    pub public_key: String,
+    pub signed_at: NaiveDateTime,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct PdfSignatureCheck {
+    /// SHA-256 of the uploaded file, in hex.
+    pub sha256: String,
+    /// Whether the file is the signed PDF, byte for byte, and the signature holds.
+    pub valid: bool,
+    pub signature: PdfSignature,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SigningKey {
+    /// Ed25519 public key, in hex.
+    pub public_key: String,
+    /// The same key as a PEM `PUBLIC KEY` block.
+    pub public_key_pem: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/pdf-signatures/{code}",
+    params(("code" = String, Path, description = "Verification code printed on the PDF")),
+    responses(
+        (status = 200, body = PdfSignature),
+        (status = 404, description = "No signed PDF has this code"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(()),
+    tag = "Months",
+    summary = "Look up a signed PDF",
+    description = "Returns the hash and signature recorded for the month report printed with this code. Reports carry the link to this endpoint when the server signs PDFs. No authentication required."
+)]
+pub async fn get_pdf_signature(
+    State(pool): State<SqlitePool>,
+    Path(code): Path<String>,
+) -> Result<Json<PdfSignature>, PaymeError> {
+    Ok(Json(load_signature(&pool, &code).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/pdf-signatures/{code}/verify",
+    params(("code" = String, Path, description = "Verification code printed on the PDF")),
+    request_body(content = Vec<u8>, content_type = "application/pdf", description = "The PDF to check"),
+    responses(
+        (status = 200, body = PdfSignatureCheck),
+        (status = 404, description = "No signed PDF has this code"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(()),
+    tag = "Months",
+    summary = "Verify a signed PDF",
+    description = "Checks that the uploaded file is the report signed under this code and that its signature matches the recorded public key. No authentication required."
+)]
+pub async fn verify_pdf_signature(
+    State(pool): State<SqlitePool>,
+    Path(code): Path<String>,
+    body: Bytes,
+) -> Result<Json<PdfSignatureCheck>, PaymeError> {
+    let signature = load_signature(&pool, &code).await?;
+    let sha256 = storage::content_hash(&body);
+    let valid = sha256 == signature.sha256
+        && signing::verify(&signature.public_key, &body, &signature.signature);
+
+    Ok(Json(PdfSignatureCheck {
+        sha256,
+        valid,
+        signature,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/signing-key",
+    responses(
+        (status = 200, body = SigningKey),
+        (status = 404, description = "PDF signing is not configured")
+    ),
+    security(()),
+    tag = "Months",
+    summary = "Get the PDF signing key",
+    description = "Returns the public key the server signs month reports with, to check signatures offline. No authentication required."
+)]
+pub async fn get_signing_key() -> Result<Json<SigningKey>, PaymeError> {
+    let signer = signing::pdf_signer().ok_or(PaymeError::NotFound)?;
+
+    Ok(Json(SigningKey {
+        public_key: signer.public_key(),
+        public_key_pem: signer.public_key_pem(),
+    }))
+}
+
+async fn load_signature(pool: &SqlitePool, code: &str) -> Result<PdfSignature, PaymeError> {
+    let row: Option<(i32, i32, Option<String>, String, String, NaiveDateTime)> = sqlx::query_as(
+        r#"
+        SELECT m.year, m.month, s.sha256, s.signature, s.signed_by, s.created_at
+        FROM monthly_snapshots s
+        JOIN months m ON m.id = s.month_id
+        WHERE s.verification_code = ?
+        "#,
+    )
+    .bind(code)
+    .fetch_optional(pool)
+    .await?;
+    let (year, month, sha256, signature, public_key, signed_at) =
+        row.ok_or(PaymeError::NotFound)?;
+
+    Ok(PdfSignature {
+        year,
+        month,
+        // Signed snapshots are always stored with their hash
+        sha256: sha256.unwrap_or_default(),
+        signature,
+        public_key,
+        signed_at,
+    })
+}
@@ -0,0 +1,480 @@
+use std::collections::{HashMap, HashSet};
+
+use axum::extract::{Query, State};
+use chrono::{Datelike, Months, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::forecast::{compound, cumulative, Baseline, CategorySpend, Scenario};
+use crate::handlers::pace::days_in_month;
+use crate::handlers::settings::{load_settings, save_settings};
+use crate::middleware::auth::Claims;
+use crate::models::FixedExpense;
+use crate::summary::days_elapsed;
+
+const PROJECTION_MONTHS: usize = 12;
+
+fn default_history_months() -> i64 {
+    3
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct SimulationRequest {
+    /// Added to the monthly income, negative for a pay cut.
+    #[serde(default)]
+    #[validate(custom(function = "crate::money::amount"))]
+    pub income_change: f64,
+    #[serde(default)]
+    #[validate(nested)]
+    pub category_changes: Vec<CategoryChange>,
+    /// New recurring monthly payments, e.g. a loan or a debt repayment plan.
+    #[serde(default)]
+    #[validate(nested)]
+    pub new_payments: Vec<NewPayment>,
+    /// Recent months the typical month is averaged from, 3 by default.
+    #[serde(default = "default_history_months")]
+    #[validate(range(min = 1, max = 24))]
+    pub history_months: i64,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct CategoryChange {
+    pub category_id: i64,
+    /// Percentage change, e.g. -20 to cut spending by a fifth.
+    #[validate(range(min = -100.0, max = 1000.0))]
+    pub percent: f64,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct NewPayment {
+    #[validate(length(min = 1, max = 100))]
+    pub label: String,
+    #[validate(range(min = 0.0), custom(function = "crate::money::amount"))]
+    pub amount: f64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SimulatedCategory {
+    pub category_id: i64,
+    pub label: String,
+    pub baseline: f64,
+    pub simulated: f64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct MonthOutcome {
+    pub income: f64,
+    pub fixed: f64,
+    pub spending: f64,
+    /// Negative for a deficit.
+    pub remaining: f64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ProjectedMonth {
+    pub year: i32,
+    pub month: i32,
+    /// Money accumulated since now, without and with the changes.
+    pub baseline_balance: f64,
+    pub simulated_balance: f64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SimulationResult {
+    /// Number of months the typical month was averaged from.
+    pub history_months: i64,
+    /// A typical month as things are.
+    pub baseline: MonthOutcome,
+    /// A typical month with the changes.
+    pub simulated: MonthOutcome,
+    pub categories: Vec<SimulatedCategory>,
+    /// Projected money left at the end of the current month, without and with the changes.
+    pub month_end_baseline: f64,
+    pub month_end_simulated: f64,
+    /// The next 12 months.
+    pub projection: Vec<ProjectedMonth>,
+    /// How much more (or less) the changes leave after 12 months.
+    pub difference: f64,
+}
+
+#[derive(Deserialize, IntoParams, ToSchema, Validate)]
+#[into_params(parameter_in = Query)]
+pub struct SavingsScenario {
+    /// Amount added to savings every month.
+    #[validate(range(min = 0.0), custom(function = "crate::money::amount"))]
+    pub monthly: f64,
+    #[validate(range(min = 1, max = 50))]
+    pub years: u32,
+    /// Expected annual return in percent (e.g. 3.5).
+    #[validate(range(min = -100.0, max = 100.0))]
+    pub rate: f64,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct SavingsGoalFromScenario {
+    #[serde(flatten)]
+    #[validate(nested)]
+    pub scenario: SavingsScenario,
+    /// Books the monthly amount to savings in each new month under this category.
+    /// Leave out to set the goal without an auto-contribution.
+    pub category_id: Option<i64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SavingsPoint {
+    pub year: u32,
+    pub contributions: f64,
+    pub interest: f64,
+    pub balance: f64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SavingsSimulation {
+    pub starting_balance: f64,
+    pub monthly: f64,
+    pub years: u32,
+    pub rate: f64,
+    pub final_balance: f64,
+    pub points: Vec<SavingsPoint>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SavingsGoalPlan {
+    pub savings_goal: f64,
+    pub auto_contribution: Option<f64>,
+    pub auto_category_id: Option<i64>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/simulations",
+    request_body = SimulationRequest,
+    responses(
+        (status = 200, body = SimulationResult),
+        (status = 400, description = "Invalid change or unknown category"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Simulations",
+    summary = "Simulate budget changes",
+    description = "Projects the current month's end and the next 12 months with hypothetical changes to income, category spending and recurring payments. The typical month is averaged from recent months. Nothing is saved."
+)]
+pub async fn simulate(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Json(payload): Json<SimulationRequest>,
+) -> Result<Json<SimulationResult>, PaymeError> {
+    payload.validate()?;
+
+    let categories: Vec<(i64, String, bool)> = sqlx::query_as(
+        "SELECT id, label, tracking_only FROM budget_categories WHERE user_id = ? ORDER BY id",
+    )
+    .bind(claims.sub)
+    .fetch_all(&pool)
+    .await?;
+    let known: HashSet<i64> = categories.iter().map(|(id, ..)| *id).collect();
+    if let Some(change) = payload
+        .category_changes
+        .iter()
+        .find(|c| !known.contains(&c.category_id))
+    {
+        return Err(PaymeError::BadRequest(format!(
+            "Unknown category {}",
+            change.category_id
+        )));
+    }
+
+    // The current month is only read, not created, so nothing is written
+    let today = Utc::now().date_naive();
+    let (year, month) = (today.year(), today.month() as i32);
+    let current_id: Option<i64> =
+        sqlx::query_scalar("SELECT id FROM months WHERE user_id = ? AND year = ? AND month = ?")
+            .bind(claims.sub)
+            .bind(year)
+            .bind(month)
+            .fetch_optional(&pool)
+            .await?;
+    let history: Vec<(i64,)> = sqlx::query_as(
+        r#"
+        SELECT id FROM months
+        WHERE user_id = ? AND (year < ? OR (year = ? AND month < ?))
+        ORDER BY year DESC, month DESC
+        LIMIT ?
+        "#,
+    )
+    .bind(claims.sub)
+    .bind(year)
+    .bind(year)
+    .bind(month)
+    .bind(payload.history_months)
+    .fetch_all(&pool)
+    .await?;
+    let history_ids: Vec<i64> = history.into_iter().map(|(id,)| id).collect();
+    let history_count = history_ids.len().max(1) as f64;
+
+    let mut income = 0.0;
+    let mut spent: HashMap<i64, f64> = HashMap::new();
+    for month_id in &history_ids {
+        let month_income: f64 = sqlx::query_scalar(
+            "SELECT COALESCE(SUM(amount), 0.0) FROM income_entries WHERE month_id = ?",
+        )
+        .bind(month_id)
+        .fetch_one(&pool)
+        .await?;
+        income += month_income;
+
+        let by_category: Vec<(i64, f64)> = sqlx::query_as(
+            r#"
+            SELECT i.category_id, SUM(i.amount) FROM items i
+            JOIN budget_categories bc ON i.category_id = bc.id
+            WHERE i.month_id = ? AND i.savings_destination = 'none' AND bc.tracking_only = 0
+            GROUP BY i.category_id
+            "#,
+        )
+        .bind(month_id)
+        .fetch_all(&pool)
+        .await?;
+        for (category_id, amount) in by_category {
+            *spent.entry(category_id).or_default() += amount;
+        }
+    }
+
+    let fixed_expenses: Vec<FixedExpense> = sqlx::query_as(
+        "SELECT id, user_id, label, amount, billing_period, payment_month, payment_day, payment_roll FROM fixed_expenses WHERE user_id = ?",
+    )
+    .bind(claims.sub)
+    .fetch_all(&pool)
+    .await?;
+
+    let baseline = Baseline {
+        income: income / history_count,
+        fixed: fixed_expenses.iter().map(|e| e.monthly_amount()).sum(),
+        // Tracking-only categories don't count against the month, as in the summary
+        categories: categories
+            .into_iter()
+            .filter(|(.., tracking_only)| !tracking_only)
+            .map(|(category_id, label, _)| CategorySpend {
+                category_id,
+                label,
+                monthly: spent.get(&category_id).copied().unwrap_or(0.0) / history_count,
+            })
+            .collect(),
+    };
+    let scenario = Scenario {
+        income_change: payload.income_change,
+        category_changes: payload
+            .category_changes
+            .iter()
+            .map(|c| (c.category_id, c.percent))
+            .collect(),
+        extra_fixed: payload.new_payments.iter().map(|p| p.amount).sum(),
+    };
+    let simulated = baseline.apply(&scenario);
+
+    // The current month uses its own income so far when there is any
+    let current_income: f64 = sqlx::query_scalar(
+        "SELECT COALESCE(SUM(amount), 0.0) FROM income_entries WHERE month_id = ?",
+    )
+    .bind(current_id)
+    .fetch_one(&pool)
+    .await?;
+    let spent_so_far: HashMap<i64, f64> = sqlx::query_as::<_, (i64, f64)>(
+        r#"
+        SELECT i.category_id, SUM(i.amount) FROM items i
+        JOIN budget_categories bc ON i.category_id = bc.id
+        WHERE i.month_id = ? AND i.savings_destination = 'none' AND bc.tracking_only = 0
+        GROUP BY i.category_id
+        "#,
+    )
+    .bind(current_id)
+    .fetch_all(&pool)
+    .await?
+    .into_iter()
+    .collect();
+    let month_income = if current_income > 0.0 {
+        current_income
+    } else {
+        baseline.income
+    };
+    let elapsed = days_elapsed(year, month, today);
+    let days_in_month = days_in_month(year, month);
+
+    let month_end_baseline =
+        baseline.month_end(month_income, &spent_so_far, elapsed, days_in_month);
+    let month_end_simulated = simulated.month_end(
+        month_income + payload.income_change,
+        &spent_so_far,
+        elapsed,
+        days_in_month,
+    );
+
+    let first = today.with_day(1).unwrap_or(today);
+    let projection: Vec<ProjectedMonth> = cumulative(baseline.remaining(), PROJECTION_MONTHS)
+        .into_iter()
+        .zip(cumulative(simulated.remaining(), PROJECTION_MONTHS))
+        .enumerate()
+        .map(|(i, (baseline_balance, simulated_balance))| {
+            let date = first + Months::new(i as u32 + 1);
+            ProjectedMonth {
+                year: date.year(),
+                month: date.month() as i32,
+                baseline_balance,
+                simulated_balance,
+            }
+        })
+        .collect();
+    let difference = projection
+        .last()
+        .map(|p| p.simulated_balance - p.baseline_balance)
+        .unwrap_or(0.0);
+
+    let outcome = |b: &Baseline| MonthOutcome {
+        income: b.income,
+        fixed: b.fixed,
+        spending: b.spending(),
+        remaining: b.remaining(),
+    };
+
+    Ok(Json(SimulationResult {
+        history_months: history_ids.len() as i64,
+        baseline: outcome(&baseline),
+        simulated: outcome(&simulated),
+        categories: baseline
+            .categories
+            .iter()
+            .zip(&simulated.categories)
+            .map(|(before, after)| SimulatedCategory {
+                category_id: before.category_id,
+                label: before.label.clone(),
+                baseline: before.monthly,
+                simulated: after.monthly,
+            })
+            .collect(),
+        month_end_baseline,
+        month_end_simulated,
+        projection,
+        difference,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/simulations/savings",
+    params(SavingsScenario),
+    responses(
+        (status = 200, body = SavingsSimulation),
+        (status = 400, description = "Invalid scenario"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Simulations",
+    summary = "Simulate savings growth",
+    description = "Compounds a monthly contribution on top of the current savings balance, year by year. Nothing is saved."
+)]
+pub async fn simulate_savings(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Query(query): Query<SavingsScenario>,
+) -> Result<Json<SavingsSimulation>, PaymeError> {
+    query.validate()?;
+    Ok(Json(savings_simulation(&pool, claims.sub, &query).await?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/simulations/savings/goal",
+    request_body = SavingsGoalFromScenario,
+    responses(
+        (status = 200, body = SavingsGoalPlan),
+        (status = 400, description = "Invalid scenario or unknown category"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Simulations",
+    summary = "Turn a savings scenario into a goal",
+    description = "Sets the savings goal to the scenario's final balance. With a category, the monthly amount is also booked to savings as an item in every month opened from now on."
+)]
+pub async fn create_savings_goal(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Json(payload): Json<SavingsGoalFromScenario>,
+) -> Result<Json<SavingsGoalPlan>, PaymeError> {
+    payload.validate()?;
+    if let Some(category_id) = payload.category_id {
+        let exists: Option<i64> =
+            sqlx::query_scalar("SELECT id FROM budget_categories WHERE id = ? AND user_id = ?")
+                .bind(category_id)
+                .bind(claims.sub)
+                .fetch_optional(&pool)
+                .await?;
+        if exists.is_none() {
+            return Err(PaymeError::BadRequest(format!(
+                "Unknown category {category_id}"
+            )));
+        }
+    }
+
+    let simulation = savings_simulation(&pool, claims.sub, &payload.scenario).await?;
+    sqlx::query("UPDATE users SET savings_goal = ? WHERE id = ?")
+        .bind(simulation.final_balance)
+        .bind(claims.sub)
+        .execute(&pool)
+        .await?;
+
+    let mut settings = load_settings(&pool, claims.sub).await?;
+    settings.savings_auto_category_id = payload.category_id;
+    settings.savings_auto_contribution = payload
+        .category_id
+        .map(|_| payload.scenario.monthly)
+        .filter(|amount| *amount > 0.0);
+    if settings.savings_auto_contribution.is_none() {
+        settings.savings_auto_category_id = None;
+    }
+    save_settings(&pool, claims.sub, &settings).await?;
+
+    Ok(Json(SavingsGoalPlan {
+        savings_goal: simulation.final_balance,
+        auto_contribution: settings.savings_auto_contribution,
+        auto_category_id: settings.savings_auto_category_id,
+    }))
+}
+
+async fn savings_simulation(
+    pool: &SqlitePool,
+    user_id: i64,
+    scenario: &SavingsScenario,
+) -> Result<SavingsSimulation, PaymeError> {
+    let starting_balance: f64 = sqlx::query_scalar("SELECT savings FROM users WHERE id = ?")
+        .bind(user_id)
+        .fetch_one(pool)
+        .await?;
+
+    let points: Vec<SavingsPoint> = compound(
+        starting_balance,
+        scenario.monthly,
+        scenario.rate,
+        scenario.years,
+    )
+    .into_iter()
+    .zip(1..)
+    .map(|(balance, year)| {
+        let contributions = scenario.monthly * 12.0 * year as f64;
+        SavingsPoint {
+            year,
+            contributions,
+            interest: balance - starting_balance - contributions,
+            balance,
+        }
+    })
+    .collect();
+
+    Ok(SavingsSimulation {
+        starting_balance,
+        monthly: scenario.monthly,
+        years: scenario.years,
+        rate: scenario.rate,
+        final_balance: points.last().map(|p| p.balance).unwrap_or(starting_balance),
+        points,
+    })
+}
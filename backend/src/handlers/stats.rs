@@ -1,25 +1,52 @@
-use axum::{extract::State, Json};
+use axum::extract::{Query, State};
+use serde::Deserialize;
 use sqlx::SqlitePool;
+use utoipa::IntoParams;
 
+use crate::cpi::{self, PriceIndex};
 use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::handlers::analytics::load_seasonality;
 use crate::middleware::auth::Claims;
 use crate::models::{CategoryStats, MonthlyStats, StatsResponse};
 
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct StatsQuery {
+    /// Restate every amount in the prices of the latest CPI reading, see `PUT /api/v1/cpi`.
+    #[serde(default)]
+    pub real_terms: bool,
+}
+
 #[utoipa::path(
     get,
-    path = "/api/stats",
+    path = "/api/v1/stats",
+    params(StatsQuery),
     responses(
         (status = 200, description = "Get financial trends and category comparisons", body = StatsResponse),
+        (status = 400, description = "`real_terms` was asked for without a CPI series"),
         (status = 500, description = "Internal server error")
     ),
     tag = "Insights",
     summary = "Generate financial statistics",
-    description = "Calculates average monthly spending/income, monthly trends (Net income), and month-over-month category performance comparisons."
+    description = "Calculates average monthly spending/income, monthly trends (Net income), and month-over-month category performance comparisons, with the expected spending for seasonal categories. With `real_terms`, amounts are adjusted for inflation so months years apart compare fairly."
 )]
 pub async fn get_stats(
     State(pool): State<SqlitePool>,
     axum::Extension(claims): axum::Extension<Claims>,
+    Query(query): Query<StatsQuery>,
 ) -> Result<Json<StatsResponse>, PaymeError> {
+    let index = if query.real_terms {
+        let readings = cpi::load_readings(&pool, claims.sub).await?;
+        Some(PriceIndex::new(readings).ok_or_else(|| {
+            PaymeError::BadRequest("Add a CPI series to see real terms".to_string())
+        })?)
+    } else {
+        None
+    };
+    let factor = |year: i32, month: i32| index.as_ref().map_or(1.0, |i| i.factor(year, month));
+    let price_base = index.as_ref().map(|i| i.base().clone());
+
     let months: Vec<(i64, i32, i32)> = sqlx::query_as(
         "SELECT id, year, month FROM months WHERE user_id = ? ORDER BY year DESC, month DESC",
     )
@@ -33,6 +60,7 @@ pub async fn get_stats(
             monthly_trends: vec![],
             average_monthly_spending: 0.0,
             average_monthly_income: 0.0,
+            price_base,
         }));
     }
 
@@ -49,28 +77,36 @@ pub async fn get_stats(
         .await?;
 
         let spent: (f64,) =
-            sqlx::query_as("SELECT COALESCE(SUM(amount), 0.0) FROM items WHERE month_id = ? AND savings_destination = 'none'")
+            sqlx::query_as("SELECT COALESCE(SUM(amount), 0.0) FROM items WHERE month_id = ? AND savings_destination = 'none' AND category_id NOT IN (SELECT id FROM budget_categories WHERE tracking_only = 1)")
                 .bind(month_id)
                 .fetch_one(&pool)
                 .await?;
 
         let fixed: (f64,) = sqlx::query_as(
-            "SELECT COALESCE(SUM(amount), 0.0) FROM fixed_expenses WHERE user_id = ?",
+            r#"
+            SELECT COALESCE(SUM(CASE billing_period
+                WHEN 'quarterly' THEN amount / 3
+                WHEN 'yearly' THEN amount / 12
+                ELSE amount END), 0.0)
+            FROM fixed_expenses WHERE user_id = ?
+            "#,
         )
         .bind(claims.sub)
         .fetch_one(&pool)
         .await?;
 
-        total_spending += spent.0;
-        total_income_all += income.0;
+        let factor = factor(*year, *month);
+        let (income, spent, fixed) = (income.0 * factor, spent.0 * factor, fixed.0 * factor);
+        total_spending += spent;
+        total_income_all += income;
 
         monthly_trends.push(MonthlyStats {
             year: *year,
             month: *month,
-            total_income: income.0,
-            total_spent: spent.0,
-            total_fixed: fixed.0,
-            net: income.0 - fixed.0 - spent.0,
+            total_income: income,
+            total_spent: spent,
+            total_fixed: fixed,
+            net: income - fixed - spent,
         });
     }
 
@@ -89,14 +125,17 @@ pub async fn get_stats(
     let mut category_comparisons: Vec<CategoryStats> = vec![];
 
     if !months.is_empty() {
-        let current_month_id = months[0].0;
+        let (current_month_id, current_year, current_month) = months[0];
+        let current_factor = factor(current_year, current_month);
         let previous_month_id = months.get(1).map(|m| m.0);
+        let previous_factor = months.get(1).map_or(1.0, |m| factor(m.1, m.2));
 
         let categories: Vec<(i64, String)> =
             sqlx::query_as("SELECT id, label FROM budget_categories WHERE user_id = ?")
                 .bind(claims.sub)
                 .fetch_all(&pool)
                 .await?;
+        let seasonality = load_seasonality(&pool, claims.sub).await?;
 
         for (cat_id, cat_label) in categories {
             let current_spent: (f64,) = sqlx::query_as(
@@ -115,12 +154,13 @@ pub async fn get_stats(
                 .bind(cat_id)
                 .fetch_one(&pool)
                 .await?;
-                result.0
+                result.0 * previous_factor
             } else {
                 0.0
             };
 
-            let change_amount = current_spent.0 - previous_spent;
+            let current_spent = current_spent.0 * current_factor;
+            let change_amount = current_spent - previous_spent;
             let change_percent = if previous_spent > 0.0 {
                 Some((change_amount / previous_spent) * 100.0)
             } else {
@@ -130,10 +170,14 @@ pub async fn get_stats(
             category_comparisons.push(CategoryStats {
                 category_id: cat_id,
                 category_label: cat_label,
-                current_month_spent: current_spent.0,
+                current_month_spent: current_spent,
                 previous_month_spent: previous_spent,
                 change_amount,
                 change_percent,
+                seasonal_expected: seasonality
+                    .get(&cat_id)
+                    .and_then(|expected| expected.get((current_month - 1) as usize))
+                    .copied(),
             });
         }
     }
@@ -143,5 +187,6 @@ pub async fn get_stats(
         monthly_trends,
         average_monthly_spending,
         average_monthly_income,
+        price_base,
     }))
 }
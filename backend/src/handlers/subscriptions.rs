@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+
+use axum::extract::State;
+use chrono::{Datelike, Months, NaiveDate, Utc};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::middleware::auth::Claims;
+use crate::models::{CancelCandidates, FixedExpense, Subscription, SubscriptionsResponse};
+use crate::subscriptions::{self, Charge};
+
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct TagSubscriptionValue {
+    /// The subscription's `key` from the subscriptions view.
+    #[validate(length(min = 1, max = 200))]
+    pub key: String,
+    /// What it's worth keeping for, e.g. `used every day`. Null or blank removes the
+    /// tag, making it a cancel candidate again.
+    #[validate(length(max = 200))]
+    pub value: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/subscriptions",
+    responses(
+        (status = 200, body = SubscriptionsResponse),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Insights",
+    summary = "List subscriptions",
+    description = "Lists fixed expenses and charges that recur once a month in the last year of spending, with their yearly cost and next expected renewal. Entries whose amount differs from the previous period are flagged as price changes."
+)]
+pub async fn list_subscriptions(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+) -> Result<Json<SubscriptionsResponse>, PaymeError> {
+    let subscriptions = load_subscriptions(&pool, claims.sub).await?;
+    let monthly_total = subscriptions.iter().map(|s| s.monthly_amount).sum();
+    let annual_total = subscriptions.iter().map(|s| s.annual_cost).sum();
+
+    Ok(Json(SubscriptionsResponse {
+        subscriptions,
+        monthly_total,
+        annual_total,
+    }))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/subscriptions/cancel-candidates",
+    responses(
+        (status = 200, body = CancelCandidates),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Insights",
+    summary = "List cancel candidates",
+    description = "Lists subscriptions the user hasn't tagged with a value, most expensive first, with what cancelling all of them would save in a year."
+)]
+pub async fn list_cancel_candidates(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+) -> Result<Json<CancelCandidates>, PaymeError> {
+    let mut candidates: Vec<Subscription> = load_subscriptions(&pool, claims.sub)
+        .await?
+        .into_iter()
+        .filter(|s| s.value.is_none())
+        .collect();
+    candidates.sort_by(|a, b| b.annual_cost.total_cmp(&a.annual_cost));
+    let annual_savings = candidates.iter().map(|s| s.annual_cost).sum();
+
+    Ok(Json(CancelCandidates {
+        candidates,
+        annual_savings,
+    }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/subscriptions/value",
+    request_body = TagSubscriptionValue,
+    responses(
+        (status = 200, body = Subscription),
+        (status = 400, description = "Invalid value"),
+        (status = 404, description = "No subscription with that key"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Insights",
+    summary = "Tag a subscription's value",
+    description = "Records what a subscription is worth to the user, which keeps it off the cancel-candidate report. A blank value removes the tag."
+)]
+pub async fn tag_subscription_value(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Json(payload): Json<TagSubscriptionValue>,
+) -> Result<Json<Subscription>, PaymeError> {
+    payload.validate()?;
+    let mut subscription = load_subscriptions(&pool, claims.sub)
+        .await?
+        .into_iter()
+        .find(|s| s.key == payload.key)
+        .ok_or(PaymeError::NotFound)?;
+
+    let value = payload
+        .value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty());
+    match &value {
+        Some(value) => {
+            sqlx::query(
+                r#"
+                INSERT INTO subscription_values (user_id, key, value, created_at)
+                VALUES (?, ?, ?, ?)
+                ON CONFLICT (user_id, key) DO UPDATE SET value = excluded.value
+                "#,
+            )
+            .bind(claims.sub)
+            .bind(&subscription.key)
+            .bind(value)
+            .bind(Utc::now())
+            .execute(&pool)
+            .await?;
+        }
+        None => {
+            sqlx::query("DELETE FROM subscription_values WHERE user_id = ? AND key = ?")
+                .bind(claims.sub)
+                .bind(&subscription.key)
+                .execute(&pool)
+                .await?;
+        }
+    }
+    subscription.value = value;
+
+    Ok(Json(subscription))
+}
+
+/// Fixed expenses followed by charges recurring in the last year of spending, each
+/// with the value the user tagged it with.
+async fn load_subscriptions(
+    pool: &SqlitePool,
+    user_id: i64,
+) -> Result<Vec<Subscription>, PaymeError> {
+    let today = Utc::now().date_naive();
+
+    let fixed: Vec<FixedExpense> = sqlx::query_as(
+        "SELECT id, user_id, label, amount, billing_period, payment_month, payment_day, payment_roll FROM fixed_expenses WHERE user_id = ? ORDER BY label",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+
+    // Fixed expenses compare against the amount frozen in the latest closed month
+    let previous_amounts: HashMap<i64, f64> = sqlx::query_as(
+        r#"
+        SELECT cf.fixed_expense_id, cf.amount
+        FROM closed_month_fixed_expenses cf
+        JOIN months m ON cf.month_id = m.id
+        WHERE m.user_id = ?
+        ORDER BY m.year, m.month
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .collect();
+
+    let values: HashMap<String, String> =
+        sqlx::query_as("SELECT key, value FROM subscription_values WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .collect();
+
+    let mut subscriptions: Vec<Subscription> = fixed
+        .into_iter()
+        .map(|expense| {
+            let monthly_amount = expense.monthly_amount();
+            let previous_amount = previous_amounts.get(&expense.id).copied();
+            Subscription {
+                source: "fixed_expense".to_string(),
+                key: format!("fixed_expense:{}", expense.id),
+                fixed_expense_id: Some(expense.id),
+                category_label: None,
+                monthly_amount,
+                annual_cost: monthly_amount * 12.0,
+                previous_amount,
+                price_changed: previous_amount
+                    .is_some_and(|previous| subscriptions::price_changed(previous, expense.amount)),
+                last_charged: None,
+                next_renewal: next_payment(&expense, today),
+                value: values
+                    .get(&format!("fixed_expense:{}", expense.id))
+                    .cloned(),
+                label: expense.label,
+            }
+        })
+        .collect();
+
+    let since = today - Months::new(12);
+    let charges: Vec<(String, String, f64, NaiveDate)> = sqlx::query_as(
+        r#"
+        SELECT i.description, bc.label, i.amount, i.spent_on
+        FROM items i
+        JOIN months m ON i.month_id = m.id
+        JOIN budget_categories bc ON i.category_id = bc.id
+        WHERE m.user_id = ? AND i.spent_on >= ? AND i.savings_destination = 'none'
+        "#,
+    )
+    .bind(user_id)
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
+    let charges: Vec<Charge> = charges
+        .into_iter()
+        .map(|(description, category_label, amount, spent_on)| Charge {
+            description,
+            category_label,
+            amount,
+            spent_on,
+        })
+        .collect();
+
+    subscriptions.extend(
+        subscriptions::detect_recurring(&charges, today)
+            .into_iter()
+            .map(|charge| Subscription {
+                source: "item".to_string(),
+                key: item_key(&charge.label),
+                value: values.get(&item_key(&charge.label)).cloned(),
+                fixed_expense_id: None,
+                price_changed: charge.price_changed(),
+                label: charge.label,
+                category_label: Some(charge.category_label),
+                monthly_amount: charge.amount,
+                annual_cost: charge.amount * 12.0,
+                previous_amount: Some(charge.previous_amount),
+                last_charged: Some(charge.last_charged),
+                next_renewal: Some(charge.next_renewal),
+            }),
+    );
+
+    Ok(subscriptions)
+}
+
+/// Recurring charges are told apart by description, ignoring case.
+fn item_key(label: &str) -> String {
+    format!("item:{}", label.to_lowercase())
+}
+
+/// First day of the next month a quarterly or yearly payment falls in, this month
+/// included. Monthly expenses have no single renewal to point at.
+fn next_payment(expense: &FixedExpense, today: NaiveDate) -> Option<NaiveDate> {
+    expense.payment_month?;
+    let this_month = today.with_day(1)?;
+    (0..12)
+        .map(|offset| this_month + Months::new(offset))
+        .find(|date| expense.is_due_in(date.month() as i32))
+}
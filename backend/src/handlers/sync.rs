@@ -0,0 +1,527 @@
+use axum::extract::{Query, State};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Acquire, SqliteConnection, SqlitePool};
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
+
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::handlers::items::{self, CreateItem, UpdateItem};
+use crate::middleware::auth::Claims;
+use crate::models::{BudgetCategory, FixedExpense, IncomeEntry, Item, Month, MonthlyBudget};
+use crate::suggestions;
+
+/// How long deletions and applied mutation IDs are remembered. Clients that have not
+/// synced for longer start over.
+pub const HISTORY_KEPT: Duration = Duration::days(90);
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SyncQuery {
+    /// `cursor` of the previous sync. Leave out to fetch everything.
+    pub since: Option<i64>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SyncDeletion {
+    /// `month`, `category`, `fixed_expense`, `income`, `budget` or `item`.
+    pub entity_type: String,
+    pub id: i64,
+}
+
+#[derive(Serialize, ToSchema, sqlx::FromRow)]
+pub struct VersionedItem {
+    #[serde(flatten)]
+    #[sqlx(flatten)]
+    pub item: Item,
+    /// Changes on every write. Send it as `base_version` when changing the item.
+    pub version: i64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SyncResponse {
+    /// Pass as `since` on the next sync.
+    pub cursor: i64,
+    /// Whether everything was returned rather than changes, because no cursor was given
+    /// or it predates the oldest deletion kept. The client should replace its copy.
+    pub full: bool,
+    pub months: Vec<Month>,
+    pub categories: Vec<BudgetCategory>,
+    pub fixed_expenses: Vec<FixedExpense>,
+    pub income: Vec<IncomeEntry>,
+    pub budgets: Vec<MonthlyBudget>,
+    pub items: Vec<VersionedItem>,
+    /// Entities deleted since the cursor. Deleting a month also deletes its income,
+    /// budgets and items, which are not listed separately.
+    pub deleted: Vec<SyncDeletion>,
+}
+
+/// Most mutations accepted in one batch.
+const MAX_BATCH: usize = 500;
+
+#[derive(Deserialize, ToSchema)]
+pub struct SyncBatch {
+    pub mutations: Vec<SyncMutation>,
+    /// Apply every mutation or none of them. Otherwise the ones that can be applied are.
+    #[serde(default)]
+    pub atomic: bool,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct SyncMutation {
+    /// UUID generated by the client. A mutation sent again with the same one is not
+    /// applied twice.
+    pub client_id: String,
+    #[serde(flatten)]
+    pub change: SyncChange,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum SyncChange {
+    CreateItem {
+        month_id: i64,
+        item: CreateItem,
+        /// Keep the item even if it looks like a duplicate of a recent one.
+        #[serde(default)]
+        force: bool,
+    },
+    UpdateItem {
+        id: i64,
+        /// `version` of the item the client edited.
+        base_version: i64,
+        changes: UpdateItem,
+    },
+    DeleteItem {
+        id: i64,
+        /// `version` of the item the client deleted.
+        base_version: i64,
+    },
+}
+
+#[derive(Serialize, ToSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MutationStatus {
+    Applied,
+    /// The item changed or was deleted on the server since `base_version`.
+    Conflict,
+    /// The mutation is invalid, e.g. the month is closed.
+    Rejected,
+    /// It could be applied, but another mutation of an atomic batch could not.
+    RolledBack,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct MutationResult {
+    pub client_id: String,
+    pub status: MutationStatus,
+    /// The item as stored now: after the mutation when applied, or the server's copy on
+    /// a conflict. Absent once deleted.
+    pub item: Option<VersionedItem>,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct SyncBatchResponse {
+    /// One result per mutation, in the order sent.
+    pub results: Vec<MutationResult>,
+}
+
+/// Why a mutation was not applied.
+enum Refusal {
+    Conflict,
+    Rejected(String),
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/sync",
+    params(SyncQuery),
+    responses(
+        (status = 200, body = SyncResponse),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Sync",
+    summary = "Sync changes",
+    description = "Returns the months, categories, fixed expenses, income, budgets and items created or changed since `since`, and what was deleted, for offline-first clients. Store `cursor` and pass it on the next call."
+)]
+pub async fn sync(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Query(query): Query<SyncQuery>,
+) -> Result<Json<SyncResponse>, PaymeError> {
+    // Everything is read in one transaction so the cursor matches the rows returned
+    let mut tx = pool.begin().await?;
+    let (cursor, pruned_through): (i64, i64) =
+        sqlx::query_as("SELECT version, pruned_through FROM sync_clock")
+            .fetch_one(&mut *tx)
+            .await?;
+    let since = query.since.filter(|since| *since >= pruned_through);
+    let full = since.is_none();
+    let since = since.unwrap_or(-1);
+
+    let response = SyncResponse {
+        cursor,
+        full,
+        months: sqlx::query_as(
+            "SELECT id, user_id, year, month, is_closed, closed_at FROM months WHERE user_id = ? AND version > ? ORDER BY id",
+        )
+        .bind(claims.sub)
+        .bind(since)
+        .fetch_all(&mut *tx)
+        .await?,
+        categories: sqlx::query_as(
+            "SELECT id, user_id, label, default_amount, uuid, default_percent, tracking_only FROM budget_categories WHERE user_id = ? AND version > ? ORDER BY id",
+        )
+        .bind(claims.sub)
+        .bind(since)
+        .fetch_all(&mut *tx)
+        .await?,
+        fixed_expenses: sqlx::query_as(
+            "SELECT id, user_id, label, amount, billing_period, payment_month, payment_day, payment_roll FROM fixed_expenses WHERE user_id = ? AND version > ? ORDER BY id",
+        )
+        .bind(claims.sub)
+        .bind(since)
+        .fetch_all(&mut *tx)
+        .await?,
+        income: sqlx::query_as(
+            r#"
+            SELECT i.id, i.month_id, i.label, i.amount, i.uuid
+            FROM income_entries i JOIN months m ON m.id = i.month_id
+            WHERE m.user_id = ? AND i.version > ?
+            ORDER BY i.id
+            "#,
+        )
+        .bind(claims.sub)
+        .bind(since)
+        .fetch_all(&mut *tx)
+        .await?,
+        budgets: sqlx::query_as(
+            r#"
+            SELECT b.id, b.month_id, b.category_id, b.allocated_amount, b.locked_at, b.lock_reason, b.allocated_percent
+            FROM monthly_budgets b JOIN months m ON m.id = b.month_id
+            WHERE m.user_id = ? AND b.version > ?
+            ORDER BY b.id
+            "#,
+        )
+        .bind(claims.sub)
+        .bind(since)
+        .fetch_all(&mut *tx)
+        .await?,
+        items: sqlx::query_as(
+            r#"
+            SELECT i.id, i.month_id, i.category_id, i.description, i.amount, i.spent_on,
+                   i.savings_destination, i.reimbursement_status, i.tax_rate, i.tax_amount,
+                   i.paid_in_cash, i.uuid, i.version
+            FROM items i JOIN months m ON m.id = i.month_id
+            WHERE m.user_id = ? AND i.version > ?
+            ORDER BY i.id
+            "#,
+        )
+        .bind(claims.sub)
+        .bind(since)
+        .fetch_all(&mut *tx)
+        .await?,
+        deleted: deletions(&mut tx, claims.sub, since, full).await?,
+    };
+    tx.commit().await?;
+
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/sync/batch",
+    request_body = SyncBatch,
+    responses(
+        (status = 200, body = SyncBatchResponse),
+        (status = 400, description = "Empty batch or more than 500 mutations"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Sync",
+    summary = "Apply offline changes",
+    description = "Applies item changes made offline, in order and in one transaction. Updates and deletes carry the `version` the client last saw and conflict if the item changed since. Each mutation gets its own result; with `atomic` nothing is kept unless all of them apply."
+)]
+pub async fn apply_batch(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Json(payload): Json<SyncBatch>,
+) -> Result<Json<SyncBatchResponse>, PaymeError> {
+    if payload.mutations.is_empty() || payload.mutations.len() > MAX_BATCH {
+        return Err(PaymeError::BadRequest(format!(
+            "A batch holds 1 to {MAX_BATCH} mutations"
+        )));
+    }
+
+    let mut tx = pool.begin().await?;
+    let mut results = Vec::with_capacity(payload.mutations.len());
+    for mutation in payload.mutations {
+        let client_id = mutation.client_id;
+        if Uuid::parse_str(&client_id).is_err() {
+            results.push(refused(
+                client_id,
+                Refusal::Rejected("client_id must be a UUID".to_string()),
+                None,
+            ));
+            continue;
+        }
+
+        let applied: Option<i64> = sqlx::query_scalar(
+            "SELECT entity_id FROM sync_mutations WHERE user_id = ? AND client_id = ?",
+        )
+        .bind(claims.sub)
+        .bind(&client_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some(item_id) = applied {
+            let current = load_item(&mut tx, claims.sub, item_id).await?;
+            results.push(applied_result(client_id, current));
+            continue;
+        }
+
+        // Each mutation runs in a savepoint, so a refused one leaves nothing behind
+        let mut savepoint = tx.begin().await?;
+        match apply_change(&mut savepoint, &claims, mutation.change).await? {
+            Ok(item_id) => {
+                sqlx::query(
+                    "INSERT INTO sync_mutations (user_id, client_id, entity_id) VALUES (?, ?, ?)",
+                )
+                .bind(claims.sub)
+                .bind(&client_id)
+                .bind(item_id)
+                .execute(&mut *savepoint)
+                .await?;
+                let current = load_item(&mut savepoint, claims.sub, item_id).await?;
+                savepoint.commit().await?;
+                results.push(applied_result(client_id, current));
+            }
+            Err((refusal, item_id)) => {
+                savepoint.rollback().await?;
+                let current = match item_id {
+                    Some(item_id) => load_item(&mut tx, claims.sub, item_id).await?,
+                    None => None,
+                };
+                results.push(refused(client_id, refusal, current));
+            }
+        }
+    }
+
+    let all_applied = results
+        .iter()
+        .all(|result| result.status == MutationStatus::Applied);
+    if payload.atomic && !all_applied {
+        tx.rollback().await?;
+        for result in &mut results {
+            if result.status == MutationStatus::Applied {
+                result.status = MutationStatus::RolledBack;
+                result.item = None;
+            }
+        }
+    } else {
+        tx.commit().await?;
+        suggestions::invalidate(claims.sub);
+    }
+
+    Ok(Json(SyncBatchResponse { results }))
+}
+
+/// Applies one change and returns the ID of the item it touched. A refused change comes
+/// back with the ID of the item to report, if any. Database failures end the batch.
+async fn apply_change(
+    conn: &mut SqliteConnection,
+    claims: &Claims,
+    change: SyncChange,
+) -> Result<Result<i64, (Refusal, Option<i64>)>, PaymeError> {
+    let result = match change {
+        SyncChange::CreateItem {
+            month_id,
+            item,
+            force,
+        } => items::insert_item(conn, claims, month_id, item, force)
+            .await
+            .map(|created| created.item.id)
+            .map_err(|e| (e, None)),
+        SyncChange::UpdateItem {
+            id,
+            base_version,
+            changes,
+        } => {
+            let month_id = match check_version(conn, claims.sub, id, base_version).await? {
+                Ok(month_id) => month_id,
+                Err(refusal) => return Ok(Err(refusal)),
+            };
+            items::apply_item_update(conn, claims, month_id, id, changes)
+                .await
+                .map(|item| item.id)
+                .map_err(|e| (e, Some(id)))
+        }
+        SyncChange::DeleteItem { id, base_version } => {
+            let month_id = match check_version(conn, claims.sub, id, base_version).await? {
+                Ok(month_id) => month_id,
+                // Deleting an item that is already gone leaves the client's intent done
+                Err((Refusal::Conflict, None)) => return Ok(Ok(id)),
+                Err(refusal) => return Ok(Err(refusal)),
+            };
+            items::remove_item(conn, claims, month_id, id)
+                .await
+                .map(|()| id)
+                .map_err(|e| (e, Some(id)))
+        }
+    };
+
+    match result {
+        Ok(item_id) => Ok(Ok(item_id)),
+        Err((e @ (PaymeError::Database(_) | PaymeError::Internal(_)), _)) => Err(e),
+        Err((e, item_id)) => Ok(Err((Refusal::Rejected(e.to_string()), item_id))),
+    }
+}
+
+/// Month of an item of the user, if it is still at `base_version`.
+async fn check_version(
+    conn: &mut SqliteConnection,
+    user_id: i64,
+    item_id: i64,
+    base_version: i64,
+) -> Result<Result<i64, (Refusal, Option<i64>)>, PaymeError> {
+    let stored: Option<(i64, i64)> = sqlx::query_as(
+        r#"
+        SELECT i.month_id, i.version FROM items i
+        JOIN months m ON m.id = i.month_id
+        WHERE i.id = ? AND m.user_id = ?
+        "#,
+    )
+    .bind(item_id)
+    .bind(user_id)
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(match stored {
+        None => Err((Refusal::Conflict, None)),
+        Some((_, version)) if version != base_version => Err((Refusal::Conflict, Some(item_id))),
+        Some((month_id, _)) => Ok(month_id),
+    })
+}
+
+async fn load_item(
+    conn: &mut SqliteConnection,
+    user_id: i64,
+    item_id: i64,
+) -> Result<Option<VersionedItem>, PaymeError> {
+    Ok(sqlx::query_as(
+        r#"
+        SELECT i.id, i.month_id, i.category_id, i.description, i.amount, i.spent_on,
+               i.savings_destination, i.reimbursement_status, i.tax_rate, i.tax_amount,
+               i.paid_in_cash, i.uuid, i.version
+        FROM items i JOIN months m ON m.id = i.month_id
+        WHERE i.id = ? AND m.user_id = ?
+        "#,
+    )
+    .bind(item_id)
+    .bind(user_id)
+    .fetch_optional(conn)
+    .await?)
+}
+
+fn applied_result(client_id: String, current: Option<VersionedItem>) -> MutationResult {
+    MutationResult {
+        client_id,
+        status: MutationStatus::Applied,
+        item: current,
+        error: None,
+    }
+}
+
+fn refused(client_id: String, refusal: Refusal, current: Option<VersionedItem>) -> MutationResult {
+    let (status, error) = match refusal {
+        Refusal::Conflict => (
+            MutationStatus::Conflict,
+            Some(if current.is_some() {
+                "Item was changed on the server".to_string()
+            } else {
+                "Item was deleted on the server".to_string()
+            }),
+        ),
+        Refusal::Rejected(reason) => (MutationStatus::Rejected, Some(reason)),
+    };
+
+    MutationResult {
+        client_id,
+        status,
+        item: current,
+        error,
+    }
+}
+
+async fn deletions(
+    conn: &mut SqliteConnection,
+    user_id: i64,
+    since: i64,
+    full: bool,
+) -> Result<Vec<SyncDeletion>, PaymeError> {
+    // A full sync replaces the client's copy, so it has nothing to delete
+    if full {
+        return Ok(Vec::new());
+    }
+    let deleted: Vec<(String, i64)> = sqlx::query_as(
+        "SELECT entity_type, entity_id FROM sync_deletions WHERE user_id = ? AND version > ? ORDER BY version",
+    )
+    .bind(user_id)
+    .bind(since)
+    .fetch_all(conn)
+    .await?;
+
+    Ok(deleted
+        .into_iter()
+        .map(|(entity_type, id)| SyncDeletion { entity_type, id })
+        .collect())
+}
+
+/// Checks a UUID chosen by the client for a new entity and returns it in canonical form.
+pub(crate) fn client_uuid(uuid: Option<&str>) -> Result<Option<String>, PaymeError> {
+    uuid.map(|uuid| {
+        Uuid::parse_str(uuid)
+            .map(|uuid| uuid.to_string())
+            .map_err(|_| PaymeError::BadRequest(format!("{uuid} is not a UUID")))
+    })
+    .transpose()
+}
+
+/// Forgets the client IDs of mutations applied before `before`. Returns how many were
+/// forgotten.
+pub async fn prune_mutations(pool: &SqlitePool, before: DateTime<Utc>) -> Result<u64, PaymeError> {
+    Ok(
+        sqlx::query("DELETE FROM sync_mutations WHERE created_at < ?")
+            .bind(before.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string())
+            .execute(pool)
+            .await?
+            .rows_affected(),
+    )
+}
+
+/// Drops deletion records older than `before`. Clients whose cursor predates them get
+/// a full sync next time. Returns how many were dropped.
+pub async fn prune_deletions(pool: &SqlitePool, before: DateTime<Utc>) -> Result<u64, PaymeError> {
+    let mut tx = pool.begin().await?;
+    let newest: Option<i64> =
+        sqlx::query_scalar("SELECT MAX(version) FROM sync_deletions WHERE deleted_at < ?")
+            .bind(before.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string())
+            .fetch_one(&mut *tx)
+            .await?;
+    let Some(newest) = newest else {
+        return Ok(0);
+    };
+
+    let pruned = sqlx::query("DELETE FROM sync_deletions WHERE version <= ?")
+        .bind(newest)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    sqlx::query("UPDATE sync_clock SET pruned_through = MAX(pruned_through, ?)")
+        .bind(newest)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok(pruned)
+}
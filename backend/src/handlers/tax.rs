@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+
+use axum::extract::{Query, State};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use utoipa::IntoParams;
+
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::middleware::auth::Claims;
+use crate::models::{TaxMonthTotal, TaxRateTotal, TaxSummary};
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TaxQuery {
+    pub year: i32,
+    /// Narrow the summary to one month (1-12).
+    pub month: Option<i32>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/tax",
+    params(TaxQuery),
+    responses(
+        (status = 200, body = TaxSummary),
+        (status = 400, description = "Invalid month"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Tax",
+    summary = "VAT/GST summary",
+    description = "Adds up the tax included in items with a tax rate over a year or a single month, per rate and per month. Closed months use the items as they were when the month was closed, matching their PDF."
+)]
+pub async fn get_tax_summary(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Query(query): Query<TaxQuery>,
+) -> Result<Json<TaxSummary>, PaymeError> {
+    if query.month.is_some_and(|m| !(1..=12).contains(&m)) {
+        return Err(PaymeError::BadRequest(
+            "Month must be between 1 and 12".to_string(),
+        ));
+    }
+
+    let rows: Vec<(i32, f64, f64, f64)> = sqlx::query_as(
+        r#"
+        SELECT m.month, x.amount, x.tax_rate, x.tax_amount
+        FROM (
+            SELECT month_id, amount, tax_rate, tax_amount, savings_destination
+            FROM closed_month_items
+            UNION ALL
+            SELECT i.month_id, i.amount, i.tax_rate, i.tax_amount, i.savings_destination
+            FROM items i
+            JOIN months mo ON i.month_id = mo.id
+            WHERE mo.frozen_at IS NULL
+        ) x
+        JOIN months m ON x.month_id = m.id
+        WHERE m.user_id = ? AND m.year = ? AND (? IS NULL OR m.month = ?)
+          AND x.tax_rate IS NOT NULL AND x.savings_destination = 'none'
+        ORDER BY m.month
+        "#,
+    )
+    .bind(claims.sub)
+    .bind(query.year)
+    .bind(query.month)
+    .bind(query.month)
+    .fetch_all(&pool)
+    .await?;
+
+    // Rates are keyed in hundredths of a percent so they can be ordered and grouped
+    let mut rates: BTreeMap<i64, TaxRateTotal> = BTreeMap::new();
+    let mut months: BTreeMap<i32, TaxMonthTotal> = BTreeMap::new();
+    for (month, gross, rate, tax) in rows {
+        let by_rate = rates
+            .entry((rate * 100.0).round() as i64)
+            .or_insert(TaxRateTotal {
+                rate,
+                gross: 0.0,
+                tax: 0.0,
+                net: 0.0,
+                items: 0,
+            });
+        by_rate.gross += gross;
+        by_rate.tax += tax;
+        by_rate.net += gross - tax;
+        by_rate.items += 1;
+
+        let by_month = months.entry(month).or_insert(TaxMonthTotal {
+            month,
+            gross: 0.0,
+            tax: 0.0,
+            net: 0.0,
+        });
+        by_month.gross += gross;
+        by_month.tax += tax;
+        by_month.net += gross - tax;
+    }
+
+    let rates: Vec<TaxRateTotal> = rates.into_values().collect();
+    let gross = rates.iter().map(|r| r.gross).sum();
+    let tax = rates.iter().map(|r| r.tax).sum();
+    let net = rates.iter().map(|r| r.net).sum();
+
+    Ok(Json(TaxSummary {
+        year: query.year,
+        month: query.month,
+        gross,
+        tax,
+        net,
+        rates,
+        months: months.into_values().collect(),
+    }))
+}
@@ -0,0 +1,378 @@
+use axum::extract::State;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{QueryBuilder, Sqlite, SqlitePool};
+use utoipa::{PartialSchema, ToSchema};
+use validator::Validate;
+
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::filters::{Columns, ListFilter};
+use crate::middleware::auth::Claims;
+use crate::summary;
+
+fn default_history_months() -> i64 {
+    12
+}
+
+fn default_search_limit() -> i64 {
+    50
+}
+
+/// A read-only question an assistant can ask, named by `name` with its `arguments`.
+#[derive(Deserialize, ToSchema)]
+#[serde(tag = "name", content = "arguments", rename_all = "snake_case")]
+pub enum ToolCall {
+    MonthSummary(MonthSummaryArgs),
+    CategoryHistory(CategoryHistoryArgs),
+    SearchItems(SearchItemsArgs),
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct MonthSummaryArgs {
+    pub year: i32,
+    #[validate(range(min = 1, max = 12))]
+    pub month: i32,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CategoryHistoryArgs {
+    /// Category label, ignoring case.
+    pub category: String,
+    /// How many of the latest months to include.
+    #[serde(default = "default_history_months")]
+    #[validate(range(min = 1, max = 120))]
+    pub months: i64,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct SearchItemsArgs {
+    /// Text in the description or category label, ignoring case.
+    pub text: Option<String>,
+    /// Category label, ignoring case.
+    pub category: Option<String>,
+    /// First day to include (`YYYY-MM-DD`).
+    pub from: Option<NaiveDate>,
+    /// Last day to include (`YYYY-MM-DD`).
+    pub to: Option<NaiveDate>,
+    pub min_amount: Option<f64>,
+    pub max_amount: Option<f64>,
+    /// Most items to list; `count` and `total` cover every match regardless.
+    #[serde(default = "default_search_limit")]
+    #[validate(range(min = 1, max = 200))]
+    pub limit: i64,
+}
+
+/// A tool as assistants expect it described: what it answers and the JSON schema of
+/// its arguments.
+#[derive(Serialize, ToSchema)]
+pub struct ToolDefinition {
+    pub name: &'static str,
+    pub description: &'static str,
+    #[schema(value_type = Object)]
+    pub input_schema: Value,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(untagged)]
+pub enum ToolResult {
+    MonthSummary(MonthOverview),
+    CategoryHistory(CategoryHistory),
+    SearchItems(ItemSearch),
+}
+
+/// A month's totals and what each category was given and spent, without its items.
+#[derive(Serialize, ToSchema)]
+pub struct MonthOverview {
+    pub year: i32,
+    pub month: i32,
+    pub is_closed: bool,
+    pub total_income: f64,
+    pub total_fixed: f64,
+    pub total_spent: f64,
+    pub remaining: f64,
+    pub categories: Vec<CategorySpending>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CategorySpending {
+    pub category: String,
+    pub allocated: f64,
+    pub spent: f64,
+    /// Spending here is left out of `total_spent` and `remaining`.
+    pub tracking_only: bool,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct CategoryHistory {
+    pub category: String,
+    /// Oldest month first.
+    pub months: Vec<CategoryMonth>,
+}
+
+#[derive(Serialize, ToSchema, sqlx::FromRow)]
+pub struct CategoryMonth {
+    pub year: i32,
+    pub month: i32,
+    pub allocated: f64,
+    pub spent: f64,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ItemSearch {
+    /// Matching items, including those past `limit`.
+    pub count: i64,
+    /// Sum of every matching item.
+    pub total: f64,
+    /// Newest first, at most `limit`.
+    pub items: Vec<FoundItem>,
+}
+
+#[derive(Serialize, ToSchema, sqlx::FromRow)]
+pub struct FoundItem {
+    pub spent_on: NaiveDate,
+    pub description: String,
+    pub category: String,
+    pub amount: f64,
+}
+
+fn input_schema<T: PartialSchema>() -> Value {
+    serde_json::to_value(T::schema()).unwrap_or_default()
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/tools",
+    responses(
+        (status = 200, body = [ToolDefinition])
+    ),
+    tag = "Tools",
+    summary = "List assistant tools",
+    description = "Describes the read-only tools a local LLM assistant can call through `POST /api/v1/tools/call`, with a JSON schema for each one's arguments. The list can be handed to the model as is."
+)]
+pub async fn list_tools() -> Json<Vec<ToolDefinition>> {
+    Json(vec![
+        ToolDefinition {
+            name: "month_summary",
+            description: "Income, fixed costs, spending and what is left for one month, with the budget and spending of each category.",
+            input_schema: input_schema::<MonthSummaryArgs>(),
+        },
+        ToolDefinition {
+            name: "category_history",
+            description: "What one category was budgeted and spent in each of the latest months.",
+            input_schema: input_schema::<CategoryHistoryArgs>(),
+        },
+        ToolDefinition {
+            name: "search_items",
+            description: "Spending items matching a text, category, date range or amount range, with their count and total. Savings transfers are not included.",
+            input_schema: input_schema::<SearchItemsArgs>(),
+        },
+    ])
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/tools/call",
+    request_body = ToolCall,
+    responses(
+        (status = 200, body = ToolResult),
+        (status = 400, description = "Arguments out of range"),
+        (status = 404, description = "The month asked about doesn't exist"),
+        (status = 422, description = "Unknown tool, malformed arguments, or a category the user doesn't have"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Tools",
+    summary = "Call an assistant tool",
+    description = "Runs one of the tools from `GET /api/v1/tools` against the caller's data. Tools only read; nothing is created or changed, so they also answer in read-only mode."
+)]
+pub async fn call_tool(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Json(call): Json<ToolCall>,
+) -> Result<Json<ToolResult>, PaymeError> {
+    let result = match call {
+        ToolCall::MonthSummary(args) => {
+            args.validate()?;
+            ToolResult::MonthSummary(month_overview(&pool, claims.sub, args).await?)
+        }
+        ToolCall::CategoryHistory(args) => {
+            args.validate()?;
+            ToolResult::CategoryHistory(category_history(&pool, claims.sub, args).await?)
+        }
+        ToolCall::SearchItems(args) => {
+            args.validate()?;
+            ToolResult::SearchItems(search_items(&pool, claims.sub, args).await?)
+        }
+    };
+    Ok(Json(result))
+}
+
+async fn month_overview(
+    pool: &SqlitePool,
+    user_id: i64,
+    args: MonthSummaryArgs,
+) -> Result<MonthOverview, PaymeError> {
+    let month_id: i64 =
+        sqlx::query_scalar("SELECT id FROM months WHERE user_id = ? AND year = ? AND month = ?")
+            .bind(user_id)
+            .bind(args.year)
+            .bind(args.month)
+            .fetch_optional(pool)
+            .await?
+            .ok_or(PaymeError::NotFound)?;
+    let summary = summary::month_summary(pool, user_id, month_id, false).await?;
+
+    Ok(MonthOverview {
+        year: summary.month.year,
+        month: summary.month.month,
+        is_closed: summary.month.is_closed,
+        total_income: summary.total_income,
+        total_fixed: summary.total_fixed,
+        total_spent: summary.total_spent,
+        remaining: summary.remaining,
+        categories: summary
+            .budgets
+            .into_iter()
+            .map(|b| CategorySpending {
+                category: b.category_label,
+                allocated: b.allocated_amount,
+                spent: b.spent_amount,
+                tracking_only: b.tracking_only,
+            })
+            .collect(),
+    })
+}
+
+async fn category_history(
+    pool: &SqlitePool,
+    user_id: i64,
+    args: CategoryHistoryArgs,
+) -> Result<CategoryHistory, PaymeError> {
+    let (category_id, category) = find_category(pool, user_id, &args.category).await?;
+    let mut months: Vec<CategoryMonth> = sqlx::query_as(
+        r#"
+        SELECT m.year, m.month,
+               COALESCE((SELECT SUM(mb.allocated_amount) FROM monthly_budgets mb
+                         WHERE mb.month_id = m.id AND mb.category_id = ?), 0.0) AS allocated,
+               COALESCE((SELECT SUM(i.amount) FROM items i
+                         WHERE i.month_id = m.id AND i.category_id = ?
+                           AND i.savings_destination = 'none'), 0.0) AS spent
+        FROM months m
+        WHERE m.user_id = ?
+        ORDER BY m.year DESC, m.month DESC
+        LIMIT ?
+        "#,
+    )
+    .bind(category_id)
+    .bind(category_id)
+    .bind(user_id)
+    .bind(args.months)
+    .fetch_all(pool)
+    .await?;
+    months.reverse();
+
+    Ok(CategoryHistory { category, months })
+}
+
+async fn search_items(
+    pool: &SqlitePool,
+    user_id: i64,
+    args: SearchItemsArgs,
+) -> Result<ItemSearch, PaymeError> {
+    let category_id = match &args.category {
+        Some(label) => Some(find_category(pool, user_id, label).await?.0),
+        None => None,
+    };
+    let filter = ListFilter {
+        category_id,
+        from: args.from,
+        to: args.to,
+        min_amount: args.min_amount,
+        max_amount: args.max_amount,
+        q: args.text,
+    };
+
+    let mut totals = matching_items(
+        "SELECT COUNT(*), COALESCE(SUM(i.amount), 0.0)",
+        user_id,
+        &filter,
+    )?;
+    let (count, total): (i64, f64) = totals.build_query_as().fetch_one(pool).await?;
+
+    let mut query = matching_items(
+        "SELECT i.spent_on, i.description, bc.label AS category, i.amount",
+        user_id,
+        &filter,
+    )?;
+    query
+        .push(" ORDER BY i.spent_on DESC, i.id DESC LIMIT ")
+        .push_bind(args.limit);
+    let items: Vec<FoundItem> = query.build_query_as().fetch_all(pool).await?;
+
+    Ok(ItemSearch {
+        count,
+        total,
+        items,
+    })
+}
+
+/// The user's spending items matching `filter`, selecting `select`.
+fn matching_items<'a>(
+    select: &str,
+    user_id: i64,
+    filter: &ListFilter,
+) -> Result<QueryBuilder<'a, Sqlite>, PaymeError> {
+    let mut query = QueryBuilder::new(format!(
+        r#"
+        {select}
+        FROM items i
+        JOIN months m ON i.month_id = m.id
+        JOIN budget_categories bc ON i.category_id = bc.id
+        WHERE i.savings_destination = 'none' AND m.user_id = "#
+    ));
+    query.push_bind(user_id);
+    filter.apply(
+        &mut query,
+        &Columns {
+            category: Some("i.category_id"),
+            date: Some("i.spent_on"),
+            amount: Some("i.amount"),
+            text: &["i.description", "bc.label"],
+        },
+    )?;
+    Ok(query)
+}
+
+/// Looks a category up by label, ignoring case. An unknown label names the user's
+/// categories so the assistant can retry with one of them.
+async fn find_category(
+    pool: &SqlitePool,
+    user_id: i64,
+    label: &str,
+) -> Result<(i64, String), PaymeError> {
+    let categories: Vec<(i64, String)> =
+        sqlx::query_as("SELECT id, label FROM budget_categories WHERE user_id = ? ORDER BY label")
+            .bind(user_id)
+            .fetch_all(pool)
+            .await?;
+    let label = label.trim();
+    if let Some(found) = categories
+        .iter()
+        .find(|(_, existing)| existing.to_lowercase() == label.to_lowercase())
+    {
+        return Ok(found.clone());
+    }
+
+    let known: Vec<&str> = categories.iter().map(|(_, l)| l.as_str()).collect();
+    Err(PaymeError::InvalidBody {
+        field: Some("arguments.category".to_string()),
+        reason: format!(
+            "No category named {label:?}; the categories are: {}",
+            known.join(", ")
+        ),
+    })
+}
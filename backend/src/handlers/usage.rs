@@ -0,0 +1,118 @@
+use axum::extract::State;
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::Serialize;
+use sqlx::SqlitePool;
+use utoipa::ToSchema;
+
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::middleware::auth::Claims;
+
+#[derive(Serialize, ToSchema)]
+pub struct UsageReport {
+    pub months: i64,
+    pub items: i64,
+    /// PDF snapshots stored for closed months.
+    pub pdf_snapshots: i64,
+    /// Files stored by saved report runs.
+    pub attachments: i64,
+    pub pdf_bytes: i64,
+    pub attachment_bytes: i64,
+    pub total_bytes: i64,
+    /// First day of the earliest tracked month.
+    pub oldest_month: Option<NaiveDate>,
+    pub oldest_item: Option<NaiveDate>,
+    pub member_since: NaiveDateTime,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/usage",
+    responses(
+        (status = 200, body = UsageReport),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Usage",
+    summary = "Get data usage",
+    description = "Counts the months, items and stored files of the account, the bytes its PDF snapshots and report files take up, and how far back its data goes. Sizes are as stored, after encryption."
+)]
+pub async fn get_usage(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+) -> Result<Json<UsageReport>, PaymeError> {
+    Ok(Json(usage_for(&pool, claims.sub).await?))
+}
+
+pub(crate) async fn usage_for(pool: &SqlitePool, user_id: i64) -> Result<UsageReport, PaymeError> {
+    let (months, oldest_year, oldest_month): (i64, Option<i32>, Option<u32>) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*),
+               (SELECT year FROM months WHERE user_id = ? ORDER BY year, month LIMIT 1),
+               (SELECT month FROM months WHERE user_id = ? ORDER BY year, month LIMIT 1)
+        FROM months WHERE user_id = ?
+        "#,
+    )
+    .bind(user_id)
+    .bind(user_id)
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+
+    let (items, oldest_item): (i64, Option<NaiveDate>) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*), MIN(i.spent_on)
+        FROM items i
+        JOIN months m ON i.month_id = m.id
+        WHERE m.user_id = ?
+        "#,
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+
+    let (pdf_snapshots, pdf_bytes): (i64, i64) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*), COALESCE(SUM(s.size), 0)
+        FROM monthly_snapshots s
+        JOIN months m ON s.month_id = m.id
+        WHERE m.user_id = ?
+        "#,
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+
+    let (attachments, attachment_bytes): (i64, i64) = sqlx::query_as(
+        r#"
+        SELECT COUNT(*), COALESCE(SUM(a.size), 0)
+        FROM report_artifacts a
+        JOIN saved_reports r ON a.report_id = r.id
+        WHERE r.user_id = ?
+        "#,
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await?;
+
+    let member_since: NaiveDateTime =
+        sqlx::query_scalar("SELECT created_at FROM users WHERE id = ?")
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or(PaymeError::NotFound)?;
+
+    Ok(UsageReport {
+        months,
+        items,
+        pdf_snapshots,
+        attachments,
+        pdf_bytes,
+        attachment_bytes,
+        total_bytes: pdf_bytes + attachment_bytes,
+        oldest_month: oldest_year
+            .zip(oldest_month)
+            .and_then(|(year, month)| NaiveDate::from_ymd_opt(year, month, 1)),
+        oldest_item,
+        member_since,
+    })
+}
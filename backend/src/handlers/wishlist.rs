@@ -0,0 +1,324 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+};
+use chrono::{NaiveDate, Utc};
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use utoipa::ToSchema;
+use validator::Validate;
+
+use crate::activity;
+use crate::error::PaymeError;
+use crate::extract::Json;
+use crate::handlers::items::verify_category_unlocked;
+use crate::handlers::months::current_month;
+use crate::handlers::settings::money_format;
+use crate::middleware::auth::Claims;
+use crate::models::{Item, WishlistEntry};
+use crate::quotas;
+
+fn default_priority() -> i64 {
+    3
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct CreateWishlistEntry {
+    #[validate(length(min = 1, max = 200))]
+    pub description: String,
+    #[validate(range(min = 0.0), custom(function = "crate::money::amount"))]
+    pub estimated_cost: f64,
+    /// From 1 (buy first) to 5 (can wait), 3 by default.
+    #[serde(default = "default_priority")]
+    #[validate(range(min = 1, max = 5))]
+    pub priority: i64,
+    pub category_id: Option<i64>,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateWishlistEntry {
+    #[validate(length(min = 1, max = 200))]
+    pub description: Option<String>,
+    #[validate(range(min = 0.0), custom(function = "crate::money::amount"))]
+    pub estimated_cost: Option<f64>,
+    #[validate(range(min = 1, max = 5))]
+    pub priority: Option<i64>,
+    pub category_id: Option<i64>,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct PurchaseWishlistEntry {
+    /// What it actually cost, the estimate by default.
+    #[validate(range(min = 0.0), custom(function = "crate::money::amount"))]
+    pub amount: Option<f64>,
+    /// Defaults to today.
+    pub spent_on: Option<NaiveDate>,
+    /// Required when the entry has no target category.
+    pub category_id: Option<i64>,
+}
+
+const SELECT_ENTRY: &str = r#"
+    SELECT w.id, w.description, w.estimated_cost, w.priority, w.category_id,
+           bc.label as category_label, w.created_at
+    FROM wishlist w
+    LEFT JOIN budget_categories bc ON w.category_id = bc.id
+"#;
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/wishlist",
+    responses(
+        (status = 200, body = [WishlistEntry]),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Wishlist",
+    summary = "List planned purchases",
+    description = "Lists wishlist entries by priority, oldest first within the same priority."
+)]
+pub async fn list_wishlist(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+) -> Result<Json<Vec<WishlistEntry>>, PaymeError> {
+    let entries: Vec<WishlistEntry> = sqlx::query_as(&format!(
+        "{SELECT_ENTRY} WHERE w.user_id = ? ORDER BY w.priority, w.created_at, w.id"
+    ))
+    .bind(claims.sub)
+    .fetch_all(&pool)
+    .await?;
+
+    Ok(Json(entries))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/wishlist",
+    request_body = CreateWishlistEntry,
+    responses(
+        (status = 200, body = WishlistEntry),
+        (status = 400, description = "Invalid entry or category"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Wishlist",
+    summary = "Add a planned purchase",
+    description = "Adds something to buy later, optionally planned against a budget category."
+)]
+pub async fn create_wishlist_entry(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Json(payload): Json<CreateWishlistEntry>,
+) -> Result<Json<WishlistEntry>, PaymeError> {
+    payload.validate()?;
+    if let Some(category_id) = payload.category_id {
+        verify_category(&pool, claims.sub, category_id).await?;
+    }
+
+    let id: i64 = sqlx::query_scalar(
+        "INSERT INTO wishlist (user_id, description, estimated_cost, priority, category_id, created_at) VALUES (?, ?, ?, ?, ?, ?) RETURNING id",
+    )
+    .bind(claims.sub)
+    .bind(&payload.description)
+    .bind(payload.estimated_cost)
+    .bind(payload.priority)
+    .bind(payload.category_id)
+    .bind(Utc::now())
+    .fetch_one(&pool)
+    .await?;
+
+    Ok(Json(fetch_entry(&pool, claims.sub, id).await?))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/wishlist/{id}",
+    params(("id" = i64, Path, description = "Wishlist entry ID")),
+    request_body = UpdateWishlistEntry,
+    responses(
+        (status = 200, body = WishlistEntry),
+        (status = 404, description = "Entry not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Wishlist",
+    summary = "Update a planned purchase",
+    description = "Updates the description, estimated cost, priority or target category of a wishlist entry."
+)]
+pub async fn update_wishlist_entry(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(entry_id): Path<i64>,
+    Json(payload): Json<UpdateWishlistEntry>,
+) -> Result<Json<WishlistEntry>, PaymeError> {
+    payload.validate()?;
+    let existing = fetch_entry(&pool, claims.sub, entry_id).await?;
+    if let Some(category_id) = payload.category_id {
+        verify_category(&pool, claims.sub, category_id).await?;
+    }
+
+    sqlx::query(
+        "UPDATE wishlist SET description = ?, estimated_cost = ?, priority = ?, category_id = ? WHERE id = ?",
+    )
+    .bind(payload.description.unwrap_or(existing.description))
+    .bind(payload.estimated_cost.unwrap_or(existing.estimated_cost))
+    .bind(payload.priority.unwrap_or(existing.priority))
+    .bind(payload.category_id.or(existing.category_id))
+    .bind(entry_id)
+    .execute(&pool)
+    .await?;
+
+    Ok(Json(fetch_entry(&pool, claims.sub, entry_id).await?))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/wishlist/{id}",
+    params(("id" = i64, Path, description = "Wishlist entry ID")),
+    responses((status = 204, description = "Deleted")),
+    tag = "Wishlist",
+    summary = "Remove a planned purchase",
+    description = "Drops a wishlist entry without buying it."
+)]
+pub async fn delete_wishlist_entry(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(entry_id): Path<i64>,
+) -> Result<StatusCode, PaymeError> {
+    sqlx::query("DELETE FROM wishlist WHERE id = ? AND user_id = ?")
+        .bind(entry_id)
+        .bind(claims.sub)
+        .execute(&pool)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/wishlist/{id}/purchase",
+    params(("id" = i64, Path, description = "Wishlist entry ID")),
+    request_body = PurchaseWishlistEntry,
+    responses(
+        (status = 200, description = "The item recorded for the purchase", body = Item),
+        (status = 400, description = "No category, or the current month is closed"),
+        (status = 403, description = "Category is locked for this month"),
+        (status = 404, description = "Entry not found"),
+        (status = 409, description = "The entry was bought or removed in the meantime"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "Wishlist",
+    summary = "Buy a planned purchase",
+    description = "Records the entry as an item in the current month, creating the month if needed, and removes it from the wishlist. The amount defaults to the estimate and the category to the entry's target."
+)]
+pub async fn purchase_wishlist_entry(
+    State(pool): State<SqlitePool>,
+    axum::Extension(claims): axum::Extension<Claims>,
+    Path(entry_id): Path<i64>,
+    Json(payload): Json<PurchaseWishlistEntry>,
+) -> Result<Json<Item>, PaymeError> {
+    payload.validate()?;
+    let entry = fetch_entry(&pool, claims.sub, entry_id).await?;
+
+    let category_id = payload.category_id.or(entry.category_id).ok_or_else(|| {
+        PaymeError::BadRequest("A category is required for this purchase".to_string())
+    })?;
+    verify_category(&pool, claims.sub, category_id).await?;
+
+    let month = current_month(&pool, claims.sub).await?;
+    if month.is_closed {
+        return Err(PaymeError::BadRequest("Month is closed".to_string()));
+    }
+    verify_category_unlocked(&pool, month.id, category_id).await?;
+    quotas::check_items(&pool, month.id, 1).await?;
+
+    let amount = payload.amount.unwrap_or(entry.estimated_cost);
+    let spent_on = payload.spent_on.unwrap_or_else(|| Utc::now().date_naive());
+    let money = money_format(&pool, claims.sub).await?;
+
+    let mut tx = pool.begin().await?;
+    // Removing the entry first keeps two requests from both recording the purchase
+    let removed = sqlx::query("DELETE FROM wishlist WHERE id = ? AND user_id = ?")
+        .bind(entry_id)
+        .bind(claims.sub)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    if removed != 1 {
+        return Err(PaymeError::Conflict(
+            "The wishlist entry was bought or removed in the meantime".to_string(),
+        ));
+    }
+
+    let id: i64 = sqlx::query_scalar(
+        "INSERT INTO items (month_id, category_id, description, amount, spent_on, savings_destination, created_at) VALUES (?, ?, ?, ?, ?, 'none', ?) RETURNING id",
+    )
+    .bind(month.id)
+    .bind(category_id)
+    .bind(&entry.description)
+    .bind(amount)
+    .bind(spent_on)
+    .bind(Utc::now())
+    .fetch_one(&mut *tx)
+    .await?;
+
+    activity::record(
+        &mut *tx,
+        &claims,
+        Some(month.id),
+        "item",
+        id,
+        "create",
+        format!(
+            "{} bought {} {} from the wishlist",
+            claims.username,
+            entry.description,
+            money.format(amount)
+        ),
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(Json(Item {
+        id,
+        month_id: month.id,
+        category_id,
+        description: entry.description,
+        amount,
+        spent_on,
+        savings_destination: "none".to_string(),
+        reimbursement_status: None,
+        tax_rate: None,
+        tax_amount: None,
+        paid_in_cash: false,
+        uuid: None,
+        client: None,
+        billable: false,
+    }))
+}
+
+async fn fetch_entry(
+    pool: &SqlitePool,
+    user_id: i64,
+    entry_id: i64,
+) -> Result<WishlistEntry, PaymeError> {
+    sqlx::query_as(&format!("{SELECT_ENTRY} WHERE w.id = ? AND w.user_id = ?"))
+        .bind(entry_id)
+        .bind(user_id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or(PaymeError::NotFound)
+}
+
+async fn verify_category(
+    pool: &SqlitePool,
+    user_id: i64,
+    category_id: i64,
+) -> Result<(), PaymeError> {
+    let _category: (i64,) =
+        sqlx::query_as("SELECT id FROM budget_categories WHERE id = ? AND user_id = ?")
+            .bind(category_id)
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or(PaymeError::BadRequest("Invalid category".to_string()))?;
+    Ok(())
+}
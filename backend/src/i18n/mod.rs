@@ -0,0 +1,11 @@
+//! Message catalogs come from `payme-core`; this adds picking the locale for a request.
+
+use axum::http::{header::ACCEPT_LANGUAGE, HeaderMap};
+
+pub use payme_core::i18n::*;
+
+/// The user's saved locale, else the best match for the request's `Accept-Language`.
+pub fn locale_for_request(preferred: Option<&str>, headers: &HeaderMap) -> Locale {
+    let accept_language = headers.get(ACCEPT_LANGUAGE).and_then(|v| v.to_str().ok());
+    Locale::resolve(preferred, accept_language)
+}
@@ -0,0 +1,430 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::{SqliteExecutor, SqlitePool};
+use tokio::sync::Notify;
+
+use crate::config;
+use crate::connectors::{self, Provider};
+use crate::delivery::{self, Document};
+use crate::error::PaymeError;
+use crate::format::MoneyFormat;
+use crate::handlers::months::{get_month_summary, read_snapshot};
+use crate::handlers::settings::load_settings;
+use crate::i18n::Locale;
+use crate::pdf;
+use crate::reports::{self, ReportJob};
+use crate::seasonality;
+use crate::signing;
+use crate::storage;
+
+/// Renders and stores the PDF snapshot of a closed month.
+pub const MONTH_PDF: &str = "month_pdf";
+
+/// Looks for categories whose spending follows the seasons.
+pub const SEASONALITY: &str = "seasonality";
+
+/// Generates a saved report, stores it and delivers it.
+pub const REPORT: &str = "report";
+
+/// Uploads a closed month's PDF snapshot to the user's WebDAV folder.
+pub const WEBDAV_PUSH: &str = "webdav_push";
+
+/// Uploads a closed month's PDF snapshot to a connected cloud drive.
+pub const CLOUD_PUSH: &str = "cloud_push";
+
+/// A job that fails this many times is marked `failed` instead of being retried.
+const MAX_ATTEMPTS: i64 = 3;
+/// How often the worker looks for jobs when it has not been woken up.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+static WAKE: Notify = Notify::const_new();
+
+#[derive(Serialize, Deserialize)]
+pub struct MonthPdfJob {
+    pub month_id: i64,
+    /// Language tag the report is rendered in.
+    pub locale: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct WebdavPushJob {
+    pub month_id: i64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct CloudPushJob {
+    pub month_id: i64,
+    /// `dropbox` or `google_drive`.
+    pub provider: String,
+}
+
+/// Stores a job and returns its id. Call [`wake`] once the job is committed so the
+/// worker picks it up right away.
+pub async fn enqueue<'e>(
+    executor: impl SqliteExecutor<'e>,
+    user_id: i64,
+    kind: &str,
+    payload: &impl Serialize,
+) -> Result<i64, PaymeError> {
+    let payload =
+        serde_json::to_string(payload).map_err(|e| PaymeError::Internal(e.to_string()))?;
+    let id: i64 = sqlx::query_scalar(
+        "INSERT INTO jobs (user_id, kind, payload, created_at) VALUES (?, ?, ?, ?) RETURNING id",
+    )
+    .bind(user_id)
+    .bind(kind)
+    .bind(payload)
+    .bind(Utc::now())
+    .fetch_one(executor)
+    .await?;
+
+    Ok(id)
+}
+
+/// Tells the worker that new jobs are waiting.
+pub fn wake() {
+    WAKE.notify_one();
+}
+
+/// Runs queued jobs until none are left and returns how many were processed.
+pub async fn run_pending(pool: &SqlitePool) -> Result<usize, sqlx::Error> {
+    let mut processed = 0;
+    while let Some((id, user_id, kind, payload, attempts)) = claim_next(pool).await? {
+        let result = execute(pool, id, user_id, &kind, &payload, attempts).await;
+        finish(pool, id, attempts, result).await?;
+        processed += 1;
+    }
+    Ok(processed)
+}
+
+/// Processes jobs as they are enqueued. Jobs left `running` by a previous process are
+/// queued again on startup.
+pub async fn run_worker(pool: SqlitePool) {
+    if let Err(e) = sqlx::query("UPDATE jobs SET status = 'queued' WHERE status = 'running'")
+        .execute(&pool)
+        .await
+    {
+        tracing::error!("Failed to requeue interrupted jobs: {e}");
+    }
+
+    loop {
+        if let Err(e) = run_pending(&pool).await {
+            tracing::error!("Failed to process jobs: {e}");
+        }
+        tokio::select! {
+            _ = WAKE.notified() => {}
+            _ = tokio::time::sleep(POLL_INTERVAL) => {}
+        }
+    }
+}
+
+async fn claim_next(
+    pool: &SqlitePool,
+) -> Result<Option<(i64, i64, String, String, i64)>, sqlx::Error> {
+    sqlx::query_as(
+        r#"
+        UPDATE jobs SET status = 'running', attempts = attempts + 1
+        WHERE id = (SELECT id FROM jobs WHERE status = 'queued' ORDER BY id LIMIT 1)
+        RETURNING id, user_id, kind, payload, attempts
+        "#,
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+async fn finish(
+    pool: &SqlitePool,
+    id: i64,
+    attempts: i64,
+    result: Result<(), PaymeError>,
+) -> Result<(), sqlx::Error> {
+    let (status, error) = match result {
+        Ok(()) => ("done", None),
+        Err(e) => {
+            tracing::error!("Job {id} failed on attempt {attempts}: {e}");
+            let status = if attempts >= MAX_ATTEMPTS {
+                "failed"
+            } else {
+                "queued"
+            };
+            (status, Some(e.to_string()))
+        }
+    };
+    let finished_at = (status != "queued").then(Utc::now);
+
+    sqlx::query("UPDATE jobs SET status = ?, error = ?, finished_at = ? WHERE id = ?")
+        .bind(status)
+        .bind(error)
+        .bind(finished_at)
+        .bind(id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+async fn execute(
+    pool: &SqlitePool,
+    id: i64,
+    user_id: i64,
+    kind: &str,
+    payload: &str,
+    attempts: i64,
+) -> Result<(), PaymeError> {
+    match kind {
+        MONTH_PDF => {
+            let job: MonthPdfJob =
+                serde_json::from_str(payload).map_err(|e| PaymeError::Internal(e.to_string()))?;
+            generate_month_pdf(pool, user_id, job).await
+        }
+        SEASONALITY => detect_seasonality(pool, user_id).await,
+        REPORT => {
+            let job: ReportJob =
+                serde_json::from_str(payload).map_err(|e| PaymeError::Internal(e.to_string()))?;
+            reports::run_job(pool, id, user_id, job).await
+        }
+        WEBDAV_PUSH => {
+            let job: WebdavPushJob =
+                serde_json::from_str(payload).map_err(|e| PaymeError::Internal(e.to_string()))?;
+            push_to_webdav(pool, user_id, job, attempts).await
+        }
+        CLOUD_PUSH => {
+            let job: CloudPushJob =
+                serde_json::from_str(payload).map_err(|e| PaymeError::Internal(e.to_string()))?;
+            let provider = Provider::parse(&job.provider).ok_or_else(|| {
+                PaymeError::Internal(format!("Unknown provider {}", job.provider))
+            })?;
+            let document = snapshot_document(pool, user_id, job.month_id).await?;
+            connectors::upload_for(pool, user_id, provider, document).await
+        }
+        other => Err(PaymeError::Internal(format!("Unknown job kind {other}"))),
+    }
+}
+
+async fn generate_month_pdf(
+    pool: &SqlitePool,
+    user_id: i64,
+    job: MonthPdfJob,
+) -> Result<(), PaymeError> {
+    let exists: bool =
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM monthly_snapshots WHERE month_id = ?)")
+            .bind(job.month_id)
+            .fetch_one(pool)
+            .await?;
+    if exists {
+        return Ok(());
+    }
+
+    let summary = get_month_summary(pool, user_id, job.month_id).await?.0;
+    let settings = load_settings(pool, user_id).await?;
+    let locale = Locale::from_tag(&job.locale).unwrap_or_default();
+    let money = MoneyFormat::from_settings(&settings);
+
+    // Signed snapshots carry a link to where their signature can be checked
+    let signer = signing::pdf_signer();
+    let code = signer.map(|_| uuid::Uuid::new_v4().simple().to_string());
+    let pdf_data = match &code {
+        Some(code) => {
+            let url = format!(
+                "{}/api/v1/pdf-signatures/{code}",
+                config::public_url().unwrap_or_default()
+            );
+            pdf::generate_verifiable_pdf(&summary, &money, locale, &url)
+        }
+        None => pdf::generate_pdf(&summary, &money, locale),
+    }
+    .map_err(|e| PaymeError::Internal(e.to_string()))?;
+    let signature = signer.map(|signer| signer.sign(&pdf_data));
+
+    let key = storage::snapshot_key(job.month_id);
+    let hash = storage::content_hash(&pdf_data);
+    let size = storage::put_sealed(storage::backend(pool).as_ref(), &key, pdf_data).await?;
+
+    // A month keeps the first snapshot stored for it, so retries and reruns are no-ops
+    let mut tx = pool.begin().await?;
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO monthly_snapshots
+            (month_id, storage_key, size, sha256, signature, signed_by, verification_code)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        ON CONFLICT(month_id) DO NOTHING
+        "#,
+    )
+    .bind(job.month_id)
+    .bind(&key)
+    .bind(size as i64)
+    .bind(&hash)
+    .bind(signature)
+    .bind(signer.map(|signer| signer.public_key()))
+    .bind(code)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected()
+        > 0;
+
+    if inserted && settings.webdav_url.is_some() {
+        sqlx::query("UPDATE monthly_snapshots SET sync_status = 'pending' WHERE month_id = ?")
+            .bind(job.month_id)
+            .execute(&mut *tx)
+            .await?;
+        enqueue(
+            &mut *tx,
+            user_id,
+            WEBDAV_PUSH,
+            &WebdavPushJob {
+                month_id: job.month_id,
+            },
+        )
+        .await?;
+    }
+    if inserted {
+        let providers: Vec<String> = sqlx::query_scalar(
+            "SELECT provider FROM cloud_connections WHERE user_id = ? AND mirror_snapshots = 1",
+        )
+        .bind(user_id)
+        .fetch_all(&mut *tx)
+        .await?;
+        for provider in providers {
+            enqueue(
+                &mut *tx,
+                user_id,
+                CLOUD_PUSH,
+                &CloudPushJob {
+                    month_id: job.month_id,
+                    provider,
+                },
+            )
+            .await?;
+        }
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Uploads the snapshot as `month-YYYY-MM.pdf` and records the outcome on it. The
+/// snapshot is marked `failed` once the job runs out of attempts.
+async fn push_to_webdav(
+    pool: &SqlitePool,
+    user_id: i64,
+    job: WebdavPushJob,
+    attempts: i64,
+) -> Result<(), PaymeError> {
+    let result = upload_snapshot(pool, user_id, job.month_id).await;
+    let (status, error) = match &result {
+        Ok(()) => ("synced", None),
+        Err(_) if attempts < MAX_ATTEMPTS => ("pending", None),
+        Err(e) => ("failed", Some(e.to_string())),
+    };
+    sqlx::query(
+        "UPDATE monthly_snapshots SET sync_status = ?, sync_error = ?, synced_at = ? WHERE month_id = ?",
+    )
+    .bind(status)
+    .bind(error)
+    .bind((status == "synced").then(Utc::now))
+    .bind(job.month_id)
+    .execute(pool)
+    .await?;
+
+    result
+}
+
+async fn upload_snapshot(pool: &SqlitePool, user_id: i64, month_id: i64) -> Result<(), PaymeError> {
+    let url = load_settings(pool, user_id)
+        .await?
+        .webdav_url
+        .ok_or_else(|| PaymeError::BadRequest("No WebDAV folder is set".to_string()))?;
+    delivery::webdav(&url, snapshot_document(pool, user_id, month_id).await?).await
+}
+
+/// A month's PDF snapshot named `month-YYYY-MM.pdf`.
+async fn snapshot_document(
+    pool: &SqlitePool,
+    user_id: i64,
+    month_id: i64,
+) -> Result<Document, PaymeError> {
+    let (year, month): (i32, u32) =
+        sqlx::query_as("SELECT year, month FROM months WHERE id = ? AND user_id = ?")
+            .bind(month_id)
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?
+            .ok_or(PaymeError::NotFound)?;
+    let data = read_snapshot(pool, month_id)
+        .await?
+        .ok_or(PaymeError::NotFound)?;
+
+    Ok(Document {
+        filename: format!("month-{year}-{month:02}.pdf"),
+        content_type: "application/pdf".to_string(),
+        data,
+    })
+}
+
+/// Replaces the user's seasonal expectations with ones detected from every tracked
+/// month, counting months without spending in a category as zero.
+async fn detect_seasonality(pool: &SqlitePool, user_id: i64) -> Result<(), PaymeError> {
+    let months: Vec<(i64, i32)> = sqlx::query_as("SELECT id, month FROM months WHERE user_id = ?")
+        .bind(user_id)
+        .fetch_all(pool)
+        .await?;
+    let categories: Vec<(i64,)> =
+        sqlx::query_as("SELECT id FROM budget_categories WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_all(pool)
+            .await?;
+    let spent: HashMap<(i64, i64), f64> = sqlx::query_as::<_, (i64, i64, f64)>(
+        r#"
+        SELECT i.category_id, i.month_id, SUM(i.amount)
+        FROM items i
+        JOIN months m ON i.month_id = m.id
+        WHERE m.user_id = ? AND i.savings_destination = 'none'
+        GROUP BY i.category_id, i.month_id
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?
+    .into_iter()
+    .map(|(category_id, month_id, amount)| ((category_id, month_id), amount))
+    .collect();
+
+    let mut tx = pool.begin().await?;
+    sqlx::query(
+        "DELETE FROM category_seasonality WHERE category_id IN (SELECT id FROM budget_categories WHERE user_id = ?)",
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    let computed_at = Utc::now();
+    for (category_id,) in categories {
+        let history: Vec<(u32, f64)> = months
+            .iter()
+            .map(|(month_id, month)| {
+                let amount = spent.get(&(category_id, *month_id)).copied();
+                (*month as u32, amount.unwrap_or(0.0))
+            })
+            .collect();
+        let Some(expected) = seasonality::seasonal_expectations(&history) else {
+            continue;
+        };
+        for (month, amount) in (1..).zip(expected) {
+            sqlx::query(
+                "INSERT INTO category_seasonality (category_id, month, expected, computed_at) VALUES (?, ?, ?, ?)",
+            )
+            .bind(category_id)
+            .bind(month)
+            .bind(amount)
+            .bind(computed_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+    tx.commit().await?;
+
+    Ok(())
+}
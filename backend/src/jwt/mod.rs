@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+use std::env;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use chrono::{Duration, Utc};
+use jsonwebtoken::{
+    decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+
+use crate::error::PaymeError;
+
+pub const DEFAULT_SECRET: &str = "payme-secret-key-change-in-production";
+const DEFAULT_LIFETIME_HOURS: i64 = 24 * 30;
+
+/// The key this instance signs tokens with, plus the HMAC secrets it still accepts
+/// after a rotation.
+///
+/// Every token carries a `kid` header. Public keys of asymmetric signing keys are
+/// published to the `jwt_keys` table so that other instances, and this one after a
+/// rotation, keep accepting tokens signed with them until those tokens expire.
+pub struct SigningKeys {
+    algorithm: Algorithm,
+    kid: String,
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    public_pem: Option<String>,
+    previous_secrets: HashMap<String, DecodingKey>,
+    lifetime: Duration,
+}
+
+static KEYS: OnceLock<SigningKeys> = OnceLock::new();
+
+/// Keys configured through the environment, loaded on first use.
+pub fn keys() -> &'static SigningKeys {
+    KEYS.get_or_init(|| {
+        SigningKeys::from_env().unwrap_or_else(|e| panic!("Invalid JWT configuration: {e}"))
+    })
+}
+
+impl SigningKeys {
+    /// Reads `JWT_ALGORITHM` (HS256, RS256 or EdDSA), `JWT_LIFETIME_HOURS`, and either
+    /// `JWT_SECRET`/`JWT_PREVIOUS_SECRETS` or `JWT_PRIVATE_KEY_FILE`/`JWT_PUBLIC_KEY_FILE`.
+    pub fn from_env() -> Result<Self, String> {
+        let lifetime = Duration::hours(
+            env::var("JWT_LIFETIME_HOURS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .filter(|hours| *hours > 0)
+                .unwrap_or(DEFAULT_LIFETIME_HOURS),
+        );
+        let algorithm = env::var("JWT_ALGORITHM").unwrap_or_else(|_| "HS256".to_string());
+
+        match algorithm.to_ascii_uppercase().as_str() {
+            "HS256" => {
+                let secret = env::var("JWT_SECRET").unwrap_or_else(|_| DEFAULT_SECRET.to_string());
+                let previous: Vec<String> = env::var("JWT_PREVIOUS_SECRETS")
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(String::from)
+                    .collect();
+                Ok(Self::hmac(&secret, &previous, lifetime))
+            }
+            "RS256" | "EDDSA" => {
+                let read = |var: &str| {
+                    let path = env::var(var).map_err(|_| format!("{var} is required"))?;
+                    std::fs::read_to_string(&path).map_err(|e| format!("{path}: {e}"))
+                };
+                let algorithm = if algorithm.eq_ignore_ascii_case("RS256") {
+                    Algorithm::RS256
+                } else {
+                    Algorithm::EdDSA
+                };
+                Self::asymmetric(
+                    algorithm,
+                    &read("JWT_PRIVATE_KEY_FILE")?,
+                    &read("JWT_PUBLIC_KEY_FILE")?,
+                    lifetime,
+                )
+            }
+            other => Err(format!("unsupported JWT_ALGORITHM {other}")),
+        }
+    }
+
+    pub fn hmac(secret: &str, previous_secrets: &[String], lifetime: Duration) -> Self {
+        Self {
+            algorithm: Algorithm::HS256,
+            kid: hmac_kid(secret),
+            encoding: EncodingKey::from_secret(secret.as_bytes()),
+            decoding: DecodingKey::from_secret(secret.as_bytes()),
+            public_pem: None,
+            previous_secrets: previous_secrets
+                .iter()
+                .map(|s| (hmac_kid(s), DecodingKey::from_secret(s.as_bytes())))
+                .collect(),
+            lifetime,
+        }
+    }
+
+    pub fn asymmetric(
+        algorithm: Algorithm,
+        private_pem: &str,
+        public_pem: &str,
+        lifetime: Duration,
+    ) -> Result<Self, String> {
+        let (encoding, decoding) = match algorithm {
+            Algorithm::RS256 => (
+                EncodingKey::from_rsa_pem(private_pem.as_bytes()),
+                DecodingKey::from_rsa_pem(public_pem.as_bytes()),
+            ),
+            Algorithm::EdDSA => (
+                EncodingKey::from_ed_pem(private_pem.as_bytes()),
+                DecodingKey::from_ed_pem(public_pem.as_bytes()),
+            ),
+            other => return Err(format!("{other:?} is not an asymmetric algorithm")),
+        };
+
+        Ok(Self {
+            algorithm,
+            kid: fingerprint(public_pem.trim().as_bytes()),
+            encoding: encoding.map_err(|e| format!("invalid private key: {e}"))?,
+            decoding: decoding.map_err(|e| format!("invalid public key: {e}"))?,
+            public_pem: Some(public_pem.trim().to_string()),
+            previous_secrets: HashMap::new(),
+            lifetime,
+        })
+    }
+
+    /// How long newly issued session tokens stay valid.
+    pub fn lifetime(&self) -> Duration {
+        self.lifetime
+    }
+
+    pub fn kid(&self) -> &str {
+        &self.kid
+    }
+
+    pub fn sign<T: Serialize>(&self, claims: &T) -> Result<String, PaymeError> {
+        let mut header = Header::new(self.algorithm);
+        header.kid = Some(self.kid.clone());
+        encode(&header, claims, &self.encoding).map_err(|e| PaymeError::Internal(e.to_string()))
+    }
+
+    /// Checks the signature and expiry of a token signed by the current key, a previous
+    /// HMAC secret, or a key published in `jwt_keys`.
+    pub async fn verify<T: DeserializeOwned>(
+        &self,
+        pool: &SqlitePool,
+        token: &str,
+    ) -> Result<T, PaymeError> {
+        let header = decode_header(token).map_err(|_| PaymeError::Unauthorized)?;
+
+        let (algorithm, key) = match header.kid.as_deref() {
+            Some(kid) if kid == self.kid => (self.algorithm, self.decoding.clone()),
+            // Tokens issued before key ids were introduced
+            None if self.algorithm == Algorithm::HS256 => (self.algorithm, self.decoding.clone()),
+            None => return Err(PaymeError::Unauthorized),
+            Some(kid) => match self.previous_secrets.get(kid) {
+                Some(key) => (Algorithm::HS256, key.clone()),
+                None => published_key(pool, kid).await?,
+            },
+        };
+
+        decode::<T>(token, &key, &Validation::new(algorithm))
+            .map(|data| data.claims)
+            .map_err(|_| PaymeError::Unauthorized)
+    }
+
+    /// Publishes this instance's public key, marks other keys as retired, and removes
+    /// keys retired long enough ago that every token they signed has expired.
+    pub async fn publish(&self, pool: &SqlitePool) -> Result<(), sqlx::Error> {
+        let now = Utc::now();
+        if let Some(public_pem) = &self.public_pem {
+            sqlx::query(
+                r#"
+                INSERT INTO jwt_keys (kid, algorithm, public_key, created_at) VALUES (?, ?, ?, ?)
+                ON CONFLICT(kid) DO UPDATE SET retired_at = NULL
+                "#,
+            )
+            .bind(&self.kid)
+            .bind(format!("{:?}", self.algorithm))
+            .bind(public_pem)
+            .bind(now)
+            .execute(pool)
+            .await?;
+        }
+
+        sqlx::query("UPDATE jwt_keys SET retired_at = ? WHERE kid != ? AND retired_at IS NULL")
+            .bind(now)
+            .bind(&self.kid)
+            .execute(pool)
+            .await?;
+
+        sqlx::query("DELETE FROM jwt_keys WHERE retired_at < ?")
+            .bind(now - self.lifetime)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+async fn published_key(
+    pool: &SqlitePool,
+    kid: &str,
+) -> Result<(Algorithm, DecodingKey), PaymeError> {
+    let (algorithm, public_key): (String, String) =
+        sqlx::query_as("SELECT algorithm, public_key FROM jwt_keys WHERE kid = ?")
+            .bind(kid)
+            .fetch_optional(pool)
+            .await?
+            .ok_or(PaymeError::Unauthorized)?;
+
+    let algorithm = Algorithm::from_str(&algorithm).map_err(|_| PaymeError::Unauthorized)?;
+    let key = match algorithm {
+        Algorithm::RS256 => DecodingKey::from_rsa_pem(public_key.as_bytes()),
+        Algorithm::EdDSA => DecodingKey::from_ed_pem(public_key.as_bytes()),
+        _ => return Err(PaymeError::Unauthorized),
+    }
+    .map_err(|e| PaymeError::Internal(e.to_string()))?;
+
+    Ok((algorithm, key))
+}
+
+/// Random 256-bit HMAC secret, hex encoded.
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Key id for an HMAC secret; hashed so the header does not reveal the secret.
+fn hmac_kid(secret: &str) -> String {
+    fingerprint(format!("hmac:{secret}").as_bytes())
+}
+
+fn fingerprint(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)[..8]
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct TestClaims {
+        sub: i64,
+        exp: usize,
+    }
+
+    fn claims() -> TestClaims {
+        TestClaims {
+            sub: 7,
+            exp: (Utc::now() + Duration::hours(1)).timestamp() as usize,
+        }
+    }
+
+    #[test]
+    fn test_token_carries_kid() {
+        let keys = SigningKeys::hmac("secret", &[], Duration::hours(1));
+        let token = keys.sign(&claims()).unwrap();
+        let header = decode_header(&token).unwrap();
+        assert_eq!(header.kid.as_deref(), Some(keys.kid()));
+        assert_eq!(keys.kid().len(), 16);
+    }
+
+    #[test]
+    fn test_kid_does_not_depend_on_lifetime() {
+        let a = SigningKeys::hmac("secret", &[], Duration::hours(1));
+        let b = SigningKeys::hmac("secret", &[], Duration::hours(2));
+        let c = SigningKeys::hmac("other", &[], Duration::hours(1));
+        assert_eq!(a.kid(), b.kid());
+        assert_ne!(a.kid(), c.kid());
+    }
+
+    #[test]
+    fn test_asymmetric_requires_matching_algorithm() {
+        assert!(SigningKeys::asymmetric(Algorithm::HS256, "", "", Duration::hours(1)).is_err());
+        assert!(
+            SigningKeys::asymmetric(Algorithm::EdDSA, "junk", "junk", Duration::hours(1)).is_err()
+        );
+    }
+}
@@ -1,117 +1,426 @@
+pub mod activity;
+pub mod bulk;
+pub mod categorize;
+pub mod cli;
 pub mod config;
-pub mod db;
+pub mod connectors;
+pub mod crypto;
+pub mod delivery;
+pub mod dry_run;
 pub mod error;
+pub mod extract;
+pub mod feed;
+pub mod filters;
+pub mod frontend;
 pub mod handlers;
+pub mod i18n;
+pub mod jobs;
+pub mod jwt;
+pub mod logging;
 pub mod middleware;
-pub mod models;
+pub mod money;
 pub mod openapi;
-pub mod pdf;
+pub mod quotas;
+pub mod redaction;
+pub mod reports;
+pub mod signing;
+pub mod storage;
+pub mod subscriptions;
+pub mod suggestions;
+#[cfg(feature = "testing")]
+pub mod testing;
 
+// The budget engine lives in `payme-core` so it can be used without the server; its
+// modules keep their paths here
+pub use payme_core::{
+    cpi, db, envelopes, forecast, format, holidays, models, pdf, periods, seasonality, streaks,
+    summary,
+};
+
+use axum::http::HeaderValue;
 use axum::{
-    middleware::from_fn,
+    middleware::{from_fn, from_fn_with_state},
     routing::{delete, get, post, put},
-    Router,
+    Extension, Router,
 };
 use sqlx::SqlitePool;
-use tower_http::cors::{Any, CorsLayer};
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, Any, CorsLayer};
 
 use handlers::{
-    auth, budget, export, fixed_expenses, health, income, items, months, savings, stats,
+    admin, analytics, auth, budget, dashboard, data_quality, export, fixed_expenses, health,
+    income, insights, invoices, iou, items, months, onboarding, pace, plans, projects, retirement,
+    rules, savings, settings, share, stats, wishlist,
 };
 use middleware::auth::auth_middleware;
+use middleware::locale::localize_errors;
+use middleware::maintenance::{read_only_guard, MaintenanceMode};
+use middleware::request_log::{log_requests, skip_request_log};
+use middleware::security::{security_headers, SecurityHeaders};
+use middleware::versioning::{unversioned, versioned, ApiVersion};
+
+/// Any origin may call the API unless `CORS_ALLOWED_ORIGINS` lists them. Credentials
+/// can't be combined with wildcards, so listed origins get the requested methods and
+/// headers echoed back instead.
+fn cors_layer() -> CorsLayer {
+    let origins: Vec<HeaderValue> = config::cors_allowed_origins()
+        .into_iter()
+        .filter_map(|origin| match HeaderValue::from_str(&origin) {
+            Ok(value) => Some(value),
+            Err(_) => {
+                tracing::warn!("Ignoring invalid CORS origin {origin:?}");
+                None
+            }
+        })
+        .collect();
+
+    if origins.is_empty() {
+        if config::cors_allow_credentials() {
+            tracing::warn!("CORS_ALLOW_CREDENTIALS needs CORS_ALLOWED_ORIGINS and is ignored");
+        }
+        return CorsLayer::new()
+            .allow_origin(Any)
+            .allow_methods(Any)
+            .allow_headers(Any)
+            .allow_credentials(false);
+    }
+
+    CorsLayer::new()
+        .allow_origin(AllowOrigin::list(origins))
+        .allow_methods(AllowMethods::mirror_request())
+        .allow_headers(AllowHeaders::mirror_request())
+        .allow_credentials(config::cors_allow_credentials())
+}
 
 /// Create the application router with all routes
 pub fn create_app(pool: SqlitePool) -> Router {
+    let maintenance = MaintenanceMode::from_env();
+
     let public_routes = Router::new()
-        .route("/health", get(health::health_check))
-        .route("/api/auth/register", post(auth::register))
-        .route("/api/auth/login", post(auth::login));
+        .route("/auth/register", post(auth::register))
+        .route("/auth/login", post(auth::login))
+        .route("/shared/{token}", get(share::get_shared_month))
+        .route("/shared/{token}/pdf", get(share::get_shared_month_pdf))
+        .route("/public/stats/{slug}", get(share::get_public_stats))
+        .route(
+            "/connectors/{provider}/callback",
+            get(handlers::connectors::connection_callback),
+        )
+        .route(
+            "/pdf-signatures/{code}",
+            get(handlers::signatures::get_pdf_signature),
+        )
+        .route(
+            "/pdf-signatures/{code}/verify",
+            post(handlers::signatures::verify_pdf_signature),
+        )
+        .route("/signing-key", get(handlers::signatures::get_signing_key))
+        .route("/admin/maintenance", get(admin::get_maintenance))
+        .route("/admin/maintenance", put(admin::update_maintenance))
+        .route("/admin/log-level", get(admin::get_log_level))
+        .route("/admin/log-level", put(admin::update_log_level))
+        .route("/admin/migrations", get(admin::get_migrations))
+        .route("/admin/integrity", get(admin::get_integrity))
+        .route("/admin/integrity/repair", post(admin::repair_integrity))
+        .route("/admin/usage", get(admin::get_usage));
 
     let protected_routes = Router::new()
-        .route("/api/auth/logout", post(auth::logout))
-        .route("/api/auth/me", get(auth::me))
-        .route("/api/auth/change-username", put(auth::change_username))
-        .route("/api/auth/change-password", put(auth::change_password))
-        .route("/api/auth/clear-data", delete(auth::clear_all_data))
-        .route("/api/export", get(auth::export_db))
-        .route("/api/months", get(months::list_months))
+        .route("/auth/logout", post(auth::logout))
+        .route("/auth/me", get(auth::me))
+        .route("/auth/security-events", get(auth::list_security_events))
+        .route("/auth/change-username", put(auth::change_username))
+        .route("/auth/change-password", put(auth::change_password))
+        .route("/auth/clear-data", delete(auth::clear_all_data))
+        .route(
+            "/export",
+            get(auth::export_db).layer(from_fn(skip_request_log)),
+        )
+        .route("/months", get(months::list_months))
+        .route("/months/current", get(months::get_or_create_current_month))
+        .route("/dashboard", get(dashboard::get_dashboard))
+        .route(
+            "/periods/settings",
+            get(handlers::periods::get_period_settings)
+                .put(handlers::periods::update_period_settings),
+        )
+        .route(
+            "/periods/current",
+            get(handlers::periods::get_current_period),
+        )
+        .route("/periods/{date}", get(handlers::periods::get_period))
+        .route("/months/{id}/pace", get(pace::get_month_pace))
+        .route("/months/{id}", get(months::get_month))
+        .route("/months/{id}/close", post(months::close_month))
+        .route("/months/{id}/pdf", get(months::get_month_pdf))
+        .route("/months/{id}/pdf/verify", get(months::verify_month_pdf))
+        .route("/months/{id}/digest", get(months::get_month_digest))
+        .route(
+            "/months/{id}/cashflow",
+            get(handlers::cashflow::get_cashflow),
+        )
+        .route("/months/{id}/export", get(export::export_month))
+        .route("/months/{id}/activity", get(months::list_month_activity))
+        .route("/months/{id}/share", post(share::create_share))
+        .route("/months/{id}/share", delete(share::revoke_shares))
+        .route("/public-stats", post(share::enable_public_stats))
+        .route("/public-stats", delete(share::disable_public_stats))
+        .route(
+            "/fixed-expenses/inflation",
+            get(fixed_expenses::get_fixed_cost_inflation),
+        )
         .route(
-            "/api/months/current",
-            get(months::get_or_create_current_month),
+            "/fixed-expenses/{id}/history",
+            get(fixed_expenses::get_fixed_expense_history),
         )
-        .route("/api/months/{id}", get(months::get_month))
-        .route("/api/months/{id}/close", post(months::close_month))
-        .route("/api/months/{id}/pdf", get(months::get_month_pdf))
+        .route("/fixed-expenses", get(fixed_expenses::list_fixed_expenses))
         .route(
-            "/api/fixed-expenses",
-            get(fixed_expenses::list_fixed_expenses),
+            "/fixed-expenses/bulk",
+            put(fixed_expenses::bulk_update_fixed_expenses),
         )
         .route(
-            "/api/fixed-expenses",
+            "/fixed-expenses",
             post(fixed_expenses::create_fixed_expense),
         )
         .route(
-            "/api/fixed-expenses/{id}",
+            "/fixed-expenses/{id}",
             put(fixed_expenses::update_fixed_expense),
         )
         .route(
-            "/api/fixed-expenses/{id}",
+            "/fixed-expenses/{id}",
             delete(fixed_expenses::delete_fixed_expense),
         )
-        .route("/api/categories", get(budget::list_categories))
-        .route("/api/categories", post(budget::create_category))
-        .route("/api/categories/{id}", put(budget::update_category))
-        .route("/api/categories/{id}", delete(budget::delete_category))
+        .route("/categories", get(budget::list_categories))
+        .route("/categories", post(budget::create_category))
+        .route("/categories/bulk", put(budget::bulk_update_categories))
+        .route("/categories/{id}", put(budget::update_category))
+        .route("/categories/{id}", delete(budget::delete_category))
+        .route("/categories/{id}/rules", get(rules::get_category_rules))
+        .route("/categories/{id}/rules", put(rules::set_category_rules))
+        .route("/months/{id}/budgets", get(budget::list_monthly_budgets))
         .route(
-            "/api/months/{id}/budgets",
-            get(budget::list_monthly_budgets),
+            "/months/{month_id}/budgets/{id}",
+            put(budget::update_monthly_budget),
         )
+        .route("/months/{id}/envelopes", get(budget::get_envelopes))
         .route(
-            "/api/months/{month_id}/budgets/{id}",
-            put(budget::update_monthly_budget),
+            "/months/{month_id}/budgets/{id}/lock",
+            post(budget::lock_monthly_budget),
+        )
+        .route(
+            "/months/{month_id}/budgets/{id}/lock",
+            delete(budget::unlock_monthly_budget),
+        )
+        .route(
+            "/months/{month_id}/budgets/{id}/review",
+            post(budget::review_monthly_budget),
+        )
+        .route("/reviews/{year}", get(budget::get_year_review))
+        .route(
+            "/months/{month_id}/budgets/{id}/earmarks",
+            post(budget::create_earmark),
         )
-        .route("/api/months/{id}/income", get(income::list_income))
-        .route("/api/months/{id}/income", post(income::create_income))
         .route(
-            "/api/months/{month_id}/income/{id}",
-            put(income::update_income),
+            "/months/{month_id}/budgets/{id}/earmarks/{earmark_id}",
+            delete(budget::delete_earmark),
         )
+        .route("/months/{id}/income", get(income::list_income))
+        .route("/months/{id}/income", post(income::create_income))
+        .route("/months/{month_id}/income/{id}", put(income::update_income))
         .route(
-            "/api/months/{month_id}/income/{id}",
+            "/months/{month_id}/income/{id}",
             delete(income::delete_income),
         )
-        .route("/api/months/{id}/items", get(items::list_items))
-        .route("/api/months/{id}/items", post(items::create_item))
-        .route("/api/months/{month_id}/items/{id}", put(items::update_item))
+        .route("/items/suggestions", get(items::suggest_descriptions))
+        .route("/months/{id}/items", get(items::list_items))
+        .route("/months/{id}/items", post(items::create_item))
+        .route(
+            "/months/{id}/items/uncategorized",
+            get(items::list_uncategorized_items),
+        )
+        .route("/months/{month_id}/items/{id}", put(items::update_item))
+        .route("/months/{month_id}/items/{id}", delete(items::delete_item))
+        .route(
+            "/months/{month_id}/items/{id}/splits",
+            get(iou::list_splits),
+        )
+        .route(
+            "/months/{month_id}/items/{id}/splits",
+            post(iou::create_split),
+        )
+        .route(
+            "/months/{month_id}/items/{item_id}/splits/{id}",
+            delete(iou::delete_split),
+        )
+        .route(
+            "/months/{id}/items/mileage",
+            post(handlers::allowances::create_mileage),
+        )
         .route(
-            "/api/months/{month_id}/items/{id}",
-            delete(items::delete_item),
+            "/months/{id}/items/per-diem",
+            post(handlers::allowances::create_per_diem),
         )
-        .route("/api/stats", get(stats::get_stats))
-        .route("/api/savings", get(savings::get_savings))
-        .route("/api/savings", put(savings::update_savings))
-        .route("/api/savings/goal", put(savings::update_savings_goal))
         .route(
-            "/api/retirement-savings",
-            get(savings::get_retirement_savings),
+            "/months/{month_id}/items/{id}/recalculate",
+            post(handlers::allowances::recalculate),
         )
         .route(
-            "/api/retirement-savings",
+            "/months/{month_id}/items/{id}/reimbursement",
+            put(items::update_reimbursement),
+        )
+        .route(
+            "/reimbursements",
+            get(handlers::reimbursements::list_reimbursements),
+        )
+        .route(
+            "/months/{month_id}/items/{id}/project",
+            put(projects::link_item),
+        )
+        .route("/cash", get(handlers::cash::get_cash_report))
+        .route("/iou", get(iou::get_iou))
+        .route("/iou/{id}/repayments", post(iou::record_repayment))
+        .route("/jobs/{id}", get(handlers::jobs::get_job))
+        .route("/sync", get(handlers::sync::sync))
+        .route("/sync/batch", post(handlers::sync::apply_batch))
+        .route("/stats", get(stats::get_stats))
+        .route("/cpi", get(handlers::cpi::get_cpi))
+        .route("/cpi", put(handlers::cpi::set_cpi))
+        .route("/tax", get(handlers::tax::get_tax_summary))
+        .route("/analytics/top", get(analytics::get_top_spending))
+        .route("/analytics/streaks", get(analytics::get_streaks))
+        .route("/analytics/seasonality", get(analytics::get_seasonality))
+        .route(
+            "/analytics/seasonality/refresh",
+            post(analytics::refresh_seasonality),
+        )
+        .route(
+            "/subscriptions",
+            get(handlers::subscriptions::list_subscriptions),
+        )
+        .route(
+            "/subscriptions/cancel-candidates",
+            get(handlers::subscriptions::list_cancel_candidates),
+        )
+        .route(
+            "/subscriptions/value",
+            put(handlers::subscriptions::tag_subscription_value),
+        )
+        .route("/clients/report", get(handlers::clients::get_client_report))
+        .route("/projects", get(projects::list_projects))
+        .route("/projects", post(projects::create_project))
+        .route("/projects/{id}", put(projects::update_project))
+        .route("/projects/{id}", delete(projects::delete_project))
+        .route("/projects/{id}/summary", get(projects::get_project_summary))
+        .route("/wishlist", get(wishlist::list_wishlist))
+        .route("/wishlist", post(wishlist::create_wishlist_entry))
+        .route("/wishlist/{id}", put(wishlist::update_wishlist_entry))
+        .route("/wishlist/{id}", delete(wishlist::delete_wishlist_entry))
+        .route(
+            "/wishlist/{id}/purchase",
+            post(wishlist::purchase_wishlist_entry),
+        )
+        .route("/invoices", get(invoices::list_invoices))
+        .route("/invoices", post(invoices::create_invoice))
+        .route("/invoices/{id}", put(invoices::update_invoice))
+        .route("/invoices/{id}", delete(invoices::delete_invoice))
+        .route(
+            "/invoices/{id}/status",
+            put(invoices::update_invoice_status),
+        )
+        .route("/insights", get(insights::list_insights))
+        .route("/insights/{id}/read", post(insights::mark_insight_read))
+        .route("/insights/{id}/dismiss", post(insights::dismiss_insight))
+        .route("/savings", get(savings::get_savings))
+        .route("/savings", put(savings::update_savings))
+        .route("/savings/goal", put(savings::update_savings_goal))
+        .route("/retirement-savings", get(savings::get_retirement_savings))
+        .route(
+            "/retirement-savings",
             put(savings::update_retirement_savings),
         )
-        .route("/api/export/json", get(export::export_json))
-        .route("/api/import/json", post(export::import_json))
-        .layer(from_fn(auth_middleware));
+        .route("/wealth/history", get(savings::get_wealth_history))
+        .route("/retirement/projection", get(retirement::get_projection))
+        .route("/reports/saved", get(handlers::reports::list_reports))
+        .route("/reports/saved", post(handlers::reports::create_report))
+        .route("/reports/saved/{id}", put(handlers::reports::update_report))
+        .route(
+            "/reports/saved/{id}",
+            delete(handlers::reports::delete_report),
+        )
+        .route(
+            "/reports/saved/{id}/run",
+            post(handlers::reports::run_report),
+        )
+        .route(
+            "/reports/saved/{id}/artifacts",
+            get(handlers::reports::list_artifacts),
+        )
+        .route(
+            "/reports/artifacts/{id}",
+            get(handlers::reports::download_artifact),
+        )
+        .route("/connectors", get(handlers::connectors::list_connections))
+        .route(
+            "/connectors/{provider}/authorize",
+            post(handlers::connectors::authorize_connection),
+        )
+        .route(
+            "/connectors/{provider}",
+            delete(handlers::connectors::delete_connection),
+        )
+        .route("/simulations", post(handlers::simulations::simulate))
+        .route(
+            "/simulations/savings",
+            get(handlers::simulations::simulate_savings),
+        )
+        .route(
+            "/simulations/savings/goal",
+            post(handlers::simulations::create_savings_goal),
+        )
+        .route("/usage", get(handlers::usage::get_usage))
+        .route("/tools", get(handlers::tools::list_tools))
+        .route("/tools/call", post(handlers::tools::call_tool))
+        .route("/settings", get(settings::get_settings))
+        .route("/close-checklist", get(handlers::checklist::get_checklist))
+        .route("/close-checklist", put(handlers::checklist::set_checklist))
+        .route("/settings", put(settings::update_settings))
+        .route("/onboarding", post(onboarding::complete_onboarding))
+        .route("/plans/{year}", get(plans::get_plan))
+        .route("/plans/{year}", post(plans::set_plan))
+        .route(
+            "/maintenance/data-quality",
+            get(data_quality::get_data_quality),
+        )
+        .route(
+            "/maintenance/data-quality/{check}/fix",
+            post(data_quality::fix_data_quality),
+        )
+        .route("/export/json", get(export::export_json))
+        .route("/export/redacted", get(export::export_redacted))
+        .route("/import/json", post(export::import_json))
+        .layer(from_fn_with_state(pool.clone(), auth_middleware));
 
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any)
-        .allow_credentials(false);
+    let v1 = Router::new().merge(public_routes).merge(protected_routes);
 
+    // Unversioned paths predate `/api/v1` and keep serving v1 for existing clients
     Router::new()
-        .merge(public_routes)
-        .merge(protected_routes)
-        .layer(cors)
+        .route("/health", get(health::health_check))
+        .nest(
+            &ApiVersion::V1.prefix(),
+            v1.clone()
+                .layer(from_fn_with_state(ApiVersion::V1, versioned)),
+        )
+        .nest(
+            "/api",
+            v1.layer(from_fn_with_state(ApiVersion::V1, unversioned)),
+        )
+        .fallback(frontend::serve)
+        .layer(from_fn_with_state(maintenance.clone(), read_only_guard))
+        .layer(Extension(maintenance))
+        .layer(from_fn_with_state(pool.clone(), localize_errors))
+        .layer(from_fn_with_state(
+            SecurityHeaders::from_env(),
+            security_headers,
+        ))
+        .layer(cors_layer())
+        .layer(from_fn(log_requests))
         .with_state(pool)
 }
@@ -0,0 +1,30 @@
+use std::sync::{Mutex, OnceLock};
+
+use tracing_subscriber::{filter::LevelFilter, fmt, prelude::*, reload, Registry};
+
+static HANDLE: OnceLock<reload::Handle<LevelFilter, Registry>> = OnceLock::new();
+static LEVEL: Mutex<LevelFilter> = Mutex::new(LevelFilter::INFO);
+
+/// Installs the global subscriber at `level`. The level can be changed later through
+/// [`set_level`] without a restart.
+pub fn init(level: LevelFilter) {
+    let (filter, handle) = reload::Layer::new(level);
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(fmt::layer())
+        .init();
+    HANDLE.set(handle).ok();
+    *LEVEL.lock().unwrap() = level;
+}
+
+pub fn level() -> LevelFilter {
+    *LEVEL.lock().unwrap()
+}
+
+pub fn set_level(level: LevelFilter) -> Result<(), String> {
+    if let Some(handle) = HANDLE.get() {
+        handle.reload(level).map_err(|e| e.to_string())?;
+    }
+    *LEVEL.lock().unwrap() = level;
+    Ok(())
+}
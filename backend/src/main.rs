@@ -1,16 +1,23 @@
-use tower_http::services::ServeDir;
+use std::net::SocketAddr;
 
-use payme::config::Config;
+use payme::config::{self, Config};
 use payme::create_app;
+use payme::crypto;
 use payme::db;
+use payme::feed;
+use payme::frontend;
+use payme::jobs;
+use payme::jwt;
+use payme::logging;
 use payme::openapi::ApiDoc;
+use payme::reports;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
 #[tokio::main]
 async fn main() {
-    tracing_subscriber::fmt::init();
     let config = Config::from_env();
+    logging::init(config::log_level());
     let pool = db::create_pool(&config.database_url)
         .await
         .expect("Failed to create database pool");
@@ -19,9 +26,39 @@ async fn main() {
         .await
         .expect("Failed to run migrations");
 
+    match db::find_orphans(&pool).await {
+        Ok(orphans) if orphans.is_empty() => {}
+        Ok(orphans) => tracing::warn!(
+            "Rows with missing parents found: {orphans:?}. Repair them with POST /api/v1/admin/integrity/repair"
+        ),
+        Err(e) => tracing::error!("Failed to check foreign keys: {e}"),
+    }
+
+    jwt::keys()
+        .publish(&pool)
+        .await
+        .expect("Failed to publish JWT signing key");
+
+    match crypto::encrypt_existing_files(&pool).await {
+        Ok(0) => {}
+        Ok(count) => tracing::info!("Encrypted {} existing stored files", count),
+        Err(e) => panic!("Failed to encrypt existing stored files: {e}"),
+    }
+    match crypto::encrypt_existing_secrets(&pool).await {
+        Ok(0) => {}
+        Ok(count) => tracing::info!("Encrypted {} existing stored secrets", count),
+        Err(e) => panic!("Failed to encrypt existing stored secrets: {e}"),
+    }
+
+    tokio::spawn(feed::run_nightly_refresh(pool.clone()));
+    tokio::spawn(jobs::run_worker(pool.clone()));
+    tokio::spawn(reports::run_scheduler(pool.clone()));
+
     let app = create_app(pool)
-        .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
-        .fallback_service(ServeDir::new("/app/static"));
+        .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()));
+    if !frontend::is_embedded() {
+        tracing::info!("No frontend build embedded, serving the API only");
+    }
 
     let addr = format!("0.0.0.0:{}", config.port);
     tracing::info!("Server running on {}", addr);
@@ -30,10 +67,13 @@ async fn main() {
         .await
         .expect("Failed to bind to address");
 
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await
-        .expect("Server error");
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal())
+    .await
+    .expect("Server error");
 }
 
 async fn shutdown_signal() {
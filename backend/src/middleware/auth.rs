@@ -1,9 +1,15 @@
-use axum::{extract::Request, middleware::Next, response::Response};
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::Response,
+};
 use axum_extra::extract::CookieJar;
-use jsonwebtoken::{decode, DecodingKey, Validation};
 use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
 
 use crate::error::PaymeError;
+use crate::jwt;
+use crate::middleware::request_log::AuthenticatedUser;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
@@ -13,6 +19,7 @@ pub struct Claims {
 }
 
 pub async fn auth_middleware(
+    State(pool): State<SqlitePool>,
     jar: CookieJar,
     mut request: Request,
     next: Next,
@@ -30,16 +37,11 @@ pub async fn auth_middleware(
         })
         .ok_or(PaymeError::Unauthorized)?;
 
-    let secret = std::env::var("JWT_SECRET")
-        .unwrap_or_else(|_| "payme-secret-key-change-in-production".to_string());
+    let claims: Claims = jwt::keys().verify(&pool, &token).await?;
 
-    let token_data = decode::<Claims>(
-        &token,
-        &DecodingKey::from_secret(secret.as_bytes()),
-        &Validation::default(),
-    )
-    .map_err(|_| PaymeError::Unauthorized)?;
-
-    request.extensions_mut().insert(token_data.claims);
-    Ok(next.run(request).await)
+    let user_id = claims.sub;
+    request.extensions_mut().insert(claims);
+    let mut response = next.run(request).await;
+    response.extensions_mut().insert(AuthenticatedUser(user_id));
+    Ok(response)
 }
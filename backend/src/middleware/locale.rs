@@ -0,0 +1,53 @@
+use axum::{
+    extract::{Request, State},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+use sqlx::SqlitePool;
+
+use crate::error::ErrorMessage;
+use crate::i18n;
+use crate::middleware::request_log::AuthenticatedUser;
+
+/// Replaces the empty body of error responses with a message in the user's saved
+/// language, else the one requested through `Accept-Language`. The explanation of
+/// the error, when it has one, is passed on untranslated.
+pub async fn localize_errors(
+    State(pool): State<SqlitePool>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let headers = request.headers().clone();
+    let mut response = next.run(request).await;
+
+    let Some(error) = response.extensions_mut().remove::<ErrorMessage>() else {
+        return response;
+    };
+
+    let saved: Option<String> = match response.extensions().get::<AuthenticatedUser>() {
+        Some(AuthenticatedUser(user_id)) => {
+            sqlx::query_scalar("SELECT locale FROM user_settings WHERE user_id = ?")
+                .bind(user_id)
+                .fetch_optional(&pool)
+                .await
+                .ok()
+                .flatten()
+        }
+        None => None,
+    };
+    let locale = i18n::locale_for_request(saved.as_deref(), &headers);
+
+    let mut body = json!({ "error": locale.text(error.text) });
+    if !error.fields.is_empty() {
+        body["fields"] = json!(error.fields);
+    }
+    if let Some(reason) = error.reason {
+        body["reason"] = json!(reason);
+    }
+    if let Some(duplicate_of) = error.duplicate_of {
+        body["duplicate_of"] = json!(duplicate_of);
+    }
+    (response.status(), Json(body)).into_response()
+}
@@ -0,0 +1,89 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use axum::{
+    extract::{Request, State},
+    http::Method,
+    middleware::Next,
+    response::Response,
+};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+use crate::config;
+use crate::error::PaymeError;
+use crate::middleware::versioning::route_path;
+
+/// Paths that keep accepting writes in read-only mode, so admins can switch it off and
+/// users can still sign in to browse, plus reads that are sent as a POST. Paths are
+/// relative to the API version prefix.
+const ALWAYS_WRITABLE: &[&str] = &[
+    "/admin/maintenance",
+    "/admin/log-level",
+    "/admin/integrity/repair",
+    "/auth/login",
+    "/auth/logout",
+    "/tools/call",
+];
+
+/// Read-only switch shared by the guard middleware and the admin endpoint.
+#[derive(Clone)]
+pub struct MaintenanceMode {
+    read_only: Arc<AtomicBool>,
+    admin_token: Option<Arc<str>>,
+}
+
+impl MaintenanceMode {
+    pub fn new(read_only: bool, admin_token: Option<String>) -> Self {
+        Self {
+            read_only: Arc::new(AtomicBool::new(read_only)),
+            admin_token: admin_token.map(Arc::from),
+        }
+    }
+
+    /// Reads `MAINTENANCE_MODE` and `ADMIN_TOKEN`.
+    pub fn from_env() -> Self {
+        Self::new(config::maintenance_mode(), config::admin_token())
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::SeqCst)
+    }
+
+    pub fn set_read_only(&self, read_only: bool) {
+        self.read_only.store(read_only, Ordering::SeqCst);
+    }
+
+    /// Compares digests in constant time, so neither the token nor its length can be
+    /// worked out from response times.
+    pub fn is_admin(&self, token: Option<&str>) -> bool {
+        match (&self.admin_token, token) {
+            (Some(expected), Some(given)) => Sha256::digest(expected.as_bytes())
+                .ct_eq(&Sha256::digest(given.as_bytes()))
+                .into(),
+            _ => false,
+        }
+    }
+}
+
+/// Rejects requests that may write while the API is read-only.
+pub async fn read_only_guard(
+    State(mode): State<MaintenanceMode>,
+    request: Request,
+    next: Next,
+) -> Result<Response, PaymeError> {
+    let is_read = matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS
+    );
+    if mode.is_read_only()
+        && !is_read
+        && !ALWAYS_WRITABLE.contains(&route_path(request.uri().path()))
+    {
+        return Err(PaymeError::ReadOnly);
+    }
+
+    Ok(next.run(request).await)
+}
@@ -1 +1,6 @@
 pub mod auth;
+pub mod locale;
+pub mod maintenance;
+pub mod request_log;
+pub mod security;
+pub mod versioning;
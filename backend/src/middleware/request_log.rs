@@ -0,0 +1,112 @@
+use std::time::Instant;
+
+use axum::{extract::Request, http::Uri, middleware::Next, response::Response};
+
+/// Set on responses to authenticated requests, so the request log can name the user.
+#[derive(Clone, Copy)]
+pub struct AuthenticatedUser(pub i64);
+
+/// Keeps a response out of the request log.
+#[derive(Clone, Copy)]
+pub struct SkipRequestLog;
+
+/// Query parameters whose values never reach the log.
+const SECRET_PARAMS: &[&str] = &["token", "password", "secret", "key"];
+/// Path segments followed by a secret, like the token of a share link.
+const SECRET_SEGMENTS: &[&str] = &["shared"];
+const REDACTED: &str = "[redacted]";
+
+/// Logs method, path, status, latency and user of every request. Only the URL is
+/// logged, never headers or bodies, so passwords and the token cookie stay out; secrets
+/// in the URL itself are redacted.
+pub async fn log_requests(request: Request, next: Next) -> Response {
+    let method = request.method().clone();
+    let path = redact(request.uri());
+    let started = Instant::now();
+
+    let response = next.run(request).await;
+    if response.extensions().get::<SkipRequestLog>().is_some() {
+        return response;
+    }
+
+    let user_id = response
+        .extensions()
+        .get::<AuthenticatedUser>()
+        .map(|u| u.0);
+    tracing::info!(
+        %method,
+        path,
+        status = response.status().as_u16(),
+        latency_ms = started.elapsed().as_millis() as u64,
+        user_id,
+        "request"
+    );
+    response
+}
+
+/// Route layer for endpoints that must not show up in the request log, such as the
+/// database download.
+pub async fn skip_request_log(request: Request, next: Next) -> Response {
+    let mut response = next.run(request).await;
+    response.extensions_mut().insert(SkipRequestLog);
+    response
+}
+
+fn redact(uri: &Uri) -> String {
+    let mut redact_next = false;
+    let path: Vec<&str> = uri
+        .path()
+        .split('/')
+        .map(|segment| {
+            let segment = if redact_next { REDACTED } else { segment };
+            redact_next = SECRET_SEGMENTS.contains(&segment);
+            segment
+        })
+        .collect();
+    let mut redacted = path.join("/");
+
+    if let Some(query) = uri.query() {
+        let params: Vec<String> = query
+            .split('&')
+            .map(|param| match param.split_once('=') {
+                Some((name, _))
+                    if SECRET_PARAMS
+                        .iter()
+                        .any(|secret| name.to_ascii_lowercase().contains(secret)) =>
+                {
+                    format!("{name}={REDACTED}")
+                }
+                _ => param.to_string(),
+            })
+            .collect();
+        redacted.push('?');
+        redacted.push_str(&params.join("&"));
+    }
+    redacted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redacted(uri: &str) -> String {
+        redact(&uri.parse().unwrap())
+    }
+
+    #[test]
+    fn test_redacts_share_tokens() {
+        assert_eq!(
+            redacted("/api/v1/shared/abc123/pdf"),
+            "/api/v1/shared/[redacted]/pdf"
+        );
+        assert_eq!(redacted("/api/v1/months/4"), "/api/v1/months/4");
+    }
+
+    #[test]
+    fn test_redacts_secret_query_params() {
+        assert_eq!(
+            redacted("/api/v1/stats?year=2024&access_token=abc&Password=x"),
+            "/api/v1/stats?year=2024&access_token=[redacted]&Password=[redacted]"
+        );
+    }
+}
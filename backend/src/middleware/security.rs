@@ -0,0 +1,74 @@
+use axum::{
+    extract::{Request, State},
+    http::{
+        header::{
+            CONTENT_SECURITY_POLICY, REFERRER_POLICY, STRICT_TRANSPORT_SECURITY,
+            X_CONTENT_TYPE_OPTIONS,
+        },
+        HeaderValue,
+    },
+    middleware::Next,
+    response::Response,
+};
+
+use crate::config;
+
+/// Policy for the embedded frontend and API responses unless `CONTENT_SECURITY_POLICY`
+/// replaces it. Allows the Google Fonts stylesheet the frontend loads, and inline style
+/// attributes.
+pub const DEFAULT_CONTENT_SECURITY_POLICY: &str = "default-src 'self'; img-src 'self' data:; style-src 'self' 'unsafe-inline' https://fonts.googleapis.com; font-src 'self' https://fonts.gstatic.com; object-src 'none'; base-uri 'self'; frame-ancestors 'none'";
+
+/// Set by PDF downloads instead of the default, so a PDF opened in the browser can't
+/// run scripts or load anything.
+pub const PDF_CONTENT_SECURITY_POLICY: &str = "default-src 'none'; frame-ancestors 'none'; sandbox";
+
+/// Header values computed once at startup.
+#[derive(Clone)]
+pub struct SecurityHeaders {
+    content_security_policy: HeaderValue,
+    strict_transport_security: Option<HeaderValue>,
+}
+
+impl SecurityHeaders {
+    pub fn from_env() -> Self {
+        let policy = config::content_security_policy();
+        let content_security_policy = HeaderValue::from_str(&policy).unwrap_or_else(|_| {
+            tracing::warn!("Ignoring invalid CONTENT_SECURITY_POLICY");
+            HeaderValue::from_static(DEFAULT_CONTENT_SECURITY_POLICY)
+        });
+
+        let max_age = config::hsts_max_age();
+        let strict_transport_security = (max_age > 0).then(|| {
+            HeaderValue::from_str(&format!("max-age={max_age}; includeSubDomains"))
+                .expect("max-age is a valid header value")
+        });
+
+        Self {
+            content_security_policy,
+            strict_transport_security,
+        }
+    }
+}
+
+/// Adds HSTS, `nosniff`, a referrer policy and the content security policy to every
+/// response. Handlers that set their own `Content-Security-Policy` keep it.
+pub async fn security_headers(
+    State(security): State<SecurityHeaders>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+
+    headers.insert(X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+    // Share links carry their token in the URL, which must not leak to other sites
+    headers.insert(REFERRER_POLICY, HeaderValue::from_static("no-referrer"));
+    if let Some(hsts) = security.strict_transport_security {
+        headers.insert(STRICT_TRANSPORT_SECURITY, hsts);
+    }
+    headers
+        .entry(CONTENT_SECURITY_POLICY)
+        .or_insert(security.content_security_policy);
+
+    response
+}
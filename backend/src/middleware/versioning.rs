@@ -0,0 +1,134 @@
+use axum::{
+    extract::{OriginalUri, Request, State},
+    http::HeaderValue,
+    middleware::Next,
+    response::Response,
+};
+
+use crate::error::PaymeError;
+
+/// Request header clients use to ask for a version, and response header naming the
+/// version that served the request.
+pub const VERSION_HEADER: &str = "API-Version";
+
+/// API version a request is served by. Handlers read it from the request extensions
+/// when a later version changes their behaviour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApiVersion(pub u32);
+
+impl ApiVersion {
+    pub const V1: ApiVersion = ApiVersion(1);
+
+    /// Mount point of this version's routes.
+    pub fn prefix(self) -> String {
+        format!("/api/v{}", self.0)
+    }
+}
+
+/// Tags requests under `/api/v{n}` with their version and echoes it in `API-Version`.
+/// An `API-Version` request header naming a different version is rejected rather than
+/// silently answered with another version's payloads.
+pub async fn versioned(
+    State(version): State<ApiVersion>,
+    request: Request,
+    next: Next,
+) -> Result<Response, PaymeError> {
+    if let Some(requested) = requested_version(&request)? {
+        if requested != version {
+            return Err(PaymeError::BadRequest(format!(
+                "{} does not serve API version {}",
+                version.prefix(),
+                requested.0
+            )));
+        }
+    }
+
+    Ok(serve(version, request, next).await)
+}
+
+/// Serves the unversioned `/api` paths as the given version and marks them deprecated,
+/// pointing clients to the versioned path through a `Link` header.
+pub async fn unversioned(
+    State(version): State<ApiVersion>,
+    OriginalUri(uri): OriginalUri,
+    request: Request,
+    next: Next,
+) -> Result<Response, PaymeError> {
+    if let Some(requested) = requested_version(&request)? {
+        if requested != version {
+            return Err(PaymeError::BadRequest(format!(
+                "API version {} is only served under {}",
+                requested.0,
+                requested.prefix()
+            )));
+        }
+    }
+
+    let successor = uri
+        .path()
+        .strip_prefix("/api")
+        .map(|rest| format!("<{}{}>; rel=\"successor-version\"", version.prefix(), rest));
+
+    let mut response = serve(version, request, next).await;
+    let headers = response.headers_mut();
+    headers.insert("Deprecation", HeaderValue::from_static("true"));
+    if let Some(link) = successor.and_then(|link| HeaderValue::from_str(&link).ok()) {
+        headers.insert("Link", link);
+    }
+    Ok(response)
+}
+
+/// Strips the `/api` or `/api/v{n}` prefix, leaving the path as routed within a version.
+pub fn route_path(path: &str) -> &str {
+    let Some(rest) = path.strip_prefix("/api") else {
+        return path;
+    };
+    rest.strip_prefix("/v")
+        .and_then(|versioned| {
+            let digits = versioned.find('/').unwrap_or(versioned.len());
+            let (number, rest) = versioned.split_at(digits);
+            number.parse::<u32>().ok().map(|_| rest)
+        })
+        .unwrap_or(rest)
+}
+
+fn requested_version(request: &Request) -> Result<Option<ApiVersion>, PaymeError> {
+    let Some(value) = request.headers().get(VERSION_HEADER) else {
+        return Ok(None);
+    };
+    value
+        .to_str()
+        .ok()
+        .map(|v| v.trim().trim_start_matches(['v', 'V']))
+        .and_then(|v| v.parse().ok())
+        .map(|number| Some(ApiVersion(number)))
+        .ok_or_else(|| PaymeError::BadRequest(format!("Invalid {VERSION_HEADER} header")))
+}
+
+async fn serve(version: ApiVersion, mut request: Request, next: Next) -> Response {
+    request.extensions_mut().insert(version);
+    let mut response = next.run(request).await;
+    response
+        .headers_mut()
+        .insert(VERSION_HEADER, HeaderValue::from(version.0));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route_path() {
+        assert_eq!(route_path("/api/v1/auth/login"), "/auth/login");
+        assert_eq!(route_path("/api/v12/months"), "/months");
+        assert_eq!(route_path("/api/auth/login"), "/auth/login");
+        assert_eq!(route_path("/api/vacations"), "/vacations");
+        assert_eq!(route_path("/health"), "/health");
+    }
+
+    #[test]
+    fn test_prefix() {
+        assert_eq!(ApiVersion::V1.prefix(), "/api/v1");
+    }
+}
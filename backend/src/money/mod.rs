@@ -0,0 +1,57 @@
+use validator::ValidationError;
+
+use crate::config;
+
+/// Validates an amount of money from a payload: it must be finite, have at most two
+/// decimal places and stay within `MAX_AMOUNT` either way. Used as
+/// `#[validate(custom(function = "crate::money::amount"))]` next to each field's own
+/// range, so the error names the offending field.
+pub fn amount(value: f64) -> Result<(), ValidationError> {
+    if !value.is_finite() {
+        return Err(ValidationError::new("finite"));
+    }
+    if value.abs() > config::max_amount() {
+        return Err(ValidationError::new("range"));
+    }
+    // `Display` prints the shortest form that reads back as the same number, never
+    // in exponent notation, so its decimals are the ones the client sent
+    let decimals = value
+        .to_string()
+        .split_once('.')
+        .map_or(0, |(_, fraction)| fraction.len());
+    if decimals > 2 {
+        return Err(ValidationError::new("decimals"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_accepts_cents() {
+        assert!(amount(0.0).is_ok());
+        assert!(amount(19.99).is_ok());
+        assert!(amount(-5.5).is_ok());
+        assert!(amount(999_999_999.99).is_ok());
+    }
+
+    #[test]
+    fn test_rejects_non_finite() {
+        assert_eq!(amount(f64::NAN).unwrap_err().code, "finite");
+        assert_eq!(amount(f64::INFINITY).unwrap_err().code, "finite");
+    }
+
+    #[test]
+    fn test_rejects_fractions_of_cents() {
+        assert_eq!(amount(0.001).unwrap_err().code, "decimals");
+        assert_eq!(amount(10.125).unwrap_err().code, "decimals");
+    }
+
+    #[test]
+    fn test_rejects_absurd_magnitudes() {
+        assert_eq!(amount(1e308).unwrap_err().code, "range");
+        assert_eq!(amount(-1e12).unwrap_err().code, "range");
+    }
+}
@@ -1,38 +1,189 @@
-use utoipa::OpenApi;
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
 
+use crate::bulk::{BulkChange, BulkEdit, BulkEditResult, Relabel};
+use crate::dry_run::{DryRunReport, TableChanges};
 use crate::handlers::{
+    admin::{
+        IntegrityRepair, IntegrityReport, LogLevel, MaintenanceStatus, MigrationInfo,
+        MigrationStatus, UpdateMaintenance, UserUsage,
+    },
+    allowances::{CalculatedItem, CreateMileage, CreatePerDiem, Recalculate},
+    analytics::SeasonalityRefresh,
     auth::{AuthRequest, AuthResponse},
-    budget::{CreateCategory, UpdateCategory, UpdateMonthlyBudget},
+    budget::{
+        CategoryYearReview, CreateCategory, CreateEarmark, LockBudget, MonthReview, ReviewBudget,
+        UpdateCategory, UpdateMonthlyBudget, YearReview,
+    },
+    cashflow::CashflowEntry,
+    checklist::SetChecklist,
+    clients::{ClientMonth, ClientReport},
+    connectors::{CloudAuthorization, ConnectCloud},
+    cpi::SetCpi,
+    dashboard::{Dashboard, DashboardMonth, MonthComparison, UpcomingFixedExpense},
+    data_quality::DataQualityFix,
     export::{
         BudgetExport, CategoryExport, FixedExpenseExport, IncomeExport, ItemExport, MonthExport,
         UserExport,
     },
     fixed_expenses::{CreateFixedExpense, UpdateFixedExpense},
     income::{CreateIncome, UpdateIncome},
-    items::{CreateItem, UpdateItem},
+    invoices::{CreateInvoice, UpdateInvoice, UpdateInvoiceStatus},
+    iou::{CreateSplit, RecordRepayment},
+    items::{CreateItem, CreateItemResponse, DuplicateItem, UpdateItem, UpdateReimbursement},
+    months::{CloseMonth, CloseMonthResponse, PdfVerification},
+    onboarding::OnboardingRequest,
+    periods::UpdatePeriodSettings,
+    plans::{PlannedCategory, SetYearPlan},
+    projects::{CreateProject, LinkItemProject, UpdateProject},
+    reports::{ReportRun, SaveReport},
+    retirement::{ProjectionPoint, RetirementProjection},
+    rules::{NewCategoryRule, SetCategoryRules},
     savings::{RetirementSavingsResponse, SavingsResponse, UpdateRetirementSavings, UpdateSavings},
+    settings::UpdateSettings,
+    share::{CategoryShare, CreateShare, PublicStats, PublicStatsLink, ShareResponse},
+    signatures::{PdfSignature, PdfSignatureCheck, SigningKey},
+    simulations::{
+        CategoryChange, MonthOutcome, NewPayment, ProjectedMonth, SavingsGoalFromScenario,
+        SavingsGoalPlan, SavingsPoint, SavingsScenario, SavingsSimulation, SimulatedCategory,
+        SimulationRequest, SimulationResult,
+    },
+    subscriptions::TagSubscriptionValue,
+    sync::{
+        MutationResult, MutationStatus, SyncBatch, SyncBatchResponse, SyncChange, SyncDeletion,
+        SyncMutation, SyncResponse, VersionedItem,
+    },
+    tools::{
+        CategoryHistory, CategoryHistoryArgs, CategoryMonth, CategorySpending, FoundItem,
+        ItemSearch, MonthOverview, MonthSummaryArgs, SearchItemsArgs, ToolCall, ToolDefinition,
+        ToolResult,
+    },
+    usage::UsageReport,
+    wishlist::{CreateWishlistEntry, PurchaseWishlistEntry, UpdateWishlistEntry},
 };
 use crate::models::{
-    BudgetCategory, CategoryStats, FixedExpense, IncomeEntry, Item, ItemWithCategory, Month,
-    MonthSummary, MonthlyBudget, MonthlyStats, StatsResponse,
+    ActivityEntry, ActivityPage, AuthEvent, BudgetCategory, BudgetPeriodSettings, BudgetReview,
+    CancelCandidates, CashMonth, CashReport, CategoryPace, CategoryPlan, CategoryRule,
+    CategoryStats, ChecklistAcknowledgement, ChecklistEntry, CloudConnection, CoverSuggestion,
+    CpiReading, DailySpend, DataQualityReport, DescriptionStats, DescriptionSuggestion, Earmark,
+    Envelope, EnvelopesResponse, FixedCostInflation, FixedExpense, FixedExpenseInflation,
+    FixedExpensePrice, IncomeEntry, Insight, InsightsResponse, Invoice, IouEntry, IouReport, Item,
+    ItemCalculation, ItemSplit, ItemWithCategory, Job, Month, MonthDigest, MonthMetrics,
+    MonthNoSpend, MonthPace, MonthSummary, MonthlyBudget, MonthlyStats, PeriodCategory,
+    PeriodSummary, PersonIou, Project, ProjectMonth, ProjectSummary, QualityFinding,
+    ReimbursementsReport, ReportArtifact, ReportSpec, SavedReport, SeasonalCategory,
+    SeasonalityResponse, StatsResponse, StreaksResponse, Subscription, SubscriptionsResponse,
+    TaxMonthTotal, TaxRateTotal, TaxSummary, TopSpendingResponse, UserSettings, WealthSnapshot,
+    WishlistEntry, YearPlan,
 };
+use crate::redaction::{AmountRedaction, DateRedaction, TextRedaction};
+
+/// Session JWT in the `token` cookie, set by login. Used by the web app.
+pub const COOKIE_AUTH: &str = "cookie_auth";
+/// The same JWT sent as `Authorization: Bearer`, for scripts and other clients.
+pub const BEARER_AUTH: &str = "bearer_auth";
+/// The instance's `ADMIN_TOKEN` in `X-Admin-Token`, for the admin endpoints.
+pub const ADMIN_TOKEN: &str = "admin_token";
+
+/// Declares how callers authenticate. Operations need the session cookie or a bearer
+/// token unless their `security(...)` says otherwise.
+struct SecuritySchemes;
+
+impl Modify for SecuritySchemes {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            COOKIE_AUTH,
+            SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::with_description(
+                "token",
+                "Session JWT set by `POST /api/v1/auth/login`.",
+            ))),
+        );
+        components.add_security_scheme(
+            BEARER_AUTH,
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .description(Some("The value of the `token` cookie set by login."))
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            ADMIN_TOKEN,
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::with_description(
+                "X-Admin-Token",
+                "Value of the `ADMIN_TOKEN` setting.",
+            ))),
+        );
+    }
+}
 
 #[derive(OpenApi)]
 #[openapi(
+    modifiers(&SecuritySchemes),
+    security(("cookie_auth" = []), ("bearer_auth" = [])),
     paths(
         crate::handlers::auth::register,
         crate::handlers::auth::login,
         crate::handlers::auth::logout,
         crate::handlers::auth::me,
+        crate::handlers::auth::list_security_events,
         crate::handlers::export::export_json,
+        crate::handlers::export::export_redacted,
+        crate::handlers::export::export_month,
         crate::handlers::export::import_json,
         crate::handlers::budget::list_monthly_budgets,
         crate::handlers::budget::update_monthly_budget,
+        crate::handlers::budget::lock_monthly_budget,
+        crate::handlers::budget::unlock_monthly_budget,
+        crate::handlers::budget::review_monthly_budget,
+        crate::handlers::budget::get_year_review,
+        crate::handlers::budget::create_earmark,
+        crate::handlers::budget::delete_earmark,
+        crate::handlers::budget::get_envelopes,
+        crate::handlers::plans::get_plan,
+        crate::handlers::plans::set_plan,
+        crate::handlers::subscriptions::list_subscriptions,
+        crate::handlers::subscriptions::list_cancel_candidates,
+        crate::handlers::subscriptions::tag_subscription_value,
+        crate::handlers::items::update_reimbursement,
+        crate::handlers::allowances::create_mileage,
+        crate::handlers::allowances::create_per_diem,
+        crate::handlers::allowances::recalculate,
+        crate::handlers::reimbursements::list_reimbursements,
+        crate::handlers::cash::get_cash_report,
+        crate::handlers::iou::list_splits,
+        crate::handlers::iou::create_split,
+        crate::handlers::iou::delete_split,
+        crate::handlers::iou::get_iou,
+        crate::handlers::iou::record_repayment,
+        crate::handlers::projects::list_projects,
+        crate::handlers::projects::create_project,
+        crate::handlers::projects::update_project,
+        crate::handlers::projects::delete_project,
+        crate::handlers::projects::get_project_summary,
+        crate::handlers::projects::link_item,
+        crate::handlers::clients::get_client_report,
+        crate::handlers::invoices::list_invoices,
+        crate::handlers::invoices::create_invoice,
+        crate::handlers::invoices::update_invoice,
+        crate::handlers::invoices::update_invoice_status,
+        crate::handlers::invoices::delete_invoice,
+        crate::handlers::wishlist::list_wishlist,
+        crate::handlers::wishlist::create_wishlist_entry,
+        crate::handlers::wishlist::update_wishlist_entry,
+        crate::handlers::wishlist::delete_wishlist_entry,
+        crate::handlers::wishlist::purchase_wishlist_entry,
+        crate::handlers::data_quality::get_data_quality,
+        crate::handlers::data_quality::fix_data_quality,
         crate::handlers::income::list_income,
         crate::handlers::income::create_income,
         crate::handlers::income::update_income,
         crate::handlers::income::delete_income,
+        crate::handlers::items::suggest_descriptions,
         crate::handlers::items::list_items,
+        crate::handlers::items::list_uncategorized_items,
         crate::handlers::items::create_item,
         crate::handlers::items::update_item,
         crate::handlers::items::delete_item,
@@ -40,34 +191,150 @@ use crate::models::{
         crate::handlers::fixed_expenses::create_fixed_expense,
         crate::handlers::fixed_expenses::update_fixed_expense,
         crate::handlers::fixed_expenses::delete_fixed_expense,
+        crate::handlers::fixed_expenses::bulk_update_fixed_expenses,
+        crate::handlers::fixed_expenses::get_fixed_expense_history,
+        crate::handlers::fixed_expenses::get_fixed_cost_inflation,
         crate::handlers::budget::list_categories,
         crate::handlers::budget::create_category,
         crate::handlers::budget::update_category,
+        crate::handlers::budget::bulk_update_categories,
+        crate::handlers::rules::get_category_rules,
+        crate::handlers::rules::set_category_rules,
         crate::handlers::budget::delete_category,
         crate::handlers::months::list_months,
         crate::handlers::months::get_or_create_current_month,
+        crate::handlers::periods::get_period_settings,
+        crate::handlers::periods::update_period_settings,
+        crate::handlers::periods::get_current_period,
+        crate::handlers::periods::get_period,
+        crate::handlers::dashboard::get_dashboard,
+        crate::handlers::pace::get_month_pace,
         crate::handlers::months::get_month,
         crate::handlers::months::close_month,
         crate::handlers::months::get_month_pdf,
+        crate::handlers::months::verify_month_pdf,
+        crate::handlers::months::get_month_digest,
+        crate::handlers::cashflow::get_cashflow,
+        crate::handlers::months::list_month_activity,
+        crate::handlers::jobs::get_job,
+        crate::handlers::share::create_share,
+        crate::handlers::share::revoke_shares,
+        crate::handlers::share::get_shared_month,
+        crate::handlers::share::get_shared_month_pdf,
+        crate::handlers::share::enable_public_stats,
+        crate::handlers::share::disable_public_stats,
+        crate::handlers::share::get_public_stats,
+        crate::handlers::signatures::get_pdf_signature,
+        crate::handlers::signatures::verify_pdf_signature,
+        crate::handlers::signatures::get_signing_key,
         crate::handlers::savings::get_savings,
         crate::handlers::savings::update_savings,
         crate::handlers::savings::get_retirement_savings,
         crate::handlers::savings::update_retirement_savings,
-        crate::handlers::stats::get_stats
+        crate::handlers::savings::get_wealth_history,
+        crate::handlers::retirement::get_projection,
+        crate::handlers::reports::list_reports,
+        crate::handlers::reports::create_report,
+        crate::handlers::reports::update_report,
+        crate::handlers::reports::delete_report,
+        crate::handlers::reports::run_report,
+        crate::handlers::reports::list_artifacts,
+        crate::handlers::reports::download_artifact,
+        crate::handlers::connectors::list_connections,
+        crate::handlers::connectors::authorize_connection,
+        crate::handlers::connectors::connection_callback,
+        crate::handlers::connectors::delete_connection,
+        crate::handlers::simulations::simulate,
+        crate::handlers::simulations::simulate_savings,
+        crate::handlers::simulations::create_savings_goal,
+        crate::handlers::checklist::get_checklist,
+        crate::handlers::checklist::set_checklist,
+        crate::handlers::settings::get_settings,
+        crate::handlers::settings::update_settings,
+        crate::handlers::onboarding::complete_onboarding,
+        crate::handlers::stats::get_stats,
+        crate::handlers::cpi::get_cpi,
+        crate::handlers::cpi::set_cpi,
+        crate::handlers::tax::get_tax_summary,
+        crate::handlers::analytics::get_top_spending,
+        crate::handlers::analytics::get_streaks,
+        crate::handlers::analytics::get_seasonality,
+        crate::handlers::analytics::refresh_seasonality,
+        crate::handlers::insights::list_insights,
+        crate::handlers::insights::mark_insight_read,
+        crate::handlers::insights::dismiss_insight,
+        crate::handlers::admin::get_maintenance,
+        crate::handlers::admin::update_maintenance,
+        crate::handlers::admin::get_log_level,
+        crate::handlers::admin::update_log_level,
+        crate::handlers::admin::get_migrations,
+        crate::handlers::admin::get_integrity,
+        crate::handlers::admin::repair_integrity,
+        crate::handlers::admin::get_usage,
+        crate::handlers::usage::get_usage,
+        crate::handlers::tools::list_tools,
+        crate::handlers::tools::call_tool,
+        crate::handlers::sync::sync,
+        crate::handlers::sync::apply_batch
     ),
     components(schemas(
         AuthRequest,
+        Dashboard,
+        DashboardMonth,
+        UpcomingFixedExpense,
+        MonthComparison,
+        MonthPace,
+        CategoryPace,
+        BudgetPeriodSettings,
+        UpdatePeriodSettings,
+        PeriodSummary,
+        PeriodCategory,
+        DailySpend,
         AuthResponse,
+        AuthEvent,
         MonthlyBudget,
         UpdateMonthlyBudget,
+        LockBudget,
+        ReviewBudget,
+        BudgetReview,
+        YearReview,
+        CategoryYearReview,
+        MonthReview,
+        CreateEarmark,
+        Earmark,
+        Envelope,
+        CoverSuggestion,
+        EnvelopesResponse,
+        YearPlan,
+        CategoryPlan,
+        SetYearPlan,
+        PlannedCategory,
         IncomeEntry,
         CreateIncome,
         UpdateIncome,
         Item,
         ItemWithCategory,
+        DescriptionSuggestion,
         CreateItem,
+        CreateItemResponse,
+        DuplicateItem,
         UpdateItem,
+        UpdateReimbursement,
+        ItemCalculation,
+        CalculatedItem,
+        CreateMileage,
+        CreatePerDiem,
+        Recalculate,
+        ReimbursementsReport,
+        CashReport,
+        CashMonth,
+        TaxSummary,
+        TaxRateTotal,
+        TaxMonthTotal,
         FixedExpense,
+        FixedExpensePrice,
+        FixedCostInflation,
+        FixedExpenseInflation,
         CreateFixedExpense,
         UpdateFixedExpense,
         BudgetCategory,
@@ -75,20 +342,148 @@ use crate::models::{
         UpdateCategory,
         Month,
         MonthSummary,
+        MonthDigest,
+        CashflowEntry,
+        MonthMetrics,
+        CloseMonthResponse,
+        PdfVerification,
+        PdfSignature,
+        PdfSignatureCheck,
+        SigningKey,
+        Job,
+        ActivityEntry,
+        ActivityPage,
+        CreateShare,
+        ShareResponse,
+        PublicStatsLink,
+        PublicStats,
+        CategoryShare,
         StatsResponse,
+        SetCpi,
+        CpiReading,
         CategoryStats,
         MonthlyStats,
+        DescriptionStats,
+        TopSpendingResponse,
+        MonthNoSpend,
+        StreaksResponse,
+        SeasonalCategory,
+        SeasonalityResponse,
+        SeasonalityRefresh,
+        Subscription,
+        SubscriptionsResponse,
+        CancelCandidates,
+        TagSubscriptionValue,
+        ItemSplit,
+        CreateSplit,
+        IouEntry,
+        PersonIou,
+        IouReport,
+        RecordRepayment,
+        Project,
+        ProjectMonth,
+        ProjectSummary,
+        CreateProject,
+        UpdateProject,
+        LinkItemProject,
+        Invoice,
+        CreateInvoice,
+        UpdateInvoice,
+        UpdateInvoiceStatus,
+        WishlistEntry,
+        CreateWishlistEntry,
+        UpdateWishlistEntry,
+        PurchaseWishlistEntry,
+        DataQualityReport,
+        QualityFinding,
+        DataQualityFix,
+        Insight,
+        InsightsResponse,
         RetirementSavingsResponse,
         SavingsResponse,
         UpdateSavings,
         UpdateRetirementSavings,
+        WealthSnapshot,
+        RetirementProjection,
+        ProjectionPoint,
+        ReportSpec,
+        SavedReport,
+        SaveReport,
+        ReportRun,
+        CloudConnection,
+        ConnectCloud,
+        CloseMonth,
+        SetChecklist,
+        SetCategoryRules,
+        NewCategoryRule,
+        CategoryRule,
+        ChecklistEntry,
+        ChecklistAcknowledgement,
+        CloudAuthorization,
+        ReportArtifact,
+        SimulationRequest,
+        CategoryChange,
+        NewPayment,
+        SimulationResult,
+        MonthOutcome,
+        SimulatedCategory,
+        ProjectedMonth,
+        SavingsScenario,
+        SavingsGoalFromScenario,
+        SavingsSimulation,
+        SavingsPoint,
+        SavingsGoalPlan,
+        UserSettings,
+        UpdateSettings,
+        OnboardingRequest,
         UserExport,
+        TextRedaction,
+        AmountRedaction,
+        DateRedaction,
         CategoryExport,
         MonthExport,
         FixedExpenseExport,
         IncomeExport,
         BudgetExport,
-        ItemExport
+        ItemExport,
+        MaintenanceStatus,
+        UpdateMaintenance,
+        LogLevel,
+        MigrationStatus,
+        DryRunReport,
+        BulkEdit,
+        Relabel,
+        BulkEditResult,
+        BulkChange,
+        TableChanges,
+        MigrationInfo,
+        IntegrityReport,
+        IntegrityRepair,
+        UsageReport,
+        ToolDefinition,
+        ToolCall,
+        MonthSummaryArgs,
+        CategoryHistoryArgs,
+        SearchItemsArgs,
+        ToolResult,
+        ClientReport,
+        ClientMonth,
+        MonthOverview,
+        CategorySpending,
+        CategoryHistory,
+        CategoryMonth,
+        ItemSearch,
+        FoundItem,
+        UserUsage,
+        SyncResponse,
+        SyncDeletion,
+        VersionedItem,
+        SyncBatch,
+        SyncMutation,
+        SyncChange,
+        SyncBatchResponse,
+        MutationResult,
+        MutationStatus
     ))
 )]
 pub struct ApiDoc;
@@ -0,0 +1,116 @@
+//! Per-account limits that keep one user, or a runaway import script, from filling up an
+//! instance shared with others. Each limit is off unless its `QUOTA_*` setting is set.
+
+use sqlx::SqliteExecutor;
+
+use crate::config;
+use crate::error::PaymeError;
+
+const MONTHS: &str = "months per account";
+const ITEMS: &str = "items per month";
+const STORAGE: &str = "bytes of stored files per account";
+
+/// Fails when `adding` more on top of `current` would go past `limit`.
+pub fn check(limit: Option<i64>, current: i64, adding: i64, what: &str) -> Result<(), PaymeError> {
+    match limit {
+        Some(limit) if current + adding > limit => Err(PaymeError::QuotaExceeded(format!(
+            "This server allows at most {limit} {what}"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that the user may create `adding` more months.
+pub async fn check_months<'e>(
+    executor: impl SqliteExecutor<'e>,
+    user_id: i64,
+    adding: i64,
+) -> Result<(), PaymeError> {
+    let Some(limit) = config::quota_max_months() else {
+        return Ok(());
+    };
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM months WHERE user_id = ?")
+        .bind(user_id)
+        .fetch_one(executor)
+        .await?;
+    check(Some(limit), count, adding, MONTHS)
+}
+
+/// Checks data that is about to replace all of a user's months, such as an import:
+/// `months` months holding `items` items each.
+pub fn check_replacement(
+    months: usize,
+    items: impl IntoIterator<Item = usize>,
+) -> Result<(), PaymeError> {
+    check(config::quota_max_months(), 0, months as i64, MONTHS)?;
+    let limit = config::quota_max_items_per_month();
+    items
+        .into_iter()
+        .try_for_each(|count| check(limit, 0, count as i64, ITEMS))
+}
+
+/// Checks that `adding` more items fit in the month.
+pub async fn check_items<'e>(
+    executor: impl SqliteExecutor<'e>,
+    month_id: i64,
+    adding: i64,
+) -> Result<(), PaymeError> {
+    let Some(limit) = config::quota_max_items_per_month() else {
+        return Ok(());
+    };
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM items WHERE month_id = ?")
+        .bind(month_id)
+        .fetch_one(executor)
+        .await?;
+    check(Some(limit), count, adding, ITEMS)
+}
+
+/// Checks that a file of `adding` bytes fits in the user's storage, counting PDF
+/// snapshots and report files as the usage report does.
+pub async fn check_storage<'e>(
+    executor: impl SqliteExecutor<'e>,
+    user_id: i64,
+    adding: i64,
+) -> Result<(), PaymeError> {
+    let Some(limit) = config::quota_max_storage_bytes() else {
+        return Ok(());
+    };
+    let used: i64 = sqlx::query_scalar(
+        r#"
+        SELECT
+            (SELECT COALESCE(SUM(s.size), 0) FROM monthly_snapshots s
+             JOIN months m ON s.month_id = m.id WHERE m.user_id = ?1)
+          + (SELECT COALESCE(SUM(a.size), 0) FROM report_artifacts a
+             JOIN saved_reports r ON a.report_id = r.id WHERE r.user_id = ?1)
+        "#,
+    )
+    .bind(user_id)
+    .fetch_one(executor)
+    .await?;
+    check(Some(limit), used, adding, STORAGE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_limit() {
+        assert!(check(None, 1_000, 1_000, "items").is_ok());
+    }
+
+    #[test]
+    fn test_up_to_limit() {
+        assert!(check(Some(10), 9, 1, "items").is_ok());
+        assert!(check(Some(10), 10, 0, "items").is_ok());
+    }
+
+    #[test]
+    fn test_over_limit() {
+        let error = check(Some(10), 10, 1, "items per month").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Quota exceeded: This server allows at most 10 items per month"
+        );
+    }
+}
@@ -0,0 +1,204 @@
+use chrono::{Datelike, NaiveDate};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::handlers::export::UserExport;
+
+/// What happens to item descriptions.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TextRedaction {
+    Keep,
+    /// Replaced by a token that is the same for the same text within one export, so
+    /// repeated purchases can still be told apart from one-offs.
+    #[default]
+    Hash,
+    /// Replaced by an empty string.
+    Drop,
+}
+
+/// What happens to amounts.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AmountRedaction {
+    Keep,
+    /// Rounded to the nearest multiple of the bucket size.
+    #[default]
+    Bucket,
+    /// Multiplied by one factor, keeping every ratio between amounts intact.
+    Scale,
+}
+
+/// What happens to item dates.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DateRedaction {
+    #[default]
+    Keep,
+    /// Moved to the first of their month.
+    Month,
+}
+
+/// How each kind of field is anonymized in an export.
+pub struct Redaction {
+    pub descriptions: TextRedaction,
+    /// Category, income and fixed expense labels.
+    pub labels: TextRedaction,
+    pub amounts: AmountRedaction,
+    pub bucket_size: f64,
+    pub scale_factor: f64,
+    pub dates: DateRedaction,
+    /// Mixed into every hash so tokens can't be matched against hashes of guessed text
+    /// or across exports.
+    salt: String,
+}
+
+impl Redaction {
+    /// Without a `scale_factor`, one between 0.5 and 1.5 is picked at random and kept
+    /// out of the export.
+    pub fn new(
+        descriptions: TextRedaction,
+        labels: TextRedaction,
+        amounts: AmountRedaction,
+        bucket_size: f64,
+        scale_factor: Option<f64>,
+        dates: DateRedaction,
+    ) -> Self {
+        let salt = Uuid::new_v4();
+        let scale_factor =
+            scale_factor.unwrap_or_else(|| 0.5 + (salt.as_u128() % 1000) as f64 / 1000.0);
+        Self {
+            descriptions,
+            labels,
+            amounts,
+            bucket_size,
+            scale_factor,
+            dates,
+            salt: salt.to_string(),
+        }
+    }
+
+    pub fn apply(&self, export: &mut UserExport) {
+        let amount = |value: &mut f64| *value = self.amount(*value);
+        let label = |value: &mut String| *value = self.text(self.labels, "label", value);
+
+        for balance in [&mut export.savings, &mut export.retirement_savings]
+            .into_iter()
+            .flatten()
+        {
+            amount(balance);
+        }
+        for expense in &mut export.fixed_expenses {
+            label(&mut expense.label);
+            amount(&mut expense.amount);
+        }
+        for category in &mut export.categories {
+            label(&mut category.label);
+            amount(&mut category.default_amount);
+        }
+        for month in &mut export.months {
+            for income in &mut month.income_entries {
+                label(&mut income.label);
+                amount(&mut income.amount);
+            }
+            for budget in &mut month.budgets {
+                label(&mut budget.category_label);
+                amount(&mut budget.allocated_amount);
+            }
+            for item in &mut month.items {
+                label(&mut item.category_label);
+                item.description = self.text(self.descriptions, "item", &item.description);
+                amount(&mut item.amount);
+                item.spent_on = self.date(&item.spent_on);
+            }
+        }
+    }
+
+    fn text(&self, redaction: TextRedaction, prefix: &str, text: &str) -> String {
+        match redaction {
+            TextRedaction::Keep => text.to_string(),
+            TextRedaction::Drop => String::new(),
+            TextRedaction::Hash => {
+                let digest = Sha256::digest(format!("{}:{text}", self.salt).as_bytes());
+                let token: String = digest[..6].iter().map(|b| format!("{b:02x}")).collect();
+                format!("{prefix}-{token}")
+            }
+        }
+    }
+
+    fn amount(&self, amount: f64) -> f64 {
+        let redacted = match self.amounts {
+            AmountRedaction::Keep => return amount,
+            AmountRedaction::Bucket => (amount / self.bucket_size).round() * self.bucket_size,
+            AmountRedaction::Scale => amount * self.scale_factor,
+        };
+        (redacted * 100.0).round() / 100.0
+    }
+
+    fn date(&self, date: &str) -> String {
+        match (self.dates, NaiveDate::parse_from_str(date, "%Y-%m-%d")) {
+            (DateRedaction::Month, Ok(date)) => date.with_day(1).unwrap_or(date).to_string(),
+            _ => date.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redaction(amounts: AmountRedaction) -> Redaction {
+        Redaction::new(
+            TextRedaction::Hash,
+            TextRedaction::Keep,
+            amounts,
+            10.0,
+            Some(2.0),
+            DateRedaction::Month,
+        )
+    }
+
+    #[test]
+    fn test_hash_is_stable_within_an_export() {
+        let redaction = redaction(AmountRedaction::Keep);
+        let first = redaction.text(TextRedaction::Hash, "item", "Pharmacy");
+        assert_eq!(
+            first,
+            redaction.text(TextRedaction::Hash, "item", "Pharmacy")
+        );
+        assert_ne!(first, redaction.text(TextRedaction::Hash, "item", "Bakery"));
+        assert!(first.starts_with("item-"));
+        assert!(!first.contains("Pharmacy"));
+
+        let other_export = Redaction::new(
+            TextRedaction::Hash,
+            TextRedaction::Keep,
+            AmountRedaction::Keep,
+            10.0,
+            None,
+            DateRedaction::Keep,
+        );
+        assert_ne!(
+            first,
+            other_export.text(TextRedaction::Hash, "item", "Pharmacy")
+        );
+    }
+
+    #[test]
+    fn test_amounts_are_bucketed_or_scaled() {
+        assert_eq!(redaction(AmountRedaction::Bucket).amount(43.99), 40.0);
+        assert_eq!(redaction(AmountRedaction::Bucket).amount(46.0), 50.0);
+        assert_eq!(redaction(AmountRedaction::Scale).amount(12.5), 25.0);
+        assert_eq!(redaction(AmountRedaction::Keep).amount(12.345), 12.345);
+    }
+
+    #[test]
+    fn test_dates_move_to_month_start() {
+        assert_eq!(
+            redaction(AmountRedaction::Keep).date("2024-06-17"),
+            "2024-06-01"
+        );
+    }
+}
@@ -0,0 +1,465 @@
+use std::collections::{BTreeMap, HashSet};
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, Days, Months, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+
+use crate::connectors::{self, Provider};
+use crate::crypto;
+use crate::delivery::{self, Document};
+use crate::error::PaymeError;
+use crate::format::MoneyFormat;
+use crate::handlers::export::{build_export, write_csv, write_zip};
+use crate::handlers::months::{get_month_summary, read_snapshot};
+use crate::handlers::settings::load_settings;
+use crate::i18n::Locale;
+use crate::jobs;
+use crate::models::{ReportSpec, SavedReport};
+use crate::pdf;
+use crate::quotas;
+use crate::storage;
+
+/// How often the scheduler looks for reports that are due.
+const SCHEDULER_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Serialize, Deserialize)]
+pub struct ReportJob {
+    pub report_id: i64,
+}
+
+/// A saved report as stored, with its spec still in JSON.
+#[derive(sqlx::FromRow)]
+struct ReportRow {
+    id: i64,
+    name: String,
+    spec: String,
+    schedule: Option<String>,
+    delivery: Option<String>,
+    target: Option<String>,
+    next_run_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+struct ReportFile<'a> {
+    name: &'a str,
+    from: NaiveDate,
+    to: NaiveDate,
+    total: f64,
+    categories: Vec<CategoryTotal>,
+    items: Vec<ReportLine>,
+}
+
+#[derive(Serialize)]
+struct CategoryTotal {
+    category: String,
+    total: f64,
+}
+
+#[derive(Serialize)]
+struct ReportLine {
+    spent_on: NaiveDate,
+    category: String,
+    description: String,
+    amount: f64,
+}
+
+/// First and last day of the last complete week (Monday to Sunday) or calendar month
+/// before `today`.
+pub fn last_period(period: &str, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+    match period {
+        "week" => {
+            let monday = today - Days::new(today.weekday().num_days_from_monday() as u64);
+            (monday - Days::new(7), monday - Days::new(1))
+        }
+        _ => {
+            let first = today.with_day(1).unwrap_or(today);
+            (first - Months::new(1), first - Days::new(1))
+        }
+    }
+}
+
+/// When a schedule next runs after `today`, at midnight UTC: the next Monday for
+/// `weekly`, the first of next month for `monthly`.
+pub fn next_run(schedule: &str, today: NaiveDate) -> DateTime<Utc> {
+    let date = match schedule {
+        "weekly" => today + Days::new(7 - today.weekday().num_days_from_monday() as u64),
+        _ => today.with_day(1).unwrap_or(today) + Months::new(1),
+    };
+    date.and_hms_opt(0, 0, 0).unwrap().and_utc()
+}
+
+pub(crate) async fn load_report(
+    pool: &SqlitePool,
+    user_id: i64,
+    report_id: i64,
+) -> Result<SavedReport, PaymeError> {
+    let row: ReportRow = sqlx::query_as(
+        r#"
+        SELECT id, name, spec, schedule, delivery, target, next_run_at, created_at
+        FROM saved_reports WHERE id = ? AND user_id = ?
+        "#,
+    )
+    .bind(report_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await?
+    .ok_or(PaymeError::NotFound)?;
+
+    Ok(SavedReport {
+        id: row.id,
+        name: row.name,
+        spec: serde_json::from_str(&row.spec).map_err(|e| PaymeError::Internal(e.to_string()))?,
+        schedule: row.schedule,
+        delivery: row.delivery,
+        target: row.target.as_deref().map(crypto::open_text).transpose()?,
+        next_run_at: row.next_run_at,
+        created_at: row.created_at,
+    })
+}
+
+/// Builds the report file for the last complete period before `today`. Savings
+/// transfers are left out of csv and json reports; backups hold everything.
+pub async fn generate(
+    pool: &SqlitePool,
+    user_id: i64,
+    report: &SavedReport,
+    today: NaiveDate,
+) -> Result<(NaiveDate, NaiveDate, Document), PaymeError> {
+    let ReportSpec {
+        period,
+        category_ids,
+        format,
+    } = &report.spec;
+    let (from, to) = last_period(period, today);
+    if format == "backup" {
+        return Ok((from, to, backup(pool, user_id, report, from, to).await?));
+    }
+
+    let categories: HashSet<i64> = category_ids.iter().copied().collect();
+    let rows: Vec<(i64, NaiveDate, String, String, f64)> = sqlx::query_as(
+        r#"
+        SELECT i.category_id, i.spent_on, bc.label, i.description, i.amount
+        FROM items i
+        JOIN months m ON i.month_id = m.id
+        JOIN budget_categories bc ON i.category_id = bc.id
+        WHERE m.user_id = ? AND i.savings_destination = 'none'
+          AND i.spent_on BETWEEN ? AND ?
+        ORDER BY i.spent_on, i.id
+        "#,
+    )
+    .bind(user_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+    let items: Vec<ReportLine> = rows
+        .into_iter()
+        .filter(|(category_id, ..)| categories.is_empty() || categories.contains(category_id))
+        .map(|(_, spent_on, category, description, amount)| ReportLine {
+            spent_on,
+            category,
+            description,
+            amount,
+        })
+        .collect();
+
+    let filename = format!("{}-{from}.{format}", slug(&report.name));
+    let (content_type, data) = if format == "csv" {
+        let rows = items
+            .iter()
+            .map(|i| {
+                vec![
+                    i.spent_on.to_string(),
+                    i.category.clone(),
+                    i.description.clone(),
+                    i.amount.to_string(),
+                ]
+            })
+            .collect();
+        (
+            "text/csv",
+            write_csv(&["spent_on", "category", "description", "amount"], rows)?,
+        )
+    } else {
+        let mut totals: BTreeMap<String, f64> = BTreeMap::new();
+        for item in &items {
+            *totals.entry(item.category.clone()).or_default() += item.amount;
+        }
+        let file = ReportFile {
+            name: &report.name,
+            from,
+            to,
+            total: items.iter().map(|i| i.amount).sum(),
+            categories: totals
+                .into_iter()
+                .map(|(category, total)| CategoryTotal { category, total })
+                .collect(),
+            items,
+        };
+        (
+            "application/json",
+            serde_json::to_vec_pretty(&file).map_err(|e| PaymeError::Internal(e.to_string()))?,
+        )
+    };
+
+    Ok((
+        from,
+        to,
+        Document {
+            filename,
+            content_type: content_type.to_string(),
+            data,
+        },
+    ))
+}
+
+/// A zip with the account export, which `POST /api/v1/import/json` restores, and the
+/// PDF of each month the period falls in. Closed months get their stored snapshot.
+async fn backup(
+    pool: &SqlitePool,
+    user_id: i64,
+    report: &SavedReport,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Document, PaymeError> {
+    let export = build_export(pool, user_id).await?;
+    let mut files = vec![(
+        "export.json".to_string(),
+        serde_json::to_vec_pretty(&export).map_err(|e| PaymeError::Internal(e.to_string()))?,
+    )];
+
+    let months: Vec<(i64, i32, i32)> = sqlx::query_as(
+        r#"
+        SELECT id, year, month FROM months
+        WHERE user_id = ? AND printf('%04d-%02d-01', year, month) BETWEEN ? AND ?
+        ORDER BY year, month
+        "#,
+    )
+    .bind(user_id)
+    .bind(from.with_day(1).unwrap_or(from))
+    .bind(to)
+    .fetch_all(pool)
+    .await?;
+    let settings = load_settings(pool, user_id).await?;
+    for (month_id, year, month) in months {
+        let pdf = match read_snapshot(pool, month_id).await? {
+            Some(pdf) => pdf,
+            None => {
+                let summary = get_month_summary(pool, user_id, month_id).await?.0;
+                pdf::generate_pdf(
+                    &summary,
+                    &MoneyFormat::from_settings(&settings),
+                    Locale::resolve(settings.locale.as_deref(), None),
+                )
+                .map_err(|e| PaymeError::Internal(e.to_string()))?
+            }
+        };
+        files.push((format!("month-{year}-{month:02}.pdf"), pdf));
+    }
+
+    Ok(Document {
+        filename: format!("{}-{from}.zip", slug(&report.name)),
+        content_type: "application/zip".to_string(),
+        data: write_zip(files)?,
+    })
+}
+
+/// Runs a report job: stores the file for download, then delivers it. A retried job
+/// delivers the file stored by its first attempt.
+pub(crate) async fn run_job(
+    pool: &SqlitePool,
+    job_id: i64,
+    user_id: i64,
+    job: ReportJob,
+) -> Result<(), PaymeError> {
+    let report = match load_report(pool, user_id, job.report_id).await {
+        Ok(report) => report,
+        // Deleted since the run was queued
+        Err(PaymeError::NotFound) => return Ok(()),
+        Err(e) => return Err(e),
+    };
+
+    let storage = storage::backend(pool);
+    let stored: Option<(NaiveDate, NaiveDate, String, String, String)> = sqlx::query_as(
+        "SELECT period_start, period_end, filename, content_type, storage_key FROM report_artifacts WHERE job_id = ?",
+    )
+    .bind(job_id)
+    .fetch_optional(pool)
+    .await?;
+    let (from, to, document) = match stored {
+        Some((from, to, filename, content_type, key)) => (
+            from,
+            to,
+            Document {
+                filename,
+                content_type,
+                data: storage::read_sealed(pool, storage.as_ref(), &key).await?,
+            },
+        ),
+        None => {
+            let (from, to, document) =
+                generate(pool, user_id, &report, Utc::now().date_naive()).await?;
+            quotas::check_storage(pool, user_id, document.data.len() as i64).await?;
+            let key = storage::artifact_key(report.id, job_id);
+            let size = storage::put_sealed(storage.as_ref(), &key, document.data.clone()).await?;
+            sqlx::query(
+                r#"
+                INSERT INTO report_artifacts
+                    (report_id, job_id, period_start, period_end, filename, content_type, storage_key, size, created_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(report.id)
+            .bind(job_id)
+            .bind(from)
+            .bind(to)
+            .bind(&document.filename)
+            .bind(&document.content_type)
+            .bind(&key)
+            .bind(size as i64)
+            .bind(Utc::now())
+            .execute(pool)
+            .await?;
+            (from, to, document)
+        }
+    };
+
+    match (report.delivery.as_deref(), report.target.as_deref()) {
+        (Some("email"), Some(to_address)) => {
+            let subject = format!("{}: {from} to {to}", report.name);
+            let body = format!(
+                "Your report \"{}\" for {from} to {to} is attached.",
+                report.name
+            );
+            delivery::email(to_address, &subject, &body, document).await
+        }
+        (Some("webhook"), Some(url)) => delivery::webhook(url, document).await,
+        (Some("webdav"), Some(url)) => delivery::webdav(url, document).await,
+        (Some("s3"), Some(target)) => delivery::s3(target, document).await,
+        (Some(name), _) => match Provider::parse(name) {
+            Some(provider) => connectors::upload_for(pool, user_id, provider, document).await,
+            None => Ok(()),
+        },
+        (None, _) => Ok(()),
+    }
+}
+
+/// Queues a run for every scheduled report that is due and moves its next run
+/// forward. Returns how many were queued.
+pub async fn queue_due(pool: &SqlitePool, now: DateTime<Utc>) -> Result<usize, PaymeError> {
+    let due: Vec<(i64, i64, String)> = sqlx::query_as(
+        r#"
+        SELECT id, user_id, schedule FROM saved_reports
+        WHERE schedule IS NOT NULL AND next_run_at <= ?
+        "#,
+    )
+    .bind(now)
+    .fetch_all(pool)
+    .await?;
+
+    for (report_id, user_id, schedule) in &due {
+        let mut tx = pool.begin().await?;
+        jobs::enqueue(
+            &mut *tx,
+            *user_id,
+            jobs::REPORT,
+            &ReportJob {
+                report_id: *report_id,
+            },
+        )
+        .await?;
+        sqlx::query("UPDATE saved_reports SET next_run_at = ? WHERE id = ?")
+            .bind(next_run(schedule, now.date_naive()))
+            .bind(report_id)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+    }
+
+    Ok(due.len())
+}
+
+/// Queues scheduled reports as they become due.
+pub async fn run_scheduler(pool: SqlitePool) {
+    loop {
+        match queue_due(&pool, Utc::now()).await {
+            Ok(0) => {}
+            Ok(_) => jobs::wake(),
+            Err(e) => tracing::error!("Failed to queue scheduled reports: {e}"),
+        }
+        tokio::time::sleep(SCHEDULER_INTERVAL).await;
+    }
+}
+
+/// Lowercase name with anything but letters and digits turned into dashes, for filenames.
+fn slug(name: &str) -> String {
+    let slug: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let slug = slug
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if slug.is_empty() {
+        "report".to_string()
+    } else {
+        slug
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_last_period() {
+        // A Wednesday
+        let today = date(2024, 3, 13);
+        assert_eq!(
+            last_period("week", today),
+            (date(2024, 3, 4), date(2024, 3, 10))
+        );
+        assert_eq!(
+            last_period("month", today),
+            (date(2024, 2, 1), date(2024, 2, 29))
+        );
+        assert_eq!(
+            last_period("month", date(2024, 1, 1)),
+            (date(2023, 12, 1), date(2023, 12, 31))
+        );
+    }
+
+    #[test]
+    fn test_next_run() {
+        let monday = date(2024, 3, 11);
+        assert_eq!(next_run("weekly", monday).date_naive(), date(2024, 3, 18));
+        assert_eq!(
+            next_run("weekly", date(2024, 3, 17)).date_naive(),
+            date(2024, 3, 18)
+        );
+        assert_eq!(
+            next_run("monthly", date(2024, 12, 31)).date_naive(),
+            date(2025, 1, 1)
+        );
+    }
+
+    #[test]
+    fn test_slug() {
+        assert_eq!(slug("Monthly groceries (CSV)"), "monthly-groceries-csv");
+        assert_eq!(slug("***"), "report");
+    }
+}
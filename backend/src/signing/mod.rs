@@ -0,0 +1,114 @@
+use std::sync::OnceLock;
+
+use ed25519_dalek::pkcs8::{spki::der::pem::LineEnding, EncodePublicKey};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+
+use crate::crypto::key_from_hex;
+
+/// Ed25519 key that signs the PDF snapshots of closed months, so a copy can be shown
+/// to come from this server unchanged.
+pub struct PdfSigner {
+    key: SigningKey,
+}
+
+static SIGNER: OnceLock<Option<PdfSigner>> = OnceLock::new();
+
+/// Signer for the key in `PDF_SIGNING_KEY` (64 hex characters), or `None` when PDFs are
+/// not signed.
+pub fn pdf_signer() -> Option<&'static PdfSigner> {
+    SIGNER
+        .get_or_init(|| {
+            let key = std::env::var("PDF_SIGNING_KEY").ok()?;
+            Some(
+                PdfSigner::from_hex(&key)
+                    .unwrap_or_else(|e| panic!("Invalid PDF_SIGNING_KEY: {e}")),
+            )
+        })
+        .as_ref()
+}
+
+impl PdfSigner {
+    pub fn from_hex(key: &str) -> Result<Self, String> {
+        Ok(Self {
+            key: SigningKey::from_bytes(&key_from_hex(key)?),
+        })
+    }
+
+    /// Hex Ed25519 signature over the exact bytes of the file.
+    pub fn sign(&self, data: &[u8]) -> String {
+        hex(&self.key.sign(data).to_bytes())
+    }
+
+    /// Public key in hex, as stored next to each signature.
+    pub fn public_key(&self) -> String {
+        hex(self.key.verifying_key().as_bytes())
+    }
+
+    /// Public key as a PEM `PUBLIC KEY` block, for tools such as `openssl pkeyutl`.
+    pub fn public_key_pem(&self) -> String {
+        self.key
+            .verifying_key()
+            .to_public_key_pem(LineEnding::LF)
+            .unwrap_or_default()
+    }
+}
+
+/// Checks a hex signature made by `PdfSigner::sign` against a hex public key.
+pub fn verify(public_key: &str, data: &[u8], signature: &str) -> bool {
+    let Ok(key) = key_from_hex(public_key) else {
+        return false;
+    };
+    let Ok(key) = VerifyingKey::from_bytes(&key) else {
+        return false;
+    };
+    let Some(signature) = unhex(signature).and_then(|bytes| Signature::from_slice(&bytes).ok())
+    else {
+        return false;
+    };
+    key.verify(data, &signature).is_ok()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn unhex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) || !text.is_ascii() {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        let signer = PdfSigner::from_hex(&"11".repeat(32)).unwrap();
+        let signature = signer.sign(b"%PDF-1.3 report");
+
+        assert_eq!(signature.len(), 128);
+        assert!(verify(&signer.public_key(), b"%PDF-1.3 report", &signature));
+        assert!(!verify(
+            &signer.public_key(),
+            b"%PDF-1.3 edited",
+            &signature
+        ));
+        assert!(!verify(&signer.public_key(), b"%PDF-1.3 report", "00"));
+
+        let other = PdfSigner::from_hex(&"22".repeat(32)).unwrap();
+        assert!(!verify(&other.public_key(), b"%PDF-1.3 report", &signature));
+    }
+
+    #[test]
+    fn test_public_key_pem() {
+        let signer = PdfSigner::from_hex(&"11".repeat(32)).unwrap();
+        assert!(signer
+            .public_key_pem()
+            .starts_with("-----BEGIN PUBLIC KEY-----"));
+    }
+}
@@ -0,0 +1,95 @@
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures_util::future::BoxFuture;
+use futures_util::stream::{self, StreamExt, TryStreamExt};
+use sqlx::SqlitePool;
+
+use super::{check_key, ByteStream, Storage, StoredObject};
+use crate::error::PaymeError;
+
+/// Keeps files in the `stored_objects` table, so a single database file holds everything.
+/// Files are buffered whole, which suits the small PDFs and reports stored by default.
+pub struct DatabaseStorage {
+    pool: SqlitePool,
+}
+
+impl DatabaseStorage {
+    pub fn new(pool: SqlitePool) -> Self {
+        Self { pool }
+    }
+}
+
+impl Storage for DatabaseStorage {
+    fn put<'a>(&'a self, key: &'a str, data: ByteStream) -> BoxFuture<'a, Result<u64, PaymeError>> {
+        Box::pin(async move {
+            check_key(key)?;
+            let data: Vec<u8> = data
+                .try_fold(Vec::new(), |mut data, chunk| async move {
+                    data.extend_from_slice(&chunk);
+                    Ok(data)
+                })
+                .await?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO stored_objects (key, data, created_at) VALUES (?, ?, ?)
+                ON CONFLICT(key) DO UPDATE SET data = excluded.data, created_at = excluded.created_at
+                "#,
+            )
+            .bind(key)
+            .bind(&data)
+            .bind(Utc::now())
+            .execute(&self.pool)
+            .await?;
+
+            Ok(data.len() as u64)
+        })
+    }
+
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<ByteStream, PaymeError>> {
+        Box::pin(async move {
+            let (data,): (Vec<u8>,) =
+                sqlx::query_as("SELECT data FROM stored_objects WHERE key = ?")
+                    .bind(key)
+                    .fetch_optional(&self.pool)
+                    .await?
+                    .ok_or(PaymeError::NotFound)?;
+
+            Ok(stream::once(async { Ok(Bytes::from(data)) }).boxed())
+        })
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<(), PaymeError>> {
+        Box::pin(async move {
+            sqlx::query("DELETE FROM stored_objects WHERE key = ?")
+                .bind(key)
+                .execute(&self.pool)
+                .await?;
+            Ok(())
+        })
+    }
+
+    fn list<'a>(&'a self, prefix: &'a str) -> BoxFuture<'a, Result<Vec<StoredObject>, PaymeError>> {
+        Box::pin(async move {
+            let rows: Vec<(String, i64, DateTime<Utc>)> = sqlx::query_as(
+                r#"
+                SELECT key, LENGTH(data), created_at FROM stored_objects
+                WHERE substr(key, 1, length(?1)) = ?1
+                ORDER BY key
+                "#,
+            )
+            .bind(prefix)
+            .fetch_all(&self.pool)
+            .await?;
+
+            Ok(rows
+                .into_iter()
+                .map(|(key, size, modified)| StoredObject {
+                    key,
+                    size: size as u64,
+                    modified,
+                })
+                .collect())
+        })
+    }
+}
@@ -0,0 +1,137 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use futures_util::future::BoxFuture;
+use futures_util::stream::{StreamExt, TryStreamExt};
+use tokio::io::AsyncWriteExt;
+use tokio_util::io::ReaderStream;
+
+use super::{check_key, io_error, ByteStream, Storage, StoredObject};
+use crate::error::PaymeError;
+
+/// Keeps files in a directory, one file per key.
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+impl LocalStorage {
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, PaymeError> {
+        check_key(key)?;
+        Ok(self.root.join(key))
+    }
+}
+
+impl Storage for LocalStorage {
+    fn put<'a>(
+        &'a self,
+        key: &'a str,
+        mut data: ByteStream,
+    ) -> BoxFuture<'a, Result<u64, PaymeError>> {
+        Box::pin(async move {
+            let path = self.path(key)?;
+            if let Some(dir) = path.parent() {
+                tokio::fs::create_dir_all(dir).await.map_err(io_error)?;
+            }
+
+            // Written next to the target and renamed, so readers never see half a file
+            let partial = path.with_file_name(format!(".{}.partial", uuid::Uuid::new_v4()));
+            let written = async {
+                let mut file = tokio::fs::File::create(&partial).await.map_err(io_error)?;
+                let mut size = 0;
+                while let Some(chunk) = data.next().await {
+                    let chunk = chunk?;
+                    file.write_all(&chunk).await.map_err(io_error)?;
+                    size += chunk.len() as u64;
+                }
+                file.sync_all().await.map_err(io_error)?;
+                Ok::<_, PaymeError>(size)
+            }
+            .await;
+
+            match written {
+                Ok(size) => {
+                    tokio::fs::rename(&partial, &path).await.map_err(io_error)?;
+                    Ok(size)
+                }
+                Err(e) => {
+                    let _ = tokio::fs::remove_file(&partial).await;
+                    Err(e)
+                }
+            }
+        })
+    }
+
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<ByteStream, PaymeError>> {
+        Box::pin(async move {
+            let file = tokio::fs::File::open(self.path(key)?)
+                .await
+                .map_err(io_error)?;
+            Ok(ReaderStream::new(file).map_err(io_error).boxed())
+        })
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<(), PaymeError>> {
+        Box::pin(async move {
+            match tokio::fs::remove_file(self.path(key)?).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(io_error(e)),
+                _ => Ok(()),
+            }
+        })
+    }
+
+    fn list<'a>(&'a self, prefix: &'a str) -> BoxFuture<'a, Result<Vec<StoredObject>, PaymeError>> {
+        Box::pin(async move {
+            let mut objects = Vec::new();
+            let mut dirs = vec![self.root.clone()];
+            while let Some(dir) = dirs.pop() {
+                let mut entries = match tokio::fs::read_dir(&dir).await {
+                    Ok(entries) => entries,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                    Err(e) => return Err(io_error(e)),
+                };
+                while let Some(entry) = entries.next_entry().await.map_err(io_error)? {
+                    let metadata = entry.metadata().await.map_err(io_error)?;
+                    if metadata.is_dir() {
+                        dirs.push(entry.path());
+                        continue;
+                    }
+                    let Some(key) = key_of(&self.root, &entry.path()) else {
+                        continue;
+                    };
+                    if !key.starts_with(prefix) {
+                        continue;
+                    }
+                    objects.push(StoredObject {
+                        key,
+                        size: metadata.len(),
+                        modified: metadata
+                            .modified()
+                            .map(DateTime::<Utc>::from)
+                            .map_err(io_error)?,
+                    });
+                }
+            }
+
+            objects.sort_by(|a, b| a.key.cmp(&b.key));
+            Ok(objects)
+        })
+    }
+}
+
+/// Key of a file under `root`, skipping files still being written.
+fn key_of(root: &Path, path: &Path) -> Option<String> {
+    let parts: Vec<&str> = path
+        .strip_prefix(root)
+        .ok()?
+        .components()
+        .map(|part| part.as_os_str().to_str())
+        .collect::<Option<_>>()?;
+    if parts.last()?.starts_with('.') {
+        return None;
+    }
+    Some(parts.join("/"))
+}
@@ -0,0 +1,283 @@
+mod database;
+mod local;
+mod s3;
+
+use std::collections::HashSet;
+use std::sync::{Arc, OnceLock};
+
+use axum::body::Body;
+use bytes::Bytes;
+use chrono::{DateTime, Duration, Utc};
+use futures_util::future::BoxFuture;
+use futures_util::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+
+use crate::config;
+use crate::crypto;
+use crate::error::PaymeError;
+
+pub use database::DatabaseStorage;
+pub use local::LocalStorage;
+pub use s3::S3Storage;
+
+/// Prefixes of the files owned by database rows, which are removed once their row is gone.
+const OWNED_PREFIXES: [&str; 2] = ["snapshots/", "reports/"];
+
+/// How old an unreferenced file must be before it is removed, so files whose row is
+/// still being written are left alone.
+const ORPHAN_GRACE: Duration = Duration::hours(1);
+
+pub type ByteStream = BoxStream<'static, Result<Bytes, PaymeError>>;
+
+pub struct StoredObject {
+    pub key: String,
+    pub size: u64,
+    pub modified: DateTime<Utc>,
+}
+
+/// A place to keep files such as PDF snapshots, addressed by `/`-separated keys.
+pub trait Storage: Send + Sync {
+    /// Stores the stream under `key`, replacing what was there. Returns the size written.
+    fn put<'a>(&'a self, key: &'a str, data: ByteStream) -> BoxFuture<'a, Result<u64, PaymeError>>;
+
+    /// Streams the file at `key`, or fails with `NotFound`.
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<ByteStream, PaymeError>>;
+
+    /// Removes the file at `key`. Missing files are not an error.
+    fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<(), PaymeError>>;
+
+    /// Lists the files whose key starts with `prefix`.
+    fn list<'a>(&'a self, prefix: &'a str) -> BoxFuture<'a, Result<Vec<StoredObject>, PaymeError>>;
+}
+
+static BACKEND: OnceLock<Option<Arc<dyn Storage>>> = OnceLock::new();
+
+/// The backend chosen with `STORAGE_BACKEND`. The `database` backend keeps files in
+/// the database behind `pool`.
+pub fn backend(pool: &SqlitePool) -> Arc<dyn Storage> {
+    let configured = BACKEND.get_or_init(|| match config::storage_backend().as_str() {
+        "database" => None,
+        "local" => Some(Arc::new(LocalStorage::new(config::storage_dir())) as Arc<dyn Storage>),
+        "s3" => Some(Arc::new(
+            config::s3_settings()
+                .and_then(S3Storage::new)
+                .unwrap_or_else(|e| panic!("Invalid S3 storage settings: {e}")),
+        )),
+        other => panic!("Unknown STORAGE_BACKEND {other}, expected database, local or s3"),
+    });
+
+    match configured {
+        Some(storage) => storage.clone(),
+        None => Arc::new(DatabaseStorage::new(pool.clone())),
+    }
+}
+
+pub fn snapshot_key(month_id: i64) -> String {
+    format!("snapshots/{month_id}.pdf")
+}
+
+pub fn artifact_key(report_id: i64, job_id: i64) -> String {
+    format!("reports/{report_id}/{job_id}")
+}
+
+/// Seals `data` and stores it. Returns the size stored.
+pub async fn put_sealed(
+    storage: &dyn Storage,
+    key: &str,
+    data: Vec<u8>,
+) -> Result<u64, PaymeError> {
+    let sealed = Bytes::from(crypto::seal(data)?);
+    storage
+        .put(key, stream::once(async { Ok(sealed) }).boxed())
+        .await
+}
+
+/// Reads the file at `key` into memory. Files still kept in the database, from before
+/// `STORAGE_BACKEND` was switched, are moved to `storage` on the way.
+pub async fn read(
+    pool: &SqlitePool,
+    storage: &dyn Storage,
+    key: &str,
+) -> Result<Vec<u8>, PaymeError> {
+    match read_all(storage, key).await {
+        Err(PaymeError::NotFound) => {}
+        result => return result,
+    }
+
+    let database = DatabaseStorage::new(pool.clone());
+    let data = read_all(&database, key).await?;
+    let moved = Bytes::from(data.clone());
+    storage
+        .put(key, stream::once(async { Ok(moved) }).boxed())
+        .await?;
+    database.delete(key).await?;
+    tracing::info!("Moved stored file {key} out of the database");
+
+    Ok(data)
+}
+
+/// Reads a file stored with `put_sealed` back into memory, moving it like `read`.
+pub async fn read_sealed(
+    pool: &SqlitePool,
+    storage: &dyn Storage,
+    key: &str,
+) -> Result<Vec<u8>, PaymeError> {
+    crypto::open(read(pool, storage, key).await?)
+}
+
+/// Response body for a file stored with `put_sealed`. It is streamed as is unless
+/// encryption at rest is on, since sealed files can only be opened whole.
+pub async fn sealed_body(
+    pool: &SqlitePool,
+    storage: &dyn Storage,
+    key: &str,
+) -> Result<Body, PaymeError> {
+    if crypto::blob_cipher().is_some() {
+        return Ok(Body::from(read_sealed(pool, storage, key).await?));
+    }
+    match storage.get(key).await {
+        Ok(data) => Ok(Body::from_stream(data)),
+        Err(PaymeError::NotFound) => Ok(Body::from(read_sealed(pool, storage, key).await?)),
+        Err(e) => Err(e),
+    }
+}
+
+/// Hex SHA-256 of a file's content, stored to verify it when it is read back.
+pub fn content_hash(data: &[u8]) -> String {
+    to_hex(&Sha256::digest(data))
+}
+
+pub fn to_hex(data: &[u8]) -> String {
+    data.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+pub async fn read_all(storage: &dyn Storage, key: &str) -> Result<Vec<u8>, PaymeError> {
+    storage
+        .get(key)
+        .await?
+        .try_fold(Vec::new(), |mut data, chunk| async move {
+            data.extend_from_slice(&chunk);
+            Ok(data)
+        })
+        .await
+}
+
+/// Removes snapshot and report files that no row refers to any more, e.g. after a
+/// month was reopened or a report deleted. Returns how many were removed.
+pub async fn remove_orphans(
+    pool: &SqlitePool,
+    storage: &dyn Storage,
+    now: DateTime<Utc>,
+) -> Result<usize, PaymeError> {
+    let mut removed = 0;
+    for prefix in OWNED_PREFIXES {
+        // Listed before reading the keys in use, so files stored in between count as used
+        let objects = storage.list(prefix).await?;
+        let used: Vec<(String,)> = sqlx::query_as(
+            "SELECT storage_key FROM monthly_snapshots UNION SELECT storage_key FROM report_artifacts",
+        )
+        .fetch_all(pool)
+        .await?;
+        let used: HashSet<String> = used.into_iter().map(|(key,)| key).collect();
+
+        for object in objects {
+            if used.contains(&object.key) || object.modified > now - ORPHAN_GRACE {
+                continue;
+            }
+            storage.delete(&object.key).await?;
+            removed += 1;
+        }
+    }
+
+    Ok(removed)
+}
+
+/// Moves the files kept in the database to the configured backend, after switching
+/// `STORAGE_BACKEND` away from `database`. Returns how many were moved.
+pub async fn move_from_database(pool: &SqlitePool) -> Result<usize, PaymeError> {
+    let target = backend(pool);
+    if config::storage_backend() == "database" {
+        return Err(PaymeError::BadRequest(
+            "STORAGE_BACKEND is database, there is nowhere to move files to".to_string(),
+        ));
+    }
+
+    let source = DatabaseStorage::new(pool.clone());
+    let objects = source.list("").await?;
+    for object in &objects {
+        target
+            .put(&object.key, source.get(&object.key).await?)
+            .await?;
+        source.delete(&object.key).await?;
+    }
+
+    Ok(objects.len())
+}
+
+/// Errors of a backend, with missing files mapped to `NotFound`.
+fn io_error(e: std::io::Error) -> PaymeError {
+    if e.kind() == std::io::ErrorKind::NotFound {
+        PaymeError::NotFound
+    } else {
+        PaymeError::Internal(format!("Storage error: {e}"))
+    }
+}
+
+/// Rejects keys that could escape the storage root, such as `../x` or `/x`.
+fn check_key(key: &str) -> Result<(), PaymeError> {
+    let valid = !key.is_empty()
+        && key
+            .split('/')
+            .all(|part| !part.is_empty() && part != "." && part != "..");
+    if valid {
+        Ok(())
+    } else {
+        Err(PaymeError::Internal(format!("Invalid storage key {key:?}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_key() {
+        assert!(check_key("snapshots/1.pdf").is_ok());
+        assert!(check_key("reports/2/3").is_ok());
+        assert!(check_key("").is_err());
+        assert!(check_key("/etc/passwd").is_err());
+        assert!(check_key("snapshots/../../x").is_err());
+        assert!(check_key("snapshots//1.pdf").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_local_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = LocalStorage::new(dir.path().to_path_buf());
+
+        let chunks = vec![Ok(Bytes::from("hello ")), Ok(Bytes::from("world"))];
+        let size = storage
+            .put("reports/1/2", stream::iter(chunks).boxed())
+            .await
+            .unwrap();
+        assert_eq!(size, 11);
+        assert_eq!(
+            read_all(&storage, "reports/1/2").await.unwrap(),
+            b"hello world"
+        );
+
+        let listed = storage.list("reports/").await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].key, "reports/1/2");
+        assert_eq!(listed[0].size, 11);
+        assert!(storage.list("snapshots/").await.unwrap().is_empty());
+
+        storage.delete("reports/1/2").await.unwrap();
+        storage.delete("reports/1/2").await.unwrap();
+        assert!(matches!(
+            storage.get("reports/1/2").await,
+            Err(PaymeError::NotFound)
+        ));
+    }
+}
@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use futures_util::future::BoxFuture;
+use futures_util::stream::{StreamExt, TryStreamExt};
+use object_store::aws::AmazonS3Builder;
+use object_store::path::Path;
+use object_store::{ObjectStore, WriteMultipart};
+
+use super::{check_key, ByteStream, Storage, StoredObject};
+use crate::config::S3Settings;
+use crate::error::PaymeError;
+
+/// Parts uploaded at once while streaming a file to the bucket.
+const UPLOAD_CONCURRENCY: usize = 4;
+
+/// Keeps files in an S3 bucket or an S3-compatible service such as MinIO.
+pub struct S3Storage {
+    store: Arc<dyn ObjectStore>,
+}
+
+impl S3Storage {
+    pub fn new(settings: S3Settings) -> Result<Self, String> {
+        let mut builder = AmazonS3Builder::new()
+            .with_bucket_name(settings.bucket)
+            .with_region(settings.region)
+            .with_access_key_id(settings.access_key_id)
+            .with_secret_access_key(settings.secret_access_key);
+        if let Some(endpoint) = settings.endpoint {
+            builder = builder
+                .with_allow_http(endpoint.starts_with("http://"))
+                .with_endpoint(endpoint);
+        }
+
+        Ok(Self {
+            store: Arc::new(builder.build().map_err(|e| e.to_string())?),
+        })
+    }
+}
+
+fn s3_error(e: object_store::Error) -> PaymeError {
+    match e {
+        object_store::Error::NotFound { .. } => PaymeError::NotFound,
+        e => PaymeError::Internal(format!("Storage error: {e}")),
+    }
+}
+
+impl Storage for S3Storage {
+    fn put<'a>(
+        &'a self,
+        key: &'a str,
+        mut data: ByteStream,
+    ) -> BoxFuture<'a, Result<u64, PaymeError>> {
+        Box::pin(async move {
+            check_key(key)?;
+            let upload = self
+                .store
+                .put_multipart(&Path::from(key))
+                .await
+                .map_err(s3_error)?;
+            let mut upload = WriteMultipart::new(upload);
+
+            let mut size = 0;
+            while let Some(chunk) = data.next().await {
+                let chunk = match chunk {
+                    Ok(chunk) => chunk,
+                    Err(e) => {
+                        let _ = upload.abort().await;
+                        return Err(e);
+                    }
+                };
+                upload
+                    .wait_for_capacity(UPLOAD_CONCURRENCY)
+                    .await
+                    .map_err(s3_error)?;
+                upload.write(&chunk);
+                size += chunk.len() as u64;
+            }
+            upload.finish().await.map_err(s3_error)?;
+
+            Ok(size)
+        })
+    }
+
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<ByteStream, PaymeError>> {
+        Box::pin(async move {
+            check_key(key)?;
+            let result = self.store.get(&Path::from(key)).await.map_err(s3_error)?;
+            Ok(result.into_stream().map_err(s3_error).boxed())
+        })
+    }
+
+    fn delete<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Result<(), PaymeError>> {
+        Box::pin(async move {
+            check_key(key)?;
+            match self.store.delete(&Path::from(key)).await {
+                Err(object_store::Error::NotFound { .. }) | Ok(()) => Ok(()),
+                Err(e) => Err(s3_error(e)),
+            }
+        })
+    }
+
+    fn list<'a>(&'a self, prefix: &'a str) -> BoxFuture<'a, Result<Vec<StoredObject>, PaymeError>> {
+        Box::pin(async move {
+            let objects: Vec<_> = self
+                .store
+                .list(Some(&Path::from(prefix)))
+                .map_err(s3_error)
+                .try_collect()
+                .await?;
+
+            Ok(objects
+                .into_iter()
+                .map(|meta| StoredObject {
+                    key: meta.location.to_string(),
+                    size: meta.size,
+                    modified: meta.last_modified,
+                })
+                .filter(|object| object.key.starts_with(prefix))
+                .collect())
+        })
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Months, NaiveDate};
+
+/// Months in a row a description must be charged before it counts as recurring.
+pub const MIN_MONTHS: usize = 3;
+
+/// A single spending item considered for recurring detection.
+#[derive(Debug, Clone)]
+pub struct Charge {
+    pub description: String,
+    pub category_label: String,
+    pub amount: f64,
+    pub spent_on: NaiveDate,
+}
+
+/// A description charged once a month for at least [`MIN_MONTHS`] months in a row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecurringCharge {
+    pub label: String,
+    pub category_label: String,
+    pub amount: f64,
+    /// Amount of the charge before the latest one.
+    pub previous_amount: f64,
+    pub last_charged: NaiveDate,
+    /// One month after the last charge.
+    pub next_renewal: NaiveDate,
+}
+
+impl RecurringCharge {
+    pub fn price_changed(&self) -> bool {
+        price_changed(self.previous_amount, self.amount)
+    }
+}
+
+/// True when two amounts differ by at least a cent.
+pub fn price_changed(previous: f64, current: f64) -> bool {
+    ((current - previous) * 100.0).round() != 0.0
+}
+
+/// Finds descriptions charged exactly once per month over the latest [`MIN_MONTHS`]
+/// consecutive months, the last one this month or last month. Descriptions bought
+/// several times in a month, like groceries, are not subscriptions and are skipped.
+pub fn detect_recurring(charges: &[Charge], today: NaiveDate) -> Vec<RecurringCharge> {
+    let mut by_description: BTreeMap<String, Vec<&Charge>> = BTreeMap::new();
+    for charge in charges {
+        by_description
+            .entry(charge.description.trim().to_lowercase())
+            .or_default()
+            .push(charge);
+    }
+
+    let mut recurring: Vec<RecurringCharge> = by_description
+        .into_values()
+        .filter_map(|mut group| {
+            group.sort_by_key(|c| c.spent_on);
+            let months: Vec<i32> = group.iter().map(|c| month_index(c.spent_on)).collect();
+            if months.len() < MIN_MONTHS || months.windows(2).any(|w| w[0] == w[1]) {
+                return None;
+            }
+            let latest = &months[months.len() - MIN_MONTHS..];
+            let lapsed = month_index(today) - latest[MIN_MONTHS - 1] > 1;
+            if lapsed || latest.windows(2).any(|w| w[1] - w[0] != 1) {
+                return None;
+            }
+
+            let last = group[group.len() - 1];
+            let previous = group[group.len() - 2];
+            Some(RecurringCharge {
+                label: last.description.trim().to_string(),
+                category_label: last.category_label.clone(),
+                amount: last.amount,
+                previous_amount: previous.amount,
+                last_charged: last.spent_on,
+                next_renewal: last.spent_on + Months::new(1),
+            })
+        })
+        .collect();
+    recurring.sort_by_key(|r| r.next_renewal);
+    recurring
+}
+
+fn month_index(date: NaiveDate) -> i32 {
+    date.year() * 12 + date.month0() as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 7, 1).unwrap()
+    }
+
+    fn charge(description: &str, amount: f64, year: i32, month: u32, day: u32) -> Charge {
+        Charge {
+            description: description.to_string(),
+            category_label: "Fun".to_string(),
+            amount,
+            spent_on: NaiveDate::from_ymd_opt(year, month, day).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_detects_monthly_charge() {
+        let charges = [
+            charge("Netflix", 15.99, 2024, 4, 3),
+            charge("netflix ", 15.99, 2024, 5, 3),
+            charge("Netflix", 17.99, 2024, 6, 3),
+        ];
+        let recurring = detect_recurring(&charges, today());
+        assert_eq!(recurring.len(), 1);
+        assert_eq!(recurring[0].label, "Netflix");
+        assert_eq!(recurring[0].amount, 17.99);
+        assert_eq!(recurring[0].previous_amount, 15.99);
+        assert!(recurring[0].price_changed());
+        assert_eq!(
+            recurring[0].next_renewal,
+            NaiveDate::from_ymd_opt(2024, 7, 3).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_renewal_clamps_to_month_end() {
+        let charges = [
+            charge("Gym", 30.0, 2023, 11, 30),
+            charge("Gym", 30.0, 2023, 12, 31),
+            charge("Gym", 30.0, 2024, 1, 31),
+        ];
+        let recurring = detect_recurring(&charges, NaiveDate::from_ymd_opt(2024, 2, 1).unwrap());
+        assert!(!recurring[0].price_changed());
+        assert_eq!(
+            recurring[0].next_renewal,
+            NaiveDate::from_ymd_opt(2024, 2, 29).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_skips_gaps_and_repeat_purchases() {
+        let charges = [
+            charge("Spotify", 9.99, 2024, 3, 1),
+            charge("Spotify", 9.99, 2024, 4, 1),
+            charge("Spotify", 9.99, 2024, 6, 1),
+            charge("Bakery", 4.0, 2024, 4, 2),
+            charge("Bakery", 4.0, 2024, 5, 2),
+            charge("Bakery", 4.0, 2024, 5, 9),
+            charge("Bakery", 4.0, 2024, 6, 2),
+        ];
+        assert!(detect_recurring(&charges, today()).is_empty());
+    }
+
+    #[test]
+    fn test_skips_lapsed_subscription() {
+        let charges = [
+            charge("Magazine", 5.0, 2024, 1, 10),
+            charge("Magazine", 5.0, 2024, 2, 10),
+            charge("Magazine", 5.0, 2024, 3, 10),
+        ];
+        assert!(detect_recurring(&charges, today()).is_empty());
+    }
+}
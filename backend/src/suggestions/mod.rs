@@ -0,0 +1,177 @@
+//! Autocompletion for item descriptions, built from what the user entered before.
+//!
+//! Each user's suggestions are computed with one grouped query and kept for a few
+//! minutes, so typing a description letter by letter doesn't scan the items table on
+//! every keystroke. Changing an item drops the user's entry.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use sqlx::SqlitePool;
+
+use crate::models::DescriptionSuggestion;
+
+/// How long a user's suggestions are reused before they are computed again.
+const TTL: Duration = Duration::from_secs(300);
+/// Most frequent descriptions kept per user; rarer ones are never suggested.
+const MAX_DESCRIPTIONS: i64 = 500;
+
+struct Cached {
+    at: Instant,
+    suggestions: Arc<Vec<DescriptionSuggestion>>,
+}
+
+static CACHE: Mutex<Option<HashMap<i64, Cached>>> = Mutex::new(None);
+
+#[derive(sqlx::FromRow)]
+struct DescriptionRow {
+    key: String,
+    description: String,
+    uses: i64,
+    amounts: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct CategoryRow {
+    key: String,
+    category_id: i64,
+    category_label: String,
+}
+
+/// Up to `limit` of the user's descriptions starting with `prefix`, ignoring case, most
+/// used first.
+pub async fn matching(
+    pool: &SqlitePool,
+    user_id: i64,
+    prefix: &str,
+    limit: usize,
+) -> Result<Vec<DescriptionSuggestion>, sqlx::Error> {
+    let prefix = prefix.trim().to_lowercase();
+    Ok(for_user(pool, user_id)
+        .await?
+        .iter()
+        .filter(|s| s.description.to_lowercase().starts_with(&prefix))
+        .take(limit)
+        .cloned()
+        .collect())
+}
+
+/// Forgets the user's suggestions so the next lookup sees their latest items.
+pub fn invalidate(user_id: i64) {
+    if let Some(cache) = CACHE.lock().unwrap().as_mut() {
+        cache.remove(&user_id);
+    }
+}
+
+async fn for_user(
+    pool: &SqlitePool,
+    user_id: i64,
+) -> Result<Arc<Vec<DescriptionSuggestion>>, sqlx::Error> {
+    if let Some(cached) = CACHE
+        .lock()
+        .unwrap()
+        .as_ref()
+        .and_then(|cache| cache.get(&user_id))
+        .filter(|cached| cached.at.elapsed() < TTL)
+    {
+        return Ok(cached.suggestions.clone());
+    }
+
+    let suggestions = Arc::new(load(pool, user_id).await?);
+    let mut cache = CACHE.lock().unwrap();
+    let cache = cache.get_or_insert_with(HashMap::new);
+    cache.retain(|_, cached| cached.at.elapsed() < TTL);
+    cache.insert(
+        user_id,
+        Cached {
+            at: Instant::now(),
+            suggestions: suggestions.clone(),
+        },
+    );
+    Ok(suggestions)
+}
+
+/// Groups the user's spending by description, ignoring case and surrounding spaces. The
+/// latest spelling is shown, with the category it was filed under most often.
+async fn load(pool: &SqlitePool, user_id: i64) -> Result<Vec<DescriptionSuggestion>, sqlx::Error> {
+    let descriptions: Vec<DescriptionRow> = sqlx::query_as(
+        r#"
+        SELECT LOWER(TRIM(i.description)) AS key, TRIM(i.description) AS description,
+               MAX(i.spent_on) AS last_used, COUNT(*) AS uses,
+               json_group_array(i.amount) AS amounts
+        FROM items i
+        JOIN months m ON i.month_id = m.id
+        WHERE m.user_id = ? AND i.savings_destination = 'none'
+        GROUP BY key
+        ORDER BY uses DESC, last_used DESC
+        LIMIT ?
+        "#,
+    )
+    .bind(user_id)
+    .bind(MAX_DESCRIPTIONS)
+    .fetch_all(pool)
+    .await?;
+
+    let categories: Vec<CategoryRow> = sqlx::query_as(
+        r#"
+        SELECT LOWER(TRIM(i.description)) AS key, i.category_id, bc.label AS category_label,
+               COUNT(*) AS uses, MAX(i.spent_on) AS last_used
+        FROM items i
+        JOIN months m ON i.month_id = m.id
+        JOIN budget_categories bc ON i.category_id = bc.id
+        WHERE m.user_id = ? AND i.savings_destination = 'none'
+        GROUP BY key, i.category_id
+        ORDER BY uses DESC, last_used DESC
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await?;
+    let mut usual: HashMap<String, CategoryRow> = HashMap::new();
+    for row in categories {
+        usual.entry(row.key.clone()).or_insert(row);
+    }
+
+    Ok(descriptions
+        .into_iter()
+        .filter_map(|row| {
+            let category = usual.remove(&row.key)?;
+            let amounts: Vec<f64> = serde_json::from_str(&row.amounts).unwrap_or_default();
+            Some(DescriptionSuggestion {
+                description: row.description,
+                category_id: category.category_id,
+                category_label: category.category_label,
+                median_amount: median(amounts),
+                uses: row.uses,
+            })
+        })
+        .collect())
+}
+
+/// Median rounded to the cent, so one unusual purchase doesn't skew the suggestion.
+fn median(mut amounts: Vec<f64>) -> f64 {
+    if amounts.is_empty() {
+        return 0.0;
+    }
+    amounts.sort_by(f64::total_cmp);
+    let middle = amounts.len() / 2;
+    let median = if amounts.len().is_multiple_of(2) {
+        (amounts[middle - 1] + amounts[middle]) / 2.0
+    } else {
+        amounts[middle]
+    };
+    (median * 100.0).round() / 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(vec![]), 0.0);
+        assert_eq!(median(vec![4.5, 120.0, 3.8]), 4.5);
+        assert_eq!(median(vec![3.8, 4.5, 4.0, 4.2]), 4.1);
+    }
+}
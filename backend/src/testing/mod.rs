@@ -0,0 +1,228 @@
+//! Fixtures for tests, built with the `testing` feature: an in-memory database with
+//! the real migrations applied, and builders that insert users, categories, months and
+//! items with defaults for everything a test doesn't care about.
+//!
+//! ```ignore
+//! let pool = testing::memory_pool().await;
+//! let user = UserBuilder::new("alice").create(&pool).await;
+//! let category = CategoryBuilder::new(user.id, "Groceries").create(&pool).await;
+//! let month = MonthBuilder::new(user.id, 2024, 3).create(&pool).await;
+//! ItemBuilder::new(month, category).amount(12.5).create(&pool).await;
+//! ```
+
+use chrono::Utc;
+use sqlx::SqlitePool;
+
+use crate::db;
+use crate::handlers::auth::hash_password;
+use crate::jwt;
+use crate::middleware::auth::Claims;
+
+/// A fresh in-memory database, migrated like a server's at startup.
+pub async fn memory_pool() -> SqlitePool {
+    let pool = db::create_pool("sqlite::memory:")
+        .await
+        .expect("Failed to create in-memory database");
+    db::run_migrations(&pool)
+        .await
+        .expect("Failed to run migrations");
+    pool
+}
+
+/// A user created by [`UserBuilder`], with a session token for authenticated requests.
+pub struct TestUser {
+    pub id: i64,
+    pub username: String,
+    pub token: String,
+}
+
+pub struct UserBuilder {
+    username: String,
+    password: String,
+}
+
+impl UserBuilder {
+    pub fn new(username: &str) -> Self {
+        Self {
+            username: username.to_string(),
+            password: "password123".to_string(),
+        }
+    }
+
+    pub fn password(mut self, password: &str) -> Self {
+        self.password = password.to_string();
+        self
+    }
+
+    /// Inserts the user without the starter categories registration adds.
+    pub async fn create(self, pool: &SqlitePool) -> TestUser {
+        let password_hash = hash_password(&self.password).expect("Failed to hash password");
+        let id = sqlx::query_scalar::<_, i64>(
+            "INSERT INTO users (username, password_hash) VALUES (?, ?) RETURNING id",
+        )
+        .bind(&self.username)
+        .bind(&password_hash)
+        .fetch_one(pool)
+        .await
+        .expect("Failed to create test user");
+
+        let claims = Claims {
+            sub: id,
+            username: self.username.clone(),
+            exp: (Utc::now() + jwt::keys().lifetime()).timestamp() as usize,
+        };
+        TestUser {
+            id,
+            username: self.username,
+            token: jwt::keys().sign(&claims).expect("Failed to sign token"),
+        }
+    }
+}
+
+pub struct CategoryBuilder {
+    user_id: i64,
+    label: String,
+    default_amount: f64,
+    tracking_only: bool,
+}
+
+impl CategoryBuilder {
+    pub fn new(user_id: i64, label: &str) -> Self {
+        Self {
+            user_id,
+            label: label.to_string(),
+            default_amount: 0.0,
+            tracking_only: false,
+        }
+    }
+
+    pub fn default_amount(mut self, amount: f64) -> Self {
+        self.default_amount = amount;
+        self
+    }
+
+    pub fn tracking_only(mut self) -> Self {
+        self.tracking_only = true;
+        self
+    }
+
+    pub async fn create(self, pool: &SqlitePool) -> i64 {
+        sqlx::query_scalar::<_, i64>(
+            "INSERT INTO budget_categories (user_id, label, default_amount, tracking_only) VALUES (?, ?, ?, ?) RETURNING id",
+        )
+        .bind(self.user_id)
+        .bind(&self.label)
+        .bind(self.default_amount)
+        .bind(self.tracking_only)
+        .fetch_one(pool)
+        .await
+        .expect("Failed to create test category")
+    }
+}
+
+pub struct MonthBuilder {
+    user_id: i64,
+    year: i32,
+    month: i32,
+    closed: bool,
+}
+
+impl MonthBuilder {
+    pub fn new(user_id: i64, year: i32, month: i32) -> Self {
+        Self {
+            user_id,
+            year,
+            month,
+            closed: false,
+        }
+    }
+
+    /// Marks the month closed without freezing its data, so its summary is still
+    /// computed from the live tables.
+    pub fn closed(mut self) -> Self {
+        self.closed = true;
+        self
+    }
+
+    pub async fn create(self, pool: &SqlitePool) -> i64 {
+        sqlx::query_scalar::<_, i64>(
+            r#"
+            INSERT INTO months (user_id, year, month, is_closed, closed_at)
+            VALUES (?, ?, ?, ?, CASE WHEN ? THEN datetime('now') END)
+            RETURNING id
+            "#,
+        )
+        .bind(self.user_id)
+        .bind(self.year)
+        .bind(self.month)
+        .bind(self.closed)
+        .bind(self.closed)
+        .fetch_one(pool)
+        .await
+        .expect("Failed to create test month")
+    }
+}
+
+pub struct ItemBuilder {
+    month_id: i64,
+    category_id: i64,
+    description: String,
+    amount: f64,
+    spent_on: Option<String>,
+    savings_destination: String,
+}
+
+impl ItemBuilder {
+    pub fn new(month_id: i64, category_id: i64) -> Self {
+        Self {
+            month_id,
+            category_id,
+            description: "Item".to_string(),
+            amount: 10.0,
+            spent_on: None,
+            savings_destination: "none".to_string(),
+        }
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = description.to_string();
+        self
+    }
+
+    pub fn amount(mut self, amount: f64) -> Self {
+        self.amount = amount;
+        self
+    }
+
+    /// Defaults to the first day of the item's month.
+    pub fn spent_on(mut self, date: &str) -> Self {
+        self.spent_on = Some(date.to_string());
+        self
+    }
+
+    /// `savings` or `retirement_savings` to move money instead of spending it.
+    pub fn savings_destination(mut self, destination: &str) -> Self {
+        self.savings_destination = destination.to_string();
+        self
+    }
+
+    pub async fn create(self, pool: &SqlitePool) -> i64 {
+        sqlx::query_scalar::<_, i64>(
+            r#"
+            INSERT INTO items (month_id, category_id, description, amount, spent_on, savings_destination)
+            SELECT id, ?, ?, ?, COALESCE(?, printf('%04d-%02d-01', year, month)), ?
+            FROM months WHERE id = ?
+            RETURNING id
+            "#,
+        )
+        .bind(self.category_id)
+        .bind(&self.description)
+        .bind(self.amount)
+        .bind(&self.spent_on)
+        .bind(&self.savings_destination)
+        .bind(self.month_id)
+        .fetch_one(pool)
+        .await
+        .expect("Failed to create test item")
+    }
+}
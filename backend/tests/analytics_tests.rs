@@ -0,0 +1,197 @@
+mod common;
+
+use chrono::{Datelike, Duration, Utc};
+use common::{
+    auth_name, auth_value, create_test_category, create_test_item, create_test_month,
+    create_test_pool, create_test_server, create_test_user, generate_token,
+};
+use payme::create_app;
+
+async fn setup_with_user() -> (axum_test::TestServer, sqlx::SqlitePool, i64, String) {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let app = create_app(pool.clone());
+    let server = create_test_server(app);
+    (server, pool, user_id, token)
+}
+
+fn days_ago(days: i64) -> String {
+    (Utc::now().date_naive() - Duration::days(days)).to_string()
+}
+
+#[tokio::test]
+async fn test_top_spending() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let food = create_test_category(&pool, user_id, "Food", 500.0).await;
+    let fun = create_test_category(&pool, user_id, "Fun", 200.0).await;
+    for day in 1..=3 {
+        create_test_item(&pool, month_id, food, "Coffee", 4.0, &days_ago(day)).await;
+    }
+    create_test_item(&pool, month_id, food, "coffee", 4.0, &days_ago(4)).await;
+    create_test_item(&pool, month_id, food, "Supermarket", 120.0, &days_ago(5)).await;
+    create_test_item(&pool, month_id, fun, "Concert", 80.0, &days_ago(6)).await;
+    create_test_item(&pool, month_id, fun, "Old trip", 900.0, &days_ago(200)).await;
+
+    let response = server
+        .get("/api/analytics/top?period=90d")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+
+    response.assert_status_ok();
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["top_by_spend"][0]["description"], "Supermarket");
+    assert_eq!(body["most_frequent"][0]["purchase_count"], 4);
+    assert_eq!(body["most_frequent"][0]["total_spent"], 16.0);
+    assert_eq!(body["largest_transactions"][0]["amount"], 120.0);
+    assert_eq!(body["largest_transactions"].as_array().unwrap().len(), 6);
+
+    let body: serde_json::Value = server
+        .get(&format!("/api/analytics/top?period=1y&category_id={}", fun))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(body["top_by_spend"][0]["description"], "Old trip");
+    assert_eq!(body["top_by_spend"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_top_spending_invalid_period() {
+    let (server, _pool, _user_id, token) = setup_with_user().await;
+
+    for period in ["forever", "9223372036854775807y"] {
+        server
+            .get(&format!("/api/analytics/top?period={period}"))
+            .add_header(auth_name(), auth_value(&token))
+            .await
+            .assert_status_bad_request();
+    }
+}
+
+#[tokio::test]
+async fn test_streaks() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let spent_on = Utc::now().date_naive() - Duration::days(3);
+    let month_id =
+        create_test_month(&pool, user_id, spent_on.year(), spent_on.month() as i32).await;
+    let cat_id = create_test_category(&pool, user_id, "Food", 500.0).await;
+    create_test_item(
+        &pool,
+        month_id,
+        cat_id,
+        "Lunch",
+        12.0,
+        &spent_on.to_string(),
+    )
+    .await;
+
+    let response = server
+        .get("/api/analytics/streaks")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+
+    response.assert_status_ok();
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["current_streak"], 3);
+    assert!(body["best_streak"].as_i64().unwrap() >= 3);
+    let months = body["months"].as_array().unwrap();
+    assert_eq!(months.len(), 1);
+    assert_eq!(months[0]["longest_streak"], body["best_streak"]);
+}
+
+#[tokio::test]
+async fn test_streaks_without_months() {
+    let (server, _pool, _user_id, token) = setup_with_user().await;
+
+    let body: serde_json::Value = server
+        .get("/api/analytics/streaks")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(body["current_streak"], 0);
+    assert!(body["months"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_seasonality_detection() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let heating = create_test_category(&pool, user_id, "Heating", 100.0).await;
+    let food = create_test_category(&pool, user_id, "Food", 400.0).await;
+
+    let heating_bills = [
+        200.0, 180.0, 120.0, 60.0, 20.0, 0.0, 0.0, 0.0, 20.0, 80.0, 150.0, 210.0,
+    ];
+    for (month, bill) in (1..=12).zip(heating_bills) {
+        let month_id = create_test_month(&pool, user_id, 2023, month).await;
+        let day = format!("2023-{:02}-10", month);
+        if bill > 0.0 {
+            create_test_item(&pool, month_id, heating, "Gas", bill, &day).await;
+        }
+        create_test_item(&pool, month_id, food, "Groceries", 400.0, &day).await;
+    }
+
+    let empty: serde_json::Value = server
+        .get("/api/v1/analytics/seasonality")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert!(empty["computed_at"].is_null());
+    assert!(empty["categories"].as_array().unwrap().is_empty());
+
+    let refresh: serde_json::Value = server
+        .post("/api/v1/analytics/seasonality/refresh")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert!(refresh["job_id"].as_i64().is_some());
+    payme::jobs::run_pending(&pool).await.unwrap();
+
+    let result: serde_json::Value = server
+        .get("/api/v1/analytics/seasonality")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert!(result["computed_at"].as_str().is_some());
+    let categories = result["categories"].as_array().unwrap();
+    assert_eq!(categories.len(), 1);
+    assert_eq!(categories[0]["category_id"], heating);
+    let average = categories[0]["average"].as_f64().unwrap();
+    assert!((average - 1040.0 / 12.0).abs() < 1e-9);
+    assert_eq!(categories[0]["expected"][11], 210.0);
+    assert_eq!(categories[0]["expected"][6], 0.0);
+
+    // The year plan shows what a seasonal category is expected to cost each month
+    let plan: serde_json::Value = server
+        .post("/api/v1/plans/2024")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&serde_json::json!({
+            "categories": [
+                { "category_id": heating, "amounts": vec![100.0; 12] },
+                { "category_id": food, "amounts": vec![400.0; 12] }
+            ]
+        }))
+        .await
+        .json();
+    let plans = plan["categories"].as_array().unwrap();
+    let heating_plan = plans.iter().find(|p| p["category_id"] == heating).unwrap();
+    assert_eq!(heating_plan["seasonal"][0], 200.0);
+    let food_plan = plans.iter().find(|p| p["category_id"] == food).unwrap();
+    assert!(food_plan["seasonal"].is_null());
+
+    let stats: serde_json::Value = server
+        .get("/api/v1/stats")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    let heating_stats = stats["category_comparisons"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|c| c["category_id"] == heating)
+        .unwrap()
+        .clone();
+    assert_eq!(heating_stats["seasonal_expected"], 210.0);
+}
@@ -1,5 +1,8 @@
 mod common;
 
+use std::net::SocketAddr;
+
+use axum::extract::connect_info::MockConnectInfo;
 use common::{
     auth_name, auth_value, create_test_pool, create_test_server, create_test_user, generate_token,
 };
@@ -39,6 +42,39 @@ async fn test_register_success() {
     assert!(body["id"].as_i64().is_some());
 }
 
+#[tokio::test]
+async fn test_register_seeds_starter_data() {
+    let pool = create_test_pool().await;
+    let server = create_test_server(create_app(pool.clone()));
+
+    let body: serde_json::Value = server
+        .post("/api/auth/register")
+        .json(&json!({
+            "username": "newuser",
+            "password": "password123"
+        }))
+        .await
+        .json();
+    let user_id = body["id"].as_i64().unwrap();
+
+    let categories: Vec<(String, f64)> = sqlx::query_as(
+        "SELECT label, default_amount FROM budget_categories WHERE user_id = ? ORDER BY id",
+    )
+    .bind(user_id)
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(categories[0], ("Groceries".to_string(), 400.0));
+    assert_eq!(categories.len(), 5);
+
+    let fixed: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM fixed_expenses WHERE user_id = ?")
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(fixed, 3);
+}
+
 #[tokio::test]
 async fn test_register_duplicate_username() {
     let pool = create_test_pool().await;
@@ -110,6 +146,78 @@ async fn test_login_success() {
     assert!(cookies.iter().any(|c| c.name() == "token"));
 }
 
+#[tokio::test]
+async fn test_login_locked_after_repeated_failures() {
+    let pool = create_test_pool().await;
+    create_test_user(&pool, "loginuser", "password123").await;
+    let server = create_test_server(create_app(pool));
+
+    for _ in 0..5 {
+        server
+            .post("/api/auth/login")
+            .json(&json!({ "username": "loginuser", "password": "wrongpassword" }))
+            .await
+            .assert_status_unauthorized();
+    }
+
+    server
+        .post("/api/auth/login")
+        .json(&json!({ "username": "loginuser", "password": "password123" }))
+        .await
+        .assert_status(axum::http::StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn test_security_events() {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "loginuser", "password123").await;
+    let token = generate_token(user_id, "loginuser");
+    std::env::set_var("TRUSTED_PROXIES", "10.0.0.1");
+    let proxy = SocketAddr::from(([10, 0, 0, 1], 443));
+    let server = create_test_server(create_app(pool.clone()).layer(MockConnectInfo(proxy)));
+
+    server
+        .post("/api/auth/login")
+        .add_header("user-agent", "payme-test")
+        .add_header("x-forwarded-for", "198.51.100.1, 203.0.113.7, 10.0.0.1")
+        .json(&json!({ "username": "loginuser", "password": "wrongpassword" }))
+        .await
+        .assert_status_unauthorized();
+    server
+        .post("/api/auth/login")
+        .json(&json!({ "username": "loginuser", "password": "password123" }))
+        .await
+        .assert_status_ok();
+
+    let response = server
+        .get("/api/auth/security-events")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+
+    response.assert_status_ok();
+    let events: serde_json::Value = response.json();
+    assert_eq!(events[0]["kind"], "login_succeeded");
+    assert_eq!(events[1]["kind"], "login_failed");
+    assert_eq!(events[1]["ip"], "203.0.113.7");
+    assert_eq!(events[1]["user_agent"], "payme-test");
+
+    // Without a trusted proxy in front, the forwarded address is whatever the client says
+    let direct = SocketAddr::from(([192, 0, 2, 5], 50000));
+    let server = create_test_server(create_app(pool).layer(MockConnectInfo(direct)));
+    server
+        .post("/api/auth/login")
+        .add_header("x-forwarded-for", "203.0.113.7")
+        .json(&json!({ "username": "loginuser", "password": "wrongpassword" }))
+        .await
+        .assert_status_unauthorized();
+    let events: serde_json::Value = server
+        .get("/api/auth/security-events")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(events[0]["ip"], "192.0.2.5");
+}
+
 #[tokio::test]
 async fn test_login_invalid_username() {
     let server = setup().await;
@@ -258,6 +366,35 @@ async fn test_change_password_wrong_current() {
     response.assert_status_unauthorized();
 }
 
+#[tokio::test]
+async fn test_clear_all_data_dry_run() {
+    let (server, _user_id, token) = setup_with_user().await;
+
+    let response = server
+        .delete("/api/auth/clear-data?dry_run=true")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "password": "password123"
+        }))
+        .await;
+    response.assert_status_ok();
+    let report: serde_json::Value = response.json();
+    assert_eq!(report["dry_run"], true);
+    let users = report["changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|c| c["table"] == "users")
+        .unwrap();
+    assert_eq!(users["deleted"], 1);
+
+    server
+        .get("/api/auth/me")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_ok();
+}
+
 #[tokio::test]
 async fn test_clear_all_data() {
     let (server, _user_id, token) = setup_with_user().await;
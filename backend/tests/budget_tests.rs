@@ -2,7 +2,8 @@ mod common;
 
 use common::{
     auth_name, auth_value, close_test_month, create_test_budget, create_test_category,
-    create_test_month, create_test_pool, create_test_server, create_test_user, generate_token,
+    create_test_fixed_expense, create_test_income, create_test_item, create_test_month,
+    create_test_pool, create_test_server, create_test_user, generate_token,
 };
 use payme::create_app;
 use serde_json::json;
@@ -148,6 +149,57 @@ async fn test_list_monthly_budgets() {
     assert_eq!(body[0]["allocated_amount"], 500.0);
 }
 
+#[tokio::test]
+async fn test_list_monthly_budgets_with_average() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let food = create_test_category(&pool, user_id, "Food", 500.0).await;
+    let fun = create_test_category(&pool, user_id, "Fun", 100.0).await;
+    let january = create_test_month(&pool, user_id, 2024, 1).await;
+    let may = create_test_month(&pool, user_id, 2024, 5).await;
+    let july = create_test_month(&pool, user_id, 2024, 7).await;
+    let august = create_test_month(&pool, user_id, 2024, 8).await;
+    create_test_budget(&pool, august, food, 500.0).await;
+    create_test_budget(&pool, august, fun, 100.0).await;
+
+    // January is outside the six months before August
+    create_test_item(&pool, january, food, "Feast", 1000.0, "2024-01-10").await;
+    create_test_item(&pool, may, food, "Groceries", 100.0, "2024-05-10").await;
+    create_test_item(&pool, july, food, "Groceries", 200.0, "2024-07-10").await;
+    let saved = create_test_item(&pool, july, food, "Put aside", 50.0, "2024-07-11").await;
+    sqlx::query("UPDATE items SET savings_destination = 'savings' WHERE id = ?")
+        .bind(saved)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let response = server
+        .get(&format!(
+            "/api/months/{}/budgets?include_average=true",
+            august
+        ))
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+
+    response.assert_status_ok();
+    let body: Vec<serde_json::Value> = response.json();
+    let average = |category: i64| {
+        body.iter()
+            .find(|b| b["category_id"] == category)
+            .map(|b| b["average_spent"].clone())
+            .unwrap()
+    };
+    assert_eq!(average(food), 150.0);
+    assert_eq!(average(fun), 0.0);
+
+    let response = server
+        .get(&format!("/api/months/{}/budgets", august))
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    let body: Vec<serde_json::Value> = response.json();
+    assert!(body[0].get("average_spent").is_none());
+}
+
 #[tokio::test]
 async fn test_update_monthly_budget() {
     let (server, pool, user_id, token) = setup_with_user().await;
@@ -188,3 +240,819 @@ async fn test_update_monthly_budget_closed_month() {
 
     response.assert_status_bad_request();
 }
+
+#[tokio::test]
+async fn test_locked_category_refuses_new_items() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let dining_id = create_test_category(&pool, user_id, "Dining Out", 150.0).await;
+    let food_id = create_test_category(&pool, user_id, "Food", 500.0).await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let budget_id = create_test_budget(&pool, month_id, dining_id, 150.0).await;
+    create_test_budget(&pool, month_id, food_id, 500.0).await;
+    let item_id = create_test_item(&pool, month_id, food_id, "Lunch", 12.0, "2024-06-05").await;
+
+    let lock_url = format!("/api/months/{}/budgets/{}/lock", month_id, budget_id);
+    let response = server
+        .post(&lock_url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "reason": " No more eating out " }))
+        .await;
+    response.assert_status_ok();
+    let budget: serde_json::Value = response.json();
+    assert!(budget["locked_at"].is_string());
+    assert_eq!(budget["lock_reason"], "No more eating out");
+
+    let items_url = format!("/api/months/{}/items", month_id);
+    let response = server
+        .post(&items_url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "category_id": dining_id,
+            "description": "Pizza",
+            "amount": 20.0,
+            "spent_on": "2024-06-10"
+        }))
+        .expect_failure()
+        .await;
+    response.assert_status_forbidden();
+    let body: serde_json::Value = response.json();
+    assert_eq!(
+        body["reason"],
+        "Dining Out is locked for this month: No more eating out"
+    );
+
+    server
+        .put(&format!("/api/months/{}/items/{}", month_id, item_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "category_id": dining_id }))
+        .expect_failure()
+        .await
+        .assert_status_forbidden();
+
+    server
+        .post(&items_url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "category_id": food_id,
+            "description": "Groceries",
+            "amount": 40.0,
+            "spent_on": "2024-06-10"
+        }))
+        .await
+        .assert_status_ok();
+
+    let response = server
+        .delete(&lock_url)
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    let budget: serde_json::Value = response.json();
+    assert!(budget["locked_at"].is_null());
+
+    server
+        .post(&items_url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "category_id": dining_id,
+            "description": "Pizza",
+            "amount": 20.0,
+            "spent_on": "2024-06-10"
+        }))
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_review_budget() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let cat_id = create_test_category(&pool, user_id, "Food", 500.0).await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let budget_id = create_test_budget(&pool, month_id, cat_id, 500.0).await;
+
+    let url = format!("/api/months/{}/budgets/{}/review", month_id, budget_id);
+    server
+        .post(&url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "rating": 2, "note": "Too much takeout" }))
+        .await
+        .assert_status_ok();
+    let response = server
+        .post(&url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "rating": 4, "note": "  Cooked more  " }))
+        .await;
+    response.assert_status_ok();
+    let review: serde_json::Value = response.json();
+    assert_eq!(review["rating"], 4);
+    assert_eq!(review["note"], "Cooked more");
+
+    close_test_month(&pool, month_id).await;
+    let summary: serde_json::Value = server
+        .get(&format!("/api/months/{}", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(summary["budgets"][0]["review"]["rating"], 4);
+    assert_eq!(summary["budgets"][0]["review"]["note"], "Cooked more");
+
+    // The closed month's PDF snapshot is final
+    server
+        .post(&url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "rating": 1 }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+}
+
+#[tokio::test]
+async fn test_year_review() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let food = create_test_category(&pool, user_id, "Food", 500.0).await;
+    let fun = create_test_category(&pool, user_id, "Fun", 100.0).await;
+    for (month, food_rating, fun_rating) in [(3, 2, None), (6, 5, Some(3))] {
+        let month_id = create_test_month(&pool, user_id, 2024, month).await;
+        let food_budget = create_test_budget(&pool, month_id, food, 500.0).await;
+        let fun_budget = create_test_budget(&pool, month_id, fun, 100.0).await;
+        let review = |budget_id: i64, rating: i64| {
+            server
+                .post(&format!(
+                    "/api/months/{month_id}/budgets/{budget_id}/review"
+                ))
+                .add_header(auth_name(), auth_value(&token))
+                .json(&json!({ "rating": rating, "note": format!("Month {month}") }))
+        };
+        review(food_budget, food_rating).await.assert_status_ok();
+        if let Some(rating) = fun_rating {
+            review(fun_budget, rating).await.assert_status_ok();
+        }
+        close_test_month(&pool, month_id).await;
+    }
+    let other_year = create_test_month(&pool, user_id, 2023, 6).await;
+    let budget_id = create_test_budget(&pool, other_year, food, 500.0).await;
+    server
+        .post(&format!(
+            "/api/months/{other_year}/budgets/{budget_id}/review"
+        ))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "rating": 1 }))
+        .await
+        .assert_status_ok();
+
+    let response = server
+        .get("/api/v1/reviews/2024")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    let year: serde_json::Value = response.json();
+    assert_eq!(year["year"], 2024);
+    assert_eq!(
+        year["categories"],
+        json!([
+            {
+                "category_label": "Food",
+                "average_rating": 3.5,
+                "months": [
+                    { "month": 3, "rating": 2, "note": "Month 3" },
+                    { "month": 6, "rating": 5, "note": "Month 6" }
+                ]
+            },
+            {
+                "category_label": "Fun",
+                "average_rating": 3.0,
+                "months": [{ "month": 6, "rating": 3, "note": "Month 6" }]
+            }
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_review_budget_validation() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let cat_id = create_test_category(&pool, user_id, "Food", 500.0).await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let budget_id = create_test_budget(&pool, month_id, cat_id, 500.0).await;
+
+    server
+        .post(&format!(
+            "/api/months/{}/budgets/{}/review",
+            month_id, budget_id
+        ))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "rating": 6 }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+
+    server
+        .post(&format!(
+            "/api/months/{}/budgets/{}/review",
+            month_id,
+            budget_id + 1
+        ))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "rating": 3 }))
+        .expect_failure()
+        .await
+        .assert_status_not_found();
+}
+
+#[tokio::test]
+async fn test_envelopes_suggest_covering_overspending() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    create_test_income(&pool, month_id, "Salary", 1000.0).await;
+    let food = create_test_category(&pool, user_id, "Food", 300.0).await;
+    let fun = create_test_category(&pool, user_id, "Fun", 200.0).await;
+    let food_budget = create_test_budget(&pool, month_id, food, 300.0).await;
+    let fun_budget = create_test_budget(&pool, month_id, fun, 200.0).await;
+    create_test_item(&pool, month_id, food, "Groceries", 350.0, "2024-06-05").await;
+    create_test_item(&pool, month_id, fun, "Cinema", 20.0, "2024-06-06").await;
+
+    // Overspend food by more than the unassigned income
+    sqlx::query("UPDATE monthly_budgets SET allocated_amount = 0 WHERE id = ?")
+        .bind(food_budget)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let response = server
+        .get(&format!("/api/months/{}/envelopes", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    let body: serde_json::Value = response.json();
+
+    assert_eq!(body["unassigned"], 800.0);
+    let envelopes = body["envelopes"].as_array().unwrap();
+    let food_envelope = envelopes
+        .iter()
+        .find(|e| e["budget_id"] == food_budget)
+        .unwrap();
+    assert_eq!(food_envelope["available"], -350.0);
+    let fun_envelope = envelopes
+        .iter()
+        .find(|e| e["budget_id"] == fun_budget)
+        .unwrap();
+    assert_eq!(fun_envelope["available"], 180.0);
+    assert_eq!(
+        body["suggestions"],
+        json!([{ "from_budget_id": null, "to_budget_id": food_budget, "amount": 350.0 }])
+    );
+
+    let summary: serde_json::Value = server
+        .get(&format!("/api/months/{}", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    let fun_summary = summary["budgets"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|b| b["id"] == fun_budget)
+        .unwrap();
+    assert_eq!(fun_summary["available"], 180.0);
+}
+
+#[tokio::test]
+async fn test_envelopes_other_user_month() {
+    let (server, pool, _user_id, token) = setup_with_user().await;
+
+    let other_id = create_test_user(&pool, "other", "password123").await;
+    let month_id = create_test_month(&pool, other_id, 2024, 6).await;
+
+    server
+        .get(&format!("/api/months/{}/envelopes", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .expect_failure()
+        .await
+        .assert_status_not_found();
+}
+
+#[tokio::test]
+async fn test_delete_category_dry_run() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let food = create_test_category(&pool, user_id, "Food", 300.0).await;
+    create_test_budget(&pool, month_id, food, 300.0).await;
+    create_test_item(&pool, month_id, food, "Groceries", 40.0, "2024-06-03").await;
+    create_test_item(&pool, month_id, food, "Bakery", 6.0, "2024-06-04").await;
+
+    let response = server
+        .delete(&format!("/api/categories/{}?dry_run=true", food))
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    let report: serde_json::Value = response.json();
+    assert_eq!(report["dry_run"], true);
+    assert_eq!(
+        report["changes"],
+        json!([
+            { "table": "budget_categories", "inserted": 0, "updated": 0, "deleted": 1 },
+            { "table": "items", "inserted": 0, "updated": 0, "deleted": 2 },
+            { "table": "monthly_budgets", "inserted": 0, "updated": 0, "deleted": 1 }
+        ])
+    );
+    assert_eq!(report["affected_months"], json!(["2024-06"]));
+
+    let items: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM items")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(items, 2);
+
+    server
+        .delete(&format!("/api/categories/{}", food))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status(axum::http::StatusCode::NO_CONTENT);
+    let items: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM items")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(items, 0);
+}
+
+#[tokio::test]
+async fn test_percent_allocation_follows_income() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    create_test_income(&pool, month_id, "Salary", 3000.0).await;
+
+    let response = server
+        .post("/api/categories")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "label": "Fun", "default_amount": 0.0, "default_percent": 10.0 }))
+        .await;
+    response.assert_status_ok();
+    let category: serde_json::Value = response.json();
+    assert_eq!(category["default_percent"], 10.0);
+
+    let allocation = || async {
+        let response = server
+            .get(&format!("/api/months/{}/budgets", month_id))
+            .add_header(auth_name(), auth_value(&token))
+            .await;
+        let body: Vec<serde_json::Value> = response.json();
+        body[0].clone()
+    };
+    let budget = allocation().await;
+    assert_eq!(budget["allocated_amount"], 300.0);
+    assert_eq!(budget["allocated_percent"], 10.0);
+
+    server
+        .post(&format!("/api/months/{}/income", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "label": "Bonus", "amount": 1000.0 }))
+        .await
+        .assert_status_ok();
+    assert_eq!(allocation().await["allocated_amount"], 400.0);
+
+    // Setting the allocation by hand stops it following income
+    server
+        .put(&format!(
+            "/api/months/{}/budgets/{}",
+            month_id, budget["id"]
+        ))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "allocated_amount": 250.0 }))
+        .await
+        .assert_status_ok();
+    server
+        .post(&format!("/api/months/{}/income", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "label": "Gift", "amount": 500.0 }))
+        .await
+        .assert_status_ok();
+    let budget = allocation().await;
+    assert_eq!(budget["allocated_amount"], 250.0);
+    assert!(budget["allocated_percent"].is_null());
+}
+
+#[tokio::test]
+async fn test_clear_category_percent() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let cat_id = create_test_category(&pool, user_id, "Fun", 100.0).await;
+
+    let response = server
+        .put(&format!("/api/categories/{}", cat_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "default_percent": 5.0 }))
+        .await;
+    assert_eq!(response.json::<serde_json::Value>()["default_percent"], 5.0);
+
+    let response = server
+        .put(&format!("/api/categories/{}", cat_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "default_percent": null }))
+        .await;
+    let body: serde_json::Value = response.json();
+    assert!(body["default_percent"].is_null());
+    assert_eq!(body["default_amount"], 100.0);
+
+    server
+        .put(&format!("/api/categories/{}", cat_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "default_percent": 150.0 }))
+        .await
+        .assert_status_bad_request();
+}
+
+#[tokio::test]
+async fn test_income_change_rebudgets_and_alerts() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    create_test_income(&pool, month_id, "Salary", 3000.0).await;
+    create_test_fixed_expense(&pool, user_id, "Rent", 1000.0).await;
+    server
+        .post("/api/categories")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "label": "Fun", "default_amount": 0.0, "default_percent": 10.0 }))
+        .await
+        .assert_status_ok();
+
+    server
+        .post(&format!("/api/months/{}/income", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "label": "Bonus", "amount": 1000.0 }))
+        .await
+        .assert_status_ok();
+
+    let summary: serde_json::Value = server
+        .get(&format!("/api/months/{}", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(summary["total_budgeted"], 400.0);
+    assert_eq!(summary["to_be_budgeted"], 2600.0);
+
+    // The alert survives the rule-based insights being recomputed
+    let insights: serde_json::Value = server
+        .get("/api/insights")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    let alert = insights["insights"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|i| i["kind"] == "income_rebudget")
+        .expect("income change alert");
+    let message = alert["message"].as_str().unwrap();
+    assert!(message.contains("Fun $300.00 → $400.00"), "{message}");
+    assert!(message.contains("Left to budget: $2,600.00"), "{message}");
+
+    // Opting out leaves allocations where they are
+    server
+        .put("/api/settings")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "rebudget_on_income_change": false }))
+        .await
+        .assert_status_ok();
+    server
+        .post(&format!("/api/months/{}/income", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "label": "Gift", "amount": 1000.0 }))
+        .await
+        .assert_status_ok();
+    let budgets: Vec<serde_json::Value> = server
+        .get(&format!("/api/months/{}/budgets", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(budgets[0]["allocated_amount"], 400.0);
+}
+
+#[tokio::test]
+async fn test_earmark_consumed_by_matching_item() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let cat_id = create_test_category(&pool, user_id, "Fun", 500.0).await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let budget_id = create_test_budget(&pool, month_id, cat_id, 500.0).await;
+    create_test_item(&pool, month_id, cat_id, "Cinema", 50.0, "2024-06-02").await;
+
+    let earmark: serde_json::Value = server
+        .post(&format!(
+            "/api/months/{}/budgets/{}/earmarks",
+            month_id, budget_id
+        ))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "label": " Concert tickets ", "amount": 200.0 }))
+        .await
+        .json();
+    assert_eq!(earmark["label"], "Concert tickets");
+    assert!(earmark["item_id"].is_null());
+
+    let summary: serde_json::Value = server
+        .get(&format!("/api/months/{}", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    let budget = &summary["budgets"][0];
+    assert_eq!(budget["available"], 450.0);
+    assert_eq!(budget["committed"], 200.0);
+    assert_eq!(budget["free"], 250.0);
+
+    let item: serde_json::Value = server
+        .post(&format!("/api/months/{}/items", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "category_id": cat_id,
+            "description": "concert tickets",
+            "amount": 180.0,
+            "spent_on": "2024-06-12"
+        }))
+        .await
+        .json();
+
+    let summary: serde_json::Value = server
+        .get(&format!("/api/months/{}", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    let budget = &summary["budgets"][0];
+    assert_eq!(budget["earmarks"][0]["item_id"], item["id"]);
+    assert_eq!(budget["available"], 270.0);
+    assert_eq!(budget["committed"], 0.0);
+    assert_eq!(budget["free"], 270.0);
+
+    // Deleting the purchase puts the money back on hold
+    server
+        .delete(&format!("/api/months/{}/items/{}", month_id, item["id"]))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_success();
+    let summary: serde_json::Value = server
+        .get(&format!("/api/months/{}", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(summary["budgets"][0]["committed"], 200.0);
+}
+
+#[tokio::test]
+async fn test_earmark_limits_and_removal() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let cat_id = create_test_category(&pool, user_id, "Fun", 300.0).await;
+    let other_cat_id = create_test_category(&pool, user_id, "Food", 300.0).await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let budget_id = create_test_budget(&pool, month_id, cat_id, 300.0).await;
+    create_test_budget(&pool, month_id, other_cat_id, 300.0).await;
+    let url = format!("/api/months/{}/budgets/{}/earmarks", month_id, budget_id);
+
+    let earmark: serde_json::Value = server
+        .post(&url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "label": "Festival", "amount": 200.0 }))
+        .await
+        .json();
+    server
+        .post(&url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "label": "Museum", "amount": 150.0 }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+    server
+        .post(&url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "label": "Museum", "amount": 0.0 }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+
+    // An earmark can only be consumed by an item in its own category
+    server
+        .post(&format!("/api/months/{}/items", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "category_id": other_cat_id,
+            "description": "Groceries",
+            "amount": 20.0,
+            "spent_on": "2024-06-12",
+            "earmark_id": earmark["id"]
+        }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+
+    server
+        .delete(&format!("{}/{}", url, earmark["id"]))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status(axum::http::StatusCode::NO_CONTENT);
+    let summary: serde_json::Value = server
+        .get(&format!("/api/months/{}", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    let budget = summary["budgets"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|b| b["id"] == budget_id)
+        .unwrap();
+    assert_eq!(budget["committed"], 0.0);
+    assert!(budget["earmarks"].as_array().unwrap().is_empty());
+
+    close_test_month(&pool, month_id).await;
+    server
+        .post(&url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "label": "Museum", "amount": 50.0 }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+}
+
+#[tokio::test]
+async fn test_tracking_only_category() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let food = create_test_category(&pool, user_id, "Food", 500.0).await;
+    let work: serde_json::Value = server
+        .post("/api/categories")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "label": "Work", "default_amount": 0.0, "tracking_only": true }))
+        .await
+        .json();
+    assert_eq!(work["tracking_only"], true);
+    let work_id = work["id"].as_i64().unwrap();
+
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    create_test_budget(&pool, month_id, food, 500.0).await;
+    create_test_budget(&pool, month_id, work_id, 0.0).await;
+    create_test_income(&pool, month_id, "Salary", 3000.0).await;
+    create_test_item(&pool, month_id, food, "Groceries", 200.0, "2024-06-03").await;
+    create_test_item(
+        &pool,
+        month_id,
+        work_id,
+        "Train to client",
+        80.0,
+        "2024-06-04",
+    )
+    .await;
+
+    let summary: serde_json::Value = server
+        .get(&format!("/api/months/{}", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(summary["total_spent"], 200.0);
+    assert_eq!(summary["tracked_spent"], 80.0);
+    assert_eq!(summary["remaining"], 2800.0);
+    let budgets = summary["budgets"].as_array().unwrap();
+    let work_budget = budgets
+        .iter()
+        .find(|b| b["category_id"] == work_id)
+        .unwrap();
+    assert_eq!(work_budget["tracking_only"], true);
+    assert_eq!(work_budget["spent_amount"], 80.0);
+    assert_eq!(work_budget["available"], 0.0);
+
+    let envelopes: serde_json::Value = server
+        .get(&format!("/api/months/{}/envelopes", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(envelopes["envelopes"].as_array().unwrap().len(), 1);
+    assert!(envelopes["suggestions"].as_array().unwrap().is_empty());
+
+    let stats: serde_json::Value = server
+        .get("/api/stats")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(stats["monthly_trends"][0]["total_spent"], 200.0);
+
+    // Turning the flag off counts the category's past spending again
+    server
+        .put(&format!("/api/categories/{}", work_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "tracking_only": false }))
+        .await
+        .assert_status_ok();
+    let summary: serde_json::Value = server
+        .get(&format!("/api/months/{}", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(summary["total_spent"], 280.0);
+    assert_eq!(summary["tracked_spent"], 0.0);
+}
+
+#[tokio::test]
+async fn test_tracking_only_is_frozen_with_closed_month() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let food = create_test_category(&pool, user_id, "Food", 500.0).await;
+    let work: serde_json::Value = server
+        .post("/api/categories")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "label": "Work", "default_amount": 0.0, "tracking_only": true }))
+        .await
+        .json();
+    let work_id = work["id"].as_i64().unwrap();
+
+    let month_id = create_test_month(&pool, user_id, 2024, 5).await;
+    create_test_budget(&pool, month_id, food, 500.0).await;
+    create_test_budget(&pool, month_id, work_id, 0.0).await;
+    create_test_income(&pool, month_id, "Salary", 3000.0).await;
+    create_test_item(&pool, month_id, food, "Groceries", 200.0, "2024-05-03").await;
+    create_test_item(
+        &pool,
+        month_id,
+        work_id,
+        "Train to client",
+        80.0,
+        "2024-05-04",
+    )
+    .await;
+    server
+        .post(&format!("/api/months/{}/close", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_ok();
+
+    server
+        .put(&format!("/api/categories/{}", work_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "tracking_only": false }))
+        .await
+        .assert_status_ok();
+
+    // The closed month keeps the flag it was closed with
+    let summary: serde_json::Value = server
+        .get(&format!("/api/months/{}", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(summary["total_spent"], 200.0);
+    assert_eq!(summary["tracked_spent"], 80.0);
+    assert_eq!(summary["remaining"], 2800.0);
+    let work_budget = summary["budgets"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|b| b["category_id"] == work_id)
+        .unwrap();
+    assert_eq!(work_budget["tracking_only"], true);
+    assert_eq!(work_budget["available"], 0.0);
+}
+
+#[tokio::test]
+async fn test_bulk_relabel_categories() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let food = create_test_category(&pool, user_id, "Food", 300.0).await;
+    let fun = create_test_category(&pool, user_id, "Fun", 100.0).await;
+
+    let response = server
+        .put("/api/v1/categories/bulk")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "ids": [food],
+            "increase_percent": -10.0,
+            "labels": [{ "id": food, "label": "Groceries" }, { "id": fun, "label": "Going out" }]
+        }))
+        .await;
+
+    response.assert_status_ok();
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["dry_run"], false);
+    assert_eq!(body["changes"][0]["old_label"], "Food");
+    assert_eq!(body["changes"][0]["label"], "Groceries");
+    assert_eq!(body["changes"][0]["amount"], 270.0);
+    assert_eq!(body["changes"][1]["amount"], 100.0);
+
+    let categories: Vec<(String, f64)> =
+        sqlx::query_as("SELECT label, default_amount FROM budget_categories ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    assert_eq!(
+        categories,
+        [
+            ("Groceries".to_string(), 270.0),
+            ("Going out".to_string(), 100.0)
+        ]
+    );
+
+    let response = server
+        .put("/api/v1/categories/bulk")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "ids": [fun] }))
+        .expect_failure()
+        .await;
+    response.assert_status_bad_request();
+}
@@ -0,0 +1,173 @@
+mod common;
+
+use common::{
+    auth_name, auth_value, create_test_income, create_test_month, create_test_pool,
+    create_test_server, create_test_user, generate_token,
+};
+use payme::create_app;
+use serde_json::{json, Value};
+
+async fn setup_with_user() -> (axum_test::TestServer, sqlx::SqlitePool, i64, String) {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+    (server, pool, user_id, token)
+}
+
+async fn add_fixed_expense(server: &axum_test::TestServer, token: &str, expense: Value) {
+    server
+        .post("/api/v1/fixed-expenses")
+        .add_header(auth_name(), auth_value(token))
+        .json(&expense)
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_cashflow_rolls_around_weekends_and_holidays() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let june = create_test_month(&pool, user_id, 2024, 6).await;
+    let august = create_test_month(&pool, user_id, 2024, 8).await;
+    create_test_income(&pool, june, "Salary", 3000.0).await;
+    server
+        .put("/api/v1/settings")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "holiday_country": "gb", "payday": 30 }))
+        .await
+        .assert_status_ok();
+
+    add_fixed_expense(
+        &server,
+        &token,
+        json!({ "label": "Rent", "amount": 1200.0, "payment_day": 1 }),
+    )
+    .await;
+    add_fixed_expense(
+        &server,
+        &token,
+        json!({ "label": "Gym", "amount": 40.0, "payment_day": 31, "payment_roll": "backward" }),
+    )
+    .await;
+    add_fixed_expense(
+        &server,
+        &token,
+        json!({ "label": "Phone", "amount": 20.0, "payment_day": 26 }),
+    )
+    .await;
+    add_fixed_expense(
+        &server,
+        &token,
+        json!({ "label": "Internet", "amount": 30.0 }),
+    )
+    .await;
+    add_fixed_expense(
+        &server,
+        &token,
+        json!({
+            "label": "Insurance",
+            "amount": 90.0,
+            "billing_period": "quarterly",
+            "payment_month": 7,
+            "payment_day": 5
+        }),
+    )
+    .await;
+
+    let response = server
+        .get(&format!("/api/v1/months/{june}/cashflow"))
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    let entries: Vec<Value> = response.json();
+    let days: Vec<(&str, &str, f64)> = entries
+        .iter()
+        .map(|e| {
+            (
+                e["label"].as_str().unwrap(),
+                e["date"].as_str().unwrap(),
+                e["balance"].as_f64().unwrap(),
+            )
+        })
+        .collect();
+    // 1 June is a Saturday, 30 June a Sunday
+    assert_eq!(
+        days,
+        vec![
+            ("Rent", "2024-06-03", -1200.0),
+            ("Phone", "2024-06-26", -1220.0),
+            ("Salary", "2024-06-28", 1780.0),
+            ("Gym", "2024-06-28", 1740.0),
+        ]
+    );
+    assert_eq!(entries[0]["scheduled_on"], "2024-06-01");
+    assert_eq!(entries[2]["kind"], "income");
+    assert_eq!(entries[2]["amount"], 3000.0);
+
+    // 26 August 2024 is the summer bank holiday in England
+    let entries: Vec<Value> = server
+        .get(&format!("/api/v1/months/{august}/cashflow"))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    let phone = entries.iter().find(|e| e["label"] == "Phone").unwrap();
+    assert_eq!(phone["scheduled_on"], "2024-08-26");
+    assert_eq!(phone["date"], "2024-08-27");
+}
+
+#[tokio::test]
+async fn test_payment_schedule_validation() {
+    let (server, _pool, _user_id, token) = setup_with_user().await;
+
+    for settings in [
+        json!({ "holiday_country": "XX" }),
+        json!({ "payday": 32 }),
+        json!({ "payday_roll": "sideways" }),
+    ] {
+        let response = server
+            .put("/api/v1/settings")
+            .add_header(auth_name(), auth_value(&token))
+            .json(&settings)
+            .expect_failure()
+            .await;
+        response.assert_status_bad_request();
+    }
+
+    let response = server
+        .post("/api/v1/fixed-expenses")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "label": "Rent", "amount": 1200.0, "payment_roll": "sideways" }))
+        .expect_failure()
+        .await;
+    response.assert_status_bad_request();
+
+    let settings: Value = server
+        .put("/api/v1/settings")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "holiday_country": "de", "payday": 25 }))
+        .await
+        .json();
+    assert_eq!(settings["holiday_country"], "DE");
+    let settings: Value = server
+        .put("/api/v1/settings")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "holiday_country": "", "payday": null }))
+        .await
+        .json();
+    assert!(settings["holiday_country"].is_null());
+    assert!(settings["payday"].is_null());
+}
+
+#[tokio::test]
+async fn test_cashflow_of_another_user() {
+    let (server, pool, _user_id, token) = setup_with_user().await;
+    let other = create_test_user(&pool, "other", "password123").await;
+    let month = create_test_month(&pool, other, 2024, 6).await;
+
+    let response = server
+        .get(&format!("/api/v1/months/{month}/cashflow"))
+        .add_header(auth_name(), auth_value(&token))
+        .expect_failure()
+        .await;
+    response.assert_status_not_found();
+}
@@ -0,0 +1,199 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{
+    auth_name, auth_value, create_test_category, create_test_item, create_test_month,
+    create_test_pool, create_test_server, create_test_user, generate_token,
+};
+use payme::storage::{LocalStorage, Storage};
+use payme::{cli, create_app};
+use serde_json::json;
+
+#[tokio::test]
+async fn test_create_user_and_reset_password() {
+    let pool = create_test_pool().await;
+    let server = create_test_server(create_app(pool.clone()));
+
+    cli::create_user(&pool, "alice", "password123")
+        .await
+        .unwrap();
+    assert!(cli::create_user(&pool, "alice", "password123")
+        .await
+        .is_err());
+    assert!(cli::create_user(&pool, "bob", "short").await.is_err());
+
+    server
+        .post("/api/auth/login")
+        .json(&json!({ "username": "alice", "password": "password123" }))
+        .await
+        .assert_status_ok();
+
+    cli::reset_password(&pool, "alice", "new-password")
+        .await
+        .unwrap();
+    assert!(cli::reset_password(&pool, "nobody", "new-password")
+        .await
+        .is_err());
+
+    server
+        .post("/api/auth/login")
+        .json(&json!({ "username": "alice", "password": "password123" }))
+        .expect_failure()
+        .await
+        .assert_status(StatusCode::UNAUTHORIZED);
+    server
+        .post("/api/auth/login")
+        .json(&json!({ "username": "alice", "password": "new-password" }))
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_export_user() {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let food = create_test_category(&pool, user_id, "Food", 300.0).await;
+    create_test_item(&pool, month_id, food, "Groceries", 40.0, "2024-06-03").await;
+
+    let export = cli::export_user(&pool, "testuser").await.unwrap();
+    assert_eq!(export.categories.len(), 1);
+    assert_eq!(export.months.len(), 1);
+    assert_eq!(export.months[0].items[0].description, "Groceries");
+}
+
+#[tokio::test]
+async fn test_reopen_month() {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let dir = tempfile::tempdir().unwrap();
+    let storage = LocalStorage::new(dir.path().to_path_buf());
+
+    assert!(cli::reopen_month(&pool, &storage, "testuser", 2024, 6)
+        .await
+        .is_err());
+
+    server
+        .post(&format!("/api/months/{month_id}/close"))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_ok();
+    let key = payme::storage::snapshot_key(month_id);
+    payme::storage::put_sealed(&storage, &key, b"%PDF".to_vec())
+        .await
+        .unwrap();
+    sqlx::query(
+        "INSERT OR REPLACE INTO monthly_snapshots (month_id, storage_key, size) VALUES (?, ?, 4)",
+    )
+    .bind(month_id)
+    .bind(&key)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    cli::reopen_month(&pool, &storage, "testuser", 2024, 6)
+        .await
+        .unwrap();
+    assert!(storage.list("snapshots/").await.unwrap().is_empty());
+
+    let (is_closed, frozen_at): (bool, Option<String>) =
+        sqlx::query_as("SELECT is_closed, frozen_at FROM months WHERE id = ?")
+            .bind(month_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert!(!is_closed);
+    assert!(frozen_at.is_none());
+
+    // Closing again records a fresh wealth snapshot instead of hitting the old one
+    server
+        .post(&format!("/api/months/{month_id}/close"))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_backup() {
+    let dir = tempfile::tempdir().unwrap();
+    let pool = payme::db::create_pool(&format!(
+        "sqlite:{}?mode=rwc",
+        dir.path().join("payme.db").display()
+    ))
+    .await
+    .unwrap();
+    payme::db::run_migrations(&pool).await.unwrap();
+    create_test_user(&pool, "testuser", "password123").await;
+    let path = dir.path().join("backup.db");
+
+    cli::backup(&pool, &path).await.unwrap();
+    assert!(cli::backup(&pool, &path).await.is_err());
+
+    let copy = payme::db::create_pool(&format!("sqlite:{}", path.display()))
+        .await
+        .unwrap();
+    let users: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users")
+        .fetch_one(&copy)
+        .await
+        .unwrap();
+    assert_eq!(users, 1);
+}
+
+#[tokio::test]
+async fn test_upload_backup() {
+    let dir = tempfile::tempdir().unwrap();
+    let pool = payme::db::create_pool(&format!(
+        "sqlite:{}?mode=rwc",
+        dir.path().join("payme.db").display()
+    ))
+    .await
+    .unwrap();
+    payme::db::run_migrations(&pool).await.unwrap();
+    create_test_user(&pool, "testuser", "password123").await;
+    let storage = LocalStorage::new(dir.path().join("files"));
+
+    let key = cli::upload_backup(&pool, &storage).await.unwrap();
+
+    assert!(key.starts_with("backups/payme-"));
+    let listed = storage.list("backups/").await.unwrap();
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0].key, key);
+    let copy = payme::db::create_pool(&format!(
+        "sqlite:{}",
+        dir.path().join("files").join(&key).display()
+    ))
+    .await
+    .unwrap();
+    let users: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM users")
+        .fetch_one(&copy)
+        .await
+        .unwrap();
+    assert_eq!(users, 1);
+}
+
+#[test]
+fn test_openapi_security() {
+    let spec: serde_json::Value = serde_json::from_str(&cli::openapi_spec()).unwrap();
+
+    let schemes = &spec["components"]["securitySchemes"];
+    assert_eq!(schemes["cookie_auth"]["in"], "cookie");
+    assert_eq!(schemes["cookie_auth"]["name"], "token");
+    assert_eq!(schemes["bearer_auth"]["scheme"], "bearer");
+    assert_eq!(schemes["admin_token"]["name"], "X-Admin-Token");
+    assert_eq!(
+        spec["security"],
+        json!([{ "cookie_auth": [] }, { "bearer_auth": [] }])
+    );
+
+    let paths = &spec["paths"];
+    assert_eq!(paths["/api/v1/auth/login"]["post"]["security"], json!([{}]));
+    assert_eq!(
+        paths["/api/v1/admin/maintenance"]["get"]["security"],
+        json!([{ "admin_token": [] }])
+    );
+    // Protected routes inherit the document-wide requirement
+    assert!(paths["/api/v1/months"]["get"]["security"].is_null());
+}
@@ -0,0 +1,178 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{
+    auth_name, auth_value, create_test_category, create_test_month, create_test_pool,
+    create_test_server, create_test_user, generate_token,
+};
+use payme::create_app;
+use serde_json::{json, Value};
+
+async fn setup_with_user() -> (axum_test::TestServer, sqlx::SqlitePool, i64, String) {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+    (server, pool, user_id, token)
+}
+
+async fn enable_business_profile(server: &axum_test::TestServer, token: &str) {
+    server
+        .put("/api/v1/settings")
+        .add_header(auth_name(), auth_value(token))
+        .json(&json!({ "business_profile": true }))
+        .await
+        .assert_status_ok();
+}
+
+fn add_item(
+    server: &axum_test::TestServer,
+    token: &str,
+    month_id: i64,
+    category_id: i64,
+    item: Value,
+) -> axum_test::TestRequest {
+    let mut body = json!({
+        "category_id": category_id,
+        "description": "Train ticket",
+        "amount": 40.0,
+        "spent_on": "2024-06-10"
+    });
+    body.as_object_mut()
+        .unwrap()
+        .extend(item.as_object().unwrap().clone());
+    server
+        .post(&format!("/api/v1/months/{month_id}/items?force=true"))
+        .add_header(auth_name(), auth_value(token))
+        .json(&body)
+}
+
+#[tokio::test]
+async fn test_client_fields_need_business_profile() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let cat = create_test_category(&pool, user_id, "Travel", 300.0).await;
+    let month = create_test_month(&pool, user_id, 2024, 6).await;
+
+    let response = add_item(
+        &server,
+        &token,
+        month,
+        cat,
+        json!({ "client": "Acme", "billable": true }),
+    )
+    .expect_failure()
+    .await;
+    response.assert_status(StatusCode::FORBIDDEN);
+
+    enable_business_profile(&server, &token).await;
+
+    let response = add_item(&server, &token, month, cat, json!({ "billable": true }))
+        .expect_failure()
+        .await;
+    response.assert_status_bad_request();
+
+    let response = add_item(
+        &server,
+        &token,
+        month,
+        cat,
+        json!({ "client": "Acme", "billable": true }),
+    )
+    .await;
+    response.assert_status_ok();
+    let item: Value = response.json();
+    assert_eq!(item["client"], "Acme");
+    assert_eq!(item["billable"], true);
+
+    let response = server
+        .put(&format!("/api/v1/months/{month}/items/{}", item["id"]))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "client": null }))
+        .expect_failure()
+        .await;
+    response.assert_status_bad_request();
+
+    let response = server
+        .put(&format!("/api/v1/months/{month}/items/{}", item["id"]))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "client": null, "billable": false }))
+        .await;
+    response.assert_status_ok();
+    let item: Value = response.json();
+    assert!(item.get("client").is_none());
+    assert_eq!(item["billable"], false);
+}
+
+#[tokio::test]
+async fn test_client_report_totals_billable_items_by_month() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let cat = create_test_category(&pool, user_id, "Travel", 300.0).await;
+    let may = create_test_month(&pool, user_id, 2024, 5).await;
+    let june = create_test_month(&pool, user_id, 2024, 6).await;
+    enable_business_profile(&server, &token).await;
+
+    let billable = |client: &str| json!({ "client": client, "billable": true });
+    add_item(&server, &token, may, cat, billable("Acme")).await;
+    add_item(&server, &token, june, cat, billable("Acme")).await;
+    add_item(
+        &server,
+        &token,
+        june,
+        cat,
+        json!({ "client": "Acme", "billable": true, "amount": 12.5 }),
+    )
+    .await;
+    add_item(&server, &token, june, cat, billable("Globex")).await;
+    add_item(&server, &token, june, cat, json!({ "client": "Acme" })).await;
+    add_item(&server, &token, june, cat, json!({})).await;
+
+    let response = server
+        .get("/api/v1/clients/report")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    let body: Value = response.json();
+    assert_eq!(body.as_array().unwrap().len(), 2);
+    assert_eq!(body[0]["client"], "Acme");
+    assert_eq!(body[0]["total"], 92.5);
+    assert_eq!(body[0]["months"][0]["month"], 5);
+    assert_eq!(body[0]["months"][0]["total"], 40.0);
+    assert_eq!(body[0]["months"][1]["total"], 52.5);
+    assert_eq!(body[0]["months"][1]["items"], 2);
+    assert_eq!(body[1]["client"], "Globex");
+
+    let response = server
+        .get("/api/v1/clients/report?q=glob")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    let body: Value = response.json();
+    assert_eq!(body.as_array().unwrap().len(), 1);
+    assert_eq!(body[0]["total"], 40.0);
+}
+
+#[tokio::test]
+async fn test_client_report_only_sees_own_items() {
+    let (server, pool, _user_id, token) = setup_with_user().await;
+    let other = create_test_user(&pool, "other", "password123").await;
+    let other_token = generate_token(other, "other");
+    let cat = create_test_category(&pool, other, "Travel", 300.0).await;
+    let month = create_test_month(&pool, other, 2024, 6).await;
+    enable_business_profile(&server, &other_token).await;
+    add_item(
+        &server,
+        &other_token,
+        month,
+        cat,
+        json!({ "client": "Acme", "billable": true }),
+    )
+    .await
+    .assert_status_ok();
+
+    let response = server
+        .get("/api/v1/clients/report")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    let body: Value = response.json();
+    assert!(body.as_array().unwrap().is_empty());
+}
@@ -1,13 +1,10 @@
 #![allow(dead_code)]
 
-use argon2::{
-    password_hash::{rand_core::OsRng, PasswordHasher, SaltString},
-    Argon2,
-};
 use axum::Router;
 use axum_test::TestServer;
 use chrono::{Duration, Utc};
 use jsonwebtoken::{encode, EncodingKey, Header};
+use payme::testing::{self, CategoryBuilder, ItemBuilder, MonthBuilder, UserBuilder};
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
 
@@ -22,165 +19,16 @@ pub struct Claims {
 
 /// Create an in-memory SQLite pool and run migrations
 pub async fn create_test_pool() -> SqlitePool {
-    let pool = SqlitePool::connect(":memory:")
-        .await
-        .expect("Failed to create in-memory database");
-
-    run_migrations(&pool).await;
-    pool
-}
-
-/// Run database migrations (copied from db/mod.rs to avoid circular deps)
-async fn run_migrations(pool: &SqlitePool) {
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS users (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            username TEXT NOT NULL UNIQUE,
-            password_hash TEXT NOT NULL,
-            savings REAL NOT NULL DEFAULT 0,
-            savings_goal REAL NOT NULL DEFAULT 0,
-            retirement_savings REAL NOT NULL DEFAULT 0,
-            created_at TEXT NOT NULL DEFAULT (datetime('now'))
-        )
-        "#,
-    )
-    .execute(pool)
-    .await
-    .expect("Failed to create users table");
-
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS fixed_expenses (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            user_id INTEGER NOT NULL,
-            label TEXT NOT NULL,
-            amount REAL NOT NULL,
-            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
-        )
-        "#,
-    )
-    .execute(pool)
-    .await
-    .expect("Failed to create fixed_expenses table");
-
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS budget_categories (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            user_id INTEGER NOT NULL,
-            label TEXT NOT NULL,
-            default_amount REAL NOT NULL,
-            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
-        )
-        "#,
-    )
-    .execute(pool)
-    .await
-    .expect("Failed to create budget_categories table");
-
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS months (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            user_id INTEGER NOT NULL,
-            year INTEGER NOT NULL,
-            month INTEGER NOT NULL,
-            is_closed INTEGER NOT NULL DEFAULT 0,
-            closed_at TEXT,
-            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
-            UNIQUE(user_id, year, month)
-        )
-        "#,
-    )
-    .execute(pool)
-    .await
-    .expect("Failed to create months table");
-
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS income_entries (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            month_id INTEGER NOT NULL,
-            label TEXT NOT NULL,
-            amount REAL NOT NULL,
-            FOREIGN KEY (month_id) REFERENCES months(id) ON DELETE CASCADE
-        )
-        "#,
-    )
-    .execute(pool)
-    .await
-    .expect("Failed to create income_entries table");
-
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS monthly_budgets (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            month_id INTEGER NOT NULL,
-            category_id INTEGER NOT NULL,
-            allocated_amount REAL NOT NULL,
-            FOREIGN KEY (month_id) REFERENCES months(id) ON DELETE CASCADE,
-            FOREIGN KEY (category_id) REFERENCES budget_categories(id) ON DELETE CASCADE,
-            UNIQUE(month_id, category_id)
-        )
-        "#,
-    )
-    .execute(pool)
-    .await
-    .expect("Failed to create monthly_budgets table");
-
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS items (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            month_id INTEGER NOT NULL,
-            category_id INTEGER NOT NULL,
-            description TEXT NOT NULL,
-            amount REAL NOT NULL,
-            spent_on TEXT NOT NULL,
-            savings_destination TEXT NOT NULL DEFAULT 'none',
-            FOREIGN KEY (month_id) REFERENCES months(id) ON DELETE CASCADE,
-            FOREIGN KEY (category_id) REFERENCES budget_categories(id) ON DELETE CASCADE
-        )
-        "#,
-    )
-    .execute(pool)
-    .await
-    .expect("Failed to create items table");
-
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS monthly_snapshots (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            month_id INTEGER NOT NULL UNIQUE,
-            pdf_data BLOB NOT NULL,
-            created_at TEXT NOT NULL DEFAULT (datetime('now')),
-            FOREIGN KEY (month_id) REFERENCES months(id) ON DELETE CASCADE
-        )
-        "#,
-    )
-    .execute(pool)
-    .await
-    .expect("Failed to create monthly_snapshots table");
+    testing::memory_pool().await
 }
 
 /// Create a test user and return their ID
 pub async fn create_test_user(pool: &SqlitePool, username: &str, password: &str) -> i64 {
-    let salt = SaltString::generate(&mut OsRng);
-    let argon2 = Argon2::default();
-    let password_hash = argon2
-        .hash_password(password.as_bytes(), &salt)
-        .expect("Failed to hash password")
-        .to_string();
-
-    sqlx::query_scalar::<_, i64>(
-        "INSERT INTO users (username, password_hash) VALUES (?, ?) RETURNING id",
-    )
-    .bind(username)
-    .bind(&password_hash)
-    .fetch_one(pool)
-    .await
-    .expect("Failed to create test user")
+    UserBuilder::new(username)
+        .password(password)
+        .create(pool)
+        .await
+        .id
 }
 
 /// Generate a JWT token for a user
@@ -228,28 +76,15 @@ pub async fn create_test_category(
     label: &str,
     default_amount: f64,
 ) -> i64 {
-    sqlx::query_scalar::<_, i64>(
-        "INSERT INTO budget_categories (user_id, label, default_amount) VALUES (?, ?, ?) RETURNING id",
-    )
-    .bind(user_id)
-    .bind(label)
-    .bind(default_amount)
-    .fetch_one(pool)
-    .await
-    .expect("Failed to create test category")
+    CategoryBuilder::new(user_id, label)
+        .default_amount(default_amount)
+        .create(pool)
+        .await
 }
 
 /// Create a test month and return its ID
 pub async fn create_test_month(pool: &SqlitePool, user_id: i64, year: i32, month: i32) -> i64 {
-    sqlx::query_scalar::<_, i64>(
-        "INSERT INTO months (user_id, year, month) VALUES (?, ?, ?) RETURNING id",
-    )
-    .bind(user_id)
-    .bind(year)
-    .bind(month)
-    .fetch_one(pool)
-    .await
-    .expect("Failed to create test month")
+    MonthBuilder::new(user_id, year, month).create(pool).await
 }
 
 /// Create a test fixed expense and return its ID
@@ -292,18 +127,12 @@ pub async fn create_test_item(
     amount: f64,
     spent_on: &str,
 ) -> i64 {
-    sqlx::query_scalar::<_, i64>(
-        "INSERT INTO items (month_id, category_id, description, amount, spent_on, savings_destination) VALUES (?, ?, ?, ?, ?, ?) RETURNING id",
-    )
-    .bind(month_id)
-    .bind(category_id)
-    .bind(description)
-    .bind(amount)
-    .bind(spent_on)
-    .bind("none")
-    .fetch_one(pool)
-    .await
-    .expect("Failed to create test item")
+    ItemBuilder::new(month_id, category_id)
+        .description(description)
+        .amount(amount)
+        .spent_on(spent_on)
+        .create(pool)
+        .await
 }
 
 /// Create a test monthly budget and return its ID
@@ -0,0 +1,192 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{
+    auth_name, auth_value, create_test_month, create_test_pool, create_test_server,
+    create_test_user, generate_token,
+};
+use payme::create_app;
+use serde_json::json;
+
+/// Dropbox is configured on the test server, Google Drive is not.
+async fn setup_with_user() -> (axum_test::TestServer, sqlx::SqlitePool, i64, String) {
+    std::env::set_var("DROPBOX_APP_KEY", "dropbox-app");
+    std::env::set_var("DROPBOX_APP_SECRET", "dropbox-secret");
+    std::env::set_var("PUBLIC_URL", "https://payme.example.com");
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+    (server, pool, user_id, token)
+}
+
+async fn insert_connection(pool: &sqlx::SqlitePool, user_id: i64, provider: &str) {
+    sqlx::query(
+        "INSERT INTO cloud_connections (user_id, provider, refresh_token, folder, created_at) VALUES (?, ?, 'refresh', 'payme', '2024-06-01T00:00:00Z')",
+    )
+    .bind(user_id)
+    .bind(provider)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_authorize_connection() {
+    let (server, _pool, _user_id, token) = setup_with_user().await;
+
+    let authorization: serde_json::Value = server
+        .post("/api/v1/connectors/dropbox/authorize")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "folder": "/Backups/payme" }))
+        .await
+        .json();
+    let url = url::Url::parse(authorization["url"].as_str().unwrap()).unwrap();
+    let param = |name: &str| {
+        url.query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    };
+    assert_eq!(url.host_str(), Some("www.dropbox.com"));
+    assert_eq!(param("client_id").as_deref(), Some("dropbox-app"));
+    assert_eq!(
+        param("redirect_uri").as_deref(),
+        Some("https://payme.example.com/api/v1/connectors/dropbox/callback")
+    );
+    assert!(param("state").is_some());
+
+    server
+        .post("/api/v1/connectors/google_drive/authorize")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({}))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+
+    server
+        .post("/api/v1/connectors/onedrive/authorize")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({}))
+        .expect_failure()
+        .await
+        .assert_status_not_found();
+}
+
+#[tokio::test]
+async fn test_connection_callback() {
+    let (server, _pool, _user_id, _token) = setup_with_user().await;
+
+    let response = server
+        .get("/api/v1/connectors/dropbox/callback?error=access_denied")
+        .await;
+    response.assert_status(StatusCode::SEE_OTHER);
+    assert_eq!(response.header("location"), "/?connector_error=dropbox");
+
+    server
+        .get("/api/v1/connectors/dropbox/callback?code=abc&state=forged")
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+
+    server
+        .get("/api/v1/connectors/dropbox/callback?code=abc")
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+}
+
+#[tokio::test]
+async fn test_list_and_delete_connections() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    insert_connection(&pool, user_id, "dropbox").await;
+    let other_id = create_test_user(&pool, "other", "password123").await;
+    insert_connection(&pool, other_id, "google_drive").await;
+
+    let connections: serde_json::Value = server
+        .get("/api/v1/connectors")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    let connections = connections.as_array().unwrap();
+    assert_eq!(connections.len(), 1);
+    assert_eq!(connections[0]["provider"], "dropbox");
+    assert_eq!(connections[0]["mirror_snapshots"], true);
+    assert!(connections[0].get("refresh_token").is_none());
+
+    server
+        .delete("/api/v1/connectors/dropbox")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status(StatusCode::NO_CONTENT);
+    server
+        .delete("/api/v1/connectors/dropbox")
+        .add_header(auth_name(), auth_value(&token))
+        .expect_failure()
+        .await
+        .assert_status_not_found();
+}
+
+#[tokio::test]
+async fn test_report_delivery_needs_connection() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let report = json!({
+        "name": "Backup",
+        "spec": { "period": "month", "format": "backup" },
+        "schedule": "monthly",
+        "delivery": "dropbox"
+    });
+
+    server
+        .post("/api/v1/reports/saved")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&report)
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+
+    insert_connection(&pool, user_id, "dropbox").await;
+    let saved: serde_json::Value = server
+        .post("/api/v1/reports/saved")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&report)
+        .await
+        .json();
+    assert_eq!(saved["delivery"], "dropbox");
+}
+
+#[tokio::test]
+async fn test_closed_month_pdf_is_mirrored() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    // Google Drive has no OAuth app here, so the upload fails without leaving the machine
+    insert_connection(&pool, user_id, "google_drive").await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+
+    server
+        .post(&format!("/api/months/{}/close", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_ok();
+    payme::jobs::run_pending(&pool).await.unwrap();
+
+    let (status, payload): (String, String) =
+        sqlx::query_as("SELECT status, payload FROM jobs WHERE kind = 'cloud_push'")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(status, "failed");
+    let payload: serde_json::Value = serde_json::from_str(&payload).unwrap();
+    assert_eq!(
+        payload,
+        json!({ "month_id": month_id, "provider": "google_drive" })
+    );
+
+    let connections: serde_json::Value = server
+        .get("/api/v1/connectors")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert!(connections[0]["last_error"]
+        .as_str()
+        .unwrap()
+        .contains("Google Drive is not configured"));
+}
@@ -0,0 +1,416 @@
+//! Checks the handlers against the OpenAPI document: every documented operation is
+//! called, must answer with a documented status, and JSON bodies must match the schema
+//! their annotation promises. Writes are sent the smallest body their request schema
+//! accepts, each against freshly seeded data. A route whose behaviour drifts from its `#[utoipa::path]`
+//! fails here instead of in a generated client.
+
+mod common;
+
+use axum::http::{HeaderName, HeaderValue, Method, StatusCode};
+use axum_test::TestServer;
+use common::{auth_name, auth_value, create_test_budget, create_test_income, create_test_server};
+use payme::create_app;
+use payme::openapi::ApiDoc;
+use payme::testing::{self, CategoryBuilder, ItemBuilder, MonthBuilder, UserBuilder};
+use serde_json::{json, Value};
+use utoipa::OpenApi;
+
+const ADMIN_TOKEN: &str = "test-admin-token";
+/// Stands in for ids and codes the fixture doesn't create; those routes must answer
+/// with a documented "not found".
+const UNKNOWN: &str = "999999";
+
+struct Operation {
+    method: Method,
+    template: String,
+    spec: Value,
+}
+
+/// Ids created by [`seed`], used to fill in path parameters.
+struct Fixture {
+    token: String,
+    month_id: i64,
+    category_id: i64,
+    item_id: i64,
+    budget_id: i64,
+    income_id: i64,
+}
+
+fn spec() -> Value {
+    serde_json::to_value(ApiDoc::openapi()).unwrap()
+}
+
+fn operations(spec: &Value) -> Vec<Operation> {
+    let mut operations = Vec::new();
+    for (template, item) in spec["paths"].as_object().unwrap() {
+        for (method, operation) in item.as_object().unwrap() {
+            operations.push(Operation {
+                method: method.to_uppercase().parse().unwrap(),
+                template: template.clone(),
+                spec: operation.clone(),
+            });
+        }
+    }
+    operations
+}
+
+async fn seed() -> (TestServer, Fixture) {
+    std::env::set_var("ADMIN_TOKEN", ADMIN_TOKEN);
+    let pool = testing::memory_pool().await;
+    let user = UserBuilder::new("testuser").create(&pool).await;
+    let category_id = CategoryBuilder::new(user.id, "Groceries")
+        .default_amount(300.0)
+        .create(&pool)
+        .await;
+    let month_id = MonthBuilder::new(user.id, 2024, 3).create(&pool).await;
+    let budget_id = create_test_budget(&pool, month_id, category_id, 300.0).await;
+    let income_id = create_test_income(&pool, month_id, "Salary", 2500.0).await;
+    let item_id = ItemBuilder::new(month_id, category_id)
+        .description("Bakery")
+        .amount(12.5)
+        .create(&pool)
+        .await;
+    let fixture = Fixture {
+        token: user.token,
+        month_id,
+        category_id,
+        item_id,
+        budget_id,
+        income_id,
+    };
+    (create_test_server(create_app(pool)), fixture)
+}
+
+/// Fills path parameters from the segment before them (`/months/{id}` takes the month)
+/// and adds the required query parameters.
+fn url(operation: &Operation, fixture: &Fixture) -> String {
+    let mut segments = Vec::new();
+    let mut previous = "";
+    for segment in operation.template.split('/') {
+        let value = match segment.strip_prefix('{').and_then(|s| s.strip_suffix('}')) {
+            None => segment.to_string(),
+            Some("year") => "2024".to_string(),
+            Some(_) => match previous {
+                "months" => fixture.month_id.to_string(),
+                "items" => fixture.item_id.to_string(),
+                "categories" => fixture.category_id.to_string(),
+                "budgets" => fixture.budget_id.to_string(),
+                "income" => fixture.income_id.to_string(),
+                _ => UNKNOWN.to_string(),
+            },
+        };
+        previous = segment;
+        segments.push(value);
+    }
+
+    let query: Vec<String> = operation.spec["parameters"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|p| p["in"] == "query" && p["required"] == true)
+        .map(|p| {
+            let value = match (p["name"].as_str(), p["schema"]["type"].as_str()) {
+                (Some("year"), _) => "2024",
+                (_, Some("integer")) => "10",
+                (_, Some("number")) => "100",
+                _ => "x",
+            };
+            format!("{}={value}", p["name"].as_str().unwrap())
+        })
+        .collect();
+
+    let path = segments.join("/");
+    if query.is_empty() {
+        path
+    } else {
+        format!("{path}?{}", query.join("&"))
+    }
+}
+
+fn is_public(operation: &Operation) -> bool {
+    operation.spec["security"] == json!([{}])
+}
+
+fn is_admin(operation: &Operation) -> bool {
+    operation.spec["security"] == json!([{ "admin_token": [] }])
+}
+
+async fn call(
+    server: &TestServer,
+    operation: &Operation,
+    fixture: &Fixture,
+    authenticated: bool,
+) -> axum_test::TestResponse {
+    let mut request = server.method(operation.method.clone(), &url(operation, fixture));
+    if authenticated {
+        request = request
+            .add_header(auth_name(), auth_value(&fixture.token))
+            .add_header(
+                HeaderName::from_static("x-admin-token"),
+                HeaderValue::from_static(ADMIN_TOKEN),
+            );
+    }
+    if operation.method != Method::GET {
+        request = request.json(&json!({}));
+    }
+    request.await
+}
+
+/// Like [`call`], with a body built from the operation's request schema.
+async fn call_with_body(
+    server: &TestServer,
+    spec: &Value,
+    operation: &Operation,
+    fixture: &Fixture,
+) -> axum_test::TestResponse {
+    let schema = &operation.spec["requestBody"]["content"]["application/json"]["schema"];
+    let body = if schema.is_null() {
+        json!({})
+    } else {
+        example(spec, schema, None, fixture)
+    };
+    server
+        .method(operation.method.clone(), &url(operation, fixture))
+        .add_header(auth_name(), auth_value(&fixture.token))
+        .add_header(
+            HeaderName::from_static("x-admin-token"),
+            HeaderValue::from_static(ADMIN_TOKEN),
+        )
+        .json(&body)
+        .await
+}
+
+/// The smallest value `schema` accepts: objects get their required properties only,
+/// and ids named after a seeded record point at it.
+fn example(spec: &Value, schema: &Value, name: Option<&str>, fixture: &Fixture) -> Value {
+    if let Some(reference) = schema["$ref"].as_str() {
+        let name = reference.trim_start_matches("#/components/schemas/");
+        return example(spec, &spec["components"]["schemas"][name], None, fixture);
+    }
+    if let Some(parts) = schema["allOf"].as_array() {
+        let mut merged = serde_json::Map::new();
+        for part in parts {
+            if let Value::Object(object) = example(spec, part, name, fixture) {
+                merged.extend(object);
+            }
+        }
+        return Value::Object(merged);
+    }
+    for combinator in ["oneOf", "anyOf"] {
+        let variant = schema[combinator]
+            .as_array()
+            .and_then(|variants| variants.iter().find(|v| v["type"] != "null"));
+        if let Some(variant) = variant {
+            return example(spec, variant, name, fixture);
+        }
+    }
+    if let Some(first) = schema["enum"].as_array().and_then(|values| values.first()) {
+        return first.clone();
+    }
+
+    let kind = match &schema["type"] {
+        Value::Array(kinds) => kinds.iter().find(|k| *k != "null").cloned(),
+        kind => Some(kind.clone()),
+    };
+    let number = |default: f64| {
+        let minimum = schema["minimum"]
+            .as_f64()
+            .or(schema["exclusiveMinimum"].as_f64().map(|m| m + 1.0));
+        let maximum = schema["maximum"].as_f64();
+        let value = minimum.map_or(default, |m| m.max(default));
+        maximum.map_or(value, |m| value.min(m))
+    };
+    match kind.as_ref().and_then(Value::as_str) {
+        Some("object") | None => {
+            let mut object = serde_json::Map::new();
+            for required in schema["required"].as_array().into_iter().flatten() {
+                let property = required.as_str().unwrap();
+                let value = example(
+                    spec,
+                    &schema["properties"][property],
+                    Some(property),
+                    fixture,
+                );
+                object.insert(property.to_string(), value);
+            }
+            Value::Object(object)
+        }
+        Some("array") => {
+            let items = example(spec, &schema["items"], name, fixture);
+            json!([items])
+        }
+        Some("integer") => match name {
+            Some("month_id") => json!(fixture.month_id),
+            Some("category_id") => json!(fixture.category_id),
+            Some("item_id") => json!(fixture.item_id),
+            Some("budget_id") => json!(fixture.budget_id),
+            Some("income_id") => json!(fixture.income_id),
+            Some("year") => json!(2024),
+            Some("month") => json!(3),
+            _ => json!(number(1.0) as i64),
+        },
+        Some("number") => json!(number(10.0)),
+        Some("boolean") => json!(false),
+        _ => match schema["format"].as_str() {
+            Some("date") => json!("2024-03-10"),
+            Some("date-time") => json!("2024-03-10T12:00:00Z"),
+            _ => json!("x".repeat(schema["minLength"].as_u64().unwrap_or(1) as usize)),
+        },
+    }
+}
+
+/// Problems with one response, or nothing when it matches the document.
+fn check_response(
+    spec: &Value,
+    operation: &Operation,
+    response: &axum_test::TestResponse,
+) -> Vec<String> {
+    let status = response.status_code();
+    let Some(documented) = operation.spec["responses"]
+        .get(status.as_str())
+        .or_else(|| operation.spec["responses"].get("default"))
+    else {
+        return vec![format!("undocumented status {status}")];
+    };
+    if status.is_server_error() {
+        return vec![format!("{status} on seeded data")];
+    }
+    if !status.is_success() {
+        return Vec::new();
+    }
+
+    let Some((media_type, content)) = documented["content"]
+        .as_object()
+        .and_then(|content| content.iter().next())
+    else {
+        return Vec::new();
+    };
+    let content_type = response
+        .headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default();
+    if !content_type.starts_with(media_type.as_str()) {
+        return vec![format!(
+            "content type {content_type:?}, documented {media_type}"
+        )];
+    }
+    if media_type != "application/json" {
+        return Vec::new();
+    }
+
+    // Component references resolve against the schema's own root, so carry them along
+    let mut schema = content["schema"].clone();
+    if let Some(object) = schema.as_object_mut() {
+        object.insert("components".to_string(), spec["components"].clone());
+    }
+    let validator = jsonschema::validator_for(&schema).unwrap();
+    let body: Value = response.json();
+    validator
+        .iter_errors(&body)
+        .map(|e| format!("{} at {}", e, e.instance_path))
+        .collect()
+}
+
+#[tokio::test]
+async fn test_responses_match_documented_schemas() {
+    let spec = spec();
+    let (server, fixture) = seed().await;
+
+    let mut failures = Vec::new();
+    for operation in operations(&spec).iter().filter(|o| o.method == Method::GET) {
+        let response = call(&server, operation, &fixture, true).await;
+        for problem in check_response(&spec, operation, &response) {
+            failures.push(format!("GET {}: {problem}", operation.template));
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[tokio::test]
+async fn test_write_responses_match_documented_schemas() {
+    let spec = spec();
+
+    let mut failures = Vec::new();
+    let mut validated = 0;
+    for operation in operations(&spec).iter().filter(|o| o.method != Method::GET) {
+        // Writes change the data the next one would see, so each gets its own
+        let (server, fixture) = seed().await;
+        let response = call_with_body(&server, &spec, operation, &fixture).await;
+        if response.status_code().is_success() {
+            validated += 1;
+        }
+        for problem in check_response(&spec, operation, &response) {
+            failures.push(format!(
+                "{} {}: {problem}",
+                operation.method, operation.template
+            ));
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+    // The generated bodies have to get most writes past validation to check anything
+    assert!(validated >= 40, "only {validated} writes succeeded");
+}
+
+#[tokio::test]
+async fn test_documented_operations_are_routed() {
+    let spec = spec();
+    let (server, fixture) = seed().await;
+
+    let mut failures = Vec::new();
+    for operation in operations(&spec) {
+        let response = call(&server, &operation, &fixture, true).await;
+        let status = response.status_code();
+        // The SPA fallback answers unknown API paths with an empty 404
+        let unrouted = status == StatusCode::METHOD_NOT_ALLOWED
+            || (status == StatusCode::NOT_FOUND && response.as_bytes().is_empty());
+        if unrouted {
+            failures.push(format!(
+                "{} {}: not routed",
+                operation.method, operation.template
+            ));
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[tokio::test]
+async fn test_security_requirements_are_enforced() {
+    let spec = spec();
+    let (server, fixture) = seed().await;
+
+    let mut failures = Vec::new();
+    for operation in operations(&spec) {
+        let status = call(&server, &operation, &fixture, false)
+            .await
+            .status_code();
+        let rejected = status == StatusCode::UNAUTHORIZED;
+        if is_public(&operation) == rejected {
+            failures.push(format!(
+                "{} {}: {status} without credentials",
+                operation.method, operation.template
+            ));
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+
+    // Admin routes want the admin token, a user's session isn't enough
+    for operation in operations(&spec).iter().filter(|o| is_admin(o)) {
+        let response = server
+            .method(operation.method.clone(), &url(operation, &fixture))
+            .add_header(auth_name(), auth_value(&fixture.token))
+            .await;
+        response.assert_status(StatusCode::UNAUTHORIZED);
+    }
+}
+
+/// The frontend generates its API types from a copy of the spec.
+#[test]
+fn test_frontend_spec_is_current() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/../frontend/openapi.json");
+    let copy: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+    assert!(
+        copy == spec(),
+        "frontend/openapi.json is out of date, run `cargo run --bin payme-admin openapi > ../frontend/openapi.json`"
+    );
+}
@@ -0,0 +1,42 @@
+mod common;
+
+use axum::http::{HeaderName, HeaderValue, Method};
+use common::{create_test_pool, create_test_server};
+use payme::create_app;
+
+fn header(name: &'static str, value: &'static str) -> (HeaderName, HeaderValue) {
+    (
+        HeaderName::from_static(name),
+        HeaderValue::from_static(value),
+    )
+}
+
+#[tokio::test]
+async fn test_configured_origins_allow_credentials() {
+    std::env::set_var(
+        "CORS_ALLOWED_ORIGINS",
+        "http://localhost:3000, https://budget.example.com/",
+    );
+    std::env::set_var("CORS_ALLOW_CREDENTIALS", "true");
+    let server = create_test_server(create_app(create_test_pool().await));
+
+    let (origin, allowed) = header("origin", "https://budget.example.com");
+    let (request_method, post) = header("access-control-request-method", "POST");
+    let response = server
+        .method(Method::OPTIONS, "/api/v1/auth/login")
+        .add_header(origin, allowed)
+        .add_header(request_method, post)
+        .await;
+    assert_eq!(
+        response.header("access-control-allow-origin"),
+        "https://budget.example.com"
+    );
+    assert_eq!(response.header("access-control-allow-credentials"), "true");
+    assert_eq!(response.header("access-control-allow-methods"), "POST");
+
+    let (origin, other) = header("origin", "https://evil.example.com");
+    let response = server.get("/health").add_header(origin, other).await;
+    assert!(response
+        .maybe_header("access-control-allow-origin")
+        .is_none());
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use chrono::{Datelike, Months, Utc};
+use common::{
+    auth_name, auth_value, create_test_category, create_test_fixed_expense, create_test_item,
+    create_test_month, create_test_pool, create_test_server, create_test_user, generate_token,
+};
+use payme::create_app;
+use serde_json::Value;
+
+async fn setup_with_user() -> (axum_test::TestServer, sqlx::SqlitePool, i64, String) {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+    (server, pool, user_id, token)
+}
+
+#[tokio::test]
+async fn test_dashboard_for_new_user() {
+    let (server, _pool, _user_id, token) = setup_with_user().await;
+
+    let response = server
+        .get("/api/dashboard")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+
+    response.assert_status_ok();
+    let body: Value = response.json();
+    let today = Utc::now().date_naive();
+    assert_eq!(body["month"]["month"]["year"], today.year());
+    assert_eq!(body["month"]["month"]["month"], today.month());
+    assert_eq!(body["month"]["total_spent"], 0.0);
+    assert!(body["month"].get("items").is_none());
+    assert_eq!(body["savings"], 0.0);
+    assert!(body["last_month"].is_null());
+}
+
+#[tokio::test]
+async fn test_dashboard_compares_with_last_month() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let today = Utc::now().date_naive();
+    let previous = today.checked_sub_months(Months::new(1)).unwrap();
+
+    let cat_id = create_test_category(&pool, user_id, "Food", 500.0).await;
+    let this_month = create_test_month(&pool, user_id, today.year(), today.month() as i32).await;
+    let last_month =
+        create_test_month(&pool, user_id, previous.year(), previous.month() as i32).await;
+    let this_day = today.format("%Y-%m-01").to_string();
+    let last_day = previous.format("%Y-%m-01").to_string();
+    create_test_item(&pool, this_month, cat_id, "Groceries", 150.0, &this_day).await;
+    create_test_item(&pool, last_month, cat_id, "Groceries", 100.0, &last_day).await;
+    create_test_fixed_expense(&pool, user_id, "Rent", 1200.0).await;
+
+    let response = server
+        .get("/api/dashboard")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+
+    response.assert_status_ok();
+    let body: Value = response.json();
+    assert_eq!(body["month"]["total_spent"], 150.0);
+    assert_eq!(body["last_month"]["total_spent"], 100.0);
+    assert_eq!(body["last_month"]["spent_change"], 50.0);
+    assert_eq!(body["last_month"]["spent_change_ratio"], 0.5);
+
+    // A monthly expense is due this month and next
+    let upcoming = body["upcoming_fixed_expenses"].as_array().unwrap();
+    assert_eq!(upcoming.len(), 2);
+    assert_eq!(upcoming[0]["label"], "Rent");
+    assert_eq!(upcoming[0]["due_month"], today.month());
+}
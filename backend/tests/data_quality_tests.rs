@@ -0,0 +1,101 @@
+mod common;
+
+use common::{
+    auth_name, auth_value, create_test_budget, create_test_category, create_test_fixed_expense,
+    create_test_income, create_test_item, create_test_month, create_test_pool, create_test_server,
+    create_test_user, generate_token,
+};
+use payme::create_app;
+
+#[tokio::test]
+async fn test_data_quality_report_and_fixes() {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+
+    let old_month = create_test_month(&pool, user_id, 2020, 1).await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    create_test_income(&pool, month_id, "Salary", 3000.0).await;
+    let food = create_test_category(&pool, user_id, "Food", 300.0).await;
+    create_test_item(&pool, month_id, food, "Free sample", 0.0, "2024-06-02").await;
+    create_test_item(&pool, month_id, food, "Groceries", 40.0, "2024-06-03").await;
+    create_test_fixed_expense(&pool, user_id, "Rent", 1000.0).await;
+    let duplicate = create_test_fixed_expense(&pool, user_id, " rent", 1000.0).await;
+    // A second subscription with the same name but another price is no duplicate
+    create_test_fixed_expense(&pool, user_id, "Rent", 450.0).await;
+
+    // Budgets of deleted categories can only be left over from before foreign keys
+    let mut conn = pool.acquire().await.unwrap();
+    sqlx::query("PRAGMA foreign_keys = OFF")
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+    let orphan = create_test_budget_on(&mut conn, month_id, 999).await;
+    sqlx::query("PRAGMA foreign_keys = ON")
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+    drop(conn);
+    create_test_budget(&pool, month_id, food, 300.0).await;
+
+    let response = server
+        .get("/api/v1/maintenance/data-quality")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    let report: serde_json::Value = response.json();
+
+    assert_eq!(report["zero_amount_items"][0]["label"], "Free sample");
+    assert_eq!(report["idle_categories"][0]["id"], food);
+    assert_eq!(report["orphaned_budgets"][0]["id"], orphan);
+    let months_without_income = report["months_without_income"].as_array().unwrap();
+    assert_eq!(months_without_income.len(), 1);
+    assert_eq!(months_without_income[0]["month_id"], old_month);
+    assert_eq!(months_without_income[0]["label"], "2020-01");
+    let duplicates = report["duplicate_fixed_expenses"].as_array().unwrap();
+    assert_eq!(duplicates.len(), 1);
+    assert_eq!(duplicates[0]["id"], duplicate);
+
+    for check in ["zero_amount_items", "orphaned_budgets"] {
+        let response = server
+            .post(&format!("/api/v1/maintenance/data-quality/{check}/fix"))
+            .add_header(auth_name(), auth_value(&token))
+            .await;
+        response.assert_status_ok();
+        assert_eq!(response.json::<serde_json::Value>()["fixed"], 1);
+    }
+
+    for check in ["idle_categories", "duplicate_fixed_expenses"] {
+        server
+            .post(&format!("/api/v1/maintenance/data-quality/{check}/fix"))
+            .add_header(auth_name(), auth_value(&token))
+            .expect_failure()
+            .await
+            .assert_status_bad_request();
+    }
+
+    let report: serde_json::Value = server
+        .get("/api/v1/maintenance/data-quality")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert!(report["zero_amount_items"].as_array().unwrap().is_empty());
+    assert!(report["orphaned_budgets"].as_array().unwrap().is_empty());
+    assert_eq!(report["duplicate_fixed_expenses"][0]["id"], duplicate);
+}
+
+async fn create_test_budget_on(
+    conn: &mut sqlx::SqliteConnection,
+    month_id: i64,
+    category_id: i64,
+) -> i64 {
+    sqlx::query_scalar(
+        "INSERT INTO monthly_budgets (month_id, category_id, allocated_amount) VALUES (?, ?, 0) RETURNING id",
+    )
+    .bind(month_id)
+    .bind(category_id)
+    .fetch_one(conn)
+    .await
+    .unwrap()
+}
@@ -190,3 +190,170 @@ async fn test_import_json_replaces_existing() {
     assert_eq!(categories.len(), 1);
     assert_eq!(categories[0]["label"], "New Category");
 }
+
+async fn setup_month(pool: &sqlx::SqlitePool, user_id: i64) -> i64 {
+    let cat_id = create_test_category(pool, user_id, "Food", 500.0).await;
+    let month_id = create_test_month(pool, user_id, 2024, 6).await;
+    create_test_income(pool, month_id, "Salary", 5000.0).await;
+    create_test_budget(pool, month_id, cat_id, 500.0).await;
+    create_test_item(pool, month_id, cat_id, "Milk, eggs", 150.0, "2024-06-15").await;
+    month_id
+}
+
+#[tokio::test]
+async fn test_export_month_json_and_csv() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = setup_month(&pool, user_id).await;
+
+    let response = server
+        .get(&format!("/api/months/{}/export", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(
+        response.headers().get("content-disposition").unwrap(),
+        "attachment; filename=\"month-2024-06.json\""
+    );
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["total_income"], 5000.0);
+
+    let response = server
+        .get(&format!("/api/months/{}/export?format=csv", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.headers().get("content-type").unwrap(), "text/csv");
+    let csv = response.text();
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next(),
+        Some("id,spent_on,category,description,amount,savings_destination")
+    );
+    assert!(lines
+        .next()
+        .unwrap()
+        .ends_with(",2024-06-15,Food,\"Milk, eggs\",150,none"));
+}
+
+#[tokio::test]
+async fn test_export_month_zip() {
+    use std::io::Read;
+
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = setup_month(&pool, user_id).await;
+
+    let response = server
+        .get(&format!("/api/months/{}/export?format=zip", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+
+    let mut archive =
+        zip::ZipArchive::new(std::io::Cursor::new(response.as_bytes().to_vec())).unwrap();
+    let mut names: Vec<&str> = archive.file_names().collect();
+    names.sort();
+    assert_eq!(
+        names,
+        vec!["budgets.csv", "income.csv", "items.csv", "summary.json"]
+    );
+
+    let mut budgets = String::new();
+    archive
+        .by_name("budgets.csv")
+        .unwrap()
+        .read_to_string(&mut budgets)
+        .unwrap();
+    assert!(budgets.contains(",Food,500,150,350"));
+}
+
+#[tokio::test]
+async fn test_export_month_rejects_unknown_format_and_other_users() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = setup_month(&pool, user_id).await;
+
+    server
+        .get(&format!("/api/months/{}/export?format=xlsx", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+
+    let other_id = create_test_user(&pool, "other", "password123").await;
+    server
+        .get(&format!("/api/months/{}/export", month_id))
+        .add_header(auth_name(), auth_value(&generate_token(other_id, "other")))
+        .expect_failure()
+        .await
+        .assert_status_not_found();
+}
+
+#[tokio::test]
+async fn test_export_redacted_defaults() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let cat_id = create_test_category(&pool, user_id, "Health", 200.0).await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    create_test_item(&pool, month_id, cat_id, "Pharmacy", 43.99, "2024-06-15").await;
+    create_test_item(&pool, month_id, cat_id, "Pharmacy", 18.0, "2024-06-20").await;
+    create_test_item(&pool, month_id, cat_id, "Therapist", 96.0, "2024-06-21").await;
+
+    let response = server
+        .get("/api/v1/export/redacted")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+
+    response.assert_status_ok();
+    let body: serde_json::Value = response.json();
+    let items = body["months"][0]["items"].as_array().unwrap();
+    let description = |i: usize| items[i]["description"].as_str().unwrap();
+    let amounts: Vec<f64> = items
+        .iter()
+        .map(|i| i["amount"].as_f64().unwrap())
+        .collect();
+    let mut sorted = amounts.clone();
+    sorted.sort_by(f64::total_cmp);
+    assert_eq!(sorted, [20.0, 40.0, 100.0]);
+    assert!(!response.text().contains("Pharmacy"));
+    assert!(!response.text().contains("Therapist"));
+    let pharmacy: Vec<usize> = (0..3).filter(|&i| amounts[i] != 100.0).collect();
+    assert_eq!(description(pharmacy[0]), description(pharmacy[1]));
+    assert_eq!(items[0]["category_label"], "Health");
+    assert_eq!(body["categories"][0]["default_amount"], 200.0);
+}
+
+#[tokio::test]
+async fn test_export_redacted_per_field() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    create_test_fixed_expense(&pool, user_id, "Rent", 1500.0).await;
+    let cat_id = create_test_category(&pool, user_id, "Health", 200.0).await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    create_test_income(&pool, month_id, "ACME Corp", 5000.0).await;
+    create_test_item(&pool, month_id, cat_id, "Pharmacy", 12.5, "2024-06-15").await;
+
+    let response = server
+        .get("/api/v1/export/redacted?descriptions=drop&labels=hash&amounts=scale&scale_factor=2&dates=month")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+
+    response.assert_status_ok();
+    let body: serde_json::Value = response.json();
+    let month = &body["months"][0];
+    assert_eq!(month["items"][0]["description"], "");
+    assert_eq!(month["items"][0]["amount"], 25.0);
+    assert_eq!(month["items"][0]["spent_on"], "2024-06-01");
+    assert_eq!(month["income_entries"][0]["amount"], 10000.0);
+    assert_eq!(body["fixed_expenses"][0]["amount"], 3000.0);
+    // Labels are hashed the same wherever the category appears
+    assert_ne!(body["categories"][0]["label"], "Health");
+    assert_eq!(
+        month["items"][0]["category_label"],
+        body["categories"][0]["label"]
+    );
+    assert!(!response.text().contains("ACME"));
+
+    server
+        .get("/api/v1/export/redacted?amounts=bucket&bucket_size=0")
+        .add_header(auth_name(), auth_value(&token))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+}
@@ -1,8 +1,8 @@
 mod common;
 
 use common::{
-    auth_name, auth_value, create_test_fixed_expense, create_test_pool, create_test_server,
-    create_test_user, generate_token,
+    auth_name, auth_value, create_test_fixed_expense, create_test_month, create_test_pool,
+    create_test_server, create_test_user, generate_token,
 };
 use payme::create_app;
 use serde_json::json;
@@ -110,3 +110,270 @@ async fn test_delete_fixed_expense() {
     let body: Vec<serde_json::Value> = list_response.json();
     assert!(body.is_empty());
 }
+
+#[tokio::test]
+async fn test_yearly_expense_is_spread_over_months() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    create_test_fixed_expense(&pool, user_id, "Rent", 1000.0).await;
+
+    let response = server
+        .post("/api/v1/fixed-expenses")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "label": "Car insurance",
+            "amount": 600.0,
+            "billing_period": "yearly",
+            "payment_month": 3
+        }))
+        .await;
+    response.assert_status_ok();
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["billing_period"], "yearly");
+    assert_eq!(body["payment_month"], 3);
+
+    let march = create_test_month(&pool, user_id, 2024, 3).await;
+    let april = create_test_month(&pool, user_id, 2024, 4).await;
+
+    let summary: serde_json::Value = server
+        .get(&format!("/api/v1/months/{}", march))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(summary["total_fixed"], 1050.0);
+    assert_eq!(summary["fixed_due"], 1600.0);
+
+    let summary: serde_json::Value = server
+        .get(&format!("/api/v1/months/{}", april))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(summary["total_fixed"], 1050.0);
+    assert_eq!(summary["fixed_due"], 1000.0);
+}
+
+#[tokio::test]
+async fn test_billing_period_validation() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    server
+        .post("/api/v1/fixed-expenses")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "label": "Gym", "amount": 90.0, "billing_period": "quarterly" }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+
+    server
+        .post("/api/v1/fixed-expenses")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "label": "Gym", "amount": 90.0, "billing_period": "weekly" }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+
+    let expense_id = create_test_fixed_expense(&pool, user_id, "Water", 60.0).await;
+    let response = server
+        .put(&format!("/api/v1/fixed-expenses/{}", expense_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "billing_period": "quarterly", "payment_month": 2 }))
+        .await;
+    response.assert_status_ok();
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["amount"], 60.0);
+    assert_eq!(body["billing_period"], "quarterly");
+    assert_eq!(body["payment_month"], 2);
+
+    let response = server
+        .put(&format!("/api/v1/fixed-expenses/{}", expense_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "billing_period": "monthly" }))
+        .await;
+    response.assert_status_ok();
+    assert!(response.json::<serde_json::Value>()["payment_month"].is_null());
+}
+
+async fn add_price(
+    pool: &sqlx::SqlitePool,
+    expense_id: i64,
+    amount: f64,
+    billing_period: &str,
+    from: &str,
+) {
+    sqlx::query(
+        "INSERT INTO fixed_expense_history (fixed_expense_id, amount, billing_period, effective_from) VALUES (?, ?, ?, ?)",
+    )
+    .bind(expense_id)
+    .bind(amount)
+    .bind(billing_period)
+    .bind(from)
+    .execute(pool)
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn test_fixed_expense_history_records_changes() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let rent = create_test_fixed_expense(&pool, user_id, "Rent", 1000.0).await;
+    sqlx::query(
+        "UPDATE fixed_expense_history SET effective_from = '2024-01-01' WHERE fixed_expense_id = ?",
+    )
+    .bind(rent)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    for amount in [1050.0, 1073.0] {
+        server
+            .put(&format!("/api/v1/fixed-expenses/{rent}"))
+            .add_header(auth_name(), auth_value(&token))
+            .json(&json!({ "amount": amount }))
+            .await
+            .assert_status_ok();
+    }
+    // Relabeling isn't a price change
+    server
+        .put(&format!("/api/v1/fixed-expenses/{rent}"))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "label": "Flat" }))
+        .await
+        .assert_status_ok();
+
+    let response = server
+        .get(&format!("/api/v1/fixed-expenses/{rent}/history"))
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+
+    response.assert_status_ok();
+    let body: Vec<serde_json::Value> = response.json();
+    assert_eq!(body.len(), 2);
+    assert_eq!(body[0]["amount"], 1000.0);
+    assert_eq!(body[0]["effective_from"], "2024-01-01");
+    assert!(body[0]["change_percent"].is_null());
+    assert_eq!(body[1]["amount"], 1073.0);
+    assert_eq!(body[1]["change_percent"], 7.3);
+
+    let other = create_test_user(&pool, "other", "password123").await;
+    let response = server
+        .get(&format!("/api/v1/fixed-expenses/{rent}/history"))
+        .add_header(auth_name(), auth_value(&generate_token(other, "other")))
+        .expect_failure()
+        .await;
+    response.assert_status_not_found();
+}
+
+#[tokio::test]
+async fn test_fixed_cost_inflation_report() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let rent = create_test_fixed_expense(&pool, user_id, "Rent", 1100.0).await;
+    let internet = create_test_fixed_expense(&pool, user_id, "Internet", 50.0).await;
+    let gym = create_test_fixed_expense(&pool, user_id, "Gym", 30.0).await;
+    create_test_fixed_expense(&pool, user_id, "Streaming", 12.0).await;
+    add_price(&pool, rent, 1000.0, "monthly", "2023-06-01").await;
+    add_price(&pool, rent, 1100.0, "monthly", "2024-04-01").await;
+    add_price(&pool, internet, 600.0, "yearly", "2023-01-01").await;
+    add_price(&pool, gym, 30.0, "monthly", "2024-05-01").await;
+    server
+        .put("/api/v1/cpi")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "readings": [
+            { "year": 2024, "month": 1, "value": 100.0 },
+            { "year": 2024, "month": 12, "value": 103.0 }
+        ] }))
+        .await
+        .assert_status_ok();
+
+    let response = server
+        .get("/api/v1/fixed-expenses/inflation?year=2024")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+
+    response.assert_status_ok();
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["to"], "2024-12-31");
+    assert_eq!(body["start_monthly_total"], 1050.0);
+    assert_eq!(body["end_monthly_total"], 1150.0);
+    assert_eq!(body["change_percent"], 9.5);
+    assert_eq!(body["cpi_change_percent"], 3.0);
+    let expenses = body["expenses"].as_array().unwrap();
+    assert_eq!(expenses.len(), 3);
+    assert_eq!(expenses[0]["label"], "Rent");
+    assert_eq!(expenses[0]["change_percent"], 10.0);
+    assert_eq!(expenses[1]["label"], "Internet");
+    assert_eq!(expenses[1]["change_percent"], 0.0);
+    assert_eq!(expenses[2]["label"], "Gym");
+    assert!(expenses[2]["start_monthly_amount"].is_null());
+
+    let response = server
+        .get("/api/v1/fixed-expenses/inflation?year=2999")
+        .add_header(auth_name(), auth_value(&token))
+        .expect_failure()
+        .await;
+    response.assert_status_bad_request();
+}
+
+#[tokio::test]
+async fn test_bulk_increase_fixed_expenses() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let rent = create_test_fixed_expense(&pool, user_id, "Rent", 1200.0).await;
+    let power = create_test_fixed_expense(&pool, user_id, "Power", 80.0).await;
+    let internet = create_test_fixed_expense(&pool, user_id, "Internet", 40.0).await;
+    let edit = json!({
+        "ids": [rent, power],
+        "increase_percent": 5.0,
+        "labels": [{ "id": internet, "label": "Fibre" }]
+    });
+
+    let response = server
+        .put("/api/v1/fixed-expenses/bulk?dry_run=true")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&edit)
+        .await;
+    response.assert_status_ok();
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["dry_run"], true);
+    assert_eq!(body["changes"][0]["amount"], 1260.0);
+    let unchanged: f64 = sqlx::query_scalar("SELECT amount FROM fixed_expenses WHERE id = ?")
+        .bind(rent)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(unchanged, 1200.0);
+
+    let response = server
+        .put("/api/v1/fixed-expenses/bulk")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&edit)
+        .await;
+    response.assert_status_ok();
+    let expenses: Vec<(String, f64)> =
+        sqlx::query_as("SELECT label, amount FROM fixed_expenses ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+    assert_eq!(
+        expenses,
+        [
+            ("Rent".to_string(), 1260.0),
+            ("Power".to_string(), 84.0),
+            ("Fibre".to_string(), 40.0)
+        ]
+    );
+
+    // Another user's expense fails the whole edit
+    let other = create_test_user(&pool, "other", "password123").await;
+    let foreign = create_test_fixed_expense(&pool, other, "Rent", 900.0).await;
+    let response = server
+        .put("/api/v1/fixed-expenses/bulk")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "ids": [rent, foreign], "increase_percent": 5.0 }))
+        .expect_failure()
+        .await;
+    response.assert_status_bad_request();
+    let amount: f64 = sqlx::query_scalar("SELECT amount FROM fixed_expenses WHERE id = ?")
+        .bind(rent)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(amount, 1260.0);
+}
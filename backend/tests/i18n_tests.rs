@@ -0,0 +1,108 @@
+mod common;
+
+use axum::http::{header::ACCEPT_LANGUAGE, HeaderValue};
+use common::{
+    auth_name, auth_value, create_test_fixed_expense, create_test_income, create_test_month,
+    create_test_pool, create_test_server, create_test_user, generate_token,
+};
+use payme::create_app;
+use serde_json::json;
+
+async fn setup_with_user() -> (axum_test::TestServer, sqlx::SqlitePool, i64, String) {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let app = create_app(pool.clone());
+    let server = create_test_server(app);
+    (server, pool, user_id, token)
+}
+
+#[tokio::test]
+async fn test_validation_error_uses_accept_language() {
+    let (server, _pool, _user_id, token) = setup_with_user().await;
+
+    let response = server
+        .put("/api/settings")
+        .add_header(auth_name(), auth_value(&token))
+        .add_header(
+            ACCEPT_LANGUAGE,
+            HeaderValue::from_static("de-DE,de;q=0.9,en;q=0.8"),
+        )
+        .json(&json!({ "currency": "EURO" }))
+        .await;
+
+    response.assert_status_bad_request();
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"], "Einige Felder sind ungültig");
+    assert_eq!(body["fields"], json!(["currency"]));
+}
+
+#[tokio::test]
+async fn test_error_defaults_to_english() {
+    let (server, _pool, _user_id, token) = setup_with_user().await;
+
+    let response = server
+        .get("/api/months/99999")
+        .add_header(auth_name(), auth_value(&token))
+        .add_header(ACCEPT_LANGUAGE, HeaderValue::from_static("ja"))
+        .await;
+
+    response.assert_status_not_found();
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"], "Not found");
+}
+
+#[tokio::test]
+async fn test_insights_use_saved_locale() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    create_test_fixed_expense(&pool, user_id, "Loyer", 2000.0).await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    create_test_income(&pool, month_id, "Salaire", 3000.0).await;
+
+    server
+        .put("/api/settings")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "locale": "fr-FR" }))
+        .await
+        .assert_status_ok();
+
+    let body: serde_json::Value = server
+        .get("/api/insights")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    let insight = body["insights"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|i| i["kind"] == "fixed_cost_ratio")
+        .unwrap();
+    assert_eq!(
+        insight["message"],
+        "Les charges fixes représentent 67 % des revenus de ce mois"
+    );
+}
+
+#[tokio::test]
+async fn test_error_uses_saved_locale_and_keeps_reason() {
+    let (server, _pool, _user_id, token) = setup_with_user().await;
+
+    server
+        .put("/api/settings")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "locale": "fr-FR" }))
+        .await
+        .assert_status_ok();
+
+    let response = server
+        .get("/api/analytics/top?period=forever")
+        .add_header(auth_name(), auth_value(&token))
+        .add_header(ACCEPT_LANGUAGE, HeaderValue::from_static("de"))
+        .await;
+
+    response.assert_status_bad_request();
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["error"], "La requête n'a pas pu être traitée");
+    assert_eq!(body["reason"], "Invalid period: forever");
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use common::{
+    auth_name, auth_value, create_test_category, create_test_fixed_expense, create_test_income,
+    create_test_item, create_test_month, create_test_pool, create_test_server, create_test_user,
+    generate_token,
+};
+use payme::create_app;
+
+async fn setup_with_user() -> (axum_test::TestServer, sqlx::SqlitePool, i64, String) {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let app = create_app(pool.clone());
+    let server = create_test_server(app);
+    (server, pool, user_id, token)
+}
+
+#[tokio::test]
+async fn test_insights_empty() {
+    let (server, _pool, _user_id, token) = setup_with_user().await;
+
+    let response = server
+        .get("/api/insights")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+
+    response.assert_status_ok();
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["health_score"], 100);
+    assert!(body["insights"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_insights_category_spike_and_fixed_ratio() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let cat_id = create_test_category(&pool, user_id, "Food", 500.0).await;
+    create_test_fixed_expense(&pool, user_id, "Rent", 2000.0).await;
+    for month in 3..=5 {
+        let month_id = create_test_month(&pool, user_id, 2024, month).await;
+        create_test_item(&pool, month_id, cat_id, "Groceries", 100.0, "2024-03-15").await;
+    }
+    let current_id = create_test_month(&pool, user_id, 2024, 6).await;
+    create_test_income(&pool, current_id, "Salary", 3000.0).await;
+    create_test_item(&pool, current_id, cat_id, "Groceries", 150.0, "2024-06-15").await;
+
+    let response = server
+        .get("/api/insights")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+
+    response.assert_status_ok();
+    let body: serde_json::Value = response.json();
+    let kinds: Vec<&str> = body["insights"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|i| i["kind"].as_str().unwrap())
+        .collect();
+    assert!(kinds.contains(&"category_spike"));
+    assert!(kinds.contains(&"fixed_cost_ratio"));
+    assert_eq!(body["health_score"], 70);
+}
+
+#[tokio::test]
+async fn test_dismissed_insight_stays_hidden_after_refresh() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    create_test_fixed_expense(&pool, user_id, "Rent", 2000.0).await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    create_test_income(&pool, month_id, "Salary", 3000.0).await;
+
+    let body: serde_json::Value = server
+        .get("/api/insights")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    let insight = body["insights"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|i| i["kind"] == "fixed_cost_ratio")
+        .unwrap();
+    let insight_id = insight["id"].as_i64().unwrap();
+
+    let response = server
+        .post(&format!("/api/insights/{}/dismiss", insight_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    let dismissed: serde_json::Value = response.json();
+    assert_eq!(dismissed["is_dismissed"], true);
+
+    let body: serde_json::Value = server
+        .get("/api/insights?refresh=true")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert!(body["insights"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .all(|i| i["id"] != insight_id));
+}
+
+#[tokio::test]
+async fn test_mark_insight_read_not_found() {
+    let (server, _pool, _user_id, token) = setup_with_user().await;
+
+    let response = server
+        .post("/api/insights/99999/read")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+
+    response.assert_status_not_found();
+}
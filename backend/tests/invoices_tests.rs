@@ -0,0 +1,169 @@
+mod common;
+
+use common::{
+    auth_name, auth_value, close_test_month, create_test_month, create_test_pool,
+    create_test_server, create_test_user, generate_token,
+};
+use payme::create_app;
+use serde_json::json;
+
+async fn setup_with_user() -> (axum_test::TestServer, sqlx::SqlitePool, i64, String) {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+    (server, pool, user_id, token)
+}
+
+#[tokio::test]
+async fn test_paid_invoice_becomes_income() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let invoice: serde_json::Value = server
+        .post("/api/v1/invoices")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "client": "Acme",
+            "amount": 1200.0,
+            "issued_on": "2024-05-01",
+            "due_on": "2024-05-31"
+        }))
+        .await
+        .json();
+    assert_eq!(invoice["status"], "sent");
+    assert_eq!(invoice["overdue"], true);
+    let status_url = format!("/api/v1/invoices/{}/status", invoice["id"]);
+
+    let paid: serde_json::Value = server
+        .put(&status_url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "status": "paid", "paid_on": "2024-06-12" }))
+        .await
+        .json();
+    assert_eq!(paid["paid_on"], "2024-06-12");
+    assert_eq!(paid["overdue"], false);
+
+    let month_id: i64 =
+        sqlx::query_scalar("SELECT id FROM months WHERE user_id = ? AND year = 2024 AND month = 6")
+            .bind(user_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    let summary: serde_json::Value = server
+        .get(&format!("/api/v1/months/{}", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(summary["total_income"], 1200.0);
+    assert_eq!(summary["income_entries"][0]["label"], "Invoice: Acme");
+
+    server
+        .put(&format!("/api/v1/invoices/{}", invoice["id"]))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "amount": 1500.0 }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+
+    server
+        .put(&status_url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "status": "sent" }))
+        .await
+        .assert_status_ok();
+    let summary: serde_json::Value = server
+        .get(&format!("/api/v1/months/{}", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(summary["total_income"], 0.0);
+}
+
+#[tokio::test]
+async fn test_invoice_paid_in_closed_month_rejected() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    close_test_month(&pool, month_id).await;
+
+    let invoice: serde_json::Value = server
+        .post("/api/v1/invoices")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "client": "Acme", "amount": 300.0, "issued_on": "2024-06-01", "due_on": "2024-06-30" }))
+        .await
+        .json();
+
+    server
+        .put(&format!("/api/v1/invoices/{}/status", invoice["id"]))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "status": "paid", "paid_on": "2024-06-20" }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+}
+
+#[tokio::test]
+async fn test_invoice_paid_twice_at_once_books_one_income() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    create_test_month(&pool, user_id, 2024, 6).await;
+
+    let invoice: serde_json::Value = server
+        .post("/api/v1/invoices")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "client": "Acme", "amount": 300.0, "issued_on": "2024-06-01", "due_on": "2024-06-30" }))
+        .await
+        .json();
+    let status_url = format!("/api/v1/invoices/{}/status", invoice["id"]);
+    let pay = || {
+        server
+            .put(&status_url)
+            .add_header(auth_name(), auth_value(&token))
+            .json(&json!({ "status": "paid", "paid_on": "2024-06-20" }))
+    };
+
+    let (first, second) = tokio::join!(pay(), pay());
+    let statuses = [first.status_code().as_u16(), second.status_code().as_u16()];
+    assert!(statuses.contains(&200), "{statuses:?}");
+    assert!(
+        statuses.iter().all(|s| [200, 409].contains(s)),
+        "{statuses:?}"
+    );
+
+    let (entries, activity): (i64, String) = sqlx::query_as(
+        "SELECT (SELECT COUNT(*) FROM income_entries), (SELECT summary FROM activity_log WHERE entity_type = 'income')",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(entries, 1);
+    assert_eq!(activity, "testuser recorded Invoice: Acme $300.00");
+}
+
+#[tokio::test]
+async fn test_overdue_invoice_in_insights() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    create_test_month(&pool, user_id, 2024, 6).await;
+
+    for (client, due_on) in [("Acme", "2024-06-01"), ("Globex", "2099-01-01")] {
+        server
+            .post("/api/v1/invoices")
+            .add_header(auth_name(), auth_value(&token))
+            .json(&json!({ "client": client, "amount": 500.0, "issued_on": "2024-05-01", "due_on": due_on }))
+            .await
+            .assert_status_ok();
+    }
+
+    let body: serde_json::Value = server
+        .get("/api/v1/insights?refresh=true")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    let overdue: Vec<&str> = body["insights"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|i| i["kind"] == "invoice_overdue")
+        .map(|i| i["message"].as_str().unwrap())
+        .collect();
+    assert_eq!(overdue.len(), 1);
+    assert!(overdue[0].starts_with("The invoice to Acme for $500.00 is"));
+}
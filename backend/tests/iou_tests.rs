@@ -0,0 +1,147 @@
+mod common;
+
+use chrono::{Datelike, Utc};
+use common::{
+    auth_name, auth_value, create_test_category, create_test_item, create_test_month,
+    create_test_pool, create_test_server, create_test_user, generate_token,
+};
+use payme::create_app;
+use serde_json::json;
+
+async fn setup_with_user() -> (axum_test::TestServer, sqlx::SqlitePool, i64, String) {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+    (server, pool, user_id, token)
+}
+
+#[tokio::test]
+async fn test_split_and_repay() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let today = Utc::now();
+    let category_id = create_test_category(&pool, user_id, "Dining Out", 150.0).await;
+    let month_id = create_test_month(&pool, user_id, today.year(), today.month() as i32).await;
+    let dinner = create_test_item(&pool, month_id, category_id, "Dinner", 90.0, "2024-06-10").await;
+    let tickets =
+        create_test_item(&pool, month_id, category_id, "Concert", 120.0, "2024-06-12").await;
+
+    let splits_url = format!("/api/v1/months/{}/items/{}/splits", month_id, dinner);
+    for person in ["Sam", "Alex"] {
+        server
+            .post(&splits_url)
+            .add_header(auth_name(), auth_value(&token))
+            .json(&json!({ "person": person, "amount": 30.0 }))
+            .await
+            .assert_status_ok();
+    }
+    server
+        .post(&splits_url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "person": "Jo", "amount": 30.01 }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+
+    let item_url = format!("/api/v1/months/{}/items/{}", month_id, dinner);
+    server
+        .put(&item_url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "amount": 59.99 }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+    server
+        .put(&item_url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "amount": 60.0 }))
+        .await
+        .assert_status_ok();
+    server
+        .put(&item_url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "amount": 90.0 }))
+        .await
+        .assert_status_ok();
+
+    let concert: serde_json::Value = server
+        .post(&format!(
+            "/api/v1/months/{}/items/{}/splits",
+            month_id, tickets
+        ))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "person": "sam", "amount": 60.0 }))
+        .await
+        .json();
+
+    let report: serde_json::Value = server
+        .get("/api/v1/iou")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(report["total_outstanding"], 120.0);
+    assert_eq!(report["people"][0]["person"], "Sam");
+    assert_eq!(report["people"][0]["outstanding"], 90.0);
+    assert_eq!(report["people"][1]["person"], "Alex");
+
+    let repay_url = format!("/api/v1/iou/{}/repayments", concert["id"]);
+    let response = server
+        .post(&repay_url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "amount": 20.0 }))
+        .await;
+    response.assert_status_ok();
+    let income: serde_json::Value = response.json();
+    assert_eq!(income["month_id"], month_id);
+    assert_eq!(income["label"], "sam repaid Concert");
+
+    server
+        .post(&repay_url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "amount": 50.0 }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+    server
+        .post(&repay_url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({}))
+        .await
+        .assert_status_ok();
+
+    let report: serde_json::Value = server
+        .get("/api/v1/iou")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(report["total_outstanding"], 60.0);
+    assert_eq!(report["people"][0]["outstanding"], 30.0);
+    assert_eq!(report["people"][0]["entries"][0]["description"], "Dinner");
+
+    let summary: serde_json::Value = server
+        .get(&format!("/api/v1/months/{}", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(summary["total_income"], 60.0);
+}
+
+#[tokio::test]
+async fn test_split_other_users_item_not_found() {
+    let (server, pool, _user_id, token) = setup_with_user().await;
+    let other = create_test_user(&pool, "other", "password123").await;
+    let category_id = create_test_category(&pool, other, "Food", 100.0).await;
+    let month_id = create_test_month(&pool, other, 2024, 6).await;
+    let item_id = create_test_item(&pool, month_id, category_id, "Lunch", 20.0, "2024-06-10").await;
+
+    server
+        .post(&format!(
+            "/api/v1/months/{}/items/{}/splits",
+            month_id, item_id
+        ))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "person": "Sam", "amount": 10.0 }))
+        .expect_failure()
+        .await
+        .assert_status_not_found();
+}
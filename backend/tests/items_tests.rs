@@ -61,6 +61,183 @@ async fn test_create_item() {
     assert_eq!(body["category_id"], cat_id);
 }
 
+#[tokio::test]
+async fn test_create_item_duplicate_rejected() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let cat_id = create_test_category(&pool, user_id, "Food", 500.0).await;
+    let url = format!("/api/months/{}/items", month_id);
+
+    let first: serde_json::Value = server
+        .post(&url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "category_id": cat_id,
+            "description": "Coffee shop",
+            "amount": 5.0,
+            "spent_on": "2024-06-15"
+        }))
+        .await
+        .json();
+
+    let duplicate = json!({
+        "category_id": cat_id,
+        "description": "coffee  SHOP!",
+        "amount": 5.0,
+        "spent_on": "2024-06-15"
+    });
+
+    let response = server
+        .post(&url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&duplicate)
+        .await;
+    response.assert_status(axum::http::StatusCode::CONFLICT);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["duplicate_of"], first["id"]);
+    assert_eq!(body["error"], "This looks like an item entered moments ago");
+
+    let response = server
+        .post(&format!("{}?force=true", url))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&duplicate)
+        .await;
+
+    response.assert_status_ok();
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["duplicate_of"], first["id"]);
+}
+
+#[tokio::test]
+async fn test_create_item_different_date_not_duplicate() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let cat_id = create_test_category(&pool, user_id, "Food", 500.0).await;
+
+    for day in ["2024-06-15", "2024-06-16"] {
+        let response = server
+            .post(&format!("/api/months/{}/items", month_id))
+            .add_header(auth_name(), auth_value(&token))
+            .json(&json!({
+                "category_id": cat_id,
+                "description": "Coffee",
+                "amount": 5.0,
+                "spent_on": day
+            }))
+            .await;
+
+        response.assert_status_ok();
+        let body: serde_json::Value = response.json();
+        assert!(body.get("duplicate_of").is_none());
+    }
+}
+
+#[tokio::test]
+async fn test_list_items_filtered() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let food = create_test_category(&pool, user_id, "Food", 500.0).await;
+    let fun = create_test_category(&pool, user_id, "Fun", 100.0).await;
+    create_test_item(&pool, month_id, food, "Groceries", 80.0, "2024-06-03").await;
+    create_test_item(&pool, month_id, food, "Coffee 100%", 4.5, "2024-06-10").await;
+    create_test_item(&pool, month_id, fun, "Cinema", 12.0, "2024-06-20").await;
+
+    let list = |query: String| list_descriptions(&server, &token, month_id, query);
+
+    assert_eq!(list(format!("category_id={}", fun)).await, ["Cinema"]);
+    assert_eq!(
+        list("from=2024-06-05&to=2024-06-15".into()).await,
+        ["Coffee 100%"]
+    );
+    assert_eq!(list("min_amount=10&max_amount=50".into()).await, ["Cinema"]);
+    assert_eq!(list("q=FUN".into()).await, ["Cinema"]);
+    assert_eq!(list("q=%25".into()).await, ["Coffee 100%"]);
+}
+
+async fn list_descriptions(
+    server: &axum_test::TestServer,
+    token: &str,
+    month_id: i64,
+    query: String,
+) -> Vec<String> {
+    let response = server
+        .get(&format!("/api/months/{}/items?{}", month_id, query))
+        .add_header(auth_name(), auth_value(token))
+        .await;
+    response.assert_status_ok();
+    let body: Vec<serde_json::Value> = response.json();
+    body.iter()
+        .map(|item| item["description"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn test_create_item_rejects_unreasonable_amounts() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let cat_id = create_test_category(&pool, user_id, "Food", 500.0).await;
+
+    for amount in [1e308, 12.345] {
+        let response = server
+            .post(&format!("/api/months/{}/items", month_id))
+            .add_header(auth_name(), auth_value(&token))
+            .json(&json!({
+                "category_id": cat_id,
+                "description": "Coffee",
+                "amount": amount,
+                "spent_on": "2024-06-15"
+            }))
+            .await;
+
+        response.assert_status_bad_request();
+        let body: serde_json::Value = response.json();
+        assert_eq!(body["fields"], json!(["amount"]));
+    }
+}
+
+#[tokio::test]
+async fn test_create_item_rejects_unknown_and_mistyped_fields() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let cat_id = create_test_category(&pool, user_id, "Food", 500.0).await;
+
+    let response = server
+        .post(&format!("/api/months/{}/items", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "category_id": cat_id,
+            "description": "Coffee",
+            "ammount": 5.0,
+            "spent_on": "2024-06-15"
+        }))
+        .await;
+
+    response.assert_status(axum::http::StatusCode::UNPROCESSABLE_ENTITY);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["fields"], json!(["ammount"]));
+
+    let response = server
+        .post(&format!("/api/months/{}/items", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "category_id": cat_id,
+            "description": "Coffee",
+            "amount": "5",
+            "spent_on": "2024-06-15"
+        }))
+        .await;
+
+    response.assert_status(axum::http::StatusCode::UNPROCESSABLE_ENTITY);
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["fields"], json!(["amount"]));
+    assert_eq!(body["reason"], "invalid type: string \"5\", expected f64");
+}
+
 #[tokio::test]
 async fn test_create_item_invalid_category() {
     let (server, pool, user_id, token) = setup_with_user().await;
@@ -171,3 +348,505 @@ async fn test_delete_item() {
     let body: Vec<serde_json::Value> = list_response.json();
     assert!(body.is_empty());
 }
+
+#[tokio::test]
+async fn test_reimbursement_workflow() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let cat_id = create_test_category(&pool, user_id, "Travel", 500.0).await;
+    create_test_item(&pool, month_id, cat_id, "Train", 40.0, "2024-06-03").await;
+
+    let response = server
+        .post(&format!("/api/months/{}/items", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "category_id": cat_id,
+            "description": "Client hotel",
+            "amount": 200.0,
+            "spent_on": "2024-06-10",
+            "reimbursable": true
+        }))
+        .await;
+    response.assert_status_ok();
+    let hotel: serde_json::Value = response.json();
+    assert_eq!(hotel["reimbursement_status"], "pending");
+
+    let report: serde_json::Value = server
+        .get("/api/reimbursements")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(report["items"][0]["description"], "Client hotel");
+    assert_eq!(report["total_pending"], 200.0);
+
+    close_test_month(&pool, month_id).await;
+    let url = format!(
+        "/api/months/{}/items/{}/reimbursement",
+        month_id, hotel["id"]
+    );
+    server
+        .put(&url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "status": "paid" }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+    let response = server
+        .put(&url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "status": "reimbursed" }))
+        .await;
+    response.assert_status_ok();
+    let hotel: serde_json::Value = response.json();
+    assert_eq!(hotel["reimbursement_status"], "reimbursed");
+
+    let report: serde_json::Value = server
+        .get("/api/reimbursements")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert!(report["items"].as_array().unwrap().is_empty());
+    assert_eq!(report["total_outstanding"], 0.0);
+
+    let summary: serde_json::Value = server
+        .get(&format!("/api/months/{}", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(summary["total_spent"], 240.0);
+
+    let summary: serde_json::Value = server
+        .get(&format!("/api/months/{}?exclude_reimbursed=true", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(summary["total_spent"], 40.0);
+}
+
+#[tokio::test]
+async fn test_mileage_and_per_diem_items() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let cat_id = create_test_category(&pool, user_id, "Travel", 500.0).await;
+    let mileage_url = format!("/api/months/{}/items/mileage", month_id);
+
+    server
+        .post(&mileage_url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "category_id": cat_id, "distance": 120.0, "spent_on": "2024-06-10" }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+
+    server
+        .put("/api/settings")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "mileage_rate": 0.5 }))
+        .await
+        .assert_status_ok();
+
+    let response = server
+        .post(&mileage_url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "category_id": cat_id,
+            "distance": 120.0,
+            "spent_on": "2024-06-10",
+            "purpose": "Client visit",
+            "reimbursable": true
+        }))
+        .await;
+    response.assert_status_ok();
+    let mileage: serde_json::Value = response.json();
+    assert_eq!(mileage["amount"], 60.0);
+    assert_eq!(mileage["description"], "Client visit - mileage 120 x 0.50");
+    assert_eq!(mileage["reimbursement_status"], "pending");
+    assert_eq!(mileage["calculation"]["rate"], 0.5);
+
+    let response = server
+        .post(&format!("/api/months/{}/items/per-diem", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(
+            &json!({ "category_id": cat_id, "days": 2.5, "rate": 40.0, "spent_on": "2024-06-11" }),
+        )
+        .await;
+    response.assert_status_ok();
+    let per_diem: serde_json::Value = response.json();
+    assert_eq!(per_diem["amount"], 100.0);
+    assert_eq!(per_diem["description"], "Per diem 2.5 days x 40.00");
+
+    server
+        .put("/api/settings")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "mileage_rate": 0.67 }))
+        .await
+        .assert_status_ok();
+    let response = server
+        .post(&format!(
+            "/api/months/{}/items/{}/recalculate",
+            month_id, mileage["id"]
+        ))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({}))
+        .await;
+    response.assert_status_ok();
+    let recalculated: serde_json::Value = response.json();
+    assert_eq!(recalculated["amount"], 80.4);
+    assert_eq!(
+        recalculated["description"],
+        "Client visit - mileage 120 x 0.67"
+    );
+
+    let summary: serde_json::Value = server
+        .get(&format!("/api/months/{}", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(summary["total_spent"], 180.4);
+    let items = summary["items"].as_array().unwrap();
+    assert!(items
+        .iter()
+        .all(|item| item["calculation"]["kind"].is_string()));
+}
+
+#[tokio::test]
+async fn test_allowance_amounts_are_bounded() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let cat_id = create_test_category(&pool, user_id, "Travel", 500.0).await;
+    let mileage_url = format!("/api/months/{}/items/mileage", month_id);
+
+    server
+        .post(&mileage_url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "category_id": cat_id, "distance": 10, "rate": 1e308, "spent_on": "2024-06-10" }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+
+    server
+        .put("/api/settings")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "per_diem_rate": 1e308 }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+
+    let response = server
+        .post(&mileage_url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "category_id": cat_id, "distance": 100000, "rate": 100000, "spent_on": "2024-06-10" }))
+        .expect_failure()
+        .await;
+    response.assert_status_bad_request();
+    let body: serde_json::Value = response.json();
+    assert!(body.to_string().contains("amount"));
+
+    let summary: serde_json::Value = server
+        .get(&format!("/api/months/{}", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(summary["items"].as_array().unwrap().len(), 0);
+}
+
+#[tokio::test]
+async fn test_item_tax_and_summary() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let cat_id = create_test_category(&pool, user_id, "Office", 300.0).await;
+    let may = create_test_month(&pool, user_id, 2024, 5).await;
+    let june = create_test_month(&pool, user_id, 2024, 6).await;
+
+    let response = server
+        .post(&format!("/api/months/{}/items", may))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "category_id": cat_id,
+            "description": "Laptop stand",
+            "amount": 120.0,
+            "spent_on": "2024-05-10",
+            "tax_rate": 20.0
+        }))
+        .await;
+    response.assert_status_ok();
+    let stand: serde_json::Value = response.json();
+    assert_eq!(stand["tax_amount"], 20.0);
+
+    server
+        .post(&format!("/api/months/{}/close", may))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_ok();
+
+    let item_id = create_test_item(&pool, june, cat_id, "Paper", 11.0, "2024-06-03").await;
+    let response = server
+        .put(&format!("/api/months/{}/items/{}", june, item_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "tax_rate": 10.0 }))
+        .await;
+    response.assert_status_ok();
+    let paper: serde_json::Value = response.json();
+    assert_eq!(paper["tax_amount"], 1.0);
+
+    let summary: serde_json::Value = server
+        .get("/api/v1/tax?year=2024")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(summary["gross"], 131.0);
+    assert_eq!(summary["tax"], 21.0);
+    assert_eq!(summary["rates"][0]["rate"], 10.0);
+    assert_eq!(summary["rates"][1]["net"], 100.0);
+    assert_eq!(summary["months"][0]["month"], 5);
+
+    let response = server
+        .put(&format!("/api/months/{}/items/{}", june, item_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "tax_rate": null }))
+        .await;
+    response.assert_status_ok();
+    let paper: serde_json::Value = response.json();
+    assert!(paper["tax_amount"].is_null());
+
+    let summary: serde_json::Value = server
+        .get("/api/v1/tax?year=2024&month=6")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(summary["tax"], 0.0);
+    assert!(summary["rates"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_cash_wallet() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let cat_id = create_test_category(&pool, user_id, "Food", 300.0).await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+
+    let items_url = format!("/api/months/{}/items", month_id);
+    server
+        .post(&items_url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "category_id": cat_id,
+            "description": "ATM",
+            "amount": 200.0,
+            "spent_on": "2024-06-01",
+            "savings_destination": "cash"
+        }))
+        .await
+        .assert_status_ok();
+    let response = server
+        .post(&items_url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "category_id": cat_id,
+            "description": "Market",
+            "amount": 45.0,
+            "spent_on": "2024-06-02",
+            "paid_in_cash": true
+        }))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<serde_json::Value>()["paid_in_cash"], true);
+
+    server
+        .post(&items_url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "category_id": cat_id,
+            "description": "ATM",
+            "amount": 50.0,
+            "spent_on": "2024-06-03",
+            "savings_destination": "cash",
+            "paid_in_cash": true
+        }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+
+    let summary: serde_json::Value = server
+        .get(&format!("/api/months/{}", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(summary["total_spent"], 45.0);
+
+    let cash: serde_json::Value = server
+        .get("/api/v1/cash")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(cash["withdrawn"], 200.0);
+    assert_eq!(cash["itemized"], 45.0);
+    assert_eq!(cash["untracked"], 155.0);
+    assert_eq!(cash["months"][0]["month"], 6);
+}
+
+#[tokio::test]
+async fn test_description_suggestions() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let food = create_test_category(&pool, user_id, "Food", 500.0).await;
+    let fun = create_test_category(&pool, user_id, "Fun", 100.0).await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    create_test_item(&pool, month_id, food, "Coffee", 3.8, "2024-06-01").await;
+    create_test_item(&pool, month_id, food, "coffee", 4.2, "2024-06-02").await;
+    create_test_item(&pool, month_id, fun, "Coffee ", 40.0, "2024-06-03").await;
+    create_test_item(&pool, month_id, fun, "Concert", 60.0, "2024-06-04").await;
+    create_test_item(&pool, month_id, food, "Bakery", 6.0, "2024-06-05").await;
+
+    let suggestions: serde_json::Value = server
+        .get("/api/items/suggestions?prefix=co")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(
+        suggestions,
+        json!([
+            {
+                "description": "Coffee",
+                "category_id": food,
+                "category_label": "Food",
+                "median_amount": 4.2,
+                "uses": 3
+            },
+            {
+                "description": "Concert",
+                "category_id": fun,
+                "category_label": "Fun",
+                "median_amount": 60.0,
+                "uses": 1
+            }
+        ])
+    );
+
+    // New items show up straight away
+    server
+        .post(&format!("/api/months/{}/items", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "category_id": food,
+            "description": "Cornflakes",
+            "amount": 3.5,
+            "spent_on": "2024-06-06"
+        }))
+        .await
+        .assert_status_ok();
+    let suggestions: serde_json::Value = server
+        .get("/api/items/suggestions?prefix=COR&limit=5")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(suggestions[0]["description"], "Cornflakes");
+
+    server
+        .get("/api/items/suggestions?prefix=co&limit=100")
+        .add_header(auth_name(), auth_value(&token))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+}
+
+#[tokio::test]
+async fn test_uncategorized_items_block_close() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let cat_id = create_test_category(&pool, user_id, "Food", 500.0).await;
+    create_test_item(&pool, month_id, cat_id, "Groceries", 150.0, "2024-06-15").await;
+
+    let response = server
+        .post(&format!("/api/v1/months/{month_id}/items"))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "description": "Card payment 4411",
+            "amount": 23.5,
+            "spent_on": "2024-06-12"
+        }))
+        .await;
+    response.assert_status_ok();
+    let item: serde_json::Value = response.json();
+
+    let response = server
+        .get(&format!("/api/v1/months/{month_id}/items/uncategorized"))
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    let uncategorized: Vec<serde_json::Value> = response.json();
+    assert_eq!(uncategorized.len(), 1);
+    assert_eq!(uncategorized[0]["description"], "Card payment 4411");
+    assert_eq!(uncategorized[0]["category_label"], "Uncategorized");
+
+    let response = server
+        .post(&format!("/api/v1/months/{month_id}/close"))
+        .add_header(auth_name(), auth_value(&token))
+        .expect_failure()
+        .await;
+    response.assert_status_bad_request();
+
+    server
+        .put(&format!("/api/v1/months/{month_id}/items/{}", item["id"]))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "category_id": cat_id }))
+        .await
+        .assert_status_ok();
+    let uncategorized: Vec<serde_json::Value> = server
+        .get(&format!("/api/v1/months/{month_id}/items/uncategorized"))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert!(uncategorized.is_empty());
+
+    server
+        .post(&format!("/api/v1/months/{month_id}/close"))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_close_with_uncategorized_setting() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+
+    server
+        .post(&format!("/api/v1/months/{month_id}/items"))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "category_id": null,
+            "description": "Card payment 4411",
+            "amount": 23.5,
+            "spent_on": "2024-06-12"
+        }))
+        .await
+        .assert_status_ok();
+    server
+        .put("/api/v1/settings")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "close_with_uncategorized": true }))
+        .await
+        .assert_status_ok();
+
+    server
+        .post(&format!("/api/v1/months/{month_id}/close"))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_uncategorized_items_of_another_user() {
+    let (server, pool, _user_id, token) = setup_with_user().await;
+    let other = create_test_user(&pool, "other", "password123").await;
+    let month_id = create_test_month(&pool, other, 2024, 6).await;
+
+    let response = server
+        .get(&format!("/api/v1/months/{month_id}/items/uncategorized"))
+        .add_header(auth_name(), auth_value(&token))
+        .expect_failure()
+        .await;
+    response.assert_status_not_found();
+}
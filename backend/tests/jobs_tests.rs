@@ -0,0 +1,311 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use common::{
+    auth_name, auth_value, create_test_month, create_test_pool, create_test_server,
+    create_test_user, generate_token,
+};
+use payme::create_app;
+use serde_json::json;
+
+async fn setup_with_user() -> (axum_test::TestServer, sqlx::SqlitePool, i64, String) {
+    // Deliveries go to servers on this machine
+    std::env::set_var("ALLOW_PRIVATE_TARGETS", "true");
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let app = create_app(pool.clone());
+    let server = create_test_server(app);
+    (server, pool, user_id, token)
+}
+
+async fn stored_snapshot(pool: &sqlx::SqlitePool, month_id: i64) -> Vec<u8> {
+    let key: String =
+        sqlx::query_scalar("SELECT storage_key FROM monthly_snapshots WHERE month_id = ?")
+            .bind(month_id)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+    payme::storage::read_all(payme::storage::backend(pool).as_ref(), &key)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_close_month_queues_pdf_job() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+
+    let response = server
+        .post(&format!("/api/months/{}/close", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    let job_id = response.json::<serde_json::Value>()["pdf_job_id"]
+        .as_i64()
+        .unwrap();
+
+    let job: serde_json::Value = server
+        .get(&format!("/api/jobs/{}", job_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(job["kind"], "month_pdf");
+    assert_eq!(job["status"], "queued");
+
+    server
+        .get(&format!("/api/months/{}/pdf", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .expect_failure()
+        .await
+        .assert_status_not_found();
+
+    assert_eq!(payme::jobs::run_pending(&pool).await.unwrap(), 1);
+
+    let job: serde_json::Value = server
+        .get(&format!("/api/jobs/{}", job_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(job["status"], "done");
+    assert_eq!(job["attempts"], 1);
+    assert!(job["finished_at"].as_str().is_some());
+
+    server
+        .get(&format!("/api/months/{}/pdf", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_failed_job_is_retried_then_marked_failed() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let job_id = payme::jobs::enqueue(&pool, user_id, "unknown", &serde_json::json!({}))
+        .await
+        .unwrap();
+    assert_eq!(payme::jobs::run_pending(&pool).await.unwrap(), 3);
+
+    let job: serde_json::Value = server
+        .get(&format!("/api/jobs/{}", job_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(job["status"], "failed");
+    assert_eq!(job["attempts"], 3);
+    assert!(job["error"].as_str().unwrap().contains("unknown"));
+}
+
+#[tokio::test]
+async fn test_job_of_other_user_not_found() {
+    let (server, pool, _user_id, token) = setup_with_user().await;
+    let other_id = create_test_user(&pool, "other", "password123").await;
+
+    let job_id = payme::jobs::enqueue(&pool, other_id, "unknown", &serde_json::json!({}))
+        .await
+        .unwrap();
+
+    server
+        .get(&format!("/api/jobs/{}", job_id))
+        .add_header(auth_name(), auth_value(&token))
+        .expect_failure()
+        .await
+        .assert_status_not_found();
+}
+
+#[tokio::test]
+async fn test_month_pdf_job_is_idempotent() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+
+    server
+        .post(&format!("/api/months/{}/close", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_ok();
+    payme::jobs::run_pending(&pool).await.unwrap();
+    let first = stored_snapshot(&pool, month_id).await;
+
+    let job_id = payme::jobs::enqueue(
+        &pool,
+        user_id,
+        payme::jobs::MONTH_PDF,
+        &payme::jobs::MonthPdfJob {
+            month_id,
+            locale: "fr".to_string(),
+        },
+    )
+    .await
+    .unwrap();
+    payme::jobs::run_pending(&pool).await.unwrap();
+
+    let job: serde_json::Value = server
+        .get(&format!("/api/jobs/{}", job_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(job["status"], "done");
+
+    let snapshots: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM monthly_snapshots WHERE month_id = ?")
+            .bind(month_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(snapshots, 1);
+    assert_eq!(stored_snapshot(&pool, month_id).await, first);
+}
+
+#[tokio::test]
+async fn test_close_month_twice_queues_one_job() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+
+    server
+        .post(&format!("/api/months/{}/close", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_ok();
+    server
+        .post(&format!("/api/months/{}/close", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+
+    let jobs: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM jobs WHERE user_id = ?")
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(jobs, 1);
+}
+
+/// Serves a WebDAV stand-in on a local port that records each PUT as
+/// `(path, authorization header, body length)`.
+async fn webdav_server() -> (String, Arc<Mutex<Vec<(String, String, usize)>>>) {
+    let uploads = Arc::new(Mutex::new(Vec::new()));
+    let recorded = uploads.clone();
+    let app = axum::Router::new().fallback(axum::routing::put(
+        move |uri: axum::http::Uri, headers: axum::http::HeaderMap, body: axum::body::Bytes| {
+            let recorded = recorded.clone();
+            async move {
+                let auth = headers
+                    .get("authorization")
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or_default()
+                    .to_string();
+                recorded
+                    .lock()
+                    .unwrap()
+                    .push((uri.path().to_string(), auth, body.len()));
+                axum::http::StatusCode::CREATED
+            }
+        },
+    ));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{addr}"), uploads)
+}
+
+async fn close_month_with_webdav(
+    server: &axum_test::TestServer,
+    pool: &sqlx::SqlitePool,
+    user_id: i64,
+    token: &str,
+    webdav_url: &str,
+) -> (i64, serde_json::Value) {
+    server
+        .put("/api/settings")
+        .add_header(auth_name(), auth_value(token))
+        .json(&json!({ "webdav_url": webdav_url }))
+        .await
+        .assert_status_ok();
+    let month_id = create_test_month(pool, user_id, 2024, 6).await;
+    server
+        .post(&format!("/api/months/{}/close", month_id))
+        .add_header(auth_name(), auth_value(token))
+        .await
+        .assert_status_ok();
+    payme::jobs::run_pending(pool).await.unwrap();
+
+    let summary = server
+        .get(&format!("/api/months/{}", month_id))
+        .add_header(auth_name(), auth_value(token))
+        .await
+        .json();
+    (month_id, summary)
+}
+
+#[tokio::test]
+async fn test_closed_month_pdf_is_pushed_to_webdav() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let (base, uploads) = webdav_server().await;
+    let url = base.replace("http://", "http://alice:secret@") + "/dav/payme";
+
+    let (month_id, summary) = close_month_with_webdav(&server, &pool, user_id, &token, &url).await;
+
+    assert_eq!(summary["pdf_sync_status"], "synced");
+    let uploads = uploads.lock().unwrap().clone();
+    assert_eq!(uploads.len(), 1);
+    let (path, auth, size) = &uploads[0];
+    assert_eq!(path, "/dav/payme/month-2024-06.pdf");
+    assert_eq!(auth, "Basic YWxpY2U6c2VjcmV0");
+    assert_eq!(*size, stored_snapshot(&pool, month_id).await.len());
+}
+
+#[tokio::test]
+async fn test_webdav_push_marked_failed_after_retries() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let (month_id, summary) =
+        close_month_with_webdav(&server, &pool, user_id, &token, "http://127.0.0.1:1/dav").await;
+
+    assert_eq!(summary["pdf_sync_status"], "failed");
+    let (attempts, error): (i64, Option<String>) = sqlx::query_as(
+        "SELECT j.attempts, s.sync_error FROM jobs j, monthly_snapshots s WHERE j.kind = 'webdav_push' AND s.month_id = ?",
+    )
+    .bind(month_id)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(attempts, 3);
+    assert!(error.is_some());
+
+    // The PDF itself is unaffected
+    server
+        .get(&format!("/api/months/{}/pdf", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_month_without_webdav_is_not_pushed() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let (_month_id, summary) = close_month_with_webdav(&server, &pool, user_id, &token, "").await;
+
+    assert!(summary["pdf_sync_status"].is_null());
+    let pushes: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM jobs WHERE kind = 'webdav_push'")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(pushes, 0);
+}
+
+#[tokio::test]
+async fn test_webdav_url_must_be_http() {
+    let (server, _pool, _user_id, token) = setup_with_user().await;
+
+    server
+        .put("/api/settings")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "webdav_url": "ftp://example.com/payme" }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+}
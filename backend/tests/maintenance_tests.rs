@@ -0,0 +1,504 @@
+mod common;
+
+use axum::http::{HeaderName, HeaderValue};
+use common::{
+    auth_name, auth_value, create_test_budget, create_test_category, create_test_item,
+    create_test_month, create_test_pool, create_test_server, create_test_user, generate_token,
+};
+use payme::create_app;
+use serde_json::json;
+
+const ADMIN_TOKEN: &str = "test-admin-token";
+
+async fn setup() -> (axum_test::TestServer, String) {
+    std::env::set_var("ADMIN_TOKEN", ADMIN_TOKEN);
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    (create_test_server(create_app(pool)), token)
+}
+
+fn admin_name() -> HeaderName {
+    HeaderName::from_static("x-admin-token")
+}
+
+fn admin_value(token: &str) -> HeaderValue {
+    HeaderValue::from_str(token).unwrap()
+}
+
+async fn set_read_only(server: &axum_test::TestServer, read_only: bool) {
+    server
+        .put("/api/admin/maintenance")
+        .add_header(admin_name(), admin_value(ADMIN_TOKEN))
+        .json(&json!({ "read_only": read_only }))
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_admin_requires_token() {
+    let (server, _) = setup().await;
+
+    server
+        .get("/api/admin/maintenance")
+        .expect_failure()
+        .await
+        .assert_status_unauthorized();
+
+    server
+        .put("/api/admin/maintenance")
+        .add_header(admin_name(), admin_value("wrong"))
+        .json(&json!({ "read_only": true }))
+        .expect_failure()
+        .await
+        .assert_status_unauthorized();
+
+    let response = server
+        .get("/api/admin/maintenance")
+        .add_header(admin_name(), admin_value(ADMIN_TOKEN))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<serde_json::Value>()["read_only"], false);
+}
+
+#[tokio::test]
+async fn test_read_only_blocks_mutations() {
+    let (server, token) = setup().await;
+    set_read_only(&server, true).await;
+
+    let response = server
+        .post("/api/categories")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "label": "Books", "default_amount": 50.0 }))
+        .expect_failure()
+        .await;
+    response.assert_status(axum::http::StatusCode::SERVICE_UNAVAILABLE);
+    let body: serde_json::Value = response.json();
+    assert!(body["error"].as_str().unwrap().contains("read-only"));
+
+    server
+        .get("/api/categories")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_ok();
+
+    server
+        .post("/api/auth/login")
+        .json(&json!({ "username": "testuser", "password": "password123" }))
+        .await
+        .assert_status_ok();
+
+    server
+        .post("/api/v1/auth/login")
+        .json(&json!({ "username": "testuser", "password": "password123" }))
+        .await
+        .assert_status_ok();
+
+    // Assistant tools only read, even though they are called with a POST
+    server
+        .post("/api/v1/tools/call")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "name": "search_items", "arguments": {} }))
+        .await
+        .assert_status_ok();
+}
+
+async fn count(pool: &sqlx::SqlitePool, table: &str) -> i64 {
+    sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table}"))
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_read_only_current_month_is_not_created() {
+    std::env::set_var("ADMIN_TOKEN", ADMIN_TOKEN);
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+    set_read_only(&server, true).await;
+
+    for path in ["/api/v1/months/current", "/api/v1/dashboard"] {
+        server
+            .get(path)
+            .add_header(auth_name(), auth_value(&token))
+            .expect_failure()
+            .await
+            .assert_status(axum::http::StatusCode::SERVICE_UNAVAILABLE);
+    }
+    assert_eq!(count(&pool, "months").await, 0);
+
+    set_read_only(&server, false).await;
+    server
+        .get("/api/v1/months/current")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_ok();
+    set_read_only(&server, true).await;
+    server
+        .get("/api/v1/months/current")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_ok();
+    assert_eq!(count(&pool, "months").await, 1);
+}
+
+#[tokio::test]
+async fn test_read_only_dashboard_does_not_compute_insights() {
+    std::env::set_var("ADMIN_TOKEN", ADMIN_TOKEN);
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+    server
+        .get("/api/v1/months/current")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_ok();
+    set_read_only(&server, true).await;
+
+    let response = server
+        .get("/api/v1/dashboard")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<serde_json::Value>()["alerts"], json!([]));
+    assert_eq!(count(&pool, "insight_scores").await, 0);
+}
+
+#[tokio::test]
+async fn test_read_only_insights_are_not_refreshed() {
+    std::env::set_var("ADMIN_TOKEN", ADMIN_TOKEN);
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+    set_read_only(&server, true).await;
+
+    server
+        .get("/api/v1/insights")
+        .add_header(auth_name(), auth_value(&token))
+        .expect_failure()
+        .await
+        .assert_status(axum::http::StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(count(&pool, "insight_scores").await, 0);
+
+    set_read_only(&server, false).await;
+    server
+        .get("/api/v1/insights")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_ok();
+    sqlx::query("UPDATE insight_scores SET health_score = 42")
+        .execute(&pool)
+        .await
+        .unwrap();
+    set_read_only(&server, true).await;
+
+    let response = server
+        .get("/api/v1/insights?refresh=true")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<serde_json::Value>()["health_score"], 42);
+}
+
+#[tokio::test]
+async fn test_read_only_projection_does_not_save_assumptions() {
+    std::env::set_var("ADMIN_TOKEN", ADMIN_TOKEN);
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+    set_read_only(&server, true).await;
+
+    let response = server
+        .get("/api/v1/retirement/projection?current_age=30&target_age=65")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<serde_json::Value>()["target_age"], 65);
+
+    let settings: serde_json::Value = server
+        .get("/api/v1/settings")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert!(settings["retirement_current_age"].is_null());
+    assert_eq!(count(&pool, "user_settings").await, 0);
+}
+
+#[tokio::test]
+async fn test_read_only_rejects_connector_callback() {
+    let (server, _) = setup().await;
+    set_read_only(&server, true).await;
+
+    server
+        .get("/api/v1/connectors/dropbox/callback?code=abc&state=xyz")
+        .expect_failure()
+        .await
+        .assert_status(axum::http::StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn test_disabling_read_only_restores_writes() {
+    let (server, token) = setup().await;
+    set_read_only(&server, true).await;
+    set_read_only(&server, false).await;
+
+    server
+        .post("/api/categories")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "label": "Books", "default_amount": 50.0 }))
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_integrity_check_and_repair() {
+    std::env::set_var("ADMIN_TOKEN", ADMIN_TOKEN);
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let server = create_test_server(create_app(pool.clone()));
+
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let food = create_test_category(&pool, user_id, "Food", 300.0).await;
+    let rent = create_test_category(&pool, user_id, "Rent", 1000.0).await;
+    create_test_budget(&pool, month_id, food, 300.0).await;
+    create_test_budget(&pool, month_id, rent, 1000.0).await;
+    create_test_item(&pool, month_id, food, "Groceries", 40.0, "2024-06-03").await;
+
+    // Deleting with foreign keys off skips the cascade, as older databases did
+    let mut conn = pool.acquire().await.unwrap();
+    sqlx::query("PRAGMA foreign_keys = OFF")
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM budget_categories WHERE id = ?")
+        .bind(food)
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+    sqlx::query("PRAGMA foreign_keys = ON")
+        .execute(&mut *conn)
+        .await
+        .unwrap();
+    drop(conn);
+
+    server
+        .get("/api/v1/admin/integrity")
+        .expect_failure()
+        .await
+        .assert_status_unauthorized();
+
+    let response = server
+        .get("/api/v1/admin/integrity")
+        .add_header(admin_name(), admin_value(ADMIN_TOKEN))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(
+        response.json::<serde_json::Value>()["orphans"],
+        json!({ "items": 1, "monthly_budgets": 1 })
+    );
+
+    set_read_only(&server, true).await;
+    let response = server
+        .post("/api/v1/admin/integrity/repair")
+        .add_header(admin_name(), admin_value(ADMIN_TOKEN))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(
+        response.json::<serde_json::Value>()["removed"],
+        json!({ "items": 1, "monthly_budgets": 1 })
+    );
+
+    let response = server
+        .get("/api/v1/admin/integrity")
+        .add_header(admin_name(), admin_value(ADMIN_TOKEN))
+        .await;
+    assert_eq!(response.json::<serde_json::Value>()["orphans"], json!({}));
+
+    let budgets: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM monthly_budgets")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(budgets, 1);
+}
+
+#[tokio::test]
+async fn test_migration_status() {
+    std::env::set_var("ADMIN_TOKEN", ADMIN_TOKEN);
+    let pool = payme::db::create_pool("sqlite::memory:").await.unwrap();
+    payme::db::run_migrations(&pool).await.unwrap();
+    // Running again is a no-op and the lock was released
+    payme::db::run_migrations(&pool).await.unwrap();
+    let server = create_test_server(create_app(pool));
+
+    server
+        .get("/api/admin/migrations")
+        .expect_failure()
+        .await
+        .assert_status_unauthorized();
+
+    let response = server
+        .get("/api/admin/migrations")
+        .add_header(admin_name(), admin_value(ADMIN_TOKEN))
+        .await;
+    response.assert_status_ok();
+    let status: serde_json::Value = response.json();
+    assert_eq!(status["applied"][0]["version"], 1);
+    assert_eq!(status["applied"][0]["description"], "baseline");
+    assert_eq!(status["pending"], json!([]));
+}
+
+#[tokio::test]
+async fn test_upgrade_from_unversioned_schema() {
+    let pool = payme::db::create_pool("sqlite::memory:").await.unwrap();
+    // The schema databases had before migrations were versioned
+    sqlx::raw_sql(
+        r#"
+        CREATE TABLE users (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            username TEXT NOT NULL UNIQUE,
+            password_hash TEXT NOT NULL,
+            savings REAL NOT NULL DEFAULT 0,
+            savings_goal REAL NOT NULL DEFAULT 0,
+            created_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        CREATE TABLE fixed_expenses (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id INTEGER NOT NULL,
+            label TEXT NOT NULL,
+            amount REAL NOT NULL,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+        );
+        CREATE TABLE budget_categories (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id INTEGER NOT NULL,
+            label TEXT NOT NULL,
+            default_amount REAL NOT NULL,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+        );
+        CREATE TABLE months (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            user_id INTEGER NOT NULL,
+            year INTEGER NOT NULL,
+            month INTEGER NOT NULL,
+            is_closed INTEGER NOT NULL DEFAULT 0,
+            closed_at TEXT,
+            FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+            UNIQUE(user_id, year, month)
+        );
+        CREATE TABLE income_entries (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            month_id INTEGER NOT NULL,
+            label TEXT NOT NULL,
+            amount REAL NOT NULL,
+            FOREIGN KEY (month_id) REFERENCES months(id) ON DELETE CASCADE
+        );
+        CREATE TABLE monthly_budgets (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            month_id INTEGER NOT NULL,
+            category_id INTEGER NOT NULL,
+            allocated_amount REAL NOT NULL,
+            FOREIGN KEY (month_id) REFERENCES months(id) ON DELETE CASCADE,
+            FOREIGN KEY (category_id) REFERENCES budget_categories(id) ON DELETE CASCADE,
+            UNIQUE(month_id, category_id)
+        );
+        CREATE TABLE items (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            month_id INTEGER NOT NULL,
+            category_id INTEGER NOT NULL,
+            description TEXT NOT NULL,
+            amount REAL NOT NULL,
+            spent_on TEXT NOT NULL,
+            savings_destination TEXT NOT NULL DEFAULT 'none',
+            FOREIGN KEY (month_id) REFERENCES months(id) ON DELETE CASCADE,
+            FOREIGN KEY (category_id) REFERENCES budget_categories(id) ON DELETE CASCADE
+        );
+        CREATE TABLE monthly_snapshots (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            month_id INTEGER NOT NULL UNIQUE,
+            pdf_data BLOB NOT NULL,
+            created_at TEXT NOT NULL DEFAULT (datetime('now')),
+            FOREIGN KEY (month_id) REFERENCES months(id) ON DELETE CASCADE
+        );
+        INSERT INTO users (username, password_hash) VALUES ('legacy', 'x');
+        INSERT INTO fixed_expenses (user_id, label, amount) VALUES (1, 'Rent', 1200);
+        INSERT INTO budget_categories (user_id, label, default_amount) VALUES (1, 'Food', 300);
+        INSERT INTO months (user_id, year, month, is_closed, closed_at)
+            VALUES (1, 2024, 5, 1, '2024-06-01');
+        INSERT INTO items (month_id, category_id, description, amount, spent_on)
+            VALUES (1, 1, 'Groceries', 42.5, '2024-05-03');
+        "#,
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    payme::db::run_migrations(&pool).await.unwrap();
+
+    // Fixed expense history is seeded from the billing period the upgrade adds
+    let history: (f64, String, String) =
+        sqlx::query_as("SELECT amount, billing_period, effective_from FROM fixed_expense_history")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(
+        history,
+        (1200.0, "monthly".to_string(), "2024-05-01".to_string())
+    );
+    sqlx::query("UPDATE fixed_expenses SET amount = 1250")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let changes: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM fixed_expense_history")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(changes, 2);
+
+    let frozen: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM closed_month_items")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(frozen, 1);
+    let pending = payme::db::MIGRATOR.iter().filter(|m| m.version > 0).count();
+    let applied = payme::db::applied_migrations(&pool).await.unwrap();
+    assert_eq!(applied.len(), pending);
+}
+
+#[tokio::test]
+async fn test_log_level() {
+    let (server, _) = setup().await;
+
+    server
+        .put("/api/v1/admin/log-level")
+        .json(&json!({ "level": "debug" }))
+        .expect_failure()
+        .await
+        .assert_status_unauthorized();
+
+    server
+        .put("/api/v1/admin/log-level")
+        .add_header(admin_name(), admin_value(ADMIN_TOKEN))
+        .json(&json!({ "level": "loud" }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+
+    let response = server
+        .put("/api/v1/admin/log-level")
+        .add_header(admin_name(), admin_value(ADMIN_TOKEN))
+        .json(&json!({ "level": "DEBUG" }))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.json::<serde_json::Value>()["level"], "debug");
+
+    let response = server
+        .get("/api/v1/admin/log-level")
+        .add_header(admin_name(), admin_value(ADMIN_TOKEN))
+        .await;
+    assert_eq!(response.json::<serde_json::Value>()["level"], "debug");
+}
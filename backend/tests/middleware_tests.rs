@@ -142,3 +142,34 @@ async fn test_login_no_auth() {
 
     response.assert_status_ok();
 }
+
+#[tokio::test]
+async fn test_unknown_api_path_is_not_served_the_frontend() {
+    let server = setup().await;
+
+    for path in ["/api/v1/nope", "/api/nope", "/api"] {
+        let response = server.get(path).expect_failure().await;
+        response.assert_status_not_found();
+        assert!(!response.text().contains("<html"));
+    }
+}
+
+#[tokio::test]
+async fn test_security_headers() {
+    let server = setup().await;
+
+    let response = server.get("/health").await;
+    assert_eq!(response.header("x-content-type-options"), "nosniff");
+    assert_eq!(response.header("referrer-policy"), "no-referrer");
+    assert_eq!(
+        response.header("strict-transport-security"),
+        "max-age=31536000; includeSubDomains"
+    );
+    assert_eq!(
+        response.header("content-security-policy"),
+        payme::middleware::security::DEFAULT_CONTENT_SECURITY_POLICY
+    );
+
+    let response = server.get("/api/v1/nope").expect_failure().await;
+    assert_eq!(response.header("x-content-type-options"), "nosniff");
+}
@@ -1,7 +1,8 @@
 mod common;
 
 use common::{
-    auth_name, auth_value, close_test_month, create_test_category, create_test_month,
+    auth_name, auth_value, close_test_month, create_test_budget, create_test_category,
+    create_test_fixed_expense, create_test_income, create_test_item, create_test_month,
     create_test_pool, create_test_server, create_test_user, generate_token,
 };
 use payme::create_app;
@@ -29,6 +30,34 @@ async fn test_list_months_empty() {
     assert!(body.is_empty());
 }
 
+#[tokio::test]
+async fn test_list_months_filtered_by_date() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    create_test_month(&pool, user_id, 2023, 12).await;
+    create_test_month(&pool, user_id, 2024, 2).await;
+    create_test_month(&pool, user_id, 2024, 5).await;
+
+    let response = server
+        .get("/api/months?from=2024-01-01&to=2024-04-30")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+
+    response.assert_status_ok();
+    let body: Vec<serde_json::Value> = response.json();
+    assert_eq!(body.len(), 1);
+    assert_eq!(body[0]["month"], 2);
+
+    let response = server
+        .get("/api/months?min_amount=10")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+
+    response.assert_status_bad_request();
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["fields"], serde_json::json!(["min_amount"]));
+}
+
 #[tokio::test]
 async fn test_list_months_ordered() {
     let (server, pool, user_id, token) = setup_with_user().await;
@@ -115,6 +144,59 @@ async fn test_get_month_success() {
     assert_eq!(body["month"]["month"], 6);
 }
 
+#[tokio::test]
+async fn test_get_month_no_spend_days() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let cat_id = create_test_category(&pool, user_id, "Food", 500.0).await;
+    create_test_item(&pool, month_id, cat_id, "Lunch", 12.0, "2024-06-15").await;
+    create_test_item(&pool, month_id, cat_id, "Dinner", 30.0, "2024-06-15").await;
+    create_test_item(&pool, month_id, cat_id, "Snack", 3.0, "2024-06-20").await;
+
+    let body: serde_json::Value = server
+        .get(&format!("/api/months/{}", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(body["no_spend_days"], 28);
+}
+
+#[tokio::test]
+async fn test_get_month_metrics() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let cat_id = create_test_category(&pool, user_id, "Food", 500.0).await;
+    create_test_income(&pool, month_id, "Salary", 4000.0).await;
+    create_test_fixed_expense(&pool, user_id, "Rent", 1000.0).await;
+    create_test_item(&pool, month_id, cat_id, "Groceries", 300.0, "2024-06-10").await;
+
+    let body: serde_json::Value = server
+        .get(&format!("/api/months/{}", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(body["metrics"]["savings_rate"], 0.675);
+    assert_eq!(body["metrics"]["fixed_cost_ratio"], 0.25);
+    assert_eq!(body["metrics"]["discretionary_per_day"], 10.0);
+}
+
+#[tokio::test]
+async fn test_get_month_metrics_without_income() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+
+    let body: serde_json::Value = server
+        .get(&format!("/api/months/{}", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert!(body["metrics"]["savings_rate"].is_null());
+    assert_eq!(body["metrics"]["discretionary_per_day"], 0.0);
+}
+
 #[tokio::test]
 async fn test_get_month_not_found() {
     let (server, _pool, _user_id, token) = setup_with_user().await;
@@ -190,6 +272,7 @@ async fn test_get_month_pdf_success() {
         .post(&format!("/api/months/{}/close", month_id))
         .add_header(auth_name(), auth_value(&token))
         .await;
+    payme::jobs::run_pending(&pool).await.unwrap();
 
     let response = server
         .get(&format!("/api/months/{}/pdf", month_id))
@@ -200,6 +283,10 @@ async fn test_get_month_pdf_success() {
 
     let content_type = response.headers().get("content-type").unwrap();
     assert_eq!(content_type, "application/pdf");
+    assert_eq!(
+        response.header("content-security-policy"),
+        payme::middleware::security::PDF_CONTENT_SECURITY_POLICY
+    );
 }
 
 #[tokio::test]
@@ -215,3 +302,473 @@ async fn test_get_month_pdf_not_closed() {
 
     response.assert_status_not_found();
 }
+
+#[tokio::test]
+async fn test_month_activity_feed() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let cat_id = create_test_category(&pool, user_id, "Food", 500.0).await;
+
+    server
+        .post(&format!("/api/months/{}/items", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&serde_json::json!({
+            "category_id": cat_id,
+            "description": "Groceries",
+            "amount": 42.1,
+            "spent_on": "2024-06-15"
+        }))
+        .await
+        .assert_status_ok();
+
+    server
+        .post(&format!("/api/months/{}/income", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&serde_json::json!({ "label": "Salary", "amount": 3000.0 }))
+        .await
+        .assert_status_ok();
+
+    let response = server
+        .get(&format!("/api/months/{}/activity", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+
+    response.assert_status_ok();
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["total"], 2);
+    let entries = body["entries"].as_array().unwrap();
+    assert_eq!(entries[0]["entity_type"], "income");
+    assert_eq!(entries[1]["summary"], "testuser added Groceries $42.10");
+
+    let response = server
+        .get(&format!(
+            "/api/months/{}/activity?entity_type=item&per_page=1",
+            month_id
+        ))
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["total"], 1);
+    assert_eq!(body["entries"].as_array().unwrap().len(), 1);
+    assert_eq!(body["entries"][0]["action"], "create");
+}
+
+#[tokio::test]
+async fn test_activity_uses_saved_currency() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let cat_id = create_test_category(&pool, user_id, "Food", 500.0).await;
+
+    server
+        .put("/api/settings")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&serde_json::json!({ "locale": "de-DE", "currency": "EUR" }))
+        .await
+        .assert_status_ok();
+    server
+        .post(&format!("/api/months/{}/items", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&serde_json::json!({
+            "category_id": cat_id,
+            "description": "Groceries",
+            "amount": 1042.1,
+            "spent_on": "2024-06-15"
+        }))
+        .await
+        .assert_status_ok();
+
+    let body: serde_json::Value = server
+        .get(&format!("/api/months/{}/activity", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(
+        body["entries"][0]["summary"],
+        "testuser added Groceries 1.042,10\u{a0}€"
+    );
+}
+
+#[tokio::test]
+async fn test_month_activity_not_found() {
+    let (server, _pool, _user_id, token) = setup_with_user().await;
+
+    let response = server
+        .get("/api/months/99999/activity")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+
+    response.assert_status_not_found();
+}
+
+#[tokio::test]
+async fn test_closed_month_survives_deleted_category_and_fixed_expense() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let category_id = create_test_category(&pool, user_id, "Food", 300.0).await;
+    create_test_budget(&pool, month_id, category_id, 300.0).await;
+    create_test_item(
+        &pool,
+        month_id,
+        category_id,
+        "Groceries",
+        120.0,
+        "2024-06-10",
+    )
+    .await;
+    let rent_id = create_test_fixed_expense(&pool, user_id, "Rent", 1000.0).await;
+
+    server
+        .post(&format!("/api/months/{}/close", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_ok();
+
+    server
+        .delete(&format!("/api/categories/{}", category_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status(axum::http::StatusCode::NO_CONTENT);
+    server
+        .delete(&format!("/api/fixed-expenses/{}", rent_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status(axum::http::StatusCode::NO_CONTENT);
+
+    let body: serde_json::Value = server
+        .get(&format!("/api/months/{}", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+
+    assert_eq!(body["fixed_expenses"][0]["label"], "Rent");
+    assert_eq!(body["budgets"][0]["category_label"], "Food");
+    assert_eq!(body["budgets"][0]["spent_amount"], 120.0);
+    assert_eq!(body["items"][0]["description"], "Groceries");
+    assert_eq!(body["total_fixed"], 1000.0);
+    assert_eq!(body["total_spent"], 120.0);
+}
+
+#[tokio::test]
+async fn test_verify_month_pdf() {
+    use sha2::{Digest, Sha256};
+
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+
+    server
+        .get(&format!("/api/months/{}/pdf/verify", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .expect_failure()
+        .await
+        .assert_status_not_found();
+
+    server
+        .post(&format!("/api/months/{}/close", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_ok();
+    payme::jobs::run_pending(&pool).await.unwrap();
+
+    let summary: serde_json::Value = server
+        .get(&format!("/api/months/{}", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    let pdf = server
+        .get(&format!("/api/months/{}/pdf", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .into_bytes();
+    let hash: String = Sha256::digest(&pdf)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect();
+    assert_eq!(summary["pdf_sha256"], hash);
+
+    let response = server
+        .get(&format!("/api/months/{}/pdf/verify", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["valid"], true);
+    assert_eq!(body["sha256"], hash);
+    assert_eq!(body["computed_sha256"], hash);
+
+    sqlx::query("UPDATE stored_objects SET data = ?")
+        .bind(b"%PDF-tampered".to_vec())
+        .execute(&pool)
+        .await
+        .unwrap();
+    let body: serde_json::Value = server
+        .get(&format!("/api/months/{}/pdf/verify", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(body["valid"], false);
+    assert_eq!(body["sha256"], hash);
+    assert_ne!(body["computed_sha256"], hash);
+}
+
+#[tokio::test]
+async fn test_month_pace() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let food = create_test_category(&pool, user_id, "Food", 300.0).await;
+    create_test_budget(&pool, month_id, food, 300.0).await;
+    create_test_item(&pool, month_id, food, "Groceries", 100.0, "2024-06-03").await;
+    create_test_item(&pool, month_id, food, "Bakery", 20.0, "2024-06-03").await;
+    create_test_item(&pool, month_id, food, "Market", 30.0, "2024-06-10").await;
+
+    let response = server
+        .get(&format!("/api/months/{}/pace", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+
+    response.assert_status_ok();
+    let body: serde_json::Value = response.json();
+    // A past month is over, so the whole budget was expected to be spent
+    assert_eq!(body["days_in_month"], 30);
+    assert_eq!(body["days_elapsed"], 30);
+    let food = &body["categories"][0];
+    assert_eq!(food["spent_amount"], 150.0);
+    assert_eq!(food["expected_amount"], 300.0);
+    assert_eq!(food["pace"], 0.5);
+    assert_eq!(food["daily_rate"], 5.0);
+    assert_eq!(food["days_left"], 30.0);
+    assert_eq!(food["daily"].as_array().unwrap().len(), 2);
+    assert_eq!(food["daily"][0]["amount"], 120.0);
+}
+
+#[tokio::test]
+async fn test_month_pace_of_another_user() {
+    let (server, pool, _user_id, token) = setup_with_user().await;
+    let other_id = create_test_user(&pool, "other", "password123").await;
+    let month_id = create_test_month(&pool, other_id, 2024, 6).await;
+
+    server
+        .get(&format!("/api/months/{}/pace", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .expect_failure()
+        .await
+        .assert_status_not_found();
+}
+
+#[tokio::test]
+async fn test_close_checklist() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+
+    let checklist: serde_json::Value = server
+        .put("/api/v1/close-checklist")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&serde_json::json!({
+            "entries": ["Paid the credit card", " Updated savings balance "]
+        }))
+        .await
+        .json();
+    assert_eq!(checklist[1]["label"], "Updated savings balance");
+    let paid_id = checklist[0]["id"].as_i64().unwrap();
+    let savings_id = checklist[1]["id"].as_i64().unwrap();
+
+    server
+        .post(&format!("/api/months/{}/close", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&serde_json::json!({ "acknowledged": [paid_id] }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+
+    server
+        .post(&format!("/api/months/{}/close", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+
+    server
+        .post(&format!("/api/months/{}/close", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&serde_json::json!({ "acknowledged": [paid_id, savings_id] }))
+        .await
+        .assert_status_ok();
+
+    // Later edits don't change what the closed month recorded
+    server
+        .put("/api/v1/close-checklist")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&serde_json::json!({ "entries": [] }))
+        .await
+        .assert_status_ok();
+
+    let summary: serde_json::Value = server
+        .get(&format!("/api/months/{}", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(
+        summary["close_checklist"],
+        serde_json::json!([
+            { "label": "Paid the credit card", "acknowledged": true },
+            { "label": "Updated savings balance", "acknowledged": true }
+        ])
+    );
+}
+
+#[tokio::test]
+async fn test_close_checklist_force() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    server
+        .put("/api/v1/close-checklist")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&serde_json::json!({ "entries": ["Paid the credit card"] }))
+        .await
+        .assert_status_ok();
+
+    server
+        .post(&format!("/api/months/{}/close", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&serde_json::json!({ "force": true }))
+        .await
+        .assert_status_ok();
+    payme::jobs::run_pending(&pool).await.unwrap();
+
+    let summary: serde_json::Value = server
+        .get(&format!("/api/months/{}", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(summary["close_checklist"][0]["acknowledged"], false);
+    server
+        .get(&format!("/api/months/{}/pdf", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_close_checklist_validation() {
+    let (server, _pool, _user_id, token) = setup_with_user().await;
+
+    for entries in [
+        serde_json::json!([" "]),
+        serde_json::json!(["x".repeat(101)]),
+        serde_json::json!(vec!["Step"; 21]),
+    ] {
+        server
+            .put("/api/v1/close-checklist")
+            .add_header(auth_name(), auth_value(&token))
+            .json(&serde_json::json!({ "entries": entries }))
+            .expect_failure()
+            .await
+            .assert_status_bad_request();
+    }
+
+    let checklist: serde_json::Value = server
+        .get("/api/v1/close-checklist")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(checklist, serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn test_close_month_with_empty_json_body() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+
+    server
+        .post(&format!("/api/months/{}/close", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .content_type("application/json")
+        .await
+        .assert_status_ok();
+}
+
+#[tokio::test]
+async fn test_close_month_records_digest() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let food = create_test_category(&pool, user_id, "Food", 300.0).await;
+    let fun = create_test_category(&pool, user_id, "Fun", 100.0).await;
+    let may = create_test_month(&pool, user_id, 2024, 5).await;
+    let june = create_test_month(&pool, user_id, 2024, 6).await;
+    let set_wealth = |savings: f64, retirement: f64| {
+        sqlx::query("UPDATE users SET savings = ?, retirement_savings = ? WHERE id = ?")
+            .bind(savings)
+            .bind(retirement)
+            .bind(user_id)
+            .execute(&pool)
+    };
+
+    set_wealth(1000.0, 5000.0).await.unwrap();
+    let response = server
+        .post(&format!("/api/v1/months/{may}/close"))
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    let body: serde_json::Value = response.json();
+    assert!(body["digest"]["savings_change"].is_null());
+    assert!(body["digest"]["top_overspent_category"].is_null());
+
+    create_test_budget(&pool, june, food, 300.0).await;
+    create_test_budget(&pool, june, fun, 100.0).await;
+    create_test_item(&pool, june, food, "Groceries", 320.0, "2024-06-03").await;
+    create_test_item(&pool, june, fun, "Concert", 180.0, "2024-06-20").await;
+    set_wealth(1250.5, 4900.0).await.unwrap();
+
+    let response = server
+        .post(&format!("/api/v1/months/{june}/close"))
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    let digest = &response.json::<serde_json::Value>()["digest"];
+    assert_eq!(digest["savings_change"], 250.5);
+    assert_eq!(digest["net_worth_change"], 150.5);
+    assert_eq!(digest["top_overspent_category"], "Fun");
+    assert_eq!(digest["top_overspent_by"], 80.0);
+    assert_eq!(digest["biggest_purchase"], "Groceries");
+    assert_eq!(digest["biggest_purchase_amount"], 320.0);
+
+    let response = server
+        .get(&format!("/api/v1/months/{june}/digest"))
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(&response.json::<serde_json::Value>(), digest);
+
+    let summary: serde_json::Value = server
+        .get(&format!("/api/v1/months/{june}"))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(&summary["digest"], digest);
+}
+
+#[tokio::test]
+async fn test_month_digest_not_found() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+
+    let response = server
+        .get(&format!("/api/v1/months/{month_id}/digest"))
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_not_found();
+
+    server
+        .post(&format!("/api/v1/months/{month_id}/close"))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_ok();
+    let other = create_test_user(&pool, "other", "password123").await;
+    let response = server
+        .get(&format!("/api/v1/months/{month_id}/digest"))
+        .add_header(auth_name(), auth_value(&generate_token(other, "other")))
+        .await;
+    response.assert_status_not_found();
+}
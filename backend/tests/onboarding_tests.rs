@@ -0,0 +1,86 @@
+mod common;
+
+use common::{
+    auth_name, auth_value, create_test_category, create_test_month, create_test_pool,
+    create_test_server, create_test_user, generate_token,
+};
+use payme::create_app;
+use serde_json::json;
+
+async fn setup_with_user() -> (axum_test::TestServer, sqlx::SqlitePool, i64, String) {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let app = create_app(pool.clone());
+    let server = create_test_server(app);
+    (server, pool, user_id, token)
+}
+
+#[tokio::test]
+async fn test_onboarding_creates_first_month() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    create_test_category(&pool, user_id, "Starter", 10.0).await;
+
+    let response = server
+        .post("/api/onboarding")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "categories": [
+                { "label": "Food", "default_amount": 500.0 },
+                { "label": "Fun", "default_amount": 100.0 }
+            ],
+            "fixed_expenses": [{ "label": "Rent", "amount": 1500.0 }],
+            "income": [{ "label": "Salary", "amount": 4000.0 }],
+            "savings": 2500.0
+        }))
+        .await;
+
+    response.assert_status_ok();
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["total_income"], 4000.0);
+    assert_eq!(body["total_fixed"], 1500.0);
+    assert_eq!(body["budgets"].as_array().unwrap().len(), 2);
+    assert_eq!(body["remaining"], 2500.0);
+
+    let savings: serde_json::Value = server
+        .get("/api/savings")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(savings["savings"], 2500.0);
+}
+
+#[tokio::test]
+async fn test_onboarding_rejected_after_setup() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    create_test_month(&pool, user_id, 2024, 6).await;
+
+    server
+        .post("/api/onboarding")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "savings": 100.0 }))
+        .await
+        .assert_status_bad_request();
+}
+
+#[tokio::test]
+async fn test_onboarding_validation_is_atomic() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    server
+        .post("/api/onboarding")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "categories": [{ "label": "Food", "default_amount": 500.0 }],
+            "income": [{ "label": "", "amount": 4000.0 }]
+        }))
+        .await
+        .assert_status_bad_request();
+
+    let months: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM months WHERE user_id = ?")
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(months, 0);
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use common::{
+    auth_name, auth_value, create_test_budget, create_test_category, create_test_income,
+    create_test_item, create_test_month, create_test_pool, create_test_server, create_test_user,
+    generate_token,
+};
+use payme::create_app;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_weekly_period_across_months() {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+
+    let food = create_test_category(&pool, user_id, "Food", 0.0).await;
+    let may = create_test_month(&pool, user_id, 2024, 5).await;
+    let june = create_test_month(&pool, user_id, 2024, 6).await;
+    create_test_budget(&pool, may, food, 310.0).await;
+    create_test_budget(&pool, june, food, 300.0).await;
+    create_test_income(&pool, may, "Salary", 3100.0).await;
+    create_test_income(&pool, june, "Salary", 3000.0).await;
+    create_test_item(&pool, may, food, "Market", 20.0, "2024-05-28").await;
+    create_test_item(&pool, june, food, "Bakery", 5.0, "2024-06-02").await;
+    create_test_item(&pool, june, food, "Groceries", 50.0, "2024-06-03").await;
+
+    let response = server
+        .get("/api/v1/periods/settings")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(
+        response.json::<serde_json::Value>(),
+        json!({ "period_type": "monthly", "starts_on": null })
+    );
+
+    server
+        .put("/api/v1/periods/settings")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "period_type": "fortnightly" }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+    server
+        .put("/api/v1/periods/settings")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "period_type": "weekly", "starts_on": "2024-01-01" }))
+        .await
+        .assert_status_ok();
+
+    let response = server
+        .get("/api/v1/periods/2024-05-30")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    let period: serde_json::Value = response.json();
+    assert_eq!(period["period_type"], "weekly");
+    assert_eq!(period["start"], "2024-05-27");
+    assert_eq!(period["end"], "2024-06-02");
+    assert_eq!(period["month_ids"], json!([may, june]));
+    // Five days of May and two of June
+    let income = period["total_income"].as_f64().unwrap();
+    assert!((income - (500.0 + 200.0)).abs() < 1e-9, "{income}");
+    let category = &period["categories"][0];
+    assert_eq!(category["category_label"], "Food");
+    let allocated = category["allocated_amount"].as_f64().unwrap();
+    assert!((allocated - (50.0 + 20.0)).abs() < 1e-9, "{allocated}");
+    assert_eq!(category["spent_amount"], 25.0);
+    assert_eq!(period["total_spent"], 25.0);
+
+    server
+        .get("/api/v1/periods/2023-01-04")
+        .add_header(auth_name(), auth_value(&token))
+        .expect_failure()
+        .await
+        .assert_status_not_found();
+}
+
+#[tokio::test]
+async fn test_current_period_creates_months() {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+
+    let response = server
+        .put("/api/v1/periods/settings")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "period_type": "biweekly" }))
+        .await;
+    response.assert_status_ok();
+    let starts_on = response.json::<serde_json::Value>()["starts_on"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let response = server
+        .get("/api/v1/periods/current")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    let period: serde_json::Value = response.json();
+    assert_eq!(period["start"], starts_on);
+    let months: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM months WHERE user_id = ?")
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(
+        months as usize,
+        period["month_ids"].as_array().unwrap().len()
+    );
+}
@@ -0,0 +1,131 @@
+mod common;
+
+use chrono::{Datelike, Utc};
+use common::{
+    auth_name, auth_value, create_test_category, create_test_pool, create_test_server,
+    create_test_user, generate_token,
+};
+use payme::create_app;
+use serde_json::json;
+
+async fn setup_with_user() -> (axum_test::TestServer, sqlx::SqlitePool, i64, String) {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+    (server, pool, user_id, token)
+}
+
+#[tokio::test]
+async fn test_set_and_get_plan() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let utilities = create_test_category(&pool, user_id, "Utilities", 100.0).await;
+    let gifts = create_test_category(&pool, user_id, "Gifts", 0.0).await;
+
+    let mut gift_amounts = vec![0.0; 12];
+    gift_amounts[11] = 400.0;
+    let response = server
+        .post("/api/v1/plans/2025")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "categories": [
+                { "category_id": utilities, "amounts": [150, 150, 120, 100, 80, 80, 80, 80, 80, 100, 120, 150] },
+                { "category_id": gifts, "amounts": gift_amounts }
+            ]
+        }))
+        .await;
+    response.assert_status_ok();
+
+    let plan: serde_json::Value = server
+        .get("/api/v1/plans/2025")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(plan["year"], 2025);
+    let categories = plan["categories"].as_array().unwrap();
+    assert_eq!(categories.len(), 2);
+    assert_eq!(categories[0]["category_label"], "Gifts");
+    assert_eq!(categories[0]["amounts"][11], 400.0);
+    assert_eq!(categories[1]["amounts"][0], 150.0);
+
+    // Posting again replaces the whole year
+    server
+        .post("/api/v1/plans/2025")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "categories": [{ "category_id": gifts, "amounts": vec![10.0; 12] }] }))
+        .await
+        .assert_status_ok();
+    let plan: serde_json::Value = server
+        .get("/api/v1/plans/2025")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(plan["categories"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_new_month_uses_plan() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let planned = create_test_category(&pool, user_id, "Heating", 100.0).await;
+    create_test_category(&pool, user_id, "Food", 300.0).await;
+
+    let now = Utc::now();
+    let mut amounts = vec![0.0; 12];
+    amounts[now.month0() as usize] = 175.0;
+    server
+        .post(&format!("/api/v1/plans/{}", now.year()))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "categories": [{ "category_id": planned, "amounts": amounts }] }))
+        .await
+        .assert_status_ok();
+
+    let summary: serde_json::Value = server
+        .get("/api/v1/months/current")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    let budgets = summary["budgets"].as_array().unwrap();
+    let allocated = |label: &str| {
+        budgets
+            .iter()
+            .find(|b| b["category_label"] == label)
+            .unwrap()["allocated_amount"]
+            .clone()
+    };
+    assert_eq!(allocated("Heating"), 175.0);
+    assert_eq!(allocated("Food"), 300.0);
+}
+
+#[tokio::test]
+async fn test_plan_validation() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let category = create_test_category(&pool, user_id, "Food", 300.0).await;
+
+    server
+        .post("/api/v1/plans/2025")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "categories": [{ "category_id": category, "amounts": vec![10.0; 11] }] }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+
+    let mut amounts = vec![10.0; 12];
+    amounts[3] = -1.0;
+    server
+        .post("/api/v1/plans/2025")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "categories": [{ "category_id": category, "amounts": amounts }] }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+
+    let other_id = create_test_user(&pool, "other", "password123").await;
+    let other_category = create_test_category(&pool, other_id, "Theirs", 50.0).await;
+    server
+        .post("/api/v1/plans/2025")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "categories": [{ "category_id": other_category, "amounts": vec![10.0; 12] }] }))
+        .expect_failure()
+        .await
+        .assert_status_not_found();
+}
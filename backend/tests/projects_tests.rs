@@ -0,0 +1,120 @@
+mod common;
+
+use common::{
+    auth_name, auth_value, close_test_month, create_test_category, create_test_item,
+    create_test_month, create_test_pool, create_test_server, create_test_user, generate_token,
+};
+use payme::create_app;
+use serde_json::json;
+
+async fn setup_with_user() -> (axum_test::TestServer, sqlx::SqlitePool, i64, String) {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+    (server, pool, user_id, token)
+}
+
+#[tokio::test]
+async fn test_project_summary_spans_months() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let category_id = create_test_category(&pool, user_id, "Home", 500.0).await;
+    let may = create_test_month(&pool, user_id, 2024, 5).await;
+    let june = create_test_month(&pool, user_id, 2024, 6).await;
+    let tiles = create_test_item(&pool, may, category_id, "Tiles", 800.0, "2024-05-20").await;
+    let sink = create_test_item(&pool, june, category_id, "Sink", 450.0, "2024-06-02").await;
+    create_test_item(&pool, june, category_id, "Groceries", 60.0, "2024-06-03").await;
+    close_test_month(&pool, may).await;
+
+    let project: serde_json::Value = server
+        .post("/api/v1/projects")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "name": "Kitchen renovation", "budget": 1000.0 }))
+        .await
+        .json();
+    assert_eq!(project["spent"], 0.0);
+
+    for (month_id, item_id) in [(may, tiles), (june, sink)] {
+        server
+            .put(&format!(
+                "/api/v1/months/{}/items/{}/project",
+                month_id, item_id
+            ))
+            .add_header(auth_name(), auth_value(&token))
+            .json(&json!({ "project_id": project["id"] }))
+            .await
+            .assert_status(axum::http::StatusCode::NO_CONTENT);
+    }
+
+    let summary: serde_json::Value = server
+        .get(&format!("/api/v1/projects/{}/summary", project["id"]))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(summary["project"]["spent"], 1250.0);
+    assert_eq!(summary["remaining"], -250.0);
+    assert_eq!(summary["months"].as_array().unwrap().len(), 2);
+    assert_eq!(summary["months"][0]["month"], 5);
+    assert_eq!(summary["months"][0]["spent"], 800.0);
+    assert_eq!(summary["items"][1]["description"], "Sink");
+
+    server
+        .put(&format!("/api/v1/months/{}/items/{}/project", may, tiles))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "project_id": null }))
+        .await
+        .assert_status(axum::http::StatusCode::NO_CONTENT);
+
+    let projects: serde_json::Value = server
+        .get("/api/v1/projects")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(projects[0]["spent"], 450.0);
+
+    server
+        .delete(&format!("/api/v1/projects/{}", project["id"]))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status(axum::http::StatusCode::NO_CONTENT);
+    let month: serde_json::Value = server
+        .get(&format!("/api/v1/months/{}", june))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(month["items"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn test_link_to_other_users_project_not_found() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let other = create_test_user(&pool, "other", "password123").await;
+    let project_id: i64 = sqlx::query_scalar(
+        "INSERT INTO projects (user_id, name, budget, created_at) VALUES (?, 'Japan trip', 3000, datetime('now')) RETURNING id",
+    )
+    .bind(other)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    let category_id = create_test_category(&pool, user_id, "Travel", 200.0).await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let item_id =
+        create_test_item(&pool, month_id, category_id, "Flights", 900.0, "2024-06-01").await;
+
+    server
+        .put(&format!(
+            "/api/v1/months/{}/items/{}/project",
+            month_id, item_id
+        ))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "project_id": project_id }))
+        .expect_failure()
+        .await
+        .assert_status_not_found();
+    server
+        .get(&format!("/api/v1/projects/{}/summary", project_id))
+        .add_header(auth_name(), auth_value(&token))
+        .expect_failure()
+        .await
+        .assert_status_not_found();
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{
+    auth_name, auth_value, create_test_category, create_test_item, create_test_month,
+    create_test_pool, create_test_server, create_test_user, generate_token,
+};
+use payme::create_app;
+use serde_json::json;
+
+async fn setup_with_user() -> (axum_test::TestServer, sqlx::SqlitePool, i64, String) {
+    std::env::set_var("QUOTA_MAX_MONTHS", "2");
+    std::env::set_var("QUOTA_MAX_ITEMS_PER_MONTH", "2");
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+    (server, pool, user_id, token)
+}
+
+#[tokio::test]
+async fn test_items_per_month_quota() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let cat_id = create_test_category(&pool, user_id, "Food", 500.0).await;
+    create_test_item(&pool, month_id, cat_id, "Bread", 3.0, "2024-06-01").await;
+    create_test_item(&pool, month_id, cat_id, "Milk", 2.0, "2024-06-02").await;
+
+    let response = server
+        .post(&format!("/api/months/{}/items", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "category_id": cat_id,
+            "description": "Coffee",
+            "amount": 5.0,
+            "spent_on": "2024-06-15"
+        }))
+        .expect_failure()
+        .await;
+
+    response.assert_status(StatusCode::CONFLICT);
+    let body: serde_json::Value = response.json();
+    assert_eq!(
+        body["reason"],
+        "This server allows at most 2 items per month"
+    );
+}
+
+#[tokio::test]
+async fn test_months_quota() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    create_test_month(&pool, user_id, 2020, 1).await;
+    create_test_month(&pool, user_id, 2020, 2).await;
+
+    let response = server
+        .get("/api/months/current")
+        .add_header(auth_name(), auth_value(&token))
+        .expect_failure()
+        .await;
+
+    response.assert_status(StatusCode::CONFLICT);
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM months WHERE user_id = ?")
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 2);
+}
+
+#[tokio::test]
+async fn test_import_over_quota_is_refused() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    create_test_month(&pool, user_id, 2020, 1).await;
+
+    let month = |month: i32| {
+        json!({
+            "year": 2024, "month": month, "is_closed": false,
+            "income_entries": [], "budgets": [], "items": []
+        })
+    };
+    let response = server
+        .post("/api/import/json")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "version": 1,
+            "fixed_expenses": [],
+            "categories": [],
+            "months": [month(1), month(2), month(3)]
+        }))
+        .expect_failure()
+        .await;
+
+    response.assert_status(StatusCode::CONFLICT);
+    let years: Vec<i32> = sqlx::query_scalar("SELECT year FROM months WHERE user_id = ?")
+        .bind(user_id)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert_eq!(years, vec![2020]);
+}
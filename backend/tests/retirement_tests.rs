@@ -0,0 +1,161 @@
+mod common;
+
+use common::{
+    auth_name, auth_value, create_test_category, create_test_month, create_test_pool,
+    create_test_server, create_test_user, generate_token,
+};
+use payme::create_app;
+use serde_json::json;
+
+async fn setup_with_user() -> (axum_test::TestServer, sqlx::SqlitePool, i64, String) {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let app = create_app(pool.clone());
+    let server = create_test_server(app);
+    (server, pool, user_id, token)
+}
+
+#[tokio::test]
+async fn test_projection_requires_ages() {
+    let (server, _pool, _user_id, token) = setup_with_user().await;
+
+    let response = server
+        .get("/api/retirement/projection?monthly_contribution=100")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+
+    response.assert_status_bad_request();
+}
+
+#[tokio::test]
+async fn test_projection_without_returns() {
+    let (server, _pool, _user_id, token) = setup_with_user().await;
+
+    server
+        .put("/api/retirement-savings")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "retirement_savings": 1000.0 }))
+        .await;
+
+    let response = server
+        .get("/api/retirement/projection?monthly_contribution=100&return_rate=0&current_age=30&target_age=32")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+
+    response.assert_status_ok();
+    let body: serde_json::Value = response.json();
+    let points = body["points"].as_array().unwrap();
+    assert_eq!(points.len(), 2);
+    assert_eq!(points[0]["age"], 31);
+    assert_eq!(points[0]["balance"], 2200.0);
+    assert_eq!(points[1]["total_contributions"], 2400.0);
+    assert_eq!(body["projected_balance"], 3400.0);
+}
+
+#[tokio::test]
+async fn test_projection_reuses_saved_assumptions() {
+    let (server, _pool, _user_id, token) = setup_with_user().await;
+
+    server
+        .get("/api/retirement/projection?monthly_contribution=50&return_rate=7&current_age=40&target_age=65")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_ok();
+
+    let response = server
+        .get("/api/retirement/projection")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+
+    response.assert_status_ok();
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["monthly_contribution"], 50.0);
+    assert_eq!(body["return_rate"], 7.0);
+    assert_eq!(body["points"].as_array().unwrap().len(), 25);
+
+    let settings: serde_json::Value = server
+        .get("/api/settings")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(settings["retirement_target_age"], 65);
+}
+
+#[tokio::test]
+async fn test_projection_uses_historical_contributions() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let cat_id = create_test_category(&pool, user_id, "Investing", 300.0).await;
+    create_test_month(&pool, user_id, 2024, 7).await;
+
+    server
+        .post(&format!("/api/months/{}/items", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "category_id": cat_id,
+            "description": "IRA",
+            "amount": 300.0,
+            "spent_on": "2024-06-01",
+            "savings_destination": "retirement_savings"
+        }))
+        .await
+        .assert_status_ok();
+
+    let response = server
+        .get("/api/retirement/projection?current_age=30&target_age=31")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+
+    response.assert_status_ok();
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["historical_monthly_contribution"], 150.0);
+    assert_eq!(body["monthly_contribution"], 150.0);
+}
+
+#[tokio::test]
+async fn test_update_settings_partial() {
+    let (server, _pool, _user_id, token) = setup_with_user().await;
+
+    server
+        .put("/api/settings")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "retirement_current_age": 35 }))
+        .await
+        .assert_status_ok();
+
+    let response = server
+        .put("/api/settings")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "retirement_target_age": 67 }))
+        .await;
+
+    response.assert_status_ok();
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["retirement_current_age"], 35);
+    assert_eq!(body["retirement_target_age"], 67);
+}
+
+#[tokio::test]
+async fn test_update_settings_locale_and_currency() {
+    let (server, _pool, _user_id, token) = setup_with_user().await;
+
+    let response = server
+        .put("/api/settings")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "locale": "de-DE", "currency": "eur" }))
+        .await;
+
+    response.assert_status_ok();
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["locale"], "de-DE");
+    assert_eq!(body["currency"], "EUR");
+
+    server
+        .put("/api/settings")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "currency": "EURO" }))
+        .await
+        .assert_status_bad_request();
+}
@@ -0,0 +1,191 @@
+mod common;
+
+use common::{
+    auth_name, auth_value, create_test_category, create_test_month, create_test_pool,
+    create_test_server, create_test_user, generate_token,
+};
+use payme::create_app;
+use serde_json::{json, Value};
+
+async fn setup_with_user() -> (axum_test::TestServer, sqlx::SqlitePool, i64, String) {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+    (server, pool, user_id, token)
+}
+
+async fn add_item(
+    server: &axum_test::TestServer,
+    token: &str,
+    month_id: i64,
+    item: Value,
+) -> Value {
+    let response = server
+        .post(&format!("/api/v1/months/{month_id}/items?force=true"))
+        .add_header(auth_name(), auth_value(token))
+        .json(&item)
+        .await;
+    response.assert_status_ok();
+    response.json()
+}
+
+#[tokio::test]
+async fn test_items_without_category_follow_rules() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let food = create_test_category(&pool, user_id, "Food", 300.0).await;
+    let fun = create_test_category(&pool, user_id, "Fun", 100.0).await;
+    let month = create_test_month(&pool, user_id, 2024, 6).await;
+
+    let response = server
+        .put(&format!("/api/v1/categories/{food}/rules"))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "rules": [
+            { "kind": "contains", "pattern": "tesco" },
+            { "kind": "regex", "pattern": "^uber\\s*eats" }
+        ] }))
+        .await;
+    response.assert_status_ok();
+    let rules: Value = response.json();
+    assert_eq!(rules.as_array().unwrap().len(), 2);
+    assert_eq!(rules[1]["kind"], "regex");
+    server
+        .put(&format!("/api/v1/categories/{fun}/rules"))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "rules": [{ "kind": "contains", "pattern": "🎬" }] }))
+        .await
+        .assert_status_ok();
+
+    let item = |description: &str| {
+        json!({
+            "category_id": null,
+            "description": description,
+            "amount": 12.0,
+            "spent_on": "2024-06-10"
+        })
+    };
+    let tesco = add_item(&server, &token, month, item("Tesco Express")).await;
+    assert_eq!(tesco["category_id"], food);
+    let uber = add_item(&server, &token, month, item("Uber Eats order")).await;
+    assert_eq!(uber["category_id"], food);
+    let cinema = add_item(&server, &token, month, item("🎬 Dune")).await;
+    assert_eq!(cinema["category_id"], fun);
+
+    let unmatched = add_item(&server, &token, month, item("Mystery charge")).await;
+    let uncategorized = unmatched["category_id"].as_i64().unwrap();
+    assert_ne!(uncategorized, food);
+    assert_ne!(uncategorized, fun);
+    let mut without_field = item("Another mystery");
+    without_field.as_object_mut().unwrap().remove("category_id");
+    let again = add_item(&server, &token, month, without_field).await;
+    assert_eq!(again["category_id"], uncategorized);
+
+    let categories: Value = server
+        .get("/api/v1/categories")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    let system: Vec<&Value> = categories
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|c| c["system"] == true)
+        .collect();
+    assert_eq!(system.len(), 1);
+    assert_eq!(system[0]["label"], "Uncategorized");
+    assert_eq!(system[0]["id"], uncategorized);
+}
+
+#[tokio::test]
+async fn test_uncategorized_category_is_protected() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month = create_test_month(&pool, user_id, 2024, 6).await;
+    let item = add_item(
+        &server,
+        &token,
+        month,
+        json!({ "description": "Mystery charge", "amount": 12.0, "spent_on": "2024-06-10" }),
+    )
+    .await;
+    let uncategorized = item["category_id"].as_i64().unwrap();
+
+    server
+        .put(&format!("/api/v1/categories/{uncategorized}"))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "label": "Misc", "tracking_only": true }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+    server
+        .put("/api/v1/categories/bulk")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "labels": [{ "id": uncategorized, "label": "Misc" }] }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+    server
+        .delete(&format!("/api/v1/categories/{uncategorized}"))
+        .add_header(auth_name(), auth_value(&token))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+
+    let (label, items): (String, i64) = sqlx::query_as(
+        "SELECT bc.label, (SELECT COUNT(*) FROM items WHERE category_id = bc.id) FROM budget_categories bc WHERE bc.id = ?",
+    )
+    .bind(uncategorized)
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(label, "Uncategorized");
+    assert_eq!(items, 1);
+}
+
+#[tokio::test]
+async fn test_set_rules_validation() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let food = create_test_category(&pool, user_id, "Food", 300.0).await;
+
+    for rules in [
+        json!([{ "kind": "regex", "pattern": "(" }]),
+        json!([{ "kind": "glob", "pattern": "*" }]),
+        json!([{ "kind": "contains", "pattern": "" }]),
+    ] {
+        let response = server
+            .put(&format!("/api/v1/categories/{food}/rules"))
+            .add_header(auth_name(), auth_value(&token))
+            .json(&json!({ "rules": rules }))
+            .expect_failure()
+            .await;
+        response.assert_status_bad_request();
+    }
+
+    let rules: Value = server
+        .get(&format!("/api/v1/categories/{food}/rules"))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(rules, json!([]));
+}
+
+#[tokio::test]
+async fn test_rules_of_another_user() {
+    let (server, pool, _user_id, token) = setup_with_user().await;
+    let other = create_test_user(&pool, "other", "password123").await;
+    let theirs = create_test_category(&pool, other, "Food", 300.0).await;
+
+    let response = server
+        .put(&format!("/api/v1/categories/{theirs}/rules"))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "rules": [{ "kind": "contains", "pattern": "tesco" }] }))
+        .expect_failure()
+        .await;
+    response.assert_status_not_found();
+
+    let response = server
+        .get(&format!("/api/v1/categories/{theirs}/rules"))
+        .add_header(auth_name(), auth_value(&token))
+        .expect_failure()
+        .await;
+    response.assert_status_not_found();
+}
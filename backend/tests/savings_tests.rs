@@ -109,3 +109,49 @@ async fn test_savings_validation() {
 
     response.assert_status_bad_request();
 }
+
+#[tokio::test]
+async fn test_wealth_history_records_balances_on_close() {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+
+    let may = common::create_test_month(&pool, user_id, 2024, 5).await;
+    let june = common::create_test_month(&pool, user_id, 2024, 6).await;
+
+    for (month_id, savings, retirement) in [(june, 1500.0, 300.0), (may, 1000.0, 200.0)] {
+        server
+            .put("/api/savings")
+            .add_header(auth_name(), auth_value(&token))
+            .json(&json!({ "savings": savings }))
+            .await
+            .assert_status_ok();
+        server
+            .put("/api/retirement-savings")
+            .add_header(auth_name(), auth_value(&token))
+            .json(&json!({ "retirement_savings": retirement }))
+            .await
+            .assert_status_ok();
+        server
+            .post(&format!("/api/months/{}/close", month_id))
+            .add_header(auth_name(), auth_value(&token))
+            .await
+            .assert_status_ok();
+    }
+
+    let response = server
+        .get("/api/wealth/history")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    let body: Vec<serde_json::Value> = response.json();
+
+    assert_eq!(body.len(), 2);
+    assert_eq!(body[0]["month"], 5);
+    assert_eq!(body[0]["savings"], 1000.0);
+    assert_eq!(body[0]["net_worth"], 1200.0);
+    assert_eq!(body[1]["month"], 6);
+    assert_eq!(body[1]["retirement_savings"], 300.0);
+    assert_eq!(body[1]["net_worth"], 1800.0);
+}
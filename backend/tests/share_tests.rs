@@ -0,0 +1,147 @@
+mod common;
+
+use common::{
+    auth_name, auth_value, create_test_income, create_test_month, create_test_pool,
+    create_test_server, create_test_user, generate_token,
+};
+use payme::create_app;
+use serde_json::json;
+
+async fn setup_with_user() -> (axum_test::TestServer, sqlx::SqlitePool, i64, String) {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let app = create_app(pool.clone());
+    let server = create_test_server(app);
+    (server, pool, user_id, token)
+}
+
+#[tokio::test]
+async fn test_shared_month_without_auth() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    create_test_income(&pool, month_id, "Salary", 5000.0).await;
+
+    let response = server
+        .post(&format!("/api/months/{}/share", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "expires_in_days": 3 }))
+        .await;
+
+    response.assert_status_ok();
+    let share: serde_json::Value = response.json();
+    let url = share["url"].as_str().unwrap();
+
+    let response = server.get(url).await;
+    response.assert_status_ok();
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["month"]["id"], month_id);
+    assert_eq!(body["total_income"], 5000.0);
+
+    let response = server.get(share["pdf_url"].as_str().unwrap()).await;
+    response.assert_status_ok();
+    assert_eq!(
+        response.headers().get("content-type").unwrap(),
+        "application/pdf"
+    );
+}
+
+#[tokio::test]
+async fn test_revoked_share_is_not_found() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+
+    let share: serde_json::Value = server
+        .post(&format!("/api/months/{}/share", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({}))
+        .await
+        .json();
+
+    server
+        .delete(&format!("/api/months/{}/share", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status(axum::http::StatusCode::NO_CONTENT);
+
+    server
+        .get(share["url"].as_str().unwrap())
+        .await
+        .assert_status_not_found();
+}
+
+#[tokio::test]
+async fn test_invalid_share_token() {
+    let (server, _pool, _user_id, _token) = setup_with_user().await;
+
+    server
+        .get("/api/shared/not-a-token")
+        .await
+        .assert_status_not_found();
+}
+
+#[tokio::test]
+async fn test_share_other_users_month() {
+    let pool = create_test_pool().await;
+    let user1_id = create_test_user(&pool, "user1", "password123").await;
+    let user2_id = create_test_user(&pool, "user2", "password123").await;
+    let month_id = create_test_month(&pool, user1_id, 2024, 6).await;
+    let token2 = generate_token(user2_id, "user2");
+    let server = create_test_server(create_app(pool));
+
+    server
+        .post(&format!("/api/months/{}/share", month_id))
+        .add_header(auth_name(), auth_value(&token2))
+        .json(&json!({}))
+        .await
+        .assert_status_not_found();
+}
+
+#[tokio::test]
+async fn test_public_stats_expose_only_percentages() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    create_test_income(&pool, month_id, "Salary", 4000.0).await;
+    let food = common::create_test_category(&pool, user_id, "Food", 500.0).await;
+    let fun = common::create_test_category(&pool, user_id, "Fun", 500.0).await;
+    common::create_test_budget(&pool, month_id, food, 500.0).await;
+    common::create_test_budget(&pool, month_id, fun, 500.0).await;
+    common::create_test_item(&pool, month_id, food, "Groceries", 300.0, "2024-06-03").await;
+    common::create_test_item(&pool, month_id, fun, "Cinema", 100.0, "2024-06-04").await;
+
+    let link: serde_json::Value = server
+        .post("/api/public-stats")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    let again: serde_json::Value = server
+        .post("/api/public-stats")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(link["slug"], again["slug"]);
+
+    let response = server.get(link["url"].as_str().unwrap()).await;
+    response.assert_status_ok();
+    let body: serde_json::Value = response.json();
+    assert_eq!(body["savings_rate_percent"], 90.0);
+    assert_eq!(body["categories"][0]["label"], "Food");
+    assert_eq!(body["categories"][0]["percent"], 75.0);
+    assert_eq!(body["categories"][1]["percent"], 25.0);
+    let text = response.text();
+    assert!(!text.contains("4000") && !text.contains("300"));
+
+    server
+        .delete("/api/public-stats")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status(axum::http::StatusCode::NO_CONTENT);
+    server
+        .get(link["url"].as_str().unwrap())
+        .expect_failure()
+        .await
+        .assert_status_not_found();
+}
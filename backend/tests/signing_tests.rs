@@ -0,0 +1,112 @@
+mod common;
+
+use common::{
+    auth_name, auth_value, create_test_month, create_test_pool, create_test_server,
+    create_test_user, generate_token,
+};
+use payme::create_app;
+use serde_json::Value;
+
+async fn setup_with_signing_key() -> (axum_test::TestServer, sqlx::SqlitePool, i64, String) {
+    std::env::set_var("PDF_SIGNING_KEY", "11".repeat(32));
+    std::env::set_var("PUBLIC_URL", "https://payme.example.com/");
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+    (server, pool, user_id, token)
+}
+
+/// Closes a month, stores its signed PDF and returns the PDF with its verification code.
+async fn close_signed_month(
+    server: &axum_test::TestServer,
+    pool: &sqlx::SqlitePool,
+    token: &str,
+    month_id: i64,
+) -> (Vec<u8>, String) {
+    server
+        .post(&format!("/api/months/{}/close", month_id))
+        .add_header(auth_name(), auth_value(token))
+        .await
+        .assert_status_ok();
+    payme::jobs::run_pending(pool).await.unwrap();
+
+    let pdf = server
+        .get(&format!("/api/months/{}/pdf", month_id))
+        .add_header(auth_name(), auth_value(token))
+        .await
+        .as_bytes()
+        .to_vec();
+    let code: String =
+        sqlx::query_scalar("SELECT verification_code FROM monthly_snapshots WHERE month_id = ?")
+            .bind(month_id)
+            .fetch_one(pool)
+            .await
+            .unwrap();
+
+    (pdf, code)
+}
+
+#[tokio::test]
+async fn test_signed_pdf_verifies() {
+    let (server, pool, user_id, token) = setup_with_signing_key().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let (pdf, code) = close_signed_month(&server, &pool, &token, month_id).await;
+
+    let response = server
+        .post(&format!("/api/pdf-signatures/{}/verify", code))
+        .bytes(pdf.into())
+        .await;
+    response.assert_status_ok();
+    let body: Value = response.json();
+    assert_eq!(body["valid"], true);
+    assert_eq!(body["sha256"], body["signature"]["sha256"]);
+    assert_eq!(body["signature"]["year"], 2024);
+    assert_eq!(body["signature"]["month"], 6);
+
+    let key: Value = server.get("/api/signing-key").await.json();
+    assert_eq!(body["signature"]["public_key"], key["public_key"]);
+    assert!(key["public_key_pem"]
+        .as_str()
+        .unwrap()
+        .starts_with("-----BEGIN PUBLIC KEY-----"));
+}
+
+#[tokio::test]
+async fn test_modified_pdf_does_not_verify() {
+    let (server, pool, user_id, token) = setup_with_signing_key().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let (mut pdf, code) = close_signed_month(&server, &pool, &token, month_id).await;
+    pdf.extend_from_slice(b"\n% edited");
+
+    let body: Value = server
+        .post(&format!("/api/pdf-signatures/{}/verify", code))
+        .bytes(pdf.into())
+        .await
+        .json();
+    assert_eq!(body["valid"], false);
+    assert_ne!(body["sha256"], body["signature"]["sha256"]);
+}
+
+#[tokio::test]
+async fn test_get_pdf_signature() {
+    let (server, pool, user_id, token) = setup_with_signing_key().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let (pdf, code) = close_signed_month(&server, &pool, &token, month_id).await;
+
+    let response = server.get(&format!("/api/pdf-signatures/{}", code)).await;
+    response.assert_status_ok();
+    let body: Value = response.json();
+    assert_eq!(body["sha256"], payme::storage::content_hash(&pdf));
+    assert!(payme::signing::verify(
+        body["public_key"].as_str().unwrap(),
+        &pdf,
+        body["signature"].as_str().unwrap()
+    ));
+
+    server
+        .get("/api/pdf-signatures/unknown")
+        .expect_failure()
+        .await
+        .assert_status_not_found();
+}
@@ -0,0 +1,190 @@
+mod common;
+
+use common::{
+    auth_name, auth_value, create_test_category, create_test_fixed_expense, create_test_income,
+    create_test_item, create_test_month, create_test_pool, create_test_server, create_test_user,
+    generate_token,
+};
+use payme::create_app;
+use serde_json::json;
+
+async fn setup_with_user() -> (axum_test::TestServer, sqlx::SqlitePool, i64, String) {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+    (server, pool, user_id, token)
+}
+
+#[tokio::test]
+async fn test_simulate_budget_changes() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let food = create_test_category(&pool, user_id, "Food", 500.0).await;
+    let fun = create_test_category(&pool, user_id, "Fun", 200.0).await;
+    let reimbursed = create_test_category(&pool, user_id, "Work trips", 0.0).await;
+    sqlx::query("UPDATE budget_categories SET tracking_only = 1 WHERE id = ?")
+        .bind(reimbursed)
+        .execute(&pool)
+        .await
+        .unwrap();
+    create_test_fixed_expense(&pool, user_id, "Rent", 1000.0).await;
+
+    let january = create_test_month(&pool, user_id, 2020, 1).await;
+    create_test_income(&pool, january, "Salary", 3000.0).await;
+    create_test_item(&pool, january, food, "Groceries", 400.0, "2020-01-10").await;
+    create_test_item(&pool, january, fun, "Concert", 100.0, "2020-01-20").await;
+    let february = create_test_month(&pool, user_id, 2020, 2).await;
+    create_test_income(&pool, february, "Salary", 3000.0).await;
+    create_test_item(&pool, february, food, "Groceries", 600.0, "2020-02-10").await;
+    create_test_item(&pool, february, fun, "Cinema", 300.0, "2020-02-20").await;
+    create_test_item(&pool, february, reimbursed, "Hotel", 900.0, "2020-02-21").await;
+
+    let result: serde_json::Value = server
+        .post("/api/v1/simulations")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "income_change": -200.0,
+            "category_changes": [{ "category_id": fun, "percent": -50.0 }],
+            "new_payments": [{ "label": "Car loan", "amount": 150.0 }],
+            "history_months": 2
+        }))
+        .await
+        .json();
+
+    assert_eq!(result["history_months"], 2);
+    assert_eq!(result["baseline"]["income"], 3000.0);
+    assert_eq!(result["baseline"]["fixed"], 1000.0);
+    assert_eq!(result["baseline"]["spending"], 700.0);
+    assert_eq!(result["baseline"]["remaining"], 1300.0);
+    assert_eq!(result["simulated"]["income"], 2800.0);
+    assert_eq!(result["simulated"]["fixed"], 1150.0);
+    assert_eq!(result["simulated"]["spending"], 600.0);
+    assert_eq!(result["simulated"]["remaining"], 1050.0);
+    assert_eq!(result["categories"][1]["simulated"], 100.0);
+    assert_eq!(result["categories"].as_array().unwrap().len(), 2);
+
+    let projection = result["projection"].as_array().unwrap();
+    assert_eq!(projection.len(), 12);
+    assert_eq!(projection[11]["baseline_balance"], 15600.0);
+    assert_eq!(projection[11]["simulated_balance"], 12600.0);
+    assert_eq!(result["difference"], -3000.0);
+
+    // Nothing is saved, not even the current month
+    let months: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM months WHERE user_id = ?")
+        .bind(user_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(months, 2);
+}
+
+#[tokio::test]
+async fn test_simulate_unknown_category() {
+    let (server, _pool, _user_id, token) = setup_with_user().await;
+
+    server
+        .post("/api/v1/simulations")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "category_changes": [{ "category_id": 999, "percent": 10.0 }] }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+}
+
+#[tokio::test]
+async fn test_simulate_savings() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    sqlx::query("UPDATE users SET savings = 1000 WHERE id = ?")
+        .bind(user_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let result: serde_json::Value = server
+        .get("/api/v1/simulations/savings?monthly=100&years=2&rate=0")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(result["starting_balance"], 1000.0);
+    assert_eq!(result["final_balance"], 3400.0);
+    assert_eq!(result["points"][0]["contributions"], 1200.0);
+    assert_eq!(result["points"][1]["balance"], 3400.0);
+    assert_eq!(result["points"][1]["interest"], 0.0);
+
+    let grown: serde_json::Value = server
+        .get("/api/v1/simulations/savings?monthly=100&years=2&rate=5")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert!(grown["points"][1]["interest"].as_f64().unwrap() > 0.0);
+
+    server
+        .get("/api/v1/simulations/savings?monthly=100&years=0&rate=5")
+        .add_header(auth_name(), auth_value(&token))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+}
+
+#[tokio::test]
+async fn test_savings_scenario_becomes_goal_with_auto_contribution() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let savings = create_test_category(&pool, user_id, "Savings", 0.0).await;
+
+    let plan: serde_json::Value = server
+        .post("/api/v1/simulations/savings/goal")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "monthly": 250.0, "years": 1, "rate": 0.0, "category_id": savings }))
+        .await
+        .json();
+    assert_eq!(plan["savings_goal"], 3000.0);
+    assert_eq!(plan["auto_contribution"], 250.0);
+    assert_eq!(plan["auto_category_id"], savings);
+
+    let balance: serde_json::Value = server
+        .get("/api/v1/savings")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(balance["savings_goal"], 3000.0);
+
+    // Opening a new month books the contribution
+    server
+        .get("/api/v1/months/current")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_ok();
+    let (description, amount, destination): (String, f64, String) =
+        sqlx::query_as("SELECT description, amount, savings_destination FROM items")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(description, "Savings contribution");
+    assert_eq!(amount, 250.0);
+    assert_eq!(destination, "savings");
+
+    let balance: serde_json::Value = server
+        .get("/api/v1/savings")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(balance["savings"], 250.0);
+
+    // A goal without a category drops the auto-contribution
+    let plan: serde_json::Value = server
+        .post("/api/v1/simulations/savings/goal")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "monthly": 250.0, "years": 1, "rate": 0.0 }))
+        .await
+        .json();
+    assert_eq!(plan["savings_goal"], 3250.0);
+    assert!(plan["auto_contribution"].is_null());
+
+    server
+        .post("/api/v1/simulations/savings/goal")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "monthly": 250.0, "years": 1, "rate": 0.0, "category_id": 999 }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+}
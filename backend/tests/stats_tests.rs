@@ -6,6 +6,7 @@ use common::{
     generate_token,
 };
 use payme::create_app;
+use serde_json::json;
 
 async fn setup_with_user() -> (axum_test::TestServer, sqlx::SqlitePool, i64, String) {
     let pool = create_test_pool().await;
@@ -120,3 +121,97 @@ async fn test_stats_change_percent() {
 
     assert_eq!(food_comparison["change_percent"], 50.0);
 }
+
+#[tokio::test]
+async fn test_stats_in_real_terms() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+
+    let old_month_id = create_test_month(&pool, user_id, 2022, 6).await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let cat_id = create_test_category(&pool, user_id, "Food", 500.0).await;
+    create_test_income(&pool, old_month_id, "Salary", 2000.0).await;
+    create_test_income(&pool, month_id, "Salary", 2000.0).await;
+    create_test_item(
+        &pool,
+        old_month_id,
+        cat_id,
+        "Groceries",
+        400.0,
+        "2022-06-15",
+    )
+    .await;
+    create_test_item(&pool, month_id, cat_id, "Groceries", 500.0, "2024-06-15").await;
+
+    server
+        .get("/api/stats?real_terms=true")
+        .add_header(auth_name(), auth_value(&token))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+
+    server
+        .put("/api/cpi")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "readings": [
+            { "year": 2024, "month": 1, "value": 125.0 },
+            { "year": 2022, "month": 1, "value": 100.0 }
+        ] }))
+        .await
+        .assert_status_ok();
+
+    let body: serde_json::Value = server
+        .get("/api/stats?real_terms=true")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(
+        body["price_base"],
+        json!({ "year": 2024, "month": 1, "value": 125.0 })
+    );
+    let trends = body["monthly_trends"].as_array().unwrap();
+    assert_eq!(trends[0]["total_spent"], 500.0);
+    assert_eq!(trends[1]["year"], 2022);
+    assert_eq!(trends[1]["total_spent"], 500.0);
+    assert_eq!(trends[1]["total_income"], 2500.0);
+    assert_eq!(body["average_monthly_income"], 2250.0);
+    let comparison = &body["category_comparisons"][0];
+    assert_eq!(comparison["previous_month_spent"], 500.0);
+    assert_eq!(comparison["change_amount"], 0.0);
+
+    let body: serde_json::Value = server
+        .get("/api/stats")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert!(body["price_base"].is_null());
+    assert_eq!(body["monthly_trends"][1]["total_spent"], 400.0);
+}
+
+#[tokio::test]
+async fn test_cpi_validation() {
+    let (server, _pool, _user_id, token) = setup_with_user().await;
+
+    for readings in [
+        json!([{ "year": 2024, "month": 13, "value": 100.0 }]),
+        json!([{ "year": 2024, "month": 1, "value": 0.0 }]),
+        json!([
+            { "year": 2024, "month": 1, "value": 100.0 },
+            { "year": 2024, "month": 1, "value": 101.0 }
+        ]),
+    ] {
+        server
+            .put("/api/cpi")
+            .add_header(auth_name(), auth_value(&token))
+            .json(&json!({ "readings": readings }))
+            .expect_failure()
+            .await
+            .assert_status_bad_request();
+    }
+
+    let readings: serde_json::Value = server
+        .get("/api/cpi")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(readings, json!([]));
+}
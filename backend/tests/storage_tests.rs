@@ -0,0 +1,170 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use common::{
+    auth_name, auth_value, create_test_month, create_test_pool, create_test_server,
+    create_test_user, generate_token,
+};
+use payme::create_app;
+use payme::storage::{self, DatabaseStorage, LocalStorage, Storage};
+
+async fn setup_with_user() -> (axum_test::TestServer, sqlx::SqlitePool, i64, String) {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+    (server, pool, user_id, token)
+}
+
+#[tokio::test]
+async fn test_month_pdf_is_kept_in_storage() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+
+    server
+        .post(&format!("/api/months/{}/close", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_ok();
+    payme::jobs::run_pending(&pool).await.unwrap();
+
+    let (key, size): (String, i64) =
+        sqlx::query_as("SELECT storage_key, size FROM monthly_snapshots WHERE month_id = ?")
+            .bind(month_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(key, storage::snapshot_key(month_id));
+
+    let stored = DatabaseStorage::new(pool.clone())
+        .list("snapshots/")
+        .await
+        .unwrap();
+    assert_eq!(stored.len(), 1);
+    assert_eq!(stored[0].size, size as u64);
+
+    let response = server
+        .get(&format!("/api/months/{}/pdf", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    assert!(response.as_bytes().starts_with(b"%PDF"));
+}
+
+#[tokio::test]
+async fn test_remove_orphans() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    server
+        .post(&format!("/api/months/{}/close", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_ok();
+    payme::jobs::run_pending(&pool).await.unwrap();
+
+    let storage = DatabaseStorage::new(pool.clone());
+    storage::put_sealed(&storage, "snapshots/999.pdf", b"left behind".to_vec())
+        .await
+        .unwrap();
+    storage::put_sealed(&storage, "backups/payme.db", b"not owned".to_vec())
+        .await
+        .unwrap();
+
+    // Files younger than the grace period are kept
+    assert_eq!(
+        storage::remove_orphans(&pool, &storage, Utc::now())
+            .await
+            .unwrap(),
+        0
+    );
+
+    let later = Utc::now() + Duration::hours(2);
+    assert_eq!(
+        storage::remove_orphans(&pool, &storage, later)
+            .await
+            .unwrap(),
+        1
+    );
+    let keys: Vec<String> = storage
+        .list("")
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|object| object.key)
+        .collect();
+    assert_eq!(
+        keys,
+        vec![
+            "backups/payme.db".to_string(),
+            storage::snapshot_key(month_id)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_month_pdf_is_verified_on_download() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    server
+        .post(&format!("/api/months/{}/close", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_ok();
+    payme::jobs::run_pending(&pool).await.unwrap();
+
+    // Snapshots from before hashes were kept get one on first read
+    sqlx::query("UPDATE monthly_snapshots SET sha256 = NULL")
+        .execute(&pool)
+        .await
+        .unwrap();
+    server
+        .get(&format!("/api/months/{}/pdf", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status_ok();
+    let hash: Option<String> =
+        sqlx::query_scalar("SELECT sha256 FROM monthly_snapshots WHERE month_id = ?")
+            .bind(month_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+    assert_eq!(hash.map(|h| h.len()), Some(64));
+
+    sqlx::query("UPDATE stored_objects SET data = ? WHERE key = ?")
+        .bind(b"%PDF-tampered".to_vec())
+        .bind(storage::snapshot_key(month_id))
+        .execute(&pool)
+        .await
+        .unwrap();
+    server
+        .get(&format!("/api/months/{}/pdf", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .expect_failure()
+        .await
+        .assert_status_internal_server_error();
+}
+
+#[tokio::test]
+async fn test_read_moves_files_out_of_the_database() {
+    let pool = create_test_pool().await;
+    let database = DatabaseStorage::new(pool.clone());
+    storage::put_sealed(&database, "snapshots/5.pdf", b"%PDF-1.4".to_vec())
+        .await
+        .unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let local = LocalStorage::new(dir.path().to_path_buf());
+
+    let data = storage::read_sealed(&pool, &local, "snapshots/5.pdf")
+        .await
+        .unwrap();
+
+    assert_eq!(data, b"%PDF-1.4");
+    assert!(database.list("").await.unwrap().is_empty());
+    assert_eq!(local.list("snapshots/").await.unwrap().len(), 1);
+    assert_eq!(
+        storage::read_sealed(&pool, &local, "snapshots/5.pdf")
+            .await
+            .unwrap(),
+        b"%PDF-1.4"
+    );
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use chrono::{Datelike, Months, Utc};
+use common::{
+    auth_name, auth_value, create_test_category, create_test_fixed_expense, create_test_item,
+    create_test_month, create_test_pool, create_test_server, create_test_user, generate_token,
+};
+use payme::create_app;
+use serde_json::json;
+
+#[tokio::test]
+async fn test_list_subscriptions() {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+
+    let internet = create_test_fixed_expense(&pool, user_id, "Internet", 40.0).await;
+    let fun = create_test_category(&pool, user_id, "Fun", 100.0).await;
+
+    let today = Utc::now().date_naive();
+    for (months_ago, amount) in [(2, 12.99), (1, 12.99), (0, 14.99)] {
+        let date = today - Months::new(months_ago);
+        let month_id = create_test_month(&pool, user_id, date.year(), date.month() as i32).await;
+        let spent_on = date.with_day(1).unwrap().to_string();
+        create_test_item(&pool, month_id, fun, "Streaming", amount, &spent_on).await;
+        create_test_item(&pool, month_id, fun, "Cinema", 9.0, &spent_on).await;
+        create_test_item(&pool, month_id, fun, "Cinema", 9.0, &spent_on).await;
+
+        if months_ago == 2 {
+            server
+                .post(&format!("/api/v1/months/{}/close", month_id))
+                .add_header(auth_name(), auth_value(&token))
+                .await
+                .assert_status_ok();
+        }
+    }
+
+    server
+        .put(&format!("/api/v1/fixed-expenses/{}", internet))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "amount": 45.0 }))
+        .await
+        .assert_status_ok();
+
+    let response = server
+        .get("/api/v1/subscriptions")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    let body: serde_json::Value = response.json();
+
+    let subscriptions = body["subscriptions"].as_array().unwrap();
+    assert_eq!(subscriptions.len(), 2);
+
+    let fixed = &subscriptions[0];
+    assert_eq!(fixed["source"], "fixed_expense");
+    assert_eq!(fixed["label"], "Internet");
+    assert_eq!(fixed["annual_cost"], 540.0);
+    assert_eq!(fixed["previous_amount"], 40.0);
+    assert_eq!(fixed["price_changed"], true);
+
+    let detected = &subscriptions[1];
+    assert_eq!(detected["source"], "item");
+    assert_eq!(detected["label"], "Streaming");
+    assert_eq!(detected["category_label"], "Fun");
+    assert_eq!(detected["monthly_amount"], 14.99);
+    assert_eq!(detected["previous_amount"], 12.99);
+    assert_eq!(detected["price_changed"], true);
+    let next_renewal = (today.with_day(1).unwrap() + Months::new(1)).to_string();
+    assert_eq!(detected["next_renewal"], next_renewal);
+
+    assert_eq!(body["monthly_total"], 45.0 + 14.99);
+}
+
+#[tokio::test]
+async fn test_cancel_candidates() {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+
+    let gym = create_test_fixed_expense(&pool, user_id, "Gym", 30.0).await;
+    create_test_fixed_expense(&pool, user_id, "Internet", 40.0).await;
+    let fun = create_test_category(&pool, user_id, "Fun", 100.0).await;
+    let today = Utc::now().date_naive();
+    for months_ago in 0..3 {
+        let date = today - Months::new(months_ago);
+        let month_id = create_test_month(&pool, user_id, date.year(), date.month() as i32).await;
+        let spent_on = date.with_day(1).unwrap().to_string();
+        create_test_item(&pool, month_id, fun, "Streaming", 12.99, &spent_on).await;
+    }
+
+    let response = server
+        .put("/api/v1/subscriptions/value")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "key": format!("fixed_expense:{gym}"), "value": " Go three times a week " }))
+        .await;
+    response.assert_status_ok();
+    let tagged: serde_json::Value = response.json();
+    assert_eq!(tagged["label"], "Gym");
+    assert_eq!(tagged["value"], "Go three times a week");
+    server
+        .put("/api/v1/subscriptions/value")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "key": "item:rent", "value": "Roof" }))
+        .expect_failure()
+        .await
+        .assert_status_not_found();
+
+    let body: serde_json::Value = server
+        .get("/api/v1/subscriptions/cancel-candidates")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    let candidates = body["candidates"].as_array().unwrap();
+    assert_eq!(candidates.len(), 2);
+    assert_eq!(candidates[0]["label"], "Internet");
+    assert_eq!(candidates[1]["key"], "item:streaming");
+    assert_eq!(body["annual_savings"], 40.0 * 12.0 + 12.99 * 12.0);
+
+    server
+        .put("/api/v1/subscriptions/value")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "key": format!("fixed_expense:{gym}"), "value": null }))
+        .await
+        .assert_status_ok();
+    let body: serde_json::Value = server
+        .get("/api/v1/subscriptions/cancel-candidates")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(body["candidates"].as_array().unwrap().len(), 3);
+}
@@ -0,0 +1,416 @@
+mod common;
+
+use chrono::{Duration, Utc};
+use common::{
+    auth_name, auth_value, create_test_category, create_test_item, create_test_month,
+    create_test_pool, create_test_server, create_test_user, generate_token,
+};
+use payme::create_app;
+use serde_json::{json, Value};
+
+async fn setup_with_user() -> (axum_test::TestServer, sqlx::SqlitePool, i64, String) {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+    (server, pool, user_id, token)
+}
+
+async fn sync(server: &axum_test::TestServer, token: &str, since: Option<i64>) -> Value {
+    let url = match since {
+        Some(since) => format!("/api/sync?since={}", since),
+        None => "/api/sync".to_string(),
+    };
+    let response = server
+        .get(&url)
+        .add_header(auth_name(), auth_value(token))
+        .await;
+    response.assert_status_ok();
+    response.json()
+}
+
+#[tokio::test]
+async fn test_full_sync() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let cat_id = create_test_category(&pool, user_id, "Food", 500.0).await;
+    create_test_item(&pool, month_id, cat_id, "Groceries", 150.0, "2024-06-15").await;
+
+    let other_id = create_test_user(&pool, "other", "password123").await;
+    create_test_month(&pool, other_id, 2024, 6).await;
+
+    let body = sync(&server, &token, None).await;
+    assert_eq!(body["full"], true);
+    assert_eq!(body["months"].as_array().unwrap().len(), 1);
+    assert_eq!(body["categories"][0]["label"], "Food");
+    assert_eq!(body["items"][0]["description"], "Groceries");
+    assert!(body["deleted"].as_array().unwrap().is_empty());
+    assert!(body["cursor"].as_i64().unwrap() > 0);
+}
+
+#[tokio::test]
+async fn test_sync_returns_changes_since_cursor() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let cat_id = create_test_category(&pool, user_id, "Food", 500.0).await;
+    let kept_id = create_test_item(&pool, month_id, cat_id, "Groceries", 150.0, "2024-06-15").await;
+    let deleted_id = create_test_item(&pool, month_id, cat_id, "Snacks", 10.0, "2024-06-16").await;
+    let cursor = sync(&server, &token, None).await["cursor"]
+        .as_i64()
+        .unwrap();
+
+    let body = sync(&server, &token, Some(cursor)).await;
+    assert_eq!(body["full"], false);
+    assert_eq!(body["cursor"], cursor);
+    assert!(body["items"].as_array().unwrap().is_empty());
+    assert!(body["months"].as_array().unwrap().is_empty());
+
+    server
+        .put(&format!("/api/months/{}/items/{}", month_id, kept_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "amount": 175.0 }))
+        .await
+        .assert_status_ok();
+    server
+        .delete(&format!("/api/months/{}/items/{}", month_id, deleted_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status(axum::http::StatusCode::NO_CONTENT);
+
+    let body = sync(&server, &token, Some(cursor)).await;
+    assert_eq!(body["full"], false);
+    assert!(body["cursor"].as_i64().unwrap() > cursor);
+    let items = body["items"].as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["id"], kept_id);
+    assert_eq!(items[0]["amount"], 175.0);
+    assert!(body["categories"].as_array().unwrap().is_empty());
+    assert_eq!(
+        body["deleted"],
+        json!([{ "entity_type": "item", "id": deleted_id }])
+    );
+}
+
+#[tokio::test]
+async fn test_sync_after_pruning_is_full() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let cat_id = create_test_category(&pool, user_id, "Food", 500.0).await;
+    let item_id = create_test_item(&pool, month_id, cat_id, "Groceries", 150.0, "2024-06-15").await;
+    let cursor = sync(&server, &token, None).await["cursor"]
+        .as_i64()
+        .unwrap();
+
+    server
+        .delete(&format!("/api/months/{}/items/{}", month_id, item_id))
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status(axum::http::StatusCode::NO_CONTENT);
+    let pruned = payme::handlers::sync::prune_deletions(&pool, Utc::now() + Duration::hours(1))
+        .await
+        .unwrap();
+    assert_eq!(pruned, 1);
+
+    let body = sync(&server, &token, Some(cursor)).await;
+    assert_eq!(body["full"], true);
+    assert!(body["items"].as_array().unwrap().is_empty());
+    assert_eq!(body["months"].as_array().unwrap().len(), 1);
+
+    let next = body["cursor"].as_i64().unwrap();
+    assert_eq!(sync(&server, &token, Some(next)).await["full"], false);
+}
+
+async fn apply_batch(server: &axum_test::TestServer, token: &str, batch: Value) -> Value {
+    let response = server
+        .post("/api/sync/batch")
+        .add_header(auth_name(), auth_value(token))
+        .json(&batch)
+        .await;
+    response.assert_status_ok();
+    response.json()
+}
+
+#[tokio::test]
+async fn test_batch_applies_mutations() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let cat_id = create_test_category(&pool, user_id, "Food", 500.0).await;
+    let item_id = create_test_item(&pool, month_id, cat_id, "Groceries", 150.0, "2024-06-15").await;
+    let version = sync(&server, &token, None).await["items"][0]["version"]
+        .as_i64()
+        .unwrap();
+
+    let body = apply_batch(
+        &server,
+        &token,
+        json!({
+            "mutations": [
+                {
+                    "client_id": "6f1c1a8e-3f4c-4c59-9a39-0d7f0f1f6a01",
+                    "op": "create_item",
+                    "month_id": month_id,
+                    "item": {
+                        "category_id": cat_id,
+                        "description": "Coffee",
+                        "amount": 4.5,
+                        "spent_on": "2024-06-16"
+                    }
+                },
+                {
+                    "client_id": "6f1c1a8e-3f4c-4c59-9a39-0d7f0f1f6a02",
+                    "op": "update_item",
+                    "id": item_id,
+                    "base_version": version,
+                    "changes": { "amount": 160.0 }
+                }
+            ]
+        }),
+    )
+    .await;
+
+    let results = body["results"].as_array().unwrap();
+    assert_eq!(results[0]["status"], "applied");
+    assert_eq!(results[0]["item"]["description"], "Coffee");
+    assert_eq!(results[1]["status"], "applied");
+    assert_eq!(results[1]["item"]["amount"], 160.0);
+    assert!(results[1]["item"]["version"].as_i64().unwrap() > version);
+
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM items")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 2);
+}
+
+#[tokio::test]
+async fn test_batch_reports_conflicts() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let cat_id = create_test_category(&pool, user_id, "Food", 500.0).await;
+    let item_id = create_test_item(&pool, month_id, cat_id, "Groceries", 150.0, "2024-06-15").await;
+    let version = sync(&server, &token, None).await["items"][0]["version"]
+        .as_i64()
+        .unwrap();
+
+    // Someone else edits the item before the offline client syncs
+    server
+        .put(&format!("/api/months/{}/items/{}", month_id, item_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "amount": 175.0 }))
+        .await
+        .assert_status_ok();
+
+    let body = apply_batch(
+        &server,
+        &token,
+        json!({
+            "mutations": [{
+                "client_id": "0b8d8f5e-7c1e-4a43-8f4e-5a2f6c3d9e10",
+                "op": "delete_item",
+                "id": item_id,
+                "base_version": version
+            }]
+        }),
+    )
+    .await;
+
+    let result = &body["results"][0];
+    assert_eq!(result["status"], "conflict");
+    assert_eq!(result["item"]["amount"], 175.0);
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM items")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+}
+
+#[tokio::test]
+async fn test_batch_is_idempotent() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let cat_id = create_test_category(&pool, user_id, "Food", 500.0).await;
+    let batch = json!({
+        "mutations": [{
+            "client_id": "c2b3e7a4-1d2f-4e5a-9b8c-7d6e5f4a3b21",
+            "op": "create_item",
+            "month_id": month_id,
+            "item": {
+                "category_id": cat_id,
+                "description": "Coffee",
+                "amount": 4.5,
+                "spent_on": "2024-06-16"
+            }
+        }]
+    });
+
+    let first = apply_batch(&server, &token, batch.clone()).await;
+    let second = apply_batch(&server, &token, batch).await;
+
+    assert_eq!(second["results"][0]["status"], "applied");
+    assert_eq!(
+        second["results"][0]["item"]["id"],
+        first["results"][0]["item"]["id"]
+    );
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM items")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+}
+
+#[tokio::test]
+async fn test_atomic_batch_rolls_back() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let cat_id = create_test_category(&pool, user_id, "Food", 500.0).await;
+
+    let body = apply_batch(
+        &server,
+        &token,
+        json!({
+            "atomic": true,
+            "mutations": [
+                {
+                    "client_id": "9a7c5e3b-1f2d-4c6e-8a0b-2d4f6a8c0e11",
+                    "op": "create_item",
+                    "month_id": month_id,
+                    "item": {
+                        "category_id": cat_id,
+                        "description": "Coffee",
+                        "amount": 4.5,
+                        "spent_on": "2024-06-16"
+                    }
+                },
+                {
+                    "client_id": "9a7c5e3b-1f2d-4c6e-8a0b-2d4f6a8c0e12",
+                    "op": "create_item",
+                    "month_id": month_id,
+                    "item": {
+                        "category_id": cat_id,
+                        "description": "",
+                        "amount": 4.5,
+                        "spent_on": "2024-06-16"
+                    }
+                }
+            ]
+        }),
+    )
+    .await;
+
+    assert_eq!(body["results"][0]["status"], "rolled_back");
+    assert_eq!(body["results"][1]["status"], "rejected");
+    let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM items")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(count, 0);
+}
+
+#[tokio::test]
+async fn test_batch_rejects_invalid_client_id() {
+    let (server, _pool, _user_id, token) = setup_with_user().await;
+
+    let body = apply_batch(
+        &server,
+        &token,
+        json!({
+            "mutations": [{
+                "client_id": "not-a-uuid",
+                "op": "delete_item",
+                "id": 1,
+                "base_version": 1
+            }]
+        }),
+    )
+    .await;
+
+    assert_eq!(body["results"][0]["status"], "rejected");
+}
+
+#[tokio::test]
+async fn test_create_with_uuid_is_idempotent() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+    let cat_id = create_test_category(&pool, user_id, "Food", 500.0).await;
+    let uuid = "6F9619FF-8B86-D011-B42D-00C04FC964FF";
+
+    let create_item = || async {
+        let response = server
+            .post(&format!("/api/months/{}/items", month_id))
+            .add_header(auth_name(), auth_value(&token))
+            .json(&json!({
+                "category_id": cat_id,
+                "description": "Groceries",
+                "amount": 150.0,
+                "spent_on": "2024-06-15",
+                "uuid": uuid
+            }))
+            .await;
+        response.assert_status_ok();
+        response.json::<Value>()
+    };
+    let first = create_item().await;
+    let second = create_item().await;
+    assert_eq!(first["id"], second["id"]);
+    assert_eq!(first["uuid"], "6f9619ff-8b86-d011-b42d-00c04fc964ff");
+
+    let create_income = || async {
+        let response = server
+            .post(&format!("/api/months/{}/income", month_id))
+            .add_header(auth_name(), auth_value(&token))
+            .json(&json!({ "label": "Salary", "amount": 3000.0, "uuid": uuid }))
+            .await;
+        response.assert_status_ok();
+        response.json::<Value>()
+    };
+    assert_eq!(create_income().await["id"], create_income().await["id"]);
+
+    let create_category = || async {
+        let response = server
+            .post("/api/categories")
+            .add_header(auth_name(), auth_value(&token))
+            .json(&json!({ "label": "Travel", "default_amount": 200.0, "uuid": uuid }))
+            .await;
+        response.assert_status_ok();
+        response.json::<Value>()
+    };
+    assert_eq!(create_category().await["id"], create_category().await["id"]);
+
+    let items: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM items")
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(items, 1);
+}
+
+#[tokio::test]
+async fn test_create_with_bad_or_foreign_uuid() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let month_id = create_test_month(&pool, user_id, 2024, 6).await;
+
+    let response = server
+        .post(&format!("/api/months/{}/income", month_id))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "label": "Salary", "amount": 3000.0, "uuid": "not-a-uuid" }))
+        .expect_failure()
+        .await;
+    response.assert_status_bad_request();
+
+    let other_id = create_test_user(&pool, "other", "password123").await;
+    let other_token = generate_token(other_id, "other");
+    let uuid = "0b5c8d8e-4c1f-4f0e-9a55-3f1a2b3c4d5e";
+    server
+        .post("/api/categories")
+        .add_header(auth_name(), auth_value(&other_token))
+        .json(&json!({ "label": "Travel", "default_amount": 200.0, "uuid": uuid }))
+        .await
+        .assert_status_ok();
+
+    let response = server
+        .post("/api/categories")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "label": "Travel", "default_amount": 200.0, "uuid": uuid }))
+        .expect_failure()
+        .await;
+    response.assert_status(axum::http::StatusCode::CONFLICT);
+}
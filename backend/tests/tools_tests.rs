@@ -0,0 +1,175 @@
+mod common;
+
+use axum::http::StatusCode;
+use common::{
+    auth_name, auth_value, create_test_budget, create_test_category, create_test_income,
+    create_test_item, create_test_month, create_test_pool, create_test_server, create_test_user,
+    generate_token,
+};
+use payme::create_app;
+use payme::testing::ItemBuilder;
+use serde_json::{json, Value};
+
+async fn setup_with_user() -> (axum_test::TestServer, sqlx::SqlitePool, i64, String) {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+    (server, pool, user_id, token)
+}
+
+#[tokio::test]
+async fn test_list_tools_describes_arguments() {
+    let (server, _pool, _user_id, token) = setup_with_user().await;
+
+    let response = server
+        .get("/api/v1/tools")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+
+    response.assert_status_ok();
+    let body: Value = response.json();
+    let names: Vec<&str> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|t| t["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, ["month_summary", "category_history", "search_items"]);
+    assert_eq!(body[0]["input_schema"]["type"], "object");
+    assert!(body[2]["input_schema"]["properties"]["category"].is_object());
+}
+
+#[tokio::test]
+async fn test_search_items_totals_a_quarter() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let groceries = create_test_category(&pool, user_id, "Groceries", 300.0).await;
+    let fun = create_test_category(&pool, user_id, "Fun", 100.0).await;
+    let march = create_test_month(&pool, user_id, 2024, 3).await;
+    let april = create_test_month(&pool, user_id, 2024, 4).await;
+    let june = create_test_month(&pool, user_id, 2024, 6).await;
+    create_test_item(&pool, march, groceries, "Market", 40.0, "2024-03-30").await;
+    create_test_item(&pool, april, groceries, "Market", 55.0, "2024-04-02").await;
+    create_test_item(&pool, june, groceries, "Bakery", 12.5, "2024-06-20").await;
+    create_test_item(&pool, june, fun, "Cinema", 20.0, "2024-06-21").await;
+    ItemBuilder::new(june, groceries)
+        .description("Jar fund")
+        .amount(100.0)
+        .spent_on("2024-06-22")
+        .savings_destination("savings")
+        .create(&pool)
+        .await;
+
+    let response = server
+        .post("/api/v1/tools/call")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({
+            "name": "search_items",
+            "arguments": {
+                "category": "groceries",
+                "from": "2024-04-01",
+                "to": "2024-06-30",
+                "limit": 1
+            }
+        }))
+        .await;
+
+    response.assert_status_ok();
+    let body: Value = response.json();
+    assert_eq!(body["count"], 2);
+    assert_eq!(body["total"], 67.5);
+    assert_eq!(body["items"].as_array().unwrap().len(), 1);
+    assert_eq!(body["items"][0]["description"], "Bakery");
+    assert_eq!(body["items"][0]["category"], "Groceries");
+}
+
+#[tokio::test]
+async fn test_month_summary_and_category_history() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let food = create_test_category(&pool, user_id, "Food", 300.0).await;
+    let may = create_test_month(&pool, user_id, 2024, 5).await;
+    let june = create_test_month(&pool, user_id, 2024, 6).await;
+    create_test_income(&pool, june, "Salary", 2000.0).await;
+    create_test_budget(&pool, may, food, 250.0).await;
+    create_test_budget(&pool, june, food, 300.0).await;
+    create_test_item(&pool, may, food, "Market", 80.0, "2024-05-10").await;
+    create_test_item(&pool, june, food, "Market", 120.0, "2024-06-10").await;
+
+    let response = server
+        .post("/api/v1/tools/call")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "name": "month_summary", "arguments": { "year": 2024, "month": 6 } }))
+        .await;
+    response.assert_status_ok();
+    let body: Value = response.json();
+    assert_eq!(body["total_income"], 2000.0);
+    assert_eq!(body["total_spent"], 120.0);
+    assert_eq!(body["categories"][0]["category"], "Food");
+    assert_eq!(body["categories"][0]["allocated"], 300.0);
+    assert!(body.get("items").is_none());
+
+    let response = server
+        .post("/api/v1/tools/call")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "name": "category_history", "arguments": { "category": "food" } }))
+        .await;
+    response.assert_status_ok();
+    let body: Value = response.json();
+    assert_eq!(body["category"], "Food");
+    assert_eq!(body["months"][0]["month"], 5);
+    assert_eq!(body["months"][0]["allocated"], 250.0);
+    assert_eq!(body["months"][0]["spent"], 80.0);
+    assert_eq!(body["months"][1]["spent"], 120.0);
+}
+
+#[tokio::test]
+async fn test_tool_calls_are_validated() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    create_test_category(&pool, user_id, "Food", 300.0).await;
+
+    let response = server
+        .post("/api/v1/tools/call")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "name": "drop_tables", "arguments": {} }))
+        .expect_failure()
+        .await;
+    response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+
+    let response = server
+        .post("/api/v1/tools/call")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "name": "category_history", "arguments": { "category": "Rent" } }))
+        .expect_failure()
+        .await;
+    response.assert_status(StatusCode::UNPROCESSABLE_ENTITY);
+    let body: Value = response.json();
+    assert!(body["reason"].as_str().unwrap().contains("Food"));
+
+    let response = server
+        .post("/api/v1/tools/call")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "name": "month_summary", "arguments": { "year": 2024, "month": 2 } }))
+        .expect_failure()
+        .await;
+    response.assert_status_not_found();
+}
+
+#[tokio::test]
+async fn test_tools_only_see_own_data() {
+    let (server, pool, _user_id, token) = setup_with_user().await;
+    let other = create_test_user(&pool, "other", "password123").await;
+    let cat = create_test_category(&pool, other, "Food", 300.0).await;
+    let month = create_test_month(&pool, other, 2024, 6).await;
+    create_test_item(&pool, month, cat, "Market", 50.0, "2024-06-10").await;
+
+    let response = server
+        .post("/api/v1/tools/call")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "name": "search_items", "arguments": { "text": "market" } }))
+        .await;
+
+    response.assert_status_ok();
+    let body: Value = response.json();
+    assert_eq!(body["count"], 0);
+    assert_eq!(body["total"], 0.0);
+}
@@ -0,0 +1,108 @@
+mod common;
+
+use axum::http::{HeaderName, HeaderValue};
+use common::{
+    auth_name, auth_value, create_test_category, create_test_item, create_test_month,
+    create_test_pool, create_test_server, create_test_user, generate_token,
+};
+use payme::create_app;
+use serde_json::Value;
+use sqlx::SqlitePool;
+
+const ADMIN_TOKEN: &str = "test-admin-token";
+
+async fn setup_with_user() -> (axum_test::TestServer, SqlitePool, i64, String) {
+    std::env::set_var("ADMIN_TOKEN", ADMIN_TOKEN);
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+    (server, pool, user_id, token)
+}
+
+async fn add_snapshot(pool: &SqlitePool, month_id: i64, size: usize) {
+    sqlx::query("INSERT INTO monthly_snapshots (month_id, storage_key, size) VALUES (?, ?, ?)")
+        .bind(month_id)
+        .bind(format!("snapshots/{month_id}.pdf"))
+        .bind(size as i64)
+        .execute(pool)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_usage_counts_data_and_storage() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let cat_id = create_test_category(&pool, user_id, "Food", 200.0).await;
+    let older = create_test_month(&pool, user_id, 2023, 11).await;
+    let newer = create_test_month(&pool, user_id, 2024, 2).await;
+    create_test_item(&pool, older, cat_id, "Bread", 3.0, "2023-11-04").await;
+    create_test_item(&pool, newer, cat_id, "Milk", 2.0, "2024-02-10").await;
+    create_test_item(&pool, newer, cat_id, "Eggs", 4.0, "2024-02-11").await;
+    add_snapshot(&pool, older, 1200).await;
+
+    let response = server
+        .get("/api/usage")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+
+    response.assert_status_ok();
+    let body: Value = response.json();
+    assert_eq!(body["months"], 2);
+    assert_eq!(body["items"], 3);
+    assert_eq!(body["pdf_snapshots"], 1);
+    assert_eq!(body["attachments"], 0);
+    assert_eq!(body["pdf_bytes"], 1200);
+    assert_eq!(body["total_bytes"], 1200);
+    assert_eq!(body["oldest_month"], "2023-11-01");
+    assert_eq!(body["oldest_item"], "2023-11-04");
+    assert!(body["member_since"].is_string());
+}
+
+#[tokio::test]
+async fn test_usage_of_empty_account() {
+    let (server, _pool, _user_id, token) = setup_with_user().await;
+
+    let response = server
+        .get("/api/usage")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+
+    response.assert_status_ok();
+    let body: Value = response.json();
+    assert_eq!(body["months"], 0);
+    assert_eq!(body["total_bytes"], 0);
+    assert!(body["oldest_month"].is_null());
+    assert!(body["oldest_item"].is_null());
+}
+
+#[tokio::test]
+async fn test_admin_usage_lists_every_user() {
+    let (server, pool, user_id, _token) = setup_with_user().await;
+    let other_id = create_test_user(&pool, "otheruser", "password123").await;
+    let month_id = create_test_month(&pool, other_id, 2024, 1).await;
+    add_snapshot(&pool, month_id, 500).await;
+    create_test_month(&pool, user_id, 2024, 1).await;
+
+    server
+        .get("/api/admin/usage")
+        .expect_failure()
+        .await
+        .assert_status_unauthorized();
+
+    let response = server
+        .get("/api/admin/usage")
+        .add_header(
+            HeaderName::from_static("x-admin-token"),
+            HeaderValue::from_static(ADMIN_TOKEN),
+        )
+        .await;
+
+    response.assert_status_ok();
+    let body: Vec<Value> = response.json();
+    assert_eq!(body.len(), 2);
+    assert_eq!(body[0]["username"], "otheruser");
+    assert_eq!(body[0]["total_bytes"], 500);
+    assert_eq!(body[1]["username"], "testuser");
+    assert_eq!(body[1]["months"], 1);
+}
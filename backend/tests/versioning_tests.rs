@@ -0,0 +1,83 @@
+mod common;
+
+use axum::http::{HeaderName, HeaderValue};
+use common::{
+    auth_name, auth_value, create_test_pool, create_test_server, create_test_user, generate_token,
+};
+use payme::create_app;
+
+async fn setup() -> (axum_test::TestServer, String) {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    (create_test_server(create_app(pool)), token)
+}
+
+fn version_name() -> HeaderName {
+    HeaderName::from_static("api-version")
+}
+
+#[tokio::test]
+async fn test_v1_routes() {
+    let (server, token) = setup().await;
+
+    let response = server
+        .get("/api/v1/categories")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.header("api-version"), "1");
+    assert!(response.maybe_header("deprecation").is_none());
+}
+
+#[tokio::test]
+async fn test_unversioned_routes_are_deprecated() {
+    let (server, token) = setup().await;
+
+    let response = server
+        .get("/api/categories")
+        .add_header(auth_name(), auth_value(&token))
+        .await;
+    response.assert_status_ok();
+    assert_eq!(response.header("api-version"), "1");
+    assert_eq!(response.header("deprecation"), "true");
+    assert_eq!(
+        response.header("link"),
+        "</api/v1/categories>; rel=\"successor-version\""
+    );
+}
+
+#[tokio::test]
+async fn test_requested_version_must_match() {
+    let (server, token) = setup().await;
+
+    server
+        .get("/api/v1/categories")
+        .add_header(auth_name(), auth_value(&token))
+        .add_header(version_name(), HeaderValue::from_static("1"))
+        .await
+        .assert_status_ok();
+
+    server
+        .get("/api/v1/categories")
+        .add_header(auth_name(), auth_value(&token))
+        .add_header(version_name(), HeaderValue::from_static("2"))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+
+    server
+        .get("/api/categories")
+        .add_header(auth_name(), auth_value(&token))
+        .add_header(version_name(), HeaderValue::from_static("v2"))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+
+    server
+        .get("/api/v2/categories")
+        .add_header(auth_name(), auth_value(&token))
+        .expect_failure()
+        .await
+        .assert_status_not_found();
+}
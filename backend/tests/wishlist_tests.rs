@@ -0,0 +1,197 @@
+mod common;
+
+use chrono::{Datelike, Utc};
+use common::{
+    auth_name, auth_value, close_test_month, create_test_category, create_test_month,
+    create_test_pool, create_test_server, create_test_user, generate_token,
+};
+use payme::create_app;
+use serde_json::json;
+
+async fn setup_with_user() -> (axum_test::TestServer, sqlx::SqlitePool, i64, String) {
+    let pool = create_test_pool().await;
+    let user_id = create_test_user(&pool, "testuser", "password123").await;
+    let token = generate_token(user_id, "testuser");
+    let server = create_test_server(create_app(pool.clone()));
+    (server, pool, user_id, token)
+}
+
+#[tokio::test]
+async fn test_wishlist_crud() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let fun = create_test_category(&pool, user_id, "Fun", 100.0).await;
+
+    let response = server
+        .post("/api/v1/wishlist")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "description": "Bike", "estimated_cost": 800.0, "category_id": fun }))
+        .await;
+    response.assert_status_ok();
+    let bike: serde_json::Value = response.json();
+    assert_eq!(bike["priority"], 3);
+    assert_eq!(bike["category_label"], "Fun");
+
+    server
+        .post("/api/v1/wishlist")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "description": "Headphones", "estimated_cost": 150.0, "priority": 1 }))
+        .await
+        .assert_status_ok();
+
+    let entries: Vec<serde_json::Value> = server
+        .get("/api/v1/wishlist")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    let order: Vec<&str> = entries
+        .iter()
+        .map(|e| e["description"].as_str().unwrap())
+        .collect();
+    assert_eq!(order, vec!["Headphones", "Bike"]);
+
+    let url = format!("/api/v1/wishlist/{}", bike["id"]);
+    let response = server
+        .put(&url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "estimated_cost": 650.0, "priority": 2 }))
+        .await;
+    response.assert_status_ok();
+    let updated: serde_json::Value = response.json();
+    assert_eq!(updated["estimated_cost"], 650.0);
+    assert_eq!(updated["category_id"], fun);
+
+    server
+        .post("/api/v1/wishlist")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "description": "Boat", "estimated_cost": 10.0, "priority": 9 }))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+
+    server
+        .delete(&url)
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .assert_status(axum::http::StatusCode::NO_CONTENT);
+    let entries: Vec<serde_json::Value> = server
+        .get("/api/v1/wishlist")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(entries.len(), 1);
+}
+
+#[tokio::test]
+async fn test_purchase_wishlist_entry() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let fun = create_test_category(&pool, user_id, "Fun", 100.0).await;
+
+    let entry: serde_json::Value = server
+        .post("/api/v1/wishlist")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "description": "Bike", "estimated_cost": 800.0 }))
+        .await
+        .json();
+    let url = format!("/api/v1/wishlist/{}/purchase", entry["id"]);
+
+    server
+        .post(&url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({}))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+
+    let response = server
+        .post(&url)
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "category_id": fun, "amount": 749.99 }))
+        .await;
+    response.assert_status_ok();
+    let item: serde_json::Value = response.json();
+    assert_eq!(item["description"], "Bike");
+    assert_eq!(item["amount"], 749.99);
+    assert_eq!(item["spent_on"], Utc::now().date_naive().to_string());
+
+    let summary: serde_json::Value = server
+        .get("/api/v1/months/current")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert_eq!(summary["month"]["id"], item["month_id"]);
+    assert_eq!(summary["items"][0]["description"], "Bike");
+
+    let entries: Vec<serde_json::Value> = server
+        .get("/api/v1/wishlist")
+        .add_header(auth_name(), auth_value(&token))
+        .await
+        .json();
+    assert!(entries.is_empty());
+}
+
+#[tokio::test]
+async fn test_purchase_into_closed_month_rejected() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let fun = create_test_category(&pool, user_id, "Fun", 100.0).await;
+    let today = Utc::now();
+    let month_id = create_test_month(&pool, user_id, today.year(), today.month() as i32).await;
+    close_test_month(&pool, month_id).await;
+
+    let entry: serde_json::Value = server
+        .post("/api/v1/wishlist")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "description": "Bike", "estimated_cost": 800.0, "category_id": fun }))
+        .await
+        .json();
+
+    server
+        .post(&format!("/api/v1/wishlist/{}/purchase", entry["id"]))
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({}))
+        .expect_failure()
+        .await
+        .assert_status_bad_request();
+}
+
+#[tokio::test]
+async fn test_purchase_twice_at_once_records_one_item() {
+    let (server, pool, user_id, token) = setup_with_user().await;
+    let fun = create_test_category(&pool, user_id, "Fun", 100.0).await;
+    let today = Utc::now();
+    create_test_month(&pool, user_id, today.year(), today.month() as i32).await;
+
+    let entry: serde_json::Value = server
+        .post("/api/v1/wishlist")
+        .add_header(auth_name(), auth_value(&token))
+        .json(&json!({ "description": "Bike", "estimated_cost": 800.0, "category_id": fun }))
+        .await
+        .json();
+    let url = format!("/api/v1/wishlist/{}/purchase", entry["id"]);
+    let buy = || {
+        server
+            .post(&url)
+            .add_header(auth_name(), auth_value(&token))
+            .json(&json!({}))
+    };
+
+    let (first, second) = tokio::join!(buy(), buy());
+    let statuses = [first.status_code().as_u16(), second.status_code().as_u16()];
+    assert_eq!(
+        statuses.iter().filter(|s| **s == 200).count(),
+        1,
+        "{statuses:?}"
+    );
+    assert!(
+        statuses.iter().all(|s| [200, 404, 409].contains(s)),
+        "{statuses:?}"
+    );
+
+    let (items, activity): (i64, String) = sqlx::query_as(
+        "SELECT (SELECT COUNT(*) FROM items), (SELECT summary FROM activity_log WHERE entity_type = 'item')",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(items, 1);
+    assert_eq!(activity, "testuser bought Bike $800.00 from the wishlist");
+}
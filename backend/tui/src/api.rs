@@ -0,0 +1,179 @@
+//! The slice of the payme HTTP API the terminal client uses.
+
+use std::fmt;
+
+use chrono::NaiveDate;
+use payme_core::models::{BudgetCategory, ItemWithCategory, Month, UserSettings};
+use reqwest::header::SET_COOKIE;
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// The parts of `GET /api/v1/months/current` the month view shows.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MonthView {
+    pub month: Month,
+    pub budgets: Vec<BudgetLine>,
+    pub items: Vec<ItemWithCategory>,
+    pub total_income: f64,
+    pub total_fixed: f64,
+    pub total_spent: f64,
+    pub remaining: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct BudgetLine {
+    pub category_label: String,
+    pub allocated_amount: f64,
+    pub spent_amount: f64,
+    pub available: f64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ChecklistEntry {
+    pub id: i64,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NewItem {
+    pub category_id: i64,
+    pub description: String,
+    pub amount: f64,
+    pub spent_on: NaiveDate,
+}
+
+#[derive(Debug)]
+pub enum ApiError {
+    /// The server answered with an error status and, usually, a message.
+    Status(StatusCode, String),
+    Transport(reqwest::Error),
+}
+
+impl ApiError {
+    /// A `409` when saving an item means it matches one entered moments ago.
+    pub fn is_conflict(&self) -> bool {
+        matches!(self, ApiError::Status(StatusCode::CONFLICT, _))
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ApiError::Status(status, message) if message.is_empty() => write!(f, "{status}"),
+            ApiError::Status(_, message) => f.write_str(message),
+            ApiError::Transport(e) => write!(f, "Can't reach the server: {e}"),
+        }
+    }
+}
+
+impl From<reqwest::Error> for ApiError {
+    fn from(e: reqwest::Error) -> Self {
+        ApiError::Transport(e)
+    }
+}
+
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+    token: String,
+}
+
+impl Client {
+    /// Logs in and keeps the session token for later requests.
+    pub async fn login(base_url: &str, username: &str, password: &str) -> Result<Self, ApiError> {
+        let http = reqwest::Client::new();
+        let base_url = base_url.trim_end_matches('/').to_string();
+        let response = http
+            .post(format!("{base_url}/api/v1/auth/login"))
+            .json(&json!({ "username": username, "password": password }))
+            .send()
+            .await?;
+        let response = check(response).await?;
+        let token = response
+            .headers()
+            .get_all(SET_COOKIE)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .find_map(|cookie| cookie.split(';').next()?.strip_prefix("token="))
+            .map(str::to_string)
+            .ok_or_else(|| {
+                ApiError::Status(StatusCode::UNAUTHORIZED, "No session token".to_string())
+            })?;
+        Ok(Self::with_token(&base_url, token))
+    }
+
+    /// Uses an existing session token, e.g. one copied from the browser.
+    pub fn with_token(base_url: &str, token: String) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token,
+        }
+    }
+
+    pub async fn current_month(&self) -> Result<MonthView, ApiError> {
+        self.send(self.request(Method::GET, "/months/current"))
+            .await
+    }
+
+    pub async fn categories(&self) -> Result<Vec<BudgetCategory>, ApiError> {
+        self.send(self.request(Method::GET, "/categories")).await
+    }
+
+    pub async fn settings(&self) -> Result<UserSettings, ApiError> {
+        self.send(self.request(Method::GET, "/settings")).await
+    }
+
+    pub async fn close_checklist(&self) -> Result<Vec<ChecklistEntry>, ApiError> {
+        self.send(self.request(Method::GET, "/close-checklist"))
+            .await
+    }
+
+    /// Saves an item; `force` keeps it even if it looks like a duplicate.
+    pub async fn add_item(
+        &self,
+        month_id: i64,
+        item: &NewItem,
+        force: bool,
+    ) -> Result<(), ApiError> {
+        let request = self
+            .request(Method::POST, &format!("/months/{month_id}/items"))
+            .query(&[("force", force)])
+            .json(item);
+        self.send::<Value>(request).await.map(drop)
+    }
+
+    /// Closes the month, acknowledging the given close checklist entries.
+    pub async fn close_month(&self, month_id: i64, acknowledged: &[i64]) -> Result<(), ApiError> {
+        let request = self
+            .request(Method::POST, &format!("/months/{month_id}/close"))
+            .json(&json!({ "acknowledged": acknowledged }));
+        self.send::<Value>(request).await.map(drop)
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.http
+            .request(method, format!("{}/api/v1{path}", self.base_url))
+            .bearer_auth(&self.token)
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, ApiError> {
+        Ok(check(request.send().await?).await?.json().await?)
+    }
+}
+
+/// Turns error statuses into `ApiError`, keeping the server's message.
+async fn check(response: reqwest::Response) -> Result<reqwest::Response, ApiError> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let message = response
+        .json::<Value>()
+        .await
+        .ok()
+        .and_then(|body| body["error"].as_str().map(str::to_string))
+        .unwrap_or_default();
+    Err(ApiError::Status(status, message))
+}
@@ -0,0 +1,372 @@
+//! Screen state and key handling. Talking to the server is left to the caller, which
+//! carries out the `Action` a key press asks for.
+
+use chrono::NaiveDate;
+use payme_core::format::MoneyFormat;
+use payme_core::models::BudgetCategory;
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::api::{ChecklistEntry, MonthView, NewItem};
+
+pub enum Mode {
+    Browse,
+    AddItem(ItemForm),
+    ConfirmClose(Vec<ChecklistEntry>),
+}
+
+/// What the event loop should do after a key press.
+#[derive(Debug, PartialEq)]
+pub enum Action {
+    None,
+    Quit,
+    Refresh,
+    /// Fetch the close checklist and ask for confirmation.
+    PrepareClose,
+    AddItem {
+        item: NewItem,
+        force: bool,
+    },
+    CloseMonth {
+        acknowledged: Vec<i64>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Field {
+    Description,
+    Amount,
+    Category,
+    Date,
+}
+
+impl Field {
+    fn next(self) -> Self {
+        match self {
+            Field::Description => Field::Amount,
+            Field::Amount => Field::Category,
+            Field::Category => Field::Date,
+            Field::Date => Field::Description,
+        }
+    }
+}
+
+pub struct ItemForm {
+    pub description: String,
+    pub amount: String,
+    /// Index into `App::categories`.
+    pub category: usize,
+    pub date: String,
+    pub focus: Field,
+    pub error: Option<String>,
+    /// Set after the server flagged the item as a duplicate; submitting again keeps it.
+    pub force: bool,
+}
+
+impl ItemForm {
+    fn new(today: NaiveDate) -> Self {
+        Self {
+            description: String::new(),
+            amount: String::new(),
+            category: 0,
+            date: today.to_string(),
+            focus: Field::Description,
+            error: None,
+            force: false,
+        }
+    }
+
+    fn text_mut(&mut self) -> Option<&mut String> {
+        match self.focus {
+            Field::Description => Some(&mut self.description),
+            Field::Amount => Some(&mut self.amount),
+            Field::Date => Some(&mut self.date),
+            Field::Category => None,
+        }
+    }
+
+    /// The item to save, or what's wrong with the input.
+    fn parse(&self, categories: &[BudgetCategory]) -> Result<NewItem, String> {
+        let description = self.description.trim();
+        if description.is_empty() {
+            return Err("Enter a description".to_string());
+        }
+        let amount = self
+            .amount
+            .trim()
+            .replace(',', ".")
+            .parse::<f64>()
+            .ok()
+            .filter(|amount| *amount >= 0.0)
+            .ok_or("Amount must be a positive number")?;
+        let category = categories
+            .get(self.category)
+            .ok_or("Create a category first")?;
+        let spent_on = NaiveDate::parse_from_str(self.date.trim(), "%Y-%m-%d")
+            .map_err(|_| "Date must look like 2024-01-31")?;
+        Ok(NewItem {
+            category_id: category.id,
+            description: description.to_string(),
+            amount,
+            spent_on,
+        })
+    }
+}
+
+pub struct App {
+    pub month: Option<MonthView>,
+    pub categories: Vec<BudgetCategory>,
+    pub money: MoneyFormat,
+    pub mode: Mode,
+    /// First item row shown in the item list.
+    pub scroll: usize,
+    /// Last error or confirmation, shown in the footer.
+    pub status: Option<String>,
+    pub today: NaiveDate,
+}
+
+impl App {
+    pub fn new(money: MoneyFormat, today: NaiveDate) -> Self {
+        Self {
+            month: None,
+            categories: Vec::new(),
+            money,
+            mode: Mode::Browse,
+            scroll: 0,
+            status: None,
+            today,
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Action {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Action::Quit;
+        }
+        match &mut self.mode {
+            Mode::Browse => self.browse_key(key.code),
+            Mode::AddItem(_) => self.form_key(key.code),
+            Mode::ConfirmClose(checklist) => match key.code {
+                KeyCode::Char('y') | KeyCode::Enter => {
+                    let acknowledged = checklist.iter().map(|entry| entry.id).collect();
+                    self.mode = Mode::Browse;
+                    Action::CloseMonth { acknowledged }
+                }
+                KeyCode::Char('n') | KeyCode::Esc => {
+                    self.mode = Mode::Browse;
+                    Action::None
+                }
+                _ => Action::None,
+            },
+        }
+    }
+
+    fn browse_key(&mut self, code: KeyCode) -> Action {
+        let is_closed = self.month.as_ref().is_some_and(|m| m.month.is_closed);
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => Action::Quit,
+            KeyCode::Char('r') => Action::Refresh,
+            KeyCode::Char('a') if is_closed => {
+                self.status = Some("This month is closed".to_string());
+                Action::None
+            }
+            KeyCode::Char('a') => {
+                self.mode = Mode::AddItem(ItemForm::new(self.today));
+                self.status = None;
+                Action::None
+            }
+            KeyCode::Char('c') if is_closed => {
+                self.status = Some("This month is already closed".to_string());
+                Action::None
+            }
+            KeyCode::Char('c') => Action::PrepareClose,
+            KeyCode::Down | KeyCode::Char('j') => {
+                let items = self.month.as_ref().map_or(0, |m| m.items.len());
+                self.scroll = (self.scroll + 1).min(items.saturating_sub(1));
+                Action::None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.scroll = self.scroll.saturating_sub(1);
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn form_key(&mut self, code: KeyCode) -> Action {
+        let category_count = self.categories.len().max(1);
+        let Mode::AddItem(form) = &mut self.mode else {
+            return Action::None;
+        };
+        match code {
+            KeyCode::Esc => self.mode = Mode::Browse,
+            KeyCode::Tab | KeyCode::Down => form.focus = form.focus.next(),
+            KeyCode::BackTab | KeyCode::Up => {
+                form.focus = form.focus.next().next().next();
+            }
+            KeyCode::Left if form.focus == Field::Category => {
+                form.category = (form.category + category_count - 1) % category_count;
+            }
+            KeyCode::Right if form.focus == Field::Category => {
+                form.category = (form.category + 1) % category_count;
+            }
+            KeyCode::Enter => match form.parse(&self.categories) {
+                Ok(item) => {
+                    return Action::AddItem {
+                        item,
+                        force: form.force,
+                    }
+                }
+                Err(error) => form.error = Some(error),
+            },
+            KeyCode::Backspace => {
+                if let Some(text) = form.text_mut() {
+                    text.pop();
+                }
+            }
+            KeyCode::Char(c) => {
+                if let Some(text) = form.text_mut() {
+                    text.push(c);
+                    form.force = false;
+                }
+            }
+            _ => {}
+        }
+        Action::None
+    }
+
+    /// Shows a fresh copy of the month, keeping the scroll position where possible.
+    pub fn load(&mut self, month: MonthView) {
+        self.scroll = self.scroll.min(month.items.len().saturating_sub(1));
+        self.month = Some(month);
+    }
+
+    /// Called once the server saved the item from the form.
+    pub fn item_added(&mut self) {
+        self.mode = Mode::Browse;
+        self.status = Some("Item added".to_string());
+    }
+
+    /// Called when the server refused the item from the form.
+    pub fn item_rejected(&mut self, error: String, duplicate: bool) {
+        if let Mode::AddItem(form) = &mut self.mode {
+            form.force = duplicate;
+            form.error = Some(if duplicate {
+                format!("{error} Press Enter again to save it anyway.")
+            } else {
+                error
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn type_text(app: &mut App, text: &str) {
+        for c in text.chars() {
+            app.handle_key(key(KeyCode::Char(c)));
+        }
+    }
+
+    fn app() -> App {
+        let mut app = App::new(
+            MoneyFormat::default(),
+            NaiveDate::from_ymd_opt(2024, 3, 15).unwrap(),
+        );
+        app.categories = ["Food", "Transport"]
+            .iter()
+            .enumerate()
+            .map(|(i, label)| BudgetCategory {
+                id: i as i64 + 10,
+                user_id: 1,
+                label: label.to_string(),
+                default_amount: 0.0,
+                uuid: None,
+                default_percent: None,
+                tracking_only: false,
+                system: false,
+            })
+            .collect();
+        app
+    }
+
+    #[test]
+    fn test_quick_form_builds_item() {
+        let mut app = app();
+        app.handle_key(key(KeyCode::Char('a')));
+        type_text(&mut app, "Groceries");
+        app.handle_key(key(KeyCode::Tab));
+        type_text(&mut app, "12,50");
+        app.handle_key(key(KeyCode::Tab));
+        app.handle_key(key(KeyCode::Right));
+
+        let action = app.handle_key(key(KeyCode::Enter));
+        assert_eq!(
+            action,
+            Action::AddItem {
+                item: NewItem {
+                    category_id: 11,
+                    description: "Groceries".to_string(),
+                    amount: 12.5,
+                    spent_on: NaiveDate::from_ymd_opt(2024, 3, 15).unwrap(),
+                },
+                force: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_amount_stays_in_form() {
+        let mut app = app();
+        app.handle_key(key(KeyCode::Char('a')));
+        type_text(&mut app, "Bus");
+        app.handle_key(key(KeyCode::Tab));
+        type_text(&mut app, "abc");
+
+        assert_eq!(app.handle_key(key(KeyCode::Enter)), Action::None);
+        let Mode::AddItem(form) = &app.mode else {
+            panic!("form was closed");
+        };
+        assert!(form.error.is_some());
+    }
+
+    #[test]
+    fn test_duplicate_is_forced_on_second_submit() {
+        let mut app = app();
+        app.handle_key(key(KeyCode::Char('a')));
+        type_text(&mut app, "Coffee");
+        app.handle_key(key(KeyCode::Tab));
+        type_text(&mut app, "3");
+        app.handle_key(key(KeyCode::Enter));
+        app.item_rejected("Looks like a duplicate.".to_string(), true);
+
+        let Action::AddItem { force, .. } = app.handle_key(key(KeyCode::Enter)) else {
+            panic!("expected the item to be submitted");
+        };
+        assert!(force);
+    }
+
+    #[test]
+    fn test_close_acknowledges_checklist() {
+        let mut app = app();
+        assert_eq!(
+            app.handle_key(key(KeyCode::Char('c'))),
+            Action::PrepareClose
+        );
+        app.mode = Mode::ConfirmClose(vec![ChecklistEntry {
+            id: 4,
+            label: "Reconcile bank".to_string(),
+        }]);
+
+        assert_eq!(
+            app.handle_key(key(KeyCode::Char('y'))),
+            Action::CloseMonth {
+                acknowledged: vec![4]
+            }
+        );
+    }
+}
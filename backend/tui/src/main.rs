@@ -0,0 +1,156 @@
+//! Terminal client for payme. Talks to a running server over its HTTP API, so entries
+//! go through the same validation, quotas and activity log as the web app.
+
+mod api;
+mod app;
+mod ui;
+
+use std::io::{self, BufRead, Write};
+use std::process::ExitCode;
+use std::time::Duration;
+
+use chrono::Local;
+use payme_core::format::MoneyFormat;
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+use ratatui::DefaultTerminal;
+
+use api::{ApiError, Client};
+use app::{Action, App, Mode};
+
+const USAGE: &str = "\
+Usage: payme-tui [username]
+
+Shows the current month of a payme server, adds items and closes the month.
+
+Environment:
+  PAYME_URL    Server to connect to (default http://localhost:3001)
+  PAYME_TOKEN  Session token to use instead of logging in";
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let username = match args.as_slice() {
+        [] => None,
+        [flag] if matches!(flag.as_str(), "help" | "-h" | "--help") => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        [username] => Some(username.clone()),
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let base_url =
+        std::env::var("PAYME_URL").unwrap_or_else(|_| "http://localhost:3001".to_string());
+    let client = match connect(&base_url, username).await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Failed to log in to {base_url}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let money = match client.settings().await {
+        Ok(settings) => MoneyFormat::from_settings(&settings),
+        Err(e) => {
+            eprintln!("Failed to load settings: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut app = App::new(money, Local::now().date_naive());
+
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, &client, &mut app).await;
+    ratatui::restore();
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn connect(base_url: &str, username: Option<String>) -> Result<Client, ApiError> {
+    if let Ok(token) = std::env::var("PAYME_TOKEN") {
+        return Ok(Client::with_token(base_url, token));
+    }
+    let username = match username {
+        Some(username) => username,
+        None => prompt("Username: "),
+    };
+    Client::login(base_url, &username, &prompt("Password: ")).await
+}
+
+fn prompt(label: &str) -> String {
+    eprint!("{label}");
+    io::stderr().flush().ok();
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line).ok();
+    line.trim_end_matches(['\r', '\n']).to_string()
+}
+
+async fn run(terminal: &mut DefaultTerminal, client: &Client, app: &mut App) -> io::Result<()> {
+    refresh(client, app).await;
+    loop {
+        terminal.draw(|frame| ui::draw(frame, app))?;
+        if !event::poll(Duration::from_millis(250))? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        match app.handle_key(key) {
+            Action::None => {}
+            Action::Quit => return Ok(()),
+            Action::Refresh => refresh(client, app).await,
+            Action::PrepareClose => match client.close_checklist().await {
+                Ok(checklist) => app.mode = Mode::ConfirmClose(checklist),
+                Err(e) => app.status = Some(e.to_string()),
+            },
+            Action::AddItem { item, force } => {
+                let Some(month_id) = app.month.as_ref().map(|m| m.month.id) else {
+                    continue;
+                };
+                match client.add_item(month_id, &item, force).await {
+                    Ok(()) => {
+                        app.item_added();
+                        refresh(client, app).await;
+                    }
+                    Err(e) => app.item_rejected(e.to_string(), e.is_conflict() && !force),
+                }
+            }
+            Action::CloseMonth { acknowledged } => {
+                let Some(month_id) = app.month.as_ref().map(|m| m.month.id) else {
+                    continue;
+                };
+                match client.close_month(month_id, &acknowledged).await {
+                    Ok(()) => {
+                        refresh(client, app).await;
+                        app.status = Some("Month closed; its PDF is being generated".to_string());
+                    }
+                    Err(e) => app.status = Some(e.to_string()),
+                }
+            }
+        }
+    }
+}
+
+/// Reloads the month and categories, reporting failures in the footer.
+async fn refresh(client: &Client, app: &mut App) {
+    let loaded = async {
+        app.categories = client.categories().await?;
+        app.load(client.current_month().await?);
+        Ok::<_, ApiError>(())
+    };
+    if let Err(e) = loaded.await {
+        app.status = Some(e.to_string());
+    }
+}
@@ -0,0 +1,214 @@
+//! Drawing: the month summary with budgets and items, and the dialogs over it.
+
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table};
+use ratatui::Frame;
+
+use crate::api::{ChecklistEntry, MonthView};
+use crate::app::{App, Field, ItemForm, Mode};
+
+const HELP: &str = "a add item  c close month  r refresh  j/k scroll  q quit";
+
+pub fn draw(frame: &mut Frame, app: &App) {
+    let [header, body, footer] = Layout::vertical([
+        Constraint::Length(4),
+        Constraint::Min(0),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+
+    match &app.month {
+        Some(month) => {
+            draw_totals(frame, app, month, header);
+            let [budgets, items] =
+                Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                    .areas(body);
+            draw_budgets(frame, app, month, budgets);
+            draw_items(frame, app, month, items);
+        }
+        None => frame.render_widget(Paragraph::new("Loading…"), body),
+    }
+
+    let footer_text = match &app.status {
+        Some(status) => Line::from(status.as_str()).style(Style::default().fg(Color::Yellow)),
+        None => Line::from(HELP).style(Style::default().fg(Color::DarkGray)),
+    };
+    frame.render_widget(Paragraph::new(footer_text), footer);
+
+    match &app.mode {
+        Mode::Browse => {}
+        Mode::AddItem(form) => draw_form(frame, app, form),
+        Mode::ConfirmClose(checklist) => draw_confirm_close(frame, checklist),
+    }
+}
+
+fn draw_totals(frame: &mut Frame, app: &App, month: &MonthView, area: Rect) {
+    let money = |amount| app.money.format(amount);
+    let state = if month.month.is_closed {
+        " (closed)"
+    } else {
+        ""
+    };
+    let title = format!(" {}-{:02}{state} ", month.month.year, month.month.month);
+    let remaining_style = if month.remaining < 0.0 {
+        Style::default().fg(Color::Red)
+    } else {
+        Style::default().fg(Color::Green)
+    };
+    let lines = vec![
+        Line::from(format!(
+            "Income {}   Fixed {}   Spent {}",
+            money(month.total_income),
+            money(month.total_fixed),
+            money(month.total_spent),
+        )),
+        Line::from(vec![
+            Span::raw("Remaining "),
+            Span::styled(money(month.remaining), remaining_style),
+        ]),
+    ];
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title)),
+        area,
+    );
+}
+
+fn draw_budgets(frame: &mut Frame, app: &App, month: &MonthView, area: Rect) {
+    let rows = month.budgets.iter().map(|budget| {
+        let available =
+            Cell::from(app.money.format(budget.available)).style(if budget.available < 0.0 {
+                Style::default().fg(Color::Red)
+            } else {
+                Style::default()
+            });
+        Row::new(vec![
+            Cell::from(budget.category_label.as_str()),
+            Cell::from(app.money.format(budget.spent_amount)),
+            Cell::from(app.money.format(budget.allocated_amount)),
+            available,
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Fill(1),
+            Constraint::Length(12),
+            Constraint::Length(12),
+            Constraint::Length(12),
+        ],
+    )
+    .header(header_row(["Category", "Spent", "Budget", "Left"]))
+    .block(Block::default().borders(Borders::ALL).title(" Budgets "));
+    frame.render_widget(table, area);
+}
+
+fn draw_items(frame: &mut Frame, app: &App, month: &MonthView, area: Rect) {
+    let rows = month.items.iter().skip(app.scroll).map(|item| {
+        Row::new(vec![
+            Cell::from(item.spent_on.format("%d %b").to_string()),
+            Cell::from(item.description.as_str()),
+            Cell::from(item.category_label.as_str()),
+            Cell::from(app.money.format(item.amount)),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(6),
+            Constraint::Fill(2),
+            Constraint::Fill(1),
+            Constraint::Length(12),
+        ],
+    )
+    .header(header_row(["Date", "Description", "Category", "Amount"]))
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" Items ({}) ", month.items.len())),
+    );
+    frame.render_widget(table, area);
+}
+
+fn header_row<'a>(labels: [&'a str; 4]) -> Row<'a> {
+    Row::new(labels).style(Style::default().add_modifier(Modifier::BOLD))
+}
+
+fn draw_form(frame: &mut Frame, app: &App, form: &ItemForm) {
+    let category = app
+        .categories
+        .get(form.category)
+        .map_or("(no categories)", |c| c.label.as_str());
+    let field = |label: &str, value: String, field: Field| {
+        let style = if form.focus == field {
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default()
+        };
+        Line::from(vec![
+            Span::styled(format!("{label:<12}"), style),
+            Span::raw(value),
+        ])
+    };
+    let mut lines = vec![
+        field("Description", form.description.clone(), Field::Description),
+        field("Amount", form.amount.clone(), Field::Amount),
+        field("Category", format!("◀ {category} ▶"), Field::Category),
+        field("Date", form.date.clone(), Field::Date),
+        Line::from(""),
+    ];
+    lines.push(match &form.error {
+        Some(error) => Line::from(error.as_str()).style(Style::default().fg(Color::Red)),
+        None => Line::from("Tab next field  ←/→ category  Enter save  Esc cancel")
+            .style(Style::default().fg(Color::DarkGray)),
+    });
+
+    let area = centered(frame.area(), 60, lines.len() as u16 + 2);
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(" New item ")),
+        area,
+    );
+}
+
+fn draw_confirm_close(frame: &mut Frame, checklist: &[ChecklistEntry]) {
+    let mut lines = vec![Line::from(
+        "Close this month? It can't be edited afterwards.",
+    )];
+    if !checklist.is_empty() {
+        lines.push(Line::from(""));
+        lines.push(Line::from("Confirming ticks off your close checklist:"));
+        lines.extend(
+            checklist
+                .iter()
+                .map(|entry| Line::from(format!("  ✓ {}", entry.label))),
+        );
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from("y close  n cancel").style(Style::default().fg(Color::DarkGray)));
+
+    let area = centered(frame.area(), 60, lines.len() as u16 + 2);
+    frame.render_widget(Clear, area);
+    frame.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(" Close month "),
+        ),
+        area,
+    );
+}
+
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
+}
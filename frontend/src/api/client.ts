@@ -1,4 +1,8 @@
-const BASE_URL = "/api";
+import type { components } from "./schema";
+
+type Schemas = components["schemas"];
+
+const BASE_URL = "/api/v1";
 
 async function request<T>(
   endpoint: string,
@@ -24,6 +28,17 @@ async function request<T>(
   return response.json();
 }
 
+function listQuery(filter: ListFilter = {}): string {
+  const params = new URLSearchParams();
+  for (const [key, value] of Object.entries(filter)) {
+    if (value !== undefined && value !== null && value !== "") {
+      params.set(key, String(value));
+    }
+  }
+  const query = params.toString();
+  return query ? `?${query}` : "";
+}
+
 export const api = {
   auth: {
     register: (username: string, password: string) =>
@@ -56,62 +71,182 @@ export const api = {
   },
 
   months: {
-    list: () => request<Month[]>("/months"),
-    current: () => request<MonthSummary>("/months/current"),
-    get: (id: number) => request<MonthSummary>(`/months/${id}`),
-    close: (id: number) => request<Month>(`/months/${id}/close`, { method: "POST" }),
+    list: (filter?: ListFilter) => request<Month[]>(`/months${listQuery(filter)}`),
+    current: (excludeReimbursed = false) =>
+      request<MonthSummary>(`/months/current${excludeReimbursed ? "?exclude_reimbursed=true" : ""}`),
+    get: (id: number, excludeReimbursed = false) =>
+      request<MonthSummary>(`/months/${id}${excludeReimbursed ? "?exclude_reimbursed=true" : ""}`),
+    close: (id: number, data: { acknowledged?: number[]; force?: boolean } = {}) =>
+      request<Month & { pdf_job_id: number; digest: MonthDigest }>(`/months/${id}/close`, {
+        method: "POST",
+        body: JSON.stringify(data),
+      }),
     downloadPdf: async (id: number) => {
       const response = await fetch(`${BASE_URL}/months/${id}/pdf`, {
         credentials: "include",
       });
       return response.blob();
     },
+    verifyPdf: (id: number) =>
+      request<PdfVerification>(`/months/${id}/pdf/verify`),
+    pace: (id: number) => request<MonthPace>(`/months/${id}/pace`),
+    digest: (id: number) => request<MonthDigest>(`/months/${id}/digest`),
+    cashflow: (id: number) => request<CashflowEntry[]>(`/months/${id}/cashflow`),
+  },
+
+  pdfSignatures: {
+    get: (code: string) => request<PdfSignature>(`/pdf-signatures/${code}`),
+    verify: (code: string, pdf: Blob) =>
+      request<PdfSignatureCheck>(`/pdf-signatures/${code}/verify`, {
+        method: "POST",
+        headers: { "Content-Type": "application/pdf" },
+        body: pdf,
+      }),
+    signingKey: () => request<SigningKey>("/signing-key"),
+  },
+
+  jobs: {
+    get: (id: number) => request<Job>(`/jobs/${id}`),
   },
 
   fixedExpenses: {
-    list: () => request<FixedExpense[]>("/fixed-expenses"),
-    create: (data: { label: string; amount: number }) =>
+    list: (filter?: ListFilter) =>
+      request<FixedExpense[]>(`/fixed-expenses${listQuery(filter)}`),
+    create: (data: {
+      label: string;
+      amount: number;
+      billing_period?: BillingPeriod;
+      payment_month?: number;
+      payment_day?: number;
+      payment_roll?: PaymentRoll;
+    }) =>
       request<FixedExpense>("/fixed-expenses", {
         method: "POST",
         body: JSON.stringify(data),
       }),
-    update: (id: number, data: { label?: string; amount?: number }) =>
+    update: (
+      id: number,
+      data: {
+        label?: string;
+        amount?: number;
+        billing_period?: BillingPeriod;
+        payment_month?: number;
+        payment_day?: number | null;
+        payment_roll?: PaymentRoll;
+      }
+    ) =>
       request<FixedExpense>(`/fixed-expenses/${id}`, {
         method: "PUT",
         body: JSON.stringify(data),
       }),
     delete: (id: number) =>
       request<void>(`/fixed-expenses/${id}`, { method: "DELETE" }),
+    bulk: (edit: BulkEdit, dryRun = false) =>
+      request<BulkEditResult>(`/fixed-expenses/bulk${dryRun ? "?dry_run=true" : ""}`, {
+        method: "PUT",
+        body: JSON.stringify(edit),
+      }),
+    history: (id: number) =>
+      request<FixedExpensePrice[]>(`/fixed-expenses/${id}/history`),
+    inflation: (year?: number) =>
+      request<FixedCostInflation>(
+        `/fixed-expenses/inflation${year === undefined ? "" : `?year=${year}`}`
+      ),
   },
 
   categories: {
     list: () => request<BudgetCategory[]>("/categories"),
-    create: (data: { label: string; default_amount: number }) =>
+    create: (data: {
+      label: string;
+      default_amount: number;
+      default_percent?: number;
+      tracking_only?: boolean;
+      uuid?: string;
+    }) =>
       request<BudgetCategory>("/categories", {
         method: "POST",
         body: JSON.stringify(data),
       }),
-    update: (id: number, data: { label?: string; default_amount?: number }) =>
+    update: (
+      id: number,
+      data: {
+        label?: string;
+        default_amount?: number;
+        default_percent?: number | null;
+        tracking_only?: boolean;
+      }
+    ) =>
       request<BudgetCategory>(`/categories/${id}`, {
         method: "PUT",
         body: JSON.stringify(data),
       }),
     delete: (id: number) =>
       request<void>(`/categories/${id}`, { method: "DELETE" }),
+    previewDelete: (id: number) =>
+      request<DryRunReport>(`/categories/${id}?dry_run=true`, { method: "DELETE" }),
+    bulk: (edit: BulkEdit, dryRun = false) =>
+      request<BulkEditResult>(`/categories/bulk${dryRun ? "?dry_run=true" : ""}`, {
+        method: "PUT",
+        body: JSON.stringify(edit),
+      }),
+    rules: (id: number) => request<CategoryRule[]>(`/categories/${id}/rules`),
+    setRules: (id: number, rules: { kind: "contains" | "regex"; pattern: string }[]) =>
+      request<CategoryRule[]>(`/categories/${id}/rules`, {
+        method: "PUT",
+        body: JSON.stringify({ rules }),
+      }),
   },
 
   budgets: {
-    list: (monthId: number) => request<MonthlyBudget[]>(`/months/${monthId}/budgets`),
+    list: (monthId: number, includeAverage = false) =>
+      request<MonthlyBudget[]>(
+        `/months/${monthId}/budgets${includeAverage ? "?include_average=true" : ""}`
+      ),
     update: (monthId: number, budgetId: number, amount: number) =>
       request<MonthlyBudget>(`/months/${monthId}/budgets/${budgetId}`, {
         method: "PUT",
         body: JSON.stringify({ allocated_amount: amount }),
       }),
+    review: (monthId: number, budgetId: number, rating: number, note?: string) =>
+      request<BudgetReview>(`/months/${monthId}/budgets/${budgetId}/review`, {
+        method: "POST",
+        body: JSON.stringify({ rating, note }),
+      }),
+    lock: (monthId: number, budgetId: number, reason?: string) =>
+      request<MonthlyBudget>(`/months/${monthId}/budgets/${budgetId}/lock`, {
+        method: "POST",
+        body: JSON.stringify({ reason }),
+      }),
+    unlock: (monthId: number, budgetId: number) =>
+      request<MonthlyBudget>(`/months/${monthId}/budgets/${budgetId}/lock`, {
+        method: "DELETE",
+      }),
+    earmark: (monthId: number, budgetId: number, label: string, amount: number) =>
+      request<Earmark>(`/months/${monthId}/budgets/${budgetId}/earmarks`, {
+        method: "POST",
+        body: JSON.stringify({ label, amount }),
+      }),
+    deleteEarmark: (monthId: number, budgetId: number, earmarkId: number) =>
+      request<void>(`/months/${monthId}/budgets/${budgetId}/earmarks/${earmarkId}`, {
+        method: "DELETE",
+      }),
+    envelopes: (monthId: number) => request<EnvelopesResponse>(`/months/${monthId}/envelopes`),
+    yearReview: (year: number) => request<YearReview>(`/reviews/${year}`),
+  },
+
+  plans: {
+    get: (year: number) => request<YearPlan>(`/plans/${year}`),
+    set: (year: number, categories: { category_id: number; amounts: number[] }[]) =>
+      request<YearPlan>(`/plans/${year}`, {
+        method: "POST",
+        body: JSON.stringify({ categories }),
+      }),
   },
 
   income: {
-    list: (monthId: number) => request<IncomeEntry[]>(`/months/${monthId}/income`),
-    create: (monthId: number, data: { label: string; amount: number }) =>
+    list: (monthId: number, filter?: ListFilter) =>
+      request<IncomeEntry[]>(`/months/${monthId}/income${listQuery(filter)}`),
+    create: (monthId: number, data: { label: string; amount: number; uuid?: string }) =>
       request<IncomeEntry>(`/months/${monthId}/income`, {
         method: "POST",
         body: JSON.stringify(data),
@@ -130,10 +265,30 @@ export const api = {
   },
 
   items: {
-    list: (monthId: number) => request<ItemWithCategory[]>(`/months/${monthId}/items`),
+    list: (monthId: number, filter?: ListFilter) =>
+      request<ItemWithCategory[]>(`/months/${monthId}/items${listQuery(filter)}`),
+    uncategorized: (monthId: number) =>
+      request<ItemWithCategory[]>(`/months/${monthId}/items/uncategorized`),
+    suggestions: (prefix: string, limit = 10) =>
+      request<DescriptionSuggestion[]>(
+        `/items/suggestions?prefix=${encodeURIComponent(prefix)}&limit=${limit}`
+      ),
     create: (
       monthId: number,
-      data: { category_id: number; description: string; amount: number; spent_on: string; savings_destination?: string }
+      data: {
+        category_id?: number | null;
+        description: string;
+        amount: number;
+        spent_on: string;
+        savings_destination?: string;
+        paid_in_cash?: boolean;
+        reimbursable?: boolean;
+        tax_rate?: number;
+        earmark_id?: number;
+        uuid?: string;
+        client?: string;
+        billable?: boolean;
+      }
     ) =>
       request<Item>(`/months/${monthId}/items`, {
         method: "POST",
@@ -148,6 +303,10 @@ export const api = {
         amount?: number;
         spent_on?: string;
         savings_destination?: string;
+        paid_in_cash?: boolean;
+        tax_rate?: number | null;
+        client?: string | null;
+        billable?: boolean;
       }
     ) =>
       request<Item>(`/months/${monthId}/items/${itemId}`, {
@@ -156,10 +315,287 @@ export const api = {
       }),
     delete: (monthId: number, itemId: number) =>
       request<void>(`/months/${monthId}/items/${itemId}`, { method: "DELETE" }),
+    mileage: (
+      monthId: number,
+      data: {
+        category_id: number;
+        distance: number;
+        rate?: number;
+        spent_on: string;
+        purpose?: string;
+        reimbursable?: boolean;
+      }
+    ) =>
+      request<Item & { calculation: ItemCalculation }>(`/months/${monthId}/items/mileage`, {
+        method: "POST",
+        body: JSON.stringify(data),
+      }),
+    perDiem: (
+      monthId: number,
+      data: {
+        category_id: number;
+        days: number;
+        rate?: number;
+        spent_on: string;
+        purpose?: string;
+        reimbursable?: boolean;
+      }
+    ) =>
+      request<Item & { calculation: ItemCalculation }>(`/months/${monthId}/items/per-diem`, {
+        method: "POST",
+        body: JSON.stringify(data),
+      }),
+    recalculate: (monthId: number, itemId: number, rate?: number) =>
+      request<Item & { calculation: ItemCalculation }>(
+        `/months/${monthId}/items/${itemId}/recalculate`,
+        { method: "POST", body: JSON.stringify({ rate }) }
+      ),
+    setReimbursement: (monthId: number, itemId: number, status: ReimbursementStatus | "none") =>
+      request<Item>(`/months/${monthId}/items/${itemId}/reimbursement`, {
+        method: "PUT",
+        body: JSON.stringify({ status }),
+      }),
+    splits: (monthId: number, itemId: number) =>
+      request<ItemSplit[]>(`/months/${monthId}/items/${itemId}/splits`),
+    split: (monthId: number, itemId: number, person: string, amount: number) =>
+      request<ItemSplit>(`/months/${monthId}/items/${itemId}/splits`, {
+        method: "POST",
+        body: JSON.stringify({ person, amount }),
+      }),
+    deleteSplit: (monthId: number, itemId: number, splitId: number) =>
+      request<void>(`/months/${monthId}/items/${itemId}/splits/${splitId}`, { method: "DELETE" }),
+  },
+
+  reimbursements: {
+    list: () => request<ReimbursementsReport>("/reimbursements"),
+  },
+
+  iou: {
+    get: () => request<IouReport>("/iou"),
+    repay: (splitId: number, amount?: number) =>
+      request<IncomeEntry>(`/iou/${splitId}/repayments`, {
+        method: "POST",
+        body: JSON.stringify({ amount }),
+      }),
   },
 
   stats: {
-    get: () => request<StatsResponse>("/stats"),
+    get: (realTerms = false) =>
+      request<StatsResponse>(`/stats${realTerms ? "?real_terms=true" : ""}`),
+  },
+
+  cpi: {
+    get: () => request<CpiReading[]>("/cpi"),
+    set: (readings: CpiReading[]) =>
+      request<CpiReading[]>("/cpi", {
+        method: "PUT",
+        body: JSON.stringify({ readings }),
+      }),
+  },
+
+  subscriptions: {
+    list: () => request<SubscriptionsResponse>("/subscriptions"),
+    cancelCandidates: () =>
+      request<CancelCandidates>("/subscriptions/cancel-candidates"),
+    tagValue: (key: string, value: string | null) =>
+      request<Subscription>("/subscriptions/value", {
+        method: "PUT",
+        body: JSON.stringify({ key, value }),
+      }),
+  },
+
+  wishlist: {
+    list: () => request<WishlistEntry[]>("/wishlist"),
+    create: (data: {
+      description: string;
+      estimated_cost: number;
+      priority?: number;
+      category_id?: number;
+    }) =>
+      request<WishlistEntry>("/wishlist", {
+        method: "POST",
+        body: JSON.stringify(data),
+      }),
+    update: (
+      id: number,
+      data: {
+        description?: string;
+        estimated_cost?: number;
+        priority?: number;
+        category_id?: number;
+      }
+    ) =>
+      request<WishlistEntry>(`/wishlist/${id}`, {
+        method: "PUT",
+        body: JSON.stringify(data),
+      }),
+    delete: (id: number) => request<void>(`/wishlist/${id}`, { method: "DELETE" }),
+    purchase: (id: number, data: { amount?: number; spent_on?: string; category_id?: number } = {}) =>
+      request<Item>(`/wishlist/${id}/purchase`, {
+        method: "POST",
+        body: JSON.stringify(data),
+      }),
+  },
+
+  projects: {
+    list: () => request<Project[]>("/projects"),
+    create: (data: { name: string; budget: number }) =>
+      request<Project>("/projects", {
+        method: "POST",
+        body: JSON.stringify(data),
+      }),
+    update: (id: number, data: { name?: string; budget?: number }) =>
+      request<Project>(`/projects/${id}`, {
+        method: "PUT",
+        body: JSON.stringify(data),
+      }),
+    delete: (id: number) => request<void>(`/projects/${id}`, { method: "DELETE" }),
+    summary: (id: number) => request<ProjectSummary>(`/projects/${id}/summary`),
+    linkItem: (monthId: number, itemId: number, projectId: number | null) =>
+      request<void>(`/months/${monthId}/items/${itemId}/project`, {
+        method: "PUT",
+        body: JSON.stringify({ project_id: projectId }),
+      }),
+  },
+
+  clients: {
+    report: (filter?: ListFilter) =>
+      request<ClientReport[]>(`/clients/report${listQuery(filter)}`),
+  },
+
+  invoices: {
+    list: () => request<Invoice[]>("/invoices"),
+    create: (data: { client: string; amount: number; issued_on?: string; due_on: string }) =>
+      request<Invoice>("/invoices", {
+        method: "POST",
+        body: JSON.stringify(data),
+      }),
+    update: (
+      id: number,
+      data: { client?: string; amount?: number; issued_on?: string; due_on?: string }
+    ) =>
+      request<Invoice>(`/invoices/${id}`, {
+        method: "PUT",
+        body: JSON.stringify(data),
+      }),
+    setStatus: (id: number, status: InvoiceStatus, paidOn?: string) =>
+      request<Invoice>(`/invoices/${id}/status`, {
+        method: "PUT",
+        body: JSON.stringify({ status, paid_on: paidOn }),
+      }),
+    delete: (id: number) => request<void>(`/invoices/${id}`, { method: "DELETE" }),
+  },
+
+  reports: {
+    list: () => request<SavedReport[]>("/reports/saved"),
+    create: (data: SaveReport) =>
+      request<SavedReport>("/reports/saved", {
+        method: "POST",
+        body: JSON.stringify(data),
+      }),
+    update: (id: number, data: SaveReport) =>
+      request<SavedReport>(`/reports/saved/${id}`, {
+        method: "PUT",
+        body: JSON.stringify(data),
+      }),
+    delete: (id: number) =>
+      request<void>(`/reports/saved/${id}`, { method: "DELETE" }),
+    run: (id: number) =>
+      request<{ job_id: number }>(`/reports/saved/${id}/run`, {
+        method: "POST",
+      }),
+    artifacts: (id: number) =>
+      request<ReportArtifact[]>(`/reports/saved/${id}/artifacts`),
+    downloadArtifact: async (id: number) => {
+      const response = await fetch(`${BASE_URL}/reports/artifacts/${id}`, {
+        credentials: "include",
+      });
+      return response.blob();
+    },
+  },
+
+  closeChecklist: {
+    get: () => request<ChecklistEntry[]>("/close-checklist"),
+    set: (entries: string[]) =>
+      request<ChecklistEntry[]>("/close-checklist", {
+        method: "PUT",
+        body: JSON.stringify({ entries }),
+      }),
+  },
+
+  connectors: {
+    list: () => request<CloudConnection[]>("/connectors"),
+    authorize: (
+      provider: CloudProvider,
+      data: { folder?: string; mirror_snapshots?: boolean } = {},
+    ) =>
+      request<{ url: string }>(`/connectors/${provider}/authorize`, {
+        method: "POST",
+        body: JSON.stringify(data),
+      }),
+    delete: (provider: CloudProvider) =>
+      request<void>(`/connectors/${provider}`, { method: "DELETE" }),
+  },
+
+  seasonality: {
+    get: () => request<SeasonalityResponse>("/analytics/seasonality"),
+    refresh: () =>
+      request<{ job_id: number }>("/analytics/seasonality/refresh", {
+        method: "POST",
+      }),
+  },
+
+  cash: {
+    report: () => request<CashReport>("/cash"),
+  },
+
+  simulations: {
+    run: (data: SimulationRequest) =>
+      request<SimulationResult>("/simulations", {
+        method: "POST",
+        body: JSON.stringify(data),
+      }),
+    savings: (monthly: number, years: number, rate: number) =>
+      request<SavingsSimulation>(
+        `/simulations/savings?monthly=${monthly}&years=${years}&rate=${rate}`,
+      ),
+    savingsGoal: (data: {
+      monthly: number;
+      years: number;
+      rate: number;
+      category_id?: number;
+    }) =>
+      request<SavingsGoalPlan>("/simulations/savings/goal", {
+        method: "POST",
+        body: JSON.stringify(data),
+      }),
+  },
+
+  usage: {
+    get: () => request<UsageReport>("/usage"),
+  },
+
+  sync: {
+    changes: (since?: number) =>
+      request<SyncResponse>(
+        since === undefined ? "/sync" : `/sync?since=${since}`
+      ),
+    applyBatch: (mutations: SyncMutation[], atomic = false) =>
+      request<SyncBatchResponse>("/sync/batch", {
+        method: "POST",
+        body: JSON.stringify({ mutations, atomic }),
+      }),
+  },
+
+  tax: {
+    summary: (year: number, month?: number) =>
+      request<TaxSummary>(`/tax?year=${year}${month ? `&month=${month}` : ""}`),
+  },
+
+  dataQuality: {
+    get: () => request<DataQualityReport>("/maintenance/data-quality"),
+    fix: (check: "zero_amount_items" | "orphaned_budgets") =>
+      request<{ fixed: number }>(`/maintenance/data-quality/${check}/fix`, { method: "POST" }),
   },
 
   exportDb: async () => {
@@ -179,7 +615,25 @@ export const api = {
       body: JSON.stringify(data),
     });
   },
+  previewImportJson: (data: UserExport) =>
+    request<DryRunReport>("/import/json?dry_run=true", {
+      method: "POST",
+      body: JSON.stringify(data),
+    }),
 
+  dashboard: {
+    get: () => request<Dashboard>("/dashboard"),
+  },
+  periods: {
+    getSettings: () => request<BudgetPeriodSettings>("/periods/settings"),
+    updateSettings: (data: { period_type: string; starts_on?: string }) =>
+      request<BudgetPeriodSettings>("/periods/settings", {
+        method: "PUT",
+        body: JSON.stringify(data),
+      }),
+    current: () => request<PeriodSummary>("/periods/current"),
+    get: (date: string) => request<PeriodSummary>(`/periods/${date}`),
+  },
   savings: {
     get: () => request<{ savings: number; savings_goal: number }>("/savings"),
     update: (savings: number) =>
@@ -192,6 +646,7 @@ export const api = {
         method: "PUT",
         body: JSON.stringify({ savings_goal }),
       }),
+    history: () => request<WealthSnapshot[]>("/wealth/history"),
   },
 
   retirementSavings: {
@@ -204,117 +659,108 @@ export const api = {
   },
 };
 
-export interface UserExport {
-  version: number;
-  savings?: number;
-  retirement_savings?: number;
-  fixed_expenses: { label: string; amount: number }[];
-  categories: { label: string; default_amount: number }[];
-  months: {
-    year: number;
-    month: number;
-    is_closed: boolean;
-    income_entries: { label: string; amount: number }[];
-    budgets: { category_label: string; allocated_amount: number }[];
-    items: { category_label: string; description: string; amount: number; spent_on: string }[];
-  }[];
-}
-
-export interface Month {
-  id: number;
-  user_id: number;
-  year: number;
-  month: number;
-  is_closed: boolean;
-  closed_at: string | null;
-}
-
-export interface FixedExpense {
-  id: number;
-  user_id: number;
-  label: string;
-  amount: number;
-}
-
-export interface BudgetCategory {
-  id: number;
-  user_id: number;
-  label: string;
-  default_amount: number;
-}
-
-export interface MonthlyBudget {
-  id: number;
-  month_id: number;
-  category_id: number;
-  allocated_amount: number;
-}
-
-export interface MonthlyBudgetWithCategory {
-  id: number;
-  month_id: number;
-  category_id: number;
-  category_label: string;
-  allocated_amount: number;
-  spent_amount: number;
-}
+// Response and request bodies, generated from the backend's OpenAPI spec into
+// schema.d.ts by `npm run generate:api`
+export type UserExport = Schemas["UserExport"];
+export type Month = Schemas["Month"];
+export type WealthSnapshot = Schemas["WealthSnapshot"];
+export type Job = Schemas["Job"];
+export type FixedExpense = Schemas["FixedExpense"];
+export type CashflowEntry = Schemas["CashflowEntry"];
+export type BulkEdit = Schemas["BulkEdit"];
+export type BulkEditResult = Schemas["BulkEditResult"];
+export type BulkChange = Schemas["BulkChange"];
+export type FixedExpensePrice = Schemas["FixedExpensePrice"];
+export type FixedCostInflation = Schemas["FixedCostInflation"];
+export type FixedExpenseInflation = Schemas["FixedExpenseInflation"];
+export type BudgetCategory = Schemas["BudgetCategory"];
+export type CategoryRule = Schemas["CategoryRule"];
+export type MonthlyBudget = Schemas["MonthlyBudget"];
+export type MonthlyBudgetWithCategory = Schemas["MonthlyBudgetWithCategory"];
+export type DescriptionSuggestion = Schemas["DescriptionSuggestion"];
+export type Earmark = Schemas["Earmark"];
+export type Envelope = Schemas["Envelope"];
+export type CoverSuggestion = Schemas["CoverSuggestion"];
+export type EnvelopesResponse = Schemas["EnvelopesResponse"];
+export type YearPlan = Schemas["YearPlan"];
+export type CategoryPlan = Schemas["CategoryPlan"];
+export type BudgetReview = Schemas["BudgetReview"];
+export type YearReview = Schemas["YearReview"];
+export type IncomeEntry = Schemas["IncomeEntry"];
+export type Item = Schemas["Item"];
+export type ReimbursementsReport = Schemas["ReimbursementsReport"];
+export type ItemWithCategory = Schemas["ItemWithCategory"];
+export type ItemCalculation = Schemas["ItemCalculation"];
+export type MonthSummary = Schemas["MonthSummary"];
+export type MonthDigest = Schemas["MonthDigest"];
+export type ChecklistEntry = Schemas["ChecklistEntry"];
+export type ChecklistAcknowledgement = Schemas["ChecklistAcknowledgement"];
+export type PdfVerification = Schemas["PdfVerification"];
+export type PdfSignature = Schemas["PdfSignature"];
+export type PdfSignatureCheck = Schemas["PdfSignatureCheck"];
+export type SigningKey = Schemas["SigningKey"];
+export type Insight = Schemas["Insight"];
+export type Dashboard = Schemas["Dashboard"];
+export type MonthPace = Schemas["MonthPace"];
+export type BudgetPeriodSettings = Schemas["BudgetPeriodSettings"];
+export type PeriodSummary = Schemas["PeriodSummary"];
+export type PeriodCategory = Schemas["PeriodCategory"];
+export type CategoryPace = Schemas["CategoryPace"];
+export type MonthMetrics = Schemas["MonthMetrics"];
+export type CategoryStats = Schemas["CategoryStats"];
+export type MonthlyStats = Schemas["MonthlyStats"];
+export type Subscription = Schemas["Subscription"];
+export type SubscriptionsResponse = Schemas["SubscriptionsResponse"];
+export type CancelCandidates = Schemas["CancelCandidates"];
+export type ItemSplit = Schemas["ItemSplit"];
+export type IouEntry = Schemas["IouEntry"];
+export type IouReport = Schemas["IouReport"];
+export type WishlistEntry = Schemas["WishlistEntry"];
+export type Project = Schemas["Project"];
+export type ProjectSummary = Schemas["ProjectSummary"];
+export type ClientReport = Schemas["ClientReport"];
+export type ClientMonth = Schemas["ClientMonth"];
+export type Invoice = Schemas["Invoice"];
+export type CashReport = Schemas["CashReport"];
+export type SimulationRequest = Schemas["SimulationRequest"];
+export type MonthOutcome = Schemas["MonthOutcome"];
+export type SimulationResult = Schemas["SimulationResult"];
+export type SavingsSimulation = Schemas["SavingsSimulation"];
+export type SavingsGoalPlan = Schemas["SavingsGoalPlan"];
+export type SyncDeletion = Schemas["SyncDeletion"];
+export type SyncResponse = Schemas["SyncResponse"];
+export type VersionedItem = Schemas["VersionedItem"];
+export type SyncMutation = Schemas["SyncMutation"];
+export type MutationResult = Schemas["MutationResult"];
+export type SyncBatchResponse = Schemas["SyncBatchResponse"];
+export type UsageReport = Schemas["UsageReport"];
+export type ReportSpec = Schemas["ReportSpec"];
+export type SaveReport = Schemas["SaveReport"];
+export type SavedReport = Schemas["SavedReport"];
+export type CloudConnection = Schemas["CloudConnection"];
+export type ReportArtifact = Schemas["ReportArtifact"];
+export type SeasonalCategory = Schemas["SeasonalCategory"];
+export type SeasonalityResponse = Schemas["SeasonalityResponse"];
+export type TaxSummary = Schemas["TaxSummary"];
+export type DryRunReport = Schemas["DryRunReport"];
+export type QualityFinding = Schemas["QualityFinding"];
+export type DataQualityReport = Schemas["DataQualityReport"];
+export type StatsResponse = Schemas["StatsResponse"];
+export type CpiReading = Schemas["CpiReading"];
 
-export interface IncomeEntry {
-  id: number;
-  month_id: number;
-  label: string;
-  amount: number;
-}
-
-export interface Item {
-  id: number;
-  month_id: number;
-  category_id: number;
-  description: string;
-  amount: number;
-  spent_on: string;
-  savings_destination: string;
-}
-
-export interface ItemWithCategory extends Item {
-  category_label: string;
-}
-
-export interface MonthSummary {
-  month: Month;
-  income_entries: IncomeEntry[];
-  fixed_expenses: FixedExpense[];
-  budgets: MonthlyBudgetWithCategory[];
-  items: ItemWithCategory[];
-  total_income: number;
-  total_fixed: number;
-  total_budgeted: number;
-  total_spent: number;
-  remaining: number;
-}
-
-export interface CategoryStats {
-  category_id: number;
-  category_label: string;
-  current_month_spent: number;
-  previous_month_spent: number;
-  change_amount: number;
-  change_percent: number | null;
-}
+// Values the spec documents as plain strings
+export type BillingPeriod = "monthly" | "quarterly" | "yearly";
+export type PaymentRoll = "forward" | "backward" | "none";
+export type ReimbursementStatus = "pending" | "submitted" | "reimbursed";
+export type InvoiceStatus = "sent" | "paid" | "cancelled";
+export type CloudProvider = "dropbox" | "google_drive";
+export type DeliveryMethod = "email" | "webhook" | "webdav" | "s3" | CloudProvider;
 
-export interface MonthlyStats {
-  year: number;
-  month: number;
-  total_income: number;
-  total_spent: number;
-  total_fixed: number;
-  net: number;
+export interface ListFilter {
+  category_id?: number;
+  from?: string;
+  to?: string;
+  min_amount?: number;
+  max_amount?: number;
+  q?: string;
 }
-
-export interface StatsResponse {
-  category_comparisons: CategoryStats[];
-  monthly_trends: MonthlyStats[];
-  average_monthly_spending: number;
-  average_monthly_income: number;
-}
-